@@ -19,6 +19,8 @@ pub(super) enum ErrorInner {
     IncorrectChecksum(IncorrectChecksumError),
     /// Checked data was too short.
     TooShort(TooShortError),
+    /// Output buffer was too small for the decoded data.
+    BufferTooSmall(BufferTooSmallError),
 }
 
 impl From<Infallible> for Error {
@@ -38,6 +40,22 @@ pub fn invalid_character(&self) -> Option<u8> {
         }
     }
 
+    /// Returns the byte index of the invalid base58 character, if encountered.
+    pub fn invalid_character_position(&self) -> Option<usize> {
+        match self.0 {
+            ErrorInner::Decode(ref e) => Some(e.position()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if decoding failed because of a character not in the base58 alphabet.
+    pub fn is_charset_error(&self) -> bool { matches!(self.0, ErrorInner::Decode(_)) }
+
+    /// Returns true if the data decoded but its checksum was not correct.
+    ///
+    /// This is usually caused by a typo, see [`suggest_correction`](crate::suggest_correction).
+    pub fn is_checksum_error(&self) -> bool { matches!(self.0, ErrorInner::IncorrectChecksum(_)) }
+
     /// Returns the incorrect checksum along with the expected checksum, if encountered.
     pub fn incorrect_checksum(&self) -> Option<(u32, u32)> {
         match self.0 {
@@ -53,16 +71,25 @@ pub fn invalid_length(&self) -> Option<usize> {
             _ => None,
         }
     }
+
+    /// Returns the length of the output buffer, if it was too small for the decoded data.
+    pub fn buffer_too_small(&self) -> Option<usize> {
+        match self.0 {
+            ErrorInner::BufferTooSmall(ref e) => Some(e.buffer_len),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use ErrorInner::{Decode, IncorrectChecksum, TooShort};
+        use ErrorInner::{BufferTooSmall, Decode, IncorrectChecksum, TooShort};
 
         match self.0 {
             Decode(ref e) => write_err!(f, "decode"; e),
             IncorrectChecksum(ref e) => write_err!(f, "incorrect checksum"; e),
             TooShort(ref e) => write_err!(f, "too short"; e),
+            BufferTooSmall(ref e) => write_err!(f, "buffer too small"; e),
         }
     }
 }
@@ -70,12 +97,13 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 #[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        use ErrorInner::{Decode, IncorrectChecksum, TooShort};
+        use ErrorInner::{BufferTooSmall, Decode, IncorrectChecksum, TooShort};
 
         match self.0 {
             Decode(ref e) => Some(e),
             IncorrectChecksum(ref e) => Some(e),
             TooShort(ref e) => Some(e),
+            BufferTooSmall(ref e) => Some(e),
         }
     }
 }
@@ -92,6 +120,15 @@ impl From<TooShortError> for Error {
     fn from(e: TooShortError) -> Self { Self(ErrorInner::TooShort(e)) }
 }
 
+impl From<DecodeIntoError> for Error {
+    fn from(e: DecodeIntoError) -> Self {
+        match e.0 {
+            DecodeIntoErrorInner::Decode(e) => Self(ErrorInner::Decode(e)),
+            DecodeIntoErrorInner::BufferTooSmall(e) => Self(ErrorInner::BufferTooSmall(e)),
+        }
+    }
+}
+
 /// Checksum was not correct.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct IncorrectChecksumError {
@@ -148,6 +185,7 @@ impl std::error::Error for TooShortError {}
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct InvalidCharacterErrorInner {
     pub(super) invalid: u8,
+    pub(super) position: usize,
 }
 
 impl From<Infallible> for InvalidCharacterError {
@@ -159,17 +197,116 @@ fn from(never: Infallible) -> Self { match never {} }
 }
 
 impl InvalidCharacterError {
-    pub(super) fn new(invalid: u8) -> Self { Self(InvalidCharacterErrorInner { invalid }) }
+    pub(super) fn new(invalid: u8, position: usize) -> Self {
+        Self(InvalidCharacterErrorInner { invalid, position })
+    }
 
     /// Returns the invalid base58 character.
     pub fn invalid_character(&self) -> u8 { self.0.invalid }
+
+    /// Returns the byte index of the invalid character in the decoded string.
+    pub fn position(&self) -> usize { self.0.position }
 }
 
 impl fmt::Display for InvalidCharacterError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "invalid base58 character {:#x}", self.0.invalid)
+        write!(f, "invalid base58 character {:#x} at position {}", self.0.invalid, self.0.position)
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for InvalidCharacterError {}
+
+/// The output buffer was too small for the encoded or decoded data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferTooSmallError {
+    /// The length of the output buffer.
+    pub(super) buffer_len: usize,
+}
+
+impl From<Infallible> for BufferTooSmallError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl BufferTooSmallError {
+    /// Returns the length of the output buffer that was too small.
+    pub fn buffer_len(&self) -> usize { self.buffer_len }
+}
+
+impl fmt::Display for BufferTooSmallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "output buffer of {} bytes is too small", self.buffer_len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmallError {}
+
+/// An error occurred during base58 decoding into a caller-provided buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeIntoError(pub(super) DecodeIntoErrorInner);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum DecodeIntoErrorInner {
+    /// Invalid character while decoding.
+    Decode(InvalidCharacterError),
+    /// Output buffer was too small for the decoded data.
+    BufferTooSmall(BufferTooSmallError),
+}
+
+impl From<Infallible> for DecodeIntoError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl From<Infallible> for DecodeIntoErrorInner {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl DecodeIntoError {
+    /// Returns the invalid base58 character, if encountered.
+    pub fn invalid_character(&self) -> Option<u8> {
+        match self.0 {
+            DecodeIntoErrorInner::Decode(ref e) => Some(e.invalid_character()),
+            DecodeIntoErrorInner::BufferTooSmall(_) => None,
+        }
+    }
+
+    /// Returns the length of the output buffer, if it was too small for the decoded data.
+    pub fn buffer_too_small(&self) -> Option<usize> {
+        match self.0 {
+            DecodeIntoErrorInner::BufferTooSmall(ref e) => Some(e.buffer_len),
+            DecodeIntoErrorInner::Decode(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for DecodeIntoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use DecodeIntoErrorInner::{BufferTooSmall, Decode};
+
+        match self.0 {
+            Decode(ref e) => write_err!(f, "decode"; e),
+            BufferTooSmall(ref e) => write_err!(f, "buffer too small"; e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeIntoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        use DecodeIntoErrorInner::{BufferTooSmall, Decode};
+
+        match self.0 {
+            Decode(ref e) => Some(e),
+            BufferTooSmall(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<InvalidCharacterError> for DecodeIntoError {
+    fn from(e: InvalidCharacterError) -> Self { Self(DecodeIntoErrorInner::Decode(e)) }
+}
+
+impl From<BufferTooSmallError> for DecodeIntoError {
+    fn from(e: BufferTooSmallError) -> Self { Self(DecodeIntoErrorInner::BufferTooSmall(e)) }
+}
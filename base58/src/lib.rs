@@ -2,10 +2,10 @@
 
 //! Bitcoin base58 encoding and decoding.
 //!
-//! This crate can be used in a no-std environment but requires an allocator.
+//! This crate can be used in a no-std environment. Without an allocator (i.e. if the `alloc`
+//! feature is disabled) only the functions that encode into and decode into caller-provided
+//! buffers are available, e.g. [`encode_into`] and [`decode_into`].
 
-// NB: This crate is empty if `alloc` is not enabled.
-#![cfg(feature = "alloc")]
 #![no_std]
 // Experimental features we need.
 #![cfg_attr(bench, feature(test))]
@@ -19,6 +19,7 @@
 // Exclude lints we don't think are valuable.
 #![allow(clippy::incompatible_msrv)] // Has FPs and we're testing it which is more reliable anyway.
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
 #[cfg(bench)]
@@ -31,14 +32,16 @@
 
 pub mod error;
 
-#[cfg(not(feature = "std"))]
+#[cfg(all(feature = "alloc", not(feature = "std")))]
 pub use alloc::{string::String, vec::Vec};
+#[cfg(feature = "alloc")]
 use core::fmt;
 #[cfg(feature = "std")]
 pub use std::{string::String, vec::Vec};
 
 use hashes::sha256d;
 use internals::array::ArrayExt;
+#[cfg(feature = "alloc")]
 use internals::array_vec::ArrayVec;
 #[allow(unused)] // MSRV polyfill
 use internals::slice::SliceExt;
@@ -47,7 +50,7 @@
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
-pub use self::error::{Error, InvalidCharacterError};
+pub use self::error::{BufferTooSmallError, DecodeIntoError, Error, InvalidCharacterError};
 
 #[rustfmt::skip]
 static BASE58_DIGITS: [Option<u8>; 128] = [
@@ -74,20 +77,21 @@
 /// # Errors
 ///
 /// Returns an error if the input contains an invalid base58 character (not in the base58 alphabet).
+#[cfg(feature = "alloc")]
 #[allow(clippy::missing_panics_doc)] // Internal assertion, not user-controllable.
 pub fn decode(data: &str) -> Result<Vec<u8>, InvalidCharacterError> {
     // 11/15 is just over log_256(58)
     let mut scratch = Vec::with_capacity(1 + data.len() * 11 / 15);
     // Build in base 256
-    for d58 in data.bytes() {
+    for (position, d58) in data.bytes().enumerate() {
         // Compute "X = X * 58 + next_digit" in base 256
         if usize::from(d58) >= BASE58_DIGITS.len() {
-            return Err(InvalidCharacterError::new(d58));
+            return Err(InvalidCharacterError::new(d58, position));
         }
         let mut carry = match BASE58_DIGITS[usize::from(d58)] {
             Some(d58) => u32::from(d58),
             None => {
-                return Err(InvalidCharacterError::new(d58));
+                return Err(InvalidCharacterError::new(d58, position));
             }
         };
         if scratch.is_empty() {
@@ -119,6 +123,7 @@ pub fn decode(data: &str) -> Result<Vec<u8>, InvalidCharacterError> {
 /// * The input contains an invalid base58 character.
 /// * The decoded data is less than 4 bytes (too short for checksum verification).
 /// * The checksum does not match the expected value.
+#[cfg(feature = "alloc")]
 pub fn decode_check(data: &str) -> Result<Vec<u8>, Error> {
     let mut ret: Vec<u8> = decode(data)?;
     let (remaining, &data_check) =
@@ -137,9 +142,132 @@ pub fn decode_check(data: &str) -> Result<Vec<u8>, Error> {
     Ok(ret)
 }
 
+/// Searches for a single-character correction of an invalid base58check-encoded string.
+///
+/// If `data` does not decode, because of an invalid character or an incorrect checksum, this
+/// tries replacing each character with every base58 character and returns the byte index and
+/// replacement of the first change that results in a valid checksum. This is meant to help users
+/// fix typos in manually entered strings: the correction should be shown to the user rather than
+/// applied silently.
+///
+/// Returns `None` if `data` is valid or if there is no single-character correction.
+///
+/// # Examples
+///
+/// ```
+/// // The address is 1PfJpZsjreyVrqeoAfabrRwwjQyoSQMmHH but `j` was mistyped as `i`.
+/// let typo = "1PfJpZsireyVrqeoAfabrRwwjQyoSQMmHH";
+/// assert!(base58ck::decode_check(typo).unwrap_err().is_checksum_error());
+/// assert_eq!(base58ck::suggest_correction(typo), Some((7, 'j')));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn suggest_correction(data: &str) -> Option<(usize, char)> {
+    if decode_check(data).is_ok() {
+        return None;
+    }
+
+    let mut candidate = String::with_capacity(data.len());
+    for (position, original) in data.char_indices() {
+        for replacement in BASE58_CHARS.iter().copied().map(char::from) {
+            if replacement == original {
+                continue;
+            }
+            candidate.clear();
+            candidate.push_str(&data[..position]);
+            candidate.push(replacement);
+            candidate.push_str(&data[position + original.len_utf8()..]);
+            if decode_check(&candidate).is_ok() {
+                return Some((position, replacement));
+            }
+        }
+    }
+    None
+}
+
+/// Decodes a base58-encoded string into `out`, returning the number of bytes written.
+///
+/// This does not allocate. Decoding never produces more bytes than there are characters in
+/// `data` so a buffer of length `data.len()` is always large enough.
+///
+/// # Errors
+///
+/// * The input contains an invalid base58 character (not in the base58 alphabet).
+/// * The decoded data does not fit into `out`.
+#[allow(clippy::missing_panics_doc)] // Characters are checked upfront.
+pub fn decode_into(data: &str, out: &mut [u8]) -> Result<usize, DecodeIntoError> {
+    // Check the characters first so that the error doesn't depend on the size of `out`.
+    if let Some(position) = data.bytes().position(|d58| base58_digit(d58).is_none()) {
+        return Err(InvalidCharacterError::new(data.as_bytes()[position], position).into());
+    }
+
+    let buffer_len = out.len();
+    let too_small = || BufferTooSmallError { buffer_len };
+    let mut len = 0;
+    // Build in base 256, little endian, in the first `len` bytes of `out`.
+    for d58 in data.bytes() {
+        // Compute "X = X * 58 + next_digit" in base 256
+        let mut carry = u32::from(base58_digit(d58).expect("checked above"));
+        for d256 in &mut out[..len] {
+            carry += u32::from(*d256) * 58;
+            *d256 = carry as u8; // cast loses data intentionally
+            carry /= 256;
+        }
+        while carry > 0 {
+            *out.get_mut(len).ok_or_else(too_small)? = carry as u8; // cast loses data intentionally
+            len += 1;
+            carry /= 256;
+        }
+    }
+
+    // Leading zeroes are encoded directly.
+    let leading_zero_count = data.bytes().take_while(|&x| x == BASE58_CHARS[0]).count();
+    let total = leading_zero_count + len;
+    if total > out.len() {
+        return Err(too_small().into());
+    }
+    out[..len].reverse();
+    out.copy_within(..len, leading_zero_count);
+    out[..leading_zero_count].fill(0);
+    Ok(total)
+}
+
+/// Decodes a base58check-encoded string into `out` verifying the checksum, returning the number
+/// of bytes written (excluding the checksum).
+///
+/// This does not allocate. The checksum is decoded into `out` too so the buffer must be four
+/// bytes longer than the expected data. A buffer of length `data.len()` is always large enough.
+///
+/// # Errors
+///
+/// * The input contains an invalid base58 character.
+/// * The decoded data (including the checksum) does not fit into `out`.
+/// * The decoded data is less than 4 bytes (too short for checksum verification).
+/// * The checksum does not match the expected value.
+pub fn decode_check_into(data: &str, out: &mut [u8]) -> Result<usize, Error> {
+    let len = decode_into(data, out)?;
+    let (remaining, &data_check) =
+        out[..len].split_last_chunk::<4>().ok_or(TooShortError { length: len })?;
+
+    let hash_check = *sha256d::Hash::hash(remaining).as_byte_array().sub_array::<0, 4>();
+
+    let expected = u32::from_le_bytes(hash_check);
+    let actual = u32::from_le_bytes(data_check);
+
+    if actual != expected {
+        return Err(IncorrectChecksumError { incorrect: actual, expected }.into());
+    }
+
+    Ok(remaining.len())
+}
+
+/// Returns the value of a base58 character, or `None` if it is not in the base58 alphabet.
+fn base58_digit(d58: u8) -> Option<u8> { BASE58_DIGITS.get(usize::from(d58)).copied().flatten() }
+
+#[cfg(feature = "alloc")]
 const SHORT_OPT_BUFFER_LEN: usize = 128;
 
 /// Encodes `data` as a base58 string (see also `base58::encode_check()`).
+#[cfg(feature = "alloc")]
 #[allow(clippy::missing_panics_doc)] // fmt::Write returns Result but String is infallible.
 pub fn encode(data: &[u8]) -> String {
     let reserve_len = encoded_reserve_len(data.len());
@@ -160,6 +288,7 @@ pub fn encode(data: &[u8]) -> String {
 /// Encodes `data` as a base58 string including the checksum.
 ///
 /// The checksum is the first four bytes of the sha256d of the data, concatenated onto the end.
+#[cfg(feature = "alloc")]
 #[allow(clippy::missing_panics_doc)] // fmt::Write returns Result but String is infallible.
 pub fn encode_check(data: &[u8]) -> String {
     let mut res = String::with_capacity(encoded_check_reserve_len(data.len()));
@@ -174,10 +303,95 @@ pub fn encode_check(data: &[u8]) -> String {
 /// # Errors
 ///
 /// Returns an error if the formatter fails to write the encoded string.
+#[cfg(feature = "alloc")]
 pub fn encode_check_to_fmt(fmt: &mut fmt::Formatter, data: &[u8]) -> fmt::Result {
     encode_check_to_writer(fmt, data)
 }
 
+/// Encodes `data` as base58 into `out`, returning the number of bytes written.
+///
+/// This does not allocate. The output is ASCII, so `out[..len]` can be converted to a `str` using
+/// [`core::str::from_utf8`]. A buffer of length [`max_encoded_len`]`(data.len())` is always large
+/// enough.
+///
+/// # Errors
+///
+/// Returns an error if the encoded string does not fit into `out`.
+pub fn encode_into(data: &[u8], out: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+    encode_iter_into(data.iter().copied(), out)
+}
+
+/// Encodes `data` as base58, including the checksum, into `out`, returning the number of bytes
+/// written.
+///
+/// The checksum is the first four bytes of the sha256d of the data, concatenated onto the end.
+///
+/// This does not allocate. A buffer of length [`max_encoded_len`]`(data.len() + 4)` is always
+/// large enough.
+///
+/// # Errors
+///
+/// Returns an error if the encoded string does not fit into `out`.
+pub fn encode_check_into(data: &[u8], out: &mut [u8]) -> Result<usize, BufferTooSmallError> {
+    let checksum = sha256d::Hash::hash(data);
+    let iter = data.iter().copied().chain(checksum.as_byte_array()[0..4].iter().copied());
+    encode_iter_into(iter, out)
+}
+
+/// Returns the maximum length of the base58 encoding of `unencoded_len` bytes.
+///
+/// This is an upper bound, e.g. leading zero bytes are encoded in a single character each and
+/// take up less space than other bytes.
+pub const fn max_encoded_len(unencoded_len: usize) -> usize {
+    // log2(256) / log2(58) ~ 1.366 < 138 / 100, plus one for rounding
+    unencoded_len * 138 / 100 + 1
+}
+
+fn encode_iter_into<I>(data: I, out: &mut [u8]) -> Result<usize, BufferTooSmallError>
+where
+    I: Iterator<Item = u8>,
+{
+    let buffer_len = out.len();
+    let too_small = || BufferTooSmallError { buffer_len };
+    let mut len = 0;
+    let mut leading_zero_count = 0;
+    let mut leading_zeroes = true;
+    // Build string in little endian with 0-58 in place of characters, in the first `len` bytes
+    // of `out`...
+    for d256 in data {
+        let mut carry = u32::from(d256);
+        if leading_zeroes && carry == 0 {
+            leading_zero_count += 1;
+        } else {
+            leading_zeroes = false;
+        }
+
+        for ch in &mut out[..len] {
+            let new_ch = u32::from(*ch) * 256 + carry;
+            *ch = (new_ch % 58) as u8; // cast loses data intentionally
+            carry = new_ch / 58;
+        }
+
+        while carry > 0 {
+            *out.get_mut(len).ok_or_else(too_small)? = (carry % 58) as u8; // cast loses data intentionally
+            len += 1;
+            carry /= 58;
+        }
+    }
+
+    let total = len + leading_zero_count;
+    let encoded = out.get_mut(..total).ok_or_else(too_small)?;
+    encoded[len..].fill(0);
+    // ... then reverse it and convert to chars
+    encoded.reverse();
+    for ch in encoded {
+        *ch = BASE58_CHARS[usize::from(*ch)];
+    }
+
+    Ok(total)
+}
+
+#[cfg(feature = "alloc")]
 fn encode_check_to_writer(fmt: &mut impl fmt::Write, data: &[u8]) -> fmt::Result {
     let checksum = sha256d::Hash::hash(data);
     let iter = data.iter().copied().chain(checksum.as_byte_array()[0..4].iter().copied());
@@ -190,22 +404,26 @@ fn encode_check_to_writer(fmt: &mut impl fmt::Write, data: &[u8]) -> fmt::Result
 }
 
 /// Returns the length to reserve when encoding base58 without checksum
+#[cfg(feature = "alloc")]
 const fn encoded_reserve_len(unencoded_len: usize) -> usize {
     // log2(256) / log2(58) ~ 1.37 = 137 / 100
     unencoded_len * 137 / 100
 }
 
 /// Returns the length to reserve when encoding base58 with checksum
+#[cfg(feature = "alloc")]
 const fn encoded_check_reserve_len(unencoded_len: usize) -> usize {
     encoded_reserve_len(unencoded_len + 4)
 }
 
+#[cfg(feature = "alloc")]
 trait Buffer: Sized {
     fn push(&mut self, val: u8);
     fn slice(&self) -> &[u8];
     fn slice_mut(&mut self) -> &mut [u8];
 }
 
+#[cfg(feature = "alloc")]
 impl Buffer for Vec<u8> {
     fn push(&mut self, val: u8) { Self::push(self, val) }
 
@@ -214,6 +432,7 @@ fn slice(&self) -> &[u8] { self }
     fn slice_mut(&mut self) -> &mut [u8] { self }
 }
 
+#[cfg(feature = "alloc")]
 impl<const N: usize> Buffer for ArrayVec<u8, N> {
     fn push(&mut self, val: u8) { Self::push(self, val) }
 
@@ -222,6 +441,7 @@ fn slice(&self) -> &[u8] { self.as_slice() }
     fn slice_mut(&mut self) -> &mut [u8] { self.as_mut_slice() }
 }
 
+#[cfg(feature = "alloc")]
 fn format_iter<I, W>(writer: &mut W, data: I, buf: &mut impl Buffer) -> Result<(), fmt::Error>
 where
     I: Iterator<Item = u8> + Clone,
@@ -264,6 +484,7 @@ fn format_iter<I, W>(writer: &mut W, data: I, buf: &mut impl Buffer) -> Result<(
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "alloc")]
     use alloc::vec;
 
     use hex_lit::hex;
@@ -271,6 +492,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn base58_encode() {
         // Basics
         assert_eq!(&encode(&[0][..]), "1");
@@ -300,6 +522,7 @@ fn base58_encode() {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn base58_decode() {
         // Basics
         assert_eq!(decode("1").ok(), Some(vec![0u8]));
@@ -317,10 +540,12 @@ fn base58_decode() {
             hex!("00f8917303bfa8ef24f292e8fa1419b20460ba064d")
         );
         // Non Base58 char.
-        assert_eq!(decode("¢").unwrap_err(), InvalidCharacterError::new(194));
+        assert_eq!(decode("¢").unwrap_err(), InvalidCharacterError::new(194, 0));
+        assert_eq!(decode("211O").unwrap_err(), InvalidCharacterError::new(b'O', 3));
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn base58_roundtrip() {
         let s = "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs";
         let v: Vec<u8> = decode_check(s).unwrap();
@@ -332,6 +557,115 @@ fn base58_roundtrip() {
         // Check that `len > 4` is enforced.
         assert_eq!(decode_check(&encode(&[1, 2, 3])), Err(TooShortError { length: 3 }.into()));
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn base58_error_reporting() {
+        let valid = "1PfJpZsjreyVrqeoAfabrRwwjQyoSQMmHH";
+        assert_eq!(suggest_correction(valid), None);
+
+        // Mistyped character.
+        let typo = "1PfJpZsjreyVrqeoAfabrRwwjQyoSQMmHh";
+        let err = decode_check(typo).unwrap_err();
+        assert!(err.is_checksum_error());
+        assert!(!err.is_charset_error());
+        assert_eq!(err.invalid_character_position(), None);
+        assert_eq!(suggest_correction(typo), Some((33, 'H')));
+
+        // Character not in the alphabet.
+        let typo = "1PfJpZsjreyVrqeoAfabrRwwjQy0SQMmHH";
+        let err = decode_check(typo).unwrap_err();
+        assert!(err.is_charset_error());
+        assert!(!err.is_checksum_error());
+        assert_eq!(err.invalid_character(), Some(b'0'));
+        assert_eq!(err.invalid_character_position(), Some(27));
+        assert_eq!(suggest_correction(typo), Some((27, 'o')));
+
+        // Multi-byte characters are replaced as a whole.
+        let typo = "1PfJpZsjreyVrqeoAfabrRwwjQy¢SQMmHH";
+        assert_eq!(decode_check(typo).unwrap_err().invalid_character_position(), Some(27));
+        assert_eq!(suggest_correction(typo), Some((27, 'o')));
+
+        // Two mistyped characters.
+        assert_eq!(suggest_correction("1PfJpZsjreyVrqeoAfabrRwwjQyoSQMmhh"), None);
+    }
+
+    #[test]
+    fn base58_encode_into() {
+        let mut buf = [0; 64];
+
+        let len = encode_into(&[0, 0, 0, 0, 13, 36], &mut buf).unwrap();
+        assert_eq!(&buf[..len], b"1111211");
+        assert_eq!(encode_into(&[], &mut buf), Ok(0));
+
+        let addr = hex!("00f8917303bfa8ef24f292e8fa1419b20460ba064d");
+        let len = encode_check_into(&addr, &mut buf).unwrap();
+        assert_eq!(&buf[..len], b"1PfJpZsjreyVrqeoAfabrRwwjQyoSQMmHH");
+
+        // Both the digits and the leading zeroes must fit.
+        assert_eq!(
+            encode_into(&[0, 13, 36], &mut buf[..3]),
+            Err(BufferTooSmallError { buffer_len: 3 })
+        );
+        assert_eq!(
+            encode_into(&[0, 0, 0], &mut buf[..2]),
+            Err(BufferTooSmallError { buffer_len: 2 })
+        );
+        assert_eq!(encode_into(&[0, 13, 36], &mut buf[..4]), Ok(4));
+    }
+
+    #[test]
+    fn base58_max_encoded_len() {
+        let mut buf = [0; 256];
+        for len in 0..=128 {
+            for data in [[0xff; 128], [0x00; 128], [0x01; 128]] {
+                let encoded_len = encode_into(&data[..len], &mut buf).unwrap();
+                assert!(encoded_len <= max_encoded_len(len));
+                assert!(encode_into(&data[..len], &mut buf[..max_encoded_len(len)]).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn base58_encode_into_matches_encode() {
+        let mut buf = [0; 256];
+        let data: Vec<u8> = (0..=u8::MAX).map(|i| i.wrapping_mul(83)).collect();
+        for len in 0..128 {
+            for data in [&data[..len], &vec![0; len]] {
+                let len = encode_into(data, &mut buf).unwrap();
+                assert_eq!(core::str::from_utf8(&buf[..len]).unwrap(), encode(data));
+                let len = encode_check_into(data, &mut buf).unwrap();
+                assert_eq!(core::str::from_utf8(&buf[..len]).unwrap(), encode_check(data));
+            }
+        }
+    }
+
+    #[test]
+    fn base58_decode_into() {
+        let mut buf = [0; 64];
+
+        let len = decode_into("111211", &mut buf).unwrap();
+        assert_eq!(&buf[..len], [0, 0, 0, 13, 36]);
+        assert_eq!(decode_into("", &mut buf), Ok(0));
+
+        let s = "1PfJpZsjreyVrqeoAfabrRwwjQyoSQMmHH";
+        let len = decode_check_into(s, &mut buf).unwrap();
+        assert_eq!(&buf[..len], hex!("00f8917303bfa8ef24f292e8fa1419b20460ba064d"));
+        assert_eq!(decode_check_into(s, &mut buf[..24]).unwrap_err().buffer_too_small(), Some(24));
+        assert_eq!(decode_check_into(s, &mut buf[..25]), Ok(21));
+
+        // Both the digits and the leading zeroes must fit.
+        assert_eq!(decode_into("1211", &mut buf[..2]).unwrap_err().buffer_too_small(), Some(2));
+        assert_eq!(decode_into("111", &mut buf[..2]).unwrap_err().buffer_too_small(), Some(2));
+        // Invalid characters are reported even if the buffer is too small.
+        assert_eq!(
+            decode_into("2222220", &mut buf[..1]).unwrap_err().invalid_character(),
+            Some(b'0')
+        );
+        assert_eq!(decode_check_into("¢", &mut buf).unwrap_err().invalid_character(), Some(194));
+        assert_eq!(decode_check_into("211", &mut buf).unwrap_err().invalid_length(), Some(2));
+    }
 }
 
 #[cfg(bench)]
@@ -30,13 +30,18 @@ fn main() {
     // Writes the script as human-readable eg, OP_DUP OP_HASH160 OP_PUSHBYTES_20 ...
     println!("human-readable script: {script_code}");
 
-    // We do not implement parsing scripts from human-readable format.
-    // let decoded = s.parse::<WitnessScriptBuf>().unwrap();
+    // `FromStr` parses the human-readable format.
+    let decoded = script_code.to_string().parse::<WitnessScriptBuf>().unwrap();
+    assert_eq!(decoded, script_code);
 
     // This is not equivalent to consensus encoding i.e., does not include the length prefix.
     let hex_lower_hex_trait = format!("{script_code:x}");
     println!("hex created using `LowerHex`: {hex_lower_hex_trait}");
 
+    // And the hex created using `LowerHex`.
+    let decoded = hex_lower_hex_trait.parse::<WitnessScriptBuf>().unwrap();
+    assert_eq!(decoded, script_code);
+
     // The `deserialize_hex` function requires the length prefix.
     assert!(encode::deserialize_hex::<WitnessScriptBuf>(&hex_lower_hex_trait).is_err());
     // And so does `from_hex_prefixed`.
@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Schnorr adaptor signatures.
+//!
+//! An adaptor signature, or pre-signature, commits to a message, a public key and an adaptor
+//! point `T = t·G`. It can be verified by anyone but is not a valid BIP-0340 signature. Whoever
+//! knows the adaptor secret `t` can decrypt it into a valid signature and from the pre-signature
+//! and the published signature anyone can recover `t`. This is the building block of atomic swaps
+//! and discreet log contracts.
+//!
+//! The pre-signature contains the full nonce point `R = k·G + T`. The decrypted signature is an
+//! ordinary BIP-0340 signature on `R`, negating the nonce and the adaptor secret if `R` has an odd
+//! Y coordinate.
+//!
+//! # Stability
+//!
+//! The 65 byte encoding of pre-signatures and their nonce derivation are specific to this crate
+//! and may change, so pre-signatures should not be stored or exchanged with other implementations.
+//! The decrypted signatures are ordinary BIP-0340 signatures.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::adaptor::AdaptorSignature;
+//! use bitcoin::secp256k1::SecretKey;
+//! use bitcoin::{CompressedPublicKey, Keypair, XOnlyPublicKey};
+//!
+//! let keypair = Keypair::from_secret_key(&SecretKey::from_secret_bytes([1; 32]).unwrap());
+//! let (pubkey, _) = XOnlyPublicKey::from_keypair(&keypair);
+//! let secret = SecretKey::from_secret_bytes([2; 32]).unwrap();
+//! let adaptor = CompressedPublicKey::from_secp(secret.public_key());
+//! let msg = [3; 32];
+//!
+//! let pre_sig = AdaptorSignature::sign(&msg, &keypair, &adaptor, &[0; 32]);
+//! assert!(pre_sig.verify(&msg, &pubkey, &adaptor).is_ok());
+//!
+//! let sig = pre_sig.decrypt(&secret);
+//! assert!(bitcoin::secp256k1::schnorr::verify(&sig, &msg, &pubkey.to_inner()).is_ok());
+//! assert_eq!(pre_sig.recover(&sig, &adaptor).unwrap(), secret);
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use hashes::sha256t_tag;
+use secp256k1::{schnorr, Parity, Scalar, SecretKey};
+
+use crate::crypto::key::{CompressedPublicKey, Keypair, XOnlyPublicKey};
+use crate::crypto::taproot::{challenge, derive_nonce};
+
+sha256t_tag! {
+    struct AuxTag = hash_str("SchnorrAdaptor/aux");
+}
+
+sha256t_tag! {
+    struct NonceTag = hash_str("SchnorrAdaptor/nonce");
+}
+
+/// A Schnorr adaptor signature, a BIP-0340 signature encrypted to an adaptor point.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct AdaptorSignature {
+    /// The nonce point of the decrypted signature, including its parity.
+    nonce: secp256k1::PublicKey,
+    /// The scalar of the pre-signature, already checked to be below the curve order.
+    s: [u8; 32],
+}
+
+impl AdaptorSignature {
+    /// The size of a serialized adaptor signature.
+    pub const SIZE: usize = 65;
+
+    /// Constructs a new adaptor signature of `msg` with `keypair`, encrypted to `adaptor`.
+    ///
+    /// Like in BIP-0340 `aux_rand` should be fresh randomness, but signing is secure with any
+    /// value since the nonce is derived from the secret key, the message and the adaptor point.
+    pub fn sign(
+        msg: &[u8],
+        keypair: &Keypair,
+        adaptor: &CompressedPublicKey,
+        aux_rand: &[u8; 32],
+    ) -> Self {
+        let (pubkey, parity) = XOnlyPublicKey::from_keypair(keypair);
+        let mut secret = keypair.to_secret_key();
+        if parity == Parity::Odd {
+            secret = secret.negate();
+        }
+
+        let nonce = derive_nonce::<AuxTag, NonceTag>(
+            &secret,
+            aux_rand,
+            &[&adaptor.to_bytes(), &pubkey.serialize(), msg],
+        );
+
+        let point = nonce.public_key().combine(&adaptor.to_inner()).expect("nonce is secret");
+        let (rx, r_parity) = point.x_only_public_key();
+        let nonce = if r_parity == Parity::Odd { nonce.negate() } else { nonce };
+
+        let e = challenge(&rx.serialize(), &pubkey, msg);
+        let s = secret
+            .mul_tweak(&e)
+            .and_then(|ex| ex.add_tweak(&Scalar::from(nonce)))
+            .expect("statistically impossible to hit");
+        Self { nonce: point, s: s.to_secret_bytes() }
+    }
+
+    /// Verifies that this is an adaptor signature of `msg` by `pubkey`, encrypted to `adaptor`.
+    ///
+    /// A valid adaptor signature decrypts to a valid BIP-0340 signature with the adaptor secret.
+    pub fn verify(
+        &self,
+        msg: &[u8],
+        pubkey: &XOnlyPublicKey,
+        adaptor: &CompressedPublicKey,
+    ) -> Result<(), InvalidAdaptorSignatureError> {
+        let (rx, r_parity) = self.nonce.x_only_public_key();
+        let e = challenge(&rx.serialize(), pubkey, msg);
+
+        // The nonce of the signer is `R - T`, negated if `R` is odd.
+        let mut nonce = self
+            .nonce
+            .combine(&adaptor.to_inner().negate())
+            .map_err(|_| InvalidAdaptorSignatureError)?;
+        if r_parity == Parity::Odd {
+            nonce = nonce.negate();
+        }
+        let expected = pubkey
+            .to_inner()
+            .public_key(Parity::Even)
+            .mul_tweak(&e)
+            .and_then(|ep| ep.combine(&nonce))
+            .map_err(|_| InvalidAdaptorSignatureError)?;
+
+        let s = SecretKey::from_secret_bytes(self.s).map_err(|_| InvalidAdaptorSignatureError)?;
+        if s.public_key() == expected {
+            Ok(())
+        } else {
+            Err(InvalidAdaptorSignatureError)
+        }
+    }
+
+    /// Decrypts the adaptor signature into a BIP-0340 signature with the adaptor `secret`.
+    ///
+    /// The signature is only valid if the adaptor signature is and `secret` belongs to its
+    /// adaptor point.
+    pub fn decrypt(&self, secret: &SecretKey) -> schnorr::Signature {
+        let (rx, r_parity) = self.nonce.x_only_public_key();
+        let secret = if r_parity == Parity::Odd { secret.negate() } else { *secret };
+        let s = Scalar::from_be_bytes(self.s).expect("checked on construction");
+        // A zero sum is a valid signature scalar but not a valid secret key.
+        let s = secret.add_tweak(&s).map(|s| s.to_secret_bytes()).unwrap_or([0; 32]);
+
+        let mut sig = [0; 64];
+        sig[..32].copy_from_slice(&rx.serialize());
+        sig[32..].copy_from_slice(&s);
+        schnorr::Signature::from_byte_array(sig)
+    }
+
+    /// Recovers the adaptor secret from the signature `sig` decrypted from this adaptor
+    /// signature.
+    ///
+    /// Returns an error if `sig` is not the decryption of this adaptor signature with the secret
+    /// of `adaptor`.
+    pub fn recover(
+        &self,
+        sig: &schnorr::Signature,
+        adaptor: &CompressedPublicKey,
+    ) -> Result<SecretKey, RecoverAdaptorSecretError> {
+        let (rx, r_parity) = self.nonce.x_only_public_key();
+        let (sig_rx, sig_s) = sig.as_byte_array().split_at(32);
+        if sig_rx != rx.serialize() {
+            return Err(RecoverAdaptorSecretError);
+        }
+        let sig_s = <[u8; 32]>::try_from(sig_s).expect("signatures are 64 bytes");
+
+        // `t = s - s'`, negated if `R` is odd.
+        let pre_s = SecretKey::from_secret_bytes(self.s).map_err(|_| RecoverAdaptorSecretError)?;
+        let sig_s = Scalar::from_be_bytes(sig_s).map_err(|_| RecoverAdaptorSecretError)?;
+        let secret = pre_s.negate().add_tweak(&sig_s).map_err(|_| RecoverAdaptorSecretError)?;
+        let secret = if r_parity == Parity::Odd { secret.negate() } else { secret };
+
+        if secret.public_key() == adaptor.to_inner() {
+            Ok(secret)
+        } else {
+            Err(RecoverAdaptorSecretError)
+        }
+    }
+
+    /// Serializes the adaptor signature as the compressed nonce point followed by the scalar.
+    pub fn to_byte_array(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..33].copy_from_slice(&self.nonce.serialize());
+        bytes[33..].copy_from_slice(&self.s);
+        bytes
+    }
+
+    /// Deserializes an adaptor signature serialized with [`Self::to_byte_array`].
+    pub fn from_byte_array(bytes: &[u8; Self::SIZE]) -> Result<Self, ParseAdaptorSignatureError> {
+        let (nonce, s) = bytes.split_at(33);
+        let nonce = secp256k1::PublicKey::from_slice(nonce)
+            .map_err(|_| ParseAdaptorSignatureError::InvalidNonce)?;
+        let s = <[u8; 32]>::try_from(s).expect("65 - 33 is 32");
+        Scalar::from_be_bytes(s).map_err(|_| ParseAdaptorSignatureError::InvalidScalar)?;
+        Ok(Self { nonce, s })
+    }
+}
+
+/// Error parsing an adaptor signature, see [`AdaptorSignature::from_byte_array`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseAdaptorSignatureError {
+    /// The nonce is not a valid compressed point.
+    InvalidNonce,
+    /// The scalar is not below the curve order.
+    InvalidScalar,
+}
+
+impl From<Infallible> for ParseAdaptorSignatureError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ParseAdaptorSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidNonce => f.write_str("adaptor signature nonce is not a valid point"),
+            Self::InvalidScalar => f.write_str("adaptor signature scalar is out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseAdaptorSignatureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::InvalidNonce | Self::InvalidScalar => None,
+        }
+    }
+}
+
+/// The adaptor signature is invalid, see [`AdaptorSignature::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InvalidAdaptorSignatureError;
+
+impl fmt::Display for InvalidAdaptorSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid adaptor signature")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidAdaptorSignatureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
+/// The signature is not decrypted from the adaptor signature, see [`AdaptorSignature::recover`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RecoverAdaptorSecretError;
+
+impl fmt::Display for RecoverAdaptorSecretError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("signature does not match the adaptor signature and adaptor point")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecoverAdaptorSecretError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair(byte: u8) -> Keypair {
+        Keypair::from_secret_key(&SecretKey::from_secret_bytes([byte; 32]).unwrap())
+    }
+
+    #[test]
+    fn sign_verify_decrypt_recover() {
+        let mut parities = [false; 2];
+        for i in 1..=16 {
+            let keypair = keypair(i);
+            let (pubkey, _) = XOnlyPublicKey::from_keypair(&keypair);
+            let secret = SecretKey::from_secret_bytes([i + 100; 32]).unwrap();
+            let adaptor = CompressedPublicKey::from_secp(secret.public_key());
+            let msg = [i; 32];
+
+            let pre_sig = AdaptorSignature::sign(&msg, &keypair, &adaptor, &[i; 32]);
+            parities[pre_sig.nonce.x_only_public_key().1.to_u8() as usize] = true;
+            assert_eq!(pre_sig.verify(&msg, &pubkey, &adaptor), Ok(()));
+            // Not a valid signature before decryption.
+            let (rx, _) = pre_sig.nonce.x_only_public_key();
+            let mut undecrypted = [0; 64];
+            undecrypted[..32].copy_from_slice(&rx.serialize());
+            undecrypted[32..].copy_from_slice(&pre_sig.s);
+            let undecrypted = schnorr::Signature::from_byte_array(undecrypted);
+            assert!(schnorr::verify(&undecrypted, &msg, &pubkey.to_inner()).is_err());
+
+            let sig = pre_sig.decrypt(&secret);
+            assert!(schnorr::verify(&sig, &msg, &pubkey.to_inner()).is_ok());
+            assert_eq!(pre_sig.recover(&sig, &adaptor), Ok(secret));
+
+            let bytes = pre_sig.to_byte_array();
+            assert_eq!(AdaptorSignature::from_byte_array(&bytes), Ok(pre_sig));
+        }
+        // Both nonce parities are covered.
+        assert_eq!(parities, [true, true]);
+    }
+
+    #[test]
+    fn invalid() {
+        let keypair = keypair(1);
+        let (pubkey, _) = XOnlyPublicKey::from_keypair(&keypair);
+        let secret = SecretKey::from_secret_bytes([2; 32]).unwrap();
+        let adaptor = CompressedPublicKey::from_secp(secret.public_key());
+        let other_secret = SecretKey::from_secret_bytes([3; 32]).unwrap();
+        let other_adaptor = CompressedPublicKey::from_secp(other_secret.public_key());
+        let pre_sig = AdaptorSignature::sign(&[4; 32], &keypair, &adaptor, &[0; 32]);
+
+        assert_eq!(pre_sig.verify(&[5; 32], &pubkey, &adaptor), Err(InvalidAdaptorSignatureError));
+        assert_eq!(
+            pre_sig.verify(&[4; 32], &pubkey, &other_adaptor),
+            Err(InvalidAdaptorSignatureError)
+        );
+        let (other_pubkey, _) = XOnlyPublicKey::from_keypair(&self::keypair(6));
+        assert_eq!(
+            pre_sig.verify(&[4; 32], &other_pubkey, &adaptor),
+            Err(InvalidAdaptorSignatureError)
+        );
+
+        // Decrypting with the wrong secret gives an invalid signature that recovers nothing.
+        let sig = pre_sig.decrypt(&other_secret);
+        assert!(schnorr::verify(&sig, &[4; 32], &pubkey.to_inner()).is_err());
+        assert_eq!(pre_sig.recover(&sig, &adaptor), Err(RecoverAdaptorSecretError));
+        let unrelated = keypair.to_inner().sign_schnorr_no_aux_rand(&[4; 32]);
+        assert_eq!(pre_sig.recover(&unrelated, &adaptor), Err(RecoverAdaptorSecretError));
+
+        let mut bytes = pre_sig.to_byte_array();
+        bytes[33..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            AdaptorSignature::from_byte_array(&bytes),
+            Err(ParseAdaptorSignatureError::InvalidScalar)
+        );
+        bytes[0] = 0x04;
+        assert_eq!(
+            AdaptorSignature::from_byte_array(&bytes),
+            Err(ParseAdaptorSignatureError::InvalidNonce)
+        );
+    }
+}
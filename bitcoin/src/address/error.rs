@@ -6,7 +6,7 @@
 use internals::write_err;
 
 use crate::address::{Address, NetworkUnchecked};
-use crate::prelude::String;
+use crate::prelude::{String, Vec};
 use crate::script::{witness_program, witness_version};
 use crate::Network;
 
@@ -124,6 +124,70 @@ impl From<NetworkValidationError> for ParseError {
     fn from(e: NetworkValidationError) -> Self { Self::NetworkValidation(e) }
 }
 
+/// Error parsing a batch of addresses, see [`Address::parse_batch`].
+///
+/// Holds the errors of all entries that failed to parse, in the order of the entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBatchError {
+    /// The errors of the failed entries, never empty.
+    pub(crate) errors: Vec<BatchEntryError>,
+}
+
+impl ParseBatchError {
+    /// Returns the errors of the entries that failed to parse.
+    pub fn errors(&self) -> &[BatchEntryError] { &self.errors }
+
+    /// Returns the error of the first entry that failed to parse.
+    pub fn first(&self) -> &BatchEntryError { &self.errors[0] }
+
+    /// Converts this error into the errors of the entries that failed to parse.
+    pub fn into_errors(self) -> Vec<BatchEntryError> { self.errors }
+}
+
+impl From<Infallible> for ParseBatchError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ParseBatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} addresses failed to parse, first ", self.errors.len())?;
+        fmt::Display::fmt(self.first(), f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseBatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(self.first()) }
+}
+
+/// Error parsing a single entry of a batch of addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEntryError {
+    /// The index of the entry in the batch.
+    pub(crate) index: usize,
+    /// The error parsing the entry.
+    pub(crate) error: ParseError,
+}
+
+impl BatchEntryError {
+    /// Returns the index of the entry in the batch.
+    pub fn index(&self) -> usize { self.index }
+
+    /// Returns the error parsing the entry.
+    pub fn error(&self) -> &ParseError { &self.error }
+}
+
+impl fmt::Display for BatchEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_err!(f, "address at index {}", self.index; self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BatchEntryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(&self.error) }
+}
+
 /// Unknown HRP error.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
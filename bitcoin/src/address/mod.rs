@@ -39,6 +39,7 @@
 //! ```
 
 pub mod error;
+mod owner;
 
 use core::fmt;
 use core::marker::PhantomData;
@@ -58,8 +59,8 @@
     XOnlyPublicKey,
 };
 use crate::network::{Network, NetworkKind, Params};
-use crate::prelude::{String, ToOwned};
-use crate::script::witness_program::WitnessProgram;
+use crate::prelude::{BTreeSet, String, ToOwned, Vec};
+use crate::script::witness_program::{WitnessProgram, WitnessProgramKind};
 use crate::script::witness_version::WitnessVersion;
 use crate::script::{
     self, RedeemScriptSizeError, Script, ScriptExt as _, ScriptHash, ScriptHashableTag,
@@ -67,14 +68,19 @@
     WitnessScript, WitnessScriptExt as _, WitnessScriptSizeError,
 };
 use crate::taproot::TapNodeHash;
+use crate::transaction::{InputWeightPrediction, TxOut, TxOutExt as _};
+use crate::{Amount, Weight};
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
 pub use self::error::{
-        Base58Error, Bech32Error, FromScriptError, InvalidBase58PayloadLengthError,
-        InvalidLegacyPrefixError, LegacyAddressTooLongError, NetworkValidationError,
-        ParseError, UnknownAddressTypeError, UnknownHrpError, ParseBech32Error,
+        Base58Error, BatchEntryError, Bech32Error, FromScriptError,
+        InvalidBase58PayloadLengthError, InvalidLegacyPrefixError, LegacyAddressTooLongError,
+        NetworkValidationError, ParseBatchError, ParseError, UnknownAddressTypeError,
+        UnknownHrpError, ParseBech32Error,
 };
+#[doc(inline)]
+pub use self::owner::SpkOwner;
 
 /// The different types of addresses.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -122,6 +128,20 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
     }
 }
 
+/// The cost of spending an output paying to an address, see [`Address::spend_cost`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendCost {
+    /// The weight prediction of the input spending the output.
+    pub input: InputWeightPrediction,
+    /// Whether the input has witness data, making the spending transaction a SegWit transaction.
+    pub requires_witness: bool,
+}
+
+impl SpendCost {
+    /// Returns the weight of the input spending the output.
+    pub const fn input_weight(&self) -> Weight { self.input.total_weight() }
+}
+
 mod sealed {
     pub trait NetworkValidation {}
     impl NetworkValidation for super::NetworkChecked {}
@@ -614,18 +634,16 @@ pub fn address_type(&self) -> Option<AddressType> {
         match *self.inner() {
             AddressInner::P2pkh { .. } => Some(AddressType::P2pkh),
             AddressInner::P2sh { .. } => Some(AddressType::P2sh),
-            AddressInner::Segwit { ref program, hrp: _ } =>
-                if program.is_p2wpkh() {
-                    Some(AddressType::P2wpkh)
-                } else if program.is_p2wsh() {
-                    Some(AddressType::P2wsh)
-                } else if program.is_p2tr() {
-                    Some(AddressType::P2tr)
-                } else if program.is_p2a() {
-                    Some(AddressType::P2a)
-                } else {
-                    None
-                },
+            AddressInner::Segwit { ref program, hrp: _ } => match program.kind() {
+                WitnessProgramKind::V0Keyhash(_) => Some(AddressType::P2wpkh),
+                WitnessProgramKind::V0Scripthash(_) => Some(AddressType::P2wsh),
+                WitnessProgramKind::V1Taproot(_) => Some(AddressType::P2tr),
+                WitnessProgramKind::V1Anchor => Some(AddressType::P2a),
+                // A version 1 program of 32 bytes is a Taproot address even if it is not a key.
+                WitnessProgramKind::Future { program } if program.is_p2tr() =>
+                    Some(AddressType::P2tr),
+                WitnessProgramKind::Future { .. } => None,
+            },
         }
     }
 
@@ -670,6 +688,11 @@ pub fn witness_program(&self) -> Option<WitnessProgram> {
         }
     }
 
+    /// Gets the kind of witness program for this address if this is a SegWit address.
+    pub fn witness_program_kind(&self) -> Option<WitnessProgramKind> {
+        self.witness_program().map(|program| program.kind())
+    }
+
     /// Checks whether or not the address is following Bitcoin standardness rules when
     /// *spending* from this address. *NOT* to be called by senders.
     ///
@@ -685,6 +708,33 @@ pub fn witness_program(&self) -> Option<WitnessProgram> {
     ///
     pub fn is_spend_standard(&self) -> bool { self.address_type().is_some() }
 
+    /// Returns the cost of spending an output paying to this address.
+    ///
+    /// The input weight is that of the largest standard input, with a compressed key and a
+    /// maximum-size signature for P2PKH and P2WPKH and a default sighash key path spend for P2TR.
+    ///
+    /// Returns `None` for P2SH and P2WSH addresses, the cost of which depends on the script, and
+    /// for unknown witness versions. Use [`ScriptPubKeyExt::max_satisfaction_weight`] with the
+    /// script for those.
+    ///
+    /// [`ScriptPubKeyExt::max_satisfaction_weight`]: crate::script::ScriptPubKeyExt::max_satisfaction_weight
+    pub fn spend_cost(&self) -> Option<SpendCost> {
+        let (input, requires_witness) = match self.address_type()? {
+            AddressType::P2pkh => (InputWeightPrediction::P2PKH_COMPRESSED_MAX, false),
+            AddressType::P2wpkh => (InputWeightPrediction::P2WPKH_MAX, true),
+            AddressType::P2tr => (InputWeightPrediction::P2TR_KEY_DEFAULT_SIGHASH, true),
+            // Spent with an empty script and an empty witness.
+            AddressType::P2a => (InputWeightPrediction::from_slice(0, &[]), false),
+            AddressType::P2sh | AddressType::P2wsh => return None,
+        };
+        Some(SpendCost { input, requires_witness })
+    }
+
+    /// Returns the weight of an output paying to this address.
+    pub fn output_weight(&self) -> Weight {
+        TxOut { amount: Amount::ZERO, script_pubkey: self.script_pubkey() }.weight()
+    }
+
     /// Constructs a new [`Address`] from an output script (`scriptPubkey`).
     pub fn from_script(
         script: &ScriptPubKey,
@@ -710,6 +760,63 @@ pub fn from_script(
         }
     }
 
+    /// Parses a batch of addresses, all of which must be valid for `network`.
+    ///
+    /// Returns the addresses in the order of the entries, with a repeated address kept only at its
+    /// first occurrence. Entries are compared as addresses, so for example the upper case and
+    /// lower case encodings of a bech32 address are the same address.
+    ///
+    /// # Errors
+    ///
+    /// If any entry fails to parse or is not valid for `network`. The error holds the index and
+    /// error of every such entry, not just the first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bitcoin::{Address, Network};
+    ///
+    /// let batch = [
+    ///     "bc1qvzvkjn4q3nszqxrv3nraga2r822xjty3ykvkuw",
+    ///     "1QJVDzdqb1VpbDK7uDeyVXy9mR27CJiyhY",
+    ///     "BC1QVZVKJN4Q3NSZQXRV3NRAGA2R822XJTY3YKVKUW",
+    /// ];
+    /// let addresses = Address::parse_batch(batch, Network::Bitcoin).unwrap();
+    /// assert_eq!(addresses.len(), 2);
+    ///
+    /// let batch = ["tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx", "not an address"];
+    /// let err = Address::parse_batch(batch, Network::Bitcoin).unwrap_err();
+    /// assert_eq!(err.errors().len(), 2);
+    /// assert_eq!(err.errors()[1].index(), 1);
+    /// ```
+    pub fn parse_batch<I>(addresses: I, network: Network) -> Result<Vec<Self>, ParseBatchError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut parsed = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut errors = Vec::new();
+        for (index, address) in addresses.into_iter().enumerate() {
+            let result = address
+                .as_ref()
+                .parse::<Address<NetworkUnchecked>>()
+                .and_then(|address| address.require_network(network));
+            match result {
+                Ok(address) =>
+                    if seen.insert(address) {
+                        parsed.push(address);
+                    },
+                Err(error) => errors.push(BatchEntryError { index, error }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(parsed)
+        } else {
+            Err(ParseBatchError { errors })
+        }
+    }
+
     /// Generates a script pubkey spending to this address.
     pub fn script_pubkey(&self) -> ScriptPubKeyBuf {
         use AddressInner::*;
@@ -1230,6 +1337,64 @@ fn address_type() {
                 .expect("mainnet");
             assert_eq!(&addr.address_type(), expected_type);
         }
+
+        // Not a valid key.
+        let program = WitnessProgram::new(WitnessVersion::V1, &[0xff; 32]).unwrap();
+        let address = Address::from_witness_program(program, KnownHrp::Mainnet);
+        assert_eq!(address.address_type(), Some(AddressType::P2tr));
+    }
+
+    #[test]
+    fn parse_batch() {
+        let batch = [
+            "1QJVDzdqb1VpbDK7uDeyVXy9mR27CJiyhY",
+            "bc1qvzvkjn4q3nszqxrv3nraga2r822xjty3ykvkuw",
+            "1QJVDzdqb1VpbDK7uDeyVXy9mR27CJiyhY",
+            "BC1QVZVKJN4Q3NSZQXRV3NRAGA2R822XJTY3YKVKUW",
+            "33iFwdLuRpW1uK1RTRqsoi8rR4NpDzk66k",
+        ];
+        let addresses = Address::parse_batch(batch, Network::Bitcoin).unwrap();
+        let expected = [batch[0], batch[1], batch[4]]
+            .map(|address| address.parse::<Address<_>>().unwrap().assume_checked());
+        assert_eq!(addresses, expected);
+        assert!(Address::parse_batch(Vec::<String>::new(), Network::Bitcoin).unwrap().is_empty());
+
+        let batch = [
+            "1QJVDzdqb1VpbDK7uDeyVXy9mR27CJiyhY".to_owned(),
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_owned(),
+            "bc1qvzvkjn4q3nszqxrv3nraga2r822xjty3ykvkuw".to_owned(),
+            "not an address".to_owned(),
+        ];
+        let err = Address::parse_batch(&batch, Network::Bitcoin).unwrap_err();
+        let indices = err.errors().iter().map(BatchEntryError::index).collect::<Vec<_>>();
+        assert_eq!(indices, [1, 3]);
+        assert!(matches!(err.first().error(), ParseError::NetworkValidation(_)));
+        assert!(matches!(err.errors()[1].error(), ParseError::Base58(_)));
+        assert!(Address::parse_batch(&batch[1..2], Network::Testnet(TestnetVersion::V3)).is_ok());
+    }
+
+    #[test]
+    fn spend_cost_and_output_weight() {
+        let addresses = [
+            ("1QJVDzdqb1VpbDK7uDeyVXy9mR27CJiyhY", Some((592, false)), 136),
+            ("33iFwdLuRpW1uK1RTRqsoi8rR4NpDzk66k", None, 128),
+            ("bc1qvzvkjn4q3nszqxrv3nraga2r822xjty3ykvkuw", Some((272, true)), 124),
+            ("bc1qwqdg6squsna38e46795at95yu9atm8azzmyvckulcc7kytlcckxswvvzej", None, 172),
+            (
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                Some((230, true)),
+                172,
+            ),
+            ("bc1pfeessrawgf", Some((164, false)), 52),
+            ("bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs", None, 108),
+        ];
+        for (address, expected_cost, output_weight) in addresses {
+            let addr = address.parse::<Address<_>>().unwrap().assume_checked();
+            let cost =
+                addr.spend_cost().map(|cost| (cost.input_weight().to_wu(), cost.requires_witness));
+            assert_eq!(cost, expected_cost, "{}", address);
+            assert_eq!(addr.output_weight().to_wu(), output_weight, "{}", address);
+        }
     }
 
     #[test]
@@ -1363,6 +1528,22 @@ fn p2tr_from_untweaked() {
         roundtrips(&address, Bitcoin);
     }
 
+    #[test]
+    fn witness_program_kind() {
+        // Test case from BIP-086, the output key of `p2tr_from_untweaked`.
+        let address = "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+            .parse::<Address<_>>()
+            .unwrap()
+            .assume_checked();
+        let output_key = "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+            .parse::<XOnlyPublicKey>()
+            .unwrap();
+        assert_eq!(address.witness_program_kind(), Some(WitnessProgramKind::V1Taproot(output_key)));
+
+        let address = "1QJVDzdqb1VpbDK7uDeyVXy9mR27CJiyhY".parse::<Address<_>>().unwrap();
+        assert_eq!(address.assume_checked().witness_program_kind(), None);
+    }
+
     #[test]
     fn is_related_to_pubkey_p2wpkh() {
         let address_string = "bc1qhvd6suvqzjcu9pxjhrwhtrlj85ny3n2mqql5w4";
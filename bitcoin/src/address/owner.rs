@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Owners of output scripts, including those without an address.
+//!
+//! Indexers key outputs by who can spend them, which for most outputs is an [`Address`]. Pay to
+//! public key, bare multisig and `OP_RETURN` outputs are standard but have no address, Bitcoin
+//! Core omits the `address` field for them and only reports the descriptor inferred from the
+//! script. [`SpkOwner`] represents all of these.
+
+use core::fmt::{self, Write as _};
+
+use super::Address;
+use crate::crypto::key::PublicKey;
+use crate::network::Params;
+use crate::opcodes::all::*;
+use crate::prelude::{String, ToOwned, Vec};
+use crate::script::{
+    Builder, Instruction, ScriptExt as _, ScriptPubKey, ScriptPubKeyBuf, ScriptPubKeyBufExt as _,
+    ScriptPubKeyExt as _,
+};
+
+/// The owner of an output script, an address or one of the standard outputs without address.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum SpkOwner {
+    /// The script pays to an address.
+    Address(Address),
+    /// The script pays to a public key, `<key> OP_CHECKSIG`.
+    PubKey(PublicKey),
+    /// The script pays to a bare multisig, `<threshold> <keys>... <n> OP_CHECKMULTISIG`.
+    Multisig {
+        /// The number of signatures required.
+        threshold: usize,
+        /// The keys, in script order.
+        keys: Vec<PublicKey>,
+    },
+    /// The script is `OP_RETURN` followed by data pushes, the output is provably unspendable.
+    NullData(ScriptPubKeyBuf),
+    /// Any other script.
+    NonStandard(ScriptPubKeyBuf),
+}
+
+impl SpkOwner {
+    /// Returns the owner of `script`, using `params` for the network of addresses.
+    ///
+    /// Like Bitcoin Core, keys have to be valid and encoded the standard way, otherwise the
+    /// script is [`NonStandard`](Self::NonStandard).
+    pub fn from_script(script: &ScriptPubKey, params: impl AsRef<Params>) -> Self {
+        if let Ok(address) = Address::from_script(script, params) {
+            return Self::Address(address);
+        }
+        if let Some(key) = script.p2pk_public_key() {
+            // Hybrid keys parse but do not round trip, Bitcoin Core treats them as non-standard.
+            if ScriptPubKeyBuf::new_p2pk(key) == *script {
+                return Self::PubKey(key);
+            }
+        }
+        if let Some((threshold, keys)) = multisig(script) {
+            return Self::Multisig { threshold, keys };
+        }
+        let bytes = script.as_bytes();
+        if bytes.first() == Some(&OP_RETURN.to_u8())
+            && ScriptPubKey::from_bytes(&bytes[1..]).is_push_only()
+        {
+            Self::NullData(script.to_owned())
+        } else {
+            Self::NonStandard(script.to_owned())
+        }
+    }
+
+    /// Returns the address, if the script pays to one.
+    pub fn address(&self) -> Option<&Address> {
+        match *self {
+            Self::Address(ref address) => Some(address),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the script is standard, i.e. is not [`NonStandard`](Self::NonStandard).
+    ///
+    /// Addresses of unknown SegWit versions count as standard outputs.
+    pub fn is_standard(&self) -> bool { !matches!(*self, Self::NonStandard(_)) }
+
+    /// Returns the script this is the owner of.
+    pub fn script_pubkey(&self) -> ScriptPubKeyBuf {
+        match *self {
+            Self::Address(ref address) => address.script_pubkey(),
+            Self::PubKey(key) => ScriptPubKeyBuf::new_p2pk(key),
+            Self::Multisig { threshold, ref keys } => multisig_script(threshold, keys),
+            Self::NullData(ref script) | Self::NonStandard(ref script) => script.clone(),
+        }
+    }
+}
+
+/// Returns the threshold and the keys of a bare multisig script with standard encoding.
+fn multisig(script: &ScriptPubKey) -> Option<(usize, Vec<PublicKey>)> {
+    let instructions = script.instructions().collect::<Result<Vec<_>, _>>().ok()?;
+    let (threshold, keys) = match instructions[..] {
+        [Instruction::Op(threshold), ref keys @ .., Instruction::Op(_), Instruction::Op(OP_CHECKMULTISIG)] =>
+            (usize::from(threshold.decode_pushnum()?), keys),
+        _ => return None,
+    };
+    let keys = keys
+        .iter()
+        .map(|key| match key {
+            Instruction::PushBytes(key) => PublicKey::from_slice(key.as_bytes()).ok(),
+            Instruction::Op(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    // Re-encoding also checks the key count and rejects non-minimal pushes and hybrid keys.
+    (threshold <= keys.len() && multisig_script(threshold, &keys) == *script)
+        .then_some((threshold, keys))
+}
+
+/// Returns the bare multisig script of `threshold` of `keys`.
+fn multisig_script(threshold: usize, keys: &[PublicKey]) -> ScriptPubKeyBuf {
+    // Casts ok, parsed multisig scripts have at most 16 keys.
+    let mut builder = Builder::new().push_int_unchecked(threshold as i64);
+    for key in keys {
+        builder = builder.push_key(*key);
+    }
+    builder.push_int_unchecked(keys.len() as i64).push_opcode(OP_CHECKMULTISIG).into_script()
+}
+
+/// Displays the address, or for outputs without address the descriptor Bitcoin Core infers, for
+/// example `pk(<key>)#<checksum>`, `multi(1,<key>,<key>)#<checksum>` and `raw(<hex>)#<checksum>`.
+impl fmt::Display for SpkOwner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut descriptor = String::new();
+        match *self {
+            Self::Address(ref address) => return fmt::Display::fmt(address, f),
+            Self::PubKey(key) => write!(descriptor, "pk({})", key)?,
+            Self::Multisig { threshold, ref keys } => {
+                write!(descriptor, "multi({}", threshold)?;
+                for key in keys {
+                    write!(descriptor, ",{}", key)?;
+                }
+                descriptor.push(')');
+            }
+            Self::NullData(ref script) | Self::NonStandard(ref script) =>
+                write!(descriptor, "raw({:x})", script)?,
+        }
+        let checksum = crate::descriptor::checksum(&descriptor).expect("keys and hex are valid");
+        write!(f, "{}#", descriptor)?;
+        checksum.iter().try_for_each(|&ch| f.write_char(ch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Network;
+    use crate::prelude::ToString;
+    use crate::script::ScriptBufExt as _;
+
+    const KEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    const KEY_2: &str = "03b0da749730dc9b4b1f4a14d6902877a92541f5368778853d9c4a0cb7802dcfb2";
+    // Uncompressed key of the generator point.
+    const KEY_UNCOMPRESSED: &str = "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+    fn owner(hex: &str) -> SpkOwner {
+        let script = ScriptPubKeyBuf::from_hex_no_length_prefix(hex).unwrap();
+        let owner = SpkOwner::from_script(&script, Network::Bitcoin);
+        assert_eq!(owner.script_pubkey(), script);
+        owner
+    }
+
+    #[test]
+    fn address() {
+        let owner = owner("0014751e76e8199196d454941c45d1b3a323f1433bd6");
+        assert!(owner.is_standard());
+        assert_eq!(owner.to_string(), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        assert_eq!(owner.address().unwrap().to_string(), owner.to_string());
+    }
+
+    #[test]
+    fn pubkey() {
+        let owner = owner(&format!("21{}ac", KEY));
+        assert_eq!(owner, SpkOwner::PubKey(KEY.parse().unwrap()));
+        assert_eq!(owner.address(), None);
+        assert!(owner.to_string().starts_with(&format!("pk({})#", KEY)));
+
+        let owner = self::owner(&format!("41{}ac", KEY_UNCOMPRESSED));
+        assert_eq!(owner, SpkOwner::PubKey(KEY_UNCOMPRESSED.parse().unwrap()));
+
+        // Hybrid keys, off-curve keys and non-minimal pushes are not standard.
+        let hybrid = format!("4106{}ac", &KEY_UNCOMPRESSED[2..]);
+        assert!(matches!(self::owner(&hybrid), SpkOwner::NonStandard(_)));
+        let off_curve = format!("2102{}ac", "00".repeat(32));
+        assert!(matches!(self::owner(&off_curve), SpkOwner::NonStandard(_)));
+        let non_minimal = format!("4c21{}ac", KEY);
+        assert!(matches!(self::owner(&non_minimal), SpkOwner::NonStandard(_)));
+    }
+
+    #[test]
+    fn multisig() {
+        let owner = owner(&format!("5121{}21{}52ae", KEY, KEY_2));
+        let keys = vec![KEY.parse().unwrap(), KEY_2.parse().unwrap()];
+        assert_eq!(owner, SpkOwner::Multisig { threshold: 1, keys });
+        assert!(owner.to_string().starts_with(&format!("multi(1,{},{})#", KEY, KEY_2)));
+
+        // Wrong key count and threshold above the key count.
+        assert!(matches!(self::owner(&format!("5121{}53ae", KEY)), SpkOwner::NonStandard(_)));
+        assert!(matches!(self::owner(&format!("5221{}51ae", KEY)), SpkOwner::NonStandard(_)));
+        // A zero threshold.
+        assert!(matches!(self::owner(&format!("0021{}51ae", KEY)), SpkOwner::NonStandard(_)));
+    }
+
+    #[test]
+    fn raw() {
+        let owner = owner("6a04deadbeef");
+        assert_eq!(
+            owner,
+            SpkOwner::NullData(ScriptPubKeyBuf::from_hex_no_length_prefix("6a04deadbeef").unwrap())
+        );
+        assert!(owner.is_standard());
+        assert!(owner.to_string().starts_with("raw(6a04deadbeef)#"));
+
+        let owner = self::owner("deadbeef");
+        assert!(!owner.is_standard());
+        // Checksum from Bitcoin Core.
+        assert_eq!(owner.to_string(), "raw(deadbeef)#89f8spxm");
+        // `OP_RETURN` followed by a non-push opcode.
+        assert!(matches!(self::owner("6a61"), SpkOwner::NonStandard(_)));
+    }
+}
@@ -51,6 +51,12 @@ fn from_hmac(hmac: Hmac<sha512::Hash>) -> Self {
     #[inline]
     #[deprecated(since = "TBD", note = "use to_vec instead")]
     pub fn to_bytes(self) -> alloc::vec::Vec<u8> { self.to_vec() }
+
+    /// Attempts to erase the chain code, see [`Xpriv::non_secure_erase`].
+    #[inline]
+    pub fn non_secure_erase(&mut self) {
+        secp256k1::ffi::non_secure_erase_impl(&mut self.0, [0; 32]);
+    }
 }
 
 /// A fingerprint
@@ -69,7 +75,9 @@ pub fn to_bytes(self) -> alloc::vec::Vec<u8> { self.to_vec() }
 hashes::impl_serde_for_newtype!(XKeyIdentifier);
 
 /// Extended private key
-#[derive(Copy, Clone, PartialEq, Eq)]
+///
+/// Equality compares the chain code and the private key in constant time.
+#[derive(Copy, Clone)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct Xpriv {
     /// The network this key is to be used on
@@ -88,6 +96,37 @@ pub struct Xpriv {
 #[cfg(feature = "serde")]
 internals::serde_string_impl!(Xpriv, "a BIP-0032 extended private key");
 
+impl PartialEq for Xpriv {
+    fn eq(&self, other: &Self) -> bool {
+        let chain_code = self
+            .chain_code
+            .0
+            .iter()
+            .zip(&other.chain_code.0)
+            .fold(0, |accum, (a, b)| accum | a ^ b);
+        // Bitwise `&` so that the public fields do not short-circuit comparing the secret ones.
+        (self.network == other.network)
+            & (self.depth == other.depth)
+            & (self.parent_fingerprint == other.parent_fingerprint)
+            & (self.child_number == other.child_number)
+            & (core::hint::black_box(chain_code) == 0)
+            & (self.private_key == other.private_key)
+    }
+}
+
+impl Eq for Xpriv {}
+
+/// `Xpriv` is `Copy` so it can't be erased on drop, see [`Xpriv::non_secure_erase`].
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Xpriv {
+    fn zeroize(&mut self) { self.non_secure_erase(); }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for ChainCode {
+    fn zeroize(&mut self) { self.0.zeroize(); }
+}
+
 #[cfg(not(feature = "std"))]
 impl fmt::Debug for Xpriv {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -275,7 +314,7 @@ impl FromStr for ChildNumber {
     type Err = ParseChildNumberError;
 
     fn from_str(inp: &str) -> Result<Self, Self::Err> {
-        let is_hardened = inp.chars().last().is_some_and(|l| l == '\'' || l == 'h');
+        let is_hardened = inp.chars().last().is_some_and(|l| matches!(l, '\'' | 'h' | 'H'));
         Ok(if is_hardened {
             Self::from_hardened_idx(
                 inp[0..inp.len() - 1].parse().map_err(ParseChildNumberError::ParseInt)?,
@@ -320,6 +359,10 @@ pub trait IntoDerivationPath {
 }
 
 /// A BIP-0032 derivation path.
+///
+/// Parsing accepts `'`, `h` and `H` as hardened markers and an optional leading `m/`, so paths
+/// from descriptors, wallets and hardware devices parse to the same value. Displaying the path
+/// normalizes it, see [`DerivationPath::display`] for the available formats.
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub struct DerivationPath(Vec<ChildNumber>);
 
@@ -369,6 +412,10 @@ impl<'a> From<&'a [ChildNumber]> for DerivationPath {
     fn from(numbers: &'a [ChildNumber]) -> Self { Self(numbers.to_vec()) }
 }
 
+impl<const N: usize> From<[ChildNumber; N]> for DerivationPath {
+    fn from(numbers: [ChildNumber; N]) -> Self { Self(numbers.to_vec()) }
+}
+
 impl core::iter::FromIterator<ChildNumber> for DerivationPath {
     fn from_iter<T>(iter: T) -> Self
     where
@@ -520,30 +567,201 @@ pub fn to_u32_vec(&self) -> Vec<u32> { self.into_iter().map(|&el| el.into()).col
     pub fn from_u32_slice(numbers: &[u32]) -> Self {
         numbers.iter().map(|&n| ChildNumber::from(n)).collect()
     }
+
+    /// Returns an object that displays the derivation path in the given `format`.
+    ///
+    /// [`Display`](fmt::Display) itself omits the `m/` prefix and uses `'`, or `h` with the
+    /// alternate flag `{:#}`.
+    ///
+    /// ```
+    /// use bitcoin::bip32::{DerivationPath, HardenedMarker, PathFormat};
+    ///
+    /// let path = "84H/0'/0h/0/1".parse::<DerivationPath>().unwrap();
+    /// assert_eq!(path.display(PathFormat::BIP32).to_string(), "m/84'/0'/0'/0/1");
+    /// assert_eq!(path.display(PathFormat::DESCRIPTOR).to_string(), "84h/0h/0h/0/1");
+    ///
+    /// let format = PathFormat { marker: HardenedMarker::H, master_prefix: true };
+    /// assert_eq!(path.display(format).to_string(), "m/84h/0h/0h/0/1");
+    /// ```
+    pub fn display(&self, format: PathFormat) -> DisplayPath<'_> {
+        DisplayPath { path: self, format }
+    }
 }
 
 impl fmt::Display for DerivationPath {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut iter = self.0.iter();
-        if let Some(first_element) = iter.next() {
-            if f.alternate() {
-                write!(f, "{:#}", first_element)?;
-            } else {
-                write!(f, "{}", first_element)?;
-            }
+        let marker = if f.alternate() { HardenedMarker::H } else { HardenedMarker::Apostrophe };
+        fmt::Display::fmt(&self.display(PathFormat { marker, master_prefix: false }), f)
+    }
+}
+
+/// The marker appended to hardened child numbers when displaying a [`DerivationPath`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HardenedMarker {
+    /// An apostrophe `'`, as used in BIP-0032.
+    Apostrophe,
+    /// A lowercase `h`, as used by Bitcoin Core in descriptors. Unlike `'` it needs no quoting
+    /// in shells and JSON.
+    H,
+}
+
+impl HardenedMarker {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Apostrophe => "'",
+            Self::H => "h",
         }
-        for cn in iter {
-            f.write_str("/")?;
-            if f.alternate() {
-                write!(f, "{:#}", cn)?;
-            } else {
-                write!(f, "{}", cn)?;
+    }
+}
+
+/// How to display a [`DerivationPath`], see [`DerivationPath::display`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathFormat {
+    /// The marker appended to hardened child numbers.
+    pub marker: HardenedMarker,
+    /// Whether to start the path with `m/`, or display the master path as `m` instead of an
+    /// empty string.
+    pub master_prefix: bool,
+}
+
+impl PathFormat {
+    /// The format used in BIP-0032, e.g. `m/84'/0'/0'`.
+    pub const BIP32: Self = Self { marker: HardenedMarker::Apostrophe, master_prefix: true };
+
+    /// The format Bitcoin Core uses for key origins in descriptors, e.g. `84h/0h/0h`.
+    pub const DESCRIPTOR: Self = Self { marker: HardenedMarker::H, master_prefix: false };
+}
+
+/// Displays a [`DerivationPath`] in a [`PathFormat`], returned by [`DerivationPath::display`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayPath<'a> {
+    path: &'a DerivationPath,
+    format: PathFormat,
+}
+
+impl fmt::Display for DisplayPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.format.master_prefix {
+            f.write_str("m")?;
+        }
+        for (i, cn) in self.path.0.iter().enumerate() {
+            if i > 0 || self.format.master_prefix {
+                f.write_str("/")?;
+            }
+            match *cn {
+                ChildNumber::Normal { index } => write!(f, "{}", index)?,
+                ChildNumber::Hardened { index } =>
+                    write!(f, "{}{}", index, self.format.marker.as_str())?,
             }
         }
         Ok(())
     }
 }
 
+/// Constructs a new array of [`ChildNumber`]s from a derivation path literal, checked at compile
+/// time.
+///
+/// The path is parsed like [`DerivationPath`], with `'`, `h` or `H` as hardened markers and an
+/// optional leading `m/`. The result can be used in constants and converted into a
+/// [`DerivationPath`] with [`From`].
+///
+/// ```
+/// use bitcoin::bip32::{ChildNumber, DerivationPath};
+///
+/// const ACCOUNT: [ChildNumber; 3] = bitcoin::path!("m/84h/0h/0h");
+///
+/// let path = DerivationPath::from(ACCOUNT);
+/// assert_eq!(path, "84'/0'/0'".parse().unwrap());
+/// assert_eq!(bitcoin::path!("0/1").len(), 2);
+/// ```
+///
+/// Invalid paths fail to compile.
+///
+/// ```compile_fail
+/// const PATH: [bitcoin::bip32::ChildNumber; 1] = bitcoin::path!("2147483648");
+/// ```
+#[macro_export]
+macro_rules! path {
+    ($path:expr) => {{
+        const PATH: [$crate::bip32::ChildNumber; $crate::bip32::path_len($path)] =
+            $crate::bip32::parse_path($path);
+        PATH
+    }};
+}
+
+/// Returns the number of child numbers in `path`, for [`path!`](crate::path).
+#[doc(hidden)]
+pub const fn path_len(path: &str) -> usize {
+    let bytes = strip_master_prefix(path.as_bytes());
+    if bytes.is_empty() {
+        return 0;
+    }
+    let mut len = 1;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'/' {
+            len += 1;
+        }
+        i += 1;
+    }
+    len
+}
+
+/// Parses `path` into `N` child numbers, for [`path!`](crate::path).
+///
+/// # Panics
+///
+/// If `path` is not a derivation path of length `N`.
+#[doc(hidden)]
+pub const fn parse_path<const N: usize>(path: &str) -> [ChildNumber; N] {
+    let bytes = strip_master_prefix(path.as_bytes());
+    let mut numbers = [ChildNumber::ZERO_NORMAL; N];
+    let mut n = 0;
+    let mut i = 0;
+    while n < N {
+        if n > 0 {
+            if i == bytes.len() || bytes[i] != b'/' {
+                panic!("invalid derivation path");
+            }
+            i += 1;
+        }
+        let start = i;
+        // Wide enough not to overflow before the range check.
+        let mut wide: u64 = 0;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            wide = wide * 10 + (bytes[i] - b'0') as u64;
+            if wide >= 1 << 31 {
+                panic!("derivation path index out of range");
+            }
+            i += 1;
+        }
+        if i == start {
+            panic!("invalid derivation path");
+        }
+        let index = wide as u32; // Cast ok, checked above.
+        numbers[n] = if i < bytes.len() && matches!(bytes[i], b'\'' | b'h' | b'H') {
+            i += 1;
+            ChildNumber::Hardened { index }
+        } else {
+            ChildNumber::Normal { index }
+        };
+        n += 1;
+    }
+    if i != bytes.len() {
+        panic!("invalid derivation path");
+    }
+    numbers
+}
+
+/// Strips `m` or `m/` off the start of a derivation path.
+const fn strip_master_prefix(bytes: &[u8]) -> &[u8] {
+    match bytes {
+        [b'm'] => &[],
+        [b'm', b'/', ..] => bytes.split_at(2).1,
+        _ => bytes,
+    }
+}
+
 impl fmt::Debug for DerivationPath {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::Display::fmt(&self, f) }
 }
@@ -848,6 +1066,18 @@ pub fn identifier(&self) -> XKeyIdentifier { Xpub::from_xpriv(self).identifier()
     pub fn fingerprint(&self) -> Fingerprint {
         self.identifier().as_byte_array().sub_array::<0, 4>().into()
     }
+
+    /// Attempts to erase the private key and the chain code.
+    ///
+    /// The rest of the key is public. Note, however, that `Xpriv` is `Copy` and the compiler is
+    /// allowed to freely copy or move it to other places in memory, which this does not erase.
+    /// For more discussion on this, please see the documentation of the
+    /// [`zeroize`](https://docs.rs/zeroize) crate.
+    #[inline]
+    pub fn non_secure_erase(&mut self) {
+        self.private_key.non_secure_erase();
+        self.chain_code.non_secure_erase();
+    }
 }
 
 impl Xpub {
@@ -1133,6 +1363,33 @@ fn test_derivation_path_display() {
         assert_eq!(format!("{:#}", path), "84h/0h/0h/0/0");
     }
 
+    #[test]
+    fn derivation_path_formats() {
+        let expected = DerivationPath::from_str("m/84'/0'/0'/0/1").unwrap();
+        for path in ["84h/0H/0'/0/1", "m/84H/0h/0h/0/1"] {
+            assert_eq!(path.parse::<DerivationPath>().unwrap(), expected);
+        }
+
+        assert_eq!(expected.display(PathFormat::BIP32).to_string(), "m/84'/0'/0'/0/1");
+        assert_eq!(expected.display(PathFormat::DESCRIPTOR).to_string(), "84h/0h/0h/0/1");
+        let master = DerivationPath::master();
+        assert_eq!(master.display(PathFormat::BIP32).to_string(), "m");
+        assert_eq!(master.display(PathFormat::DESCRIPTOR).to_string(), "");
+    }
+
+    #[test]
+    fn path_macro() {
+        const PATH: [ChildNumber; 5] = crate::path!("m/84h/0H/0'/0/1");
+        assert_eq!(DerivationPath::from(PATH), "84'/0'/0'/0/1".parse().unwrap());
+        assert_eq!(crate::path!("m"), []);
+        assert_eq!(crate::path!(""), []);
+        assert_eq!(crate::path!("2147483647h"), [Hardened { index: (1 << 31) - 1 }]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn path_macro_invalid() { parse_path::<2>("0/1x"); }
+
     #[test]
     fn test_lowerhex_formatting() {
         let normal = Normal { index: 42 };
@@ -1268,6 +1525,35 @@ fn derivation_path_conversion_index() {
         assert_eq!(indexed.child(ChildNumber::from_hardened_idx(2).unwrap()), path);
     }
 
+    #[test]
+    fn xpriv_non_secure_erase() {
+        let xpriv = Xpriv::new_master(NetworkKind::Main, &[0xab; 32]);
+        let mut erased = xpriv;
+        assert_eq!(erased, xpriv);
+        erased.non_secure_erase();
+        assert_ne!(erased, xpriv);
+        assert_eq!(erased.chain_code, ChainCode::from([0; 32]));
+        assert_ne!(erased.private_key, xpriv.private_key);
+        assert_eq!(erased.network, xpriv.network);
+
+        let mut other_chain_code = xpriv;
+        other_chain_code.chain_code.non_secure_erase();
+        assert_ne!(other_chain_code, xpriv);
+    }
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn xpriv_zeroize() {
+        use zeroize::Zeroize as _;
+
+        let xpriv = Xpriv::new_master(NetworkKind::Main, &[0xab; 32]);
+        let mut erased = xpriv;
+        erased.non_secure_erase();
+        let mut zeroized = xpriv;
+        zeroized.zeroize();
+        assert_eq!(zeroized, erased);
+    }
+
     fn test_path(
         network: NetworkKind,
         seed: &[u8],
@@ -1492,6 +1778,15 @@ pub fn encode_decode_childnumber() {
         serde_round_trip!(ChildNumber::from_hardened_idx((1 << 31) - 1).unwrap());
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    pub fn encode_derivation_path() {
+        let path = DerivationPath::from_str("m/84h/0H/0'/0/1").unwrap();
+        serde_round_trip!(path);
+        assert_eq!(serde_json::to_string(&path).unwrap(), "\"84'/0'/0'/0/1\"");
+        assert_eq!(serde_json::from_str::<DerivationPath>("\"m/84h/0h/0h/0/1\"").unwrap(), path);
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     pub fn encode_fingerprint_chaincode() {
@@ -0,0 +1,404 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Reading Bitcoin Core's block storage files.
+//!
+//! Bitcoin Core stores blocks in `blocks/blk*.dat` files and their undo data in `blocks/rev*.dat`
+//! files. Both consist of records framed by the network magic and the length of the record data
+//! as a little-endian `u32`. Records in `rev*.dat` files are additionally followed by a checksum,
+//! see [`BlockUndo::checksum`]. Since version 28 Core obfuscates these files by XORing them with a
+//! random 8-byte key stored in `blocks/xor.dat`.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::block_file::BlockFileReader;
+//! use bitcoin::consensus::serialize;
+//! use bitcoin::{constants, Network};
+//!
+//! // A file containing only the regtest genesis block.
+//! let genesis = constants::genesis_block(Network::Regtest);
+//! let mut file = vec![0xfa, 0xbf, 0xb5, 0xda, 0x1d, 0x01, 0x00, 0x00];
+//! file.extend(serialize(&genesis));
+//!
+//! let mut reader = BlockFileReader::new(&file[..], [0xfa, 0xbf, 0xb5, 0xda]);
+//! let blocks = reader.blocks().collect::<Result<Vec<_>, _>>().expect("valid block file");
+//! assert_eq!(blocks.len(), 1);
+//! assert_eq!(blocks[0].block_hash(), genesis.block_hash());
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use hashes::sha256d;
+use internals::write_err;
+use io::Read;
+
+use crate::block::{Block, BlockHash};
+use crate::coins::BlockUndo;
+use crate::consensus::encode::{deserialize, DeserializeError};
+use crate::constants::MAX_BLOCK_SERIALIZED_SIZE;
+use crate::prelude::Vec;
+
+/// The size of the chunks record data is read in, to avoid trusting the record length.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reads blocks or undo data from a Bitcoin Core `blk*.dat` or `rev*.dat` file.
+///
+/// The reader stops at the end of the file or at the zero bytes Core uses to pre-allocate space
+/// at the end of a file.
+#[derive(Debug)]
+pub struct BlockFileReader<R> {
+    reader: R,
+    magic: [u8; 4],
+    xor_key: [u8; 8],
+    position: u64,
+}
+
+impl<R: Read> BlockFileReader<R> {
+    /// Constructs a new reader for the file contents `reader` of a network with the given magic.
+    ///
+    /// The network magic of the p2p crate can be converted using its `to_bytes` method.
+    pub fn new(reader: R, magic: [u8; 4]) -> Self {
+        Self { reader, magic, xor_key: [0; 8], position: 0 }
+    }
+
+    /// Sets the key the file is obfuscated with, the contents of Core's `blocks/xor.dat`.
+    ///
+    /// An all zero key (the default) means the file is not obfuscated.
+    #[must_use]
+    pub fn with_xor_key(mut self, xor_key: [u8; 8]) -> Self {
+        self.xor_key = xor_key;
+        self
+    }
+
+    /// Returns the offset in the file of the next byte to be read.
+    pub fn position(&self) -> u64 { self.position }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R { self.reader }
+
+    /// Reads the next block.
+    ///
+    /// Returns `None` at the end of the file. The block is not validated.
+    ///
+    /// # Errors
+    ///
+    /// If reading fails or the record is not a valid block.
+    pub fn read_block(&mut self) -> Result<Option<Block>, BlockFileError> {
+        let Some(len) = self.read_header()? else { return Ok(None) };
+        if len > MAX_BLOCK_SERIALIZED_SIZE {
+            return Err(BlockFileError::RecordTooLarge { len });
+        }
+        let data = self.read_data(len)?;
+        deserialize(&data).map(Some).map_err(BlockFileError::Decode)
+    }
+
+    /// Reads the next undo record.
+    ///
+    /// Returns `None` at the end of the file. The checksum is not verified, use
+    /// [`UndoRecord::is_valid_for`] for that.
+    ///
+    /// # Errors
+    ///
+    /// If reading fails or the record is not valid undo data.
+    pub fn read_undo(&mut self) -> Result<Option<UndoRecord>, BlockFileError> {
+        let Some(len) = self.read_header()? else { return Ok(None) };
+        let data = self.read_data(len)?;
+        let undo = deserialize(&data).map_err(BlockFileError::Decode)?;
+        let mut checksum = [0; 32];
+        self.read_exact(&mut checksum)?;
+        Ok(Some(UndoRecord { undo, checksum: sha256d::Hash::from_byte_array(checksum) }))
+    }
+
+    /// Returns an iterator over the remaining blocks in the file.
+    ///
+    /// The iterator ends after the first error.
+    pub fn blocks(&mut self) -> Blocks<'_, R> { Blocks { reader: self, done: false } }
+
+    /// Returns an iterator over the remaining undo records in the file.
+    ///
+    /// The iterator ends after the first error.
+    pub fn undo_records(&mut self) -> UndoRecords<'_, R> {
+        UndoRecords { reader: self, done: false }
+    }
+
+    /// Reads the magic and length of the next record, returns `None` at the end of the file.
+    fn read_header(&mut self) -> Result<Option<usize>, BlockFileError> {
+        let mut magic = [0; 4];
+        let mut read = 0;
+        while read < magic.len() {
+            match self.reader.read(&mut magic[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.unobfuscate(&mut magic);
+        if magic == [0; 4] {
+            return Ok(None);
+        }
+        if magic != self.magic {
+            return Err(BlockFileError::UnexpectedMagic {
+                position: self.position - 4,
+                expected: self.magic,
+                actual: magic,
+            });
+        }
+
+        let mut len = [0; 4];
+        self.read_exact(&mut len)?;
+        Ok(Some(u32::from_le_bytes(len) as usize))
+    }
+
+    /// Reads `len` bytes in chunks, so that a corrupt length does not cause a huge allocation.
+    fn read_data(&mut self, len: usize) -> Result<Vec<u8>, BlockFileError> {
+        let mut data = Vec::new();
+        while data.len() < len {
+            let start = data.len();
+            data.resize(start + (len - start).min(CHUNK_SIZE), 0);
+            self.read_exact(&mut data[start..])?;
+        }
+        Ok(data)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), BlockFileError> {
+        self.reader.read_exact(buf)?;
+        self.unobfuscate(buf);
+        Ok(())
+    }
+
+    /// Removes the XOR obfuscation from `buf`, which was just read, and advances the position.
+    fn unobfuscate(&mut self, buf: &mut [u8]) {
+        for byte in buf {
+            *byte ^= self.xor_key[(self.position % 8) as usize];
+            self.position += 1;
+        }
+    }
+}
+
+/// An iterator over the blocks in a block file, returned by [`BlockFileReader::blocks`].
+#[derive(Debug)]
+pub struct Blocks<'a, R> {
+    reader: &'a mut BlockFileReader<R>,
+    done: bool,
+}
+
+impl<R: Read> Iterator for Blocks<'_, R> {
+    type Item = Result<Block, BlockFileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.reader.read_block().transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+/// An iterator over the records in an undo file, returned by [`BlockFileReader::undo_records`].
+#[derive(Debug)]
+pub struct UndoRecords<'a, R> {
+    reader: &'a mut BlockFileReader<R>,
+    done: bool,
+}
+
+impl<R: Read> Iterator for UndoRecords<'_, R> {
+    type Item = Result<UndoRecord, BlockFileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.reader.read_undo().transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+/// A record of a `rev*.dat` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoRecord {
+    /// The undo data of a block.
+    pub undo: BlockUndo,
+    /// The checksum stored after the undo data.
+    pub checksum: sha256d::Hash,
+}
+
+impl UndoRecord {
+    /// Returns `true` if the checksum is valid for a block whose parent is `prev_blockhash`.
+    ///
+    /// Undo records don't identify their block, the checksum ties them to the block's parent.
+    pub fn is_valid_for(&self, prev_blockhash: BlockHash) -> bool {
+        self.undo.checksum(prev_blockhash) == self.checksum
+    }
+}
+
+/// An error reading a block file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BlockFileError {
+    /// Reading the file failed.
+    Io(io::Error),
+    /// A record did not start with the network magic.
+    UnexpectedMagic {
+        /// The offset of the record in the file.
+        position: u64,
+        /// The magic of the network being read.
+        expected: [u8; 4],
+        /// The magic found in the file.
+        actual: [u8; 4],
+    },
+    /// A block record is larger than the maximum block size.
+    RecordTooLarge {
+        /// The length of the record.
+        len: usize,
+    },
+    /// A record could not be decoded.
+    Decode(DeserializeError),
+}
+
+impl From<io::Error> for BlockFileError {
+    fn from(e: io::Error) -> Self { Self::Io(e) }
+}
+
+impl From<Infallible> for BlockFileError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for BlockFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write_err!(f, "reading block file failed"; e),
+            Self::UnexpectedMagic { position, expected, actual } => write!(
+                f,
+                "unexpected magic {:02x?} at offset {}, expected {:02x?}",
+                actual, position, expected
+            ),
+            Self::RecordTooLarge { len } =>
+                write!(f, "block record of {} bytes exceeds the maximum block size", len),
+            Self::Decode(e) => write_err!(f, "decoding block file record failed"; e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlockFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Decode(e) => Some(e),
+            Self::UnexpectedMagic { .. } | Self::RecordTooLarge { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Checked;
+    use crate::coins::{Coin, TxUndo};
+    use crate::consensus::encode::serialize;
+    use crate::constants::genesis_block;
+    use crate::network::Network;
+    use crate::script::ScriptPubKeyBuf;
+    use crate::transaction::TxOut;
+    use crate::{Amount, BlockHeight};
+
+    const MAGIC: [u8; 4] = [0xfa, 0xbf, 0xb5, 0xda];
+
+    fn record(data: &[u8]) -> Vec<u8> {
+        let mut record = MAGIC.to_vec();
+        record.extend((data.len() as u32).to_le_bytes());
+        record.extend(data);
+        record
+    }
+
+    fn obfuscate(file: &mut [u8], key: [u8; 8]) {
+        for (i, byte) in file.iter_mut().enumerate() {
+            *byte ^= key[i % 8];
+        }
+    }
+
+    fn genesis() -> Block<Checked> { genesis_block(Network::Regtest) }
+
+    #[test]
+    fn read_blocks() {
+        let block = serialize(&genesis());
+        let mut file = record(&block);
+        file.extend(record(&block));
+        // Pre-allocated space at the end of the file.
+        file.extend([0; 100]);
+
+        let key = [1, 2, 3, 4, 5, 6, 7, 8];
+        obfuscate(&mut file, key);
+
+        let mut reader = BlockFileReader::new(&file[..], MAGIC).with_xor_key(key);
+        let blocks = reader.blocks().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.iter().all(|b| b.block_hash() == genesis().block_hash()));
+        assert_eq!(reader.position(), 2 * (8 + block.len() as u64) + 4);
+    }
+
+    #[test]
+    fn read_errors() {
+        let block = serialize(&genesis());
+
+        let file = record(&block);
+        let mut reader = BlockFileReader::new(&file[..], [0xf9, 0xbe, 0xb4, 0xd9]);
+        let mut blocks = reader.blocks();
+        assert!(matches!(
+            blocks.next(),
+            Some(Err(BlockFileError::UnexpectedMagic { position: 0, actual: MAGIC, .. }))
+        ));
+        assert!(blocks.next().is_none());
+
+        let mut reader = BlockFileReader::new(&file[..file.len() - 1], MAGIC);
+        assert!(matches!(reader.read_block(), Err(BlockFileError::Io(_))));
+
+        let mut file = record(&block);
+        file.push(0);
+        file[4] += 1;
+        let mut reader = BlockFileReader::new(&file[..], MAGIC);
+        assert!(matches!(reader.read_block(), Err(BlockFileError::Decode(_))));
+
+        let mut file = MAGIC.to_vec();
+        file.extend(u32::MAX.to_le_bytes());
+        let mut reader = BlockFileReader::new(&file[..], MAGIC);
+        assert!(matches!(reader.read_block(), Err(BlockFileError::RecordTooLarge { .. })));
+
+        let mut reader = BlockFileReader::new(&[0xfa, 0xbf][..], MAGIC);
+        assert!(matches!(reader.read_block(), Err(BlockFileError::Io(_))));
+    }
+
+    #[test]
+    fn read_undo_records() {
+        let prev_blockhash = genesis().block_hash();
+        let undo = BlockUndo {
+            txs: vec![TxUndo {
+                spent: vec![Coin {
+                    output: TxOut {
+                        amount: Amount::ONE_SAT,
+                        script_pubkey: ScriptPubKeyBuf::from_bytes(vec![0x51]),
+                    },
+                    height: BlockHeight::from_u32(7),
+                    is_coinbase: true,
+                }],
+            }],
+        };
+        let mut file = record(&serialize(&undo));
+        file.extend(undo.checksum(prev_blockhash).to_byte_array());
+        file.extend(record(&serialize(&BlockUndo::default())));
+        file.extend(BlockUndo::default().checksum(prev_blockhash).to_byte_array());
+
+        let mut reader = BlockFileReader::new(&file[..], MAGIC);
+        let records = reader.undo_records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].undo, undo);
+        assert!(records[0].is_valid_for(prev_blockhash));
+        assert!(!records[0].is_valid_for(BlockHash::from_byte_array([0; 32])));
+        assert!(records[1].undo.txs.is_empty());
+    }
+}
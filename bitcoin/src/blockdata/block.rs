@@ -14,19 +14,20 @@
 use internals::ToU64;
 use io::{BufRead, Write};
 
-use crate::consensus::encode::{self, Decodable, Encodable, WriteExt as _};
+use crate::consensus::encode::{self, Decodable, Encodable, ReadExt as _, WriteExt as _};
 use crate::merkle_tree::{TxMerkleNode, WitnessMerkleNode};
 use crate::network::Params;
 use crate::prelude::Vec;
-use crate::script::{self, ScriptExt as _, ScriptIntError};
-use crate::transaction::{Coinbase, Transaction, TransactionExt as _};
-use crate::{internal_macros, BlockTime, Target, Weight, Work};
+use crate::script::{self, ScriptExt as _, ScriptIntError, ScriptPubKeyBuf, ScriptSet};
+use crate::transaction::{self, Coinbase, OutPoint, Transaction, TransactionExt as _, TxOut, Txid};
+use crate::{internal_macros, Amount, BlockTime, Target, Weight, Witness, Work};
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
 pub use primitives::block::{
     Block, Checked, Unchecked, Validation, Version, BlockHash, Header,
-    WitnessCommitment, compute_merkle_root, compute_witness_root, InvalidBlockError, ParseHeaderError,
+    WitnessCommitment, compute_merkle_root, compute_witness_commitment, compute_witness_root,
+    InvalidBlockError, ParseHeaderError, WITNESS_COMMITMENT_HEADER,
 };
 #[doc(no_inline)]
 pub use units::block::TooBigForRelativeHeightError;
@@ -114,6 +115,59 @@ fn consensus_decode<R: BufRead + ?Sized>(r: &mut R) -> Result<Self, encode::Erro
     }
 }
 
+/// Extension functionality for the [`Block<Unchecked>`] type.
+pub trait BlockUncheckedExt: sealed::Sealed {
+    /// Decodes a block from `r` into `self`, reusing the allocations of `self`.
+    ///
+    /// The result is the same as assigning [`Decodable::consensus_decode`] but the capacity of
+    /// the transaction vector and of the input, output and witness buffers of its transactions is
+    /// kept. Decoding blocks in sequence into the same value, like during initial block download,
+    /// saves reallocating these vectors. The scriptSig and scriptPubkey of every input and output
+    /// are still allocated anew.
+    ///
+    /// # Errors
+    ///
+    /// If decoding fails, `self` is left in an unspecified but valid state.
+    fn consensus_decode_into<R: BufRead + ?Sized>(
+        &mut self,
+        r: &mut R,
+    ) -> Result<(), encode::Error>;
+}
+
+impl BlockUncheckedExt for Block<Unchecked> {
+    fn consensus_decode_into<R: BufRead + ?Sized>(
+        &mut self,
+        r: &mut R,
+    ) -> Result<(), encode::Error> {
+        let mut r = io::Read::take(r, encode::MAX_VEC_SIZE.to_u64());
+        let header = Decodable::consensus_decode_from_finite_reader(&mut r)?;
+        let (_, mut transactions) =
+            core::mem::replace(self, Self::new_unchecked(header, Vec::new())).into_parts();
+
+        let result = decode_transactions_into(&mut transactions, &mut r);
+        *self = Self::new_unchecked(header, transactions);
+        result
+    }
+}
+
+/// Decodes the transactions of a block into `transactions`, reusing its allocations.
+fn decode_transactions_into<R: BufRead + ?Sized>(
+    transactions: &mut Vec<Transaction>,
+    r: &mut R,
+) -> Result<(), encode::Error> {
+    let mut scratch = Vec::new();
+    let len = r.read_compact_size()?;
+    for i in 0..len {
+        // Cast ok, the transactions before `i` are in the vector.
+        match transactions.get_mut(i as usize) {
+            Some(tx) => transaction::decode_into_from_finite_reader(tx, r, &mut scratch)?,
+            None => transactions.push(Decodable::consensus_decode_from_finite_reader(r)?),
+        }
+    }
+    transactions.truncate(len as usize);
+    Ok(())
+}
+
 /// Extension functionality for the [`Block<Checked>`] type.
 pub trait BlockCheckedExt: sealed::Sealed {
     /// Constructs a new [`Block`].
@@ -157,6 +211,11 @@ fn new_checked(
 
     /// Returns the block height, as encoded in the coinbase transaction according to BIP-0034.
     fn bip34_block_height(&self) -> Result<u64, Bip34Error>;
+
+    /// Returns the outpoints of all outputs that pay to a script in `scripts`, in block order.
+    ///
+    /// Only the TXIDs of transactions with a matching output are computed.
+    fn scan(&self, scripts: &ScriptSet) -> Vec<OutPoint>;
 }
 
 impl BlockCheckedExt for Block<Checked> {
@@ -222,6 +281,20 @@ fn bip34_block_height(&self) -> Result<u64, Bip34Error> {
             (None, _) => Err(Bip34Error::NotPresent),
         }
     }
+
+    fn scan(&self, scripts: &ScriptSet) -> Vec<OutPoint> {
+        let mut out_points = Vec::new();
+        for tx in self.transactions().iter().filter(|tx| tx.pays_to_any(scripts)) {
+            let txid = tx.compute_txid();
+            for (vout, output) in tx.outputs.iter().enumerate() {
+                if scripts.contains(&output.script_pubkey) {
+                    let vout = u32::try_from(vout).expect("less than 2^32 outputs");
+                    out_points.push(OutPoint { txid, vout });
+                }
+            }
+        }
+        out_points
+    }
 }
 
 fn block_base_size(transactions: &[Transaction]) -> usize {
@@ -284,6 +357,119 @@ fn consensus_decode<R: io::BufRead + ?Sized>(r: &mut R) -> Result<Self, encode::
     }
 }
 
+/// The witness reserved value used by Bitcoin Core, 32 zero bytes.
+pub const DEFAULT_WITNESS_RESERVED_VALUE: [u8; 32] = [0; 32];
+
+/// Returns the coinbase input witness holding `witness_reserved_value`.
+pub fn coinbase_witness(witness_reserved_value: [u8; 32]) -> Witness {
+    Witness::from_slice(&[witness_reserved_value])
+}
+
+/// Returns the script of the coinbase output committing to `commitment`.
+pub fn witness_commitment_script(commitment: WitnessCommitment) -> ScriptPubKeyBuf {
+    let mut bytes = Vec::with_capacity(38);
+    bytes.extend_from_slice(&WITNESS_COMMITMENT_HEADER);
+    bytes.extend_from_slice(commitment.as_byte_array());
+    ScriptPubKeyBuf::from_bytes(bytes)
+}
+
+/// Returns the index of the witness commitment output of `coinbase`.
+///
+/// Like consensus this is the last output with a script starting with the commitment header.
+pub fn witness_commitment_index(coinbase: &Transaction) -> Option<usize> {
+    coinbase.outputs.iter().rposition(|output| {
+        let script = output.script_pubkey.as_bytes();
+        script.len() >= 38 && script[..6] == WITNESS_COMMITMENT_HEADER
+    })
+}
+
+/// Computes the witness commitment of `transactions` and writes it to the coinbase.
+///
+/// The coinbase is the first transaction. The witness reserved value is taken from the coinbase
+/// input, which is set to [`DEFAULT_WITNESS_RESERVED_VALUE`] if its witness is not a single 32
+/// byte element. The script of the existing commitment output is replaced, if there is none a zero
+/// value output is appended.
+///
+/// Call this after adding or removing transactions. The commitment does not depend on the
+/// coinbase itself, the coinbase can change afterwards without invalidating it.
+///
+/// Returns `None`, changing nothing, if `transactions` is empty or does not start with a
+/// coinbase.
+pub fn update_witness_commitment(transactions: &mut [Transaction]) -> Option<WitnessCommitment> {
+    if !transactions.first()?.is_coinbase() {
+        return None;
+    }
+    let witness_root = compute_witness_root(transactions)?;
+
+    let coinbase = &mut transactions[0];
+    let witness = &mut coinbase.inputs[0].witness;
+    let witness_reserved_value = match witness.get(0) {
+        Some(value) if witness.len() == 1 && value.len() == 32 =>
+            <[u8; 32]>::try_from(value).expect("checked length"),
+        _ => {
+            *witness = coinbase_witness(DEFAULT_WITNESS_RESERVED_VALUE);
+            DEFAULT_WITNESS_RESERVED_VALUE
+        }
+    };
+
+    let commitment = compute_witness_commitment(witness_root, &witness_reserved_value);
+
+    let script_pubkey = witness_commitment_script(commitment);
+    match witness_commitment_index(coinbase) {
+        Some(index) => coinbase.outputs[index].script_pubkey = script_pubkey,
+        None => coinbase.outputs.push(TxOut { amount: Amount::ZERO, script_pubkey }),
+    }
+    Some(commitment)
+}
+
+/// The Merkle branch of the coinbase transaction of a block.
+///
+/// The branch only depends on the transactions following the coinbase. When only the coinbase
+/// changes, like when rolling the extranonce, the Merkle root is recomputed from the branch with
+/// one hash per level of the tree instead of hashing the whole tree again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinbaseMerkleBranch(Vec<TxMerkleNode>);
+
+impl CoinbaseMerkleBranch {
+    /// Computes the branch of the coinbase of `transactions`, the coinbase itself is not used.
+    pub fn new(transactions: &[Transaction]) -> Self {
+        Self::from_txids(transactions.iter().skip(1).map(Transaction::compute_txid))
+    }
+
+    /// Computes the branch of a coinbase followed by the transactions with `txids`.
+    pub fn from_txids<I: IntoIterator<Item = Txid>>(txids: I) -> Self {
+        // The nodes of the current level except the first, which depends on the coinbase.
+        let mut level = txids.into_iter().map(TxMerkleNode::from_leaf).collect::<Vec<_>>();
+        let mut branch = Vec::new();
+        while let Some(&sibling) = level.first() {
+            branch.push(sibling);
+            // Pair up the nodes following the sibling, duplicating the last one of an odd level.
+            level = level[1..]
+                .chunks(2)
+                .map(|pair| pair[0].combine(pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+        }
+        Self(branch)
+    }
+
+    /// Returns the Merkle root of the block with the coinbase with `coinbase_txid`.
+    pub fn merkle_root(&self, coinbase_txid: Txid) -> TxMerkleNode {
+        self.0
+            .iter()
+            .fold(TxMerkleNode::from_leaf(coinbase_txid), |node, sibling| node.combine(sibling))
+    }
+
+    /// Returns the hashes of the branch, from the leaves towards the root.
+    pub fn as_slice(&self) -> &[TxMerkleNode] { &self.0 }
+
+    /// Returns the hashes of the branch, from the leaves towards the root.
+    pub fn into_vec(self) -> Vec<TxMerkleNode> { self.0 }
+}
+
+impl From<CoinbaseMerkleBranch> for Vec<TxMerkleNode> {
+    fn from(branch: CoinbaseMerkleBranch) -> Self { branch.0 }
+}
+
 mod sealed {
     /// Seals the extension traits.
     pub trait Sealed {}
@@ -409,6 +595,32 @@ fn static_vector() {
         assert_eq!(from_iter, Some(block.header().merkle_root));
     }
 
+    #[test]
+    fn scan_for_scripts() {
+        let segwit_block = include_bytes!("../../tests/data/testnet_block_000000000000045e0b1660b6445b5e5c5ab63c9a4f956be7e1e69be04fa4497b.raw");
+        let block: Block = deserialize(&segwit_block[..]).expect("failed to deserialize block");
+        let block = block.assume_checked(None);
+        let txs = block.transactions();
+
+        let watched =
+            [&txs[0].outputs[0].script_pubkey, &txs[txs.len() - 1].outputs[0].script_pubkey];
+        let scripts = watched.iter().map(|&s| s.clone()).collect::<ScriptSet>();
+        assert!(txs[0].pays_to_any(&scripts));
+        assert!(!txs[0].pays_to_any(&ScriptSet::new()));
+
+        let mut expected = Vec::new();
+        for tx in txs {
+            for (vout, output) in tx.outputs.iter().enumerate() {
+                if watched.contains(&&output.script_pubkey) {
+                    expected.push(OutPoint { txid: tx.compute_txid(), vout: vout as u32 });
+                }
+            }
+        }
+        assert!(expected.len() >= 2);
+        assert_eq!(block.scan(&scripts), expected);
+        assert!(block.scan(&ScriptSet::new()).is_empty());
+    }
+
     #[test]
     fn coinbase_and_bip34() {
         // testnet block 100,000
@@ -510,6 +722,19 @@ fn block() {
         assert_eq!(serialize(&real_decode), some_block);
     }
 
+    #[test]
+    fn consensus_decode_into() {
+        let segwit_block = include_bytes!("../../tests/data/testnet_block_000000000000045e0b1660b6445b5e5c5ab63c9a4f956be7e1e69be04fa4497b.raw");
+        let some_block = hex!("010000004ddccd549d28f385ab457e98d1b11ce80bfea2c5ab93015ade4973e400000000bf4473e53794beae34e64fccc471dace6ae544180816f89591894e0f417a914cd74d6e49ffff001d323b3a7b0201000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0804ffff001d026e04ffffffff0100f2052a0100000043410446ef0102d1ec5240f0d061a4246c1bdef63fc3dbab7733052fbbf0ecd8f41fc26bf049ebb4f9527f374280259e7cfa99c48b0e3f39c51347a19a5819651503a5ac00000000010000000321f75f3139a013f50f315b23b0c9a2b6eac31e2bec98e5891c924664889942260000000049483045022100cb2c6b346a978ab8c61b18b5e9397755cbd17d6eb2fe0083ef32e067fa6c785a02206ce44e613f31d9a6b0517e46f3db1576e9812cc98d159bfdaf759a5014081b5c01ffffffff79cda0945903627c3da1f85fc95d0b8ee3e76ae0cfdc9a65d09744b1f8fc85430000000049483045022047957cdd957cfd0becd642f6b84d82f49b6cb4c51a91f49246908af7c3cfdf4a022100e96b46621f1bffcf5ea5982f88cef651e9354f5791602369bf5a82a6cd61a62501fffffffffe09f5fe3ffbf5ee97a54eb5e5069e9da6b4856ee86fc52938c2f979b0f38e82000000004847304402204165be9a4cbab8049e1af9723b96199bfd3e85f44c6b4c0177e3962686b26073022028f638da23fc003760861ad481ead4099312c60030d4cb57820ce4d33812a5ce01ffffffff01009d966b01000000434104ea1feff861b51fe3f5f8a3b12d0f4712db80e919548a80839fc47c6a21e66d957e9c5d8cd108c7a2d2324bad71f9904ac0ae7336507d785b17a2c115e427a32fac00000000");
+
+        let mut block = deserialize::<Block>(&some_block).unwrap();
+        for bytes in [&segwit_block[..], &some_block, &segwit_block[..]] {
+            block.consensus_decode_into(&mut &bytes[..]).unwrap();
+            assert_eq!(block, deserialize::<Block>(bytes).unwrap());
+        }
+        assert!(block.consensus_decode_into(&mut &some_block[..some_block.len() - 1]).is_err());
+    }
+
     // Check testnet block 000000000000045e0b1660b6445b5e5c5ab63c9a4f956be7e1e69be04fa4497b
     #[test]
     fn segwit_block() {
@@ -557,6 +782,72 @@ fn segwit_block() {
         assert_eq!(serialize(&real_decode), segwit_block);
     }
 
+    #[test]
+    fn update_witness_commitment_segwit_block() {
+        let segwit_block = include_bytes!("../../tests/data/testnet_block_000000000000045e0b1660b6445b5e5c5ab63c9a4f956be7e1e69be04fa4497b.raw");
+        let block: Block = deserialize(segwit_block).unwrap();
+        let (header, mut transactions) = block.into_parts();
+        let index = witness_commitment_index(&transactions[0]).unwrap();
+        let original = transactions[0].clone();
+
+        // Patching a valid commitment leaves it unchanged.
+        let commitment = update_witness_commitment(&mut transactions).unwrap();
+        assert_eq!(transactions[0], original);
+        assert_eq!(
+            transactions[0].outputs[index].script_pubkey,
+            witness_commitment_script(commitment)
+        );
+
+        // A new commitment output is appended to a coinbase without one.
+        transactions[0].outputs.remove(index);
+        transactions[0].inputs[0].witness.clear();
+        assert_eq!(witness_commitment_index(&transactions[0]), None);
+        assert_eq!(update_witness_commitment(&mut transactions), Some(commitment));
+        assert_eq!(
+            transactions[0].inputs[0].witness,
+            coinbase_witness(DEFAULT_WITNESS_RESERVED_VALUE)
+        );
+        let last = transactions[0].outputs.len() - 1;
+        assert_eq!(witness_commitment_index(&transactions[0]), Some(last));
+        assert_eq!(transactions[0].outputs[last].amount, Amount::ZERO);
+
+        // Removing a transaction changes the commitment.
+        transactions.pop();
+        let updated = update_witness_commitment(&mut transactions).unwrap();
+        assert_ne!(updated, commitment);
+        let merkle_root = block::compute_merkle_root(&transactions).unwrap();
+        let block = Block::new_unchecked(Header { merkle_root, ..header }, transactions);
+        assert!(block.check_witness_commitment().0);
+
+        let (_, mut transactions) = block.into_parts();
+        assert_eq!(update_witness_commitment(&mut transactions[1..]), None);
+        assert_eq!(update_witness_commitment(&mut []), None);
+    }
+
+    #[test]
+    fn coinbase_merkle_branch() {
+        let coinbase_txid = Txid::from_byte_array([0xff; 32]);
+        for len in 0..=9_u8 {
+            let txids = (0..len).map(|i| Txid::from_byte_array([i; 32])).collect::<Vec<_>>();
+            let branch = CoinbaseMerkleBranch::from_txids(txids.iter().copied());
+            let all = core::iter::once(coinbase_txid).chain(txids.iter().copied());
+            assert_eq!(Some(branch.merkle_root(coinbase_txid)), TxMerkleNode::calculate_root(all));
+        }
+
+        let branch =
+            CoinbaseMerkleBranch::from_txids((0..5).map(|i| Txid::from_byte_array([i; 32])));
+        assert_eq!(branch.as_slice().len(), 3);
+        assert_eq!(branch.as_slice()[0], TxMerkleNode::from_leaf(Txid::from_byte_array([0; 32])));
+        assert!(CoinbaseMerkleBranch::from_txids([]).into_vec().is_empty());
+
+        let segwit_block = include_bytes!("../../tests/data/testnet_block_000000000000045e0b1660b6445b5e5c5ab63c9a4f956be7e1e69be04fa4497b.raw");
+        let block: Block = deserialize(segwit_block).unwrap();
+        let (header, transactions) = block.into_parts();
+        let branch = CoinbaseMerkleBranch::new(&transactions);
+        let coinbase_txid = transactions[0].compute_txid();
+        assert_eq!(branch.merkle_root(coinbase_txid), header.merkle_root);
+    }
+
     #[test]
     fn block_version() {
         let block = hex!("ffffff7f0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000");
@@ -41,6 +41,8 @@
 pub const MAX_WITNESS_SCRIPT_SIZE: usize = primitives::script::MAX_WITNESS_SCRIPT_SIZE; // 10_000
 /// The maximum allowed size of any single witness stack element.
 pub const MAX_STACK_ELEMENT_SIZE: usize = 520;
+/// The maximum allowed size of a legacy or SegWit v0 script, larger scripts are unspendable.
+pub(crate) const MAX_SCRIPT_SIZE: usize = 10_000;
 /// How many blocks between halvings.
 pub const SUBSIDY_HALVING_INTERVAL: u32 = 210_000;
 /// Maximum allowed value for an integer in Script.
@@ -118,6 +120,13 @@ fn bitcoin_genesis_tx(params: &Params) -> Transaction {
     ret
 }
 
+/// Returns the hash of the genesis block of the network with `params`.
+///
+/// This is the same as `genesis_block(params).block_hash()` without constructing the block.
+pub fn genesis_block_hash(params: impl AsRef<Params>) -> BlockHash {
+    BlockHash::from_byte_array(ChainHash::using_genesis_block(params).0)
+}
+
 /// Constructs and returns the genesis block.
 pub fn genesis_block(params: impl AsRef<Params>) -> Block<Checked> {
     let params = params.as_ref();
@@ -345,6 +354,10 @@ fn bitcoin_genesis_full_block() {
             gen.header().block_hash().to_string(),
             "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
         );
+        assert_eq!(
+            gen.header().block_hash(),
+            crate::block_hash!("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+        );
     }
 
     #[test]
@@ -430,6 +443,19 @@ fn $test_name() {
         regtest_chain_hash_genesis_block, Network::Regtest;
     }
 
+    #[test]
+    fn genesis_block_hash_matches_genesis_block() {
+        for network in [
+            Network::Bitcoin,
+            Network::Testnet(TestnetVersion::V3),
+            Network::Testnet(TestnetVersion::V4),
+            Network::Signet,
+            Network::Regtest,
+        ] {
+            assert_eq!(genesis_block_hash(network), genesis_block(network).block_hash());
+        }
+    }
+
     // Test vector taken from: https://github.com/lightning/bolts/blob/master/00-introduction.md
     #[test]
     fn mainnet_chain_hash_test_vector() {
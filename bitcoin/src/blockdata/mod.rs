@@ -25,6 +25,7 @@ pub mod fee_rate {
     pub use units::fee_rate::serde;
     /// Re-export everything from the [`units::fee_rate`] module.
     pub use units::fee_rate::FeeRate;
+    pub use units::fee_rate::{Display, ParseFeeRateError, Unit};
 }
 
 /// Provides absolute and relative locktimes.
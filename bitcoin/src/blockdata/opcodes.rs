@@ -425,26 +425,10 @@ pub fn classify(self, ctx: ClassifyContext) -> Class {
             (OP_VERIF, _) | (OP_VERNOTIF, _) | (OP_INVALIDOPCODE, _) => Class::IllegalOp,
 
             // 15 opcodes illegal in Legacy context
-            #[rustfmt::skip]
-            (OP_CAT, ctx) | (OP_SUBSTR, ctx)
-            | (OP_LEFT, ctx) | (OP_RIGHT, ctx)
-            | (OP_INVERT, ctx)
-            | (OP_AND, ctx) | (OP_OR, ctx) | (OP_XOR, ctx)
-            | (OP_2MUL, ctx) | (OP_2DIV, ctx)
-            | (OP_MUL, ctx) | (OP_DIV, ctx) | (OP_MOD, ctx)
-            | (OP_LSHIFT, ctx) | (OP_RSHIFT, ctx) if ctx == ClassifyContext::Legacy => Class::IllegalOp,
+            (op, ClassifyContext::Legacy) if op.is_disabled() => Class::IllegalOp,
 
             // 87 opcodes of SuccessOp class only in TapScript context
-            (op, ClassifyContext::TapScript)
-                if op.code == 80
-                    || op.code == 98
-                    || (op.code >= 126 && op.code <= 129)
-                    || (op.code >= 131 && op.code <= 134)
-                    || (op.code >= 137 && op.code <= 138)
-                    || (op.code >= 141 && op.code <= 142)
-                    || (op.code >= 149 && op.code <= 153)
-                    || (op.code >= 187 && op.code <= 254) =>
-                Class::SuccessOp,
+            (op, ClassifyContext::TapScript) if op.is_success() => Class::SuccessOp,
 
             // 11 opcodes of NoOp class
             (OP_NOP, _) => Class::NoOp,
@@ -501,6 +485,89 @@ pub const fn decode_pushnum(self) -> Option<u8> {
             _ => None,
         }
     }
+
+    /// Returns `true` if the opcode pushes data or a number onto the stack.
+    ///
+    /// These are `OP_PUSHBYTES_0` to `OP_PUSHBYTES_75`, `OP_PUSHDATA1` to `OP_PUSHDATA4`,
+    /// `OP_1NEGATE` and `OP_1` to `OP_16`. Unlike Bitcoin Core's `IsPushOnly` this does not
+    /// include `OP_RESERVED`, see [`Opcode`].
+    #[inline]
+    pub const fn is_push(self) -> bool {
+        self.code <= OP_PUSHDATA4.code
+            || self.code == OP_1NEGATE.code
+            || self.decode_pushnum().is_some()
+    }
+
+    /// Returns `true` if the opcode is one of the 15 opcodes disabled in 2010.
+    ///
+    /// Disabled opcodes fail a legacy or SegWit v0 script even if they are not executed. In
+    /// tapscript they are `OP_SUCCESSx` opcodes, see [`is_success`](Self::is_success).
+    #[inline]
+    pub const fn is_disabled(self) -> bool {
+        matches!(
+            self,
+            OP_CAT
+                | OP_SUBSTR
+                | OP_LEFT
+                | OP_RIGHT
+                | OP_INVERT
+                | OP_AND
+                | OP_OR
+                | OP_XOR
+                | OP_2MUL
+                | OP_2DIV
+                | OP_MUL
+                | OP_DIV
+                | OP_MOD
+                | OP_LSHIFT
+                | OP_RSHIFT
+        )
+    }
+
+    /// Returns `true` if the opcode is `OP_IF`, `OP_NOTIF`, `OP_ELSE` or `OP_ENDIF`.
+    ///
+    /// Conditional opcodes are evaluated even inside a branch that is not executed, to keep
+    /// track of the nesting. `OP_VERIF` and `OP_VERNOTIF` are not conditionals, they are illegal
+    /// in every context.
+    #[inline]
+    pub const fn is_conditional(self) -> bool {
+        matches!(self, OP_IF | OP_NOTIF | OP_ELSE | OP_ENDIF)
+    }
+
+    /// Returns `true` if the opcode is an `OP_SUCCESSx` opcode in tapscript.
+    ///
+    /// BIP-0342 reserves these for future soft forks, a tapscript containing one succeeds
+    /// without being executed. In legacy and SegWit v0 scripts they have their original meaning.
+    #[inline]
+    pub const fn is_success(self) -> bool {
+        matches!(self.code, 80 | 98 | 126..=129 | 131..=134 | 137..=138 | 141..=142 | 149..=153 | 187..=254)
+    }
+
+    /// Returns the opcode that pushes `data` with the smallest encoding.
+    ///
+    /// The empty slice, single bytes from 1 to 16 and the single byte `0x81` are pushed with
+    /// `OP_0`, `OP_1` to `OP_16` and `OP_1NEGATE`, longer data with the shortest push opcode.
+    /// Data longer than `u32::MAX` bytes can not be pushed, `OP_PUSHDATA4` is returned.
+    pub fn minimal_push(data: &[u8]) -> Self {
+        match *data {
+            [] => OP_PUSHBYTES_0,
+            [n @ 1..=16] => Self { code: OP_1.code + n - 1 },
+            [0x81] => OP_1NEGATE,
+            // Cast ok, the length is at most 75.
+            _ if data.len() <= usize::from(OP_PUSHBYTES_75.code) => Self { code: data.len() as u8 },
+            _ if data.len() <= 0xff => OP_PUSHDATA1,
+            _ if data.len() <= 0xffff => OP_PUSHDATA2,
+            _ => OP_PUSHDATA4,
+        }
+    }
+
+    /// Returns `true` if this opcode is the minimal way to push `data`.
+    ///
+    /// This implements Bitcoin Core's `CheckMinimalPush`, required by the `MINIMALDATA` policy
+    /// rule. Opcodes that are not pushes return `false`.
+    pub fn is_minimal_push(self, data: &[u8]) -> bool {
+        self.is_push() && self == Self::minimal_push(data)
+    }
 }
 
 impl From<u8> for Opcode {
@@ -706,6 +773,49 @@ fn classify_test() {
         assert_eq!(op187.classify(ClassifyContext::TapScript), Class::SuccessOp);
     }
 
+    #[test]
+    fn metadata() {
+        let all = (0..=u8::MAX).map(Opcode::from);
+        assert_eq!(all.clone().filter(|op| op.is_push()).count(), 76 + 3 + 1 + 16);
+        assert_eq!(all.clone().filter(|op| op.is_disabled()).count(), 15);
+        assert_eq!(all.clone().filter(|op| op.is_success()).count(), 87);
+        assert_eq!(all.clone().filter(|op| op.is_conditional()).count(), 4);
+        for op in all {
+            assert_eq!(
+                op.is_success(),
+                op.classify(ClassifyContext::TapScript) == Class::SuccessOp,
+                "{}",
+                op
+            );
+            // Disabled opcodes are `OP_SUCCESSx` in tapscript.
+            assert!(!op.is_disabled() || op.is_success());
+        }
+        assert!(!OP_RESERVED.is_push());
+        assert!(OP_PUSHDATA4.is_push());
+        assert!(!OP_VERIF.is_conditional());
+        assert!(!OP_CHECKSIGADD.is_success());
+    }
+
+    #[test]
+    fn minimal_push() {
+        assert_eq!(Opcode::minimal_push(&[]), OP_PUSHBYTES_0);
+        assert_eq!(Opcode::minimal_push(&[0]), OP_PUSHBYTES_1);
+        assert_eq!(Opcode::minimal_push(&[1]), OP_1);
+        assert_eq!(Opcode::minimal_push(&[16]), OP_16);
+        assert_eq!(Opcode::minimal_push(&[17]), OP_PUSHBYTES_1);
+        assert_eq!(Opcode::minimal_push(&[0x81]), OP_1NEGATE);
+        assert_eq!(Opcode::minimal_push(&[0; 75]), OP_PUSHBYTES_75);
+        assert_eq!(Opcode::minimal_push(&[0; 76]), OP_PUSHDATA1);
+        assert_eq!(Opcode::minimal_push(&[0; 0x100]), OP_PUSHDATA2);
+        assert_eq!(Opcode::minimal_push(&[0; 0x10000]), OP_PUSHDATA4);
+
+        assert!(OP_PUSHBYTES_2.is_minimal_push(&[1, 2]));
+        assert!(!OP_PUSHDATA1.is_minimal_push(&[1, 2]));
+        assert!(!OP_PUSHBYTES_1.is_minimal_push(&[5]));
+        assert!(!OP_PUSHBYTES_1.is_minimal_push(&[0x81]));
+        assert!(!OP_CHECKSIG.is_minimal_push(&[0xac; 0x100]));
+    }
+
     #[test]
     fn str_roundtrip() {
         let mut unique = HashSet::new();
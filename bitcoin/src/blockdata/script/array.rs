@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! A script stored in a fixed-capacity buffer.
+//!
+//! This module contains the [`ScriptArray`] struct, which supports the pushes of [`Builder`] but
+//! stores the script inline in a buffer of `N` bytes instead of on the heap. Firmware that must not
+//! allocate (e.g. hardware signers) can so construct output scripts on the stack.
+//!
+//! [`Builder`]: super::Builder
+
+use core::convert::Infallible;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+use internals::array_vec::ArrayVec;
+
+use super::witness_version::WitnessVersion;
+use super::{opcode_to_verify, write_scriptint, PushBytes, Script, ScriptPubKeyTag, WScriptHash};
+use crate::key::{
+    PublicKey, TapTweak as _, TweakedPublicKey, UntweakedPublicKey, WPubkeyHash, XOnlyPublicKey,
+};
+use crate::opcodes::all::*;
+use crate::opcodes::Opcode;
+use crate::taproot::TapNodeHash;
+
+/// A script stored in a fixed-capacity buffer of `N` bytes.
+///
+/// The push methods produce the same bytes as those of [`Builder`](super::Builder) but return an
+/// error if the script would no longer fit into the buffer. The tag defaults to
+/// [`ScriptPubKeyTag`], a P2WPKH output script for example fits into a `ScriptArray<22>` and a
+/// P2WSH or P2TR output script into a `ScriptArray<34>`.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin::script::ScriptArray;
+/// use bitcoin::opcodes::all::*;
+///
+/// let script = ScriptArray::<3>::new()
+///     .push_int_unchecked(1)?
+///     .push_opcode(OP_CHECKSIG)?
+///     .push_verify()?;
+/// assert_eq!(script.as_bytes(), [0x51, OP_CHECKSIGVERIFY.to_u8()]);
+/// assert!(script.push_slice([0xab; 2]).is_err());
+/// # Ok::<_, bitcoin::script::CapacityError>(())
+/// ```
+pub struct ScriptArray<const N: usize, T = ScriptPubKeyTag> {
+    bytes: ArrayVec<u8, N>,
+    /// The last opcode pushed, for [`push_verify`](Self::push_verify).
+    last_opcode: Option<Opcode>,
+    tag: PhantomData<T>,
+}
+
+impl<const N: usize, T> ScriptArray<N, T> {
+    /// Constructs a new empty script.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { bytes: ArrayVec::new(), last_opcode: None, tag: PhantomData }
+    }
+
+    /// Returns the capacity of the buffer in bytes.
+    #[inline]
+    pub const fn capacity(&self) -> usize { N }
+
+    /// Returns the length in bytes of the script.
+    #[inline]
+    pub fn len(&self) -> usize { self.bytes.as_slice().len() }
+
+    /// Checks whether the script is the empty script.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Adds instructions to push an unchecked integer onto the stack.
+    ///
+    /// Integers are encoded the same way as by [`Builder::push_int_unchecked`], which does not
+    /// check whether `n` is in the range of [-2^31 +1...2^31 -1].
+    ///
+    /// [`Builder::push_int_unchecked`]: super::Builder::push_int_unchecked
+    ///
+    /// # Errors
+    ///
+    /// If the script would no longer fit into the buffer.
+    pub fn push_int_unchecked(self, n: i64) -> Result<Self, CapacityError> {
+        let mut this = match n {
+            -1 => self.push_opcode(OP_1NEGATE)?,
+            0 => self.push_opcode(OP_PUSHBYTES_0)?,
+            // Cast ok, `n` is in the range of a `u8`.
+            1..=16 => self.push_opcode(Opcode::from(n as u8 + (OP_1.to_u8() - 1)))?,
+            _ => {
+                let mut buf = [0u8; 8];
+                let len = write_scriptint(&mut buf, n);
+                self.push_data(&buf[..len])?
+            }
+        };
+        this.last_opcode = None;
+        Ok(this)
+    }
+
+    /// Adds instructions to push some arbitrary data onto the stack.
+    ///
+    /// Like [`Builder::push_slice`], data that can be produced by a numeric opcode is pushed with
+    /// that opcode.
+    ///
+    /// [`Builder::push_slice`]: super::Builder::push_slice
+    ///
+    /// # Errors
+    ///
+    /// If the script would no longer fit into the buffer.
+    pub fn push_slice<D: AsRef<PushBytes>>(self, data: D) -> Result<Self, CapacityError> {
+        let data = data.as_ref().as_bytes();
+        let opcode = Opcode::minimal_push(data);
+        if opcode == OP_1NEGATE || opcode.decode_pushnum().is_some() {
+            let mut this = self.push_opcode(opcode)?;
+            this.last_opcode = None;
+            Ok(this)
+        } else {
+            self.push_data(data)
+        }
+    }
+
+    /// Adds instructions to push some arbitrary data onto the stack without minimality.
+    ///
+    /// # Errors
+    ///
+    /// If the script would no longer fit into the buffer.
+    pub fn push_slice_non_minimal<D: AsRef<PushBytes>>(
+        self,
+        data: D,
+    ) -> Result<Self, CapacityError> {
+        self.push_data(data.as_ref().as_bytes())
+    }
+
+    /// Adds a single opcode to the script.
+    ///
+    /// # Errors
+    ///
+    /// If the script would no longer fit into the buffer.
+    pub fn push_opcode(mut self, data: Opcode) -> Result<Self, CapacityError> {
+        self.extend(&[&[data.to_u8()]])?;
+        self.last_opcode = Some(data);
+        Ok(self)
+    }
+
+    /// Adds an `OP_VERIFY` to the script or replaces the last opcode with VERIFY form.
+    ///
+    /// See [`Builder::push_verify`](super::Builder::push_verify).
+    ///
+    /// # Errors
+    ///
+    /// If `OP_VERIFY` is added and the script would no longer fit into the buffer.
+    pub fn push_verify(mut self) -> Result<Self, CapacityError> {
+        match opcode_to_verify(self.last_opcode) {
+            Some(opcode) => {
+                self.bytes.pop();
+                self.push_opcode(opcode)
+            }
+            None => self.push_opcode(OP_VERIFY),
+        }
+    }
+
+    /// Adds instructions to push a public key onto the stack.
+    ///
+    /// # Errors
+    ///
+    /// If the script would no longer fit into the buffer.
+    pub fn push_key(self, key: PublicKey) -> Result<Self, CapacityError> {
+        if key.compressed {
+            self.push_slice(key.inner.serialize())
+        } else {
+            self.push_slice(key.inner.serialize_uncompressed())
+        }
+    }
+
+    /// Adds instructions to push an XOnly public key onto the stack.
+    ///
+    /// # Errors
+    ///
+    /// If the script would no longer fit into the buffer.
+    pub fn push_x_only_key(self, x_only_key: XOnlyPublicKey) -> Result<Self, CapacityError> {
+        self.push_slice(x_only_key.serialize())
+    }
+
+    /// Returns the script.
+    #[inline]
+    pub fn as_script(&self) -> &Script<T> { Script::from_bytes(self.bytes.as_slice()) }
+
+    /// Returns the script bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] { self.bytes.as_slice() }
+
+    /// Pushes `data` with a push opcode, even if a numeric opcode would do.
+    fn push_data(mut self, data: &[u8]) -> Result<Self, CapacityError> {
+        let len = data.len();
+        let mut prefix = [0u8; 5];
+        // Casts ok, each is checked against the range of the target type and `PushBytes` is
+        // shorter than 2^32 bytes.
+        let prefix = match len {
+            n if n < usize::from(OP_PUSHDATA1.to_u8()) => {
+                prefix[0] = n as u8;
+                &prefix[..1]
+            }
+            n if n < 0x100 => {
+                prefix[..2].copy_from_slice(&[OP_PUSHDATA1.to_u8(), n as u8]);
+                &prefix[..2]
+            }
+            n if n < 0x10000 => {
+                prefix[0] = OP_PUSHDATA2.to_u8();
+                prefix[1..3].copy_from_slice(&(n as u16).to_le_bytes());
+                &prefix[..3]
+            }
+            n => {
+                prefix[0] = OP_PUSHDATA4.to_u8();
+                prefix[1..].copy_from_slice(&(n as u32).to_le_bytes());
+                &prefix[..]
+            }
+        };
+        self.extend(&[prefix, data])?;
+        self.last_opcode = None;
+        Ok(self)
+    }
+
+    /// Appends all `parts`, or none if they do not fit.
+    fn extend(&mut self, parts: &[&[u8]]) -> Result<(), CapacityError> {
+        let required = self.len() + parts.iter().map(|part| part.len()).sum::<usize>();
+        if required > N {
+            return Err(CapacityError { required, capacity: N });
+        }
+        for part in parts {
+            self.bytes.extend_from_slice(part);
+        }
+        Ok(())
+    }
+}
+
+impl ScriptArray<22> {
+    /// Generates P2WPKH-type of scriptPubkey.
+    pub fn new_p2wpkh(pubkey_hash: WPubkeyHash) -> Self {
+        Self::new_witness_program(WitnessVersion::V0, pubkey_hash)
+    }
+}
+
+impl ScriptArray<34> {
+    /// Generates P2WSH-type of scriptPubkey with a given hash of the redeem script.
+    pub fn new_p2wsh(script_hash: WScriptHash) -> Self {
+        Self::new_witness_program(WitnessVersion::V0, script_hash)
+    }
+
+    /// Generates P2TR for script spending path using an internal public key and some optional
+    /// script tree Merkle root.
+    pub fn new_p2tr<K: Into<UntweakedPublicKey>>(
+        internal_key: K,
+        merkle_root: Option<TapNodeHash>,
+    ) -> Self {
+        let (output_key, _) = internal_key.into().tap_tweak(merkle_root);
+        Self::new_p2tr_tweaked(output_key)
+    }
+
+    /// Generates P2TR for key spending path for a known [`TweakedPublicKey`].
+    pub fn new_p2tr_tweaked(output_key: TweakedPublicKey) -> Self {
+        Self::new_witness_program(WitnessVersion::V1, output_key.serialize())
+    }
+}
+
+impl<const N: usize> ScriptArray<N> {
+    /// Generates a witness program, `N` has to fit the version opcode and the program push.
+    fn new_witness_program<P: AsRef<PushBytes>>(version: WitnessVersion, program: P) -> Self {
+        Self::new()
+            .push_opcode(version.into())
+            .and_then(|script| script.push_slice(program))
+            .expect("the capacity fits the program")
+    }
+}
+
+impl<const N: usize, T> Clone for ScriptArray<N, T> {
+    #[inline]
+    fn clone(&self) -> Self { *self }
+}
+
+impl<const N: usize, T> Copy for ScriptArray<N, T> {}
+
+impl<const N: usize, T> PartialEq for ScriptArray<N, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.as_bytes() == other.as_bytes() }
+}
+
+impl<const N: usize, T> Eq for ScriptArray<N, T> {}
+
+impl<const N: usize, T> Hash for ScriptArray<N, T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) { self.as_bytes().hash(state) }
+}
+
+impl<const N: usize, T> Default for ScriptArray<N, T> {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+impl<const N: usize, T> Deref for ScriptArray<N, T> {
+    type Target = Script<T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { self.as_script() }
+}
+
+impl<const N: usize, T> AsRef<Script<T>> for ScriptArray<N, T> {
+    #[inline]
+    fn as_ref(&self) -> &Script<T> { self.as_script() }
+}
+
+impl<const N: usize, T> AsRef<[u8]> for ScriptArray<N, T> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] { self.as_bytes() }
+}
+
+impl<const N: usize, T> fmt::Display for ScriptArray<N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::Display::fmt(self.as_script(), f) }
+}
+
+impl<const N: usize, T> fmt::Debug for ScriptArray<N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::Debug::fmt(self.as_script(), f) }
+}
+
+/// A push does not fit into the buffer of a [`ScriptArray`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    /// The number of bytes required to hold the script.
+    required: usize,
+    /// The capacity of the buffer.
+    capacity: usize,
+}
+
+impl CapacityError {
+    /// Returns the number of bytes that would have been required to hold the script.
+    pub fn required(&self) -> usize { self.required }
+
+    /// Returns the capacity of the buffer.
+    pub fn capacity(&self) -> usize { self.capacity }
+}
+
+impl From<Infallible> for CapacityError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "script requires {} bytes but the buffer only holds {} bytes",
+            self.required, self.capacity
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{
+        Builder, PushBytesBuf, ScriptBufExt as _, ScriptPubKeyBuf, ScriptPubKeyBufExt as _,
+    };
+    use crate::WitnessScriptTag;
+
+    #[test]
+    fn matches_builder() {
+        let key = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            .parse::<PublicKey>()
+            .unwrap();
+        let large = PushBytesBuf::try_from(vec![7; 300]).unwrap();
+        let builder = Builder::<WitnessScriptTag>::new()
+            .push_int_unchecked(-1)
+            .push_int_unchecked(0)
+            .push_int_unchecked(16)
+            .push_int_unchecked(1000)
+            .push_slice([5])
+            .push_slice_non_minimal([5])
+            .push_slice(&large)
+            .push_key(key)
+            .push_opcode(OP_CHECKSIG)
+            .push_verify()
+            .push_x_only_key(key.into())
+            .push_verify();
+        let array = ScriptArray::<512, WitnessScriptTag>::new()
+            .push_int_unchecked(-1)
+            .and_then(|s| s.push_int_unchecked(0))
+            .and_then(|s| s.push_int_unchecked(16))
+            .and_then(|s| s.push_int_unchecked(1000))
+            .and_then(|s| s.push_slice([5]))
+            .and_then(|s| s.push_slice_non_minimal([5]))
+            .and_then(|s| s.push_slice(&large))
+            .and_then(|s| s.push_key(key))
+            .and_then(|s| s.push_opcode(OP_CHECKSIG))
+            .and_then(|s| s.push_verify())
+            .and_then(|s| s.push_x_only_key(key.into()))
+            .and_then(|s| s.push_verify())
+            .unwrap();
+        assert_eq!(array.as_script(), builder.as_script());
+        assert_eq!(array.to_string(), builder.to_string());
+    }
+
+    #[test]
+    fn capacity() {
+        let script = ScriptArray::<3>::new().push_slice([1, 2]).unwrap();
+        assert_eq!(script.len(), 3);
+        assert_eq!(script.push_opcode(OP_DROP), Err(CapacityError { required: 4, capacity: 3 }));
+        // Replacing the last opcode does not need more space.
+        let script = ScriptArray::<1>::new().push_opcode(OP_EQUAL).unwrap();
+        assert_eq!(script.push_verify().unwrap().as_bytes(), [OP_EQUALVERIFY.to_u8()]);
+        assert!(ScriptArray::<0>::new().is_empty());
+    }
+
+    #[test]
+    fn output_scripts() {
+        let key = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            .parse::<PublicKey>()
+            .unwrap();
+        let wpkh = key.wpubkey_hash().unwrap();
+        assert_eq!(
+            ScriptArray::new_p2wpkh(wpkh).as_script(),
+            ScriptPubKeyBuf::new_p2wpkh(wpkh).as_script()
+        );
+        let wsh = WScriptHash::from_byte_array([1; 32]);
+        assert_eq!(
+            ScriptArray::new_p2wsh(wsh).as_script(),
+            ScriptPubKeyBuf::new_p2wsh(wsh).as_script()
+        );
+        let internal_key = XOnlyPublicKey::from(key);
+        assert_eq!(
+            ScriptArray::new_p2tr(internal_key, None).as_script(),
+            ScriptPubKeyBuf::new_p2tr(internal_key, None).as_script()
+        );
+    }
+}
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: CC0-1.0
 
+use core::any::Any;
 use core::fmt;
 
 use hex_unstable::DisplayHex as _;
@@ -9,17 +10,19 @@
 use super::witness_version::WitnessVersion;
 use super::{
     Builder, Instruction, InstructionIndices, Instructions, PushBytes, RedeemScript,
-    RedeemScriptSizeError, Script, ScriptHash, ScriptHashableTag, ScriptPubKey, ScriptSig,
-    TapScript, WScriptHash, WitnessScript, WitnessScriptSizeError,
+    RedeemScriptSizeError, SatisfactionAssumptions, Script, ScriptHash, ScriptHashableTag,
+    ScriptLint, ScriptPubKey, ScriptSig, TapScript, TapScriptError, WScriptHash, WitnessScript,
+    WitnessScriptSizeError,
 };
 use crate::consensus::{self, Encodable};
 use crate::key::{PublicKey, UntweakedPublicKey, WPubkeyHash};
 use crate::opcodes::all::*;
 use crate::opcodes::{self, Opcode};
 use crate::policy::{DUST_RELAY_TX_FEE, MAX_OP_RETURN_RELAY};
-use crate::prelude::{sink, String, ToString};
+use crate::prelude::{sink, String, ToString, Vec};
 use crate::script::{self, ScriptPubKeyBufExt as _};
 use crate::taproot::{LeafVersion, TapLeafHash, TapNodeHash};
+use crate::transaction::InputWeightPrediction;
 use crate::witness_program::P2A_PROGRAM;
 use crate::{internal_macros, Amount, FeeRate, ScriptPubKeyBuf, WitnessScriptBuf};
 
@@ -59,6 +62,17 @@ fn count_sigops(&self) -> usize { self.count_sigops_internal(true) }
         /// so do not use this to try and estimate if a Taproot script goes over the sigop budget.)
         fn count_sigops_legacy(&self) -> usize { self.count_sigops_internal(false) }
 
+        /// Counts the sigops for this Script the way Bitcoin Core's `GetSigOpCount` does.
+        ///
+        /// With `accurate` set this is the same as [`count_sigops`](Self::count_sigops), otherwise
+        /// the same as [`count_sigops_legacy`](Self::count_sigops_legacy). Choosing the mode at
+        /// runtime is useful when counting scripts of different kinds in one place, see
+        /// [`TransactionExt::total_sigop_cost`](crate::transaction::TransactionExt::total_sigop_cost)
+        /// for the cost of a whole transaction.
+        fn legacy_sigop_count(&self, accurate: bool) -> usize {
+            self.count_sigops_internal(accurate)
+        }
+
         /// Checks whether a script is push only.
         ///
         /// Note: `OP_RESERVED` (`0x50`) and all the OP_PUSHNUM operations
@@ -124,6 +138,19 @@ fn instruction_indices_minimal(&self) -> InstructionIndices<'_> {
             InstructionIndices::from_instructions(self.instructions_minimal())
         }
 
+        /// Returns warnings about the script that do not depend on how it is spent.
+        ///
+        /// Reports non-minimal pushes, which are non-standard, pushes of more than 520 bytes, a
+        /// tail that can not be decoded and scripts over the limits of 10,000 bytes and 201
+        /// non-push opcodes. Witness scripts over 3,600 bytes and scriptSigs over 1,650 bytes are
+        /// reported as non-standard. The lints are ordered by position, followed by those about
+        /// the whole script. Decoding failures are only reported if the script is executed, so
+        /// this does not make the script invalid as an output script.
+        ///
+        /// The size and opcode limits do not apply to tapscripts, use
+        /// [`TapScriptExt::validate_as_tapscript`] for those instead.
+        fn lint(&self) -> Vec<ScriptLint> where T: Any { super::lint::lint(self) }
+
         /// Writes the human-readable assembly representation of the script to the formatter.
         #[deprecated(since = "TBD", note = "use the script's `Display` impl instead")]
         fn fmt_asm(&self, f: &mut dyn fmt::Write) -> fmt::Result {
@@ -288,6 +315,22 @@ fn to_p2tr<K: Into<UntweakedPublicKey>>(
             let merkle_root = TapNodeHash::from(leaf_hash);
             ScriptPubKeyBuf::new_p2tr(internal_key, Some(merkle_root))
         }
+
+        /// Checks the script against the rules of BIP-0342 that do not depend on the witness.
+        ///
+        /// Reports `OP_SUCCESSx` opcodes, which make the script succeed before it is executed,
+        /// `OP_CHECKMULTISIG` and `OP_CHECKMULTISIGVERIFY`, opcodes that always fail, pushes of
+        /// more than 520 bytes and signature checks given a key pushed right before them that is
+        /// not 32 bytes long. The limits of 201 opcodes and 10,000 bytes that apply to legacy and
+        /// SegWit v0 scripts do not apply to tapscripts and are not checked.
+        ///
+        /// # Errors
+        ///
+        /// The first problem found, `OP_SUCCESSx` opcodes are reported before anything else since
+        /// they make the rest of the script irrelevant.
+        fn validate_as_tapscript(&self) -> Result<(), TapScriptError> {
+            super::tapscript::validate(self)
+        }
     }
 }
 
@@ -472,6 +515,21 @@ fn minimal_non_dust(&self) -> Amount {
         fn minimal_non_dust_custom(&self, dust_relay: FeeRate) -> Option<Amount> {
             self.minimal_non_dust_internal(dust_relay.to_sat_per_kvb_ceil())
         }
+
+        /// Returns the weight prediction of the largest input spending this script pubkey.
+        ///
+        /// Recognizes P2PKH, P2WPKH, P2TR, P2A, P2PK and bare multisig outputs as well as P2SH and
+        /// P2WSH outputs whose redeem or witness script is a single key or multisig script. The
+        /// `assumptions` provide the scripts of P2SH and P2WSH outputs and the leaves of Taproot
+        /// script path spends.
+        ///
+        /// Returns `None` if the script is not recognized or a required script is missing.
+        fn max_satisfaction_weight(
+            &self,
+            assumptions: &SatisfactionAssumptions,
+        ) -> Option<InputWeightPrediction> {
+            super::satisfaction::max_satisfaction(self, assumptions)
+        }
     }
 }
 
@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Warnings about scripts that are invalid or non-standard independent of how they are spent.
+
+use core::any::{Any, TypeId};
+use core::fmt;
+
+use super::{Error, Instruction, Script, ScriptExt as _, ScriptSigTag, WitnessScriptTag};
+use crate::constants::{MAX_SCRIPT_SIZE, MAX_STACK_ELEMENT_SIZE};
+use crate::opcodes::all::*;
+use crate::opcodes::Opcode;
+use crate::policy::{MAX_STANDARD_P2WSH_SCRIPT_SIZE, MAX_STANDARD_SCRIPTSIG_SIZE};
+use crate::prelude::Vec;
+
+/// The maximum number of non-push opcodes in a legacy or SegWit v0 script.
+const MAX_OPS_PER_SCRIPT: usize = 201;
+
+/// Returns the lints of `script`, ordered by position.
+pub(super) fn lint<T: Any>(script: &Script<T>) -> Vec<ScriptLint> {
+    let mut lints = Vec::new();
+    let mut op_count = 0;
+    let mut instructions = script.instruction_indices();
+    loop {
+        let tail = script.len() - instructions.as_script::<T>().len();
+        let (position, instruction) = match instructions.next() {
+            None => break,
+            Some(Ok(instruction)) => instruction,
+            Some(Err(error)) => {
+                lints.push(ScriptLint::UnparsableTail { position: tail, error });
+                break;
+            }
+        };
+        match instruction {
+            Instruction::PushBytes(bytes) => {
+                let opcode = Opcode::from(script.as_bytes()[position]);
+                if !opcode.is_minimal_push(bytes.as_bytes()) {
+                    lints.push(ScriptLint::NonMinimalPush { position });
+                }
+                if bytes.len() > MAX_STACK_ELEMENT_SIZE {
+                    lints.push(ScriptLint::ElementTooLarge { position, size: bytes.len() });
+                }
+            }
+            // Like Bitcoin Core, count everything above `OP_16`, including `OP_NOP`s.
+            Instruction::Op(op) if op.to_u8() > OP_16.to_u8() => op_count += 1,
+            Instruction::Op(_) => {}
+        }
+    }
+
+    if script.len() > MAX_SCRIPT_SIZE {
+        lints.push(ScriptLint::ScriptTooLarge { size: script.len() });
+    } else if let Some(limit) = standard_size_limit::<T>().filter(|&limit| script.len() > limit) {
+        lints.push(ScriptLint::NonStandardSize { size: script.len(), limit });
+    }
+    if op_count > MAX_OPS_PER_SCRIPT {
+        lints.push(ScriptLint::TooManyOpcodes { count: op_count });
+    }
+    lints
+}
+
+/// Returns the standardness limit on the size of scripts of kind `T`, if it is below
+/// [`MAX_SCRIPT_SIZE`].
+fn standard_size_limit<T: Any>() -> Option<usize> {
+    let tag = TypeId::of::<T>();
+    if tag == TypeId::of::<WitnessScriptTag>() {
+        Some(MAX_STANDARD_P2WSH_SCRIPT_SIZE)
+    } else if tag == TypeId::of::<ScriptSigTag>() {
+        Some(MAX_STANDARD_SCRIPTSIG_SIZE)
+    } else {
+        None
+    }
+}
+
+/// A warning about a script, see [`ScriptExt::lint`].
+///
+/// [`ScriptExt::lint`]: super::ScriptExt::lint
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScriptLint {
+    /// Data is not pushed with the shortest possible opcode, which is non-standard.
+    NonMinimalPush {
+        /// The position of the push in the script.
+        position: usize,
+    },
+    /// A push is larger than the 520 bytes allowed on the stack, the script fails if it is
+    /// executed.
+    ElementTooLarge {
+        /// The position of the push in the script.
+        position: usize,
+        /// The size of the pushed element.
+        size: usize,
+    },
+    /// The script is larger than the 10,000 bytes allowed for legacy and SegWit v0 scripts.
+    ScriptTooLarge {
+        /// The size of the script.
+        size: usize,
+    },
+    /// The script is within the consensus limit but larger than the 3,600 bytes relayed for a
+    /// P2WSH witness script or the 1,650 bytes relayed for a scriptSig, which is non-standard.
+    NonStandardSize {
+        /// The size of the script.
+        size: usize,
+        /// The standardness limit for this kind of script.
+        limit: usize,
+    },
+    /// The script contains more than the 201 non-push opcodes allowed for legacy and SegWit v0
+    /// scripts.
+    TooManyOpcodes {
+        /// The number of non-push opcodes.
+        count: usize,
+    },
+    /// The rest of the script can not be decoded, the script fails if it is executed.
+    UnparsableTail {
+        /// The position of the first instruction that can not be decoded.
+        position: usize,
+        /// The reason decoding failed.
+        error: Error,
+    },
+}
+
+impl fmt::Display for ScriptLint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::NonMinimalPush { position } =>
+                write!(f, "non-minimal push at position {}", position),
+            Self::ElementTooLarge { position, size } => write!(
+                f,
+                "push of {} bytes at position {} exceeds the limit of {} bytes",
+                size, position, MAX_STACK_ELEMENT_SIZE
+            ),
+            Self::ScriptTooLarge { size } =>
+                write!(f, "script of {} bytes exceeds the limit of {} bytes", size, MAX_SCRIPT_SIZE),
+            Self::NonStandardSize { size, limit } => write!(
+                f,
+                "script of {} bytes exceeds the standardness limit of {} bytes",
+                size, limit
+            ),
+            Self::TooManyOpcodes { count } =>
+                write!(f, "{} opcodes exceed the limit of {} opcodes", count, MAX_OPS_PER_SCRIPT),
+            Self::UnparsableTail { position, ref error } =>
+                write!(f, "script can not be decoded from position {}: {}", position, error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{PushBytesBuf, ScriptBufExt as _};
+    use crate::{ScriptPubKeyBuf, ScriptSigBuf, WitnessScriptBuf};
+
+    #[test]
+    fn clean_script() {
+        let script = WitnessScriptBuf::builder()
+            .push_int_unchecked(1)
+            .push_slice([2; 33])
+            .push_int_unchecked(1)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        assert_eq!(script.lint(), []);
+    }
+
+    #[test]
+    fn lints() {
+        let large = PushBytesBuf::try_from(vec![0; 521]).unwrap();
+        let script = WitnessScriptBuf::builder()
+            .push_slice_non_minimal([5])
+            .push_slice(large)
+            .push_opcode(OP_DROP)
+            .into_script();
+        let mut bytes = script.to_vec();
+        // A push of two bytes with only one left.
+        bytes.extend([0x02, 0xab]);
+        let script = WitnessScriptBuf::from_bytes(bytes);
+        assert_eq!(
+            script.lint(),
+            [
+                ScriptLint::NonMinimalPush { position: 0 },
+                ScriptLint::ElementTooLarge { position: 2, size: 521 },
+                ScriptLint::UnparsableTail { position: 527, error: Error::EarlyEndOfScript },
+            ]
+        );
+
+        let mut builder = WitnessScriptBuf::builder();
+        for _ in 0..202 {
+            builder = builder.push_opcode(OP_NOP);
+        }
+        assert_eq!(builder.into_script().lint(), [ScriptLint::TooManyOpcodes { count: 202 }]);
+
+        let large = PushBytesBuf::try_from(vec![0; 500]).unwrap();
+        let mut builder = WitnessScriptBuf::builder();
+        for _ in 0..21 {
+            builder = builder.push_slice(&large).push_opcode(OP_DROP);
+        }
+        assert_eq!(builder.into_script().lint(), [ScriptLint::ScriptTooLarge { size: 21 * 504 }]);
+    }
+
+    #[test]
+    fn non_standard_size() {
+        let large = PushBytesBuf::try_from(vec![0; 500]).unwrap();
+        let mut builder = WitnessScriptBuf::builder();
+        for _ in 0..8 {
+            builder = builder.push_slice(&large).push_opcode(OP_DROP);
+        }
+        let witness_script = builder.into_script();
+        assert_eq!(
+            witness_script.lint(),
+            [ScriptLint::NonStandardSize { size: 8 * 504, limit: MAX_STANDARD_P2WSH_SCRIPT_SIZE }]
+        );
+        // No standardness limit applies to the same script as a scriptPubKey.
+        let script_pubkey = ScriptPubKeyBuf::from_bytes(witness_script.to_vec());
+        assert_eq!(script_pubkey.lint(), []);
+
+        let mut builder = ScriptSigBuf::builder();
+        for _ in 0..4 {
+            builder = builder.push_slice(&large);
+        }
+        assert_eq!(
+            builder.into_script().lint(),
+            [ScriptLint::NonStandardSize { size: 4 * 503, limit: MAX_STANDARD_SCRIPTSIG_SIZE }]
+        );
+    }
+}
@@ -45,11 +45,16 @@
 //! At the time of writing there's only one operation using the cache - `push_verify`, so the cache
 //! is minimal but we may extend it in the future if needed.
 
+mod array;
 mod borrowed;
 mod builder;
 mod instruction;
+mod lint;
 mod owned;
 mod push_bytes;
+mod satisfaction;
+mod set;
+mod tapscript;
 #[cfg(test)]
 mod tests;
 pub mod witness_program;
@@ -72,11 +77,16 @@
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
 pub use self::{
+    array::{CapacityError, ScriptArray},
     borrowed::{ScriptExt, TapScriptExt, ScriptPubKeyExt, WitnessScriptExt, ScriptSigExt},
     builder::Builder,
     instruction::{Instruction, Instructions, InstructionIndices},
+    lint::ScriptLint,
     owned::{ScriptBufExt, ScriptPubKeyBufExt},
     push_bytes::{PushBytes, PushBytesBuf, PushBytesError, PushBytesErrorReport, ScriptIntError},
+    satisfaction::{SatisfactionAssumptions, TapLeafSpend, TaprootSpend},
+    set::ScriptSet,
+    tapscript::TapScriptError,
 };
 #[doc(inline)]
 pub use primitives::script::{
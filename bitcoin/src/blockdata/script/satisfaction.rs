@@ -0,0 +1,402 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Satisfaction weight estimation for standard script templates.
+//!
+//! Coin selection needs to know how much an input will weigh before it is signed. For the common
+//! output templates the size of a satisfaction only depends on a few facts about the spending
+//! conditions, which are collected in [`SatisfactionAssumptions`].
+
+use core::iter;
+
+use super::{
+    Instruction, RedeemScript, Script, ScriptExt as _, ScriptPubKey, ScriptPubKeyExt as _,
+    WitnessScript,
+};
+use crate::opcodes::all::*;
+use crate::transaction::InputWeightPrediction;
+
+/// The length of the largest low-S DER-encoded ECDSA signature, including the sighash byte.
+const ECDSA_SIG_MAX_LEN: usize = 72;
+
+/// The length of the largest low-R, low-S DER-encoded ECDSA signature, including the sighash byte.
+const ECDSA_SIG_LOW_R_MAX_LEN: usize = 71;
+
+/// The length of a Schnorr signature using the default sighash type.
+const SCHNORR_SIG_LEN: usize = 64;
+
+/// The length of a Schnorr signature using a non-default sighash type.
+const SCHNORR_SIG_SIGHASH_LEN: usize = 65;
+
+/// The length of a Taproot control block without any merkle path hashes.
+const CONTROL_BLOCK_BASE_LEN: usize = 33;
+
+/// Facts about how an output will be spent, used to estimate its satisfaction weight.
+///
+/// The default assumes compressed keys, signatures without grinding, the default Taproot sighash
+/// type and a Taproot key path spend.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SatisfactionAssumptions<'a> {
+    redeem_script: Option<&'a RedeemScript>,
+    witness_script: Option<&'a WitnessScript>,
+    uncompressed_keys: bool,
+    ecdsa_low_r: bool,
+    schnorr_default_sighash: bool,
+    taproot_spend: TaprootSpend<'a>,
+}
+
+impl<'a> SatisfactionAssumptions<'a> {
+    /// Constructs the default assumptions.
+    pub const fn new() -> Self {
+        Self {
+            redeem_script: None,
+            witness_script: None,
+            uncompressed_keys: false,
+            ecdsa_low_r: false,
+            schnorr_default_sighash: true,
+            taproot_spend: TaprootSpend::KeyPath,
+        }
+    }
+
+    /// Sets the redeem script of a P2SH output.
+    #[must_use]
+    pub const fn with_redeem_script(mut self, redeem_script: &'a RedeemScript) -> Self {
+        self.redeem_script = Some(redeem_script);
+        self
+    }
+
+    /// Sets the witness script of a P2WSH output, nested or not.
+    #[must_use]
+    pub const fn with_witness_script(mut self, witness_script: &'a WitnessScript) -> Self {
+        self.witness_script = Some(witness_script);
+        self
+    }
+
+    /// Sets whether P2PKH outputs are spent with an uncompressed public key.
+    #[must_use]
+    pub const fn with_uncompressed_keys(mut self, uncompressed_keys: bool) -> Self {
+        self.uncompressed_keys = uncompressed_keys;
+        self
+    }
+
+    /// Sets whether ECDSA signatures are ground to have a low R value, saving a byte each.
+    #[must_use]
+    pub const fn with_ecdsa_low_r(mut self, ecdsa_low_r: bool) -> Self {
+        self.ecdsa_low_r = ecdsa_low_r;
+        self
+    }
+
+    /// Sets whether Taproot key path signatures use the default sighash type.
+    #[must_use]
+    pub const fn with_schnorr_default_sighash(mut self, default_sighash: bool) -> Self {
+        self.schnorr_default_sighash = default_sighash;
+        self
+    }
+
+    /// Sets how Taproot outputs are spent.
+    #[must_use]
+    pub const fn with_taproot_spend(mut self, taproot_spend: TaprootSpend<'a>) -> Self {
+        self.taproot_spend = taproot_spend;
+        self
+    }
+
+    /// Returns the length of an ECDSA signature.
+    fn ecdsa_sig_len(&self) -> usize {
+        if self.ecdsa_low_r {
+            ECDSA_SIG_LOW_R_MAX_LEN
+        } else {
+            ECDSA_SIG_MAX_LEN
+        }
+    }
+
+    /// Returns the length of a public key.
+    fn pubkey_len(&self) -> usize {
+        if self.uncompressed_keys {
+            65
+        } else {
+            33
+        }
+    }
+}
+
+impl Default for SatisfactionAssumptions<'_> {
+    fn default() -> Self { Self::new() }
+}
+
+/// How a Taproot output is spent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TaprootSpend<'a> {
+    /// The output is spent with a signature for the output key.
+    #[default]
+    KeyPath,
+    /// The output is spent using one of the given leaves.
+    ///
+    /// The estimate is that of the heaviest leaf.
+    ScriptPath(&'a [TapLeafSpend<'a>]),
+}
+
+/// The sizes of a Taproot script path spend.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TapLeafSpend<'a> {
+    /// The length of the leaf script.
+    pub script_len: usize,
+    /// The depth of the leaf in the script tree, the number of hashes in the merkle path.
+    pub depth: u8,
+    /// The lengths of the witness elements satisfying the leaf script, excluding the script
+    /// itself and the control block.
+    pub stack_element_lengths: &'a [usize],
+}
+
+impl<'a> TapLeafSpend<'a> {
+    /// Constructs a new leaf spend.
+    pub const fn new(script_len: usize, depth: u8, stack_element_lengths: &'a [usize]) -> Self {
+        Self { script_len, depth, stack_element_lengths }
+    }
+
+    /// Returns the weight prediction of spending this leaf.
+    fn prediction(&self) -> InputWeightPrediction {
+        let control_block_len = CONTROL_BLOCK_BASE_LEN + 32 * usize::from(self.depth);
+        let lengths = self.stack_element_lengths.iter().copied();
+        InputWeightPrediction::new(0, lengths.chain([self.script_len, control_block_len]))
+    }
+}
+
+/// Returns the maximum weight prediction of an input spending `script_pubkey`.
+pub(super) fn max_satisfaction(
+    script_pubkey: &ScriptPubKey,
+    assumptions: &SatisfactionAssumptions,
+) -> Option<InputWeightPrediction> {
+    let sig_len = assumptions.ecdsa_sig_len();
+
+    if script_pubkey.is_p2pkh() {
+        let script_sig_len = push_len(sig_len) + push_len(assumptions.pubkey_len());
+        Some(InputWeightPrediction::new(script_sig_len, iter::empty::<usize>()))
+    } else if script_pubkey.is_p2wpkh() {
+        Some(InputWeightPrediction::new(0, [sig_len, 33]))
+    } else if script_pubkey.is_p2wsh() {
+        witness_script_satisfaction(0, assumptions)
+    } else if script_pubkey.is_p2tr() {
+        match assumptions.taproot_spend {
+            TaprootSpend::KeyPath => {
+                let sig_len = if assumptions.schnorr_default_sighash {
+                    SCHNORR_SIG_LEN
+                } else {
+                    SCHNORR_SIG_SIGHASH_LEN
+                };
+                Some(InputWeightPrediction::new(0, [sig_len]))
+            }
+            TaprootSpend::ScriptPath(leaves) => leaves
+                .iter()
+                .map(TapLeafSpend::prediction)
+                .max_by_key(|prediction| prediction.witness_weight()),
+        }
+    } else if script_pubkey.is_p2sh() {
+        let redeem_script = assumptions.redeem_script?;
+        let redeem_script_push_len = push_len(redeem_script.len());
+        // Nested SegWit redeem scripts are witness programs, check them like script pubkeys.
+        let nested = ScriptPubKey::from_bytes(redeem_script.as_bytes());
+        if nested.is_p2wpkh() {
+            Some(InputWeightPrediction::new(redeem_script_push_len, [sig_len, 33]))
+        } else if nested.is_p2wsh() {
+            witness_script_satisfaction(redeem_script_push_len, assumptions)
+        } else {
+            let script_sig_len = legacy_stack_len(redeem_script, sig_len)?;
+            Some(InputWeightPrediction::new(
+                script_sig_len + redeem_script_push_len,
+                iter::empty::<usize>(),
+            ))
+        }
+    } else if script_pubkey.is_p2a() {
+        Some(InputWeightPrediction::new(0, iter::empty::<usize>()))
+    } else {
+        let script_sig_len = legacy_stack_len(script_pubkey, sig_len)?;
+        Some(InputWeightPrediction::new(script_sig_len, iter::empty::<usize>()))
+    }
+}
+
+/// Returns the prediction of spending a P2WSH output using the assumed witness script.
+fn witness_script_satisfaction(
+    script_sig_len: usize,
+    assumptions: &SatisfactionAssumptions,
+) -> Option<InputWeightPrediction> {
+    let witness_script = assumptions.witness_script?;
+    let sig_len = assumptions.ecdsa_sig_len();
+    let script_len = witness_script.len();
+    match template(witness_script)? {
+        Template::Pk => Some(InputWeightPrediction::new(script_sig_len, [sig_len, script_len])),
+        Template::Multisig(required) => {
+            // The extra element is consumed by the `OP_CHECKMULTISIG` off-by-one bug.
+            let lengths = iter::once(0)
+                .chain(iter::repeat(sig_len).take(required))
+                .chain(iter::once(script_len));
+            Some(InputWeightPrediction::new(script_sig_len, lengths))
+        }
+    }
+}
+
+/// Returns the length of the script sig pushes satisfying a bare script.
+fn legacy_stack_len<T>(script: &Script<T>, sig_len: usize) -> Option<usize> {
+    match template(script)? {
+        Template::Pk => Some(push_len(sig_len)),
+        // One byte for the `OP_0` consumed by the `OP_CHECKMULTISIG` off-by-one bug.
+        Template::Multisig(required) => Some(1 + required * push_len(sig_len)),
+    }
+}
+
+/// The script templates which can be satisfied with signatures only.
+enum Template {
+    /// `<pubkey> OP_CHECKSIG`
+    Pk,
+    /// `<m> <pubkey>... <n> OP_CHECKMULTISIG`, with the number of required signatures `m`.
+    Multisig(usize),
+}
+
+/// Returns the template of `script` if it is one of the known ones.
+fn template<T>(script: &Script<T>) -> Option<Template> {
+    let mut instructions = script.instructions();
+
+    let required = match instructions.next()?.ok()? {
+        Instruction::PushBytes(bytes) => {
+            if !matches!(bytes.len(), 33 | 65) {
+                return None;
+            }
+            return match (instructions.next(), instructions.next()) {
+                (Some(Ok(Instruction::Op(OP_CHECKSIG))), None) => Some(Template::Pk),
+                _ => None,
+            };
+        }
+        Instruction::Op(op) => op.decode_pushnum()?,
+    };
+
+    let mut num_pubkeys = 0;
+    loop {
+        match instructions.next()?.ok()? {
+            Instruction::PushBytes(_) => num_pubkeys += 1,
+            Instruction::Op(op) => {
+                if op.decode_pushnum()? != num_pubkeys {
+                    return None;
+                }
+                break;
+            }
+        }
+    }
+    match (instructions.next(), instructions.next()) {
+        (Some(Ok(Instruction::Op(OP_CHECKMULTISIG))), None) if required <= num_pubkeys =>
+            Some(Template::Multisig(usize::from(required))),
+        _ => None,
+    }
+}
+
+/// Returns the length of the minimal push of `len` bytes.
+fn push_len(len: usize) -> usize {
+    let opcode_len = match len {
+        0..=0x4b => 1,
+        0x4c..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5,
+    };
+    opcode_len + len
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_lit::hex;
+
+    use super::*;
+    use crate::script::{RedeemScriptBuf, ScriptPubKeyBufExt as _, WitnessScriptExt as _};
+    use crate::{ScriptPubKeyBuf, Weight, WitnessScriptBuf};
+
+    fn multisig_2_of_3() -> WitnessScriptBuf {
+        let pk = hex!("02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5");
+        WitnessScript::builder()
+            .push_opcode(OP_2)
+            .push_slice(pk)
+            .push_slice(pk)
+            .push_slice(pk)
+            .push_opcode(OP_3)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    #[test]
+    fn single_key_templates() {
+        let assumptions = SatisfactionAssumptions::new();
+
+        let p2pkh = ScriptPubKeyBuf::from_bytes(
+            [&[0x76, 0xa9, 0x14][..], &[0; 20], &[0x88, 0xac]].concat(),
+        );
+        assert_eq!(
+            p2pkh.max_satisfaction_weight(&assumptions),
+            Some(InputWeightPrediction::P2PKH_COMPRESSED_MAX)
+        );
+        assert_eq!(
+            p2pkh.max_satisfaction_weight(&assumptions.with_uncompressed_keys(true)),
+            Some(InputWeightPrediction::P2PKH_UNCOMPRESSED_MAX)
+        );
+
+        let p2wpkh = ScriptPubKeyBuf::from_bytes([&[0x00, 0x14][..], &[0; 20]].concat());
+        assert_eq!(
+            p2wpkh.max_satisfaction_weight(&assumptions),
+            Some(InputWeightPrediction::P2WPKH_MAX)
+        );
+        assert_eq!(
+            p2wpkh.max_satisfaction_weight(&assumptions.with_ecdsa_low_r(true)),
+            Some(InputWeightPrediction::ground_p2wpkh(1))
+        );
+
+        let redeem_script = RedeemScriptBuf::from_bytes(p2wpkh.to_vec());
+        let p2sh = ScriptPubKeyBuf::new_p2sh(redeem_script.script_hash().unwrap());
+        assert_eq!(p2sh.max_satisfaction_weight(&assumptions), None);
+        assert_eq!(
+            p2sh.max_satisfaction_weight(&assumptions.with_redeem_script(&redeem_script)),
+            Some(InputWeightPrediction::NESTED_P2WPKH_MAX)
+        );
+
+        let p2tr = ScriptPubKeyBuf::from_bytes([&[0x51, 0x20][..], &[0; 32]].concat());
+        assert_eq!(
+            p2tr.max_satisfaction_weight(&assumptions),
+            Some(InputWeightPrediction::P2TR_KEY_DEFAULT_SIGHASH)
+        );
+        assert_eq!(
+            p2tr.max_satisfaction_weight(&assumptions.with_schnorr_default_sighash(false)),
+            Some(InputWeightPrediction::P2TR_KEY_NON_DEFAULT_SIGHASH)
+        );
+    }
+
+    #[test]
+    fn multisig() {
+        let witness_script = multisig_2_of_3();
+        let p2wsh = witness_script.to_p2wsh().unwrap();
+        let assumptions = SatisfactionAssumptions::new().with_witness_script(&witness_script);
+        let prediction = p2wsh.max_satisfaction_weight(&assumptions).unwrap();
+        // Witness: count, empty element, two signatures and the 105 byte script.
+        assert_eq!(prediction.witness_weight(), Weight::from_wu(1 + 4 + 1 + 2 * 73 + 1 + 105));
+        assert_eq!(p2wsh.max_satisfaction_weight(&SatisfactionAssumptions::new()), None);
+
+        // The same script in legacy P2SH is pushed in the script sig instead.
+        let redeem_script = RedeemScriptBuf::from_bytes(witness_script.to_vec());
+        let p2sh = ScriptPubKeyBuf::new_p2sh(redeem_script.script_hash().unwrap());
+        let assumptions = SatisfactionAssumptions::new().with_redeem_script(&redeem_script);
+        let prediction = p2sh.max_satisfaction_weight(&assumptions).unwrap();
+        let script_sig_len = 1 + 2 * 73 + 2 + 105;
+        assert_eq!(prediction.witness_weight(), Weight::from_wu(4 * (3 + script_sig_len)));
+
+        // Not a known template.
+        let witness_script = WitnessScriptBuf::from_bytes(vec![OP_1.to_u8()]);
+        let assumptions = SatisfactionAssumptions::new().with_witness_script(&witness_script);
+        assert_eq!(witness_script.to_p2wsh().unwrap().max_satisfaction_weight(&assumptions), None);
+    }
+
+    #[test]
+    fn taproot_script_path() {
+        let p2tr = ScriptPubKeyBuf::from_bytes([&[0x51, 0x20][..], &[0; 32]].concat());
+        let leaves = [TapLeafSpend::new(34, 1, &[64]), TapLeafSpend::new(68, 2, &[64, 64])];
+        let assumptions =
+            SatisfactionAssumptions::new().with_taproot_spend(TaprootSpend::ScriptPath(&leaves));
+        let prediction = p2tr.max_satisfaction_weight(&assumptions).unwrap();
+        // The second leaf: count, two signatures, the script and a control block with two hashes.
+        assert_eq!(prediction.witness_weight(), Weight::from_wu(1 + 4 + 2 * 65 + 69 + 98));
+
+        let assumptions = assumptions.with_taproot_spend(TaprootSpend::ScriptPath(&[]));
+        assert_eq!(p2tr.max_satisfaction_weight(&assumptions), None);
+    }
+}
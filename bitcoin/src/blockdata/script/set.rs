@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! A set of script pubkeys optimized for matching transaction outputs.
+//!
+//! Wallet rescans test every output of every block against all the scripts a wallet watches. The
+//! [`ScriptSet`] makes such lookups cheap even for thousands of scripts.
+
+use crate::prelude::{BTreeMap, Vec};
+use crate::{ScriptPubKey, ScriptPubKeyBuf};
+
+/// Offset of the fingerprint in a script.
+///
+/// For all standard output scripts (except bare multisig) the bytes from offset 3 onwards are part
+/// of a hash, public key or witness program and hence close to uniformly distributed.
+const FINGERPRINT_OFFSET: usize = 3;
+
+/// Length of the fingerprint.
+const FINGERPRINT_LEN: usize = 8;
+
+type Fingerprint = [u8; FINGERPRINT_LEN];
+
+/// A set of script pubkeys that can be efficiently matched against transaction outputs.
+///
+/// Scripts are indexed by a few of their leading hash (or public key) bytes, so that looking up a
+/// script that is not in the set usually costs a single short comparison per tree node, without
+/// hashing the script or comparing its common template prefix over and over.
+///
+/// # Examples
+///
+/// ```
+/// # use bitcoin::{Address, Network, script::ScriptSet};
+/// let address = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"
+///     .parse::<Address<_>>()
+///     .unwrap()
+///     .require_network(Network::Bitcoin)
+///     .unwrap();
+///
+/// let mut set = ScriptSet::new();
+/// set.insert(address.script_pubkey());
+/// assert!(set.contains(&address.script_pubkey()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScriptSet {
+    /// Scripts grouped by fingerprint, the inner vectors almost always contain one script.
+    buckets: BTreeMap<Fingerprint, Vec<ScriptPubKeyBuf>>,
+    /// The number of scripts in the set.
+    len: usize,
+}
+
+impl ScriptSet {
+    /// Constructs a new empty set.
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns the number of scripts in the set.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Returns `true` if the set contains no scripts.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Adds a script to the set.
+    ///
+    /// Returns `true` if the script was not already in the set.
+    pub fn insert(&mut self, script_pubkey: ScriptPubKeyBuf) -> bool {
+        let bucket = self.buckets.entry(fingerprint(&script_pubkey)).or_default();
+        if bucket.contains(&script_pubkey) {
+            return false;
+        }
+        bucket.push(script_pubkey);
+        self.len += 1;
+        true
+    }
+
+    /// Removes a script from the set.
+    ///
+    /// Returns `true` if the script was in the set.
+    pub fn remove(&mut self, script_pubkey: &ScriptPubKey) -> bool {
+        let key = fingerprint(script_pubkey);
+        let Some(bucket) = self.buckets.get_mut(&key) else { return false };
+        let Some(index) = bucket.iter().position(|s| s.as_script() == script_pubkey) else {
+            return false;
+        };
+        bucket.swap_remove(index);
+        if bucket.is_empty() {
+            self.buckets.remove(&key);
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Returns `true` if the set contains `script_pubkey`.
+    pub fn contains(&self, script_pubkey: &ScriptPubKey) -> bool {
+        self.buckets
+            .get(&fingerprint(script_pubkey))
+            .is_some_and(|bucket| bucket.iter().any(|s| s.as_script() == script_pubkey))
+    }
+
+    /// Returns an iterator over the scripts in the set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &ScriptPubKey> {
+        self.buckets.values().flatten().map(ScriptPubKeyBuf::as_script)
+    }
+
+    /// Removes all scripts from the set.
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.len = 0;
+    }
+}
+
+impl Extend<ScriptPubKeyBuf> for ScriptSet {
+    fn extend<I: IntoIterator<Item = ScriptPubKeyBuf>>(&mut self, iter: I) {
+        for script_pubkey in iter {
+            self.insert(script_pubkey);
+        }
+    }
+}
+
+impl FromIterator<ScriptPubKeyBuf> for ScriptSet {
+    fn from_iter<I: IntoIterator<Item = ScriptPubKeyBuf>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Returns the bytes used to index `script_pubkey`.
+///
+/// Short scripts use their last bytes instead, padded with zeros.
+fn fingerprint(script_pubkey: &ScriptPubKey) -> Fingerprint {
+    let bytes = script_pubkey.as_bytes();
+    let start = FINGERPRINT_OFFSET.min(bytes.len().saturating_sub(FINGERPRINT_LEN));
+    let bytes = &bytes[start..];
+    let len = bytes.len().min(FINGERPRINT_LEN);
+
+    let mut fingerprint = [0; FINGERPRINT_LEN];
+    fingerprint[..len].copy_from_slice(&bytes[..len]);
+    fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p2wpkh(byte: u8) -> ScriptPubKeyBuf {
+        ScriptPubKeyBuf::from_bytes([&[0x00, 0x14][..], &[byte; 20]].concat())
+    }
+
+    #[test]
+    fn insert_contains_remove() {
+        let mut set = ScriptSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(p2wpkh(1)));
+        assert!(set.insert(p2wpkh(2)));
+        assert!(!set.insert(p2wpkh(1)));
+        assert_eq!(set.len(), 2);
+
+        assert!(set.contains(&p2wpkh(1)));
+        assert!(!set.contains(&p2wpkh(3)));
+
+        assert!(set.remove(&p2wpkh(1)));
+        assert!(!set.remove(&p2wpkh(1)));
+        assert!(!set.contains(&p2wpkh(1)));
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [p2wpkh(2).as_script()]);
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.iter().count(), 0);
+    }
+
+    #[test]
+    fn fingerprint_collisions() {
+        // Same fingerprint, different scripts.
+        let short = ScriptPubKeyBuf::from_bytes(vec![1, 2, 3]);
+        let padded = ScriptPubKeyBuf::from_bytes(vec![1, 2, 3, 0]);
+        let empty = ScriptPubKeyBuf::new();
+        assert_eq!(fingerprint(&short), fingerprint(&padded));
+
+        let set: ScriptSet = [short.clone(), empty.clone()].into_iter().collect();
+        assert!(set.contains(&short));
+        assert!(set.contains(&empty));
+        assert!(!set.contains(&padded));
+    }
+}
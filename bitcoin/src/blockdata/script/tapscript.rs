@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Static checks of tapscripts.
+//!
+//! BIP-0342 changes the rules for scripts in Taproot leaves of version `0xc0`: opcodes that were
+//! disabled or undefined become `OP_SUCCESSx`, `OP_CHECKMULTISIG` is replaced by
+//! `OP_CHECKSIGADD`, keys are 32 byte x-only keys and the limits of 201 opcodes and 10,000 bytes
+//! per script no longer apply. Scripts that are fine in a P2WSH witness script can therefore fail
+//! or be non-standard as a leaf script.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use internals::write_err;
+
+use super::{Error, Instruction, ScriptExt as _, TapScript};
+use crate::constants::MAX_STACK_ELEMENT_SIZE;
+use crate::opcodes::all::*;
+use crate::opcodes::{self, Opcode};
+use crate::prelude::Vec;
+
+/// Checks the parts of the tapscript rules that do not depend on the witness.
+pub(super) fn validate(script: &TapScript) -> Result<(), TapScriptError> {
+    // Any `OP_SUCCESSx` makes the script succeed, unless decoding fails before it.
+    let mut instructions = Vec::new();
+    for instruction in script.instruction_indices() {
+        let (position, instruction) = instruction.map_err(TapScriptError::Parse)?;
+        if let Instruction::Op(opcode) = instruction {
+            if opcode.is_success() {
+                return Err(TapScriptError::OpSuccess { position, opcode });
+            }
+        }
+        instructions.push((position, instruction));
+    }
+
+    let mut previous = None;
+    for &(position, instruction) in &instructions {
+        match instruction {
+            Instruction::PushBytes(bytes) if bytes.len() > MAX_STACK_ELEMENT_SIZE =>
+                return Err(TapScriptError::ElementTooLarge { position, size: bytes.len() }),
+            Instruction::PushBytes(_) => {}
+            Instruction::Op(OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY) =>
+                return Err(TapScriptError::CheckMultisig { position }),
+            Instruction::Op(opcode)
+                if opcode.classify(opcodes::ClassifyContext::TapScript)
+                    == opcodes::Class::IllegalOp =>
+                return Err(TapScriptError::IllegalOpcode { position, opcode }),
+            Instruction::Op(OP_CHECKSIG | OP_CHECKSIGVERIFY | OP_CHECKSIGADD) =>
+                match previous.and_then(pushed_len) {
+                    Some(0) => return Err(TapScriptError::EmptyKey { position }),
+                    Some(32) | None => {}
+                    Some(len) => return Err(TapScriptError::UnknownKeyType { position, len }),
+                },
+            Instruction::Op(_) => {}
+        }
+        previous = Some(instruction);
+    }
+    Ok(())
+}
+
+/// Returns the length of the element pushed by `instruction`, if it is a push.
+fn pushed_len(instruction: Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::PushBytes(bytes) => Some(bytes.len()),
+        Instruction::Op(op) if op == OP_1NEGATE || op.decode_pushnum().is_some() => Some(1),
+        Instruction::Op(_) => None,
+    }
+}
+
+/// A reason a tapscript is invalid or non-standard, see [`TapScriptExt::validate_as_tapscript`].
+///
+/// [`TapScriptExt::validate_as_tapscript`]: super::TapScriptExt::validate_as_tapscript
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TapScriptError {
+    /// The script can not be decoded.
+    Parse(Error),
+    /// The script contains an `OP_SUCCESSx` opcode.
+    ///
+    /// The script succeeds whatever the witness, spending with it is non-standard.
+    OpSuccess {
+        /// The position of the opcode in the script.
+        position: usize,
+        /// The opcode.
+        opcode: Opcode,
+    },
+    /// The script contains `OP_CHECKMULTISIG` or `OP_CHECKMULTISIGVERIFY`, which always fail in
+    /// tapscript. Multisig is expressed with `OP_CHECKSIGADD` instead.
+    CheckMultisig {
+        /// The position of the opcode in the script.
+        position: usize,
+    },
+    /// The script contains an opcode that fails even if not executed.
+    IllegalOpcode {
+        /// The position of the opcode in the script.
+        position: usize,
+        /// The opcode.
+        opcode: Opcode,
+    },
+    /// A push is larger than the 520 bytes allowed on the stack.
+    ElementTooLarge {
+        /// The position of the push in the script.
+        position: usize,
+        /// The size of the pushed element.
+        size: usize,
+    },
+    /// A signature check is given an empty key, which always fails it.
+    EmptyKey {
+        /// The position of the signature opcode in the script.
+        position: usize,
+    },
+    /// A signature check is given a key that is neither empty nor 32 bytes long.
+    ///
+    /// Such keys are reserved for future soft forks, the check succeeds but spending with it is
+    /// non-standard.
+    UnknownKeyType {
+        /// The position of the signature opcode in the script.
+        position: usize,
+        /// The length of the key.
+        len: usize,
+    },
+}
+
+impl From<Infallible> for TapScriptError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for TapScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Parse(ref e) => write_err!(f, "invalid script"; e),
+            Self::OpSuccess { position, opcode } =>
+                write!(f, "{} at position {} makes the script succeed", opcode, position),
+            Self::CheckMultisig { position } =>
+                write!(f, "multisig opcode at position {} is not available in tapscript", position),
+            Self::IllegalOpcode { position, opcode } =>
+                write!(f, "illegal opcode {} at position {}", opcode, position),
+            Self::ElementTooLarge { position, size } => write!(
+                f,
+                "push of {} bytes at position {} exceeds the limit of {} bytes",
+                size, position, MAX_STACK_ELEMENT_SIZE
+            ),
+            Self::EmptyKey { position } =>
+                write!(f, "signature check at position {} has an empty key", position),
+            Self::UnknownKeyType { position, len } => write!(
+                f,
+                "signature check at position {} has a key of unknown type ({} bytes)",
+                position, len
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TapScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::Parse(ref e) => Some(e),
+            Self::OpSuccess { .. }
+            | Self::CheckMultisig { .. }
+            | Self::IllegalOpcode { .. }
+            | Self::ElementTooLarge { .. }
+            | Self::EmptyKey { .. }
+            | Self::UnknownKeyType { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{PushBytesBuf, ScriptBufExt as _, TapScriptExt as _};
+    use crate::TapScriptBuf;
+
+    #[test]
+    fn multi_a() {
+        let key = [2; 32];
+        let script = TapScriptBuf::builder()
+            .push_slice(key)
+            .push_opcode(OP_CHECKSIG)
+            .push_slice(key)
+            .push_opcode(OP_CHECKSIGADD)
+            .push_int_unchecked(2)
+            .push_opcode(OP_NUMEQUAL)
+            .into_script();
+        assert_eq!(script.validate_as_tapscript(), Ok(()));
+
+        // Neither the opcode count nor the script size are limited.
+        let mut long = TapScriptBuf::builder();
+        for _ in 0..300 {
+            long = long.push_slice(key).push_opcode(OP_CHECKSIGVERIFY);
+        }
+        assert!(long.as_script().len() > 10_000);
+        assert_eq!(long.into_script().validate_as_tapscript(), Ok(()));
+    }
+
+    #[test]
+    fn errors() {
+        let script = TapScriptBuf::builder()
+            .push_int_unchecked(1)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        assert_eq!(
+            script.validate_as_tapscript(),
+            Err(TapScriptError::CheckMultisig { position: 1 })
+        );
+
+        // `OP_SUCCESSx` takes precedence, even after a failing opcode or before a decoding error.
+        let script = TapScriptBuf::from_bytes(vec![OP_CHECKMULTISIG.to_u8(), OP_CAT.to_u8(), 0x4c]);
+        assert_eq!(
+            script.validate_as_tapscript(),
+            Err(TapScriptError::OpSuccess { position: 1, opcode: OP_CAT })
+        );
+        let truncated = TapScriptBuf::from_bytes(vec![0x4c]);
+        assert_eq!(
+            truncated.validate_as_tapscript(),
+            Err(TapScriptError::Parse(Error::EarlyEndOfScript))
+        );
+
+        let script = TapScriptBuf::builder().push_opcode(OP_VERIF).into_script();
+        assert_eq!(
+            script.validate_as_tapscript(),
+            Err(TapScriptError::IllegalOpcode { position: 0, opcode: OP_VERIF })
+        );
+
+        let large = PushBytesBuf::try_from(vec![0; 521]).unwrap();
+        let script = TapScriptBuf::builder().push_opcode(OP_DROP).push_slice(large).into_script();
+        assert_eq!(
+            script.validate_as_tapscript(),
+            Err(TapScriptError::ElementTooLarge { position: 1, size: 521 })
+        );
+
+        let script =
+            TapScriptBuf::builder().push_int_unchecked(0).push_opcode(OP_CHECKSIG).into_script();
+        assert_eq!(script.validate_as_tapscript(), Err(TapScriptError::EmptyKey { position: 1 }));
+
+        let script =
+            TapScriptBuf::builder().push_slice([2; 33]).push_opcode(OP_CHECKSIGADD).into_script();
+        assert_eq!(
+            script.validate_as_tapscript(),
+            Err(TapScriptError::UnknownKeyType { position: 34, len: 33 })
+        );
+    }
+}
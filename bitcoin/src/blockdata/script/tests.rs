@@ -914,6 +914,20 @@ fn script_get_sigop_count() {
     assert_eq!(multi_nopushnum_op.count_sigops_legacy(), 20);
 }
 
+#[test]
+fn script_legacy_sigop_count() {
+    let multi = Script::builder()
+        .push_opcode(OP_1)
+        .push_slice([3; 33])
+        .push_slice([3; 33])
+        .push_slice([3; 33])
+        .push_opcode(OP_3)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+    assert_eq!(multi.legacy_sigop_count(true), multi.count_sigops());
+    assert_eq!(multi.legacy_sigop_count(false), multi.count_sigops_legacy());
+}
+
 #[test]
 #[cfg(feature = "serde")]
 fn script_serde_human_and_not() {
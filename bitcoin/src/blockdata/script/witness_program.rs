@@ -13,9 +13,14 @@
 use internals::array_vec::ArrayVec;
 
 use super::witness_version::WitnessVersion;
-use super::{PushBytes, WScriptHash, WitnessScript, WitnessScriptSizeError};
-use crate::crypto::key::{CompressedPublicKey, TapTweak, TweakedPublicKey, UntweakedPublicKey};
-use crate::script::WitnessScriptExt as _;
+use super::{
+    PushBytes, ScriptPubKey, ScriptPubKeyBuf, WScriptHash, WitnessScript, WitnessScriptSizeError,
+};
+use crate::crypto::key::{
+    CompressedPublicKey, TapTweak, TweakedPublicKey, UntweakedPublicKey, WPubkeyHash,
+    XOnlyPublicKey,
+};
+use crate::script::{ScriptExt as _, ScriptPubKeyBufExt as _, WitnessScriptExt as _};
 use crate::taproot::TapNodeHash;
 
 /// The minimum byte size of a segregated witness program.
@@ -139,6 +144,103 @@ pub fn is_p2tr(&self) -> bool { self.version == WitnessVersion::V1 && self.progr
     pub fn is_p2a(&self) -> bool {
         self.version == WitnessVersion::V1 && self.program == P2A_PROGRAM
     }
+
+    /// Returns the witness program of `script_pubkey`, if it is a SegWit output.
+    pub fn from_script_pubkey(script_pubkey: &ScriptPubKey) -> Option<Self> {
+        let version = script_pubkey.witness_version()?;
+        Self::new(version, &script_pubkey.as_bytes()[2..]).ok()
+    }
+
+    /// Returns the output script paying to this witness program.
+    pub fn to_script_pubkey(&self) -> ScriptPubKeyBuf { ScriptPubKeyBuf::new_witness_program(self) }
+
+    /// Returns the witness program by the output type it defines.
+    pub fn kind(&self) -> WitnessProgramKind {
+        let program = self.program.as_slice();
+        match (self.version, program.len()) {
+            (WitnessVersion::V0, 20) => WitnessProgramKind::V0Keyhash(
+                WPubkeyHash::from_byte_array(program.try_into().expect("checked length")),
+            ),
+            (WitnessVersion::V0, 32) => WitnessProgramKind::V0Scripthash(
+                WScriptHash::from_byte_array(program.try_into().expect("checked length")),
+            ),
+            (WitnessVersion::V1, 32) => {
+                let bytes = program.try_into().expect("checked length");
+                match XOnlyPublicKey::from_byte_array(&bytes) {
+                    Ok(output_key) => WitnessProgramKind::V1Taproot(output_key),
+                    Err(_) => WitnessProgramKind::Future { program: *self },
+                }
+            }
+            _ if self.is_p2a() => WitnessProgramKind::V1Anchor,
+            _ => WitnessProgramKind::Future { program: *self },
+        }
+    }
+}
+
+/// A witness program by the output type it defines.
+///
+/// Unlike [`WitnessProgram`], which only enforces length limits, the variants carry the typed
+/// contents of the outputs with known spending rules.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WitnessProgramKind {
+    /// A P2WPKH program, the hash of the public key.
+    V0Keyhash(WPubkeyHash),
+    /// A P2WSH program, the hash of the witness script.
+    V0Scripthash(WScriptHash),
+    /// A P2TR program, the tweaked output key.
+    V1Taproot(XOnlyPublicKey),
+    /// A P2A (pay to anchor) program.
+    V1Anchor,
+    /// Any other program, of a witness version or length without defined spending rules.
+    ///
+    /// This includes version 1 programs of 32 bytes that are not a valid key, outputs paying to
+    /// these can never be spent. [`WitnessProgram::is_p2tr`] only checks the length and is `true`
+    /// for these, and [`Address::address_type`] and `rpc_json::ScriptType::from_script` classify
+    /// them as Taproot like Bitcoin Core does.
+    ///
+    /// [`Address::address_type`]: crate::Address::address_type
+    Future {
+        /// The witness program.
+        program: WitnessProgram,
+    },
+}
+
+impl WitnessProgramKind {
+    /// Returns the kind of the witness program of `script_pubkey`, if it is a SegWit output.
+    pub fn from_script_pubkey(script_pubkey: &ScriptPubKey) -> Option<Self> {
+        WitnessProgram::from_script_pubkey(script_pubkey).map(|program| program.kind())
+    }
+
+    /// Returns the output script paying to this witness program.
+    pub fn to_script_pubkey(&self) -> ScriptPubKeyBuf {
+        WitnessProgram::from(*self).to_script_pubkey()
+    }
+
+    /// Returns the witness version.
+    pub fn version(&self) -> WitnessVersion {
+        match *self {
+            Self::V0Keyhash(_) | Self::V0Scripthash(_) => WitnessVersion::V0,
+            Self::V1Taproot(_) | Self::V1Anchor => WitnessVersion::V1,
+            Self::Future { program } => program.version(),
+        }
+    }
+}
+
+impl From<WitnessProgramKind> for WitnessProgram {
+    fn from(kind: WitnessProgramKind) -> Self {
+        match kind {
+            WitnessProgramKind::V0Keyhash(hash) => Self::new_p2wpkh(hash.to_byte_array()),
+            WitnessProgramKind::V0Scripthash(hash) => Self::new_p2wsh(hash.to_byte_array()),
+            WitnessProgramKind::V1Taproot(output_key) => Self::new_p2tr(output_key.serialize()),
+            WitnessProgramKind::V1Anchor => Self::p2a(),
+            WitnessProgramKind::Future { program } => program,
+        }
+    }
+}
+
+impl From<WitnessProgram> for WitnessProgramKind {
+    fn from(program: WitnessProgram) -> Self { program.kind() }
 }
 
 /// Witness program error.
@@ -222,4 +324,36 @@ fn valid_v1_witness_programs() {
             .expect("valid witness program")
             .is_p2a());
     }
+
+    #[test]
+    fn witness_program_kinds() {
+        let key = "0250863ad64a87ae8a2fe83c1af1a8403cb53f53e486d8511dad8a04887e5b2352"
+            .parse::<CompressedPublicKey>()
+            .unwrap();
+        let p2wpkh = WitnessProgram::p2wpkh(key);
+        assert_eq!(p2wpkh.kind(), WitnessProgramKind::V0Keyhash(key.wpubkey_hash()));
+
+        let hash = WScriptHash::from_byte_array([1; 32]);
+        let p2wsh = WitnessProgram::p2wsh_from_hash(hash);
+        assert_eq!(p2wsh.kind(), WitnessProgramKind::V0Scripthash(hash));
+
+        let p2tr = WitnessProgram::p2tr(key, None);
+        assert!(matches!(p2tr.kind(), WitnessProgramKind::V1Taproot(_)));
+        assert_eq!(WitnessProgram::p2a().kind(), WitnessProgramKind::V1Anchor);
+
+        // Not a point on the curve.
+        let invalid_key = WitnessProgram::new(WitnessVersion::V1, &[0xff; 32]).unwrap();
+        let future = WitnessProgram::new(WitnessVersion::V2, &[0; 32]).unwrap();
+        for program in [p2wpkh, p2wsh, p2tr, WitnessProgram::p2a(), invalid_key, future] {
+            let kind = program.kind();
+            assert_eq!(WitnessProgram::from(kind), program);
+            assert_eq!(kind.version(), program.version());
+
+            let script_pubkey = kind.to_script_pubkey();
+            assert_eq!(WitnessProgramKind::from_script_pubkey(&script_pubkey), Some(kind));
+        }
+        assert_eq!(invalid_key.kind(), WitnessProgramKind::Future { program: invalid_key });
+        assert_eq!(future.kind(), WitnessProgramKind::Future { program: future });
+        assert_eq!(WitnessProgramKind::from_script_pubkey(&ScriptPubKeyBuf::new()), None);
+    }
 }
@@ -10,26 +10,33 @@
 //!
 //! This module provides the structures and functions needed to support transactions.
 
+use core::convert::Infallible;
 use core::fmt;
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::{Arbitrary, Unstructured};
 use encoding::CompactSizeEncoder;
 use internals::{const_casts, write_err, ToU64};
-use io::{BufRead, Write};
+use io::{BufRead, Read as _, Write};
 
 use super::Weight;
-use crate::consensus::{self, encode, Decodable, Encodable};
+use crate::consensus::{self, encode, Decodable, Encodable, ReadExt as _};
+use crate::crypto::ecdsa;
+use crate::crypto::key::{CompressedPublicKey, PublicKey, XOnlyPublicKey};
 use crate::locktime::absolute::{self, Height, MedianTimePast};
-use crate::prelude::{Borrow, Vec};
+use crate::policy::DustPolicy;
+use crate::prelude::{Borrow, String, Vec};
+use crate::random::RandomSource;
 use crate::script::{
-    RedeemScript, ScriptExt as _, ScriptExtPriv as _, ScriptPubKey, ScriptPubKeyBuf,
-    ScriptPubKeyExt as _, WitnessScript,
+    Instruction, RedeemScript, Script, ScriptExt as _, ScriptExtPriv as _, ScriptPubKey,
+    ScriptPubKeyBuf, ScriptPubKeyExt as _, ScriptSet, ScriptSigBuf, ScriptSigExt as _,
+    WitnessScript,
 };
 #[cfg(doc)]
 use crate::sighash::{EcdsaSighashType, TapSighashType};
-use crate::witness::Witness;
-use crate::{internal_macros, Amount, FeeRate, Sequence, SignedAmount};
+use crate::taproot::{self, LeafScript, TapLeafHash};
+use crate::witness::{Witness, WitnessExt as _};
+use crate::{internal_macros, Amount, FeeRate, Sequence, SignedAmount, TapScript};
 
 #[rustfmt::skip]            // Keep public re-exports separate.
 #[doc(no_inline)]
@@ -103,6 +110,45 @@ fn is_null(&self) -> bool { *self == OutPoint::COINBASE_PREVOUT }
     }
 }
 
+/// An unspent transaction output and where it is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Utxo {
+    /// The location of the output.
+    pub out_point: OutPoint,
+    /// The output itself, consensus encoded as hex by `serde`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::consensus::serde::With::<crate::consensus::serde::Hex>")
+    )]
+    pub output: TxOut,
+}
+
+impl Utxo {
+    /// Returns the output at `vout` of `tx`, if there is one.
+    pub fn from_tx(tx: &Transaction, vout: u32) -> Option<Self> {
+        let output = tx.outputs.get(usize::try_from(vout).ok()?)?.clone();
+        Some(Self { out_point: OutPoint { txid: tx.compute_txid(), vout }, output })
+    }
+}
+
+impl Encodable for Utxo {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        Ok(self.out_point.consensus_encode(w)? + self.output.consensus_encode(w)?)
+    }
+}
+
+impl Decodable for Utxo {
+    fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
+        r: &mut R,
+    ) -> Result<Self, encode::Error> {
+        Ok(Self {
+            out_point: Decodable::consensus_decode_from_finite_reader(r)?,
+            output: Decodable::consensus_decode_from_finite_reader(r)?,
+        })
+    }
+}
+
 /// Returns the input base weight.
 ///
 /// Base weight excludes the witness and script.
@@ -185,9 +231,249 @@ fn base_size(&self) -> usize {
         ///
         /// If the size calculation overflows.
         fn total_size(&self) -> usize { self.base_size() + self.witness.size() }
+
+        /// Classifies the spend of this input of the output locked by `prevout_script`.
+        ///
+        /// The signatures and public keys of the returned [`SpendKind`] are extracted from the
+        /// script sig, the witness and the revealed scripts without checking that they satisfy
+        /// `prevout_script`. Signatures are described by their encoding rather than parsed, so
+        /// malformed ones are kept, see [`SpendSignature`]. Keys that do not parse are skipped.
+        fn classify_spend(&self, prevout_script: &ScriptPubKey) -> SpendKind<'_> {
+            SpendKind::classify(self, prevout_script)
+        }
+    }
+}
+
+/// The kind of spend of a transaction input, see [`TxInExt::classify_spend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SpendKind<'a> {
+    /// A spend of a P2PK output.
+    P2pk {
+        /// The signature, if the script sig pushes one.
+        signature: Option<SpendSignature<'a, ecdsa::SignatureInfo>>,
+    },
+    /// A spend of a P2PKH output.
+    P2pkh {
+        /// The signature, if the script sig pushes one.
+        signature: Option<SpendSignature<'a, ecdsa::SignatureInfo>>,
+        /// The public key, if it parses.
+        public_key: Option<PublicKey>,
+    },
+    /// A spend of a P2SH output with a non-SegWit redeem script.
+    P2sh {
+        /// The redeem script revealed by the script sig.
+        redeem_script: &'a RedeemScript,
+        /// The signatures pushed by the script sig.
+        signatures: Vec<SpendSignature<'a, ecdsa::SignatureInfo>>,
+        /// The public keys pushed by the redeem script.
+        public_keys: Vec<PublicKey>,
+    },
+    /// A spend of a P2WPKH output, or of a P2SH output wrapping one.
+    P2wpkh {
+        /// `true` if the output is P2SH-wrapped.
+        nested: bool,
+        /// The signature, if the witness holds one.
+        signature: Option<SpendSignature<'a, ecdsa::SignatureInfo>>,
+        /// The public key, if it parses.
+        public_key: Option<CompressedPublicKey>,
+    },
+    /// A spend of a P2WSH output, or of a P2SH output wrapping one.
+    P2wsh {
+        /// `true` if the output is P2SH-wrapped.
+        nested: bool,
+        /// The witness script revealed by the witness.
+        witness_script: &'a WitnessScript,
+        /// The signatures in the witness.
+        signatures: Vec<SpendSignature<'a, ecdsa::SignatureInfo>>,
+        /// The public keys pushed by the witness script.
+        public_keys: Vec<PublicKey>,
+    },
+    /// A key path spend of a P2TR output.
+    TaprootKeyPath {
+        /// The signature, if the witness holds one of a valid length.
+        signature: Option<SpendSignature<'a, taproot::SignatureInfo>>,
+    },
+    /// A script path spend of a P2TR output.
+    TaprootScriptPath {
+        /// The leaf script revealed by the witness.
+        leaf_script: LeafScript<&'a TapScript>,
+        /// The hash of the leaf.
+        leaf_hash: TapLeafHash,
+        /// The signatures in the witness.
+        signatures: Vec<SpendSignature<'a, taproot::SignatureInfo>>,
+        /// The x-only public keys pushed by the leaf script.
+        public_keys: Vec<XOnlyPublicKey>,
+    },
+    /// A spend of a P2A output.
+    P2a,
+    /// A spend of any other output, such as bare multisig, that does not involve a witness.
+    Bare {
+        /// The signatures pushed by the script sig.
+        signatures: Vec<SpendSignature<'a, ecdsa::SignatureInfo>>,
+        /// The public keys pushed by the output script.
+        public_keys: Vec<PublicKey>,
+    },
+    /// The input does not fit the output, for example a P2SH spend without a redeem script, or
+    /// the output is a witness program of an unknown version.
+    Unknown,
+}
+
+/// A signature of a [`SpendKind`], as pushed by the script sig or found in the witness.
+///
+/// `I` is [`ecdsa::SignatureInfo`] or [`taproot::SignatureInfo`]. Where the position of the
+/// signature is not fixed, for example in the witness of a P2WSH spend, the elements that start
+/// like a DER encoding or are 64 or 65 bytes long respectively are taken to be signatures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendSignature<'a, I> {
+    /// The serialized signature, including the sighash type byte if there is one.
+    pub bytes: &'a [u8],
+    /// The encoding of the signature.
+    pub info: I,
+}
+
+impl SpendSignature<'_, ecdsa::SignatureInfo> {
+    /// Parses the signature, see [`ecdsa::Signature::from_slice`].
+    pub fn to_signature(&self) -> Result<ecdsa::Signature, ecdsa::DecodeError> {
+        ecdsa::Signature::from_slice(self.bytes)
+    }
+}
+
+impl SpendSignature<'_, taproot::SignatureInfo> {
+    /// Parses the signature, see [`taproot::Signature::from_slice`].
+    pub fn to_signature(&self) -> Result<taproot::Signature, taproot::SigFromSliceError> {
+        taproot::Signature::from_slice(self.bytes)
+    }
+}
+
+impl<'a> SpendKind<'a> {
+    fn classify(input: &'a TxIn, prevout_script: &ScriptPubKey) -> Self {
+        let witness = &input.witness;
+        if prevout_script.is_p2pk() {
+            SpendKind::P2pk {
+                signature: pushes(&input.script_sig).next().and_then(ecdsa_signature),
+            }
+        } else if prevout_script.is_p2pkh() {
+            let mut pushes = pushes(&input.script_sig);
+            SpendKind::P2pkh {
+                signature: pushes.next().and_then(ecdsa_signature),
+                public_key: pushes.next().and_then(|key| PublicKey::from_slice(key).ok()),
+            }
+        } else if prevout_script.is_p2sh() {
+            let Some(redeem_script) = input.script_sig.redeem_script() else {
+                return SpendKind::Unknown;
+            };
+            if redeem_script.is_p2wpkh() {
+                Self::p2wpkh(witness, true)
+            } else if redeem_script.is_p2wsh() {
+                Self::p2wsh(witness, true)
+            } else {
+                let count = pushes(&input.script_sig).count();
+                SpendKind::P2sh {
+                    redeem_script,
+                    signatures: pushes(&input.script_sig)
+                        .take(count - 1)
+                        .filter_map(likely_ecdsa_signature)
+                        .collect(),
+                    public_keys: public_keys(redeem_script),
+                }
+            }
+        } else if prevout_script.is_p2wpkh() {
+            Self::p2wpkh(witness, false)
+        } else if prevout_script.is_p2wsh() {
+            Self::p2wsh(witness, false)
+        } else if prevout_script.is_p2tr() {
+            Self::p2tr(witness)
+        } else if prevout_script.is_p2a() {
+            SpendKind::P2a
+        } else if prevout_script.is_witness_program() {
+            SpendKind::Unknown
+        } else {
+            SpendKind::Bare {
+                signatures: pushes(&input.script_sig).filter_map(likely_ecdsa_signature).collect(),
+                public_keys: public_keys(prevout_script),
+            }
+        }
+    }
+
+    fn p2wpkh(witness: &'a Witness, nested: bool) -> Self {
+        SpendKind::P2wpkh {
+            nested,
+            signature: witness.get(0).and_then(ecdsa_signature),
+            public_key: witness.get(1).and_then(|key| CompressedPublicKey::from_slice(key).ok()),
+        }
+    }
+
+    fn p2wsh(witness: &'a Witness, nested: bool) -> Self {
+        let Some(witness_script) = witness.witness_script() else { return SpendKind::Unknown };
+        SpendKind::P2wsh {
+            nested,
+            witness_script,
+            signatures: witness
+                .iter()
+                .take(witness.len() - 1)
+                .filter_map(likely_ecdsa_signature)
+                .collect(),
+            public_keys: public_keys(witness_script),
+        }
+    }
+
+    fn p2tr(witness: &'a Witness) -> Self {
+        let Some(leaf_script) = witness.taproot_leaf_script() else {
+            return match witness.get(0) {
+                Some(signature) =>
+                    SpendKind::TaprootKeyPath { signature: taproot_signature(signature) },
+                None => SpendKind::Unknown,
+            };
+        };
+        // The stack is followed by the leaf script, the control block and possibly the annex.
+        let stack_len = witness.len() - 2 - usize::from(witness.taproot_annex().is_some());
+        SpendKind::TaprootScriptPath {
+            leaf_script,
+            leaf_hash: TapLeafHash::from_script(leaf_script.script, leaf_script.version),
+            signatures: witness.iter().take(stack_len).filter_map(taproot_signature).collect(),
+            public_keys: push_data(leaf_script.script)
+                .filter_map(|key| XOnlyPublicKey::from_byte_array(key.try_into().ok()?).ok())
+                .collect(),
+        }
     }
 }
 
+/// Returns the data pushed by `script`, stopping at the first opcode that does not push data.
+fn pushes<T>(script: &Script<T>) -> impl Iterator<Item = &[u8]> {
+    script.instructions().map_while(|instruction| match instruction {
+        Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes()),
+        _ => None,
+    })
+}
+
+/// Returns all data pushed by `script`, skipping other opcodes.
+fn push_data<T>(script: &Script<T>) -> impl Iterator<Item = &[u8]> {
+    script.instructions().filter_map(|instruction| match instruction {
+        Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes()),
+        _ => None,
+    })
+}
+
+fn ecdsa_signature(bytes: &[u8]) -> Option<SpendSignature<'_, ecdsa::SignatureInfo>> {
+    ecdsa::SignatureInfo::inspect(bytes).map(|info| SpendSignature { bytes, info })
+}
+
+/// Returns the signature if `bytes` starts like a DER encoding, for elements that may be other
+/// data.
+fn likely_ecdsa_signature(bytes: &[u8]) -> Option<SpendSignature<'_, ecdsa::SignatureInfo>> {
+    ecdsa_signature(bytes).filter(|signature| signature.info.r_len.is_some())
+}
+
+fn taproot_signature(bytes: &[u8]) -> Option<SpendSignature<'_, taproot::SignatureInfo>> {
+    taproot::SignatureInfo::inspect(bytes).map(|info| SpendSignature { bytes, info })
+}
+
+/// Returns the public keys pushed by `script`.
+fn public_keys<T>(script: &Script<T>) -> Vec<PublicKey> {
+    push_data(script).filter_map(|key| PublicKey::from_slice(key).ok()).collect()
+}
+
 internal_macros::define_extension_trait! {
     /// Extension functionality for the [`TxOut`] type.
     pub trait TxOutExt impl for TxOut {
@@ -242,6 +528,13 @@ fn minimal_non_dust_custom(script_pubkey: ScriptPubKeyBuf, dust_relay_fee: FeeRa
         {
             Some(TxOut { amount: script_pubkey.minimal_non_dust_custom(dust_relay_fee)?, script_pubkey })
         }
+
+        /// Returns `true` if this output is dust under `policy`.
+        ///
+        /// Use [`DustPolicy::DEFAULT`] for the policy of Bitcoin Core.
+        fn is_dust(&self, policy: &DustPolicy) -> bool {
+            policy.is_dust(&self.script_pubkey, self.amount)
+        }
     }
 }
 
@@ -339,9 +632,30 @@ pub trait TransactionExt: sealed::Sealed {
 
     /// Returns `true` if this transactions nLockTime is enabled ([BIP-0065]).
     ///
+    /// This is the opposite of [`Self::is_explicitly_final`].
+    ///
     /// [BIP-0065]: https://github.com/bitcoin/bips/blob/master/bip-0065.mediawiki
     fn is_lock_time_enabled(&self) -> bool;
 
+    /// Returns the BIP-0125 replaceability signaled by each input and by the whole transaction.
+    ///
+    /// The same warning as for [`Self::is_explicitly_rbf`] applies.
+    fn rbf_signaling(&self) -> RbfSignaling;
+
+    /// Returns `true` if all inputs have the final sequence number.
+    ///
+    /// Such a transaction neither signals replaceability nor enables its lock time. This is the
+    /// opposite of [`Self::is_lock_time_enabled`] and agrees with
+    /// [`RbfSignaling::is_explicitly_final`].
+    fn is_explicitly_final(&self) -> bool;
+
+    /// Returns a copy of the transaction with all script sigs and witnesses removed.
+    ///
+    /// Everything else, including the version, the lock time and the sequence numbers that
+    /// signal replaceability and enable the lock time, is kept so that the copy can be signed
+    /// again, for example after changing the outputs to bump the fee.
+    fn clone_unsigned(&self) -> Transaction;
+
     /// Returns an iterator over lengths of `script_pubkey`s in the outputs.
     ///
     /// This is useful in combination with [`predict_weight`] if you have the transaction already
@@ -351,6 +665,11 @@ pub trait TransactionExt: sealed::Sealed {
 
     /// Counts the total number of sigops.
     ///
+    /// This is the sigop cost as computed by Bitcoin Core's `GetTransactionSigOpCost`: legacy
+    /// sigops in the scriptSigs and scriptPubkeys and sigops in P2SH redeemScripts count four
+    /// times, sigops in witness scripts count once. The total of a block is limited to
+    /// [`MAX_BLOCK_SIGOPS_COST`](crate::constants::MAX_BLOCK_SIGOPS_COST).
+    ///
     /// This value is for pre-Taproot transactions only.
     ///
     /// > In Taproot, a different mechanism is used. Instead of having a global per-block limit,
@@ -370,6 +689,67 @@ fn total_sigop_cost<S>(&self, spent: S) -> usize
 
     /// Returns a reference to the output at `output_index` if it exists.
     fn tx_out(&self, output_index: usize) -> Result<&TxOut, OutputsIndexError>;
+
+    /// Returns `true` if any output of this transaction pays to a script in `scripts`.
+    fn pays_to_any(&self, scripts: &ScriptSet) -> bool;
+
+    /// Returns the weight of this transaction split up into the weight of each input, each output
+    /// and the remaining fields.
+    ///
+    /// The components add up to [`weight`](Self::weight), this is useful to attribute fees to the
+    /// parties contributing inputs and outputs to a transaction (e.g. in a coinjoin).
+    fn weight_breakdown(&self) -> WeightBreakdown;
+
+    /// Checks that the inputs of this transaction pay for its outputs and returns the fee.
+    ///
+    /// The `spent` parameter looks up the output being spent by each input, see
+    /// [`total_sigop_cost`](Self::total_sigop_cost). Like Bitcoin Core this checks that the values
+    /// of the inputs and of the outputs sum to at most [`Amount::MAX_MONEY`] and that the inputs
+    /// are worth at least as much as the outputs. Amounts are never larger than `MAX_MONEY`, so
+    /// individual values are valid by construction.
+    ///
+    /// Coinbase transactions do not spend any outputs and are rejected with
+    /// [`ValueBalanceError::MissingPrevout`] unless `spent` returns a value for the null outpoint.
+    ///
+    /// # Errors
+    ///
+    /// If an output being spent is not found, a sum exceeds `MAX_MONEY` or the outputs are worth
+    /// more than the inputs.
+    fn check_value_balance<S>(&self, spent: S) -> Result<Amount, ValueBalanceError>
+    where
+        S: FnMut(&OutPoint) -> Option<TxOut>;
+
+    /// Returns a JSON representation of this transaction for logs and debugging.
+    ///
+    /// Besides the fields of the transaction this includes the TXID and WTXID, the sizes, the
+    /// scripts decoded as assembly and the ECDSA signatures found in the scriptSig pushes and
+    /// in the witness with their sighash type. The output is stable, members always appear in
+    /// the same order and one per line, but it is not meant to be parsed back.
+    fn to_debug_json(&self) -> String;
+
+    /// Returns a summary of this transaction for CLIs and logs.
+    ///
+    /// The `spent` parameter looks up the output being spent by each input, see
+    /// [`total_sigop_cost`](Self::total_sigop_cost). It is called once per input, the fee and fee
+    /// rate are only known if it finds all of them.
+    fn summary<S>(&self, spent: S) -> TxSummary<'_>
+    where
+        S: FnMut(&OutPoint) -> Option<TxOut>;
+
+    /// Decodes a transaction from `r` into `self`, reusing the allocations of `self`.
+    ///
+    /// The result is the same as assigning [`Decodable::consensus_decode`] but the capacity of
+    /// the input, output and witness buffers is kept. Decoding many transactions in sequence into
+    /// the same value saves reallocating these vectors. The scriptSig and scriptPubkey of every
+    /// input and output are still allocated anew.
+    ///
+    /// # Errors
+    ///
+    /// If decoding fails, `self` is left in an unspecified but valid state.
+    fn consensus_decode_into<R: BufRead + ?Sized>(
+        &mut self,
+        r: &mut R,
+    ) -> Result<(), encode::Error>;
 }
 
 impl TransactionExt for Transaction {
@@ -437,6 +817,32 @@ fn is_absolute_timelock_satisfied(&self, height: Height, time: MedianTimePast) -
 
     fn is_lock_time_enabled(&self) -> bool { self.inputs.iter().any(|i| i.enables_lock_time()) }
 
+    fn rbf_signaling(&self) -> RbfSignaling {
+        RbfSignaling {
+            inputs: self.inputs.iter().map(|input| InputRbfSignal::from(input.sequence)).collect(),
+        }
+    }
+
+    fn is_explicitly_final(&self) -> bool { !self.is_lock_time_enabled() }
+
+    fn clone_unsigned(&self) -> Transaction {
+        Self {
+            version: self.version,
+            lock_time: self.lock_time,
+            inputs: self
+                .inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.previous_output,
+                    script_sig: ScriptSigBuf::new(),
+                    sequence: input.sequence,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            outputs: self.outputs.clone(),
+        }
+    }
+
     fn script_pubkey_lens(&self) -> TxOutToScriptPubkeyLengthIter<'_> {
         TxOutToScriptPubkeyLengthIter { inner: self.outputs.iter() }
     }
@@ -465,6 +871,76 @@ fn tx_out(&self, output_index: usize) -> Result<&TxOut, OutputsIndexError> {
             .get(output_index)
             .ok_or(IndexOutOfBoundsError { index: output_index, length: self.outputs.len() }.into())
     }
+
+    fn pays_to_any(&self, scripts: &ScriptSet) -> bool {
+        self.outputs.iter().any(|output| scripts.contains(&output.script_pubkey))
+    }
+
+    fn weight_breakdown(&self) -> WeightBreakdown {
+        let uses_segwit = self.uses_segwit_serialization();
+
+        let overhead_size = 4 // Serialized length of a u32 for the version number.
+            + CompactSizeEncoder::encoded_size(self.inputs.len())
+            + CompactSizeEncoder::encoded_size(self.outputs.len())
+            + absolute::LockTime::SIZE;
+        let overhead = ComponentWeight {
+            non_witness: Weight::from_vb(overhead_size.to_u64()).expect("overhead is small"),
+            // 1 byte for the marker and 1 for the flag.
+            witness: if uses_segwit { Weight::from_wu(2) } else { Weight::ZERO },
+        };
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| ComponentWeight {
+                non_witness: input.legacy_weight(),
+                witness: if uses_segwit {
+                    Weight::from_wu(input.witness.size().to_u64())
+                } else {
+                    Weight::ZERO
+                },
+            })
+            .collect();
+        let outputs = self.outputs.iter().map(TxOutExt::weight).collect();
+
+        WeightBreakdown { overhead, inputs, outputs }
+    }
+
+    fn check_value_balance<S>(&self, mut spent: S) -> Result<Amount, ValueBalanceError>
+    where
+        S: FnMut(&OutPoint) -> Option<TxOut>,
+    {
+        let mut inputs = Amount::ZERO;
+        for (index, input) in self.inputs.iter().enumerate() {
+            let out_point = input.previous_output;
+            let prevout =
+                spent(&out_point).ok_or(ValueBalanceError::MissingPrevout { index, out_point })?;
+            inputs = inputs.checked_add(prevout.amount).ok_or(ValueBalanceError::InputsOverflow)?;
+        }
+        let mut outputs = Amount::ZERO;
+        for output in &self.outputs {
+            outputs =
+                outputs.checked_add(output.amount).ok_or(ValueBalanceError::OutputsOverflow)?;
+        }
+        inputs.checked_sub(outputs).ok_or(ValueBalanceError::InsufficientInputs { inputs, outputs })
+    }
+
+    fn to_debug_json(&self) -> String { crate::debug_json::transaction(self) }
+
+    fn summary<S>(&self, mut spent: S) -> TxSummary<'_>
+    where
+        S: FnMut(&OutPoint) -> Option<TxOut>,
+    {
+        let prevouts = self.inputs.iter().map(|input| spent(&input.previous_output)).collect();
+        TxSummary { tx: self, prevouts }
+    }
+
+    fn consensus_decode_into<R: BufRead + ?Sized>(
+        &mut self,
+        r: &mut R,
+    ) -> Result<(), encode::Error> {
+        let mut r = r.take(encode::MAX_VEC_SIZE.to_u64());
+        decode_into_from_finite_reader(self, &mut r, &mut Vec::new())
+    }
 }
 
 /// Iterates over transaction outputs and for each output yields the length of the scriptPubkey.
@@ -479,6 +955,182 @@ impl Iterator for TxOutToScriptPubkeyLengthIter<'_> {
     fn next(&mut self) -> Option<usize> { self.inner.next().map(|txout| txout.script_pubkey.len()) }
 }
 
+/// The weight of a transaction split up by component, see [`TransactionExt::weight_breakdown`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WeightBreakdown {
+    /// The weight of the version, lock time, input and output counts and (if the transaction is
+    /// serialized with witnesses) the SegWit marker and flag.
+    pub overhead: ComponentWeight,
+    /// The weight of each input.
+    pub inputs: Vec<ComponentWeight>,
+    /// The weight of each output, outputs contain no witness data.
+    pub outputs: Vec<Weight>,
+}
+
+impl WeightBreakdown {
+    /// Returns the weight of the whole transaction.
+    pub fn total(&self) -> Weight { self.non_witness() + self.witness() }
+
+    /// Returns the weight of all non-witness data, four times the base size.
+    pub fn non_witness(&self) -> Weight {
+        self.overhead.non_witness
+            + self.inputs.iter().map(|input| input.non_witness).sum::<Weight>()
+            + self.outputs.iter().copied().sum::<Weight>()
+    }
+
+    /// Returns the weight of all witness data, including the SegWit marker and flag.
+    pub fn witness(&self) -> Weight {
+        self.overhead.witness + self.inputs.iter().map(|input| input.witness).sum::<Weight>()
+    }
+}
+
+/// The weight of a part of a transaction, split into non-witness and witness data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ComponentWeight {
+    /// The weight of the non-witness data, four weight units per byte.
+    pub non_witness: Weight,
+    /// The weight of the witness data, one weight unit per byte.
+    pub witness: Weight,
+}
+
+impl ComponentWeight {
+    /// Returns the combined weight of the non-witness and witness data.
+    pub fn total(&self) -> Weight { self.non_witness + self.witness }
+}
+
+/// The BIP-0125 replaceability signaled by an input through its sequence number.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InputRbfSignal {
+    /// The sequence number is below `0xfffffffe`, the input signals replaceability.
+    Replaceable,
+    /// The sequence number is `0xfffffffe`, the input enables the lock time of the transaction
+    /// without signaling replaceability.
+    NonReplaceable,
+    /// The sequence number is `0xffffffff`, the input neither signals replaceability nor enables
+    /// the lock time.
+    Final,
+}
+
+impl InputRbfSignal {
+    /// Returns `true` if the input signals replaceability.
+    pub fn is_replaceable(self) -> bool { self == Self::Replaceable }
+}
+
+impl From<Sequence> for InputRbfSignal {
+    fn from(sequence: Sequence) -> Self {
+        if sequence.is_rbf() {
+            Self::Replaceable
+        } else if sequence.is_final() {
+            Self::Final
+        } else {
+            Self::NonReplaceable
+        }
+    }
+}
+
+/// The BIP-0125 replaceability signaled by a transaction, see [`TransactionExt::rbf_signaling`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RbfSignaling {
+    inputs: Vec<InputRbfSignal>,
+}
+
+impl RbfSignaling {
+    /// Returns the signal of each input, in input order.
+    pub fn inputs(&self) -> &[InputRbfSignal] { &self.inputs }
+
+    /// Returns the indices of the inputs signaling replaceability.
+    pub fn replaceable_inputs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.inputs.iter().enumerate().filter(|(_, signal)| signal.is_replaceable()).map(|(i, _)| i)
+    }
+
+    /// Returns `true` if the transaction signals replaceability, which it does if any of its
+    /// inputs does.
+    pub fn is_explicitly_rbf(&self) -> bool {
+        self.inputs.iter().any(|signal| signal.is_replaceable())
+    }
+
+    /// Returns `true` if all inputs are final.
+    ///
+    /// The transaction does not signal replaceability and its lock time is disabled, so it can be
+    /// mined regardless of the lock time, see [`TransactionExt::is_lock_time_enabled`].
+    pub fn is_explicitly_final(&self) -> bool {
+        self.inputs.iter().all(|signal| *signal == InputRbfSignal::Final)
+    }
+}
+
+/// A summary of a transaction for display, see [`TransactionExt::summary`].
+///
+/// The [`Display`](fmt::Display) implementation prints the sizes, the RBF signal, one line per
+/// input and output and the fee. The layout is meant to be read by people, it may change and
+/// should not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxSummary<'a> {
+    tx: &'a Transaction,
+    /// The output spent by each input, if found.
+    prevouts: Vec<Option<TxOut>>,
+}
+
+impl TxSummary<'_> {
+    /// Returns the output spent by the input at `index`, if it was found.
+    pub fn prevout(&self, index: usize) -> Option<&TxOut> { self.prevouts.get(index)?.as_ref() }
+
+    /// Returns the fee of the transaction.
+    ///
+    /// # Errors
+    ///
+    /// If an output being spent was not found or the value balance is invalid, see
+    /// [`TransactionExt::check_value_balance`].
+    pub fn fee(&self) -> Result<Amount, ValueBalanceError> {
+        let mut prevouts = self.prevouts.iter();
+        self.tx.check_value_balance(|_| prevouts.next().cloned().flatten())
+    }
+
+    /// Returns the fee rate of the transaction, `None` if the fee is not known.
+    pub fn fee_rate(&self) -> Option<FeeRate> {
+        self.fee().ok()?.div_by_weight_floor(self.tx.weight()).ok()
+    }
+}
+
+impl fmt::Display for TxSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tx = self.tx;
+        writeln!(f, "txid:      {}", tx.compute_txid())?;
+        writeln!(f, "version:   {}", tx.version)?;
+        writeln!(f, "lock time: {}", tx.lock_time)?;
+        writeln!(f, "size:      {} B, {} vB, {:#}", tx.total_size(), tx.vsize(), tx.weight())?;
+        let rbf = if tx.is_explicitly_rbf() { "signaled" } else { "not signaled" };
+        writeln!(f, "rbf:       {}", rbf)?;
+
+        writeln!(f, "inputs:    {}", tx.inputs.len())?;
+        for (index, (input, prevout)) in tx.inputs.iter().zip(&self.prevouts).enumerate() {
+            write!(f, "  {:>3} {} ", index, input.previous_output)?;
+            match prevout {
+                Some(prevout) => write!(f, "{:>18}", prevout.amount)?,
+                None if input.previous_output.is_null() => write!(f, "{:>18}", "coinbase")?,
+                None => write!(f, "{:>18}", "unknown")?,
+            }
+            writeln!(f, " sequence {:#010x}", input.sequence.to_consensus_u32())?;
+        }
+
+        writeln!(f, "outputs:   {}", tx.outputs.len())?;
+        for (index, output) in tx.outputs.iter().enumerate() {
+            writeln!(f, "  {:>3} {:>18} {}", index, output.amount, output.script_pubkey)?;
+        }
+
+        match self.fee() {
+            Ok(fee) => {
+                write!(f, "fee:       {}", fee)?;
+                if let Some(fee_rate) = self.fee_rate() {
+                    write!(f, " ({:.3})", fee_rate)?;
+                }
+                Ok(())
+            }
+            Err(ValueBalanceError::MissingPrevout { .. }) => f.write_str("fee:       unknown"),
+            Err(e) => write!(f, "fee:       invalid, {}", e),
+        }
+    }
+}
+
 trait TransactionExtPriv {
     /// Gets the sigop count.
     ///
@@ -522,7 +1174,8 @@ fn count_p2sh_sigops<S>(&self, mut spent: S) -> usize
     {
         fn count_sigops(prevout: &TxOut, input: &TxIn) -> usize {
             let mut count: usize = 0;
-            if prevout.script_pubkey.is_p2sh() {
+            // Like Bitcoin Core, a scriptSig that is not push only contributes no sigops.
+            if prevout.script_pubkey.is_p2sh() && input.script_sig.is_push_only() {
                 if let Some(redeem) = input.script_sig.last_pushdata() {
                     count = count
                         .saturating_add(RedeemScript::from_bytes(redeem.as_bytes()).count_sigops());
@@ -642,6 +1295,60 @@ impl From<IndexOutOfBoundsError> for OutputsIndexError {
     fn from(e: IndexOutOfBoundsError) -> Self { Self(e) }
 }
 
+/// Error checking the value balance of a transaction, see
+/// [`TransactionExt::check_value_balance`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValueBalanceError {
+    /// The output spent by an input was not found.
+    MissingPrevout {
+        /// The index of the input.
+        index: usize,
+        /// The outpoint spent by the input.
+        out_point: OutPoint,
+    },
+    /// The values of the spent outputs sum to more than [`Amount::MAX_MONEY`].
+    InputsOverflow,
+    /// The values of the outputs sum to more than [`Amount::MAX_MONEY`].
+    OutputsOverflow,
+    /// The outputs are worth more than the inputs.
+    InsufficientInputs {
+        /// The total value of the inputs.
+        inputs: Amount,
+        /// The total value of the outputs.
+        outputs: Amount,
+    },
+}
+
+impl From<Infallible> for ValueBalanceError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ValueBalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::MissingPrevout { index, out_point } =>
+                write!(f, "output {} spent by input {} not found", out_point, index),
+            Self::InputsOverflow => f.write_str("input values sum to more than MAX_MONEY"),
+            Self::OutputsOverflow => f.write_str("output values sum to more than MAX_MONEY"),
+            Self::InsufficientInputs { inputs, outputs } =>
+                write!(f, "inputs worth {} do not pay for outputs worth {}", inputs, outputs),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValueBalanceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::MissingPrevout { .. }
+            | Self::InputsOverflow
+            | Self::OutputsOverflow
+            | Self::InsufficientInputs { .. } => None,
+        }
+    }
+}
+
 /// Error attempting to do an out of bounds access on a vector.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -793,6 +1500,108 @@ fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
     }
 }
 
+/// Decodes a transaction into `tx`, reusing its allocations and `scratch` for witness elements.
+pub(crate) fn decode_into_from_finite_reader<R: BufRead + ?Sized>(
+    tx: &mut Transaction,
+    r: &mut R,
+    scratch: &mut Vec<u8>,
+) -> Result<(), encode::Error> {
+    tx.version = Decodable::consensus_decode_from_finite_reader(r)?;
+    let mut len = r.read_compact_size()?;
+    // SegWit
+    let segwit = len == 0;
+    if segwit {
+        match u8::consensus_decode_from_finite_reader(r)? {
+            // BIP-0144 input witnesses
+            1 => len = r.read_compact_size()?,
+            // We don't support anything else
+            x => return Err(encode::ParseError::UnsupportedSegwitFlag(x).into()),
+        }
+    }
+
+    for i in 0..len {
+        let previous_output = Decodable::consensus_decode_from_finite_reader(r)?;
+        let script_sig = Decodable::consensus_decode_from_finite_reader(r)?;
+        let sequence = Decodable::consensus_decode_from_finite_reader(r)?;
+        // Cast ok, the inputs before `i` are in the vector.
+        match tx.inputs.get_mut(i as usize) {
+            Some(input) => {
+                input.previous_output = previous_output;
+                input.script_sig = script_sig;
+                input.sequence = sequence;
+            }
+            None => tx.inputs.push(TxIn {
+                previous_output,
+                script_sig,
+                sequence,
+                witness: Witness::new(),
+            }),
+        }
+    }
+    tx.inputs.truncate(len as usize);
+
+    let len = r.read_compact_size()?;
+    for i in 0..len {
+        let output = Decodable::consensus_decode_from_finite_reader(r)?;
+        match tx.outputs.get_mut(i as usize) {
+            Some(slot) => *slot = output,
+            None => tx.outputs.push(output),
+        }
+    }
+    tx.outputs.truncate(len as usize);
+
+    if segwit {
+        for input in &mut tx.inputs {
+            decode_witness_into(&mut input.witness, r, scratch)?;
+        }
+        if !tx.inputs.is_empty() && tx.inputs.iter().all(|input| input.witness.is_empty()) {
+            return Err(consensus::parse_failed_error("witness flag set but no witnesses present"));
+        }
+    } else {
+        for input in &mut tx.inputs {
+            input.witness.clear();
+        }
+    }
+    tx.lock_time = Decodable::consensus_decode_from_finite_reader(r)?;
+    Ok(())
+}
+
+/// Decodes a witness into `witness`, reusing its allocation and `scratch` for the elements.
+fn decode_witness_into<R: BufRead + ?Sized>(
+    witness: &mut Witness,
+    r: &mut R,
+    scratch: &mut Vec<u8>,
+) -> Result<(), encode::Error> {
+    witness.clear();
+    let len = r.read_compact_size()?;
+    for _ in 0..len {
+        let element_len = r.read_compact_size()? as usize;
+        encode::read_bytes_into_from_finite_reader(r, element_len, scratch)?;
+        witness.push(&scratch[..]);
+    }
+    Ok(())
+}
+
+/// Returns a lock time discouraging fee sniping, like the wallet of Bitcoin Core.
+///
+/// The lock time is the height of the chain tip, so the transaction cannot be mined in a block
+/// reorganizing the tip away. One in ten times it is up to 99 blocks earlier instead, so
+/// transactions that are delayed before being broadcast do not stand out.
+///
+/// Set the lock time only if `tip` is the current tip, and give at least one input a non-final
+/// sequence number for it to take effect.
+pub fn anti_fee_sniping_lock_time<R: RandomSource + ?Sized>(
+    tip: Height,
+    rng: &mut R,
+) -> absolute::LockTime {
+    let mut height = tip.to_u32();
+    if rng.random_below(10) == 0 {
+        let delay = u32::try_from(rng.random_below(100)).expect("less than 100");
+        height = height.saturating_sub(delay);
+    }
+    absolute::LockTime::from(Height::from_u32(height).expect("at most the height of the tip"))
+}
+
 /// Computes the value of an output accounting for the cost of spending it.
 ///
 /// The effective value is the value of an output value minus the amount to spend it. That is, the
@@ -1280,8 +2089,9 @@ mod tests {
     use super::*;
     use crate::consensus::encode::{deserialize, serialize};
     use crate::constants::WITNESS_SCALE_FACTOR;
+    use crate::opcodes::all::OP_NOP;
     use crate::parse_int;
-    use crate::script::ScriptSigBuf;
+    use crate::script::{Instruction, ScriptSigBuf};
     use crate::sighash::EcdsaSighashType;
 
     const SOME_TX: &str = "0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000";
@@ -1474,6 +2284,33 @@ fn segwit_transaction() {
         assert_eq!(tx_without_witness.total_size(), expected_strippedsize);
     }
 
+    #[test]
+    fn consensus_decode_into() {
+        let segwit = hex!(
+            "02000000000101595895ea20179de87052b4046dfe6fd515860505d6511a9004cf12a1f93cac7c01000000\
+            00ffffffff01deb807000000000017a9140f3444e271620c736808aa7b33e370bd87cb5a078702483045022\
+            100fb60dad8df4af2841adc0346638c16d0b8035f5e3f3753b88db122e70c79f9370220756e6633b17fd271\
+            0e626347d28d60b0a2d6cbb41de51740644b9fb3ba7751040121028fa937ca8cba2197a37c007176ed89410\
+            55d3bcb8627d085e94553e62f057dcc00000000"
+        );
+        let legacy = Vec::from_hex(SOME_TX).unwrap();
+
+        // Start with more inputs and outputs than either transaction has.
+        let mut tx = deserialize::<Transaction>(&segwit).unwrap();
+        tx.inputs.push(tx.inputs[0].clone());
+        tx.outputs.push(tx.outputs[0].clone());
+        for bytes in [&legacy, &segwit[..], &legacy, &segwit[..]] {
+            tx.consensus_decode_into(&mut &bytes[..]).unwrap();
+            assert_eq!(tx, deserialize::<Transaction>(bytes).unwrap());
+        }
+
+        // Witness flag set but no witnesses.
+        let no_witness = hex!("0100000000010100000000000000000000000000000000000000000000000000000000000000000000000000ffffffff0000000000000000");
+        assert!(deserialize::<Transaction>(&no_witness).is_err());
+        assert!(tx.consensus_decode_into(&mut &no_witness[..]).is_err());
+        assert!(tx.consensus_decode_into(&mut &segwit[..segwit.len() - 1]).is_err());
+    }
+
     // We temporarily abuse `Transaction` for testing consensus serde adapter.
     #[test]
     #[cfg(feature = "serde")]
@@ -1827,6 +2664,45 @@ fn txin_txout_weight() {
         }
     }
 
+    #[test]
+    fn weight_breakdown() {
+        // [(is_segwit, tx_hex)], the same transactions as in `txin_txout_weight`.
+        let txs = [
+                // one SegWit input (P2WPKH) and two legacy inputs (P2PKH)
+                (true, "010000000001036b6b6ac7e34e97c53c1cc74c99c7948af2e6aac75d8778004ae458d813456764000000006a473044022001deec7d9075109306320b3754188f81a8236d0d232b44bc69f8309115638b8f02204e17a5194a519cf994d0afeea1268740bdc10616b031a521113681cc415e815c012103488d3272a9fad78ee887f0684cb8ebcfc06d0945e1401d002e590c7338b163feffffffffc75bd7aa6424aee972789ec28ba181254ee6d8311b058d165bd045154d7660b0000000006b483045022100c8641bcbee3e4c47a00417875015d8c5d5ea918fb7e96f18c6ffe51bc555b401022074e2c46f5b1109cd79e39a9aa203eadd1d75356415e51d80928a5fb5feb0efee0121033504b4c6dfc3a5daaf7c425aead4c2dbbe4e7387ce8e6be2648805939ecf7054ffffffff494df3b205cd9430a26f8e8c0dc0bb80496fbc555a524d6ea307724bc7e60eee0100000000ffffffff026d861500000000001976a9145c54ed1360072ebaf56e87693b88482d2c6a101588ace407000000000000160014761e31e2629c6e11936f2f9888179d60a5d4c1f900000247304402201fa38a67a63e58b67b6cfffd02f59121ca1c8a1b22e1efe2573ae7e4b4f06c2b022002b9b431b58f6e36b3334fb14eaecee7d2f06967a77ef50d8d5f90dda1057f0c01210257dc6ce3b1100903306f518ee8fa113d778e403f118c080b50ce079fba40e09a00000000"),
+                // one legacy input (P2PKH)
+                (false, "0100000001c336895d9fa674f8b1e294fd006b1ac8266939161600e04788c515089991b50a030000006a47304402204213769e823984b31dcb7104f2c99279e74249eacd4246dabcf2575f85b365aa02200c3ee89c84344ae326b637101a92448664a8d39a009c8ad5d147c752cbe112970121028b1b44b4903c9103c07d5a23e3c7cf7aeb0ba45ddbd2cfdce469ab197381f195fdffffff040000000000000000536a4c5058325bb7b7251cf9e36cac35d691bd37431eeea426d42cbdecca4db20794f9a4030e6cb5211fabf887642bcad98c9994430facb712da8ae5e12c9ae5ff314127d33665000bb26c0067000bb0bf00322a50c300000000000017a9145ca04fdc0a6d2f4e3f67cfeb97e438bb6287725f8750c30000000000001976a91423086a767de0143523e818d4273ddfe6d9e4bbcc88acc8465003000000001976a914c95cbacc416f757c65c942f9b6b8a20038b9b12988ac00000000"),
+            ];
+
+        for (is_segwit, tx) in &txs {
+            let txin_weight = if *is_segwit { TxIn::segwit_weight } else { TxIn::legacy_weight };
+            let tx: Transaction = deserialize(Vec::from_hex(tx).unwrap().as_slice()).unwrap();
+
+            let breakdown = tx.weight_breakdown();
+            assert_eq!(breakdown.total(), tx.weight());
+            assert_eq!(breakdown.non_witness(), Weight::from_vb(tx.base_size().to_u64()).unwrap());
+            assert_eq!(breakdown.inputs.len(), tx.inputs.len());
+            assert_eq!(breakdown.outputs.len(), tx.outputs.len());
+            for (input, weight) in tx.inputs.iter().zip(&breakdown.inputs) {
+                assert_eq!(weight.total(), txin_weight(input));
+            }
+        }
+    }
+
+    #[test]
+    fn weight_breakdown_empty_transaction() {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![],
+            outputs: vec![],
+        };
+        let breakdown = tx.weight_breakdown();
+        // Zero-input transactions are counted with the SegWit marker and flag, like in `weight`.
+        assert_eq!(breakdown.overhead.witness, Weight::from_wu(2));
+        assert_eq!(breakdown.total(), tx.weight());
+    }
+
     #[test]
     fn tx_sigop_count() {
         let tx_hexes = [
@@ -1975,6 +2851,111 @@ fn return_none(_outpoint: &OutPoint) -> Option<TxOut> { None }
         }
     }
 
+    #[test]
+    fn tx_sigop_count_non_push_only_script_sig() {
+        // 12 sigops (1 P2SH 2-of-3 MS in (3x4), P2SH outs (0)), from `tx_sigop_count`.
+        let tx_hex = "010000000115fe9ec3dc964e41f5267ea26cfe505f202bf3b292627496b04bece84da9\
+            b18903000000fc004730440220442827f1085364bda58c5884cee7b289934083362db6d\
+            fb627dc46f6cdbf5793022078cfa524252c381f2a572f0c41486e2838ca94aa268f2384\
+            d0e515744bf0e1e9014730440220160e49536bb29a49c7626744ee83150174c22fa40d5\
+            8fb4cd554a907a6a7b825022045f6cf148504b334064686795f0968c689e542f475b8ef\
+            5a5fa42383948226a3014c69522103e54bc61efbcb8eeff3a5ab2a92a75272f5f6820e3\
+            8e3d28edb54beb06b86c0862103a553e30733d7a8df6d390d59cc136e2c9d9cf4e808f3\
+            b6ab009beae68dd60822210291c5a54bb8b00b6f72b90af0ac0ecaf78fab026d8eded28\
+            2ad95d4d65db268c953aeffffffff024c4f0d000000000017a9146ebf0484bd5053f727\
+            c755a750aa4c815dfa112887a06b12020000000017a91410065dd50b3a7f299fef3b1c5\
+            3b8216399916ab08700000000";
+        let p2sh = |_: &OutPoint| {
+            Some(
+                deserialize(&hex!(
+                    "cc721b000000000017a91428203c10cc8f18a77412caaa83dabaf62b8fbb0f87"
+                ))
+                .unwrap(),
+            )
+        };
+
+        let mut tx: Transaction = deserialize(&Vec::from_hex(tx_hex).unwrap()).unwrap();
+        assert_eq!(tx.total_sigop_cost(p2sh), 12);
+
+        // A redeem script pushed by a scriptSig that is not push only is not counted.
+        let pushes = tx.inputs[0].script_sig.instructions().map(Result::unwrap);
+        let script_sig = core::iter::once(Instruction::Op(OP_NOP)).chain(pushes).collect();
+        tx.inputs[0].script_sig = script_sig;
+        assert_eq!(tx.total_sigop_cost(p2sh), 0);
+    }
+
+    #[test]
+    fn value_balance() {
+        let tx: Transaction = deserialize(&hex!(SOME_TX)).unwrap();
+        let out_point = tx.inputs[0].previous_output;
+        let spending = |amount| {
+            move |_: &OutPoint| Some(TxOut { amount, script_pubkey: ScriptPubKeyBuf::new() })
+        };
+
+        let fee = tx.check_value_balance(spending(Amount::from_sat_u32(100_010_000)));
+        assert_eq!(fee, Ok(Amount::from_sat_u32(10_000)));
+        assert_eq!(tx.check_value_balance(spending(Amount::ONE_BTC)), Ok(Amount::ZERO));
+        assert_eq!(
+            tx.check_value_balance(spending(Amount::ONE_SAT)),
+            Err(ValueBalanceError::InsufficientInputs {
+                inputs: Amount::ONE_SAT,
+                outputs: Amount::ONE_BTC,
+            })
+        );
+        assert_eq!(
+            tx.check_value_balance(|_| None),
+            Err(ValueBalanceError::MissingPrevout { index: 0, out_point })
+        );
+
+        let mut tx = tx;
+        tx.inputs.push(tx.inputs[0].clone());
+        assert_eq!(
+            tx.check_value_balance(spending(Amount::MAX_MONEY)),
+            Err(ValueBalanceError::InputsOverflow)
+        );
+        tx.outputs.push(TxOut { amount: Amount::MAX_MONEY, ..tx.outputs[0].clone() });
+        assert_eq!(
+            tx.check_value_balance(spending(Amount::ONE_SAT)),
+            Err(ValueBalanceError::OutputsOverflow)
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let tx: Transaction = deserialize(&hex!(SOME_TX)).unwrap();
+        let prevout = TxOut {
+            amount: Amount::from_sat_u32(100_010_000),
+            script_pubkey: ScriptPubKeyBuf::new(),
+        };
+
+        let summary = tx.summary(|_| Some(prevout.clone()));
+        assert_eq!(summary.prevout(0), Some(&prevout));
+        assert_eq!(summary.fee(), Ok(Amount::from_sat_u32(10_000)));
+        assert_eq!(
+            summary.fee_rate(),
+            Amount::from_sat_u32(10_000).div_by_weight_floor(tx.weight()).ok()
+        );
+        let display = summary.to_string();
+        assert!(display.starts_with(
+            "txid:      a6eab3c14ab5272a58a5ba91505ba1a4b6d7a3a9fcbd187b6cd99a7b6d548cb7\n"
+        ));
+        assert!(display.contains("size:      193 B, 193 vB, 772 wu\n"));
+        assert!(display.contains("rbf:       not signaled\n"));
+        assert!(display.contains(":1         1.0001 BTC sequence 0xffffffff\n"));
+        assert!(display.contains("    0              1 BTC OP_DUP OP_HASH160"));
+        assert!(display.ends_with("fee:       0.0001 BTC (51.812 sat/vB)"));
+
+        let summary = tx.summary(|_| None);
+        assert_eq!(summary.fee_rate(), None);
+        assert!(summary.to_string().contains(":1            unknown sequence"));
+        assert!(summary.to_string().ends_with("fee:       unknown"));
+
+        let summary = tx.summary(|_| Some(TxOut { amount: Amount::ONE_SAT, ..prevout.clone() }));
+        assert!(summary.to_string().ends_with(
+            "fee:       invalid, inputs worth 0.00000001 BTC do not pay for outputs worth 1 BTC"
+        ));
+    }
+
     #[test]
     fn weight_predictions() {
         // TXID 3d3381f968e3a73841cba5e73bf47dcea9f25a9f7663c51c81f1db8229a309a0
@@ -2149,4 +3130,223 @@ fn coinbase_assume_methods() {
         assert_eq!(coinbase_owned.compute_txid(), coinbase_tx.compute_txid());
         assert_eq!(coinbase_owned.wtxid(), Wtxid::COINBASE);
     }
+
+    #[test]
+    fn anti_fee_sniping() {
+        use crate::random::DeterministicRng;
+
+        let tip = Height::from_u32(800_000).unwrap();
+        let lock_times = (0..=255)
+            .map(|i| anti_fee_sniping_lock_time(tip, &mut DeterministicRng::new([i; 32])))
+            .collect::<Vec<_>>();
+        let heights = lock_times
+            .iter()
+            .map(|lock_time| match *lock_time {
+                absolute::LockTime::Blocks(height) => height.to_u32(),
+                absolute::LockTime::Seconds(_) => panic!("lock time is a height"),
+            })
+            .collect::<Vec<_>>();
+        assert!(heights.iter().all(|&height| (799_901..=800_000).contains(&height)));
+        assert!(heights.iter().filter(|&&height| height == 800_000).count() > 200);
+        assert!(heights.iter().any(|&height| height < 800_000));
+
+        let lock_time =
+            anti_fee_sniping_lock_time(Height::ZERO, &mut DeterministicRng::new([0; 32]));
+        assert_eq!(lock_time, absolute::LockTime::ZERO);
+    }
+
+    #[test]
+    fn rbf_signaling() {
+        let mut tx: Transaction = deserialize(&Vec::from_hex(SOME_TX).unwrap()).unwrap();
+        let input = tx.inputs[0].clone();
+        assert_eq!(tx.rbf_signaling().inputs(), [InputRbfSignal::Final]);
+        assert!(tx.is_explicitly_final());
+        assert!(!tx.is_lock_time_enabled());
+        assert!(!tx.rbf_signaling().is_explicitly_rbf());
+
+        tx.inputs = [Sequence::ENABLE_LOCKTIME_NO_RBF, Sequence::MAX, Sequence::ZERO]
+            .into_iter()
+            .map(|sequence| TxIn { sequence, ..input.clone() })
+            .collect();
+        let signaling = tx.rbf_signaling();
+        assert_eq!(
+            signaling.inputs(),
+            [InputRbfSignal::NonReplaceable, InputRbfSignal::Final, InputRbfSignal::Replaceable]
+        );
+        assert_eq!(signaling.replaceable_inputs().collect::<Vec<_>>(), [2]);
+        assert_eq!(signaling.is_explicitly_rbf(), tx.is_explicitly_rbf());
+        assert!(signaling.is_explicitly_rbf());
+        assert!(!signaling.is_explicitly_final());
+        assert!(!tx.is_explicitly_final());
+        assert!(tx.is_lock_time_enabled());
+
+        tx.inputs.truncate(1);
+        assert!(!tx.rbf_signaling().is_explicitly_rbf());
+        assert!(!tx.is_explicitly_final());
+    }
+
+    #[test]
+    fn clone_unsigned() {
+        let tx: Transaction = deserialize(&Vec::from_hex(SOME_TX).unwrap()).unwrap();
+        let unsigned = tx.clone_unsigned();
+        assert!(!tx.inputs[0].script_sig.is_empty());
+        assert!(unsigned.inputs[0].script_sig.is_empty());
+        assert!(unsigned.inputs[0].witness.is_empty());
+        assert_eq!(unsigned.inputs[0].previous_output, tx.inputs[0].previous_output);
+        assert_eq!(unsigned.inputs[0].sequence, tx.inputs[0].sequence);
+        assert_eq!((unsigned.version, unsigned.lock_time), (tx.version, tx.lock_time));
+        assert_eq!(unsigned.outputs, tx.outputs);
+        assert_eq!(unsigned.compute_ntxid(), tx.compute_ntxid());
+    }
+
+    #[test]
+    fn utxo_from_tx() {
+        let tx: Transaction = deserialize(&Vec::from_hex(SOME_TX).unwrap()).unwrap();
+        let utxo = Utxo::from_tx(&tx, 0).unwrap();
+        assert_eq!(utxo.out_point, OutPoint { txid: tx.compute_txid(), vout: 0 });
+        assert_eq!(utxo.output, tx.outputs[0]);
+        assert_eq!(Utxo::from_tx(&tx, u32::try_from(tx.outputs.len()).unwrap()), None);
+        assert_eq!(deserialize::<Utxo>(&serialize(&utxo)).unwrap(), utxo);
+    }
+
+    #[test]
+    fn classify_spend() {
+        use crate::opcodes::all::OP_CHECKSIG;
+        use crate::script::{
+            Builder, PushBytes, RedeemScriptBuf, ScriptBufExt as _, ScriptPubKeyBufExt as _,
+            TapScriptBuf, WitnessScriptBuf, WitnessScriptExt as _,
+        };
+        use crate::taproot::LeafVersion;
+
+        let tx: Transaction = deserialize(&Vec::from_hex(SOME_TX).unwrap()).unwrap();
+        let input = &tx.inputs[0];
+        let mut script_sig = pushes(&input.script_sig);
+        let signature_bytes = script_sig.next().unwrap();
+        let signature = ecdsa::Signature::from_slice(signature_bytes).unwrap();
+        let spend_signature =
+            |bytes| SpendSignature { bytes, info: ecdsa::SignatureInfo::inspect(bytes).unwrap() };
+        let key_bytes = script_sig.next().unwrap();
+        let public_key = PublicKey::from_slice(key_bytes).unwrap();
+        let compressed = CompressedPublicKey::from_slice(key_bytes).unwrap();
+
+        let p2pkh = ScriptPubKeyBuf::new_p2pkh(public_key.pubkey_hash());
+        assert_eq!(
+            input.classify_spend(&p2pkh),
+            SpendKind::P2pkh {
+                signature: Some(spend_signature(signature_bytes)),
+                public_key: Some(public_key)
+            }
+        );
+        let p2pk = ScriptPubKeyBuf::new_p2pk(public_key);
+        assert_eq!(
+            input.classify_spend(&p2pk),
+            SpendKind::P2pk { signature: Some(spend_signature(signature_bytes)) }
+        );
+
+        // A malformed signature is kept, with its encoding error.
+        let mut padded = signature_bytes.to_vec();
+        padded[1] += 1;
+        padded[3] += 1;
+        padded.insert(4, 0x00);
+        let malformed = TxIn {
+            script_sig: Builder::new()
+                .push_slice(<&PushBytes>::try_from(padded.as_slice()).unwrap())
+                .push_key(public_key)
+                .into_script(),
+            ..input.clone()
+        };
+        let SpendKind::P2pkh { signature: Some(kept), .. } = malformed.classify_spend(&p2pkh)
+        else {
+            panic!("expected a P2PKH spend with a signature");
+        };
+        assert_eq!(kept.bytes, padded);
+        assert!(!kept.info.is_strict_der());
+        assert!(kept.to_signature().is_err());
+
+        let p2wpkh = ScriptPubKeyBuf::new_p2wpkh(compressed.wpubkey_hash());
+        let spend = TxIn {
+            script_sig: ScriptSigBuf::new(),
+            witness: Witness::p2wpkh(signature, compressed.to_inner()),
+            ..input.clone()
+        };
+        let expected = |nested| SpendKind::P2wpkh {
+            nested,
+            signature: Some(spend_signature(signature_bytes)),
+            public_key: Some(compressed),
+        };
+        assert_eq!(spend.classify_spend(&p2wpkh), expected(false));
+        let redeem_script = RedeemScriptBuf::new_p2wpkh(compressed.wpubkey_hash());
+        let nested = TxIn {
+            script_sig: Builder::new()
+                .push_slice(<&PushBytes>::try_from(redeem_script.as_bytes()).unwrap())
+                .into_script(),
+            ..spend.clone()
+        };
+        let p2sh = redeem_script.to_p2sh().unwrap();
+        assert_eq!(nested.classify_spend(&p2sh), expected(true));
+        // A P2SH spend must reveal the redeem script.
+        assert_eq!(spend.classify_spend(&p2sh), SpendKind::Unknown);
+
+        let witness_script: WitnessScriptBuf =
+            Builder::new().push_key(public_key).push_opcode(OP_CHECKSIG).into_script();
+        // Elements that do not look like a signature are not taken to be one.
+        let mut witness = Witness::new();
+        witness.push([0x02; 32]);
+        witness.push_ecdsa_signature(signature);
+        witness.push(witness_script.as_bytes());
+        let spend = TxIn { witness, ..spend };
+        assert_eq!(
+            spend.classify_spend(&witness_script.to_p2wsh().unwrap()),
+            SpendKind::P2wsh {
+                nested: false,
+                witness_script: &witness_script,
+                signatures: vec![spend_signature(signature_bytes)],
+                public_keys: vec![public_key],
+            }
+        );
+
+        assert_eq!(spend.classify_spend(&ScriptPubKeyBuf::new_p2a()), SpendKind::P2a);
+
+        let x_only = XOnlyPublicKey::from_byte_array(key_bytes[1..].try_into().unwrap()).unwrap();
+        let p2tr = ScriptPubKeyBuf::new_p2tr(x_only, None);
+        let schnorr = [7; 64];
+        let key_spend = TxIn { witness: Witness::from([schnorr.as_slice()]), ..spend.clone() };
+        assert_eq!(
+            key_spend.classify_spend(&p2tr),
+            SpendKind::TaprootKeyPath {
+                signature: Some(SpendSignature {
+                    bytes: &schnorr,
+                    info: taproot::SignatureInfo { sighash_byte: None },
+                })
+            }
+        );
+
+        let tapscript: TapScriptBuf =
+            Builder::new().push_slice(x_only.serialize()).push_opcode(OP_CHECKSIG).into_script();
+        let control_block =
+            hex!("c0ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+        let annex = hex!("50");
+        let script_spend = TxIn {
+            witness: Witness::from([
+                schnorr.as_slice(),
+                tapscript.as_bytes(),
+                &control_block,
+                &annex,
+            ]),
+            ..spend
+        };
+        let leaf_script = LeafScript { version: LeafVersion::TapScript, script: &*tapscript };
+        assert_eq!(
+            script_spend.classify_spend(&p2tr),
+            SpendKind::TaprootScriptPath {
+                leaf_script,
+                leaf_hash: TapLeafHash::from_script(&tapscript, LeafVersion::TapScript),
+                signatures: vec![SpendSignature {
+                    bytes: &schnorr,
+                    info: taproot::SignatureInfo { sighash_byte: None },
+                }],
+                public_keys: vec![x_only],
+            }
+        );
+    }
 }
@@ -4,12 +4,20 @@
 //!
 //! This module contains the [`Witness`] struct and related methods to operate on it
 
+use core::convert::Infallible;
+use core::fmt;
+
+use encoding::CompactSizeEncoder;
 use io::{BufRead, Write};
 
 use crate::consensus::encode::{self, Error, ParseError, WriteExt};
 use crate::consensus::{Decodable, Encodable};
 use crate::crypto::ecdsa;
 use crate::crypto::key::SerializedXOnlyPublicKey;
+use crate::policy::{
+    MAX_STANDARD_P2WSH_SCRIPT_SIZE, MAX_STANDARD_P2WSH_STACK_ITEMS,
+    MAX_STANDARD_P2WSH_STACK_ITEM_SIZE, MAX_STANDARD_TAPSCRIPT_STACK_ITEM_SIZE,
+};
 use crate::taproot::{self, ControlBlock, LeafScript, TaprootMerkleBranch, TAPROOT_ANNEX_PREFIX};
 use crate::{internal_macros, TapScript, WitnessScript};
 
@@ -18,6 +26,10 @@
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
 pub use primitives::witness::{Iter, Witness};
+#[doc(inline)]
+pub use primitives::array_witness::ArrayWitness;
+#[doc(no_inline)]
+pub use primitives::array_witness::{ArrayWitnessDecoderError, CapacityError};
 #[doc(no_inline)]
 pub use primitives::witness::UnexpectedEofError;
 
@@ -142,6 +154,195 @@ fn witness_script(&self) -> Option<&WitnessScript> { self.last().map(WitnessScri
     }
 }
 
+/// Builds the witness of a script spend within Bitcoin Core's standardness limits.
+///
+/// Only the stack elements a script consumes are pushed, the P2WSH witness script or the Taproot
+/// leaf script and control block are added when finishing. Relay policy limits these elements to
+/// 80 bytes each and, for P2WSH, to 100 elements. The serialized size is kept up to date as
+/// elements are pushed, for fee estimation.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin::witness::WitnessBuilder;
+/// use bitcoin::WitnessScript;
+///
+/// let script = WitnessScript::from_bytes(&[0x51]);
+/// let builder = WitnessBuilder::new().push([])?.push([0xab; 72])?;
+/// assert!(builder.clone().push([0; 81]).is_err());
+/// assert_eq!(builder.size(), 1 + 1 + 1 + 72);
+///
+/// let witness = builder.finish_p2wsh(script)?;
+/// assert_eq!(witness.len(), 3);
+/// # Ok::<_, bitcoin::witness::WitnessBuilderError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WitnessBuilder {
+    witness: Witness,
+    /// The serialized size of the elements, excluding the prefix holding the number of elements.
+    elements_size: usize,
+}
+
+impl WitnessBuilder {
+    /// Constructs a new builder with an empty stack.
+    pub fn new() -> Self { Self::default() }
+
+    /// Pushes a stack element.
+    ///
+    /// # Errors
+    ///
+    /// If `element` is larger than the 80 bytes allowed for P2WSH and Tapscript stack elements.
+    pub fn push<T: AsRef<[u8]>>(mut self, element: T) -> Result<Self, WitnessBuilderError> {
+        let element = element.as_ref();
+        // Tapscript stack elements are limited to the same size.
+        const _: () =
+            assert!(MAX_STANDARD_P2WSH_STACK_ITEM_SIZE == MAX_STANDARD_TAPSCRIPT_STACK_ITEM_SIZE);
+        if element.len() > MAX_STANDARD_P2WSH_STACK_ITEM_SIZE {
+            let index = self.witness.len();
+            return Err(WitnessBuilderError::ElementTooLarge { index, size: element.len() });
+        }
+        self.push_unchecked(element);
+        Ok(self)
+    }
+
+    /// Pushes an ECDSA signature, DER encoded with the sighash type appended.
+    ///
+    /// # Errors
+    ///
+    /// Never for valid signatures, which are at most 73 bytes long.
+    pub fn push_ecdsa_signature(
+        self,
+        signature: ecdsa::Signature,
+    ) -> Result<Self, WitnessBuilderError> {
+        self.push(signature.serialize())
+    }
+
+    /// Pushes a Schnorr signature, with the sighash type appended unless it is the default.
+    ///
+    /// # Errors
+    ///
+    /// Never, signatures are at most 65 bytes long.
+    pub fn push_taproot_signature(
+        self,
+        signature: &taproot::Signature,
+    ) -> Result<Self, WitnessBuilderError> {
+        self.push(signature.serialize())
+    }
+
+    /// Returns the number of stack elements pushed.
+    pub fn len(&self) -> usize { self.witness.len() }
+
+    /// Returns `true` if no stack element is pushed.
+    pub fn is_empty(&self) -> bool { self.witness.is_empty() }
+
+    /// Returns the serialized size of the stack elements pushed so far, the size of the witness
+    /// if it was finished without a script.
+    pub fn size(&self) -> usize {
+        CompactSizeEncoder::encoded_size(self.witness.len()) + self.elements_size
+    }
+
+    /// Returns the elements pushed as a witness, for spends without script such as P2WPKH.
+    pub fn into_witness(self) -> Witness { self.witness }
+
+    /// Finishes the witness of a P2WSH or P2SH-P2WSH spend by pushing the witness script.
+    ///
+    /// # Errors
+    ///
+    /// If more than 100 stack elements are pushed or `witness_script` is larger than 3,600 bytes.
+    pub fn finish_p2wsh(
+        mut self,
+        witness_script: &WitnessScript,
+    ) -> Result<Witness, WitnessBuilderError> {
+        if self.witness.len() > MAX_STANDARD_P2WSH_STACK_ITEMS {
+            return Err(WitnessBuilderError::TooManyElements { count: self.witness.len() });
+        }
+        if witness_script.len() > MAX_STANDARD_P2WSH_SCRIPT_SIZE {
+            return Err(WitnessBuilderError::ScriptTooLarge { size: witness_script.len() });
+        }
+        self.push_unchecked(witness_script.as_bytes());
+        Ok(self.witness)
+    }
+
+    /// Finishes the witness of a Taproot script path spend by pushing the leaf script and the
+    /// control block.
+    ///
+    /// Spends with an annex are non-standard, use [`WitnessExt::push_p2tr_script_spend`] to add
+    /// one anyway.
+    pub fn finish_p2tr_script_spend(
+        mut self,
+        script: &TapScript,
+        control_block: &ControlBlock<impl AsRef<TaprootMerkleBranch>>,
+    ) -> Witness {
+        self.witness.push_p2tr_script_spend(script, control_block, None);
+        self.witness
+    }
+
+    fn push_unchecked(&mut self, element: &[u8]) {
+        self.elements_size += CompactSizeEncoder::encoded_size(element.len()) + element.len();
+        self.witness.push(element);
+    }
+}
+
+/// Error constructing a witness with a [`WitnessBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WitnessBuilderError {
+    /// A stack element is larger than the 80 bytes allowed by relay policy.
+    ElementTooLarge {
+        /// The index of the element on the stack.
+        index: usize,
+        /// The size of the element.
+        size: usize,
+    },
+    /// A P2WSH witness has more than the 100 stack elements allowed by relay policy.
+    TooManyElements {
+        /// The number of stack elements, excluding the witness script.
+        count: usize,
+    },
+    /// The witness script is larger than the 3,600 bytes allowed by relay policy.
+    ScriptTooLarge {
+        /// The size of the witness script.
+        size: usize,
+    },
+}
+
+impl From<Infallible> for WitnessBuilderError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for WitnessBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::ElementTooLarge { index, size } => write!(
+                f,
+                "stack element {} of {} bytes exceeds the standard limit of {} bytes",
+                index, size, MAX_STANDARD_P2WSH_STACK_ITEM_SIZE
+            ),
+            Self::TooManyElements { count } => write!(
+                f,
+                "{} stack elements exceed the standard limit of {}",
+                count, MAX_STANDARD_P2WSH_STACK_ITEMS
+            ),
+            Self::ScriptTooLarge { size } => write!(
+                f,
+                "witness script of {} bytes exceeds the standard limit of {} bytes",
+                size, MAX_STANDARD_P2WSH_SCRIPT_SIZE
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WitnessBuilderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::ElementTooLarge { .. }
+            | Self::TooManyElements { .. }
+            | Self::ScriptTooLarge { .. } => None,
+        }
+    }
+}
+
 /// Represents a possible Taproot spend.
 ///
 /// Taproot can be spent as key spend or script spend and, depending on which it is, different data
@@ -433,4 +634,49 @@ fn fuzz_cases() {
         let bytes = hex!("24000000ffffffffffffffffffffffff");
         assert!(deserialize::<Witness>(&bytes).is_err()); // OversizedVectorAllocation
     }
+
+    #[test]
+    fn witness_builder() {
+        let script = WitnessScript::from_bytes(&[0x51; 10]);
+        let mut builder = WitnessBuilder::new();
+        for i in 0..100 {
+            assert_eq!(builder.size(), Witness::from_slice(&vec![[0u8; 80]; i]).size());
+            builder = builder.push([0; 80]).unwrap();
+        }
+        assert_eq!(builder.len(), 100);
+        let witness = builder.clone().finish_p2wsh(script).unwrap();
+        assert_eq!(witness.len(), 101);
+        assert_eq!(witness.witness_script(), Some(script));
+
+        assert_eq!(
+            builder.clone().push([0; 81]),
+            Err(WitnessBuilderError::ElementTooLarge { index: 100, size: 81 })
+        );
+        let too_many = builder.push([]).unwrap();
+        assert_eq!(too_many.size(), too_many.clone().into_witness().size());
+        assert_eq!(
+            too_many.finish_p2wsh(script),
+            Err(WitnessBuilderError::TooManyElements { count: 101 })
+        );
+        let large = WitnessScript::from_bytes(&[0x51; 3_601]);
+        assert_eq!(
+            WitnessBuilder::new().finish_p2wsh(large),
+            Err(WitnessBuilderError::ScriptTooLarge { size: 3_601 })
+        );
+    }
+
+    #[test]
+    fn witness_builder_p2tr() {
+        let control_block =
+            hex!("c079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+        let control_block = ControlBlock::decode(&control_block).unwrap();
+        let script = TapScript::from_bytes(&[0x51]);
+
+        let witness = WitnessBuilder::new()
+            .push([1; 64])
+            .unwrap()
+            .finish_p2tr_script_spend(script, &control_block);
+        assert_eq!(witness.taproot_leaf_script().unwrap().script, script);
+        assert_eq!(witness.get(0), Some(&[1; 64][..]));
+    }
 }
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Changes of the best chain.
+//!
+//! When the tip of the best chain changes, wallets and indexes have to undo the blocks that are
+//! no longer part of it, newest first, and then apply the blocks of the new chain, oldest first.
+//! An [`Update`] describes this sequence, computed from the headers of the old and the new branch.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::chain::{BlockEvent, Update};
+//! # use bitcoin::{block, constants, BlockHeight, Network};
+//! # let genesis = *constants::genesis_block(Network::Regtest).header();
+//! # let child = |parent: &block::Header, nonce| block::Header {
+//! #     prev_blockhash: parent.block_hash(),
+//! #     nonce,
+//! #     ..*parent
+//! # };
+//! let old = [genesis, child(&genesis, 1)];
+//! let new = [child(&genesis, 2), child(&child(&genesis, 2), 3)];
+//!
+//! let update = Update::between(BlockHeight::ZERO, &old, BlockHeight::from_u32(1), &new).unwrap();
+//! assert_eq!(update.fork_point(), (BlockHeight::ZERO, genesis.block_hash()));
+//! let events = update.events().collect::<Vec<_>>();
+//! assert_eq!(events.len(), 3);
+//! assert!(matches!(events[0], BlockEvent::Disconnected { .. }));
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use crate::block::{BlockHash, Header};
+use crate::prelude::Vec;
+use crate::BlockHeight;
+
+/// The blocks to disconnect and connect to move from one chain tip to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Update {
+    fork_point: (BlockHeight, BlockHash),
+    /// The blocks of the old branch above the fork point, newest first.
+    disconnected: Vec<(BlockHeight, BlockHash)>,
+    /// The blocks of the new branch above the fork point, oldest first.
+    connected: Vec<(BlockHeight, Header)>,
+}
+
+impl Update {
+    /// Computes the update from the tip of the `old` branch to the tip of the `new` branch.
+    ///
+    /// Each branch is a sequence of consecutive headers, the first one at the given start height.
+    /// The branches have to reach back to a common block, either by overlapping or by one branch
+    /// starting right above a block of the other.
+    ///
+    /// # Errors
+    ///
+    /// If the headers of a branch do not link to each other or reach above the maximum height,
+    /// or the branches have no block in common.
+    pub fn between(
+        old_start: BlockHeight,
+        old: &[Header],
+        new_start: BlockHeight,
+        new: &[Header],
+    ) -> Result<Self, UpdateError> {
+        let old = Branch::new(old_start, old)?;
+        let new = Branch::new(new_start, new)?;
+        let fork_point = fork_point(&old, &new).ok_or(UpdateError::NoCommonAncestor)?;
+        let (fork_height, _) = fork_point;
+
+        let disconnected = old.above(fork_height).rev().map(|(height, _, hash)| (height, hash));
+        let connected = new.above(fork_height).map(|(height, header, _)| (height, *header));
+        Ok(Self {
+            fork_point,
+            disconnected: disconnected.collect(),
+            connected: connected.collect(),
+        })
+    }
+
+    /// Returns the height and hash of the last block both chains have in common.
+    pub fn fork_point(&self) -> (BlockHeight, BlockHash) { self.fork_point }
+
+    /// Returns the blocks of the old chain that are no longer part of the best chain, newest
+    /// first.
+    pub fn disconnected(&self) -> &[(BlockHeight, BlockHash)] { &self.disconnected }
+
+    /// Returns the blocks of the new chain that become part of the best chain, oldest first.
+    pub fn connected(&self) -> &[(BlockHeight, Header)] { &self.connected }
+
+    /// Returns `true` if blocks are disconnected, rather than the new tip extending the old one.
+    pub fn is_reorg(&self) -> bool { !self.disconnected.is_empty() }
+
+    /// Returns the height and hash of the new tip.
+    pub fn new_tip(&self) -> (BlockHeight, BlockHash) {
+        self.connected
+            .last()
+            .map_or(self.fork_point, |(height, header)| (*height, header.block_hash()))
+    }
+
+    /// Returns the disconnect events followed by the connect events, the order to apply them in.
+    pub fn events(&self) -> impl Iterator<Item = BlockEvent<'_>> + '_ {
+        let disconnected = self
+            .disconnected
+            .iter()
+            .map(|&(height, block_hash)| BlockEvent::Disconnected { height, block_hash });
+        let connected = self
+            .connected
+            .iter()
+            .map(|(height, header)| BlockEvent::Connected { height: *height, header });
+        disconnected.chain(connected)
+    }
+}
+
+/// A change of the best chain by one block, see [`Update::events`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlockEvent<'a> {
+    /// The block is no longer part of the best chain.
+    Disconnected {
+        /// The height of the block.
+        height: BlockHeight,
+        /// The hash of the block.
+        block_hash: BlockHash,
+    },
+    /// The block became part of the best chain.
+    Connected {
+        /// The height of the block.
+        height: BlockHeight,
+        /// The header of the block.
+        header: &'a Header,
+    },
+}
+
+/// A branch of linked headers with their hashes.
+///
+/// The height after the last header, `start + headers.len()`, is at most `u32::MAX`.
+struct Branch<'a> {
+    start: u32,
+    headers: &'a [Header],
+    hashes: Vec<BlockHash>,
+}
+
+impl<'a> Branch<'a> {
+    fn new(start: BlockHeight, headers: &'a [Header]) -> Result<Self, UpdateError> {
+        u32::try_from(headers.len())
+            .ok()
+            .and_then(|len| start.to_u32().checked_add(len))
+            .ok_or(UpdateError::HeightOverflow)?;
+        let hashes = headers.iter().map(Header::block_hash).collect::<Vec<_>>();
+        for (i, header) in headers.iter().enumerate().skip(1) {
+            if header.prev_blockhash != hashes[i - 1] {
+                // Cast ok, the heights of the branch fit in a `u32`.
+                let height = BlockHeight::from_u32(start.to_u32() + i as u32);
+                return Err(UpdateError::NotLinked { height });
+            }
+        }
+        Ok(Self { start: start.to_u32(), headers, hashes })
+    }
+
+    /// Returns the height after the last header.
+    // Cast ok, the heights of the branch fit in a `u32`.
+    fn end(&self) -> u32 { self.start + self.headers.len() as u32 }
+
+    fn hash(&self, height: u32) -> Option<BlockHash> {
+        let index = usize::try_from(height.checked_sub(self.start)?).ok()?;
+        self.hashes.get(index).copied()
+    }
+
+    /// Returns the headers above `height`, oldest first.
+    fn above(
+        &self,
+        height: BlockHeight,
+    ) -> impl DoubleEndedIterator<Item = (BlockHeight, &'a Header, BlockHash)> + '_ {
+        let skip = (height.to_u32() + 1).saturating_sub(self.start) as usize;
+        self.headers.iter().zip(&self.hashes).enumerate().skip(skip).map(
+            move |(i, (header, hash))| {
+                (BlockHeight::from_u32(self.start + i as u32), header, *hash)
+            },
+        )
+    }
+}
+
+/// Returns the last block `old` and `new` have in common.
+fn fork_point(old: &Branch, new: &Branch) -> Option<(BlockHeight, BlockHash)> {
+    let bottom = old.start.max(new.start);
+    let top = old.end().min(new.end());
+    // The branches are linked, so they agree on every block below the highest common one.
+    for height in (bottom..top).rev() {
+        if let (Some(old_hash), Some(new_hash)) = (old.hash(height), new.hash(height)) {
+            if old_hash == new_hash {
+                return Some((BlockHeight::from_u32(height), old_hash));
+            }
+        }
+    }
+    // Otherwise the branch starting later may link to a block of the other one.
+    let (later, other) = if new.start >= old.start { (new, old) } else { (old, new) };
+    let parent = later.headers.first()?.prev_blockhash;
+    let parent_height = later.start.checked_sub(1)?;
+    let reaches_other = if other.start == later.start {
+        other.headers.first().is_some_and(|header| header.prev_blockhash == parent)
+    } else {
+        other.hash(parent_height) == Some(parent)
+    };
+    reaches_other.then(|| (BlockHeight::from_u32(parent_height), parent))
+}
+
+/// Error computing an [`Update`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UpdateError {
+    /// A header of a branch does not link to the header before it.
+    NotLinked {
+        /// The height of the header.
+        height: BlockHeight,
+    },
+    /// The branches have no block in common.
+    NoCommonAncestor,
+    /// The headers of a branch reach above the maximum block height.
+    HeightOverflow,
+}
+
+impl From<Infallible> for UpdateError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::NotLinked { height } =>
+                write!(f, "header at height {} does not link to the header before it", height),
+            Self::NoCommonAncestor => f.write_str("the branches have no block in common"),
+            Self::HeightOverflow =>
+                f.write_str("the headers of a branch reach above the maximum block height"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UpdateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::NotLinked { .. } | Self::NoCommonAncestor | Self::HeightOverflow => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants, Network};
+
+    fn height(height: u32) -> BlockHeight { BlockHeight::from_u32(height) }
+
+    /// Returns `count` headers on top of `parent`, distinguished by `nonce`.
+    fn extend(parent: &Header, count: usize, nonce: u32) -> Vec<Header> {
+        let mut parent = *parent;
+        (0..count)
+            .map(|_| {
+                parent = Header { prev_blockhash: parent.block_hash(), nonce, ..parent };
+                parent
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reorg() {
+        let genesis = *constants::genesis_block(Network::Regtest).header();
+        let common = extend(&genesis, 3, 0);
+        let old = [&common[..], &extend(&common[2], 2, 1)].concat();
+        let new = [&common[1..], &extend(&common[2], 3, 2)].concat();
+
+        let update = Update::between(height(1), &old, height(2), &new).unwrap();
+        assert_eq!(update.fork_point(), (height(3), common[2].block_hash()));
+        assert!(update.is_reorg());
+        assert_eq!(
+            update.disconnected(),
+            [(height(5), old[4].block_hash()), (height(4), old[3].block_hash())]
+        );
+        let connected = update.connected().iter().map(|(height, _)| height.to_u32());
+        assert_eq!(connected.collect::<Vec<_>>(), [4, 5, 6]);
+        assert_eq!(update.new_tip(), (height(6), new[4].block_hash()));
+        assert_eq!(update.events().count(), 5);
+
+        // The same branches, but without the common blocks in the new one.
+        let update = Update::between(height(1), &old, height(4), &new[2..]).unwrap();
+        assert_eq!(update.fork_point(), (height(3), common[2].block_hash()));
+        assert_eq!(update.disconnected().len(), 2);
+
+        // Both branches start right above the fork point.
+        let update = Update::between(height(4), &old[3..], height(4), &new[2..]).unwrap();
+        assert_eq!(update.fork_point(), (height(3), common[2].block_hash()));
+    }
+
+    #[test]
+    fn extension() {
+        let genesis = *constants::genesis_block(Network::Regtest).header();
+        let old = extend(&genesis, 2, 0);
+        let new = extend(&old[1], 2, 0);
+
+        let update = Update::between(height(1), &old, height(3), &new).unwrap();
+        assert!(!update.is_reorg());
+        assert_eq!(update.fork_point(), (height(2), old[1].block_hash()));
+        assert_eq!(update.new_tip(), (height(4), new[1].block_hash()));
+
+        // Same tip.
+        let update = Update::between(height(1), &old, height(1), &old).unwrap();
+        assert_eq!(update.events().count(), 0);
+        assert_eq!(update.new_tip(), (height(2), old[1].block_hash()));
+    }
+
+    #[test]
+    fn errors() {
+        let genesis = *constants::genesis_block(Network::Regtest).header();
+        let old = extend(&genesis, 2, 0);
+        let new = extend(&genesis, 2, 1);
+
+        assert_eq!(
+            Update::between(height(1), &old, height(2), &new[1..]),
+            Err(UpdateError::NoCommonAncestor)
+        );
+        assert_eq!(
+            Update::between(height(1), &old, height(1), &[]),
+            Err(UpdateError::NoCommonAncestor)
+        );
+        let unlinked = [old[0], new[1]];
+        assert_eq!(
+            Update::between(height(1), &unlinked, height(1), &new),
+            Err(UpdateError::NotLinked { height: height(2) })
+        );
+        assert_eq!(
+            Update::between(BlockHeight::MAX, &old, height(1), &old),
+            Err(UpdateError::HeightOverflow)
+        );
+    }
+}
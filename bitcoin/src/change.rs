@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Change output management.
+//!
+//! After selecting coins for a transaction there is usually some excess value left over. A
+//! [`ChangePolicy`] decides whether this excess is worth returning to the wallet in a change
+//! output or should be dropped to fees instead, and how the change amount is rounded.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::change::{ChangePolicy, Rounding};
+//! use bitcoin::transaction::InputWeightPrediction;
+//! use bitcoin::{Amount, FeeRate, ScriptPubKeyBuf};
+//!
+//! let script_pubkey = ScriptPubKeyBuf::from_bytes([&[0x00, 0x14][..], &[0; 20]].concat());
+//! let policy = ChangePolicy::new(&script_pubkey, InputWeightPrediction::P2WPKH_MAX)
+//!     .with_rounding(Rounding::DownToMultiple(Amount::from_sat_u32(1_000)));
+//! let fee_rate = FeeRate::from_sat_per_vb(10);
+//!
+//! // Creating the output costs 310 sat, the rest is rounded down.
+//! let change = policy.change_amount(Amount::from_sat_u32(20_000), fee_rate, fee_rate);
+//! assert_eq!(change, Some(Amount::from_sat_u32(19_000)));
+//!
+//! // Too little is left to be worth spending later.
+//! assert_eq!(policy.change_amount(Amount::from_sat_u32(1_000), fee_rate, fee_rate), None);
+//! ```
+
+use internals::ToU64 as _;
+
+use crate::prelude::{ToOwned, Vec};
+use crate::random::RandomSource;
+use crate::script::{ScriptPubKey, ScriptPubKeyExt as _};
+use crate::transaction::{InputWeightPrediction, TxOut, TxOutExt as _};
+use crate::{Amount, FeeRate, Weight};
+
+/// Decides whether excess value is returned in a change output.
+///
+/// Creating a change output only makes sense if its amount is above the dust limit and the output
+/// is worth more than it will later cost to spend it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ChangePolicy {
+    /// The weight the change output adds to the transaction.
+    output_weight: Weight,
+    /// The weight of an input spending the change output.
+    spend_weight: Weight,
+    /// The smallest non-dust amount of the change output.
+    dust_limit: Amount,
+    /// How the change amount is rounded.
+    rounding: Rounding,
+}
+
+impl ChangePolicy {
+    /// Constructs a new policy for change paid to `script_pubkey` and later spent by an input
+    /// like `spend`.
+    ///
+    /// The dust limit is that of Bitcoin Core's default `-dustrelayfee`, change amounts are not
+    /// rounded.
+    pub fn new(script_pubkey: &ScriptPubKey, spend: InputWeightPrediction) -> Self {
+        let output = TxOut { amount: Amount::ZERO, script_pubkey: script_pubkey.to_owned() };
+        Self {
+            output_weight: output.weight(),
+            spend_weight: spend.total_weight(),
+            dust_limit: script_pubkey.minimal_non_dust(),
+            rounding: Rounding::Exact,
+        }
+    }
+
+    /// Sets the dust limit, for example one computed with [`minimal_non_dust_custom`].
+    ///
+    /// [`minimal_non_dust_custom`]: crate::script::ScriptPubKeyExt::minimal_non_dust_custom
+    #[must_use]
+    pub fn with_dust_limit(self, dust_limit: Amount) -> Self { Self { dust_limit, ..self } }
+
+    /// Sets how change amounts are rounded.
+    #[must_use]
+    pub fn with_rounding(self, rounding: Rounding) -> Self { Self { rounding, ..self } }
+
+    /// Returns the weight the change output adds to the transaction.
+    pub fn output_weight(&self) -> Weight { self.output_weight }
+
+    /// Returns the weight of an input spending the change output.
+    pub fn spend_weight(&self) -> Weight { self.spend_weight }
+
+    /// Returns the smallest non-dust amount of the change output.
+    pub fn dust_limit(&self) -> Amount { self.dust_limit }
+
+    /// Returns how change amounts are rounded.
+    pub fn rounding(&self) -> Rounding { self.rounding }
+
+    /// Returns the smallest change amount worth creating.
+    ///
+    /// This is the larger of the dust limit and one satoshi more than the cost of spending the
+    /// change at `discard_fee_rate`, the fee rate the wallet expects to spend it at later.
+    pub fn min_viable_change(&self, discard_fee_rate: FeeRate) -> Amount {
+        let spend_fee = discard_fee_rate.to_fee(self.spend_weight);
+        let min = spend_fee.checked_add(Amount::ONE_SAT).unwrap_or(Amount::MAX);
+        min.max(self.dust_limit)
+    }
+
+    /// Returns the excess below which no change output is created and the excess is dropped to
+    /// fees instead.
+    ///
+    /// This is the cost of the change output at `fee_rate` plus [`Self::min_viable_change`].
+    pub fn drop_to_fee_threshold(&self, fee_rate: FeeRate, discard_fee_rate: FeeRate) -> Amount {
+        fee_rate
+            .to_fee(self.output_weight)
+            .checked_add(self.min_viable_change(discard_fee_rate))
+            .unwrap_or(Amount::MAX)
+    }
+
+    /// Returns the amount of the change output for the given `excess`, the value not spent by
+    /// the transaction before adding a change output.
+    ///
+    /// The fee of the change output at `fee_rate` is taken out of the excess and the result is
+    /// rounded. Returns `None` if the change is not worth creating, in which case the excess
+    /// should be dropped to fees.
+    pub fn change_amount(
+        &self,
+        excess: Amount,
+        fee_rate: FeeRate,
+        discard_fee_rate: FeeRate,
+    ) -> Option<Amount> {
+        let change = excess.checked_sub(fee_rate.to_fee(self.output_weight))?;
+        let change = self.rounding.round(change);
+        if change >= self.min_viable_change(discard_fee_rate) {
+            Some(change)
+        } else {
+            None
+        }
+    }
+}
+
+/// How a change amount is rounded.
+///
+/// Amounts are always rounded down, the remainder is paid as fee. Round amounts make it harder to
+/// tell the change apart from payments that are round themselves.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Rounding {
+    /// The change amount is not rounded.
+    #[default]
+    Exact,
+    /// The change amount is rounded down to a multiple of the given amount.
+    ///
+    /// A zero amount means no rounding.
+    DownToMultiple(Amount),
+    /// The change amount is rounded down to the given number of significant decimal digits.
+    ///
+    /// Zero digits means no rounding.
+    SignificantDigits(u8),
+}
+
+impl Rounding {
+    /// Rounds `amount` down according to this strategy.
+    pub fn round(self, amount: Amount) -> Amount {
+        let sat = amount.to_sat();
+        let multiple = match self {
+            Self::Exact => return amount,
+            Self::DownToMultiple(multiple) => multiple.to_sat(),
+            Self::SignificantDigits(0) => return amount,
+            Self::SignificantDigits(digits) => {
+                let len = sat.checked_ilog10().map_or(1, |log| log + 1);
+                match len.checked_sub(u32::from(digits)) {
+                    Some(drop) => 10_u64.pow(drop),
+                    None => return amount,
+                }
+            }
+        };
+        match sat.checked_rem(multiple) {
+            Some(remainder) => Amount::from_sat(sat - remainder).expect("smaller than amount"),
+            None => amount,
+        }
+    }
+}
+
+/// Inserts `change` at a uniformly random position of `outputs`.
+///
+/// Returns the index of the change output. A fixed position, most commonly last, lets observers
+/// identify the change. When building a PSBT insert a default [`Output`] at the same index.
+///
+/// [`Output`]: crate::psbt::Output
+pub fn insert_random<R: RandomSource + ?Sized>(
+    outputs: &mut Vec<TxOut>,
+    change: TxOut,
+    rng: &mut R,
+) -> usize {
+    let positions = outputs.len().to_u64() + 1;
+    let index =
+        usize::try_from(rng.random_below(positions)).expect("at most the number of outputs");
+    outputs.insert(index, change);
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptPubKeyBuf;
+
+    fn p2wpkh() -> ScriptPubKeyBuf {
+        ScriptPubKeyBuf::from_bytes([&[0x00, 0x14][..], &[0; 20]].concat())
+    }
+
+    fn sat(sat: u32) -> Amount { Amount::from_sat_u32(sat) }
+
+    #[test]
+    fn thresholds() {
+        let policy = ChangePolicy::new(&p2wpkh(), InputWeightPrediction::P2WPKH_MAX);
+        assert_eq!(policy.output_weight(), Weight::from_vb_unchecked(31));
+        assert_eq!(policy.dust_limit(), sat(294));
+
+        // At low fee rates the dust limit dominates.
+        let fee_rate = FeeRate::from_sat_per_vb(1);
+        assert_eq!(policy.min_viable_change(fee_rate), sat(294));
+        assert_eq!(policy.drop_to_fee_threshold(fee_rate, fee_rate), sat(325));
+
+        let fee_rate = FeeRate::from_sat_per_vb(10);
+        // Spending the change costs 68 vB.
+        assert_eq!(policy.min_viable_change(fee_rate), sat(681));
+        let threshold = policy.drop_to_fee_threshold(fee_rate, fee_rate);
+        assert_eq!(threshold, sat(991));
+
+        assert_eq!(policy.change_amount(threshold, fee_rate, fee_rate), Some(sat(681)));
+        assert_eq!(policy.change_amount(sat(990), fee_rate, fee_rate), None);
+        assert_eq!(policy.change_amount(sat(100), fee_rate, fee_rate), None);
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(Rounding::Exact.round(sat(123_456)), sat(123_456));
+        assert_eq!(Rounding::DownToMultiple(sat(1_000)).round(sat(123_456)), sat(123_000));
+        assert_eq!(Rounding::DownToMultiple(Amount::ZERO).round(sat(123_456)), sat(123_456));
+        assert_eq!(Rounding::SignificantDigits(2).round(sat(123_456)), sat(120_000));
+        assert_eq!(Rounding::SignificantDigits(6).round(sat(123_456)), sat(123_456));
+        assert_eq!(Rounding::SignificantDigits(9).round(sat(123_456)), sat(123_456));
+        assert_eq!(Rounding::SignificantDigits(1).round(Amount::ZERO), Amount::ZERO);
+
+        // Rounding can make change unviable.
+        let policy = ChangePolicy::new(&p2wpkh(), InputWeightPrediction::P2WPKH_MAX)
+            .with_rounding(Rounding::DownToMultiple(sat(1_000)));
+        let fee_rate = FeeRate::from_sat_per_vb(1);
+        assert_eq!(policy.change_amount(sat(1_030), fee_rate, fee_rate), None);
+        assert_eq!(policy.change_amount(sat(1_031), fee_rate, fee_rate), Some(sat(1_000)));
+    }
+
+    #[test]
+    #[cfg(all(feature = "rand", feature = "std"))]
+    fn insert_at_random_position() {
+        use secp256k1::rand;
+
+        let mut outputs = vec![TxOut { amount: sat(1), script_pubkey: ScriptPubKeyBuf::new() }; 3];
+        let change = TxOut { amount: sat(2), script_pubkey: p2wpkh() };
+        let index = insert_random(&mut outputs, change.clone(), &mut rand::rng());
+        assert_eq!(outputs.len(), 4);
+        assert_eq!(outputs[index], change);
+    }
+
+    #[test]
+    fn insert_at_deterministic_position() {
+        use crate::random::DeterministicRng;
+
+        let outputs = vec![TxOut { amount: sat(1), script_pubkey: ScriptPubKeyBuf::new() }; 3];
+        let change = TxOut { amount: sat(2), script_pubkey: p2wpkh() };
+        let insert = |seed| {
+            let mut outputs = outputs.clone();
+            let index =
+                insert_random(&mut outputs, change.clone(), &mut DeterministicRng::new(seed));
+            assert_eq!(outputs[index], change);
+            index
+        };
+        assert_eq!(insert([7; 32]), insert([7; 32]));
+        assert!((0..32).map(|i| insert([i; 32])).any(|index| index != insert([0; 32])));
+    }
+}
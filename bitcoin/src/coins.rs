@@ -0,0 +1,848 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! The unspent transaction output set.
+//!
+//! A [`CoinsView`] is a read-only view of the UTXO set, [`MemoryCoins`] is a complete in-memory
+//! UTXO set that can be updated by applying blocks to it and rolled back again using the
+//! [`BlockUndo`] data generated when applying.
+//!
+//! The undo data is encoded in the same format Bitcoin Core uses for the records in its `rev*.dat`
+//! files. A record in those files consists of the network magic, the length of the encoded
+//! [`BlockUndo`] as a little-endian `u32`, the encoded [`BlockUndo`] and finally its
+//! [`BlockUndo::checksum`].
+
+use core::convert::Infallible;
+use core::fmt;
+
+use hashes::sha256d;
+use io::{BufRead, Write};
+
+use crate::block::{Block, BlockHash, Checked};
+use crate::consensus::encode::{self, Decodable, Encodable, ReadExt as _, WriteExt as _};
+use crate::consensus::parse_failed_error;
+use crate::constants::{COINBASE_MATURITY, MAX_SCRIPT_SIZE};
+use crate::prelude::{BTreeMap, Vec};
+use crate::script::ScriptPubKeyBuf;
+use crate::transaction::{OutPoint, Transaction, TxOut, Txid};
+use crate::{Amount, BlockHeight};
+
+/// The number of special script encodings in the compressed script format.
+const SPECIAL_SCRIPTS: u64 = 6;
+
+/// An unspent transaction output together with the metadata needed to validate spending it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Coin {
+    /// The unspent output.
+    pub output: TxOut,
+    /// The height of the block containing the transaction that created the output.
+    pub height: BlockHeight,
+    /// Whether the output was created by a coinbase transaction.
+    pub is_coinbase: bool,
+}
+
+impl Coin {
+    /// Returns the height of the first block that may spend this coin.
+    ///
+    /// Coinbase outputs can only be spent after [`COINBASE_MATURITY`] blocks.
+    pub fn mature_height(&self) -> BlockHeight {
+        if self.is_coinbase {
+            BlockHeight::from_u32(self.height.to_u32().saturating_add(COINBASE_MATURITY))
+        } else {
+            self.height
+        }
+    }
+
+    /// Returns `true` if this coin may be spent in a block at `height`.
+    pub fn is_mature_at(&self, height: BlockHeight) -> bool { height >= self.mature_height() }
+}
+
+/// A read-only view of the UTXO set.
+pub trait CoinsView {
+    /// Returns the unspent output at `out_point`, if any.
+    fn coin(&self, out_point: &OutPoint) -> Option<Coin>;
+
+    /// Returns `true` if there is an unspent output at `out_point`.
+    fn contains(&self, out_point: &OutPoint) -> bool { self.coin(out_point).is_some() }
+}
+
+impl<T: CoinsView + ?Sized> CoinsView for &T {
+    fn coin(&self, out_point: &OutPoint) -> Option<Coin> { (**self).coin(out_point) }
+
+    fn contains(&self, out_point: &OutPoint) -> bool { (**self).contains(out_point) }
+}
+
+/// An in-memory UTXO set.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin::coins::MemoryCoins;
+/// use bitcoin::{constants, BlockHeight, Network};
+///
+/// let genesis = constants::genesis_block(Network::Regtest);
+/// let mut coins = MemoryCoins::new();
+/// let undo = coins.apply_block(&genesis, BlockHeight::ZERO).expect("coinbase spends nothing");
+/// assert_eq!(coins.len(), 1);
+///
+/// coins.undo_block(&genesis, &undo).expect("undo data matches the block");
+/// assert!(coins.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryCoins {
+    coins: BTreeMap<OutPoint, Coin>,
+}
+
+impl MemoryCoins {
+    /// Constructs a new empty UTXO set.
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns the number of unspent outputs.
+    pub fn len(&self) -> usize { self.coins.len() }
+
+    /// Returns `true` if there are no unspent outputs.
+    pub fn is_empty(&self) -> bool { self.coins.is_empty() }
+
+    /// Returns the unspent output at `out_point`, if any.
+    pub fn get(&self, out_point: &OutPoint) -> Option<&Coin> { self.coins.get(out_point) }
+
+    /// Adds an unspent output, returning the output previously stored at `out_point`.
+    pub fn insert(&mut self, out_point: OutPoint, coin: Coin) -> Option<Coin> {
+        self.coins.insert(out_point, coin)
+    }
+
+    /// Removes the unspent output at `out_point` and returns it.
+    pub fn remove(&mut self, out_point: &OutPoint) -> Option<Coin> { self.coins.remove(out_point) }
+
+    /// Returns an iterator over all unspent outputs, ordered by outpoint.
+    pub fn iter(&self) -> impl Iterator<Item = (&OutPoint, &Coin)> { self.coins.iter() }
+
+    /// Applies `block`, which is at `height`, to the UTXO set.
+    ///
+    /// All outputs spent by the block are removed and all new outputs are added, except for
+    /// provably unspendable ones. Transactions may spend outputs created earlier in the same block.
+    /// The returned undo data can be passed to [`Self::undo_block`] to roll back the changes.
+    ///
+    /// This does not validate scripts or amounts, only that all spent outputs exist and that
+    /// coinbase outputs are mature.
+    ///
+    /// # Errors
+    ///
+    /// If an input spends an output that is not in the set or an immature coinbase output. The set
+    /// is left unchanged in that case.
+    pub fn apply_block(
+        &mut self,
+        block: &Block<Checked>,
+        height: BlockHeight,
+    ) -> Result<BlockUndo, ApplyBlockError> {
+        let transactions = block.transactions();
+        let mut undo = BlockUndo { txs: Vec::with_capacity(transactions.len().saturating_sub(1)) };
+
+        for (index, tx) in transactions.iter().enumerate() {
+            // The coinbase input does not spend anything.
+            if index != 0 {
+                match self.spend_inputs(tx, height) {
+                    Ok(tx_undo) => undo.txs.push(tx_undo),
+                    Err(error) => {
+                        self.disconnect(&transactions[..index], &undo.txs);
+                        return Err(error);
+                    }
+                }
+            }
+
+            let txid = tx.compute_txid();
+            for (vout, output) in tx.outputs.iter().enumerate() {
+                if is_unspendable(&output.script_pubkey) {
+                    continue;
+                }
+                let coin = Coin { output: output.clone(), height, is_coinbase: index == 0 };
+                self.coins.insert(out_point(txid, vout), coin);
+            }
+        }
+        Ok(undo)
+    }
+
+    /// Rolls back `block` using the `undo` data returned when it was applied.
+    ///
+    /// The outputs created by the block are removed and the outputs spent by it are restored.
+    ///
+    /// # Errors
+    ///
+    /// If `undo` does not contain exactly one spent output for every non-coinbase input of the
+    /// block. The set is left unchanged in that case.
+    pub fn undo_block(
+        &mut self,
+        block: &Block<Checked>,
+        undo: &BlockUndo,
+    ) -> Result<(), UndoBlockError> {
+        let transactions = block.transactions();
+        let expected = transactions.len().saturating_sub(1);
+        if undo.txs.len() != expected {
+            return Err(UndoBlockError::TxCount { expected, actual: undo.txs.len() });
+        }
+        for (index, (tx, tx_undo)) in transactions.iter().skip(1).zip(&undo.txs).enumerate() {
+            if tx.inputs.len() != tx_undo.spent.len() {
+                return Err(UndoBlockError::InputCount {
+                    tx_index: index + 1,
+                    expected: tx.inputs.len(),
+                    actual: tx_undo.spent.len(),
+                });
+            }
+        }
+        self.disconnect(transactions, &undo.txs);
+        Ok(())
+    }
+
+    /// Removes the coins spent by `tx`, returning them in input order.
+    ///
+    /// Restores the already removed coins on error.
+    fn spend_inputs(
+        &mut self,
+        tx: &Transaction,
+        height: BlockHeight,
+    ) -> Result<TxUndo, ApplyBlockError> {
+        let mut spent = Vec::with_capacity(tx.inputs.len());
+        for input in &tx.inputs {
+            let out_point = input.previous_output;
+            let error = match self.coins.remove(&out_point) {
+                Some(coin) if coin.is_mature_at(height) => {
+                    spent.push(coin);
+                    continue;
+                }
+                Some(coin) => {
+                    let mature_height = coin.mature_height();
+                    self.coins.insert(out_point, coin);
+                    ApplyBlockError::ImmatureCoinbase { out_point, mature_height }
+                }
+                None => ApplyBlockError::MissingCoin { out_point },
+            };
+            for (input, coin) in tx.inputs.iter().zip(spent) {
+                self.coins.insert(input.previous_output, coin);
+            }
+            return Err(error);
+        }
+        Ok(TxUndo { spent })
+    }
+
+    /// Rolls back `transactions`, the first of which must be a coinbase.
+    ///
+    /// `undo` must contain the spent coins of all other transactions.
+    fn disconnect(&mut self, transactions: &[Transaction], undo: &[TxUndo]) {
+        for (index, tx) in transactions.iter().enumerate().rev() {
+            let txid = tx.compute_txid();
+            for vout in 0..tx.outputs.len() {
+                self.coins.remove(&out_point(txid, vout));
+            }
+            if index != 0 {
+                for (input, coin) in tx.inputs.iter().zip(&undo[index - 1].spent).rev() {
+                    self.coins.insert(input.previous_output, coin.clone());
+                }
+            }
+        }
+    }
+}
+
+impl CoinsView for MemoryCoins {
+    fn coin(&self, out_point: &OutPoint) -> Option<Coin> { self.coins.get(out_point).cloned() }
+
+    fn contains(&self, out_point: &OutPoint) -> bool { self.coins.contains_key(out_point) }
+}
+
+impl FromIterator<(OutPoint, Coin)> for MemoryCoins {
+    fn from_iter<I: IntoIterator<Item = (OutPoint, Coin)>>(iter: I) -> Self {
+        Self { coins: iter.into_iter().collect() }
+    }
+}
+
+fn out_point(txid: Txid, vout: usize) -> OutPoint {
+    OutPoint { txid, vout: u32::try_from(vout).expect("less than 2^32 outputs") }
+}
+
+/// Returns `true` if `script_pubkey` can never be spent, such outputs are not added to the set.
+fn is_unspendable(script_pubkey: &ScriptPubKeyBuf) -> bool {
+    script_pubkey.as_bytes().first() == Some(&0x6a) || script_pubkey.len() > MAX_SCRIPT_SIZE
+}
+
+/// The outputs spent by a block, needed to roll it back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockUndo {
+    /// The outputs spent by each transaction of the block, except for the coinbase.
+    pub txs: Vec<TxUndo>,
+}
+
+impl BlockUndo {
+    /// Returns the checksum stored after this undo data in a `rev*.dat` file.
+    ///
+    /// `prev_blockhash` is the hash of the parent of the block this undo data belongs to.
+    pub fn checksum(&self, prev_blockhash: BlockHash) -> sha256d::Hash {
+        let mut engine = sha256d::Hash::engine();
+        prev_blockhash.consensus_encode(&mut engine).expect("engines don't error");
+        self.consensus_encode(&mut engine).expect("engines don't error");
+        sha256d::Hash::from_engine(engine)
+    }
+}
+
+/// The outputs spent by a single transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxUndo {
+    /// The spent outputs, in input order.
+    pub spent: Vec<Coin>,
+}
+
+impl Encodable for BlockUndo {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        let mut len = w.emit_compact_size(self.txs.len())?;
+        for tx_undo in &self.txs {
+            len += tx_undo.consensus_encode(w)?;
+        }
+        Ok(len)
+    }
+}
+
+impl Decodable for BlockUndo {
+    fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
+        r: &mut R,
+    ) -> Result<Self, encode::Error> {
+        Ok(Self { txs: Decodable::consensus_decode_from_finite_reader(r)? })
+    }
+}
+
+impl Encodable for TxUndo {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        let mut len = w.emit_compact_size(self.spent.len())?;
+        for coin in &self.spent {
+            len += encode_coin(coin, w)?;
+        }
+        Ok(len)
+    }
+}
+
+impl Decodable for TxUndo {
+    fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
+        r: &mut R,
+    ) -> Result<Self, encode::Error> {
+        let count = r.read_compact_size()?;
+        // Don't trust `count` for the allocation, every coin takes at least two bytes.
+        let mut spent = Vec::with_capacity(count.min(1024) as usize);
+        for _ in 0..count {
+            spent.push(decode_coin(r)?);
+        }
+        Ok(Self { spent })
+    }
+}
+
+/// Encodes a coin the way Core encodes spent outputs in undo data.
+fn encode_coin<W: Write + ?Sized>(coin: &Coin, w: &mut W) -> Result<usize, io::Error> {
+    let code = u64::from(coin.height.to_u32()) * 2 + u64::from(coin.is_coinbase);
+    let mut len = write_varint(w, code)?;
+    if coin.height != BlockHeight::ZERO {
+        // A dummy transaction version, kept by Core for compatibility with the old format.
+        w.emit_u8(0)?;
+        len += 1;
+    }
+    len += write_varint(w, compress_amount(coin.output.amount.to_sat()))?;
+    len += encode_script(&coin.output.script_pubkey, w)?;
+    Ok(len)
+}
+
+fn decode_coin<R: BufRead + ?Sized>(r: &mut R) -> Result<Coin, encode::Error> {
+    let code = read_varint(r)?;
+    let height =
+        u32::try_from(code >> 1).map_err(|_| parse_failed_error("coin height overflow"))?;
+    if height != 0 {
+        let _version = read_varint(r)?;
+    }
+    let amount = Amount::from_sat(decompress_amount(read_varint(r)?))
+        .map_err(|_| parse_failed_error("coin amount exceeds the maximum"))?;
+    let script_pubkey = decode_script(r)?;
+    Ok(Coin {
+        output: TxOut { amount, script_pubkey },
+        height: BlockHeight::from_u32(height),
+        is_coinbase: code & 1 == 1,
+    })
+}
+
+/// Writes a variable length integer in Core's `VARINT` format.
+///
+/// This is the big-endian base-128 encoding where every continuation byte has an offset of one
+/// so that every number has exactly one encoding. It is unrelated to `CompactSize`.
+fn write_varint<W: Write + ?Sized>(w: &mut W, mut n: u64) -> Result<usize, io::Error> {
+    let mut buf = [0; 10];
+    let mut len = 0;
+    loop {
+        buf[buf.len() - 1 - len] = (n & 0x7f) as u8 | if len == 0 { 0x00 } else { 0x80 };
+        len += 1;
+        if n <= 0x7f {
+            break;
+        }
+        n = (n >> 7) - 1;
+    }
+    w.emit_slice(&buf[buf.len() - len..])
+}
+
+fn read_varint<R: BufRead + ?Sized>(r: &mut R) -> Result<u64, encode::Error> {
+    let mut n = 0_u64;
+    loop {
+        let byte = r.read_u8()?;
+        if n > u64::MAX >> 7 {
+            return Err(parse_failed_error("VARINT overflow"));
+        }
+        n = (n << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+        n = n.checked_add(1).ok_or_else(|| parse_failed_error("VARINT overflow"))?;
+    }
+}
+
+/// Compresses an amount, taking advantage of trailing decimal zeros.
+fn compress_amount(mut n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    let mut e = 0;
+    while n % 10 == 0 && e < 9 {
+        n /= 10;
+        e += 1;
+    }
+    if e < 9 {
+        let d = n % 10;
+        n /= 10;
+        1 + (n * 9 + d - 1) * 10 + e
+    } else {
+        1 + (n - 1) * 10 + 9
+    }
+}
+
+/// Reverses [`compress_amount`].
+///
+/// Invalid input saturates, the result is checked against the maximum amount anyway.
+fn decompress_amount(x: u64) -> u64 {
+    if x == 0 {
+        return 0;
+    }
+    let x = x - 1;
+    let e = x % 10;
+    let x = x / 10;
+    let n = if e < 9 {
+        let d = x % 9 + 1;
+        (x / 9).saturating_mul(10).saturating_add(d)
+    } else {
+        x + 1
+    };
+    (0..e).fold(n, |n, _| n.saturating_mul(10))
+}
+
+/// Encodes a script in Core's compressed script format.
+///
+/// P2PKH, P2SH and P2PK scripts are stored as a one byte tag followed by the hash or the public
+/// key's x-coordinate, other scripts are stored with their length plus [`SPECIAL_SCRIPTS`].
+fn encode_script<W: Write + ?Sized>(
+    script_pubkey: &ScriptPubKeyBuf,
+    w: &mut W,
+) -> Result<usize, io::Error> {
+    let bytes = script_pubkey.as_bytes();
+    match *bytes {
+        [0x76, 0xa9, 20, ref hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            w.emit_u8(0x00)?;
+            Ok(1 + w.emit_slice(hash)?)
+        }
+        [0xa9, 20, ref hash @ .., 0x87] if hash.len() == 20 => {
+            w.emit_u8(0x01)?;
+            Ok(1 + w.emit_slice(hash)?)
+        }
+        [33, parity @ (0x02 | 0x03), ref x @ .., 0xac] if x.len() == 32 => {
+            w.emit_u8(parity)?;
+            Ok(1 + w.emit_slice(x)?)
+        }
+        // Hybrid keys (0x06 and 0x07) parse as valid keys but can't be recovered from the
+        // x-coordinate, like Core they are stored as raw scripts.
+        [65, ref key @ .., 0xac]
+            if key.len() == 65
+                && key[0] == 0x04
+                && secp256k1::PublicKey::from_slice(key).is_ok() =>
+        {
+            // Only the parity of the y-coordinate is kept.
+            w.emit_u8(0x04 | (key[64] & 0x01))?;
+            Ok(1 + w.emit_slice(&key[1..33])?)
+        }
+        _ => {
+            let len = write_varint(w, bytes.len() as u64 + SPECIAL_SCRIPTS)?;
+            Ok(len + w.emit_slice(bytes)?)
+        }
+    }
+}
+
+fn decode_script<R: BufRead + ?Sized>(r: &mut R) -> Result<ScriptPubKeyBuf, encode::Error> {
+    let tag = read_varint(r)?;
+    let bytes = match tag {
+        0x00 => {
+            let mut hash = [0; 20];
+            r.read_slice(&mut hash)?;
+            [&[0x76, 0xa9, 20][..], &hash, &[0x88, 0xac]].concat()
+        }
+        0x01 => {
+            let mut hash = [0; 20];
+            r.read_slice(&mut hash)?;
+            [&[0xa9, 20][..], &hash, &[0x87]].concat()
+        }
+        0x02 | 0x03 => {
+            let x: [u8; 32] = Decodable::consensus_decode(r)?;
+            [&[33, tag as u8][..], &x, &[0xac]].concat()
+        }
+        0x04 | 0x05 => {
+            let x: [u8; 32] = Decodable::consensus_decode(r)?;
+            let mut compressed = [0; 33];
+            compressed[0] = tag as u8 - 2;
+            compressed[1..].copy_from_slice(&x);
+            let key = secp256k1::PublicKey::from_slice(&compressed)
+                .map_err(|_| parse_failed_error("invalid compressed public key"))?;
+            [&[65][..], &key.serialize_uncompressed(), &[0xac]].concat()
+        }
+        _ => {
+            let len = tag - SPECIAL_SCRIPTS;
+            if len > MAX_SCRIPT_SIZE as u64 {
+                // Like Core, replace overly long scripts with a short unspendable one.
+                let mut buf = [0; 1024];
+                let mut remaining = len;
+                while remaining > 0 {
+                    let chunk = remaining.min(buf.len() as u64) as usize;
+                    r.read_slice(&mut buf[..chunk])?;
+                    remaining -= chunk as u64;
+                }
+                vec![0x6a]
+            } else {
+                let mut bytes = vec![0; len as usize];
+                r.read_slice(&mut bytes)?;
+                bytes
+            }
+        }
+    };
+    Ok(ScriptPubKeyBuf::from_bytes(bytes))
+}
+
+/// An error applying a block to a UTXO set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ApplyBlockError {
+    /// An input spends an output that is not in the set.
+    MissingCoin {
+        /// The outpoint of the missing output.
+        out_point: OutPoint,
+    },
+    /// An input spends a coinbase output before it is mature.
+    ImmatureCoinbase {
+        /// The outpoint of the coinbase output.
+        out_point: OutPoint,
+        /// The height of the first block that may spend the output.
+        mature_height: BlockHeight,
+    },
+}
+
+impl From<Infallible> for ApplyBlockError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ApplyBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingCoin { out_point } => write!(f, "missing or spent output {}", out_point),
+            Self::ImmatureCoinbase { out_point, mature_height } => write!(
+                f,
+                "coinbase output {} spent before it matures at height {}",
+                out_point, mature_height
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ApplyBlockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MissingCoin { .. } | Self::ImmatureCoinbase { .. } => None,
+        }
+    }
+}
+
+/// An error rolling back a block because the undo data does not belong to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UndoBlockError {
+    /// The number of transactions in the undo data does not match the block.
+    TxCount {
+        /// The number of non-coinbase transactions in the block.
+        expected: usize,
+        /// The number of transactions in the undo data.
+        actual: usize,
+    },
+    /// The number of spent outputs of a transaction does not match its number of inputs.
+    InputCount {
+        /// The index of the transaction in the block.
+        tx_index: usize,
+        /// The number of inputs of the transaction.
+        expected: usize,
+        /// The number of spent outputs in the undo data.
+        actual: usize,
+    },
+}
+
+impl From<Infallible> for UndoBlockError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for UndoBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TxCount { expected, actual } => write!(
+                f,
+                "undo data has {} transactions but the block has {} non-coinbase transactions",
+                actual, expected
+            ),
+            Self::InputCount { tx_index, expected, actual } => write!(
+                f,
+                "undo data has {} spent outputs for transaction {} which has {} inputs",
+                actual, tx_index, expected
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UndoBlockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::TxCount { .. } | Self::InputCount { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_lit::hex;
+
+    use super::*;
+    use crate::consensus::encode::{deserialize, serialize};
+    use crate::constants::genesis_block;
+    use crate::network::Network;
+    use crate::test_fixtures::{block, script, tx};
+
+    fn coin(height: u32, is_coinbase: bool) -> Coin {
+        Coin {
+            output: TxOut { amount: Amount::ONE_BTC, script_pubkey: script(0) },
+            height: BlockHeight::from_u32(height),
+            is_coinbase,
+        }
+    }
+
+    #[test]
+    fn varint() {
+        // Test vectors from the documentation of `WriteVarInt` in Core's `serialize.h`.
+        let vectors: &[(u64, &[u8])] = &[
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x00]),
+            (255, &[0x80, 0x7f]),
+            (256, &[0x81, 0x00]),
+            (16383, &[0xfe, 0x7f]),
+            (16384, &[0xff, 0x00]),
+            (16511, &[0xff, 0x7f]),
+            (65535, &[0x82, 0xfe, 0x7f]),
+            (1 << 32, &[0x8e, 0xfe, 0xfe, 0xff, 0x00]),
+        ];
+        for (n, bytes) in vectors {
+            let mut buf = Vec::new();
+            assert_eq!(write_varint(&mut buf, *n).unwrap(), bytes.len());
+            assert_eq!(buf, *bytes);
+            assert_eq!(read_varint(&mut &buf[..]).unwrap(), *n);
+        }
+
+        let mut buf = Vec::new();
+        write_varint(&mut buf, u64::MAX).unwrap();
+        assert_eq!(read_varint(&mut &buf[..]).unwrap(), u64::MAX);
+        assert!(read_varint(&mut &[0xff; 11][..]).is_err());
+    }
+
+    #[test]
+    fn amount_compression() {
+        // Test vectors from Core's `compress_tests.cpp`.
+        let vectors = [
+            (0, 0x0),
+            (1, 0x1),
+            (1_000_000, 0x7),
+            (100_000_000, 0x9),
+            (5_000_000_000, 0x32),
+            (2_100_000_000_000_000, 0x1406f40),
+        ];
+        for (amount, compressed) in vectors {
+            assert_eq!(compress_amount(amount), compressed);
+            assert_eq!(decompress_amount(compressed), amount);
+        }
+        for amount in (0..100_000).chain([999_999_999_990, 1_000_000_000_000]) {
+            assert_eq!(decompress_amount(compress_amount(amount)), amount);
+        }
+    }
+
+    #[test]
+    fn script_compression() {
+        let p2pkh = hex!("76a914000102030405060708090a0b0c0d0e0f1011121388ac");
+        let p2sh = hex!("a914000102030405060708090a0b0c0d0e0f1011121387");
+        let p2pk = hex!("2102f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9ac");
+        // The uncompressed form of the key above.
+        let p2pk_uncompressed = hex!("4104f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672ac");
+        let other = hex!("0014000102030405060708090a0b0c0d0e0f10111213");
+
+        for (script, compressed_len) in [
+            (&p2pkh[..], 21),
+            (&p2sh[..], 21),
+            (&p2pk[..], 33),
+            (&p2pk_uncompressed[..], 33),
+            (&other[..], 23),
+        ] {
+            let script = ScriptPubKeyBuf::from_bytes(script.to_vec());
+            let mut buf = Vec::new();
+            assert_eq!(encode_script(&script, &mut buf).unwrap(), compressed_len);
+            assert_eq!(buf.len(), compressed_len);
+            assert_eq!(decode_script(&mut &buf[..]).unwrap(), script);
+        }
+
+        // An invalid uncompressed key isn't compressed.
+        let mut invalid = p2pk_uncompressed;
+        invalid[2] ^= 1;
+        let mut buf = Vec::new();
+        assert_eq!(
+            encode_script(&ScriptPubKeyBuf::from_bytes(invalid.to_vec()), &mut buf).unwrap(),
+            68
+        );
+    }
+
+    #[test]
+    fn hybrid_key_script_compression() {
+        let mut hybrid = hex!("4104f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672ac");
+        // The y-coordinate is even.
+        hybrid[1] = 0x06;
+        assert!(secp256k1::PublicKey::from_slice(&hybrid[1..66]).is_ok());
+
+        let script = ScriptPubKeyBuf::from_bytes(hybrid.to_vec());
+        let mut buf = Vec::new();
+        assert_eq!(encode_script(&script, &mut buf).unwrap(), 68);
+        assert_eq!(decode_script(&mut &buf[..]).unwrap(), script);
+    }
+
+    #[test]
+    fn undo_encoding() {
+        let undo = BlockUndo {
+            txs: vec![
+                TxUndo { spent: vec![coin(0, false), coin(120, true)] },
+                TxUndo { spent: vec![coin(5, false)] },
+            ],
+        };
+        let bytes = serialize(&undo);
+        // Height zero has no dummy version byte.
+        assert_eq!(bytes[..5], [0x02, 0x02, 0x00, 0x09, 0x08]);
+        assert_eq!(deserialize::<BlockUndo>(&bytes).unwrap(), undo);
+    }
+
+    #[test]
+    fn apply_and_undo() {
+        let genesis = genesis_block(Network::Regtest);
+        let mut coins = MemoryCoins::new();
+        let genesis_undo = coins.apply_block(&genesis, BlockHeight::ZERO).unwrap();
+        assert_eq!(genesis_undo, BlockUndo::default());
+
+        let coinbase = tx(
+            &[OutPoint::COINBASE_PREVOUT],
+            &[script(1), ScriptPubKeyBuf::from_bytes(vec![0x6a])],
+        );
+        let coinbase_out_point = OutPoint { txid: coinbase.compute_txid(), vout: 0 };
+        let funding = OutPoint { txid: Txid::from_byte_array([1; 32]), vout: 0 };
+        coins.insert(funding, coin(1, false));
+        let pay = tx(&[funding], &[script(2), script(3)]);
+        let pay_txid = pay.compute_txid();
+        let spend = tx(&[OutPoint { txid: pay_txid, vout: 1 }], &[script(4)]);
+        let block = block(vec![coinbase, pay, spend.clone()]);
+
+        let before = coins.clone();
+        let undo = coins.apply_block(&block, BlockHeight::from_u32(2)).unwrap();
+        assert_eq!(
+            undo.txs,
+            [
+                TxUndo { spent: vec![coin(1, false)] },
+                TxUndo {
+                    spent: vec![Coin {
+                        output: TxOut { amount: Amount::ONE_SAT, script_pubkey: script(3) },
+                        height: BlockHeight::from_u32(2),
+                        is_coinbase: false,
+                    }]
+                }
+            ]
+        );
+        assert!(!coins.contains(&funding));
+        assert!(coins.contains(&OutPoint { txid: pay_txid, vout: 0 }));
+        assert!(coins.contains(&OutPoint { txid: spend.compute_txid(), vout: 0 }));
+        // The OP_RETURN output is not added.
+        assert!(!coins.contains(&OutPoint { vout: 1, ..coinbase_out_point }));
+        assert!(coins.get(&coinbase_out_point).unwrap().is_coinbase);
+
+        let mut wrong = undo.clone();
+        wrong.txs.pop();
+        assert_eq!(
+            coins.undo_block(&block, &wrong),
+            Err(UndoBlockError::TxCount { expected: 2, actual: 1 })
+        );
+
+        let undo = deserialize::<BlockUndo>(&serialize(&undo)).unwrap();
+        coins.undo_block(&block, &undo).unwrap();
+        assert_eq!(coins, before);
+    }
+
+    #[test]
+    fn apply_errors_leave_set_unchanged() {
+        let coinbase = tx(&[OutPoint::COINBASE_PREVOUT], &[script(1)]);
+        let coinbase_out_point = OutPoint { txid: coinbase.compute_txid(), vout: 0 };
+        let funding = OutPoint { txid: Txid::from_byte_array([1; 32]), vout: 0 };
+        let missing = OutPoint { txid: Txid::from_byte_array([2; 32]), vout: 0 };
+
+        let mut coins = MemoryCoins::new();
+        coins.insert(funding, coin(1, false));
+        coins.insert(coinbase_out_point, coin(10, true));
+        let before = coins.clone();
+
+        let block_missing = block(vec![
+            tx(&[OutPoint::COINBASE_PREVOUT], &[script(5)]),
+            tx(&[funding], &[script(2)]),
+            tx(&[funding, missing], &[script(3)]),
+        ]);
+        assert_eq!(
+            coins.apply_block(&block_missing, BlockHeight::from_u32(200)),
+            Err(ApplyBlockError::MissingCoin { out_point: funding })
+        );
+        assert_eq!(coins, before);
+
+        let block_immature = block(vec![
+            tx(&[OutPoint::COINBASE_PREVOUT], &[script(4)]),
+            tx(&[funding, coinbase_out_point], &[script(3)]),
+        ]);
+        assert_eq!(
+            coins.apply_block(&block_immature, BlockHeight::from_u32(109)),
+            Err(ApplyBlockError::ImmatureCoinbase {
+                out_point: coinbase_out_point,
+                mature_height: BlockHeight::from_u32(110)
+            })
+        );
+        assert_eq!(coins, before);
+        assert!(coins.apply_block(&block_immature, BlockHeight::from_u32(110)).is_ok());
+    }
+
+    #[test]
+    fn checksum_covers_prev_blockhash() {
+        let undo = BlockUndo { txs: vec![TxUndo { spent: vec![coin(3, false)] }] };
+        let a = undo.checksum(BlockHash::from_byte_array([0; 32]));
+        let b = undo.checksum(BlockHash::from_byte_array([1; 32]));
+        assert_ne!(a, b);
+
+        let mut data = [0; 32].to_vec();
+        data.extend(serialize(&undo));
+        assert_eq!(a, sha256d::Hash::hash(&data));
+    }
+}
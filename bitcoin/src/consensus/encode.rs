@@ -556,6 +556,26 @@ fn read_bytes_from_finite_reader<D: Read + ?Sized>(
     Ok(ret)
 }
 
+/// Reads `len` bytes from a finite reader into `buf`, reusing its allocation.
+///
+/// Like [`read_bytes_from_finite_reader`] this grows the buffer in chunks, `len` could be
+/// malicious.
+pub(crate) fn read_bytes_into_from_finite_reader<D: Read + ?Sized>(
+    d: &mut D,
+    mut len: usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), Error> {
+    buf.clear();
+    while len > 0 {
+        let chunk_start = buf.len();
+        let chunk_size = cmp::min(len, 128 * 1024);
+        buf.resize(chunk_start + chunk_size, 0u8);
+        d.read_slice(&mut buf[chunk_start..])?;
+        len -= chunk_size;
+    }
+    Ok(())
+}
+
 impl Encodable for Box<[u8]> {
     #[inline]
     fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
@@ -106,3 +106,33 @@ fn consume(&mut self, len: usize) {
         }
     }
 }
+
+/// Checks that decoding `data` as a `T` round trips, for use in fuzz targets.
+///
+/// If `data` decodes, asserts that encoding the value returns `data` and that the encoding decodes
+/// to the same value again. Returns the value for further checks by the fuzz target.
+///
+/// # Panics
+///
+/// If the value does not round trip, which is a bug in the codec of `T`.
+///
+/// # Examples
+///
+/// ```
+/// fn do_test(data: &[u8]) {
+///     let _ = bitcoin::consensus::fuzz_roundtrip::<bitcoin::Transaction>(data);
+/// }
+/// # do_test(&[0; 10]);
+/// ```
+#[cfg(feature = "testutil")]
+pub fn fuzz_roundtrip<T>(data: &[u8]) -> Option<T>
+where
+    T: Decodable + Encodable + PartialEq + fmt::Debug,
+{
+    let value = deserialize::<T>(data).ok()?;
+    let encoded = serialize(&value);
+    assert_eq!(encoded, data, "re-encoding {} changed the bytes", core::any::type_name::<T>());
+    let decoded = deserialize::<T>(&encoded).expect("re-encoding decodes");
+    assert_eq!(decoded, value, "re-decoding {} changed the value", core::any::type_name::<T>());
+    Some(value)
+}
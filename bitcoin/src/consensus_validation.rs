@@ -16,6 +16,15 @@
 use crate::internal_macros::define_extension_trait;
 use crate::script::ScriptPubKey;
 use crate::transaction::{OutPoint, Transaction, TxOut};
+#[cfg(feature = "rayon")]
+use crate::{
+    key::XOnlyPublicKey,
+    script::ScriptPubKeyExt as _,
+    sighash::{Annex, Prevouts, SighashCache},
+    taproot,
+    witness_program::{WitnessProgram, WitnessProgramKind},
+    TapSighash,
+};
 
 /// Verifies spend of an input script.
 ///
@@ -117,6 +126,199 @@ pub fn verify_transaction_with_flags<S, F>(
     Ok(())
 }
 
+/// Verifies that the transactions are able to spend their inputs, using all cores.
+///
+/// Shorthand for [`consensus_validation::verify_transactions_parallel_with_flags`] with flag
+/// [`bitcoinconsensus::VERIFY_ALL_PRE_TAPROOT`].
+///
+/// **Taproot script path spends are not verified**, libbitcoinconsensus runs the scripts with
+/// the pre-Taproot rules, under which any witness spending a version 1 output is valid. Only the
+/// signatures of Taproot key path spends are checked.
+///
+/// [`bitcoinconsensus::VERIFY_ALL_PRE_TAPROOT`]: https://docs.rs/bitcoinconsensus/0.106.0+26.0/bitcoinconsensus/constant.VERIFY_ALL_PRE_TAPROOT.html
+#[cfg(feature = "rayon")]
+pub fn verify_transactions_parallel(
+    txs: &[(&Transaction, &[TxOut])],
+) -> Result<(), ParallelVerifyError> {
+    verify_transactions_parallel_with_flags(txs, bitcoinconsensus::VERIFY_ALL_PRE_TAPROOT)
+}
+
+/// Verifies that the transactions are able to spend their inputs, using all cores.
+///
+/// Each transaction is paired with the outputs spent by its inputs, `prevouts[i]` is spent by
+/// input `i`. The scripts of all inputs are verified in parallel on the rayon thread pool, which
+/// is the bulk of the work when validating a block or a large batch of transactions.
+///
+/// If `flags` include [`bitcoinconsensus::VERIFY_WITNESS`], the signatures of Taproot key path
+/// spends are queued and verified together once the scripts have been run. secp256k1 does not
+/// expose batch verification yet, so the queued signatures are verified one by one on the thread
+/// pool. **Taproot script path spends are not verified** unless `flags` include
+/// [`bitcoinconsensus::VERIFY_TAPROOT`], which libbitcoinconsensus rejects without the spent
+/// outputs of all inputs, so none of the `VERIFY_ALL_*` flags verify them.
+///
+/// # Errors
+///
+/// If any input fails to verify. The error is the one of the first failing input, in the order
+/// of `txs` and of the inputs, as if the transactions were verified sequentially. The inputs
+/// after a failure found so far are skipped.
+///
+/// [`bitcoinconsensus::VERIFY_WITNESS`]: https://docs.rs/bitcoinconsensus/0.106.0+26.0/bitcoinconsensus/constant.VERIFY_WITNESS.html
+/// [`bitcoinconsensus::VERIFY_TAPROOT`]: https://docs.rs/bitcoinconsensus/0.106.0+26.0/bitcoinconsensus/constant.VERIFY_TAPROOT.html
+#[cfg(feature = "rayon")]
+pub fn verify_transactions_parallel_with_flags<F: Into<u32>>(
+    txs: &[(&Transaction, &[TxOut])],
+    flags: F,
+) -> Result<(), ParallelVerifyError> {
+    use rayon::iter::{
+        IndexedParallelIterator as _, IntoParallelIterator as _, IntoParallelRefIterator as _,
+        ParallelIterator as _,
+    };
+
+    let flags: u32 = flags.into();
+    let serialized = txs.par_iter().map(|(tx, _)| encode::serialize(*tx)).collect::<Vec<_>>();
+    let jobs = txs
+        .iter()
+        .enumerate()
+        .flat_map(|(tx_index, (tx, _))| (0..tx.inputs.len()).map(move |input| (tx_index, input)))
+        .collect::<Vec<_>>();
+
+    let script_failure = (0..jobs.len()).into_par_iter().find_map_first(|job| {
+        let (tx_index, input) = jobs[job];
+        let (tx, prevouts) = txs[tx_index];
+        let Some(prevout) = prevouts.get(input) else {
+            return Some((
+                job,
+                TxVerifyError::UnknownSpentOutput(tx.inputs[input].previous_output),
+            ));
+        };
+        verify_script_with_flags(
+            &prevout.script_pubkey,
+            input,
+            prevout.amount,
+            &serialized[tx_index],
+            flags,
+        )
+        .err()
+        .map(|e| (job, e.into()))
+    });
+    // Signatures of inputs after the first script failure can't change the error.
+    let end = script_failure.as_ref().map_or(jobs.len(), |(job, _)| *job);
+
+    let signature_failure = if flags & bitcoinconsensus::VERIFY_WITNESS == 0 {
+        None
+    } else {
+        let first_jobs = txs
+            .iter()
+            .scan(0, |first_job, (tx, _)| {
+                let first = *first_job;
+                *first_job += tx.inputs.len();
+                Some(first)
+            })
+            .collect::<Vec<_>>();
+        let queue = txs
+            .par_iter()
+            .zip(first_jobs)
+            .flat_map_iter(|(&(tx, prevouts), first_job)| {
+                taproot_key_spends(tx, prevouts)
+                    .map(move |(input, key_spend)| (first_job + input, key_spend))
+            })
+            .filter(|(job, _)| *job < end)
+            .collect::<Vec<_>>();
+        queue.into_par_iter().find_map_first(|(job, key_spend)| match key_spend {
+            Some(key_spend) if key_spend.verify() => None,
+            _ => Some(job),
+        })
+    };
+
+    match (script_failure, signature_failure) {
+        (_, Some(job)) => {
+            let (tx_index, input) = jobs[job];
+            let outpoint = txs[tx_index].0.inputs[input].previous_output;
+            Err(ParallelVerifyError {
+                tx_index,
+                error: TxVerifyError::InvalidTaprootSignature(outpoint),
+            })
+        }
+        (Some((job, error)), None) => Err(ParallelVerifyError { tx_index: jobs[job].0, error }),
+        (None, None) => Ok(()),
+    }
+}
+
+/// A Taproot key path signature and the message it signs, queued for verification.
+#[cfg(feature = "rayon")]
+struct KeySpend {
+    signature: secp256k1::schnorr::Signature,
+    sighash: TapSighash,
+    output_key: XOnlyPublicKey,
+}
+
+#[cfg(feature = "rayon")]
+impl KeySpend {
+    /// Parses the key path `signature` spending `prevouts[input]`, `None` if it can't be valid.
+    fn new(
+        cache: &mut SighashCache<&Transaction>,
+        input: usize,
+        prevouts: &[TxOut],
+        signature: &[u8],
+        annex: Option<Annex>,
+    ) -> Option<Self> {
+        let program = WitnessProgram::from_script_pubkey(&prevouts[input].script_pubkey)?;
+        let WitnessProgramKind::V1Taproot(output_key) = program.kind() else { return None };
+        // An explicit `SIGHASH_DEFAULT` byte is invalid.
+        if signature.len() == 65 && signature[64] == 0 {
+            return None;
+        }
+        let signature = taproot::Signature::from_slice(signature).ok()?;
+        let sighash = cache
+            .taproot_signature_hash(
+                input,
+                &Prevouts::All(prevouts),
+                annex,
+                None,
+                signature.sighash_type,
+            )
+            .ok()?;
+        Some(Self { signature: signature.signature, sighash, output_key })
+    }
+
+    fn verify(&self) -> bool {
+        secp256k1::schnorr::verify(
+            &self.signature,
+            &self.sighash.to_byte_array(),
+            &self.output_key.to_inner(),
+        )
+        .is_ok()
+    }
+}
+
+/// Returns the input index of the Taproot key path spends of `tx` and their signatures, `None` if
+/// the signature can't be valid.
+#[cfg(feature = "rayon")]
+fn taproot_key_spends<'a>(
+    tx: &'a Transaction,
+    prevouts: &'a [TxOut],
+) -> impl Iterator<Item = (usize, Option<KeySpend>)> + 'a {
+    // There is no sighash without all the spent outputs, the missing ones fail the script check.
+    let prevouts = prevouts.get(..tx.inputs.len()).unwrap_or_default();
+    let mut cache = SighashCache::new(tx);
+    tx.inputs.iter().zip(prevouts).enumerate().filter_map(move |(input, (txin, prevout))| {
+        if !prevout.script_pubkey.is_p2tr() {
+            return None;
+        }
+        let witness = &txin.witness;
+        let (signature, annex) = match witness.len() {
+            0 => return Some((input, None)),
+            1 => (&witness[0], None),
+            2 => match Annex::new(&witness[1]) {
+                Ok(annex) => (&witness[0], Some(annex)),
+                Err(_) => return None, // A script path spend.
+            },
+            _ => return None, // A script path spend.
+        };
+        Some((input, KeySpend::new(&mut cache, input, prevouts, signature, annex)))
+    })
+}
+
 define_extension_trait! {
     /// Extension functionality to add validation support to the [`ScriptPubKey`] type.
     pub trait ScriptPubKeyExt impl for ScriptPubKey {
@@ -236,6 +438,8 @@ pub enum TxVerifyError {
     ScriptVerification(BitcoinconsensusError),
     /// Cannot find the spent output.
     UnknownSpentOutput(OutPoint),
+    /// The Taproot key path signature spending the output is invalid.
+    InvalidTaprootSignature(OutPoint),
 }
 
 impl From<Infallible> for TxVerifyError {
@@ -248,6 +452,8 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             Self::ScriptVerification(ref e) =>
                 write_err!(f, "bitcoinconsensus verification failed"; e),
             Self::UnknownSpentOutput(ref p) => write!(f, "unknown spent output: {}", p),
+            Self::InvalidTaprootSignature(ref p) =>
+                write!(f, "invalid Taproot key path signature spending {}", p),
         }
     }
 }
@@ -257,11 +463,133 @@ impl std::error::Error for TxVerifyError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::ScriptVerification(ref e) => Some(e),
-            Self::UnknownSpentOutput(_) => None,
+            Self::UnknownSpentOutput(_) | Self::InvalidTaprootSignature(_) => None,
         }
     }
 }
 
+/// An error verifying transactions in parallel, see [`verify_transactions_parallel`].
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParallelVerifyError {
+    tx_index: usize,
+    error: TxVerifyError,
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelVerifyError {
+    /// Returns the index of the transaction that failed to verify.
+    pub fn tx_index(&self) -> usize { self.tx_index }
+
+    /// Returns the error verifying the transaction.
+    pub fn error(&self) -> &TxVerifyError { &self.error }
+}
+
+#[cfg(feature = "rayon")]
+impl From<Infallible> for ParallelVerifyError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+#[cfg(feature = "rayon")]
+impl fmt::Display for ParallelVerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_err!(f, "transaction {} failed to verify", self.tx_index; self.error)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl std::error::Error for ParallelVerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(&self.error) }
+}
+
 impl From<BitcoinconsensusError> for TxVerifyError {
     fn from(e: BitcoinconsensusError) -> Self { Self::ScriptVerification(e) }
 }
+
+#[cfg(all(test, feature = "rayon"))]
+mod tests {
+    use secp256k1::SecretKey;
+
+    use super::*;
+    use crate::key::{Keypair, TapTweak as _};
+    use crate::script::{ScriptPubKeyBuf, ScriptPubKeyBufExt as _};
+    use crate::sighash::TapSighashType;
+    use crate::transaction::{self, TxIn};
+    use crate::{absolute, Txid, Witness};
+
+    #[test]
+    fn verify_transactions_parallel() {
+        let keypair = Keypair::from_secret_key(&SecretKey::from_secret_bytes([1; 32]).unwrap());
+        let (internal_key, _) = XOnlyPublicKey::from_keypair(&keypair);
+        let op_true = ScriptPubKeyBuf::from_bytes(vec![0x51]);
+        let prevouts = [
+            TxOut { amount: Amount::ONE_SAT, script_pubkey: op_true },
+            TxOut {
+                amount: Amount::ONE_SAT,
+                script_pubkey: ScriptPubKeyBuf::new_p2tr(internal_key, None),
+            },
+        ];
+        let mut tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: (0..2)
+                .map(|vout| TxIn {
+                    previous_output: OutPoint { txid: Txid::from_byte_array([1; 32]), vout },
+                    ..TxIn::EMPTY_COINBASE
+                })
+                .collect(),
+            outputs: vec![TxOut { amount: Amount::ONE_SAT, script_pubkey: ScriptPubKeyBuf::new() }],
+        };
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(1, &Prevouts::All(&prevouts), TapSighashType::Default)
+            .unwrap();
+        let signature = keypair
+            .tap_tweak(None)
+            .to_keypair()
+            .to_inner()
+            .sign_schnorr_no_aux_rand(&sighash.to_byte_array());
+        tx.inputs[1].witness = Witness::from_slice(&[signature.to_byte_array()]);
+
+        let txs = vec![(&tx, &prevouts[..]); 8];
+        super::verify_transactions_parallel(&txs).unwrap();
+        super::verify_transactions_parallel(&[]).unwrap();
+
+        let mut corrupted = tx.clone();
+        let mut bad_signature = signature.to_byte_array();
+        bad_signature[10] ^= 1;
+        corrupted.inputs[1].witness = Witness::from_slice(&[bad_signature]);
+        let op_false = [
+            TxOut { amount: Amount::ONE_SAT, script_pubkey: ScriptPubKeyBuf::from_bytes(vec![0]) },
+            prevouts[1].clone(),
+        ];
+
+        // The first failing input is reported, whether it fails the script or the signature check.
+        let mut failing = txs.clone();
+        failing[5] = (&corrupted, &prevouts);
+        failing[6] = (&tx, &op_false);
+        let error = super::verify_transactions_parallel(&failing).unwrap_err();
+        assert_eq!(error.tx_index(), 5);
+        assert_eq!(
+            *error.error(),
+            TxVerifyError::InvalidTaprootSignature(tx.inputs[1].previous_output)
+        );
+
+        failing[3] = (&corrupted, &op_false);
+        let error = super::verify_transactions_parallel(&failing).unwrap_err();
+        assert_eq!(error.tx_index(), 3);
+        assert!(matches!(error.error(), TxVerifyError::ScriptVerification(_)));
+
+        failing[2] = (&tx, &prevouts[..1]);
+        let error = super::verify_transactions_parallel(&failing).unwrap_err();
+        assert_eq!(error.tx_index(), 2);
+        assert_eq!(*error.error(), TxVerifyError::UnknownSpentOutput(tx.inputs[1].previous_output));
+
+        // Signatures are only checked with the SegWit rules.
+        verify_transactions_parallel_with_flags(
+            &[(&corrupted, &prevouts[..])],
+            bitcoinconsensus::VERIFY_P2SH,
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Commitments to arbitrary data in public keys and signatures.
+//!
+//! Pay-to-contract commits to data in a public key, `P' = P + H(P || data)·G`. Committing in the
+//! internal key of a Taproot output keeps both the key path and the script path spendable and the
+//! output indistinguishable from any other, see [`TapTweak::tap_tweak_with_contract`]. Sign-to-
+//! contract commits to data in the nonce of a BIP-0340 signature, `R = K + H(K || data)·G`, the
+//! signature is an ordinary signature and revealing the original nonce `K` opens the commitment.
+//!
+//! Both are used to anchor timestamps and to prove that an exchange controls the coins of its
+//! proof of reserves without additional outputs.
+//!
+//! # Stability
+//!
+//! The commitments hash the data with the `PayToContract` and `SignToContract` tags chosen by this
+//! crate, other pay-to-contract and sign-to-contract schemes hash differently and their
+//! commitments do not verify here. The tags may still change.
+//!
+//! [`TapTweak::tap_tweak_with_contract`]: crate::key::TapTweak::tap_tweak_with_contract
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::contract;
+//! use bitcoin::key::{Keypair, TapTweak, XOnlyPublicKey};
+//! use bitcoin::secp256k1::{schnorr, SecretKey};
+//!
+//! let keypair = Keypair::from_secret_key(&SecretKey::from_secret_bytes([1; 32]).unwrap());
+//! let (internal_key, _) = XOnlyPublicKey::from_keypair(&keypair);
+//!
+//! // Commit to a document in the output key, spending with the committed keypair.
+//! let (output_key, _) = internal_key.tap_tweak_with_contract(b"document", None);
+//! assert!(contract::verify_key_commitment(internal_key, b"document", None, output_key));
+//! let spending = keypair.tap_tweak_with_contract(b"document", None);
+//! assert_eq!(spending.public_parts().0, output_key);
+//!
+//! // Commit to a document in a signature.
+//! let msg = [2; 32];
+//! let (sig, opening) = contract::sign_to_contract(&msg, &keypair, b"document", &[0; 32]);
+//! assert!(schnorr::verify(&sig, &msg, &internal_key.to_inner()).is_ok());
+//! assert!(opening.verify(&sig, b"document"));
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use hashes::{sha256, sha256t, sha256t_tag, HashEngine as _};
+use secp256k1::{schnorr, Parity, Scalar};
+
+use crate::crypto::key::{
+    TapTweak as _, TweakedPublicKey, UntweakedKeypair, UntweakedPublicKey, XOnlyPublicKey,
+};
+use crate::crypto::taproot::{challenge, derive_nonce};
+use crate::taproot::TapNodeHash;
+
+sha256t_tag! {
+    struct PayToContractTag = hash_str("PayToContract");
+}
+
+sha256t_tag! {
+    struct SignToContractTag = hash_str("SignToContract");
+}
+
+sha256t_tag! {
+    struct AuxTag = hash_str("SignToContract/aux");
+}
+
+sha256t_tag! {
+    struct NonceTag = hash_str("SignToContract/nonce");
+}
+
+/// Returns the `T` tagged hash of `point` followed by `data` as a scalar.
+fn commitment_tweak<T: sha256t::Tag>(point: &[u8], data: &[u8]) -> Scalar {
+    let mut engine = sha256t::Hash::<T>::engine();
+    engine.input(point);
+    engine.input(data);
+    Scalar::from_be_bytes(engine.finalize().to_byte_array())
+        .expect("statistically impossible to hit")
+}
+
+/// Returns the pay-to-contract tweak committing `key` to `data`.
+pub(crate) fn pay_to_contract_tweak(key: &UntweakedPublicKey, data: &[u8]) -> Scalar {
+    commitment_tweak::<PayToContractTag>(&key.serialize(), data)
+}
+
+/// Returns `true` if `output_key` is the Taproot output key of `internal_key` committed to `data`
+/// with the script tree `merkle_root`.
+///
+/// See [`TapTweak::tap_tweak_with_contract`](crate::key::TapTweak::tap_tweak_with_contract).
+pub fn verify_key_commitment(
+    internal_key: UntweakedPublicKey,
+    data: &[u8],
+    merkle_root: Option<TapNodeHash>,
+    output_key: TweakedPublicKey,
+) -> bool {
+    internal_key.tap_tweak_with_contract(data, merkle_root).0 == output_key
+}
+
+/// Signs `msg` with `keypair` committing to `data` in the nonce.
+///
+/// Returns the signature, an ordinary BIP-0340 signature, and the opening of the commitment. Like
+/// in BIP-0340 `aux_rand` should be fresh randomness, but signing is secure with any value.
+pub fn sign_to_contract(
+    msg: &[u8],
+    keypair: &UntweakedKeypair,
+    data: &[u8],
+    aux_rand: &[u8; 32],
+) -> (schnorr::Signature, NonceCommitment) {
+    let (pubkey, parity) = XOnlyPublicKey::from_keypair(keypair);
+    let mut secret = keypair.to_secret_key();
+    if parity == Parity::Odd {
+        secret = secret.negate();
+    }
+
+    let data_hash = sha256::Hash::hash(data).to_byte_array();
+    let original = derive_nonce::<AuxTag, NonceTag>(
+        &secret,
+        aux_rand,
+        &[&pubkey.serialize(), &data_hash, msg],
+    );
+    let opening = NonceCommitment(original.public_key());
+    let nonce = original.add_tweak(&opening.tweak(data)).expect("statistically impossible to hit");
+    let (rx, r_parity) = nonce.x_only_public_key();
+    let nonce = if r_parity == Parity::Odd { nonce.negate() } else { nonce };
+
+    let e = challenge(&rx.serialize(), &pubkey, msg);
+    let s = secret
+        .mul_tweak(&e)
+        .and_then(|ex| ex.add_tweak(&Scalar::from(nonce)))
+        .expect("statistically impossible to hit");
+
+    let mut sig = [0; 64];
+    sig[..32].copy_from_slice(&rx.serialize());
+    sig[32..].copy_from_slice(&s.to_secret_bytes());
+    (schnorr::Signature::from_byte_array(sig), opening)
+}
+
+/// The opening of a sign-to-contract commitment, the original nonce point of the signature.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NonceCommitment(secp256k1::PublicKey);
+
+impl NonceCommitment {
+    /// The size of a serialized opening.
+    pub const SIZE: usize = 33;
+
+    /// Returns `true` if the nonce of `sig` is the original nonce committed to `data`.
+    ///
+    /// This does not verify the signature itself.
+    pub fn verify(&self, sig: &schnorr::Signature, data: &[u8]) -> bool {
+        match self.0.add_exp_tweak(&self.tweak(data)) {
+            Ok(nonce) => nonce.x_only_public_key().0.serialize() == sig.as_byte_array()[..32],
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the tweak committing the original nonce to `data`.
+    fn tweak(&self, data: &[u8]) -> Scalar {
+        commitment_tweak::<SignToContractTag>(&self.0.serialize(), data)
+    }
+
+    /// Serializes the opening as the compressed original nonce point.
+    pub fn to_byte_array(&self) -> [u8; Self::SIZE] { self.0.serialize() }
+
+    /// Deserializes an opening serialized with [`Self::to_byte_array`].
+    pub fn from_byte_array(bytes: &[u8; Self::SIZE]) -> Result<Self, ParseNonceCommitmentError> {
+        secp256k1::PublicKey::from_slice(bytes).map(Self).map_err(|_| ParseNonceCommitmentError)
+    }
+}
+
+/// The bytes are not a valid point, see [`NonceCommitment::from_byte_array`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseNonceCommitmentError;
+
+impl From<Infallible> for ParseNonceCommitmentError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ParseNonceCommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("nonce commitment is not a valid point")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseNonceCommitmentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair(byte: u8) -> UntweakedKeypair {
+        UntweakedKeypair::from_secret_key(
+            &secp256k1::SecretKey::from_secret_bytes([byte; 32]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn pay_to_contract() {
+        for i in 1..=8 {
+            let keypair = keypair(i);
+            let (key, _) = XOnlyPublicKey::from_keypair(&keypair);
+            let merkle_root = Some(TapNodeHash::from_byte_array([i; 32]));
+            let committed = key.commit_to_contract(&[i; 10]);
+            assert_ne!(committed, key);
+            assert_eq!(
+                key.tap_tweak_with_contract(&[i; 10], merkle_root),
+                committed.tap_tweak(merkle_root)
+            );
+
+            let (output_key, _) = key.tap_tweak_with_contract(&[i; 10], merkle_root);
+            assert!(verify_key_commitment(key, &[i; 10], merkle_root, output_key));
+            assert!(!verify_key_commitment(key, &[i; 11], merkle_root, output_key));
+            assert!(!verify_key_commitment(key, &[i; 10], None, output_key));
+            assert!(!verify_key_commitment(committed, &[i; 10], merkle_root, output_key));
+
+            let committed_keypair = keypair.commit_to_contract(&[i; 10]);
+            assert_eq!(XOnlyPublicKey::from_keypair(&committed_keypair).0, committed);
+            assert_eq!(
+                keypair.tap_tweak_with_contract(&[i; 10], merkle_root).public_parts().0,
+                output_key
+            );
+        }
+    }
+
+    #[test]
+    fn sign_to_contract_opening() {
+        let mut parities = [false; 2];
+        for i in 1..=16 {
+            let keypair = keypair(i);
+            let (pubkey, _) = XOnlyPublicKey::from_keypair(&keypair);
+            let msg = [i; 32];
+            let (sig, opening) = sign_to_contract(&msg, &keypair, &[i; 10], &[i; 32]);
+            assert!(schnorr::verify(&sig, &msg, &pubkey.to_inner()).is_ok());
+            assert!(opening.verify(&sig, &[i; 10]));
+            assert!(!opening.verify(&sig, &[i; 11]));
+            let tweaked = opening.0.add_exp_tweak(&opening.tweak(&[i; 10])).unwrap();
+            parities[tweaked.x_only_public_key().1.to_u8() as usize] = true;
+
+            let bytes = opening.to_byte_array();
+            assert_eq!(NonceCommitment::from_byte_array(&bytes), Ok(opening));
+        }
+        // Both nonce parities are covered.
+        assert_eq!(parities, [true, true]);
+
+        // An ordinary signature does not open a commitment.
+        let keypair = keypair(1);
+        let (sig, opening) = sign_to_contract(&[1; 32], &keypair, b"data", &[0; 32]);
+        let ordinary = keypair.to_inner().sign_schnorr_no_aux_rand(&[1; 32]);
+        assert_ne!(ordinary, sig);
+        assert!(!opening.verify(&ordinary, b"data"));
+        assert_eq!(NonceCommitment::from_byte_array(&[0; 33]), Err(ParseNonceCommitmentError));
+    }
+}
@@ -22,6 +22,7 @@
 use crate::sighash::{EcdsaSighashType, NonStandardSighashTypeError};
 
 const MAX_SIG_LEN: usize = 73;
+const MIN_SIG_LEN: usize = 9;
 
 /// An ECDSA signature with the corresponding hash type.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -48,6 +49,47 @@ pub fn from_slice(sl: &[u8]) -> Result<Self, DecodeError> {
         Ok(Self { signature, sighash_type })
     }
 
+    /// Deserializes from slice following the BIP-0066 strict DER rules and the standardness rules
+    /// for [`EcdsaSighashType`].
+    ///
+    /// See [`check_strict_der`] for a description of the rules.
+    pub fn from_slice_strict(sl: &[u8]) -> Result<Self, DecodeError> {
+        check_strict_der(sl)?;
+        Self::from_slice(sl)
+    }
+
+    /// Deserializes from slice accepting the non-strict DER encodings and sighash types found
+    /// in historical chain data.
+    ///
+    /// The signature is parsed like OpenSSL did before BIP-0066 and the sighash type is
+    /// interpreted using [`EcdsaSighashType::from_consensus`], which does not preserve
+    /// non-standard values; compute the sighash of such signatures using the raw last byte. The
+    /// S value is not normalized, use [`Self::normalize_s`] before verifying the signature with
+    /// libsecp256k1.
+    pub fn from_der_lax(sl: &[u8]) -> Result<Self, DecodeError> {
+        let (sighash_type, sig) = sl.split_last().ok_or(DecodeError::EmptySignature)?;
+        let sighash_type = EcdsaSighashType::from_consensus(u32::from(*sighash_type));
+        let signature =
+            secp256k1::ecdsa::Signature::from_der_lax(sig).map_err(DecodeError::Secp256k1)?;
+        Ok(Self { signature, sighash_type })
+    }
+
+    /// Returns `true` if the S value of the signature is in the lower half of the curve order.
+    ///
+    /// libsecp256k1 only verifies low S signatures and standardness rules (BIP-0146) require
+    /// them.
+    pub fn is_low_s(&self) -> bool {
+        let mut normalized = self.signature;
+        normalized.normalize_s();
+        normalized == self.signature
+    }
+
+    /// Normalizes the S value of the signature to the lower half of the curve order.
+    ///
+    /// Signatures with a high S value are valid by consensus but cannot be verified by
+    /// libsecp256k1, normalizing them does not change which message and key they are valid for.
+    pub fn normalize_s(&mut self) { self.signature.normalize_s() }
+
     /// Serializes an ECDSA signature (inner secp256k1 signature in DER format).
     ///
     /// This does **not** perform extra heap allocation.
@@ -96,6 +138,102 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
     }
 }
 
+/// Checks that `sig`, a DER signature followed by the sighash type byte, follows the BIP-0066
+/// strict DER encoding rules.
+///
+/// This is the check Bitcoin Core does in `IsValidSignatureEncoding`: the signature is a DER
+/// sequence of exactly two integers R and S, neither of which is empty, negative or padded with
+/// unnecessary zero bytes. The sighash type is not checked.
+///
+/// # Errors
+///
+/// Returns the first violated rule and the position of the offending byte.
+pub fn check_strict_der(sig: &[u8]) -> Result<(), StrictDerError> {
+    use StrictDerErrorKind as Kind;
+
+    let err = |kind, position| Err(StrictDerError { kind, position });
+
+    if sig.len() < MIN_SIG_LEN {
+        return err(Kind::InvalidLength, sig.len());
+    }
+    if sig.len() > MAX_SIG_LEN {
+        return err(Kind::InvalidLength, MAX_SIG_LEN);
+    }
+    if sig[0] != 0x30 {
+        return err(Kind::NotSequence, 0);
+    }
+    if usize::from(sig[1]) != sig.len() - 3 {
+        return err(Kind::SequenceLength, 1);
+    }
+    let len_r = usize::from(sig[3]);
+    if 5 + len_r >= sig.len() {
+        return err(Kind::IntegerLength, 3);
+    }
+    let len_s = usize::from(sig[5 + len_r]);
+    if len_r + len_s + 7 != sig.len() {
+        return err(Kind::IntegerLength, 5 + len_r);
+    }
+
+    // R starts at position 2, S at position 4 + len_r.
+    for (start, len) in [(2, len_r), (4 + len_r, len_s)] {
+        if sig[start] != 0x02 {
+            return err(Kind::NotInteger, start);
+        }
+        if len == 0 {
+            return err(Kind::ZeroLength, start + 1);
+        }
+        let first = sig[start + 2];
+        if first & 0x80 != 0 {
+            return err(Kind::Negative, start + 2);
+        }
+        if len > 1 && first == 0x00 && sig[start + 3] & 0x80 == 0 {
+            return err(Kind::ExcessPadding, start + 2);
+        }
+    }
+    Ok(())
+}
+
+/// The encoding of a serialized ECDSA signature, see [`SignatureInfo::inspect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInfo {
+    /// The sighash type byte, the last byte of the signature.
+    pub sighash_byte: u8,
+    /// The length of R declared by the DER encoding, if the encoding gets that far.
+    pub r_len: Option<usize>,
+    /// The length of S declared by the DER encoding, if the encoding gets that far.
+    pub s_len: Option<usize>,
+    /// The first violated BIP-0066 rule, `None` if the signature is strict DER.
+    pub der_error: Option<StrictDerError>,
+}
+
+impl SignatureInfo {
+    /// Inspects `sig`, a DER signature followed by the sighash type byte, as pushed by a script
+    /// sig or found in a witness.
+    ///
+    /// Unlike [`Signature::from_slice`] nothing is rejected, so this also describes the malformed
+    /// and non-standard signatures found in historical chain data. Returns `None` if `sig` is
+    /// empty.
+    pub fn inspect(sig: &[u8]) -> Option<Self> {
+        let (&sighash_byte, _) = sig.split_last()?;
+        // The sequence tag and length, then the integer tag and length of R.
+        let r_len =
+            (sig.len() > 4 && sig[0] == 0x30 && sig[2] == 0x02).then(|| usize::from(sig[3]));
+        let s_len = r_len.and_then(|r_len| match sig.get(4 + r_len..6 + r_len) {
+            Some(&[0x02, s_len]) => Some(usize::from(s_len)),
+            _ => None,
+        });
+        Some(Self { sighash_byte, r_len, s_len, der_error: check_strict_der(sig).err() })
+    }
+
+    /// Returns the sighash type, `None` if the sighash type byte is not standard.
+    pub fn sighash_type(&self) -> Option<EcdsaSighashType> {
+        EcdsaSighashType::from_standard(u32::from(self.sighash_byte)).ok()
+    }
+
+    /// Returns `true` if the signature follows the BIP-0066 strict DER rules.
+    pub fn is_strict_der(&self) -> bool { self.der_error.is_none() }
+}
+
 /// Holds signature serialized in-line (not in `Vec`).
 ///
 /// This avoids allocation and allows proving maximum size of the signature (73 bytes).
@@ -242,6 +380,8 @@ pub enum DecodeError {
     EmptySignature,
     /// A secp256k1 error.
     Secp256k1(secp256k1::Error),
+    /// Signature is not strict DER encoded.
+    StrictDer(StrictDerError),
 }
 
 impl From<Infallible> for DecodeError {
@@ -254,6 +394,7 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             Self::SighashType(ref e) => write_err!(f, "non-standard signature hash type"; e),
             Self::EmptySignature => write!(f, "empty ECDSA signature"),
             Self::Secp256k1(ref e) => write_err!(f, "secp256k1"; e),
+            Self::StrictDer(ref e) => write_err!(f, "non-strict DER encoding"; e),
         }
     }
 }
@@ -264,6 +405,7 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::Secp256k1(ref e) => Some(e),
             Self::SighashType(ref e) => Some(e),
+            Self::StrictDer(ref e) => Some(e),
             Self::EmptySignature => None,
         }
     }
@@ -277,6 +419,73 @@ impl From<NonStandardSighashTypeError> for DecodeError {
     fn from(e: NonStandardSighashTypeError) -> Self { Self::SighashType(e) }
 }
 
+impl From<StrictDerError> for DecodeError {
+    fn from(e: StrictDerError) -> Self { Self::StrictDer(e) }
+}
+
+/// A violation of the BIP-0066 strict DER rules, returned by [`check_strict_der`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrictDerError {
+    kind: StrictDerErrorKind,
+    position: usize,
+}
+
+impl StrictDerError {
+    /// Returns the violated rule.
+    pub fn kind(&self) -> StrictDerErrorKind { self.kind }
+
+    /// Returns the position of the offending byte in the signature.
+    ///
+    /// For [`StrictDerErrorKind::InvalidLength`] this is the length of a too short signature or
+    /// the position of the first excess byte.
+    pub fn position(&self) -> usize { self.position }
+}
+
+impl From<Infallible> for StrictDerError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for StrictDerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rule = match self.kind {
+            StrictDerErrorKind::InvalidLength => "signature length is not between 9 and 73 bytes",
+            StrictDerErrorKind::NotSequence => "signature is not a DER sequence",
+            StrictDerErrorKind::SequenceLength => "sequence length does not match signature length",
+            StrictDerErrorKind::IntegerLength => "integer length is out of bounds",
+            StrictDerErrorKind::NotInteger => "expected a DER integer",
+            StrictDerErrorKind::ZeroLength => "integer has zero length",
+            StrictDerErrorKind::Negative => "integer is negative",
+            StrictDerErrorKind::ExcessPadding => "integer has excess zero padding",
+        };
+        write!(f, "{} at byte {}", rule, self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StrictDerError {}
+
+/// The BIP-0066 strict DER rule violated by a signature.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StrictDerErrorKind {
+    /// The signature, including the sighash type, is shorter than 9 or longer than 73 bytes.
+    InvalidLength,
+    /// The signature does not start with the DER sequence tag `0x30`.
+    NotSequence,
+    /// The sequence length does not match the length of the signature.
+    SequenceLength,
+    /// The length of R or S does not fit the signature.
+    IntegerLength,
+    /// R or S does not start with the DER integer tag `0x02`.
+    NotInteger,
+    /// R or S has zero length.
+    ZeroLength,
+    /// R or S is negative.
+    Negative,
+    /// R or S starts with a zero byte that is not needed to keep it positive.
+    ExcessPadding,
+}
+
 /// Error encountered while parsing an ECDSA signature from a string.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -318,6 +527,16 @@ impl From<DecodeError> for ParseSignatureError {
     fn from(e: DecodeError) -> Self { Self::Decode(e) }
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Signature {
+    /// Overwrites the signature with an all zeros one, the sighash type is left as is.
+    fn zeroize(&mut self) {
+        let zero =
+            secp256k1::ecdsa::Signature::from_compact(&[0; 64]).expect("zero r and s are in range");
+        secp256k1::ffi::non_secure_erase_impl(&mut self.signature, zero);
+    }
+}
+
 #[cfg(feature = "arbitrary")]
 impl<'a> Arbitrary<'a> for Signature {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -381,4 +600,84 @@ fn iterate_serialized_signature() {
 
         assert_eq!(sig.serialize().iter().copied().collect::<Vec<u8>>(), sig.to_vec());
     }
+
+    #[test]
+    fn strict_der() {
+        // A valid signature from the chain (with `SIGHASH_ALL`).
+        let valid = Vec::from_hex("3045022100b7b91b8d1e8b0ba4d0cf9d1dd1c6b9838ae07a5e65ea2b35cb444b56ad5a3ef70220318a70adc700fdac918e70e4b80ae61baa4c1729c5559ed1853d79c5881861e201").unwrap();
+        assert_eq!(check_strict_der(&valid), Ok(()));
+        assert!(Signature::from_slice_strict(&valid).is_ok());
+
+        let check = |f: &dyn Fn(&mut Vec<u8>)| {
+            let mut sig = valid.clone();
+            f(&mut sig);
+            let err = check_strict_der(&sig).unwrap_err();
+            (err.kind(), err.position())
+        };
+        use StrictDerErrorKind as Kind;
+        assert_eq!(check(&|sig| sig.truncate(8)), (Kind::InvalidLength, 8));
+        assert_eq!(check(&|sig| sig.extend([0; 2])), (Kind::InvalidLength, 73));
+        assert_eq!(check(&|sig| sig[0] = 0x31), (Kind::NotSequence, 0));
+        assert_eq!(check(&|sig| sig[1] = 0x44), (Kind::SequenceLength, 1));
+        assert_eq!(check(&|sig| sig[3] = 0x44), (Kind::IntegerLength, 3));
+        assert_eq!(check(&|sig| sig[38] = 0x21), (Kind::IntegerLength, 38));
+        assert_eq!(check(&|sig| sig[2] = 0x03), (Kind::NotInteger, 2));
+        assert_eq!(check(&|sig| sig[37] = 0x03), (Kind::NotInteger, 37));
+        assert_eq!(check(&|sig| sig[5] = 0x37), (Kind::ExcessPadding, 4));
+        assert_eq!(check(&|sig| sig[39] = 0x80), (Kind::Negative, 39));
+
+        // The same signature with R padded by an extra zero byte, as found before BIP-0066.
+        let mut padded = valid.clone();
+        padded[1] += 1;
+        padded[3] += 1;
+        padded.insert(4, 0x00);
+        assert_eq!(check_strict_der(&padded).unwrap_err().kind(), Kind::ExcessPadding);
+        assert!(Signature::from_slice_strict(&padded).is_err());
+        let lax = Signature::from_der_lax(&padded).unwrap();
+        assert_eq!(lax, Signature::from_slice(&valid).unwrap());
+    }
+
+    #[test]
+    fn inspect() {
+        let valid = Vec::from_hex("3045022100b7b91b8d1e8b0ba4d0cf9d1dd1c6b9838ae07a5e65ea2b35cb444b56ad5a3ef70220318a70adc700fdac918e70e4b80ae61baa4c1729c5559ed1853d79c5881861e201").unwrap();
+        let info = SignatureInfo::inspect(&valid).unwrap();
+        assert_eq!(info.sighash_byte, 0x01);
+        assert_eq!(info.sighash_type(), Some(EcdsaSighashType::All));
+        assert_eq!((info.r_len, info.s_len), (Some(33), Some(32)));
+        assert!(info.is_strict_der());
+
+        // Padded R with a non-standard sighash type, as found before BIP-0066.
+        let mut padded = valid.clone();
+        padded[1] += 1;
+        padded[3] += 1;
+        padded.insert(4, 0x00);
+        *padded.last_mut().unwrap() = 0x04;
+        let info = SignatureInfo::inspect(&padded).unwrap();
+        assert_eq!(info.sighash_type(), None);
+        assert_eq!((info.r_len, info.s_len), (Some(34), Some(32)));
+        assert_eq!(info.der_error.unwrap().kind(), StrictDerErrorKind::ExcessPadding);
+
+        let info = SignatureInfo::inspect(&valid[..10]).unwrap();
+        assert_eq!((info.r_len, info.s_len), (Some(33), None));
+        assert!(!info.is_strict_der());
+        assert_eq!(SignatureInfo::inspect(&[0x01]).unwrap().r_len, None);
+        assert_eq!(SignatureInfo::inspect(&[]), None);
+    }
+
+    #[test]
+    fn lax_sighash_and_normalize_s() {
+        let mut sig =
+            Signature::from_slice(&Vec::from_hex(&format!("{}01", TEST_SIGNATURE_HEX)).unwrap())
+                .unwrap();
+        // S of the test signature is above half the curve order.
+        assert!(!sig.is_low_s());
+        sig.normalize_s();
+        assert!(sig.is_low_s());
+
+        let mut bytes = sig.to_vec();
+        *bytes.last_mut().unwrap() = 0x00;
+        assert!(Signature::from_slice(&bytes).is_err());
+        assert_eq!(Signature::from_der_lax(&bytes).unwrap().sighash_type, EcdsaSighashType::All);
+        assert_eq!(Signature::from_der_lax(&[]), Err(DecodeError::EmptySignature));
+    }
 }
@@ -4,12 +4,22 @@
 //!
 //! This module provides keys used in Bitcoin that can be roundtrip
 //! (de)serialized.
+//!
+//! # Contexts
+//!
+//! Signing and verification use the global context of [`secp256k1`], there is no context to
+//! construct or pass around. The context is created lazily on first use, once per thread with
+//! `std` and once per program without, and is rerandomized with the secret key after every
+//! signing operation. Use [`init_context`] to move its construction out of a latency sensitive
+//! path and [`rerandomize_context`] to mix in additional entropy.
 
 use core::convert::Infallible;
 use core::fmt::{self, Write as _};
 use core::ops;
 use core::str::FromStr;
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
 use hashes::hash160;
 use hex_unstable::{FromHex, HexToArrayError};
 use internals::array::ArrayExt;
@@ -33,6 +43,20 @@
 #[cfg(all(feature = "rand", feature = "std"))]
 pub use secp256k1::rand;
 
+/// Constructs the global context of the current thread, or of the program without `std`, unless
+/// it already exists.
+///
+/// Validators verifying signatures on a thread pool can call this when starting each thread.
+pub fn init_context() {
+    secp256k1::with_global_context(|_: &secp256k1::Secp256k1<secp256k1::All>| (), None)
+}
+
+/// Rerandomizes the global context, mixing `seed` with the entropy of previous calls.
+///
+/// Rerandomization protects signing against side-channel attacks. It is safe to pass secret data,
+/// with the `rand` feature the seed is also mixed with the thread RNG.
+pub fn rerandomize_context(seed: &[u8; 32]) { secp256k1::rerandomize_global_context(seed) }
+
 /// Encapsulation module to provide a clear barrier for construction/destruction of types.
 mod encapsulate {
     /// A Bitcoin Schnorr X-only public key used for BIP-0340 signatures.
@@ -179,6 +203,22 @@ impl SerializedXOnlyPublicKey {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for XOnlyPublicKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        // About half of all x-coordinates are on the curve, use the BIP-0341 NUMS point otherwise.
+        const NUMS: [u8; 32] = [
+            0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9,
+            0x7a, 0x5e, 0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a,
+            0xce, 0x80, 0x3a, 0xc0,
+        ];
+
+        let bytes = u.arbitrary()?;
+        Ok(Self::from_byte_array(&bytes)
+            .unwrap_or_else(|_| Self::from_byte_array(&NUMS).expect("NUMS point is valid")))
+    }
+}
+
 impl XOnlyPublicKey {
     /// Constructs an x-only public key from a keypair.
     ///
@@ -816,11 +856,18 @@ pub fn to_wif(self) -> String {
 
     /// Parses the WIF encoded private key.
     pub fn from_wif(wif: &str) -> Result<Self, FromWifError> {
-        let data = base58::decode_check(wif)?;
+        let mut data = base58::decode_check(wif)?;
+        let key = Self::from_wif_payload(&data);
+        // The decoded payload contains the secret key, do not leave it behind on the heap.
+        data.iter_mut().for_each(|byte| secp256k1::ffi::non_secure_erase_impl(byte, 0));
+        key
+    }
 
-        let (compressed, data) = if let Ok(data) = <&[u8; 33]>::try_from(&*data) {
+    /// Parses the base58 decoded payload of a WIF encoded private key.
+    fn from_wif_payload(data: &[u8]) -> Result<Self, FromWifError> {
+        let (compressed, data) = if let Ok(data) = <&[u8; 33]>::try_from(data) {
             (false, data)
-        } else if let Ok(data) = <&[u8; 34]>::try_from(&*data) {
+        } else if let Ok(data) = <&[u8; 34]>::try_from(data) {
             let (compressed_flag, data) = data.split_last::<33>();
             if *compressed_flag != 1 {
                 return Err(InvalidWifCompressionFlagError { invalid: *compressed_flag }.into());
@@ -851,6 +898,20 @@ pub fn from_wif(wif: &str) -> Result<Self, FromWifError> {
     pub fn negate(&self) -> Self {
         Self { compressed: self.compressed, network: self.network, inner: self.inner.negate() }
     }
+
+    /// Attempts to erase the secret key.
+    ///
+    /// Note, however, that `PrivateKey` is `Copy` and the compiler is allowed to freely copy or
+    /// move it to other places in memory, which this does not erase. For more discussion on this,
+    /// please see the documentation of the [`zeroize`](https://docs.rs/zeroize) crate.
+    #[inline]
+    pub fn non_secure_erase(&mut self) { self.inner.non_secure_erase(); }
+}
+
+/// `PrivateKey` is `Copy` so it can't be erased on drop, see [`PrivateKey::non_secure_erase`].
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for PrivateKey {
+    fn zeroize(&mut self) { self.non_secure_erase(); }
 }
 
 impl fmt::Display for PrivateKey {
@@ -1083,6 +1144,26 @@ pub trait TapTweak {
     /// The tweaked key and its parity.
     fn tap_tweak(self, merkle_root: Option<TapNodeHash>) -> Self::TweakedAux;
 
+    /// Commits to `contract` in an untweaked key (pay-to-contract).
+    ///
+    /// Returns the key `P' = P + H(P|contract)G`, where H is the tagged hash of the
+    /// [`contract`](crate::contract) module. Use it as the internal key of a Taproot output in
+    /// place of P, e.g. in the control blocks of script path spends.
+    #[cfg(feature = "contract")]
+    fn commit_to_contract(self, contract: &[u8]) -> Self;
+
+    /// Commits to `contract` in an untweaked key and tweaks the committed key with the optional
+    /// script tree Merkle root, see [`Self::commit_to_contract`] and [`Self::tap_tweak`].
+    ///
+    /// Anyone knowing P, `contract` and the Merkle root can check the commitment with
+    /// [`contract::verify_key_commitment`](crate::contract::verify_key_commitment).
+    #[cfg(feature = "contract")]
+    fn tap_tweak_with_contract(
+        self,
+        contract: &[u8],
+        merkle_root: Option<TapNodeHash>,
+    ) -> Self::TweakedAux;
+
     /// Directly converts an [`UntweakedPublicKey`] to a [`TweakedPublicKey`].
     ///
     /// This method is dangerous and can lead to loss of funds if used incorrectly.
@@ -1115,6 +1196,21 @@ fn tap_tweak(self, merkle_root: Option<TapNodeHash>) -> (TweakedPublicKey, Parit
         (TweakedPublicKey::dangerous_assume_tweaked(output_key), parity)
     }
 
+    #[cfg(feature = "contract")]
+    fn commit_to_contract(self, contract: &[u8]) -> Self {
+        let tweak = crate::contract::pay_to_contract_tweak(&self, contract);
+        self.add_tweak(&tweak).expect("statistically impossible to hit").0
+    }
+
+    #[cfg(feature = "contract")]
+    fn tap_tweak_with_contract(
+        self,
+        contract: &[u8],
+        merkle_root: Option<TapNodeHash>,
+    ) -> (TweakedPublicKey, Parity) {
+        self.commit_to_contract(contract).tap_tweak(merkle_root)
+    }
+
     fn dangerous_assume_tweaked(self) -> TweakedPublicKey {
         TweakedPublicKey::dangerous_assume_tweaked(self)
     }
@@ -1141,6 +1237,24 @@ fn tap_tweak(self, merkle_root: Option<TapNodeHash>) -> TweakedKeypair {
         TweakedKeypair::dangerous_assume_tweaked(Self::from(tweaked))
     }
 
+    #[cfg(feature = "contract")]
+    fn commit_to_contract(self, contract: &[u8]) -> Self {
+        let (pubkey, _parity) = XOnlyPublicKey::from_keypair(&self);
+        let tweak = crate::contract::pay_to_contract_tweak(&pubkey, contract);
+        let committed =
+            self.to_inner().add_xonly_tweak(&tweak).expect("statistically impossible to hit");
+        Self::from(committed)
+    }
+
+    #[cfg(feature = "contract")]
+    fn tap_tweak_with_contract(
+        self,
+        contract: &[u8],
+        merkle_root: Option<TapNodeHash>,
+    ) -> TweakedKeypair {
+        self.commit_to_contract(contract).tap_tweak(merkle_root)
+    }
+
     fn dangerous_assume_tweaked(self) -> TweakedKeypair {
         TweakedKeypair::dangerous_assume_tweaked(self)
     }
@@ -1987,4 +2101,17 @@ fn keypair_from_str_roundtrip() {
         let decoded = encoded.parse::<Keypair>().unwrap();
         assert_eq!(decoded, keypair);
     }
+
+    #[test]
+    fn context() {
+        init_context();
+        rerandomize_context(&[0xab; 32]);
+
+        let key = PrivateKey::from_byte_array([1; 32], NetworkKind::Main).unwrap();
+        let msg = secp256k1::Message::from_digest([2; 32]);
+        let sig = secp256k1::ecdsa::sign(msg, &key.inner);
+        rerandomize_context(&[0; 32]);
+        let pk = PublicKey::from_private_key(key);
+        assert!(secp256k1::ecdsa::verify(&sig, msg, &pk.inner).is_ok());
+    }
 }
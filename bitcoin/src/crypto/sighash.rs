@@ -23,7 +23,7 @@
 
 use crate::consensus::{encode, Encodable};
 use crate::prelude::{Borrow, BorrowMut, String, ToOwned};
-use crate::script::{ScriptExt as _, ScriptHashableTag};
+use crate::script::{ScriptExt as _, ScriptHashableTag, ScriptPubKeyExt as _};
 use crate::taproot::{LeafVersion, TapLeafHash, TapLeafTag, TAPROOT_ANNEX_PREFIX};
 use crate::transaction::TransactionExt as _;
 use crate::witness::Witness;
@@ -31,6 +31,8 @@
     transaction, Amount, ScriptPubKey, Sequence, TapScript, Transaction, TxOut, WitnessScript,
 };
 
+pub mod test_vectors;
+
 /// Used for signature hash for invalid use of SIGHASH_SINGLE.
 #[rustfmt::skip]
 pub(crate) const UINT256_ONE: [u8; 32] = [
@@ -103,39 +105,123 @@ pub struct SighashCache<T: Borrow<Transaction>> {
     tx: T,
 
     /// Common cache for Taproot and SegWit inputs, `None` for legacy inputs.
-    common_cache: Option<CommonCache>,
+    common_cache: Option<CommonMidstates>,
 
     /// Cache for SegWit v0 inputs (the result of another round of sha256 on `common_cache`).
-    segwit_cache: Option<SegwitCache>,
+    segwit_cache: Option<SegwitV0Midstates>,
 
     /// Cache for Taproot v1 inputs.
-    taproot_cache: Option<TaprootCache>,
+    taproot_cache: Option<TaprootMidstates>,
 }
 
-/// Common values cached between SegWit and Taproot inputs.
-#[derive(Debug)]
-struct CommonCache {
-    prevouts: sha256::Hash,
-    sequences: sha256::Hash,
-
+/// The hashes cached by a [`SighashCache`].
+///
+/// Computing these hashes takes time linear in the size of the transaction, they are shared by
+/// the sighashes of all inputs. Persisting them, for example between PSBT signing rounds, avoids
+/// computing them again. They are only valid for the transaction they were computed from.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Midstates {
+    /// The hashes shared by SegWit v0 and Taproot inputs.
+    pub common: Option<CommonMidstates>,
+    /// The hashes of SegWit v0 inputs.
+    pub segwit_v0: Option<SegwitV0Midstates>,
+    /// The hashes of Taproot inputs.
+    pub taproot: Option<TaprootMidstates>,
+}
+
+/// The single SHA256 hashes of transaction data shared by SegWit v0 and Taproot inputs.
+///
+/// These are `sha_prevouts`, `sha_sequences` and `sha_outputs` of BIP-0341.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CommonMidstates {
+    /// The SHA256 of all input outpoints.
+    pub prevouts: sha256::Hash,
+    /// The SHA256 of all input sequence numbers.
+    pub sequences: sha256::Hash,
+    /// The SHA256 of all outputs.
+    ///
     /// In theory `outputs` could be an `Option` since `SIGHASH_NONE` and `SIGHASH_SINGLE` do not
     /// need it, but since `SIGHASH_ALL` is by far the most used variant we don't bother.
-    outputs: sha256::Hash,
+    pub outputs: sha256::Hash,
 }
 
-/// Values cached for SegWit inputs, equivalent to [`CommonCache`] plus another round of `sha256`.
-#[derive(Debug)]
-struct SegwitCache {
-    prevouts: sha256d::Hash,
-    sequences: sha256d::Hash,
-    outputs: sha256d::Hash,
+impl CommonMidstates {
+    /// Computes the hashes for `tx`.
+    pub fn new(tx: &Transaction) -> Self {
+        let mut enc_prevouts = sha256::Hash::engine();
+        let mut enc_sequences = sha256::Hash::engine();
+        for txin in tx.inputs.iter() {
+            txin.previous_output.consensus_encode(&mut enc_prevouts).unwrap();
+            txin.sequence.consensus_encode(&mut enc_sequences).unwrap();
+        }
+        Self {
+            prevouts: sha256::Hash::from_engine(enc_prevouts),
+            sequences: sha256::Hash::from_engine(enc_sequences),
+            outputs: {
+                let mut enc = sha256::Hash::engine();
+                for txout in tx.outputs.iter() {
+                    txout.consensus_encode(&mut enc).unwrap();
+                }
+                sha256::Hash::from_engine(enc)
+            },
+        }
+    }
 }
 
-/// Values cached for Taproot inputs.
-#[derive(Debug)]
-struct TaprootCache {
-    amounts: sha256::Hash,
-    script_pubkeys: sha256::Hash,
+/// The double SHA256 hashes of transaction data used by SegWit v0 inputs.
+///
+/// These are `hashPrevouts`, `hashSequence` and `hashOutputs` of BIP-0143, equivalent to
+/// [`CommonMidstates`] plus another round of SHA256.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SegwitV0Midstates {
+    /// The double SHA256 of all input outpoints.
+    pub prevouts: sha256d::Hash,
+    /// The double SHA256 of all input sequence numbers.
+    pub sequences: sha256d::Hash,
+    /// The double SHA256 of all outputs.
+    pub outputs: sha256d::Hash,
+}
+
+impl SegwitV0Midstates {
+    /// Computes the hashes from the single SHA256 hashes.
+    pub fn from_common(common: &CommonMidstates) -> Self {
+        Self {
+            prevouts: common.prevouts.hash_again(),
+            sequences: common.sequences.hash_again(),
+            outputs: common.outputs.hash_again(),
+        }
+    }
+}
+
+/// The SHA256 hashes of the spent outputs used by Taproot inputs.
+///
+/// These are `sha_amounts` and `sha_scriptpubkeys` of BIP-0341.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TaprootMidstates {
+    /// The SHA256 of all spent output amounts.
+    pub amounts: sha256::Hash,
+    /// The SHA256 of all spent output script pubkeys.
+    pub script_pubkeys: sha256::Hash,
+}
+
+impl TaprootMidstates {
+    /// Computes the hashes for the outputs spent by all inputs of a transaction, in input order.
+    pub fn new<T: Borrow<TxOut>>(prevouts: &[T]) -> Self {
+        let mut enc_amounts = sha256::Hash::engine();
+        let mut enc_script_pubkeys = sha256::Hash::engine();
+        for prevout in prevouts {
+            prevout.borrow().amount.consensus_encode(&mut enc_amounts).unwrap();
+            prevout.borrow().script_pubkey.consensus_encode(&mut enc_script_pubkeys).unwrap();
+        }
+        Self {
+            amounts: sha256::Hash::from_engine(enc_amounts),
+            script_pubkeys: sha256::Hash::from_engine(enc_script_pubkeys),
+        }
+    }
 }
 
 /// Contains outputs of previous transactions. In the case [`TapSighashType`] variant is
@@ -593,6 +679,154 @@ impl std::error::Error for SighashTypeParseError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
 }
 
+/// The prefix of the string of a Taproot [`SighashTypeAny`] that is not `DEFAULT`.
+const TAPROOT_PREFIX: &str = "taproot:";
+
+/// The sighash type of either an ECDSA or a Schnorr signature.
+///
+/// ECDSA signatures of legacy and SegWit v0 inputs and Schnorr signatures of Taproot inputs share
+/// most sighash types, but only Taproot has [`TapSighashType::Default`] (`0x00`) and for Taproot
+/// `SIGHASH_ALL` (`0x01`) makes the signature a byte longer. Mixing the two up, e.g. in the
+/// `sighash_type` field of a PSBT input, makes the signatures invalid or needlessly large. Use
+/// [`for_script_pubkey`](Self::for_script_pubkey) to check a sighash type against the output that
+/// is spent.
+///
+/// The strings are those of Bitcoin Core's `sighashtype` RPC arguments like `ALL|ANYONECANPAY`,
+/// the `SIGHASH_` prefixed forms used by [`EcdsaSighashType`] and [`TapSighashType`] are parsed
+/// too. Except for `DEFAULT`, strings parse as ECDSA sighash types unless prefixed with
+/// `taproot:`, as in `taproot:ALL`, which is how the Taproot sighash types other than `DEFAULT`
+/// are displayed.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SighashTypeAny {
+    /// The sighash type of an ECDSA signature, for legacy and SegWit v0 inputs.
+    Ecdsa(EcdsaSighashType),
+    /// The sighash type of a Schnorr signature, for Taproot inputs.
+    Taproot(TapSighashType),
+}
+
+impl SighashTypeAny {
+    /// Returns the sighash type to use when spending an output with `script_pubkey`.
+    ///
+    /// Taproot outputs take Taproot sighash types, all others take ECDSA sighash types. Sighash
+    /// types that exist for both kinds of signatures are converted.
+    ///
+    /// # Errors
+    ///
+    /// If the sighash type is [`TapSighashType::Default`] and the output is not Taproot.
+    pub fn for_script_pubkey(
+        self,
+        script_pubkey: &ScriptPubKey,
+    ) -> Result<Self, SighashTypeMismatchError> {
+        use TapSighashType::*;
+
+        if script_pubkey.is_p2tr() {
+            return Ok(Self::Taproot(self.to_taproot()));
+        }
+        let ecdsa = match self {
+            Self::Ecdsa(ecdsa) => ecdsa,
+            Self::Taproot(Default) => return Err(SighashTypeMismatchError),
+            Self::Taproot(All) => EcdsaSighashType::All,
+            Self::Taproot(None) => EcdsaSighashType::None,
+            Self::Taproot(Single) => EcdsaSighashType::Single,
+            Self::Taproot(AllPlusAnyoneCanPay) => EcdsaSighashType::AllPlusAnyoneCanPay,
+            Self::Taproot(NonePlusAnyoneCanPay) => EcdsaSighashType::NonePlusAnyoneCanPay,
+            Self::Taproot(SinglePlusAnyoneCanPay) => EcdsaSighashType::SinglePlusAnyoneCanPay,
+        };
+        Ok(Self::Ecdsa(ecdsa))
+    }
+
+    /// Returns `true` if this is the sighash type of a Schnorr signature.
+    pub fn is_taproot(self) -> bool { matches!(self, Self::Taproot(_)) }
+
+    /// Returns the equivalent Taproot sighash type.
+    pub fn to_taproot(self) -> TapSighashType {
+        match self {
+            Self::Ecdsa(ecdsa) => ecdsa.into(),
+            Self::Taproot(taproot) => taproot,
+        }
+    }
+
+    /// Converts the sighash type to a `u32` sighash flag.
+    pub fn to_u32(self) -> u32 {
+        match self {
+            Self::Ecdsa(ecdsa) => ecdsa.to_u32(),
+            Self::Taproot(taproot) => taproot as u32,
+        }
+    }
+}
+
+impl From<EcdsaSighashType> for SighashTypeAny {
+    fn from(ecdsa: EcdsaSighashType) -> Self { Self::Ecdsa(ecdsa) }
+}
+
+impl From<TapSighashType> for SighashTypeAny {
+    fn from(taproot: TapSighashType) -> Self { Self::Taproot(taproot) }
+}
+
+impl fmt::Display for SighashTypeAny {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TapSighashType::*;
+
+        let taproot = self.to_taproot();
+        if self.is_taproot() && taproot != Default {
+            f.write_str(TAPROOT_PREFIX)?;
+        }
+        let s = match taproot {
+            Default => "DEFAULT",
+            All => "ALL",
+            None => "NONE",
+            Single => "SINGLE",
+            AllPlusAnyoneCanPay => "ALL|ANYONECANPAY",
+            NonePlusAnyoneCanPay => "NONE|ANYONECANPAY",
+            SinglePlusAnyoneCanPay => "SINGLE|ANYONECANPAY",
+        };
+        f.write_str(s)
+    }
+}
+
+impl str::FromStr for SighashTypeAny {
+    type Err = SighashTypeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use EcdsaSighashType::*;
+
+        let (taproot, name) = match s.strip_prefix(TAPROOT_PREFIX) {
+            Some(name) => (true, name),
+            Option::None => (false, s),
+        };
+        let ecdsa = match name {
+            "DEFAULT" | "SIGHASH_DEFAULT" => return Ok(Self::Taproot(TapSighashType::Default)),
+            "ALL" | "SIGHASH_ALL" => All,
+            "NONE" | "SIGHASH_NONE" => None,
+            "SINGLE" | "SIGHASH_SINGLE" => Single,
+            "ALL|ANYONECANPAY" | "SIGHASH_ALL|SIGHASH_ANYONECANPAY" => AllPlusAnyoneCanPay,
+            "NONE|ANYONECANPAY" | "SIGHASH_NONE|SIGHASH_ANYONECANPAY" => NonePlusAnyoneCanPay,
+            "SINGLE|ANYONECANPAY" | "SIGHASH_SINGLE|SIGHASH_ANYONECANPAY" => SinglePlusAnyoneCanPay,
+            _ => return Err(SighashTypeParseError { unrecognized: s.to_owned() }),
+        };
+        if taproot {
+            return Ok(Self::Taproot(ecdsa.into()));
+        }
+        Ok(Self::Ecdsa(ecdsa))
+    }
+}
+
+/// [`TapSighashType::Default`] is used for an output that is not Taproot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SighashTypeMismatchError;
+
+impl fmt::Display for SighashTypeMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SIGHASH_DEFAULT is only valid for Taproot outputs")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SighashTypeMismatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
 impl<R: Borrow<Transaction>> SighashCache<R> {
     /// Constructs a new `SighashCache` from an unsigned transaction.
     ///
@@ -603,6 +837,30 @@ pub fn new(tx: R) -> Self {
         Self { tx, common_cache: None, taproot_cache: None, segwit_cache: None }
     }
 
+    /// Constructs a new `SighashCache` from an unsigned transaction and previously computed
+    /// `midstates`.
+    ///
+    /// The midstates must have been computed for the same transaction, for example with
+    /// [`Self::midstates`], otherwise the generated sighashes are invalid. Missing midstates are
+    /// computed when required.
+    pub fn with_midstates(tx: R, midstates: Midstates) -> Self {
+        Self {
+            tx,
+            common_cache: midstates.common,
+            segwit_cache: midstates.segwit_v0,
+            taproot_cache: midstates.taproot,
+        }
+    }
+
+    /// Returns the midstates computed so far, to be reused with [`Self::with_midstates`].
+    pub fn midstates(&self) -> Midstates {
+        Midstates {
+            common: self.common_cache,
+            segwit_v0: self.segwit_cache,
+            taproot: self.taproot_cache,
+        }
+    }
+
     /// Returns the reference to the cached transaction.
     pub fn transaction(&self) -> &Transaction { self.tx.borrow() }
 
@@ -1077,61 +1335,27 @@ pub fn legacy_signature_hash<T: ScriptHashableTag>(
     }
 
     #[inline]
-    fn common_cache(&mut self) -> &CommonCache {
+    fn common_cache(&mut self) -> &CommonMidstates {
         Self::common_cache_minimal_borrow(&mut self.common_cache, self.tx.borrow())
     }
 
     fn common_cache_minimal_borrow<'a>(
-        common_cache: &'a mut Option<CommonCache>,
+        common_cache: &'a mut Option<CommonMidstates>,
         tx: &Transaction,
-    ) -> &'a CommonCache {
-        common_cache.get_or_insert_with(|| {
-            let mut enc_prevouts = sha256::Hash::engine();
-            let mut enc_sequences = sha256::Hash::engine();
-            for txin in tx.inputs.iter() {
-                txin.previous_output.consensus_encode(&mut enc_prevouts).unwrap();
-                txin.sequence.consensus_encode(&mut enc_sequences).unwrap();
-            }
-            CommonCache {
-                prevouts: sha256::Hash::from_engine(enc_prevouts),
-                sequences: sha256::Hash::from_engine(enc_sequences),
-                outputs: {
-                    let mut enc = sha256::Hash::engine();
-                    for txout in tx.outputs.iter() {
-                        txout.consensus_encode(&mut enc).unwrap();
-                    }
-                    sha256::Hash::from_engine(enc)
-                },
-            }
-        })
+    ) -> &'a CommonMidstates {
+        common_cache.get_or_insert_with(|| CommonMidstates::new(tx))
     }
 
-    fn segwit_cache(&mut self) -> &SegwitCache {
+    fn segwit_cache(&mut self) -> &SegwitV0Midstates {
         let common_cache = &mut self.common_cache;
         let tx = self.tx.borrow();
         self.segwit_cache.get_or_insert_with(|| {
-            let common_cache = Self::common_cache_minimal_borrow(common_cache, tx);
-            SegwitCache {
-                prevouts: common_cache.prevouts.hash_again(),
-                sequences: common_cache.sequences.hash_again(),
-                outputs: common_cache.outputs.hash_again(),
-            }
+            SegwitV0Midstates::from_common(Self::common_cache_minimal_borrow(common_cache, tx))
         })
     }
 
-    fn taproot_cache<T: Borrow<TxOut>>(&mut self, prevouts: &[T]) -> &TaprootCache {
-        self.taproot_cache.get_or_insert_with(|| {
-            let mut enc_amounts = sha256::Hash::engine();
-            let mut enc_script_pubkeys = sha256::Hash::engine();
-            for prevout in prevouts {
-                prevout.borrow().amount.consensus_encode(&mut enc_amounts).unwrap();
-                prevout.borrow().script_pubkey.consensus_encode(&mut enc_script_pubkeys).unwrap();
-            }
-            TaprootCache {
-                amounts: sha256::Hash::from_engine(enc_amounts),
-                script_pubkeys: sha256::Hash::from_engine(enc_script_pubkeys),
-            }
-        })
+    fn taproot_cache<T: Borrow<TxOut>>(&mut self, prevouts: &[T]) -> &TaprootMidstates {
+        self.taproot_cache.get_or_insert_with(|| TaprootMidstates::new(prevouts))
     }
 }
 
@@ -2066,6 +2290,73 @@ fn sighashtype_fromstr_display() {
         }
     }
 
+    #[test]
+    fn sighash_type_any() {
+        let any = [
+            ("DEFAULT", SighashTypeAny::Taproot(TapSighashType::Default)),
+            ("ALL", SighashTypeAny::Ecdsa(EcdsaSighashType::All)),
+            ("NONE", SighashTypeAny::Ecdsa(EcdsaSighashType::None)),
+            ("SINGLE", SighashTypeAny::Ecdsa(EcdsaSighashType::Single)),
+            ("ALL|ANYONECANPAY", SighashTypeAny::Ecdsa(EcdsaSighashType::AllPlusAnyoneCanPay)),
+            ("NONE|ANYONECANPAY", SighashTypeAny::Ecdsa(EcdsaSighashType::NonePlusAnyoneCanPay)),
+            (
+                "SINGLE|ANYONECANPAY",
+                SighashTypeAny::Ecdsa(EcdsaSighashType::SinglePlusAnyoneCanPay),
+            ),
+        ];
+        for (s, any) in any {
+            assert_eq!(any.to_string(), s);
+            assert_eq!(s.parse::<SighashTypeAny>(), Ok(any));
+            assert_eq!(any.to_taproot().to_string().parse::<SighashTypeAny>(), Ok(any));
+        }
+        assert!("ALL | ANYONECANPAY".parse::<SighashTypeAny>().is_err());
+        assert!("taproot:taproot:ALL".parse::<SighashTypeAny>().is_err());
+        assert_eq!(SighashTypeAny::Taproot(TapSighashType::All).to_string(), "taproot:ALL");
+        assert_eq!("taproot:DEFAULT".parse(), Ok(SighashTypeAny::Taproot(TapSighashType::Default)));
+        assert_eq!(
+            "taproot:SIGHASH_SINGLE".parse(),
+            Ok(SighashTypeAny::Taproot(TapSighashType::Single))
+        );
+
+        // Every variant displays as a string that parses back to it.
+        let taproot = [
+            TapSighashType::Default,
+            TapSighashType::All,
+            TapSighashType::None,
+            TapSighashType::Single,
+            TapSighashType::AllPlusAnyoneCanPay,
+            TapSighashType::NonePlusAnyoneCanPay,
+            TapSighashType::SinglePlusAnyoneCanPay,
+        ];
+        for taproot in taproot {
+            let any = SighashTypeAny::Taproot(taproot);
+            assert_eq!(any.to_string().parse::<SighashTypeAny>(), Ok(any));
+        }
+
+        let p2tr = ScriptPubKeyBuf::from_hex_no_length_prefix(
+            "51201a9ce79cbb5a3a7ecc6f45de7ad4b635ee1b8bf403f7e4d0fd7c15e40120d3d2",
+        )
+        .unwrap();
+        let p2wpkh = ScriptPubKeyBuf::from_hex_no_length_prefix(
+            "00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1",
+        )
+        .unwrap();
+        let default = SighashTypeAny::from(TapSighashType::Default);
+        let all = SighashTypeAny::from(EcdsaSighashType::All);
+        assert_eq!(default.for_script_pubkey(&p2tr), Ok(default));
+        assert_eq!(default.for_script_pubkey(&p2wpkh), Err(SighashTypeMismatchError));
+        assert_eq!(all.for_script_pubkey(&p2tr), Ok(TapSighashType::All.into()));
+        assert_eq!(all.for_script_pubkey(&p2wpkh), Ok(all));
+        let single = SighashTypeAny::from(TapSighashType::SinglePlusAnyoneCanPay);
+        assert_eq!(
+            single.for_script_pubkey(&p2wpkh),
+            Ok(EcdsaSighashType::SinglePlusAnyoneCanPay.into())
+        );
+        assert_eq!(default.to_u32(), 0x00);
+        assert_eq!(all.to_u32(), 0x01);
+        assert!(default.is_taproot() && !all.is_taproot());
+    }
+
     #[test]
     fn bip143_p2wpkh() {
         let tx = deserialize::<Transaction>(
@@ -2110,6 +2401,55 @@ fn bip143_p2wpkh() {
         );
     }
 
+    #[test]
+    fn reuse_midstates() {
+        let tx = deserialize::<Transaction>(
+            &hex!(
+                "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f000000\
+                0000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a01000000\
+                00ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093\
+                510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000"
+            ),
+        ).unwrap();
+        let spk = ScriptPubKeyBuf::from_hex_no_length_prefix(
+            "00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1",
+        )
+        .unwrap();
+        let amount = Amount::from_sat_u32(600_000_000);
+        let prevouts = [
+            TxOut { amount: Amount::from_sat_u32(1_000), script_pubkey: spk.clone() },
+            TxOut { amount, script_pubkey: spk.clone() },
+        ];
+
+        let mut cache = SighashCache::new(&tx);
+        assert_eq!(cache.midstates(), Midstates::default());
+        let segwit = cache.p2wpkh_signature_hash(1, &spk, amount, EcdsaSighashType::All).unwrap();
+        let taproot = cache
+            .taproot_key_spend_signature_hash(0, &Prevouts::All(&prevouts), TapSighashType::All)
+            .unwrap();
+
+        let midstates = cache.midstates();
+        assert_eq!(midstates.common, Some(CommonMidstates::new(&tx)));
+        assert_eq!(
+            midstates.segwit_v0,
+            Some(SegwitV0Midstates::from_common(&CommonMidstates::new(&tx)))
+        );
+        assert_eq!(midstates.taproot, Some(TaprootMidstates::new(&prevouts)));
+
+        let mut cache = SighashCache::with_midstates(&tx, midstates);
+        assert_eq!(
+            cache.p2wpkh_signature_hash(1, &spk, amount, EcdsaSighashType::All).unwrap(),
+            segwit
+        );
+        assert_eq!(
+            cache
+                .taproot_key_spend_signature_hash(0, &Prevouts::All(&prevouts), TapSighashType::All)
+                .unwrap(),
+            taproot
+        );
+        assert_eq!(cache.midstates(), midstates);
+    }
+
     #[test]
     fn bip143_p2wpkh_nested_in_p2sh() {
         let tx = deserialize::<Transaction>(
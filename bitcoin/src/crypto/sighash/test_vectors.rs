@@ -0,0 +1,382 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Generates sighash test vectors.
+//!
+//! Signers that compute signature hashes with their own implementation, for example in hardware
+//! wallet firmware, can regression test it against vectors generated by this crate. A vector
+//! contains the transaction, the outputs it spends, the input index, the spend and sighash type
+//! and the expected sighash, in the style of the vectors of [BIP-0143] and [BIP-0341].
+//!
+//! [BIP-0143]: <https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki>
+//! [BIP-0341]: <https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki>
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::sighash::test_vectors::{self, Spend};
+//! # use bitcoin::{absolute, transaction, Amount, OutPoint, ScriptPubKeyBuf, Transaction, TxIn, TxOut};
+//! # let tx = Transaction {
+//! #     version: transaction::Version::TWO,
+//! #     lock_time: absolute::LockTime::ZERO,
+//! #     inputs: vec![TxIn { previous_output: OutPoint::COINBASE_PREVOUT, ..TxIn::EMPTY_COINBASE }],
+//! #     outputs: vec![TxOut { amount: Amount::ONE_SAT, script_pubkey: ScriptPubKeyBuf::new() }],
+//! # };
+//! # let prevouts = vec![TxOut { amount: Amount::ONE_BTC, script_pubkey: ScriptPubKeyBuf::new() }];
+//!
+//! // One vector for every Taproot sighash type.
+//! let vectors = test_vectors::generate(&tx, &prevouts, 0, &Spend::TaprootKey).unwrap();
+//! assert_eq!(vectors.len(), 7);
+//! let json = test_vectors::to_json(&vectors);
+//! ```
+
+use core::fmt;
+
+use internals::write_err;
+
+use super::{
+    EcdsaSighashType, Prevouts, PrevoutsSizeError, SighashCache, SighashTypeAny, TapSighashType,
+    TaprootError,
+};
+use crate::consensus::encode;
+use crate::debug_json::Value;
+use crate::prelude::{String, ToString, Vec};
+use crate::taproot::TapLeafHash;
+use crate::transaction::{InputsIndexError, TransactionExt as _};
+use crate::{Transaction, TxOut, WitnessScriptBuf};
+
+/// The ECDSA sighash types, in the order vectors are generated.
+const ECDSA_SIGHASH_TYPES: [EcdsaSighashType; 6] = [
+    EcdsaSighashType::All,
+    EcdsaSighashType::None,
+    EcdsaSighashType::Single,
+    EcdsaSighashType::AllPlusAnyoneCanPay,
+    EcdsaSighashType::NonePlusAnyoneCanPay,
+    EcdsaSighashType::SinglePlusAnyoneCanPay,
+];
+
+/// The Taproot sighash types, in the order vectors are generated.
+const TAP_SIGHASH_TYPES: [TapSighashType; 7] = [
+    TapSighashType::Default,
+    TapSighashType::All,
+    TapSighashType::None,
+    TapSighashType::Single,
+    TapSighashType::AllPlusAnyoneCanPay,
+    TapSighashType::NonePlusAnyoneCanPay,
+    TapSighashType::SinglePlusAnyoneCanPay,
+];
+
+/// How an input spends its output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Spend {
+    /// A BIP-0143 spend of a SegWit v0 output.
+    SegwitV0 {
+        /// The script code, the witness script or for P2WPKH the equivalent P2PKH script.
+        script_code: WitnessScriptBuf,
+    },
+    /// A BIP-0341 key path spend.
+    TaprootKey,
+    /// A BIP-0341 script path spend, without `OP_CODESEPARATOR`.
+    TaprootScript {
+        /// The hash of the spent leaf.
+        leaf_hash: TapLeafHash,
+    },
+}
+
+/// A sighash test vector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// The spending transaction.
+    pub tx: Transaction,
+    /// The outputs spent by all inputs of the transaction.
+    pub prevouts: Vec<TxOut>,
+    /// The index of the signed input.
+    pub input_index: usize,
+    /// How the input spends its output.
+    pub spend: Spend,
+    /// The sighash type.
+    pub sighash_type: SighashTypeAny,
+    /// The expected sighash, in the byte order it is signed.
+    pub sighash: [u8; 32],
+}
+
+impl TestVector {
+    /// Constructs a new test vector, computing the expected sighash.
+    ///
+    /// The sighash type has to be an ECDSA one for SegWit v0 spends and a Taproot one for
+    /// Taproot spends.
+    pub fn new(
+        tx: Transaction,
+        prevouts: Vec<TxOut>,
+        input_index: usize,
+        spend: Spend,
+        sighash_type: SighashTypeAny,
+    ) -> Result<Self, TestVectorError> {
+        if prevouts.len() != tx.inputs.len() {
+            return Err(TestVectorError::PrevoutsSize(PrevoutsSizeError));
+        }
+        // The Taproot sighash only checks the index for `SIGHASH_ANYONECANPAY`.
+        tx.tx_in(input_index)?;
+        let mut cache = SighashCache::new(&tx);
+        let sighash = match (&spend, sighash_type) {
+            (Spend::SegwitV0 { script_code }, SighashTypeAny::Ecdsa(sighash_type)) => {
+                let amount = prevouts[input_index].amount;
+                cache
+                    .p2wsh_signature_hash(input_index, script_code, amount, sighash_type)?
+                    .to_byte_array()
+            }
+            (Spend::TaprootKey, SighashTypeAny::Taproot(sighash_type)) => cache
+                .taproot_key_spend_signature_hash(
+                    input_index,
+                    &Prevouts::All(&prevouts),
+                    sighash_type,
+                )?
+                .to_byte_array(),
+            (Spend::TaprootScript { leaf_hash }, SighashTypeAny::Taproot(sighash_type)) => cache
+                .taproot_script_spend_signature_hash(
+                    input_index,
+                    &Prevouts::All(&prevouts),
+                    *leaf_hash,
+                    sighash_type,
+                )?
+                .to_byte_array(),
+            _ => return Err(TestVectorError::SighashTypeMismatch),
+        };
+        Ok(Self { tx, prevouts, input_index, spend, sighash_type, sighash })
+    }
+
+    fn value(&self) -> Value {
+        let prevouts = Value::array(&self.prevouts, |prevout| {
+            Value::Object(vec![
+                ("script_pubkey", Value::hex(prevout.script_pubkey.as_bytes())),
+                ("amount_sat", Value::Number(prevout.amount.to_sat())),
+            ])
+        });
+        let (spend, script_code, leaf_hash) = match self.spend {
+            Spend::SegwitV0 { ref script_code } =>
+                ("segwit_v0", Value::hex(script_code.as_bytes()), Value::Null),
+            Spend::TaprootKey => ("taproot_key", Value::Null, Value::Null),
+            Spend::TaprootScript { leaf_hash } =>
+                ("taproot_script", Value::Null, Value::string(leaf_hash)),
+        };
+        Value::Object(vec![
+            ("tx", Value::String(encode::serialize_hex(&self.tx))),
+            ("prevouts", prevouts),
+            ("input_index", Value::Number(self.input_index as u64)),
+            ("spend", Value::string(spend)),
+            ("script_code", script_code),
+            ("leaf_hash", leaf_hash),
+            ("sighash_type", Value::Number(self.sighash_type.to_u32().into())),
+            ("sighash", Value::hex(&self.sighash)),
+        ])
+    }
+
+    /// Returns the JSON representation of the vector.
+    ///
+    /// Scripts and the transaction are hex encoded, the leaf hash is in its usual display order.
+    pub fn to_json(&self) -> String { self.value().to_string() }
+}
+
+/// Generates a vector for every sighash type of `spend`.
+///
+/// Taproot vectors with `SIGHASH_SINGLE` are skipped if there is no output at `input_index`, the
+/// sighash is undefined. The vectors are generated in the order of the sighash type values,
+/// starting with `SIGHASH_DEFAULT` for Taproot.
+pub fn generate(
+    tx: &Transaction,
+    prevouts: &[TxOut],
+    input_index: usize,
+    spend: &Spend,
+) -> Result<Vec<TestVector>, TestVectorError> {
+    let sighash_types: Vec<SighashTypeAny> = match spend {
+        Spend::SegwitV0 { .. } => ECDSA_SIGHASH_TYPES.iter().map(|&t| t.into()).collect(),
+        Spend::TaprootKey | Spend::TaprootScript { .. } => TAP_SIGHASH_TYPES
+            .iter()
+            .filter(|&&t| {
+                let single =
+                    matches!(t, TapSighashType::Single | TapSighashType::SinglePlusAnyoneCanPay);
+                !single || input_index < tx.outputs.len()
+            })
+            .map(|&t| t.into())
+            .collect(),
+    };
+    sighash_types
+        .into_iter()
+        .map(|sighash_type| {
+            TestVector::new(tx.clone(), prevouts.to_vec(), input_index, spend.clone(), sighash_type)
+        })
+        .collect()
+}
+
+/// Returns the JSON array of `vectors`.
+pub fn to_json(vectors: &[TestVector]) -> String {
+    Value::array(vectors, TestVector::value).to_string()
+}
+
+/// Error constructing a [`TestVector`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TestVectorError {
+    /// The sighash type is not one of the spend, ECDSA for SegWit v0 and Taproot for Taproot.
+    SighashTypeMismatch,
+    /// The number of prevouts differs from the number of inputs.
+    PrevoutsSize(PrevoutsSizeError),
+    /// The input index is out of bounds.
+    InputsIndex(InputsIndexError),
+    /// Computing the Taproot sighash failed.
+    Taproot(TaprootError),
+}
+
+impl From<core::convert::Infallible> for TestVectorError {
+    fn from(never: core::convert::Infallible) -> Self { match never {} }
+}
+
+impl From<InputsIndexError> for TestVectorError {
+    fn from(e: InputsIndexError) -> Self { Self::InputsIndex(e) }
+}
+
+impl From<TaprootError> for TestVectorError {
+    fn from(e: TaprootError) -> Self { Self::Taproot(e) }
+}
+
+impl fmt::Display for TestVectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::SighashTypeMismatch => f.write_str("sighash type does not match the spend"),
+            Self::PrevoutsSize(ref e) => write_err!(f, "prevouts size"; e),
+            Self::InputsIndex(ref e) => write_err!(f, "inputs index"; e),
+            Self::Taproot(ref e) => write_err!(f, "taproot sighash"; e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TestVectorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::SighashTypeMismatch => None,
+            Self::PrevoutsSize(ref e) => Some(e),
+            Self::InputsIndex(ref e) => Some(e),
+            Self::Taproot(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_lit::hex;
+
+    use super::*;
+    use crate::consensus::encode::deserialize;
+    use crate::script::ScriptBufExt as _;
+    use crate::ScriptPubKeyBuf;
+
+    #[test]
+    fn bip143_p2wpkh() {
+        let tx = deserialize::<Transaction>(&hex!(
+            "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f000000\
+            0000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a01000000\
+            00ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093\
+            510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000"
+        ))
+        .unwrap();
+        let prevouts = vec![
+            TxOut {
+                amount: crate::Amount::from_sat_u32(625_000_000),
+                script_pubkey: ScriptPubKeyBuf::from_hex_no_length_prefix(
+                    "2103c9f4836b9a4f77fc0d81f7bcb01b7f1b35916864b9476c241ce9fc198bd25432ac",
+                )
+                .unwrap(),
+            },
+            TxOut {
+                amount: crate::Amount::from_sat_u32(600_000_000),
+                script_pubkey: ScriptPubKeyBuf::from_hex_no_length_prefix(
+                    "00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1",
+                )
+                .unwrap(),
+            },
+        ];
+        let script_code = WitnessScriptBuf::from_hex_no_length_prefix(
+            "76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac",
+        )
+        .unwrap();
+
+        let spend = Spend::SegwitV0 { script_code };
+        let vectors = generate(&tx, &prevouts, 1, &spend).unwrap();
+        assert_eq!(vectors.len(), 6);
+        assert_eq!(vectors[0].sighash_type, SighashTypeAny::Ecdsa(EcdsaSighashType::All));
+        assert_eq!(
+            vectors[0].sighash,
+            hex!("c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670")
+        );
+
+        let json = serde_json::from_str::<serde_json::Value>(&to_json(&vectors)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 6);
+        assert_eq!(json[0]["tx"], encode::serialize_hex(&tx));
+        assert_eq!(json[0]["prevouts"][1]["amount_sat"], 600_000_000);
+        assert_eq!(json[0]["input_index"], 1);
+        assert_eq!(json[0]["spend"], "segwit_v0");
+        assert_eq!(json[0]["script_code"], "76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac");
+        assert!(json[0]["leaf_hash"].is_null());
+        assert_eq!(json[0]["sighash_type"], 1);
+        assert_eq!(
+            json[0]["sighash"],
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+        assert_eq!(json[5]["sighash_type"], 0x83);
+        let single = serde_json::from_str::<serde_json::Value>(&vectors[0].to_json()).unwrap();
+        assert_eq!(single, json[0]);
+    }
+
+    #[test]
+    fn taproot() {
+        let mut tx = deserialize::<Transaction>(&hex!(
+            "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f000000\
+            0000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a01000000\
+            00ffffffff01202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac11000000"
+        ))
+        .unwrap();
+        let prevout = TxOut {
+            amount: crate::Amount::ONE_BTC,
+            script_pubkey: ScriptPubKeyBuf::from_hex_no_length_prefix(
+                "51201d0f172a0ecb48aee1be1f2687d2963ae33f71a11d0f172a0ecb48aee1be1f26",
+            )
+            .unwrap(),
+        };
+        let prevouts = vec![prevout.clone(), prevout];
+
+        // No output for `SIGHASH_SINGLE` at index 1.
+        let vectors = generate(&tx, &prevouts, 1, &Spend::TaprootKey).unwrap();
+        assert_eq!(vectors.len(), 5);
+        assert_eq!(generate(&tx, &prevouts, 0, &Spend::TaprootKey).unwrap().len(), 7);
+        let mut cache = SighashCache::new(&tx);
+        let expected = cache
+            .taproot_key_spend_signature_hash(1, &Prevouts::All(&prevouts), TapSighashType::Default)
+            .unwrap();
+        assert_eq!(vectors[0].sighash, expected.to_byte_array());
+
+        let leaf_hash = TapLeafHash::from_byte_array([1; 32]);
+        let spend = Spend::TaprootScript { leaf_hash };
+        let vectors = generate(&tx, &prevouts, 0, &spend).unwrap();
+        let json = serde_json::from_str::<serde_json::Value>(&vectors[3].to_json()).unwrap();
+        assert_eq!(json["spend"], "taproot_script");
+        assert_eq!(json["leaf_hash"], leaf_hash.to_string());
+        assert_eq!(json["sighash_type"], 3);
+
+        let all = SighashTypeAny::Taproot(TapSighashType::All);
+        let ecdsa = SighashTypeAny::Ecdsa(EcdsaSighashType::All);
+        assert_eq!(
+            TestVector::new(tx.clone(), prevouts.clone(), 0, Spend::TaprootKey, ecdsa),
+            Err(TestVectorError::SighashTypeMismatch)
+        );
+        assert!(matches!(
+            TestVector::new(tx.clone(), prevouts.clone(), 2, Spend::TaprootKey, all),
+            Err(TestVectorError::InputsIndex(_))
+        ));
+        tx.inputs.pop();
+        assert_eq!(
+            TestVector::new(tx, prevouts, 0, Spend::TaprootKey, all),
+            Err(TestVectorError::PrevoutsSize(PrevoutsSizeError))
+        );
+    }
+}
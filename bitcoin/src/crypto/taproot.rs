@@ -11,11 +11,17 @@
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::{Arbitrary, Unstructured};
+#[cfg(any(feature = "adaptor", feature = "contract"))]
+use hashes::{sha256t, sha256t_tag, HashEngine as _};
 use internals::array::ArrayExt;
 use internals::{impl_to_hex_from_lower_hex, write_err};
 use io::Write;
+#[cfg(any(feature = "adaptor", feature = "contract"))]
+use secp256k1::{Scalar, SecretKey};
 
 pub use self::into_iter::IntoIter;
+#[cfg(any(feature = "adaptor", feature = "contract"))]
+use crate::crypto::key::XOnlyPublicKey;
 use crate::prelude::{DisplayHex, Vec};
 use crate::sighash::{InvalidSighashTypeError, TapSighashType};
 
@@ -86,6 +92,39 @@ pub fn serialize_to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(
     }
 }
 
+/// The encoding of a serialized Taproot signature, see [`SignatureInfo::inspect`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SignatureInfo {
+    /// The explicit sighash type byte, `None` if the signature is 64 bytes long and uses
+    /// [`TapSighashType::Default`].
+    pub sighash_byte: Option<u8>,
+}
+
+impl SignatureInfo {
+    /// Inspects `sig`, a 64 byte signature optionally followed by the sighash type byte, as found
+    /// in a witness.
+    ///
+    /// Returns `None` if `sig` is neither 64 nor 65 bytes long.
+    pub fn inspect(sig: &[u8]) -> Option<Self> {
+        match sig.len() {
+            64 => Some(Self { sighash_byte: None }),
+            65 => Some(Self { sighash_byte: Some(sig[64]) }),
+            _ => None,
+        }
+    }
+
+    /// Returns the sighash type, `None` if the sighash type byte is invalid.
+    ///
+    /// An explicit `0x00` byte is invalid, BIP-0341 requires omitting it instead.
+    pub fn sighash_type(&self) -> Option<TapSighashType> {
+        match self.sighash_byte {
+            None => Some(TapSighashType::Default),
+            Some(0x00) => None,
+            Some(byte) => TapSighashType::from_consensus_u8(byte).ok(),
+        }
+    }
+}
+
 /// A serialized Taproot Signature
 ///
 /// Serialized Taproot signatures have the issue that they can have different lengths.
@@ -401,6 +440,51 @@ impl From<InvalidSighashTypeError> for SigFromSliceError {
     fn from(err: InvalidSighashTypeError) -> Self { Self::SighashType(err) }
 }
 
+#[cfg(any(feature = "adaptor", feature = "contract"))]
+sha256t_tag! {
+    struct ChallengeTag = hash_str("BIP0340/challenge");
+}
+
+/// Returns the BIP-0340 challenge of a signature with nonce `rx` of `msg` by `pubkey`.
+#[cfg(any(feature = "adaptor", feature = "contract"))]
+pub(crate) fn challenge(rx: &[u8; 32], pubkey: &XOnlyPublicKey, msg: &[u8]) -> Scalar {
+    let mut engine = sha256t::Hash::<ChallengeTag>::engine();
+    engine.input(rx);
+    engine.input(&pubkey.serialize());
+    engine.input(msg);
+    // BIP-0340 reduces the hash modulo the curve order, a hash above it is unreachable.
+    Scalar::from_be_bytes(engine.finalize().to_byte_array())
+        .expect("statistically impossible to hit")
+}
+
+/// Derives a nonce like BIP-0340, from `secret` masked with the `A` tagged hash of `aux_rand`
+/// followed by `data`, hashed with the `N` tag.
+#[cfg(any(feature = "adaptor", feature = "contract"))]
+pub(crate) fn derive_nonce<A: sha256t::Tag, N: sha256t::Tag>(
+    secret: &SecretKey,
+    aux_rand: &[u8; 32],
+    data: &[&[u8]],
+) -> SecretKey {
+    let aux = sha256t::Hash::<A>::hash(aux_rand).to_byte_array();
+    let mut masked = secret.to_secret_bytes();
+    masked.iter_mut().zip(aux).for_each(|(byte, aux)| *byte ^= aux);
+    let mut engine = sha256t::Hash::<N>::engine();
+    engine.input(&masked);
+    secp256k1::ffi::non_secure_erase_impl(&mut masked, [0; 32]);
+    data.iter().for_each(|data| engine.input(data));
+    SecretKey::from_secret_bytes(engine.finalize().to_byte_array())
+        .expect("statistically impossible to hit")
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Signature {
+    /// Overwrites the signature with an all zeros one, the sighash type is left as is.
+    fn zeroize(&mut self) {
+        let zero = secp256k1::schnorr::Signature::from_byte_array([0; 64]);
+        secp256k1::ffi::non_secure_erase_impl(&mut self.signature, zero);
+    }
+}
+
 #[cfg(feature = "arbitrary")]
 impl<'a> Arbitrary<'a> for Signature {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -415,7 +499,27 @@ fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
 
 #[cfg(test)]
 mod tests {
-    use super::{SerializedSignature, MAX_LEN};
+    use super::{SerializedSignature, SignatureInfo, TapSighashType, MAX_LEN};
+
+    #[test]
+    fn inspect() {
+        let info = SignatureInfo::inspect(&[7; 64]).unwrap();
+        assert_eq!(info.sighash_byte, None);
+        assert_eq!(info.sighash_type(), Some(TapSighashType::Default));
+
+        let mut sig = [7; 65];
+        sig[64] = 0x83;
+        assert_eq!(
+            SignatureInfo::inspect(&sig).unwrap().sighash_type(),
+            Some(TapSighashType::SinglePlusAnyoneCanPay)
+        );
+        sig[64] = 0x00;
+        assert_eq!(SignatureInfo::inspect(&sig).unwrap().sighash_type(), None);
+        sig[64] = 0x04;
+        let info = SignatureInfo::inspect(&sig).unwrap();
+        assert_eq!((info.sighash_byte, info.sighash_type()), (Some(0x04), None));
+        assert_eq!(SignatureInfo::inspect(&sig[..63]), None);
+    }
 
     #[test]
     fn iterator_ops_are_homomorphic() {
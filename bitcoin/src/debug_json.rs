@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! JSON representations of transactions and PSBTs for debugging.
+//!
+//! The output is meant to be read by people and by log and support tooling, it is not a
+//! serialization format and can not be parsed back. Members always appear in the same order, are
+//! present even if empty (as `null` or `[]`) and the output is indented by two spaces with one
+//! member per line, so that two outputs can be diffed line by line.
+
+use core::fmt::{self, Write as _};
+
+use hex_unstable::DisplayHex as _;
+
+use crate::bip32::KeySource;
+use crate::crypto::ecdsa;
+use crate::crypto::key::XOnlyPublicKey;
+use crate::prelude::{BTreeMap, String, ToString, Vec};
+use crate::psbt::{Input, Output, Psbt};
+use crate::script::{Instruction, Script, ScriptExt as _};
+use crate::taproot::{self, TapLeafHash};
+use crate::transaction::{Transaction, TransactionExt as _, TxIn, TxOut};
+use crate::witness::Witness;
+
+/// The members of a JSON object, in order.
+pub(crate) type Members = Vec<(&'static str, Value)>;
+
+/// A JSON value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    Null,
+    Number(u64),
+    String(String),
+    Array(Vec<Self>),
+    Object(Members),
+}
+
+impl Value {
+    pub(crate) fn string(s: impl fmt::Display) -> Self { Self::String(s.to_string()) }
+
+    pub(crate) fn hex(bytes: &[u8]) -> Self { Self::String(bytes.to_lower_hex_string()) }
+
+    pub(crate) fn option<T>(value: Option<T>, f: impl FnOnce(T) -> Self) -> Self {
+        value.map_or(Self::Null, f)
+    }
+
+    pub(crate) fn array<T>(items: impl IntoIterator<Item = T>, f: impl FnMut(T) -> Self) -> Self {
+        Self::Array(items.into_iter().map(f).collect())
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        match *self {
+            Self::Null => f.write_str("null"),
+            Self::Number(n) => write!(f, "{}", n),
+            Self::String(ref s) => write_string(f, s),
+            Self::Array(ref items) if items.is_empty() => f.write_str("[]"),
+            Self::Object(ref members) if members.is_empty() => f.write_str("{}"),
+            Self::Array(ref items) => {
+                f.write_str("[\n")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{:1$}", "", indent + 2)?;
+                    item.write(f, indent + 2)?;
+                    f.write_str(if i + 1 < items.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{:1$}]", "", indent)
+            }
+            Self::Object(ref members) => {
+                f.write_str("{\n")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    write!(f, "{:1$}", "", indent + 2)?;
+                    write_string(f, key)?;
+                    f.write_str(": ")?;
+                    value.write(f, indent + 2)?;
+                    f.write_str(if i + 1 < members.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{:1$}}}", "", indent)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { self.write(f, 0) }
+}
+
+/// Writes `s` as a JSON string.
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", u32::from(c))?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Returns the JSON representation of `tx`.
+pub(crate) fn transaction(tx: &Transaction) -> String { transaction_value(tx).to_string() }
+
+/// Returns the JSON representation of `psbt`.
+pub(crate) fn psbt(psbt: &Psbt) -> String {
+    let inputs = psbt.inputs.iter().enumerate().map(|(i, input)| {
+        let spent_output = psbt.spend_utxo(i).ok();
+        input_value(input, spent_output)
+    });
+    Value::Object(vec![
+        ("version", Value::Number(psbt.version.into())),
+        ("unsigned_tx", transaction_value(&psbt.unsigned_tx)),
+        ("fee_sat", Value::option(psbt.fee().ok(), |fee| Value::Number(fee.to_sat()))),
+        ("inputs", Value::Array(inputs.collect())),
+        ("outputs", Value::array(&psbt.outputs, output_value)),
+    ])
+    .to_string()
+}
+
+fn transaction_value(tx: &Transaction) -> Value {
+    Value::Object(vec![
+        ("txid", Value::string(tx.compute_txid())),
+        ("wtxid", Value::string(tx.compute_wtxid())),
+        ("version", Value::Number(tx.version.to_u32().into())),
+        ("lock_time", Value::Number(tx.lock_time.to_consensus_u32().into())),
+        ("size", Value::Number(tx.total_size() as u64)),
+        ("vsize", Value::Number(tx.vsize() as u64)),
+        ("weight", Value::Number(tx.weight().to_wu())),
+        ("inputs", Value::array(&tx.inputs, tx_in_value)),
+        ("outputs", Value::array(&tx.outputs, tx_out_value)),
+    ])
+}
+
+fn tx_in_value(input: &TxIn) -> Value {
+    // Schnorr signatures can not be told apart from other 64 byte elements without the spent
+    // output, only ECDSA signatures are recognized.
+    let pushes = input.script_sig.instructions().filter_map(|instruction| match instruction {
+        Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes()),
+        _ => None,
+    });
+    let signatures = pushes
+        .chain(input.witness.iter())
+        .filter_map(|element| ecdsa::Signature::from_slice(element).ok());
+    Value::Object(vec![
+        ("previous_output", Value::string(input.previous_output)),
+        ("script_sig", script_value(&input.script_sig)),
+        ("sequence", Value::Number(input.sequence.to_consensus_u32().into())),
+        ("witness", witness_value(&input.witness)),
+        ("signatures", Value::array(signatures, ecdsa_signature_value)),
+    ])
+}
+
+fn tx_out_value(output: &TxOut) -> Value {
+    Value::Object(vec![
+        ("amount_sat", Value::Number(output.amount.to_sat())),
+        ("script_pubkey", script_value(&output.script_pubkey)),
+    ])
+}
+
+fn script_value<T>(script: &Script<T>) -> Value {
+    Value::Object(vec![
+        ("asm", Value::string(script.to_asm_string())),
+        ("hex", Value::hex(script.as_bytes())),
+    ])
+}
+
+fn witness_value(witness: &Witness) -> Value { Value::array(witness.iter(), Value::hex) }
+
+fn ecdsa_signature_value(sig: ecdsa::Signature) -> Value {
+    Value::Object(signature_members(&sig.to_vec(), sig.sighash_type))
+}
+
+fn taproot_signature_value(sig: &taproot::Signature) -> Value {
+    Value::Object(signature_members(&sig.to_vec(), sig.sighash_type))
+}
+
+fn signature_members(sig: &[u8], sighash_type: impl fmt::Display) -> Members {
+    vec![("signature", Value::hex(sig)), ("sighash_type", Value::string(sighash_type))]
+}
+
+fn key_source_members(key: impl fmt::Display, (fingerprint, path): &KeySource) -> Members {
+    vec![
+        ("key", Value::string(key)),
+        ("fingerprint", Value::string(fingerprint)),
+        ("path", Value::string(path)),
+    ]
+}
+
+fn input_value(input: &Input, spent_output: Option<&TxOut>) -> Value {
+    let partial_sigs = input.partial_sigs.iter().map(|(key, sig)| {
+        let mut members = vec![("key", Value::string(key))];
+        members.extend(signature_members(&sig.to_vec(), sig.sighash_type));
+        Value::Object(members)
+    });
+    let tap_script_sigs = input.tap_script_sigs.iter().map(|((key, leaf_hash), sig)| {
+        let mut members =
+            vec![("key", Value::string(key)), ("leaf_hash", Value::string(leaf_hash))];
+        members.extend(signature_members(&sig.to_vec(), sig.sighash_type));
+        Value::Object(members)
+    });
+    let tap_scripts = input.tap_scripts.iter().map(|(control_block, (script, leaf_version))| {
+        Value::Object(vec![
+            ("control_block", Value::hex(&control_block.serialize())),
+            ("leaf_version", Value::string(leaf_version)),
+            ("script", script_value(script)),
+        ])
+    });
+    Value::Object(vec![
+        ("spent_output", Value::option(spent_output, tx_out_value)),
+        ("sighash_type", Value::option(input.sighash_type, Value::string)),
+        ("redeem_script", Value::option(input.redeem_script.as_deref(), script_value)),
+        ("witness_script", Value::option(input.witness_script.as_deref(), script_value)),
+        ("partial_sigs", Value::Array(partial_sigs.collect())),
+        ("bip32_derivation", bip32_derivation_value(&input.bip32_derivation)),
+        ("final_script_sig", Value::option(input.final_script_sig.as_deref(), script_value)),
+        ("final_script_witness", Value::option(input.final_script_witness.as_ref(), witness_value)),
+        ("tap_key_sig", Value::option(input.tap_key_sig.as_ref(), taproot_signature_value)),
+        ("tap_script_sigs", Value::Array(tap_script_sigs.collect())),
+        ("tap_scripts", Value::Array(tap_scripts.collect())),
+        ("tap_key_origins", tap_key_origins_value(&input.tap_key_origins)),
+        ("tap_internal_key", Value::option(input.tap_internal_key, Value::string)),
+        ("tap_merkle_root", Value::option(input.tap_merkle_root, Value::string)),
+    ])
+}
+
+fn output_value(output: &Output) -> Value {
+    Value::Object(vec![
+        ("redeem_script", Value::option(output.redeem_script.as_deref(), script_value)),
+        ("witness_script", Value::option(output.witness_script.as_deref(), script_value)),
+        ("bip32_derivation", bip32_derivation_value(&output.bip32_derivation)),
+        ("tap_internal_key", Value::option(output.tap_internal_key, Value::string)),
+        ("tap_key_origins", tap_key_origins_value(&output.tap_key_origins)),
+    ])
+}
+
+fn bip32_derivation_value(derivation: &BTreeMap<secp256k1::PublicKey, KeySource>) -> Value {
+    Value::array(derivation, |(key, source)| Value::Object(key_source_members(key, source)))
+}
+
+fn tap_key_origins_value(
+    origins: &BTreeMap<XOnlyPublicKey, (Vec<TapLeafHash>, KeySource)>,
+) -> Value {
+    Value::array(origins, |(key, (leaf_hashes, source))| {
+        let mut members = key_source_members(key, source);
+        members.push(("leaf_hashes", Value::array(leaf_hashes, Value::string)));
+        Value::Object(members)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::SecretKey;
+
+    use super::*;
+    use crate::script::{ScriptBufExt as _, ScriptPubKeyBuf};
+    use crate::{
+        absolute, transaction, Amount, EcdsaSighashType, NetworkKind, PrivateKey, PublicKey,
+    };
+
+    #[test]
+    fn value_layout() {
+        let value = Value::Object(vec![
+            ("null", Value::Null),
+            ("string", Value::string("\"a\\b\"\n\u{1}")),
+            ("empty", Value::Array(vec![])),
+            ("array", Value::Array(vec![Value::Number(1), Value::Object(vec![])])),
+        ]);
+        let expected = "{\n  \"null\": null,\n  \"string\": \"\\\"a\\\\b\\\"\\n\\u0001\",\n  \
+             \"empty\": [],\n  \"array\": [\n    1,\n    {}\n  ]\n}";
+        assert_eq!(value.to_string(), expected);
+    }
+
+    #[test]
+    fn signed_psbt_and_transaction() {
+        let sk = SecretKey::from_secret_bytes([0x11; 32]).unwrap();
+        let priv_key = PrivateKey::new(sk, NetworkKind::Test);
+        let pk = PublicKey::from_private_key(priv_key);
+        let unsigned_tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn::EMPTY_COINBASE],
+            outputs: vec![TxOut { amount: Amount::ONE_SAT, script_pubkey: ScriptPubKeyBuf::new() }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            amount: Amount::from_sat_u32(10),
+            script_pubkey: ScriptPubKeyBuf::new_p2wpkh(pk.wpubkey_hash().unwrap()),
+        });
+        psbt.inputs[0].bip32_derivation.insert(pk.inner, Default::default());
+        psbt.sign(&[(pk, priv_key)].into_iter().collect::<BTreeMap<_, _>>()).unwrap();
+        let sig = psbt.inputs[0].partial_sigs[&pk];
+
+        let json = psbt.to_debug_json();
+        let parsed = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(parsed["fee_sat"], 9);
+        let partial_sig = &parsed["inputs"][0]["partial_sigs"][0];
+        assert_eq!(partial_sig["key"], pk.to_string());
+        assert_eq!(partial_sig["sighash_type"], "SIGHASH_ALL");
+        assert_eq!(partial_sig["signature"], sig.to_vec().to_lower_hex_string());
+        assert!(parsed["inputs"][0]["tap_key_sig"].is_null());
+        assert_eq!(parsed["outputs"][0]["tap_key_origins"], serde_json::json!([]));
+
+        let mut tx = psbt.unsigned_tx.clone();
+        let sig =
+            ecdsa::Signature { sighash_type: EcdsaSighashType::SinglePlusAnyoneCanPay, ..sig };
+        tx.inputs[0].witness = Witness::from_slice(&[sig.to_vec(), pk.to_vec()]);
+        let json = tx.to_debug_json();
+        let parsed = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(parsed["txid"], tx.compute_txid().to_string());
+        assert_eq!(parsed["wtxid"], tx.compute_wtxid().to_string());
+        assert_eq!(parsed["weight"], tx.weight().to_wu());
+        let signatures = &parsed["inputs"][0]["signatures"];
+        assert_eq!(signatures.as_array().unwrap().len(), 1);
+        assert_eq!(signatures[0]["sighash_type"], "SIGHASH_SINGLE|SIGHASH_ANYONECANPAY");
+        assert_eq!(parsed["outputs"][0]["amount_sat"], 1);
+        // Stable output.
+        assert_eq!(json, tx.clone().to_debug_json());
+    }
+}
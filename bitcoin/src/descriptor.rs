@@ -0,0 +1,511 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Single key output script descriptors.
+//!
+//! Wallets following BIP-0044, BIP-0049, BIP-0084 and BIP-0086 use the addresses derived from an
+//! account key along a branch, `0` for receiving and `1` for change. A [`Descriptor`] describes
+//! such a chain of addresses, for example `wpkh([d34db33f/84h/0h/0h]xpub.../0/*)`, and derives
+//! them lazily, as needed for gap limit scanning of watch-only wallets.
+//!
+//! Only the single key descriptors `pkh()`, `sh(wpkh())`, `wpkh()` and `tr()` with a key origin
+//! and a trailing wildcard are supported. The `#` checksum Bitcoin Core appends is optional and
+//! verified if present. Use the `miniscript` crate for anything more.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::descriptor::Descriptor;
+//! use bitcoin::Network;
+//!
+//! let descriptor = "tr([73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)"
+//!     .parse::<Descriptor>()
+//!     .unwrap();
+//! let (index, address) = descriptor.addresses(Network::Bitcoin, 0..20).unwrap().next().unwrap();
+//! assert_eq!(index, 0);
+//! assert_eq!(address.to_string(), "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+//! ```
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::Range;
+use core::str::FromStr;
+
+use internals::write_err;
+
+use crate::address::Address;
+use crate::bip32::{ChildNumber, DerivationError, DerivationPath, ParseChildNumberError, Xpub};
+use crate::hww::{DeviceKey, ParseDeviceKeyError};
+use crate::network::Network;
+
+/// The script type of a single key output descriptor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ScriptType {
+    /// `pkh(<key>)`, BIP-0044 accounts.
+    Pkh,
+    /// `sh(wpkh(<key>))`, BIP-0049 accounts.
+    ShWpkh,
+    /// `wpkh(<key>)`, BIP-0084 accounts.
+    Wpkh,
+    /// `tr(<key>)`, BIP-0086 accounts.
+    Tr,
+}
+
+impl ScriptType {
+    /// All supported script types.
+    const ALL: [Self; 4] = [Self::Pkh, Self::ShWpkh, Self::Wpkh, Self::Tr];
+
+    /// Returns the names of the script expressions wrapping the key, outermost first.
+    fn functions(self) -> &'static [&'static str] {
+        match self {
+            Self::Pkh => &["pkh"],
+            Self::ShWpkh => &["sh", "wpkh"],
+            Self::Wpkh => &["wpkh"],
+            Self::Tr => &["tr"],
+        }
+    }
+
+    /// Returns the key expression of `s` if it has this script type.
+    fn strip_functions(self, mut s: &str) -> Option<&str> {
+        for function in self.functions() {
+            s = s.strip_prefix(function)?.strip_prefix('(')?.strip_suffix(')')?;
+        }
+        Some(s)
+    }
+
+    /// Returns the address paying to `xpub` with this script type.
+    fn address(self, xpub: &Xpub, network: Network) -> Address {
+        match self {
+            Self::Pkh => Address::p2pkh(xpub.to_public_key(), network),
+            Self::ShWpkh => Address::p2shwpkh(xpub.to_public_key(), network),
+            Self::Wpkh => Address::p2wpkh(xpub.to_public_key(), network),
+            // BIP-0086 keys commit to an unspendable script path.
+            Self::Tr => Address::p2tr(xpub.to_x_only_public_key(), None, network),
+        }
+    }
+}
+
+/// A single key output descriptor with a wildcard, `<script>(<key>/<branch>/*)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Descriptor {
+    script_type: ScriptType,
+    key: DeviceKey,
+    /// The path from the key to the parent of the derived keys.
+    branch: DerivationPath,
+}
+
+impl Descriptor {
+    /// Constructs a new descriptor of the keys below the `branch` path of `key`.
+    pub fn new(script_type: ScriptType, key: DeviceKey, branch: DerivationPath) -> Self {
+        Self { script_type, key, branch }
+    }
+
+    /// Returns the script type.
+    pub fn script_type(&self) -> ScriptType { self.script_type }
+
+    /// Returns the key the addresses are derived from.
+    pub fn key(&self) -> &DeviceKey { &self.key }
+
+    /// Returns the path from the key to the parent of the derived keys.
+    pub fn branch(&self) -> &DerivationPath { &self.branch }
+
+    /// Derives the address at `index`.
+    ///
+    /// # Errors
+    ///
+    /// If the branch or `index` is hardened or the keys would be too deep.
+    pub fn address_at(&self, index: u32, network: Network) -> Result<Address, DerivationError> {
+        let child = ChildNumber::from_normal_idx(index)
+            .map_err(|_| DerivationError::CannotDeriveHardenedChild)?;
+        let xpub = self.key.xpub().derive_xpub(&self.branch)?.ckd_pub(child)?;
+        Ok(self.script_type.address(&xpub, network))
+    }
+
+    /// Returns an iterator over the indices in `range` and the addresses at them.
+    ///
+    /// The key at the branch is derived once, each address then takes a single child derivation.
+    ///
+    /// # Errors
+    ///
+    /// If the branch or an index in `range` is hardened or the keys would be too deep. This is
+    /// checked up front, the iterator itself never fails.
+    pub fn addresses(
+        &self,
+        network: Network,
+        range: Range<u32>,
+    ) -> Result<Addresses, DerivationError> {
+        let branch = self.key.xpub().derive_xpub(&self.branch)?;
+        if !range.is_empty() {
+            ChildNumber::from_normal_idx(range.end - 1)
+                .map_err(|_| DerivationError::CannotDeriveHardenedChild)?;
+            if branch.depth == u8::MAX {
+                return Err(DerivationError::MaximumDepthExceeded);
+            }
+        }
+        Ok(Addresses { script_type: self.script_type, branch, network, indices: range })
+    }
+
+    /// Returns a parallel iterator over the indices in `range` and the addresses at them.
+    ///
+    /// Like [`Self::addresses`] but derives the addresses on the rayon thread pool, for scanning
+    /// large ranges.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::addresses`].
+    #[cfg(feature = "rayon")]
+    pub fn par_addresses(
+        &self,
+        network: Network,
+        range: Range<u32>,
+    ) -> Result<impl rayon::iter::IndexedParallelIterator<Item = (u32, Address)>, DerivationError>
+    {
+        use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+
+        let addresses = self.addresses(network, range)?;
+        Ok(addresses.indices.clone().into_par_iter().map(move |i| addresses.address(i)))
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let functions = self.script_type.functions();
+        for function in functions {
+            write!(f, "{}(", function)?;
+        }
+        write!(f, "{}", self.key)?;
+        for child in &self.branch {
+            write!(f, "/{}", child)?;
+        }
+        f.write_str("/*")?;
+        for _ in functions {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Descriptor {
+    type Err = ParseDescriptorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = match s.split_once('#') {
+            Some((descriptor, got)) => {
+                let expected =
+                    checksum(descriptor).ok_or(ParseDescriptorError::UnsupportedDescriptor)?;
+                if !got.chars().eq(expected) {
+                    return Err(ParseDescriptorError::ChecksumMismatch);
+                }
+                descriptor
+            }
+            None => s,
+        };
+        let (script_type, inner) = ScriptType::ALL
+            .into_iter()
+            .find_map(|script_type| Some((script_type, script_type.strip_functions(s)?)))
+            .ok_or(ParseDescriptorError::UnsupportedDescriptor)?;
+        let inner = inner.strip_suffix("/*").ok_or(ParseDescriptorError::MissingWildcard)?;
+        // The branch starts at the first `/` after the key origin.
+        let origin_end = inner.find(']').map_or(0, |i| i + 1);
+        let (key, branch) = match inner[origin_end..].find('/') {
+            Some(i) => (&inner[..origin_end + i], &inner[origin_end + i + 1..]),
+            None => (inner, ""),
+        };
+        Ok(Self {
+            script_type,
+            key: key.parse().map_err(ParseDescriptorError::Key)?,
+            branch: branch.parse().map_err(ParseDescriptorError::Branch)?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+internals::serde_string_impl!(Descriptor, "a single key output descriptor");
+
+/// An iterator over the addresses of a [`Descriptor`], see [`Descriptor::addresses`].
+#[derive(Debug, Clone)]
+pub struct Addresses {
+    script_type: ScriptType,
+    /// The key at the branch, the parent of the derived keys.
+    branch: Xpub,
+    network: Network,
+    indices: Range<u32>,
+}
+
+impl Addresses {
+    fn address(&self, index: u32) -> (u32, Address) {
+        let child = ChildNumber::from_normal_idx(index).expect("the range is checked");
+        let xpub = self.branch.ckd_pub(child).expect("the range and depth are checked");
+        (index, self.script_type.address(&xpub, self.network))
+    }
+}
+
+impl Iterator for Addresses {
+    type Item = (u32, Address);
+
+    fn next(&mut self) -> Option<Self::Item> { self.indices.next().map(|i| self.address(i)) }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.indices.nth(n).map(|i| self.address(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { self.indices.size_hint() }
+}
+
+impl DoubleEndedIterator for Addresses {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.indices.next_back().map(|i| self.address(i))
+    }
+}
+
+impl ExactSizeIterator for Addresses {}
+
+impl FusedIterator for Addresses {}
+
+/// The characters allowed in a descriptor, in the order the checksum assigns values to them.
+const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+
+/// The characters of a checksum.
+const CHECKSUM_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Computes the checksum Bitcoin Core appends to descriptors after a `#`.
+///
+/// Returns `None` if `descriptor` contains a character not allowed in descriptors.
+pub(crate) fn checksum(descriptor: &str) -> Option<[char; 8]> {
+    fn poly_mod(c: u64, value: u64) -> u64 {
+        const GENERATORS: [u64; 5] =
+            [0xf5dee51989, 0xa9fdca3312, 0x1bab10e32d, 0x3706b1677a, 0x644d626ffd];
+        let top = c >> 35;
+        let mut c = ((c & 0x7_ffff_ffff) << 5) ^ value;
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            if top & (1 << i) != 0 {
+                c ^= generator;
+            }
+        }
+        c
+    }
+
+    let mut c = 1;
+    let mut classes = 0;
+    let mut class_count = 0;
+    for ch in descriptor.chars() {
+        let position = INPUT_CHARSET.find(ch)? as u64;
+        c = poly_mod(c, position & 31);
+        // Groups of three upper bits are added as one extra symbol.
+        classes = classes * 3 + (position >> 5);
+        class_count += 1;
+        if class_count == 3 {
+            c = poly_mod(c, classes);
+            classes = 0;
+            class_count = 0;
+        }
+    }
+    if class_count > 0 {
+        c = poly_mod(c, classes);
+    }
+    for _ in 0..8 {
+        c = poly_mod(c, 0);
+    }
+    c ^= 1;
+
+    let mut checksum = ['\0'; 8];
+    for (i, ch) in checksum.iter_mut().enumerate() {
+        *ch = char::from(CHECKSUM_CHARSET[((c >> (5 * (7 - i))) & 31) as usize]);
+    }
+    Some(checksum)
+}
+
+/// Error parsing a [`Descriptor`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseDescriptorError {
+    /// The descriptor is not one of the supported single key descriptors.
+    UnsupportedDescriptor,
+    /// The key is not followed by a `/*` wildcard.
+    MissingWildcard,
+    /// Invalid key expression.
+    Key(ParseDeviceKeyError),
+    /// Invalid branch below the key.
+    Branch(ParseChildNumberError),
+    /// The checksum after the `#` does not match the descriptor.
+    ChecksumMismatch,
+}
+
+impl From<core::convert::Infallible> for ParseDescriptorError {
+    fn from(never: core::convert::Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ParseDescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::UnsupportedDescriptor => f.write_str("unsupported descriptor"),
+            Self::MissingWildcard => f.write_str("missing wildcard after the key"),
+            Self::Key(ref e) => write_err!(f, "invalid key"; e),
+            Self::Branch(ref e) => write_err!(f, "invalid branch"; e),
+            Self::ChecksumMismatch => f.write_str("descriptor checksum mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDescriptorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::UnsupportedDescriptor | Self::MissingWildcard | Self::ChecksumMismatch => None,
+            Self::Key(ref e) => Some(e),
+            Self::Branch(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32::Xpriv;
+    use crate::prelude::{String, ToString, Vec};
+
+    /// The master key of the `abandon abandon ... about` mnemonic.
+    const MASTER: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    fn descriptor(script_type: ScriptType, account: &str, branch: &str) -> Descriptor {
+        let master = MASTER.parse::<Xpriv>().unwrap();
+        let key = DeviceKey::from_master(&master, account.parse().unwrap()).unwrap();
+        Descriptor::new(script_type, key, branch.parse().unwrap())
+    }
+
+    fn addresses(descriptor: &Descriptor, range: Range<u32>) -> Vec<String> {
+        let addresses = descriptor.addresses(Network::Bitcoin, range).unwrap();
+        addresses.map(|(_, address)| address.to_string()).collect()
+    }
+
+    #[test]
+    fn bip_test_vectors() {
+        let wpkh = descriptor(ScriptType::Wpkh, "84h/0h/0h", "0");
+        assert_eq!(
+            addresses(&wpkh, 0..2),
+            [
+                "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+                "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g",
+            ]
+        );
+        let wpkh_change = descriptor(ScriptType::Wpkh, "84h/0h/0h", "1");
+        assert_eq!(addresses(&wpkh_change, 0..1), ["bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"]);
+
+        let tr = descriptor(ScriptType::Tr, "86h/0h/0h", "0");
+        assert_eq!(
+            addresses(&tr, 0..2),
+            [
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh",
+            ]
+        );
+
+        let pkh = descriptor(ScriptType::Pkh, "44h/0h/0h", "0");
+        assert_eq!(addresses(&pkh, 0..1), ["1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"]);
+
+        let sh_wpkh = descriptor(ScriptType::ShWpkh, "49h/0h/0h", "0");
+        assert_eq!(addresses(&sh_wpkh, 0..1), ["37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf"]);
+    }
+
+    #[test]
+    fn addresses_iterator() {
+        let wpkh = descriptor(ScriptType::Wpkh, "84h/0h/0h", "0");
+        let mut iter = wpkh.addresses(Network::Bitcoin, 5..10).unwrap();
+        assert_eq!(iter.len(), 5);
+        let (index, address) = iter.next_back().unwrap();
+        assert_eq!(index, 9);
+        assert_eq!(wpkh.address_at(9, Network::Bitcoin), Ok(address));
+        assert_eq!(iter.nth(1).map(|(index, _)| index), Some(6));
+        assert_eq!(iter.len(), 2);
+
+        assert_eq!(
+            wpkh.addresses(Network::Bitcoin, 0..(1 << 31) + 1).err(),
+            Some(DerivationError::CannotDeriveHardenedChild)
+        );
+        assert!(wpkh.addresses(Network::Bitcoin, 0..1 << 31).is_ok());
+        assert_eq!(
+            wpkh.address_at(1 << 31, Network::Bitcoin),
+            Err(DerivationError::CannotDeriveHardenedChild)
+        );
+
+        let hardened = descriptor(ScriptType::Wpkh, "84h/0h/0h", "0h");
+        assert_eq!(
+            hardened.addresses(Network::Bitcoin, 0..0).err(),
+            Some(DerivationError::CannotDeriveHardenedChild)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_addresses_match_addresses() {
+        use rayon::iter::ParallelIterator as _;
+
+        let tr = descriptor(ScriptType::Tr, "86h/0h/0h", "0");
+        let parallel = tr.par_addresses(Network::Bitcoin, 0..50).unwrap().collect::<Vec<_>>();
+        let sequential = tr.addresses(Network::Bitcoin, 0..50).unwrap().collect::<Vec<_>>();
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn descriptor_string_roundtrip() {
+        for (script_type, account) in [
+            (ScriptType::Pkh, "44h/0h/0h"),
+            (ScriptType::ShWpkh, "49h/0h/0h"),
+            (ScriptType::Wpkh, "84h/0h/0h"),
+            (ScriptType::Tr, "86h/0h/0h"),
+        ] {
+            let descriptor = descriptor(script_type, account, "1");
+            let s = descriptor.to_string();
+            assert!(s.contains("/1/*)"));
+            assert_eq!(s.parse::<Descriptor>(), Ok(descriptor));
+        }
+        let no_branch = descriptor(ScriptType::Wpkh, "84h/0h/0h", "");
+        assert_eq!(no_branch.to_string().parse::<Descriptor>(), Ok(no_branch));
+
+        let s = descriptor(ScriptType::Wpkh, "84h/0h/0h", "0").to_string();
+        assert_eq!(
+            s.replace("wpkh", "wsh").parse::<Descriptor>(),
+            Err(ParseDescriptorError::UnsupportedDescriptor)
+        );
+        assert_eq!(
+            s.replace("/*", "").parse::<Descriptor>(),
+            Err(ParseDescriptorError::MissingWildcard)
+        );
+        assert!(matches!(
+            s.replace("/0/*", "/x/*").parse::<Descriptor>(),
+            Err(ParseDescriptorError::Branch(_))
+        ));
+    }
+
+    #[test]
+    fn descriptor_with_checksum() {
+        // The receive descriptor `listdescriptors` of Bitcoin Core exports for the account.
+        let exported = "wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)#afwvtk2s";
+        let wpkh = descriptor(ScriptType::Wpkh, "84h/0h/0h", "0");
+        assert_eq!(exported.parse::<Descriptor>(), Ok(wpkh.clone()));
+        // The checksum covers the hardened markers as written.
+        let apostrophes =
+            exported.replace("h/", "'/").replace("h]", "']").replace("#afwvtk2s", "#wc3n3van");
+        assert_eq!(apostrophes.parse::<Descriptor>(), Ok(wpkh));
+
+        for wrong in ["#afwvtk2q", "#afwvtk2", "#", "#afwvtk2ss"] {
+            assert_eq!(
+                exported.replace("#afwvtk2s", wrong).parse::<Descriptor>(),
+                Err(ParseDescriptorError::ChecksumMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn checksums() {
+        // Test vectors from Bitcoin Core.
+        let checksum = |s| checksum(s).map(|checksum| checksum.iter().collect::<String>());
+        assert_eq!(checksum("raw(deadbeef)").as_deref(), Some("89f8spxm"));
+        assert_eq!(
+            checksum("sh(multi(2,[00000000/111'/222]xprvA1RpRA33e1JQ7ifknakTFpgNXPmW2YvmhqLQYMmrj4xJXXWYpDPS3xz7iAxn8L39njGVyuoseXzU6rcxFLJ8HFsTjSyQbLYnMpCqE2VbFWc,xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L/0))").as_deref(),
+            Some("ggrsrxfy")
+        );
+        assert_eq!(checksum("raw(\u{e9})"), None);
+    }
+}
@@ -0,0 +1,353 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Fee estimation from the fee rates paid in recent blocks.
+//!
+//! The [`FeeEstimator`] records the fee rates of the transactions in recent blocks and derives
+//! estimates from them deterministically, without any knowledge of the mempool. This is less
+//! responsive than Bitcoin Core's `estimatesmartfee` but only needs blocks and the values of the
+//! outputs they spend.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use crate::block::{Block, Checked};
+use crate::prelude::{BTreeMap, Vec};
+use crate::transaction::{OutPoint, Transaction, TransactionExt as _, Txid};
+use crate::{Amount, BlockHeight, FeeRate, Weight};
+
+/// The fee rates paid in a block, sorted from lowest to highest, with the weight paying them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlockFeeRates {
+    rates: Vec<(FeeRate, Weight)>,
+    total_weight: Weight,
+}
+
+impl BlockFeeRates {
+    /// Returns the lowest fee rate paid by at least `percentile` percent of the block's weight,
+    /// counted from the lowest fee rate.
+    fn percentile(&self, percentile: u8) -> Option<FeeRate> {
+        let threshold = self.total_weight.to_wu() * u64::from(percentile) / 100;
+        let mut weight = 0;
+        for (rate, tx_weight) in &self.rates {
+            weight += tx_weight.to_wu();
+            if weight >= threshold {
+                return Some(*rate);
+            }
+        }
+        None
+    }
+}
+
+/// Estimates fee rates from the transactions of recent blocks.
+///
+/// For every block the estimator computes the fee rate at a given percentile of the block weight
+/// (ordered by fee rate). The estimate for a confirmation target of `n` blocks is the lowest fee
+/// rate that reached this percentile in at least one block of every run of `n` consecutive
+/// recorded blocks. A higher percentile gives more conservative estimates.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin::fee_estimation::FeeEstimator;
+/// use bitcoin::{constants, BlockHeight, Network};
+///
+/// let mut estimator = FeeEstimator::new(144);
+/// let genesis = constants::genesis_block(Network::Bitcoin);
+/// // The genesis block only contains a coinbase transaction, which has no fee rate.
+/// estimator.add_block(&genesis, BlockHeight::ZERO, |_| None).unwrap();
+/// assert_eq!(estimator.estimate(1, 50), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeEstimator {
+    blocks: BTreeMap<BlockHeight, BlockFeeRates>,
+    max_blocks: usize,
+}
+
+impl FeeEstimator {
+    /// Constructs a new estimator remembering the most recent `max_blocks` blocks.
+    pub fn new(max_blocks: usize) -> Self { Self { blocks: BTreeMap::new(), max_blocks } }
+
+    /// Returns the number of recorded blocks.
+    pub fn len(&self) -> usize { self.blocks.len() }
+
+    /// Returns `true` if no blocks are recorded.
+    pub fn is_empty(&self) -> bool { self.blocks.is_empty() }
+
+    /// Records the fee rates of the transactions in `block`, which is at `height`.
+    ///
+    /// `prevout_value` must return the value of outputs spent by the block that were created
+    /// outside of it, outputs created earlier in the block are looked up in the block itself.
+    ///
+    /// Recording a block at or below the height of already recorded blocks replaces those blocks,
+    /// as happens during a reorg. If more than the maximum number of blocks are recorded, the
+    /// oldest ones are forgotten.
+    ///
+    /// # Errors
+    ///
+    /// If `prevout_value` returns `None` for a spent output or a transaction spends less than its
+    /// outputs are worth. The estimator is left unchanged in that case.
+    pub fn add_block<F>(
+        &mut self,
+        block: &Block<Checked>,
+        height: BlockHeight,
+        mut prevout_value: F,
+    ) -> Result<(), AddBlockError>
+    where
+        F: FnMut(&OutPoint) -> Option<Amount>,
+    {
+        let transactions = block.transactions();
+        let in_block = transactions
+            .iter()
+            .map(|tx| (tx.compute_txid(), tx))
+            .collect::<BTreeMap<Txid, &Transaction>>();
+
+        let mut rates = Vec::with_capacity(transactions.len().saturating_sub(1));
+        let mut total_weight = Weight::ZERO;
+        for tx in transactions.iter().skip(1) {
+            let mut input_value = Amount::ZERO;
+            for input in &tx.inputs {
+                let out_point = &input.previous_output;
+                let value = match in_block.get(&out_point.txid) {
+                    Some(tx) => tx.outputs.get(out_point.vout as usize).map(|output| output.amount),
+                    None => prevout_value(out_point),
+                };
+                let value = value.ok_or(AddBlockError::MissingPrevout { out_point: *out_point })?;
+                input_value = input_value
+                    .checked_add(value)
+                    .ok_or(AddBlockError::InvalidFee { txid: tx.compute_txid() })?;
+            }
+            let output_value = tx
+                .outputs
+                .iter()
+                .try_fold(Amount::ZERO, |sum, output| sum.checked_add(output.amount));
+            let fee = output_value
+                .and_then(|output_value| input_value.checked_sub(output_value))
+                .ok_or(AddBlockError::InvalidFee { txid: tx.compute_txid() })?;
+
+            let weight = tx.weight();
+            let rate = fee.div_by_weight_floor(weight).expect("transactions have non-zero weight");
+            rates.push((rate, weight));
+            total_weight = total_weight.checked_add(weight).expect("block weight is bounded");
+        }
+        rates.sort_unstable();
+
+        self.blocks.split_off(&height);
+        self.blocks.insert(height, BlockFeeRates { rates, total_weight });
+        while self.blocks.len() > self.max_blocks {
+            self.blocks.pop_first();
+        }
+        Ok(())
+    }
+
+    /// Returns the estimated fee rate to confirm within `target` blocks.
+    ///
+    /// `percentile` is the share of block weight, from 0 to 100, that must pay at most the
+    /// estimated fee rate, see the [type documentation](Self). Blocks without any transactions
+    /// other than the coinbase are ignored.
+    ///
+    /// Returns `None` if fewer than `target` blocks with transactions are recorded or `target` is
+    /// zero.
+    ///
+    /// # Panics
+    ///
+    /// If `percentile` is greater than 100.
+    pub fn estimate(&self, target: usize, percentile: u8) -> Option<FeeRate> {
+        assert!(percentile <= 100, "percentile {} is greater than 100", percentile);
+        if target == 0 {
+            return None;
+        }
+        let rates = self
+            .blocks
+            .values()
+            .filter_map(|block| block.percentile(percentile))
+            .collect::<Vec<_>>();
+        rates
+            .windows(target)
+            .map(|window| window.iter().min().expect("target is not zero"))
+            .max()
+            .copied()
+    }
+
+    /// Returns the estimates for all confirmation targets from 1 to `max_target` blocks.
+    ///
+    /// The estimates never increase with the target. Targets for which there is not enough data
+    /// are omitted.
+    pub fn estimates(&self, max_target: usize, percentile: u8) -> Vec<(usize, FeeRate)> {
+        (1..=max_target)
+            .map_while(|target| self.estimate(target, percentile).map(|rate| (target, rate)))
+            .collect()
+    }
+}
+
+/// An error recording the fee rates of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AddBlockError {
+    /// The value of a spent output is unknown.
+    MissingPrevout {
+        /// The spent output.
+        out_point: OutPoint,
+    },
+    /// A transaction's outputs are worth more than its inputs.
+    InvalidFee {
+        /// The TXID of the transaction.
+        txid: Txid,
+    },
+}
+
+impl From<Infallible> for AddBlockError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for AddBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingPrevout { out_point } =>
+                write!(f, "value of spent output {} is unknown", out_point),
+            Self::InvalidFee { txid } =>
+                write!(f, "outputs of transaction {} are worth more than its inputs", txid),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AddBlockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MissingPrevout { .. } | Self::InvalidFee { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::absolute;
+    use crate::script::ScriptPubKeyBuf;
+    use crate::test_fixtures::{self, confirmed};
+    use crate::transaction::{self, TxIn, TxOut};
+
+    const INPUT_VALUE: Amount = Amount::from_sat_u32(1_000_000);
+
+    fn tx(previous_output: OutPoint, fee: u32) -> Transaction {
+        Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn { previous_output, ..TxIn::EMPTY_COINBASE }],
+            outputs: vec![TxOut {
+                amount: (INPUT_VALUE - Amount::from_sat_u32(fee)).unwrap(),
+                script_pubkey: ScriptPubKeyBuf::new(),
+            }],
+        }
+    }
+
+    /// Builds a block with transactions paying the given fees.
+    fn block(fees: &[u32]) -> Block<Checked> {
+        let coinbase = tx(OutPoint::COINBASE_PREVOUT, 0);
+        let mut transactions = vec![coinbase];
+        transactions.extend(fees.iter().zip(1..).map(|(&fee, i)| tx(confirmed(i), fee)));
+        test_fixtures::block(transactions)
+    }
+
+    fn rate(fee: u32) -> FeeRate {
+        let weight = tx(confirmed(0), 0).weight();
+        Amount::from_sat_u32(fee).div_by_weight_floor(weight).unwrap()
+    }
+
+    fn estimator(blocks: &[&[u32]]) -> FeeEstimator {
+        let mut estimator = FeeEstimator::new(10);
+        for (height, fees) in (0..).zip(blocks) {
+            estimator
+                .add_block(&block(fees), BlockHeight::from_u32(height), |_| Some(INPUT_VALUE))
+                .unwrap();
+        }
+        estimator
+    }
+
+    #[test]
+    fn percentiles() {
+        let estimator = estimator(&[&[100, 400, 200, 300]]);
+        assert_eq!(estimator.estimate(1, 0), Some(rate(100)));
+        assert_eq!(estimator.estimate(1, 25), Some(rate(100)));
+        assert_eq!(estimator.estimate(1, 26), Some(rate(200)));
+        assert_eq!(estimator.estimate(1, 50), Some(rate(200)));
+        assert_eq!(estimator.estimate(1, 100), Some(rate(400)));
+        assert_eq!(estimator.estimate(2, 50), None);
+        assert_eq!(estimator.estimate(0, 50), None);
+    }
+
+    #[test]
+    fn targets() {
+        // The median fee rates of the blocks are 500, 100, 300 and 200.
+        let estimator = estimator(&[&[500], &[100], &[], &[300], &[200]]);
+        assert_eq!(estimator.len(), 5);
+        assert_eq!(estimator.estimate(1, 50), Some(rate(500)));
+        // Every pair of consecutive blocks contains a block with a median of at most 200.
+        assert_eq!(estimator.estimate(2, 50), Some(rate(200)));
+        assert_eq!(estimator.estimate(3, 50), Some(rate(100)));
+        assert_eq!(estimator.estimate(4, 50), Some(rate(100)));
+        assert_eq!(estimator.estimate(5, 50), None);
+        assert_eq!(
+            estimator.estimates(6, 50),
+            [(1, rate(500)), (2, rate(200)), (3, rate(100)), (4, rate(100))]
+        );
+    }
+
+    #[test]
+    fn reorg_and_capacity() {
+        let mut estimator = estimator(&[&[500], &[100], &[300]]);
+        estimator
+            .add_block(&block(&[50]), BlockHeight::from_u32(1), |_| Some(INPUT_VALUE))
+            .unwrap();
+        assert_eq!(estimator.len(), 2);
+        assert_eq!(estimator.estimate(1, 50), Some(rate(500)));
+        assert_eq!(estimator.estimate(2, 50), Some(rate(50)));
+
+        let mut estimator = FeeEstimator::new(2);
+        for height in 0..5 {
+            estimator
+                .add_block(&block(&[height * 100 + 100]), BlockHeight::from_u32(height), |_| {
+                    Some(INPUT_VALUE)
+                })
+                .unwrap();
+        }
+        assert_eq!(estimator.len(), 2);
+        assert_eq!(estimator.estimate(2, 50), Some(rate(400)));
+    }
+
+    #[test]
+    fn in_block_prevouts_and_errors() {
+        let parent = tx(confirmed(1), 100);
+        let child = Transaction {
+            outputs: vec![TxOut {
+                amount: Amount::from_sat_u32(1),
+                script_pubkey: ScriptPubKeyBuf::new(),
+            }],
+            ..tx(OutPoint { txid: parent.compute_txid(), vout: 0 }, 0)
+        };
+        let mut block_txs = block(&[100]).transactions().to_vec();
+        block_txs.push(child.clone());
+        let header = *block(&[]).header();
+        let with_child = Block::new_unchecked(header, block_txs).assume_checked(None);
+
+        let mut estimator = FeeEstimator::new(10);
+        estimator.add_block(&with_child, BlockHeight::ZERO, |_| Some(INPUT_VALUE)).unwrap();
+        let child_fee = (parent.outputs[0].amount - Amount::from_sat_u32(1)).unwrap();
+        assert_eq!(
+            estimator.estimate(1, 100),
+            Some(child_fee.div_by_weight_floor(child.weight()).unwrap())
+        );
+
+        assert_eq!(
+            estimator.add_block(&block(&[100]), BlockHeight::ZERO, |_| None),
+            Err(AddBlockError::MissingPrevout { out_point: confirmed(1) })
+        );
+        let overpaying = block(&[100]);
+        let txid = overpaying.transactions()[1].compute_txid();
+        assert_eq!(
+            estimator.add_block(&overpaying, BlockHeight::ZERO, |_| Some(Amount::ONE_SAT)),
+            Err(AddBlockError::InvalidFee { txid })
+        );
+        assert_eq!(estimator.len(), 1);
+    }
+}
@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Validation of a chain of block headers.
+//!
+//! A [`HeaderChain`] connects headers received from peers to a chain starting at the genesis
+//! block, checking that each header links to its parent, commits to the required target and
+//! meets it.
+//!
+//! Light clients usually ship with trust points to sync faster and to avoid being fed a long
+//! chain of low difficulty headers: checkpoints, the known hashes of blocks at some heights, and an
+//! assumed-valid block hash. The proof of work of headers at or below a trust point connected in
+//! the same batch is not checked, their linkage always is.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::header_chain::HeaderChain;
+//! use bitcoin::{constants, BlockHeight, Network};
+//!
+//! let genesis = constants::genesis_block(Network::Bitcoin).block_hash();
+//! let chain = HeaderChain::new(Network::Bitcoin).with_checkpoints([(BlockHeight::ZERO, genesis)]);
+//! assert_eq!(chain.tip_height(), BlockHeight::ZERO);
+//! assert_eq!(chain.tip_hash(), genesis);
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use internals::write_err;
+
+use crate::block::{BlockHash, Header, HeaderExt as _, ValidationError};
+use crate::network::Params;
+use crate::pow::{self, Target};
+use crate::prelude::{BTreeMap, Vec};
+use crate::{constants, BlockHeight};
+
+/// A chain of validated block headers, starting at the genesis block.
+#[derive(Debug, Clone)]
+pub struct HeaderChain {
+    params: Params,
+    /// The headers, indexed by height.
+    headers: Vec<Header>,
+    tip_hash: BlockHash,
+    checkpoints: BTreeMap<BlockHeight, BlockHash>,
+    assume_valid: Option<BlockHash>,
+}
+
+impl HeaderChain {
+    /// Constructs a new chain containing only the genesis block of the network with `params`.
+    pub fn new(params: impl AsRef<Params>) -> Self {
+        let params = params.as_ref();
+        let genesis = constants::genesis_block(params);
+        Self {
+            params: params.clone(),
+            headers: vec![*genesis.header()],
+            tip_hash: genesis.block_hash(),
+            checkpoints: BTreeMap::new(),
+            assume_valid: None,
+        }
+    }
+
+    /// Adds `checkpoints`, the hashes of blocks at the given heights.
+    ///
+    /// Headers at a checkpoint height must have the checkpointed hash. The proof of work of a
+    /// checkpointed block and of its ancestors connected in the same call to
+    /// [`connect`](Self::connect) is not checked.
+    pub fn with_checkpoints(
+        mut self,
+        checkpoints: impl IntoIterator<Item = (BlockHeight, BlockHash)>,
+    ) -> Self {
+        self.checkpoints.extend(checkpoints);
+        self
+    }
+
+    /// Sets the hash of a block assumed to be valid.
+    ///
+    /// The proof of work of the block and of its ancestors connected in the same call to
+    /// [`connect`](Self::connect) is not checked. Unlike checkpoints a chain without this block is
+    /// not rejected, its headers are checked as usual.
+    pub fn with_assume_valid(mut self, block_hash: BlockHash) -> Self {
+        self.assume_valid = Some(block_hash);
+        self
+    }
+
+    /// Returns the checkpoints, ordered by height.
+    pub fn checkpoints(&self) -> &BTreeMap<BlockHeight, BlockHash> { &self.checkpoints }
+
+    /// Returns the hash of the block assumed to be valid.
+    pub fn assume_valid(&self) -> Option<BlockHash> { self.assume_valid }
+
+    /// Returns the height of the highest checkpoint.
+    pub fn checkpoint_height(&self) -> Option<BlockHeight> {
+        self.checkpoints.keys().next_back().copied()
+    }
+
+    /// Returns the height of the last header.
+    pub fn tip_height(&self) -> BlockHeight {
+        // Cast ok, there are far fewer than `u32::MAX` blocks.
+        BlockHeight::from_u32((self.headers.len() - 1) as u32)
+    }
+
+    /// Returns the hash of the last header.
+    pub fn tip_hash(&self) -> BlockHash { self.tip_hash }
+
+    /// Returns the header at `height`.
+    pub fn header(&self, height: BlockHeight) -> Option<&Header> {
+        self.headers.get(usize::try_from(height.to_u32()).ok()?)
+    }
+
+    /// Returns the headers, indexed by height.
+    pub fn headers(&self) -> &[Header] { &self.headers }
+
+    /// Connects `headers`, the descendants of the tip in order, to the chain.
+    ///
+    /// Either all headers are connected or, on error, none are.
+    ///
+    /// # Errors
+    ///
+    /// If a header does not link to its parent, does not match the checkpoint at its height or
+    /// does not meet the required target while not covered by a trust point.
+    pub fn connect(&mut self, headers: &[Header]) -> Result<(), ConnectHeaderError> {
+        let (len, tip_hash) = (self.headers.len(), self.tip_hash);
+        let result = self.connect_all(headers);
+        if result.is_err() {
+            self.headers.truncate(len);
+            self.tip_hash = tip_hash;
+        }
+        result
+    }
+
+    /// Connects `headers`, leaving the ones before an invalid header connected.
+    fn connect_all(&mut self, headers: &[Header]) -> Result<(), ConnectHeaderError> {
+        let hashes = headers.iter().map(|header| header.block_hash()).collect::<Vec<_>>();
+        let heights = (self.tip_height().to_u32() + 1..=u32::MAX).map(BlockHeight::from_u32);
+        // Headers up to a checkpointed or the assumed-valid block are not checked if that block
+        // is part of this batch.
+        let trusted_end = hashes
+            .iter()
+            .zip(heights)
+            .enumerate()
+            .filter(|&(_, (hash, height))| {
+                Some(*hash) == self.assume_valid || self.checkpoints.get(&height) == Some(hash)
+            })
+            .map(|(i, _)| i)
+            .last();
+
+        for (i, (header, &block_hash)) in headers.iter().zip(&hashes).enumerate() {
+            let height = BlockHeight::from_u32(self.tip_height().to_u32() + 1);
+            if header.prev_blockhash != self.tip_hash {
+                return Err(ConnectHeaderError::NotConnected { height });
+            }
+            if !trusted_end.is_some_and(|end| i <= end) {
+                self.validate_pow(header, height)?;
+            }
+            if let Some(&expected) = self.checkpoints.get(&height) {
+                if block_hash != expected {
+                    return Err(ConnectHeaderError::CheckpointMismatch {
+                        height,
+                        expected,
+                        got: block_hash,
+                    });
+                }
+            }
+            self.headers.push(*header);
+            self.tip_hash = block_hash;
+        }
+        Ok(())
+    }
+
+    /// Checks that `header`, to be connected at `height`, meets the required target.
+    fn validate_pow(&self, header: &Header, height: BlockHeight) -> Result<(), ConnectHeaderError> {
+        let tip = *self.headers.last().expect("the genesis header is always present");
+        let required = pow::next_target_after(
+            tip,
+            self.tip_height(),
+            &self.params,
+            Some(header.time.to_u32()),
+            |height| -> Result<Header, Infallible> {
+                Ok(*self.header(height).expect("ancestors of the tip are connected"))
+            },
+        )
+        .unwrap_or_else(|never| match never {});
+
+        header
+            .validate_pow(Target::from_compact(required))
+            .map(|_| ())
+            .map_err(|error| ConnectHeaderError::Validation { height, error })
+    }
+}
+
+/// Error connecting a header to a [`HeaderChain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectHeaderError {
+    /// The header does not link to the tip of the chain.
+    NotConnected {
+        /// The height the header would have been connected at.
+        height: BlockHeight,
+    },
+    /// The hash of the header does not match the checkpoint at its height.
+    CheckpointMismatch {
+        /// The height of the header.
+        height: BlockHeight,
+        /// The checkpointed block hash.
+        expected: BlockHash,
+        /// The hash of the header.
+        got: BlockHash,
+    },
+    /// The header does not have the required target or does not meet it.
+    Validation {
+        /// The height of the header.
+        height: BlockHeight,
+        /// The validation error.
+        error: ValidationError,
+    },
+}
+
+impl From<Infallible> for ConnectHeaderError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ConnectHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::NotConnected { height } =>
+                write!(f, "header at height {} does not link to the chain tip", height),
+            Self::CheckpointMismatch { height, expected, got } => write!(
+                f,
+                "header at height {} has hash {} but the checkpoint is {}",
+                height, got, expected
+            ),
+            Self::Validation { height, ref error } =>
+                write_err!(f, "invalid header at height {}", height; error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConnectHeaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::NotConnected { .. } | Self::CheckpointMismatch { .. } => None,
+            Self::Validation { ref error, .. } => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Network, TxMerkleNode};
+
+    /// Returns a regtest header on top of `prev` that meets its target if `valid` is `true`.
+    fn mine(prev: &Header, valid: bool) -> Header {
+        let mut header = Header {
+            prev_blockhash: prev.block_hash(),
+            merkle_root: TxMerkleNode::from_byte_array([2; 32]),
+            time: crate::BlockTime::from_u32(prev.time.to_u32() + 600),
+            ..*prev
+        };
+        while header.target().is_met_by(header.block_hash()) != valid {
+            header.nonce += 1;
+        }
+        header
+    }
+
+    fn headers(count: usize, invalid: usize) -> Vec<Header> {
+        let mut prev = *constants::genesis_block(Network::Regtest).header();
+        (0..count)
+            .map(|i| {
+                prev = mine(&prev, i != invalid);
+                prev
+            })
+            .collect()
+    }
+
+    #[test]
+    fn connect_validates() {
+        let headers = headers(4, 2);
+        let mut chain = HeaderChain::new(Network::Regtest);
+        assert_eq!(
+            chain.connect(&headers),
+            Err(ConnectHeaderError::Validation {
+                height: BlockHeight::from_u32(3),
+                error: ValidationError::BadProofOfWork,
+            })
+        );
+        // Nothing is connected on error.
+        assert_eq!(chain.tip_height(), BlockHeight::ZERO);
+
+        chain.connect(&headers[..2]).unwrap();
+        assert_eq!(
+            chain.connect(&headers[3..]),
+            Err(ConnectHeaderError::NotConnected { height: BlockHeight::from_u32(3) })
+        );
+        assert_eq!(chain.tip_hash(), headers[1].block_hash());
+    }
+
+    #[test]
+    fn checkpoints() {
+        let headers = headers(4, 2);
+        let checkpoint = (BlockHeight::from_u32(3), headers[2].block_hash());
+        let mut chain = HeaderChain::new(Network::Regtest).with_checkpoints([checkpoint]);
+        chain.connect(&headers).unwrap();
+        assert_eq!(chain.tip_height(), BlockHeight::from_u32(4));
+        assert_eq!(chain.header(BlockHeight::from_u32(4)), Some(&headers[3]));
+
+        let wrong = (BlockHeight::from_u32(2), headers[2].block_hash());
+        let mut chain = HeaderChain::new(Network::Regtest).with_checkpoints([checkpoint, wrong]);
+        assert_eq!(
+            chain.connect(&headers),
+            Err(ConnectHeaderError::CheckpointMismatch {
+                height: BlockHeight::from_u32(2),
+                expected: headers[2].block_hash(),
+                got: headers[1].block_hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn checkpoint_not_in_batch() {
+        // A low work header below the checkpoint is rejected unless the checkpoint is connected
+        // in the same batch.
+        let headers = headers(4, 1);
+        let checkpoint = (BlockHeight::from_u32(4), headers[3].block_hash());
+        let mut chain = HeaderChain::new(Network::Regtest).with_checkpoints([checkpoint]);
+        assert_eq!(
+            chain.connect(&headers[..3]),
+            Err(ConnectHeaderError::Validation {
+                height: BlockHeight::from_u32(2),
+                error: ValidationError::BadProofOfWork,
+            })
+        );
+        assert_eq!(chain.tip_height(), BlockHeight::ZERO);
+
+        chain.connect(&headers).unwrap();
+        assert_eq!(chain.tip_hash(), headers[3].block_hash());
+    }
+
+    #[test]
+    fn assume_valid() {
+        let headers = headers(4, 1);
+        let mut chain =
+            HeaderChain::new(Network::Regtest).with_assume_valid(headers[2].block_hash());
+        chain.connect(&headers).unwrap();
+
+        // Not trusted without the assumed-valid block in the same batch.
+        let mut chain =
+            HeaderChain::new(Network::Regtest).with_assume_valid(headers[2].block_hash());
+        assert!(matches!(chain.connect(&headers[..2]), Err(ConnectHeaderError::Validation { .. })));
+    }
+}
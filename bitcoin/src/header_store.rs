@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Compact storage of block headers.
+//!
+//! SPV clients keep every header of the chain, more than 850,000 at the time of writing. Storing
+//! them back to back as 80-byte records in a flat file keeps the storage compact and lets a header
+//! be found by its position alone. [`HeaderSlice`] is a view over such a region of memory, for
+//! example a memory mapped file, that looks headers up by height or by time without decoding the
+//! whole region.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::header_store::{encode_headers, HeaderSlice};
+//! use bitcoin::{constants, BlockHeight, Network};
+//!
+//! let genesis = *constants::genesis_block(Network::Bitcoin).header();
+//! let stored = encode_headers(&[genesis]);
+//! assert_eq!(stored.len(), 80);
+//!
+//! let headers = HeaderSlice::new(&stored, BlockHeight::ZERO).expect("whole headers");
+//! assert_eq!(headers.header(BlockHeight::ZERO), Some(genesis));
+//! assert_eq!(headers.tip_height(), Some(BlockHeight::ZERO));
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use hashes::{sha256d, HashEngine as _};
+
+use crate::block::{BlockHash, Header};
+use crate::consensus::encode::{deserialize, serialize};
+use crate::prelude::Vec;
+use crate::{BlockHeight, BlockMtp, BlockTime};
+
+/// The number of headers the median time past of a block is calculated over.
+const MEDIAN_TIME_SPAN: usize = 11;
+
+/// A block header in its consensus encoding.
+///
+/// All fields can be read without decoding the header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PackedHeader([u8; Header::SIZE]);
+
+impl PackedHeader {
+    /// Constructs a new `PackedHeader` by encoding `header`.
+    pub fn from_header(header: &Header) -> Self {
+        Self(serialize(header).try_into().expect("headers are always 80 bytes"))
+    }
+
+    /// Constructs a new `PackedHeader` from the consensus encoding of a header.
+    ///
+    /// Every 80 byte array is the encoding of some header.
+    pub const fn from_byte_array(bytes: [u8; Header::SIZE]) -> Self { Self(bytes) }
+
+    /// Returns the consensus encoding of the header.
+    pub const fn to_byte_array(self) -> [u8; Header::SIZE] { self.0 }
+
+    /// Returns a reference to the consensus encoding of the header.
+    pub const fn as_byte_array(&self) -> &[u8; Header::SIZE] { &self.0 }
+
+    /// Decodes the header.
+    pub fn to_header(self) -> Header {
+        deserialize(&self.0).expect("every 80 byte array is a valid header")
+    }
+
+    /// Returns the hash of the previous block.
+    pub fn prev_blockhash(&self) -> BlockHash {
+        BlockHash::from_byte_array(self.0[4..36].try_into().expect("32 bytes"))
+    }
+
+    /// Returns the timestamp of the block.
+    pub fn time(&self) -> BlockTime {
+        BlockTime::from_u32(u32::from_le_bytes(self.0[68..72].try_into().expect("4 bytes")))
+    }
+
+    /// Returns the hash of the block.
+    pub fn block_hash(&self) -> BlockHash {
+        let mut engine = sha256d::Hash::engine();
+        engine.input(&self.0);
+        BlockHash::from_byte_array(sha256d::Hash::from_engine(engine).to_byte_array())
+    }
+}
+
+impl From<Header> for PackedHeader {
+    fn from(header: Header) -> Self { Self::from_header(&header) }
+}
+
+impl From<PackedHeader> for Header {
+    fn from(packed: PackedHeader) -> Self { packed.to_header() }
+}
+
+/// Encodes `headers` back to back, 80 bytes each.
+pub fn encode_headers(headers: &[Header]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(headers.len() * Header::SIZE);
+    for header in headers {
+        bytes.extend_from_slice(PackedHeader::from_header(header).as_byte_array());
+    }
+    bytes
+}
+
+/// Decodes headers stored back to back, 80 bytes each.
+///
+/// # Errors
+///
+/// If the length of `bytes` is not a multiple of 80.
+pub fn decode_headers(bytes: &[u8]) -> Result<Vec<Header>, InvalidLengthError> {
+    Ok(HeaderSlice::new(bytes, BlockHeight::ZERO)?.iter().map(PackedHeader::to_header).collect())
+}
+
+/// A view over headers of consecutive blocks stored back to back, 80 bytes each.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HeaderSlice<'a> {
+    bytes: &'a [u8],
+    start_height: BlockHeight,
+}
+
+impl<'a> HeaderSlice<'a> {
+    /// Constructs a view over the headers in `bytes`, the first one of which is the header of the
+    /// block at `start_height`.
+    ///
+    /// The headers are not checked to form a chain.
+    ///
+    /// # Errors
+    ///
+    /// If the length of `bytes` is not a multiple of 80.
+    pub fn new(bytes: &'a [u8], start_height: BlockHeight) -> Result<Self, InvalidLengthError> {
+        if bytes.len() % Header::SIZE != 0 {
+            return Err(InvalidLengthError { len: bytes.len() });
+        }
+        Ok(Self { bytes, start_height })
+    }
+
+    /// Returns the number of headers.
+    pub fn len(&self) -> usize { self.bytes.len() / Header::SIZE }
+
+    /// Returns `true` if there are no headers.
+    pub fn is_empty(&self) -> bool { self.bytes.is_empty() }
+
+    /// Returns the height of the first header.
+    pub fn start_height(&self) -> BlockHeight { self.start_height }
+
+    /// Returns the height of the last header, `None` if there are no headers.
+    pub fn tip_height(&self) -> Option<BlockHeight> {
+        let offset = u32::try_from(self.len().checked_sub(1)?).ok()?;
+        self.start_height.to_u32().checked_add(offset).map(BlockHeight::from_u32)
+    }
+
+    /// Returns the header at `index`, counted from the first header.
+    fn at(&self, index: usize) -> PackedHeader {
+        let start = index * Header::SIZE;
+        let bytes = self.bytes[start..start + Header::SIZE].try_into().expect("80 bytes");
+        PackedHeader(bytes)
+    }
+
+    /// Returns the index of the header at `height`, if it is in the slice.
+    fn index(&self, height: BlockHeight) -> Option<usize> {
+        let offset = height.to_u32().checked_sub(self.start_height.to_u32())?;
+        let index = usize::try_from(offset).ok()?;
+        (index < self.len()).then_some(index)
+    }
+
+    /// Returns the height of the header at `index`, `None` if it is above `u32::MAX`.
+    fn height(&self, index: usize) -> Option<BlockHeight> {
+        let offset = u32::try_from(index).ok()?;
+        self.start_height.to_u32().checked_add(offset).map(BlockHeight::from_u32)
+    }
+
+    /// Returns the encoded header of the block at `height`.
+    pub fn get(&self, height: BlockHeight) -> Option<PackedHeader> {
+        self.index(height).map(|index| self.at(index))
+    }
+
+    /// Returns the header of the block at `height`.
+    pub fn header(&self, height: BlockHeight) -> Option<Header> {
+        self.get(height).map(PackedHeader::to_header)
+    }
+
+    /// Returns an iterator over the encoded headers, in order of height.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = PackedHeader> + 'a {
+        self.bytes
+            .chunks_exact(Header::SIZE)
+            .map(|bytes| PackedHeader(bytes.try_into().expect("80 bytes")))
+    }
+
+    /// Returns the height of the block with hash `block_hash`.
+    ///
+    /// Every header is hashed until a match is found, callers looking up many blocks should keep
+    /// an index instead.
+    pub fn height_of(&self, block_hash: BlockHash) -> Option<BlockHeight> {
+        self.iter()
+            .position(|header| header.block_hash() == block_hash)
+            .and_then(|i| self.height(i))
+    }
+
+    /// Returns the median time past of the block at `height`.
+    ///
+    /// This is the median of the timestamps of the block and the ten blocks before it, `None` if
+    /// any of these is not in the slice.
+    pub fn median_time_past(&self, height: BlockHeight) -> Option<BlockMtp> {
+        let index = self.index(height)?;
+        self.mtp_at(index)
+    }
+
+    fn mtp_at(&self, index: usize) -> Option<BlockMtp> {
+        let first = (index + 1).checked_sub(MEDIAN_TIME_SPAN)?;
+        let mut timestamps = [BlockTime::from_u32(0); MEDIAN_TIME_SPAN];
+        for (i, timestamp) in timestamps.iter_mut().enumerate() {
+            *timestamp = self.at(first + i).time();
+        }
+        Some(BlockMtp::new(timestamps))
+    }
+
+    /// Returns the height of the first block from which on all blocks are younger than `time`.
+    ///
+    /// Block timestamps are not monotonic but the median time past is, and every block is younger
+    /// than the median time past of its parent. The search therefore returns the block after the
+    /// first one with a median time past of at least `time`. This is the height a wallet created at
+    /// `time` can start scanning from, a few blocks before it may still carry a later timestamp.
+    ///
+    /// The first ten headers have no median time past and are never returned. Returns `None` if
+    /// no header in the slice is known to be younger than `time`.
+    pub fn first_height_after(&self, time: BlockTime) -> Option<BlockHeight> {
+        let time = time.to_u32();
+        let len = self.len();
+        let first = MEDIAN_TIME_SPAN - 1;
+        if len <= first {
+            return None;
+        }
+        // Binary search for the first index with a median time past of at least `time`.
+        let (mut low, mut high) = (first, len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let mtp = self.mtp_at(mid).expect("enough headers before index");
+            if mtp.to_u32() < time {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low + 1 < len {
+            self.height(low + 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The length of stored headers is not a multiple of 80 bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InvalidLengthError {
+    len: usize,
+}
+
+impl InvalidLengthError {
+    /// Returns the invalid length.
+    pub fn invalid_length(&self) -> usize { self.len }
+}
+
+impl From<Infallible> for InvalidLengthError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for InvalidLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "stored headers length {} is not a multiple of {}", self.len, Header::SIZE)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidLengthError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pow::CompactTarget;
+    use crate::{block, TxMerkleNode};
+
+    fn chain(times: &[u32]) -> Vec<Header> {
+        let mut prev_blockhash = BlockHash::from_byte_array([0; 32]);
+        times
+            .iter()
+            .map(|&time| {
+                let header = Header {
+                    version: block::Version::TWO,
+                    prev_blockhash,
+                    merkle_root: TxMerkleNode::from_byte_array([1; 32]),
+                    time: BlockTime::from_u32(time),
+                    bits: CompactTarget::from_consensus(0x207f_ffff),
+                    nonce: time,
+                };
+                prev_blockhash = header.block_hash();
+                header
+            })
+            .collect()
+    }
+
+    #[test]
+    fn packed_header() {
+        let header = chain(&[1_700_000_000])[0];
+        let packed = PackedHeader::from(header);
+        assert_eq!(packed.time(), header.time);
+        assert_eq!(packed.prev_blockhash(), header.prev_blockhash);
+        assert_eq!(packed.block_hash(), header.block_hash());
+        assert_eq!(Header::from(packed), header);
+    }
+
+    #[test]
+    fn slice_roundtrip() {
+        let headers = chain(&[10, 20, 30]);
+        let bytes = encode_headers(&headers);
+        assert_eq!(decode_headers(&bytes).unwrap(), headers);
+        assert_eq!(decode_headers(&bytes[1..]), Err(InvalidLengthError { len: 239 }));
+
+        let slice = HeaderSlice::new(&bytes, BlockHeight::from_u32(100)).unwrap();
+        assert_eq!(slice.len(), 3);
+        assert_eq!(slice.tip_height(), Some(BlockHeight::from_u32(102)));
+        assert_eq!(slice.header(BlockHeight::from_u32(101)), Some(headers[1]));
+        assert_eq!(slice.get(BlockHeight::from_u32(99)), None);
+        assert_eq!(slice.get(BlockHeight::from_u32(103)), None);
+        assert_eq!(slice.height_of(headers[2].block_hash()), Some(BlockHeight::from_u32(102)));
+        assert!(HeaderSlice::new(&[], BlockHeight::ZERO).unwrap().tip_height().is_none());
+
+        // Heights above `u32::MAX` do not exist.
+        let slice = HeaderSlice::new(&bytes, BlockHeight::MAX).unwrap();
+        assert_eq!(slice.tip_height(), None);
+        assert_eq!(slice.height_of(headers[0].block_hash()), Some(BlockHeight::MAX));
+        assert_eq!(slice.height_of(headers[1].block_hash()), None);
+    }
+
+    #[test]
+    fn search_by_time() {
+        // Timestamps rise by ten but every third block is older than its parent.
+        let times =
+            (0..100).map(|i| 1000 + i * 10 - if i % 3 == 0 { 15 } else { 0 }).collect::<Vec<_>>();
+        let bytes = encode_headers(&chain(&times));
+        let slice = HeaderSlice::new(&bytes, BlockHeight::from_u32(500)).unwrap();
+
+        let time = BlockTime::from_u32(1500);
+        let height = slice.first_height_after(time).unwrap();
+        for header in slice.iter().skip((height.to_u32() - 500) as usize) {
+            assert!(header.time() > time);
+        }
+        let parent = BlockHeight::from_u32(height.to_u32() - 1);
+        assert!(slice.median_time_past(parent).unwrap().to_u32() >= time.to_u32());
+
+        assert_eq!(
+            slice.first_height_after(BlockTime::from_u32(0)),
+            Some(BlockHeight::from_u32(511))
+        );
+        assert_eq!(slice.first_height_after(BlockTime::from_u32(10_000)), None);
+        assert_eq!(slice.median_time_past(BlockHeight::from_u32(509)), None);
+    }
+}
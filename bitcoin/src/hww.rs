@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Hardware wallet key helpers.
+//!
+//! Hardware wallets (and other external signers) export an extended public key together with its
+//! origin, the fingerprint of the master key and the derivation path from it. A [`DeviceKey`]
+//! bundles these, produces the key origin entries of PSBT inputs and outputs and tells whether a
+//! registered device can sign a PSBT input.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::bip32::ChildNumber;
+//! use bitcoin::hww::DeviceKey;
+//!
+//! let key = "[d34db33f/84h/0h/0h]xpub6DJ2dNUysrn5Vt36jH2KLBT2i1auw1tTSSomg8PhqNiUtx8QX2SvC9nrHu81fT41fvDUnhMjEzQgXnQjKEu3oaqMSzhSrHMxyyoEAmUHQbY"
+//!     .parse::<DeviceKey>()
+//!     .unwrap();
+//! let (public_key, key_source) = key.bip32_derivation([ChildNumber::ZERO_NORMAL; 2]).unwrap();
+//! assert_eq!(key_source.1.to_string(), "84'/0'/0'/0/0");
+//! # let _ = public_key;
+//! ```
+
+use core::fmt;
+use core::str::FromStr;
+
+use internals::write_err;
+
+use crate::bip32::{
+    ChildNumber, DerivationError, DerivationPath, Fingerprint, KeySource, ParseChildNumberError,
+    ParseError, Xpriv, Xpub,
+};
+use crate::crypto::key::XOnlyPublicKey;
+use crate::prelude::Vec;
+use crate::psbt::Input;
+use crate::taproot::TapLeafHash;
+
+/// An extended public key exported by a signing device, together with its origin.
+///
+/// The string representation is the key expression of output script descriptors,
+/// `[<fingerprint>/<path>]<xpub>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceKey {
+    /// The fingerprint of the master key of the device.
+    master_fingerprint: Fingerprint,
+    /// The path from the master key to `xpub`.
+    path: DerivationPath,
+    /// The exported extended public key.
+    xpub: Xpub,
+}
+
+impl DeviceKey {
+    /// Constructs a new `DeviceKey` for `xpub`, derived from the master key with
+    /// `master_fingerprint` along `path`.
+    ///
+    /// The origin is not checked, it usually comes from the same device as the key.
+    pub fn new(master_fingerprint: Fingerprint, path: DerivationPath, xpub: Xpub) -> Self {
+        Self { master_fingerprint, path, xpub }
+    }
+
+    /// Constructs a new `DeviceKey` by deriving `path` from the `master` key.
+    ///
+    /// This is mostly useful for software signers and testing.
+    pub fn from_master(master: &Xpriv, path: DerivationPath) -> Result<Self, DerivationError> {
+        let xpub = master.derive_xpriv(&path)?.to_xpub();
+        Ok(Self { master_fingerprint: master.fingerprint(), path, xpub })
+    }
+
+    /// Returns the fingerprint of the master key of the device.
+    pub fn master_fingerprint(&self) -> Fingerprint { self.master_fingerprint }
+
+    /// Returns the path from the master key to the extended public key.
+    pub fn path(&self) -> &DerivationPath { &self.path }
+
+    /// Returns the extended public key.
+    pub fn xpub(&self) -> &Xpub { &self.xpub }
+
+    /// Returns the origin of the extended public key.
+    pub fn key_source(&self) -> KeySource { (self.master_fingerprint, self.path.clone()) }
+
+    /// Derives the public key at the non-hardened `child` path below the extended public key.
+    ///
+    /// Returns the extended public key and its origin.
+    pub fn derive<P: AsRef<[ChildNumber]>>(
+        &self,
+        child: P,
+    ) -> Result<(Xpub, KeySource), DerivationError> {
+        let xpub = self.xpub.derive_xpub(&child)?;
+        Ok((xpub, (self.master_fingerprint, self.path.extend(child))))
+    }
+
+    /// Derives the entry of the `bip32_derivation` map of a PSBT input or output for the key at
+    /// the non-hardened `child` path.
+    pub fn bip32_derivation<P: AsRef<[ChildNumber]>>(
+        &self,
+        child: P,
+    ) -> Result<(secp256k1::PublicKey, KeySource), DerivationError> {
+        let (xpub, key_source) = self.derive(child)?;
+        Ok((xpub.public_key, key_source))
+    }
+
+    /// Derives the entry of the `tap_key_origins` map of a PSBT input or output for the key at
+    /// the non-hardened `child` path.
+    ///
+    /// `leaf_hashes` are the hashes of the leaves the key is used in, empty for the internal key.
+    pub fn tap_key_origin<P: AsRef<[ChildNumber]>>(
+        &self,
+        child: P,
+        leaf_hashes: Vec<TapLeafHash>,
+    ) -> Result<(XOnlyPublicKey, (Vec<TapLeafHash>, KeySource)), DerivationError> {
+        let (xpub, key_source) = self.derive(child)?;
+        Ok((xpub.to_x_only_public_key(), (leaf_hashes, key_source)))
+    }
+
+    /// Returns the path below the extended public key if `key_source` is derived from it.
+    ///
+    /// Only the fingerprint and path are compared, the key itself is not derived.
+    pub fn child_path(&self, key_source: &KeySource) -> Option<DerivationPath> {
+        let (fingerprint, path) = key_source;
+        if *fingerprint != self.master_fingerprint {
+            return None;
+        }
+        let path = path.as_ref();
+        path.strip_prefix(self.path.as_ref()).map(DerivationPath::from)
+    }
+
+    /// Returns `true` if the key derived from this key at the origin `key_source` `matches`.
+    fn owns(&self, key_source: &KeySource, matches: impl FnOnce(&Xpub) -> bool) -> bool {
+        self.child_path(key_source)
+            .and_then(|child| self.xpub.derive_xpub(child).ok())
+            .is_some_and(|xpub| matches(&xpub))
+    }
+
+    /// Returns `true` if the device can sign `input`.
+    ///
+    /// An input can be signed if one of its `bip32_derivation` or `tap_key_origins` entries is
+    /// derived from this key. The keys are derived and compared, so entries with a colliding
+    /// fingerprint or a path the signer would not derive the listed key at do not match.
+    pub fn can_sign(&self, input: &Input) -> bool {
+        input.bip32_derivation.iter().any(|(public_key, key_source)| {
+            self.owns(key_source, |xpub| xpub.public_key == *public_key)
+        }) || input.tap_key_origins.iter().any(|(x_only, (_, key_source))| {
+            self.owns(key_source, |xpub| xpub.to_x_only_public_key() == *x_only)
+        })
+    }
+}
+
+/// Returns the devices from `devices` that can sign `input`.
+///
+/// See [`DeviceKey::can_sign`].
+pub fn signers<'a>(
+    devices: &'a [DeviceKey],
+    input: &'a Input,
+) -> impl Iterator<Item = &'a DeviceKey> + 'a {
+    devices.iter().filter(move |device| device.can_sign(input))
+}
+
+impl fmt::Display for DeviceKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}", self.master_fingerprint)?;
+        for child in &self.path {
+            write!(f, "/{}", child)?;
+        }
+        write!(f, "]{}", self.xpub)
+    }
+}
+
+impl FromStr for DeviceKey {
+    type Err = ParseDeviceKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (origin, xpub) = s
+            .strip_prefix('[')
+            .and_then(|s| s.split_once(']'))
+            .ok_or(ParseDeviceKeyError::MissingOrigin)?;
+        let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+        Ok(Self {
+            master_fingerprint: fingerprint.parse().map_err(ParseDeviceKeyError::Fingerprint)?,
+            path: path.parse().map_err(ParseDeviceKeyError::Path)?,
+            xpub: xpub.parse().map_err(ParseDeviceKeyError::Xpub)?,
+        })
+    }
+}
+
+/// Error parsing a [`DeviceKey`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseDeviceKeyError {
+    /// The key origin in square brackets is missing.
+    MissingOrigin,
+    /// Invalid master key fingerprint.
+    Fingerprint(hex_unstable::HexToArrayError),
+    /// Invalid derivation path.
+    Path(ParseChildNumberError),
+    /// Invalid extended public key.
+    Xpub(ParseError),
+}
+
+impl From<core::convert::Infallible> for ParseDeviceKeyError {
+    fn from(never: core::convert::Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ParseDeviceKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::MissingOrigin => f.write_str("missing key origin"),
+            Self::Fingerprint(ref e) => write_err!(f, "invalid master key fingerprint"; e),
+            Self::Path(ref e) => write_err!(f, "invalid derivation path"; e),
+            Self::Xpub(ref e) => write_err!(f, "invalid extended public key"; e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDeviceKeyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::MissingOrigin => None,
+            Self::Fingerprint(ref e) => Some(e),
+            Self::Path(ref e) => Some(e),
+            Self::Xpub(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NetworkKind;
+
+    fn device(seed: u8) -> DeviceKey {
+        let master = Xpriv::new_master(NetworkKind::Test, &[seed; 32]);
+        DeviceKey::from_master(&master, "m/84h/1h/0h".parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn key_expression_roundtrip() {
+        let key = device(1);
+        let s = key.to_string();
+        assert!(s.starts_with(&format!("[{}/84'/1'/0']tpub", key.master_fingerprint())));
+        assert_eq!(s.parse::<DeviceKey>().unwrap(), key);
+
+        let master = DeviceKey::new(key.master_fingerprint(), DerivationPath::master(), key.xpub);
+        assert_eq!(master.to_string().parse::<DeviceKey>().unwrap(), master);
+
+        assert_eq!(
+            key.xpub().to_string().parse::<DeviceKey>(),
+            Err(ParseDeviceKeyError::MissingOrigin)
+        );
+        assert!(matches!(
+            format!("[{}/84x]{}", key.master_fingerprint(), key.xpub()).parse::<DeviceKey>(),
+            Err(ParseDeviceKeyError::Path(_))
+        ));
+    }
+
+    #[test]
+    fn match_input() {
+        let ecdsa_device = device(1);
+        let taproot_device = device(2);
+        let other_device = device(3);
+        let devices = [ecdsa_device.clone(), taproot_device.clone(), other_device];
+        let child = [ChildNumber::ZERO_NORMAL, ChildNumber::from_normal_idx(5).unwrap()];
+
+        let mut input = Input::default();
+        assert_eq!(signers(&devices, &input).count(), 0);
+
+        let (public_key, key_source) = ecdsa_device.bip32_derivation(child).unwrap();
+        assert_eq!(ecdsa_device.child_path(&key_source), Some(DerivationPath::from(&child[..])));
+        input.bip32_derivation.insert(public_key, key_source);
+
+        let (x_only, origin) = taproot_device.tap_key_origin(child, Vec::new()).unwrap();
+        input.tap_key_origins.insert(x_only, origin);
+
+        let signers = signers(&devices, &input).collect::<Vec<_>>();
+        assert_eq!(signers, [&ecdsa_device, &taproot_device]);
+
+        // Right origin but a key the device does not derive there.
+        let mut input = Input::default();
+        let (_, key_source) = ecdsa_device.bip32_derivation(child).unwrap();
+        let (public_key, _) = taproot_device.bip32_derivation(child).unwrap();
+        input.bip32_derivation.insert(public_key, key_source);
+        assert!(!ecdsa_device.can_sign(&input));
+    }
+}
@@ -13,14 +13,28 @@
 //!
 //! # Cargo features
 //!
+//! * `adaptor` - enables experimental Schnorr adaptor signatures for atomic swaps and discreet log
+//!   contracts.
 //! * `base64` (dependency) - enables encoding of PSBTs and message signatures.
 //! * `bitcoinconsensus` (dependency) - enables validating scripts and transactions.
+//! * `contract` - enables experimental pay-to-contract and sign-to-contract commitments.
 //! * `default` - enables `std` and `secp-recovery`.
+//! * `multi-way` - hashes multiple Merkle tree nodes at once on x86 CPUs that support AVX2.
+//! * `proptest` (dependency) - enables `testutil` and exposes `proptest` strategies in
+//!   `testutil::strategies`.
 //! * `rand` (transitive dependency) - makes it more convenient to generate random values.
+//! * `rayon` (dependency) - enables deriving descriptor addresses and verifying transactions in
+//!   parallel.
 //! * `serde` (dependency) - implements `serde`-based serialization and deserialization.
+//! * `serde-compact` - adds byte-string `serde` representations for compact binary formats.
 //! * `secp-lowmemory` - optimizations for low-memory devices.
 //! * `secp-recovery` - enables calculating public key from a signature and message.
 //! * `std` - the usual dependency on `std`.
+//! * `testutil` - builders for regtest blocks and signed transactions at fixed keys, for tests,
+//!   and `consensus::fuzz_roundtrip` for fuzz targets.
+//! * `ur` - enables encoding PSBTs and accounts as Uniform Resources for air-gapped devices.
+//! * `zeroize` (dependency) - implements `Zeroize` for private keys, chain codes, signatures and
+//!   PSBT inputs, and `ZeroizeOnDrop` for [`random::DeterministicRng`].
 
 #![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
 // Experimental features we need.
@@ -83,6 +97,10 @@
 /// for the SECG elliptic curve group secp256k1 and related utilities.
 pub extern crate secp256k1;
 
+/// Re-export the `zeroize` crate.
+#[cfg(feature = "zeroize")]
+pub extern crate zeroize;
+
 #[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde;
@@ -108,7 +126,7 @@ pub mod ext {
     //! ```
     #[rustfmt::skip] // Use terse custom grouping.
     pub use crate::{
-        block::{BlockCheckedExt as _, HeaderExt as _},
+        block::{BlockCheckedExt as _, BlockUncheckedExt as _, HeaderExt as _},
         pow::CompactTargetExt as _,
         script::{ScriptExt as _, ScriptBufExt as _, TapScriptExt as _, ScriptPubKeyExt as _, ScriptPubKeyBufExt as _, WitnessScriptExt as _, ScriptSigExt as _},
         transaction::{TxidExt as _, WtxidExt as _, OutPointExt as _, TxInExt as _, TxOutExt as _, TransactionExt as _},
@@ -117,33 +135,65 @@ pub mod ext {
     #[cfg(feature = "bitcoinconsensus")]
     pub use crate::consensus_validation::{ScriptPubKeyExt as _, TransactionExt as _};
 }
+#[cfg(feature = "adaptor")]
+pub mod adaptor;
 #[macro_use]
 pub mod address;
 pub mod bip158;
 pub mod bip32;
+pub mod block_file;
 pub mod blockdata;
+pub mod chain;
+pub mod change;
+pub mod coins;
 pub mod consensus;
 #[cfg(feature = "bitcoinconsensus")]
 pub mod consensus_validation;
+#[cfg(feature = "contract")]
+pub mod contract;
 // Private until we either make this a crate or flatten it - still to be decided.
 pub(crate) mod crypto;
+mod debug_json;
+pub mod descriptor;
+pub mod fee_estimation;
 pub mod hash_types;
+pub mod header_chain;
+pub mod header_store;
+pub mod hww;
+pub mod mempool;
 pub mod merkle_tree;
 pub mod network;
 pub mod policy;
 pub mod pow;
 pub mod psbt;
+pub mod random;
+#[cfg(feature = "serde")]
+pub mod rpc_json;
+pub mod scan;
 pub mod sign_message;
+pub mod stratum;
 pub mod taproot;
+#[cfg(test)]
+mod test_fixtures;
+#[cfg(feature = "testutil")]
+pub mod testutil;
+pub mod txgraph;
+#[cfg(feature = "ur")]
+pub mod ur;
+pub mod watch_only;
 
 // Re-export the type from where it is defined but the module from the highest place up the stack
 // that it is available in the event that we add some functionality there.
+#[cfg(feature = "serde-compact")]
+#[doc(inline)]
+pub use primitives::compact_serde;
 #[doc(inline)]
 pub use primitives::{
     block::{
-        compute_merkle_root, compute_witness_root, Block, BlockHash, Checked as BlockChecked,
-        Header as BlockHeader, InvalidBlockError, Unchecked as BlockUnchecked,
-        Validation as BlockValidation, Version as BlockVersion, WitnessCommitment,
+        compute_merkle_root, compute_witness_commitment, compute_witness_root, Block, BlockHash,
+        Checked as BlockChecked, Header as BlockHeader, InvalidBlockError,
+        Unchecked as BlockUnchecked, Validation as BlockValidation, Version as BlockVersion,
+        WitnessCommitment,
     },
     merkle_tree::{TxMerkleNode, WitnessMerkleNode},
     script::{
@@ -157,6 +207,8 @@ pub mod ext {
     witness::Witness,
 };
 #[doc(inline)]
+pub use primitives::{block_hash, script_hash, txid, wscript_hash, wtxid};
+#[doc(inline)]
 pub use units::{
     amount::{Amount, SignedAmount},
     block::{BlockHeight, BlockHeightInterval, BlockMtp, BlockMtpInterval},
@@ -184,7 +236,7 @@ pub mod ext {
     network::{Network, NetworkKind, TestnetVersion},
     pow::{Target, Work},
     psbt::Psbt,
-    sighash::{EcdsaSighashType, TapSighashType},
+    sighash::{EcdsaSighashType, SighashTypeAny, TapSighashType},
     taproot::{TapBranchTag, TapLeafHash, TapLeafTag, TapNodeHash, TapTweakHash, TapTweakTag},
 };
 // Re-export all modules from `blockdata`, users should never need to use `blockdata` directly.
@@ -193,8 +245,9 @@ pub mod ext {
     // Also, re-export types and modules from `blockdata` that don't come from `primitives`.
     blockdata::locktime::{absolute, relative},
     blockdata::opcodes::{self, Opcode},
-    blockdata::script::witness_program::{self, WitnessProgram},
+    blockdata::script::witness_program::{self, WitnessProgram, WitnessProgramKind},
     blockdata::script::witness_version::{self, WitnessVersion},
+    blockdata::transaction::Utxo,
     // These modules also re-export all the respective `primitives` types.
     blockdata::{block, constants, fee_rate, locktime, script, transaction, weight, witness},
 };
@@ -238,8 +291,8 @@ pub mod amount {
     pub use units::amount::{Amount, SignedAmount};
     #[doc(no_inline)]
     pub use units::amount::{
-        Denomination, Display, OutOfRangeError, ParseAmountError, ParseDenominationError,
-        ParseError,
+        Denomination, DenominationPolicy, Display, OutOfRangeError, ParseAmountError,
+        ParseDenominationError, ParseError, Separators,
     };
 
     /// Error types for bitcoin amounts.
@@ -0,0 +1,526 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Mempool transactions and their dependencies.
+//!
+//! A [`MempoolGraph`] keeps a set of unconfirmed transactions together with the dependencies
+//! between them. For every [`MempoolEntry`] it maintains the totals of its in-mempool ancestors
+//! and descendants (each including the transaction itself), the quantities Bitcoin Core's policy
+//! and mining code is based on.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use crate::prelude::{BTreeMap, BTreeSet, Vec};
+use crate::transaction::{OutPoint, Transaction, TransactionExt as _, Txid};
+use crate::{Amount, FeeRate, Weight};
+
+/// The totals of a set of transactions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PackageStats {
+    count: usize,
+    fee: Amount,
+    weight: Weight,
+}
+
+impl PackageStats {
+    fn single(fee: Amount, weight: Weight) -> Self { Self { count: 1, fee, weight } }
+
+    /// Returns the number of transactions.
+    pub fn count(&self) -> usize { self.count }
+
+    /// Returns the total fee of the transactions.
+    pub fn fee(&self) -> Amount { self.fee }
+
+    /// Returns the total weight of the transactions.
+    pub fn weight(&self) -> Weight { self.weight }
+
+    /// Returns the fee rate of the transactions as a whole, rounded down.
+    pub fn fee_rate(&self) -> FeeRate {
+        self.fee.div_by_weight_floor(self.weight).expect("transactions have non-zero weight")
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(Self {
+            count: self.count.checked_add(other.count)?,
+            fee: self.fee.checked_add(other.fee)?,
+            weight: self.weight.checked_add(other.weight)?,
+        })
+    }
+
+    /// Removes a transaction that was previously added.
+    fn remove(&mut self, other: Self) {
+        const MSG: &str = "only previously added transactions are removed";
+        self.count = self.count.checked_sub(other.count).expect(MSG);
+        self.fee = self.fee.checked_sub(other.fee).expect(MSG);
+        self.weight = self.weight.checked_sub(other.weight).expect(MSG);
+    }
+}
+
+/// A transaction in a [`MempoolGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MempoolEntry {
+    tx: Transaction,
+    txid: Txid,
+    fee: Amount,
+    weight: Weight,
+    ancestors: PackageStats,
+    descendants: PackageStats,
+}
+
+impl MempoolEntry {
+    /// Returns the transaction.
+    pub fn tx(&self) -> &Transaction { &self.tx }
+
+    /// Returns the TXID of the transaction.
+    pub fn txid(&self) -> Txid { self.txid }
+
+    /// Returns the fee paid by the transaction.
+    pub fn fee(&self) -> Amount { self.fee }
+
+    /// Returns the weight of the transaction.
+    pub fn weight(&self) -> Weight { self.weight }
+
+    /// Returns the fee rate of the transaction on its own, rounded down.
+    pub fn fee_rate(&self) -> FeeRate {
+        self.fee.div_by_weight_floor(self.weight).expect("transactions have non-zero weight")
+    }
+
+    /// Returns the totals of the transaction and all its in-mempool ancestors.
+    pub fn ancestors(&self) -> PackageStats { self.ancestors }
+
+    /// Returns the totals of the transaction and all its in-mempool descendants.
+    pub fn descendants(&self) -> PackageStats { self.descendants }
+
+    fn stats(&self) -> PackageStats { PackageStats::single(self.fee, self.weight) }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    entry: MempoolEntry,
+    parents: BTreeSet<Txid>,
+    children: BTreeSet<Txid>,
+}
+
+/// A set of unconfirmed transactions with ancestor and descendant accounting.
+///
+/// Transactions must be inserted after the in-mempool transactions they spend (i.e. in
+/// topological order) and may not conflict with each other.
+///
+/// # Examples
+///
+/// ```
+/// # use bitcoin::mempool::MempoolGraph;
+/// # use bitcoin::{Amount, Transaction, transaction::{OutPoint, TxIn, TxOut, Version}, absolute};
+/// # let tx = |previous_output| Transaction {
+/// #     version: Version::TWO,
+/// #     lock_time: absolute::LockTime::ZERO,
+/// #     inputs: vec![TxIn { previous_output, ..TxIn::EMPTY_COINBASE }],
+/// #     outputs: vec![TxOut { amount: Amount::ONE_BTC, script_pubkey: Default::default() }],
+/// # };
+/// # let confirmed = OutPoint { txid: bitcoin::Txid::from_byte_array([1; 32]), vout: 0 };
+/// let parent = tx(confirmed);
+/// let parent_txid = parent.compute_txid();
+/// let child = tx(OutPoint { txid: parent_txid, vout: 0 });
+///
+/// let mut mempool = MempoolGraph::new();
+/// mempool.insert(parent, Amount::from_sat_u32(100)).unwrap();
+/// let child = mempool.insert(child, Amount::from_sat_u32(1_000)).unwrap();
+/// assert_eq!(child.ancestors().count(), 2);
+/// assert_eq!(child.ancestors().fee(), Amount::from_sat_u32(1_100));
+/// assert_eq!(mempool.get(&parent_txid).unwrap().descendants().count(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MempoolGraph {
+    nodes: BTreeMap<Txid, Node>,
+    spenders: BTreeMap<OutPoint, Txid>,
+}
+
+impl MempoolGraph {
+    /// Constructs a new empty mempool.
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns the number of transactions.
+    pub fn len(&self) -> usize { self.nodes.len() }
+
+    /// Returns `true` if there are no transactions.
+    pub fn is_empty(&self) -> bool { self.nodes.is_empty() }
+
+    /// Returns `true` if the transaction with `txid` is in the mempool.
+    pub fn contains(&self, txid: &Txid) -> bool { self.nodes.contains_key(txid) }
+
+    /// Returns the entry of the transaction with `txid`.
+    pub fn get(&self, txid: &Txid) -> Option<&MempoolEntry> {
+        self.nodes.get(txid).map(|node| &node.entry)
+    }
+
+    /// Returns an iterator over all entries, ordered by TXID.
+    pub fn iter(&self) -> impl Iterator<Item = &MempoolEntry> {
+        self.nodes.values().map(|node| &node.entry)
+    }
+
+    /// Returns the TXID of the transaction spending `out_point`, if any.
+    pub fn spender(&self, out_point: &OutPoint) -> Option<Txid> {
+        self.spenders.get(out_point).copied()
+    }
+
+    /// Returns the TXIDs of all in-mempool ancestors of the transaction with `txid`.
+    ///
+    /// The set is empty if the transaction is not in the mempool.
+    pub fn ancestor_txids(&self, txid: &Txid) -> BTreeSet<Txid> {
+        self.closure(txid, |node| &node.parents)
+    }
+
+    /// Returns the TXIDs of all in-mempool descendants of the transaction with `txid`.
+    ///
+    /// The set is empty if the transaction is not in the mempool.
+    pub fn descendant_txids(&self, txid: &Txid) -> BTreeSet<Txid> {
+        self.closure(txid, |node| &node.children)
+    }
+
+    /// Adds `tx`, which pays `fee`, to the mempool and returns its entry.
+    ///
+    /// The ancestor totals of the new entry and the descendant totals of all its ancestors are
+    /// updated.
+    ///
+    /// # Errors
+    ///
+    /// If the transaction is already in the mempool, conflicts with a transaction in the mempool,
+    /// is spent by a transaction in the mempool or if the totals overflow. The mempool is left
+    /// unchanged in that case.
+    pub fn insert(&mut self, tx: Transaction, fee: Amount) -> Result<&MempoolEntry, InsertError> {
+        let txid = tx.compute_txid();
+        if self.nodes.contains_key(&txid) {
+            return Err(InsertError::Duplicate { txid });
+        }
+        for input in &tx.inputs {
+            if let Some(&spender) = self.spenders.get(&input.previous_output) {
+                return Err(InsertError::Conflict { out_point: input.previous_output, spender });
+            }
+        }
+        // Outputs of `tx` can only be spent already if its children were inserted before it.
+        if let Some((_, &spender)) = self
+            .spenders
+            .range(OutPoint { txid, vout: 0 }..=OutPoint { txid, vout: u32::MAX })
+            .next()
+        {
+            return Err(InsertError::SpentInMempool { spender });
+        }
+
+        let weight = tx.weight();
+        let stats = PackageStats::single(fee, weight);
+        let parents = tx
+            .inputs
+            .iter()
+            .map(|input| input.previous_output.txid)
+            .filter(|parent| self.nodes.contains_key(parent))
+            .collect::<BTreeSet<_>>();
+        let mut ancestor_txids = BTreeSet::new();
+        for parent in &parents {
+            ancestor_txids.insert(*parent);
+            ancestor_txids.extend(self.ancestor_txids(parent));
+        }
+
+        let mut ancestors = stats;
+        for ancestor in &ancestor_txids {
+            ancestors = ancestors
+                .checked_add(self.nodes[ancestor].entry.stats())
+                .ok_or(InsertError::Overflow)?;
+            self.nodes[ancestor]
+                .entry
+                .descendants
+                .checked_add(stats)
+                .ok_or(InsertError::Overflow)?;
+        }
+
+        // Nothing can fail from here on.
+        for ancestor in &ancestor_txids {
+            let entry =
+                &mut self.nodes.get_mut(ancestor).expect("ancestors are in the mempool").entry;
+            entry.descendants = entry.descendants.checked_add(stats).expect("checked above");
+        }
+        for parent in &parents {
+            self.nodes.get_mut(parent).expect("parents are in the mempool").children.insert(txid);
+        }
+        for input in &tx.inputs {
+            self.spenders.insert(input.previous_output, txid);
+        }
+        let entry = MempoolEntry { tx, txid, fee, weight, ancestors, descendants: stats };
+        let node = Node { entry, parents, children: BTreeSet::new() };
+        Ok(&self.nodes.entry(txid).or_insert(node).entry)
+    }
+
+    /// Removes the transaction with `txid` and all its descendants, for example because it
+    /// conflicts with a new transaction or was evicted.
+    ///
+    /// Returns the removed entries, parents before children.
+    pub fn remove_with_descendants(&mut self, txid: &Txid) -> Vec<MempoolEntry> {
+        if !self.nodes.contains_key(txid) {
+            return Vec::new();
+        }
+        let mut removed = self.descendant_txids(txid);
+        removed.insert(*txid);
+
+        // Only the descendant totals of the remaining ancestors change.
+        for txid in &removed {
+            let stats = self.nodes[txid].entry.stats();
+            for ancestor in self.ancestor_txids(txid).difference(&removed) {
+                let node = self.nodes.get_mut(ancestor).expect("ancestors are in the mempool");
+                node.entry.descendants.remove(stats);
+            }
+        }
+        self.remove_set(&removed)
+    }
+
+    /// Removes the transaction with `txid` and all its ancestors, for example because they were
+    /// included in a block.
+    ///
+    /// Returns the removed entries, parents before children.
+    pub fn remove_with_ancestors(&mut self, txid: &Txid) -> Vec<MempoolEntry> {
+        if !self.nodes.contains_key(txid) {
+            return Vec::new();
+        }
+        let mut removed = self.ancestor_txids(txid);
+        removed.insert(*txid);
+
+        // Only the ancestor totals of the remaining descendants change.
+        for txid in &removed {
+            let stats = self.nodes[txid].entry.stats();
+            for descendant in self.descendant_txids(txid).difference(&removed) {
+                let node = self.nodes.get_mut(descendant).expect("descendants are in the mempool");
+                node.entry.ancestors.remove(stats);
+            }
+        }
+        self.remove_set(&removed)
+    }
+
+    /// Removes all transactions in `removed` after their totals were updated.
+    fn remove_set(&mut self, removed: &BTreeSet<Txid>) -> Vec<MempoolEntry> {
+        let mut nodes = removed
+            .iter()
+            .map(|txid| self.nodes.remove(txid).expect("removed transactions are in the mempool"))
+            .collect::<Vec<_>>();
+        for node in &nodes {
+            for input in &node.entry.tx.inputs {
+                self.spenders.remove(&input.previous_output);
+            }
+            for parent in node.parents.difference(removed) {
+                self.nodes
+                    .get_mut(parent)
+                    .expect("parents are in the mempool")
+                    .children
+                    .remove(&node.entry.txid);
+            }
+            for child in node.children.difference(removed) {
+                self.nodes
+                    .get_mut(child)
+                    .expect("children are in the mempool")
+                    .parents
+                    .remove(&node.entry.txid);
+            }
+        }
+        // A transaction has more ancestors than any of its parents.
+        nodes.sort_by_key(|node| node.entry.ancestors.count);
+        nodes.into_iter().map(|node| node.entry).collect()
+    }
+
+    /// Returns the TXIDs reachable from `txid` by following `edges`, excluding `txid` itself.
+    fn closure(&self, txid: &Txid, edges: impl Fn(&Node) -> &BTreeSet<Txid>) -> BTreeSet<Txid> {
+        let mut found = BTreeSet::new();
+        let mut stack = Vec::new();
+        if let Some(node) = self.nodes.get(txid) {
+            stack.extend(edges(node).iter().copied());
+        }
+        while let Some(next) = stack.pop() {
+            if found.insert(next) {
+                stack.extend(edges(&self.nodes[&next]).iter().copied());
+            }
+        }
+        found
+    }
+}
+
+/// An error inserting a transaction into a [`MempoolGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InsertError {
+    /// The transaction is already in the mempool.
+    Duplicate {
+        /// The TXID of the transaction.
+        txid: Txid,
+    },
+    /// The transaction spends an output that is already spent by a transaction in the mempool.
+    Conflict {
+        /// The doubly spent output.
+        out_point: OutPoint,
+        /// The transaction in the mempool spending the output.
+        spender: Txid,
+    },
+    /// An output of the transaction is already spent by a transaction in the mempool, meaning the
+    /// transactions were not inserted in topological order.
+    SpentInMempool {
+        /// The transaction in the mempool spending the output.
+        spender: Txid,
+    },
+    /// The ancestor or descendant totals overflowed.
+    Overflow,
+}
+
+impl From<Infallible> for InsertError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Duplicate { txid } => write!(f, "transaction {} is already in the mempool", txid),
+            Self::Conflict { out_point, spender } => write!(
+                f,
+                "output {} is already spent by mempool transaction {}",
+                out_point, spender
+            ),
+            Self::SpentInMempool { spender } => write!(
+                f,
+                "transaction is already spent by mempool transaction {}, insert parents first",
+                spender
+            ),
+            Self::Overflow => f.write_str("mempool package totals overflowed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InsertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Duplicate { .. }
+            | Self::Conflict { .. }
+            | Self::SpentInMempool { .. }
+            | Self::Overflow => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{confirmed, tx_with_outputs};
+
+    fn sat(sat: u32) -> Amount { Amount::from_sat_u32(sat) }
+
+    /// Builds the diamond `a -> (b, c) -> d` plus an unrelated transaction `e`.
+    fn diamond() -> (MempoolGraph, [Txid; 5]) {
+        let a = tx_with_outputs(&[confirmed(1)], 2);
+        let a_txid = a.compute_txid();
+        let b = tx_with_outputs(&[OutPoint { txid: a_txid, vout: 0 }], 1);
+        let c = tx_with_outputs(&[OutPoint { txid: a_txid, vout: 1 }], 1);
+        let d = tx_with_outputs(
+            &[
+                OutPoint { txid: b.compute_txid(), vout: 0 },
+                OutPoint { txid: c.compute_txid(), vout: 0 },
+            ],
+            1,
+        );
+        let e = tx_with_outputs(&[confirmed(2)], 1);
+
+        let mut mempool = MempoolGraph::new();
+        let mut txids = [a_txid; 5];
+        for (i, (tx, fee)) in
+            [(a, 1), (b, 10), (c, 100), (d, 1_000), (e, 10_000)].into_iter().enumerate()
+        {
+            txids[i] = mempool.insert(tx, sat(fee)).unwrap().txid();
+        }
+        (mempool, txids)
+    }
+
+    #[test]
+    fn ancestor_and_descendant_totals() {
+        let (mempool, [a, b, c, d, e]) = diamond();
+        let get = |txid| mempool.get(&txid).unwrap();
+
+        assert_eq!(get(a).ancestors().count(), 1);
+        assert_eq!(get(a).descendants().count(), 4);
+        assert_eq!(get(a).descendants().fee(), sat(1_111));
+        assert_eq!(get(b).ancestors().fee(), sat(11));
+        assert_eq!(get(b).descendants().fee(), sat(1_010));
+        // `a` is counted once even though `d` reaches it through both `b` and `c`.
+        assert_eq!(get(d).ancestors().count(), 4);
+        assert_eq!(get(d).ancestors().fee(), sat(1_111));
+        let weight = get(a).weight() + get(b).weight() + get(c).weight() + get(d).weight();
+        assert_eq!(get(d).ancestors().weight(), weight);
+        assert_eq!(get(e).ancestors(), get(e).descendants());
+
+        assert_eq!(mempool.ancestor_txids(&d), [a, b, c].into_iter().collect());
+        assert_eq!(mempool.descendant_txids(&c), [d].into_iter().collect());
+        assert_eq!(mempool.spender(&OutPoint { txid: a, vout: 1 }), Some(c));
+        assert_eq!(get(d).fee_rate(), sat(1_000).div_by_weight_floor(get(d).weight()).unwrap());
+    }
+
+    #[test]
+    fn insert_errors() {
+        let (mut mempool, [a, _, c, _, e]) = diamond();
+        let before = mempool.clone();
+
+        let duplicate = mempool.get(&a).unwrap().tx().clone();
+        assert_eq!(mempool.insert(duplicate, sat(1)), Err(InsertError::Duplicate { txid: a }));
+
+        let conflict = tx_with_outputs(&[OutPoint { txid: a, vout: 1 }], 3);
+        assert_eq!(
+            mempool.insert(conflict, sat(1)),
+            Err(InsertError::Conflict { out_point: OutPoint { txid: a, vout: 1 }, spender: c })
+        );
+
+        let parent = tx_with_outputs(&[confirmed(3)], 1);
+        let child = tx_with_outputs(&[OutPoint { txid: parent.compute_txid(), vout: 0 }], 1);
+        let child_txid = mempool.insert(child, sat(1)).unwrap().txid();
+        assert_eq!(
+            mempool.insert(parent, sat(1)),
+            Err(InsertError::SpentInMempool { spender: child_txid })
+        );
+        mempool.remove_with_descendants(&child_txid);
+
+        let e_child = tx_with_outputs(&[OutPoint { txid: e, vout: 0 }], 1);
+        assert_eq!(mempool.insert(e_child, Amount::MAX), Err(InsertError::Overflow));
+        assert_eq!(mempool, before);
+    }
+
+    #[test]
+    fn remove_conflicted() {
+        let (mut mempool, [a, b, c, d, e]) = diamond();
+        let removed = mempool.remove_with_descendants(&b);
+        assert_eq!(removed.iter().map(MempoolEntry::txid).collect::<Vec<_>>(), [b, d]);
+        assert_eq!(mempool.len(), 3);
+
+        let get = |txid| mempool.get(&txid).unwrap();
+        assert_eq!(get(a).descendants().fee(), sat(101));
+        assert_eq!(get(a).descendants().count(), 2);
+        assert_eq!(get(c).descendants().count(), 1);
+        assert_eq!(get(c).ancestors().count(), 2);
+        assert_eq!(get(e).descendants().count(), 1);
+        assert!(mempool.spender(&OutPoint { txid: a, vout: 0 }).is_none());
+        assert!(mempool.remove_with_descendants(&d).is_empty());
+    }
+
+    #[test]
+    fn remove_mined() {
+        let (mut mempool, [a, b, c, d, _]) = diamond();
+        let removed = mempool.remove_with_ancestors(&b);
+        assert_eq!(removed.iter().map(MempoolEntry::txid).collect::<Vec<_>>(), [a, b]);
+
+        let get = |txid| mempool.get(&txid).unwrap();
+        assert_eq!(get(c).ancestors().count(), 1);
+        assert_eq!(get(c).ancestors().fee(), sat(100));
+        assert_eq!(get(d).ancestors().count(), 2);
+        assert_eq!(get(d).ancestors().fee(), sat(1_100));
+        assert_eq!(get(c).descendants().count(), 2);
+        assert_eq!(mempool.ancestor_txids(&d), [c].into_iter().collect());
+
+        // The remaining entries match a mempool built from scratch.
+        let mut rebuilt = MempoolGraph::new();
+        for txid in [c, d] {
+            rebuilt.insert(get(txid).tx().clone(), get(txid).fee()).unwrap();
+        }
+        for txid in [c, d] {
+            assert_eq!(rebuilt.get(&txid), mempool.get(&txid));
+        }
+    }
+}
@@ -19,6 +19,8 @@
 #[rustfmt::skip]
 #[doc(inline)]
 pub use primitives::{TxMerkleNode, WitnessMerkleNode};
+#[doc(inline)]
+pub use primitives::merkle_tree::{MerkleHash, MerkleProof, MerkleTree};
 
 use crate::consensus::{encode, Decodable, Encodable};
 
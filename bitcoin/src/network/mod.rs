@@ -14,7 +14,7 @@
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
-pub use self::params::Params;
+pub use self::params::{DeploymentStatus, Params};
 #[doc(no_inline)]
 pub use network::ParseNetworkError;
 #[doc(inline)]
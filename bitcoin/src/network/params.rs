@@ -80,6 +80,14 @@ pub struct Params {
     pub bip65_height: BlockHeight,
     /// Block height at which BIP-0066 becomes active.
     pub bip66_height: BlockHeight,
+    /// Block height at which BIP-0068, BIP-0112 and BIP-0113 (CSV) become active.
+    pub csv_height: BlockHeight,
+    /// Block height at which BIP-0141 and BIP-0143 (SegWit) become active.
+    pub segwit_height: BlockHeight,
+    /// Block height at which BIP-0341 and BIP-0342 (Taproot) become active.
+    ///
+    /// Zero on the networks where the deployment is always active.
+    pub taproot_height: BlockHeight,
     /// Enforce BIP-0094 block storm mitigation.
     pub enforce_bip94: bool,
     /// Minimum blocks including miner confirmation of the total of 2016 blocks in a retargeting period,
@@ -141,10 +149,13 @@ impl Params {
     /// The mainnet parameters.
     pub const MAINNET: Self = Self {
         network: Network::Bitcoin,
-        bip16_time: 1333238400,                      // Apr 1 2012
+        bip16_time: 1333238400,                        // Apr 1 2012
         bip34_height: BlockHeight::from_u32(227931), // 000000000000024b89b42a942fe0d9fea3bb44ab7bd1b19115dd6a759c0808b8
         bip65_height: BlockHeight::from_u32(388381), // 000000000000000004c2b624ed5d7756c508d90fd0da2c7c679febfa6c4735f0
         bip66_height: BlockHeight::from_u32(363725), // 00000000000000000379eaa19dce8c9b722d46ae6a57c2f1a988119488b50931
+        csv_height: BlockHeight::from_u32(419328), // 000000000000000004a1b34462cb8aeebd5799177f7a29cf28f2d1961716b5b5
+        segwit_height: BlockHeight::from_u32(481824), // 0000000000000000001c8018d9cb3b742ef25114f27563e3fc4a1902167f9893
+        taproot_height: BlockHeight::from_u32(709632), // 0000000000000000000687bca986194dc2c1f949318629b44bb54ec0a94d8244
         enforce_bip94: false,
         rule_change_activation_threshold: BlockHeightInterval::from_u32(1916), // 95%
         miner_confirmation_window: BlockHeightInterval::from_u32(2016),
@@ -160,10 +171,13 @@ impl Params {
     #[deprecated(since = "TBD", note = "use `TESTNET3` instead")]
     pub const TESTNET: Self = Self {
         network: Network::Testnet(TestnetVersion::V3),
-        bip16_time: 1333238400,                      // Apr 1 2012
+        bip16_time: 1333238400,                         // Apr 1 2012
         bip34_height: BlockHeight::from_u32(21111), // 0000000023b3a96d3484e5abb3755c413e7d41500f8e2a5c3f0dd01299cd8ef8
         bip65_height: BlockHeight::from_u32(581885), // 00000000007f6655f22f98e72ed80d8b06dc761d5da09df0fa1dc4be4f861eb6
         bip66_height: BlockHeight::from_u32(330776), // 000000002104c8c45e99a8853285a3b592602a3ccde2b832481da85e9e4ba182
+        csv_height: BlockHeight::from_u32(770112), // 00000000025e930139bac5c6c31a403776da130831ab85be56578f3fa75369bb
+        segwit_height: BlockHeight::from_u32(834624), // 00000000002b980fcd729daaa248fd9316a5200e9b367f4ff2c42453e84201ca
+        taproot_height: BlockHeight::from_u32(2011968), // Activation height of the deployment
         enforce_bip94: false,
         rule_change_activation_threshold: BlockHeightInterval::from_u32(1512), // 75%
         miner_confirmation_window: BlockHeightInterval::from_u32(2016),
@@ -178,10 +192,13 @@ impl Params {
     /// The testnet3 parameters.
     pub const TESTNET3: Self = Self {
         network: Network::Testnet(TestnetVersion::V3),
-        bip16_time: 1333238400,                      // Apr 1 2012
+        bip16_time: 1333238400,                         // Apr 1 2012
         bip34_height: BlockHeight::from_u32(21111), // 0000000023b3a96d3484e5abb3755c413e7d41500f8e2a5c3f0dd01299cd8ef8
         bip65_height: BlockHeight::from_u32(581885), // 00000000007f6655f22f98e72ed80d8b06dc761d5da09df0fa1dc4be4f861eb6
         bip66_height: BlockHeight::from_u32(330776), // 000000002104c8c45e99a8853285a3b592602a3ccde2b832481da85e9e4ba182
+        csv_height: BlockHeight::from_u32(770112), // 00000000025e930139bac5c6c31a403776da130831ab85be56578f3fa75369bb
+        segwit_height: BlockHeight::from_u32(834624), // 00000000002b980fcd729daaa248fd9316a5200e9b367f4ff2c42453e84201ca
+        taproot_height: BlockHeight::from_u32(2011968), // Activation height of the deployment
         enforce_bip94: false,
         rule_change_activation_threshold: BlockHeightInterval::from_u32(1512), // 75%
         miner_confirmation_window: BlockHeightInterval::from_u32(2016),
@@ -200,6 +217,9 @@ impl Params {
         bip34_height: BlockHeight::from_u32(1),
         bip65_height: BlockHeight::from_u32(1),
         bip66_height: BlockHeight::from_u32(1),
+        csv_height: BlockHeight::from_u32(1),
+        segwit_height: BlockHeight::from_u32(1),
+        taproot_height: BlockHeight::ZERO,
         enforce_bip94: true,
         rule_change_activation_threshold: BlockHeightInterval::from_u32(1512), // 75%
         miner_confirmation_window: BlockHeightInterval::from_u32(2016),
@@ -218,6 +238,9 @@ impl Params {
         bip34_height: BlockHeight::from_u32(1),
         bip65_height: BlockHeight::from_u32(1),
         bip66_height: BlockHeight::from_u32(1),
+        csv_height: BlockHeight::from_u32(1),
+        segwit_height: BlockHeight::from_u32(1),
+        taproot_height: BlockHeight::ZERO,
         enforce_bip94: false,
         rule_change_activation_threshold: BlockHeightInterval::from_u32(1916), // 95%
         miner_confirmation_window: BlockHeightInterval::from_u32(2016),
@@ -236,6 +259,9 @@ impl Params {
         bip34_height: BlockHeight::from_u32(100000000), // not activated on regtest
         bip65_height: BlockHeight::from_u32(1351),
         bip66_height: BlockHeight::from_u32(1251), // used only in rpc tests
+        csv_height: BlockHeight::from_u32(432),    // used only in rpc tests
+        segwit_height: BlockHeight::ZERO,
+        taproot_height: BlockHeight::ZERO,
         enforce_bip94: false,
         rule_change_activation_threshold: BlockHeightInterval::from_u32(108), // 75%
         miner_confirmation_window: BlockHeightInterval::from_u32(144),
@@ -265,6 +291,49 @@ pub fn difficulty_adjustment_interval(&self) -> u64 {
     }
 }
 
+/// The consensus rule changes active at a block height.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin::network::DeploymentStatus;
+/// use bitcoin::{BlockHeight, Network};
+///
+/// let status = DeploymentStatus::at(BlockHeight::from_u32(500_000), Network::Bitcoin);
+/// assert!(status.segwit);
+/// assert!(!status.taproot);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DeploymentStatus {
+    /// BIP-0034, block height in the coinbase.
+    pub bip34: bool,
+    /// BIP-0065, `OP_CHECKLOCKTIMEVERIFY`.
+    pub bip65: bool,
+    /// BIP-0066, strict DER signatures.
+    pub bip66: bool,
+    /// BIP-0068, BIP-0112 and BIP-0113, relative lock times and `OP_CHECKSEQUENCEVERIFY`.
+    pub csv: bool,
+    /// BIP-0141 and BIP-0143, segregated witness.
+    pub segwit: bool,
+    /// BIP-0341 and BIP-0342, taproot.
+    pub taproot: bool,
+}
+
+impl DeploymentStatus {
+    /// Returns the deployments active for the block at `height` on the network with `params`.
+    pub fn at(height: BlockHeight, params: impl AsRef<Params>) -> Self {
+        let params = params.as_ref();
+        Self {
+            bip34: height >= params.bip34_height,
+            bip65: height >= params.bip65_height,
+            bip66: height >= params.bip66_height,
+            csv: height >= params.csv_height,
+            segwit: height >= params.segwit_height,
+            taproot: height >= params.taproot_height,
+        }
+    }
+}
+
 impl From<Network> for Params {
     fn from(value: Network) -> Self { Self::new(value) }
 }
@@ -288,3 +357,27 @@ fn as_ref(&self) -> &Self { self }
 impl AsRef<Params> for Network {
     fn as_ref(&self) -> &Params { Self::params(*self) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deployment_status_at_activation_heights() {
+        let before = DeploymentStatus::at(BlockHeight::from_u32(481_823), Network::Bitcoin);
+        let after = DeploymentStatus::at(BlockHeight::from_u32(481_824), Network::Bitcoin);
+        assert!(before.csv && !before.segwit);
+        assert!(after.segwit && !after.taproot);
+        assert_eq!(DeploymentStatus { segwit: true, ..before }, after);
+
+        let genesis = DeploymentStatus::at(BlockHeight::ZERO, Network::Bitcoin);
+        assert!(!genesis.bip34 && !genesis.segwit && !genesis.taproot);
+        let taproot = DeploymentStatus::at(BlockHeight::from_u32(709_632), Network::Bitcoin);
+        assert!(taproot.segwit && taproot.taproot);
+
+        let regtest = DeploymentStatus::at(BlockHeight::ZERO, Network::Regtest);
+        assert!(regtest.segwit && regtest.taproot && !regtest.csv);
+        let signet = DeploymentStatus::at(BlockHeight::from_u32(1), Network::Signet);
+        assert!(signet.bip34 && signet.csv && signet.segwit && signet.taproot);
+    }
+}
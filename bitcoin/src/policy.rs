@@ -14,6 +14,9 @@
 use core::cmp;
 
 use super::constants::{MAX_BLOCK_SIGOPS_COST, WITNESS_SCALE_FACTOR};
+use crate::address::AddressType;
+use crate::script::{ScriptExt as _, ScriptPubKey, ScriptPubKeyExt as _};
+use crate::{Amount, FeeRate};
 
 /// Maximum weight of a transaction for it to be relayed by most nodes on the network
 pub const MAX_STANDARD_TX_WEIGHT: u32 = 400_000;
@@ -43,6 +46,23 @@
 /// mempools.
 pub const DEFAULT_MEMPOOL_EXPIRY: u32 = 336;
 
+/// Maximum number of stack elements, excluding the witness script, in a standard P2WSH witness.
+pub const MAX_STANDARD_P2WSH_STACK_ITEMS: usize = 100;
+
+/// Maximum size in bytes of a stack element, excluding the witness script, in a standard P2WSH
+/// witness.
+pub const MAX_STANDARD_P2WSH_STACK_ITEM_SIZE: usize = 80;
+
+/// Maximum size in bytes of a standard witness script.
+pub const MAX_STANDARD_P2WSH_SCRIPT_SIZE: usize = 3_600;
+
+/// Maximum size in bytes of a standard scriptSig.
+pub const MAX_STANDARD_SCRIPTSIG_SIZE: usize = 1_650;
+
+/// Maximum size in bytes of a stack element, excluding the leaf script and the control block, in
+/// a standard Taproot script path witness.
+pub const MAX_STANDARD_TAPSCRIPT_STACK_ITEM_SIZE: usize = 80;
+
 // 80 bytes of data, +1 for OP_RETURN, +2 for the pushdata opcodes.
 pub(crate) const MAX_OP_RETURN_RELAY: usize = 83;
 
@@ -51,3 +71,144 @@ pub fn get_virtual_tx_size(weight: i64, n_sigops: i64) -> i64 {
     (cmp::max(weight, n_sigops * DEFAULT_BYTES_PER_SIGOP as i64) + WITNESS_SCALE_FACTOR as i64 - 1)
         / WITNESS_SCALE_FACTOR as i64
 }
+
+/// The relay policy deciding which outputs are dust.
+///
+/// By default the threshold of an output is the value below which spending it at the dust relay
+/// fee rate costs more than it is worth, like Bitcoin Core's `GetDustThreshold()`. Nodes with a
+/// custom relay policy can change the fee rate (`-dustrelayfee`) and set fixed thresholds for
+/// individual script types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DustPolicy {
+    /// The fee rate used to compute the thresholds of outputs without a fixed threshold.
+    pub dust_relay_fee: FeeRate,
+    p2pkh: Option<Amount>,
+    p2sh: Option<Amount>,
+    p2wpkh: Option<Amount>,
+    p2wsh: Option<Amount>,
+    p2tr: Option<Amount>,
+    p2a: Option<Amount>,
+}
+
+impl DustPolicy {
+    /// The default policy of Bitcoin Core, using [`DUST_RELAY_TX_FEE`].
+    pub const DEFAULT: Self = Self::new(FeeRate::from_sat_per_kvb(DUST_RELAY_TX_FEE));
+
+    /// Constructs a new policy computing all thresholds from `dust_relay_fee`.
+    pub const fn new(dust_relay_fee: FeeRate) -> Self {
+        Self {
+            dust_relay_fee,
+            p2pkh: None,
+            p2sh: None,
+            p2wpkh: None,
+            p2wsh: None,
+            p2tr: None,
+            p2a: None,
+        }
+    }
+
+    /// Sets a fixed `threshold` for outputs of type `address_type`, instead of computing it from
+    /// the dust relay fee rate.
+    #[must_use]
+    pub fn with_threshold(mut self, address_type: AddressType, threshold: Amount) -> Self {
+        *self.fixed_threshold_mut(address_type) = Some(threshold);
+        self
+    }
+
+    /// Returns the smallest amount of an output with `script_pubkey` that is not dust.
+    ///
+    /// Returns `None` if the threshold computed from the dust relay fee rate overflows, in which
+    /// case any amount is dust.
+    pub fn threshold(&self, script_pubkey: &ScriptPubKey) -> Option<Amount> {
+        match address_type(script_pubkey).and_then(|ty| *self.fixed_threshold(ty)) {
+            Some(threshold) => Some(threshold),
+            None => script_pubkey.minimal_non_dust_custom(self.dust_relay_fee),
+        }
+    }
+
+    /// Returns `true` if an output of `amount` to `script_pubkey` is dust under this policy.
+    pub fn is_dust(&self, script_pubkey: &ScriptPubKey, amount: Amount) -> bool {
+        self.threshold(script_pubkey).map_or(true, |threshold| amount < threshold)
+    }
+
+    fn fixed_threshold(&self, address_type: AddressType) -> &Option<Amount> {
+        match address_type {
+            AddressType::P2pkh => &self.p2pkh,
+            AddressType::P2sh => &self.p2sh,
+            AddressType::P2wpkh => &self.p2wpkh,
+            AddressType::P2wsh => &self.p2wsh,
+            AddressType::P2tr => &self.p2tr,
+            AddressType::P2a => &self.p2a,
+        }
+    }
+
+    fn fixed_threshold_mut(&mut self, address_type: AddressType) -> &mut Option<Amount> {
+        match address_type {
+            AddressType::P2pkh => &mut self.p2pkh,
+            AddressType::P2sh => &mut self.p2sh,
+            AddressType::P2wpkh => &mut self.p2wpkh,
+            AddressType::P2wsh => &mut self.p2wsh,
+            AddressType::P2tr => &mut self.p2tr,
+            AddressType::P2a => &mut self.p2a,
+        }
+    }
+}
+
+impl Default for DustPolicy {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// Returns the address type of `script_pubkey`, independent of the network.
+fn address_type(script_pubkey: &ScriptPubKey) -> Option<AddressType> {
+    if script_pubkey.is_p2pkh() {
+        Some(AddressType::P2pkh)
+    } else if script_pubkey.is_p2sh() {
+        Some(AddressType::P2sh)
+    } else if script_pubkey.is_p2wpkh() {
+        Some(AddressType::P2wpkh)
+    } else if script_pubkey.is_p2wsh() {
+        Some(AddressType::P2wsh)
+    } else if script_pubkey.is_p2tr() {
+        Some(AddressType::P2tr)
+    } else if script_pubkey.is_p2a() {
+        Some(AddressType::P2a)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{ScriptBufExt as _, ScriptPubKeyBuf};
+    use crate::transaction::{TxOut, TxOutExt as _};
+
+    #[test]
+    fn dust_policy() {
+        let p2pkh = ScriptPubKeyBuf::from_hex_no_length_prefix(
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac",
+        )
+        .unwrap();
+        let p2wpkh = ScriptPubKeyBuf::from_hex_no_length_prefix(
+            "001462e907b15cbf27d5425399ebf6f0fb50ebb88f18",
+        )
+        .unwrap();
+        let op_return = ScriptPubKeyBuf::from_hex_no_length_prefix("6a").unwrap();
+
+        let policy = DustPolicy::default();
+        assert_eq!(policy.threshold(&p2pkh), Some(Amount::from_sat_u32(546)));
+        assert_eq!(policy.threshold(&p2wpkh), Some(Amount::from_sat_u32(294)));
+        assert!(policy.is_dust(&p2pkh, Amount::from_sat_u32(545)));
+        assert!(!policy.is_dust(&p2pkh, Amount::from_sat_u32(546)));
+        assert!(!policy.is_dust(&op_return, Amount::ZERO));
+
+        let policy = DustPolicy::new(FeeRate::from_sat_per_vb(1))
+            .with_threshold(AddressType::P2wpkh, Amount::from_sat_u32(1_000));
+        assert_eq!(policy.threshold(&p2pkh), Some(Amount::from_sat_u32(182)));
+        assert_eq!(policy.threshold(&p2wpkh), Some(Amount::from_sat_u32(1_000)));
+
+        let output = TxOut { amount: Amount::from_sat_u32(999), script_pubkey: p2wpkh };
+        assert!(output.is_dust(&policy));
+        assert!(!output.is_dust(&DustPolicy::DEFAULT));
+    }
+}
@@ -72,6 +72,65 @@ pub fn from_unprefixed_hex(s: &str) -> Result<Self, UnprefixedHexError> {
             #[doc = "` to a little-endian byte array."]
             #[inline]
             pub fn to_le_bytes(self) -> [u8; 32] { self.0.to_le_bytes() }
+
+            /// Checked addition. Returns [`None`] if overflow occurred.
+            #[must_use]
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                match self.0.overflowing_add(rhs.0) {
+                    (sum, false) => Some($ty(sum)),
+                    (_, true) => None,
+                }
+            }
+
+            /// Checked subtraction. Returns [`None`] if overflow occurred.
+            #[must_use]
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                match self.0.overflowing_sub(rhs.0) {
+                    (difference, false) => Some($ty(difference)),
+                    (_, true) => None,
+                }
+            }
+
+            /// Checked multiplication by an integer. Returns [`None`] if overflow occurred.
+            #[must_use]
+            pub fn checked_mul(self, rhs: u64) -> Option<Self> {
+                match self.0.mul_u64(rhs) {
+                    (product, false) => Some($ty(product)),
+                    (_, true) => None,
+                }
+            }
+
+            /// Checked division by an integer. Returns [`None`] if `rhs == 0`.
+            #[must_use]
+            pub fn checked_div(self, rhs: u64) -> Option<Self> {
+                if rhs == 0 {
+                    None
+                } else {
+                    Some($ty(self.0 / U256::from(rhs)))
+                }
+            }
+
+            /// Checked shift left. Returns [`None`] if any set bit is shifted out.
+            #[must_use]
+            pub fn checked_shl(self, shift: u32) -> Option<Self> {
+                if self.0.is_zero() {
+                    Some(self)
+                } else if shift > 256 - self.0.bits() {
+                    None
+                } else {
+                    Some($ty(self.0 << shift))
+                }
+            }
+
+            /// Checked shift right. Returns [`None`] if `shift` is 256 or more.
+            #[must_use]
+            pub fn checked_shr(self, shift: u32) -> Option<Self> {
+                if shift >= 256 {
+                    None
+                } else {
+                    Some($ty(self.0 >> shift))
+                }
+            }
         }
 
         impl fmt::Display for $ty {
@@ -133,6 +192,9 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(&self.0) }
 pub struct Work(U256);
 
 impl Work {
+    /// No work.
+    pub const ZERO: Self = Self(U256::ZERO);
+
     /// Converts this [`Work`] to [`Target`].
     pub fn to_target(self) -> Target { Target(self.0.inverse()) }
 
@@ -157,6 +219,16 @@ impl Sub for Work {
     fn sub(self, rhs: Self) -> Self { Self(self.0 - rhs.0) }
 }
 
+impl core::iter::Sum for Work {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |sum, work| sum + work)
+    }
+}
+
+impl<'a> core::iter::Sum<&'a Self> for Work {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self { iter.copied().sum() }
+}
+
 /// A 256 bit integer representing target.
 ///
 /// The SHA-256 hash of a block's header must be lower than or equal to the current target for the
@@ -353,6 +425,55 @@ pub fn difficulty_float(&self, params: impl AsRef<Params>) -> f64 {
         self.difficulty_float_with_max(&max)
     }
 
+    /// Computes the [`Target`] for the given `difficulty`, the inverse of
+    /// [`difficulty_float_with_max`].
+    ///
+    /// An `f64` carries 53 bits of precision, the result is therefore only exact to about 16
+    /// significant digits and most difficulties do not correspond exactly to a target. Converting
+    /// the difficulty of a block back may therefore yield a target slightly below the target of
+    /// the block, with a different lossy compact form. Difficulties below one produce targets
+    /// above `max_target`, saturating at the largest 256 bit value.
+    ///
+    /// Returns `None` if `difficulty` is not a positive finite number.
+    ///
+    /// [`difficulty_float_with_max`]: Target::difficulty_float_with_max
+    pub fn from_difficulty_with_max(difficulty: f64, max_target: &Self) -> Option<Self> {
+        if !(difficulty > 0.0 && difficulty.is_finite()) {
+            return None;
+        }
+        let target = max_target.0.to_f64() / difficulty;
+        Some(if target.is_finite() {
+            Self(U256::from_f64_saturating(target))
+        } else {
+            Self(U256::MAX)
+        })
+    }
+
+    /// Computes the [`Target`] for the given `difficulty`.
+    ///
+    /// This function calculates the target using the max attainable target set on the provided
+    /// [`Params`]. See [`Target::from_difficulty_with_max`] for details.
+    ///
+    /// Returns `None` if `difficulty` is not a positive finite number.
+    pub fn from_difficulty(difficulty: f64, params: impl AsRef<Params>) -> Option<Self> {
+        let max = params.as_ref().max_attainable_target;
+        Self::from_difficulty_with_max(difficulty, &max)
+    }
+
+    /// Returns the number of hashes expected to be computed to find a block meeting this target.
+    ///
+    /// This is the [`Work`] of the target as a float, meant for displaying and estimation.
+    pub fn expected_hashes_per_block(&self) -> f64 { self.to_work().0.to_f64() }
+
+    /// Estimates the hashrate, in hashes per second, needed to find a block meeting this target
+    /// every `block_interval` seconds on average.
+    ///
+    /// Applied to the target of recent blocks and the average time between them, this is the
+    /// usual estimate of the network hashrate.
+    pub fn estimated_hashrate(&self, block_interval: f64) -> f64 {
+        self.expected_hashes_per_block() / block_interval
+    }
+
     /// Computes the minimum valid [`Target`] threshold allowed for a block in which a difficulty
     /// adjustment occurs.
     #[deprecated(since = "0.32.0", note = "use `min_transition_threshold` instead")]
@@ -1009,6 +1130,27 @@ fn to_f64(self) -> f64 {
         // Use addition instead of bitwise OR to saturate the exponent if mantissa overflows
         f64::from_bits((exponent << 52) + mantissa)
     }
+
+    /// Converts a non-negative, finite `f64` to `U256`, truncating any fractional part.
+    ///
+    /// Values too large to fit are saturated to `U256::MAX`.
+    fn from_f64_saturating(value: f64) -> Self {
+        debug_assert!(value >= 0.0 && value.is_finite());
+        let bits = value.to_bits();
+        let biased_exponent = ((bits >> 52) & 0x7FF) as i32; // Cast ok, 11 bits.
+        if biased_exponent < 1023 {
+            return Self::ZERO; // Below one, including subnormals.
+        }
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let shift = biased_exponent - 1075;
+        if shift < 0 {
+            Self::from(mantissa >> -shift)
+        } else if shift + 53 > 256 {
+            Self::MAX
+        } else {
+            Self::from(mantissa) << shift as u32 // Cast ok, checked to be positive.
+        }
+    }
 }
 
 impl<T: Into<u128>> From<T> for U256 {
@@ -1305,7 +1447,7 @@ mod tests {
     use super::*;
     #[cfg(feature = "std")]
     use crate::pow::test_utils::u128_to_work;
-    use crate::pow::test_utils::{u32_to_target, u64_to_target};
+    use crate::pow::test_utils::{u32_to_target, u64_to_target, u64_to_work};
     use crate::BlockTime;
 
     impl U256 {
@@ -2321,6 +2463,33 @@ fn target_difficulty_float() {
         );
     }
 
+    #[test]
+    fn target_from_difficulty() {
+        let params = Params::new(crate::Network::Bitcoin);
+
+        assert_eq!(Target::from_difficulty(1.0, &params), Some(Target::MAX));
+        assert_eq!(Target::from_difficulty(0.5, &params), Some(Target(Target::MAX.0 << 1)));
+        assert_eq!(Target::from_difficulty(1e-80, &params), Some(Target(U256::MAX)));
+        for bits in [0x1c00ffff_u32, 0x1a00f3a2, 0x1d00ffff, 0x17034219, 0x1b0404cb, 0x1802aee8] {
+            let compact = CompactTarget::from_consensus(bits);
+            let difficulty = Target::from_compact(compact).difficulty_float(&params);
+            let target = Target::from_difficulty(difficulty, &params).unwrap();
+            let error = (target.difficulty_float(&params) - difficulty) / difficulty;
+            assert!(error.abs() < 1e-15, "{:x}", bits);
+        }
+
+        let target = Target::from_compact(CompactTarget::from_consensus(0x1d00ffff));
+        assert_eq!(target.expected_hashes_per_block(), 4295032833.0);
+        assert_eq!(target.estimated_hashrate(600.0), 4295032833.0 / 600.0);
+    }
+
+    #[test]
+    fn target_from_invalid_difficulty() {
+        for difficulty in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(Target::from_difficulty(difficulty, crate::Network::Bitcoin), None);
+        }
+    }
+
     #[test]
     fn roundtrip_compact_target() {
         let consensus = 0x1d00_ffff;
@@ -2342,6 +2511,35 @@ fn roundtrip_target_work() {
         assert_eq!(back, target)
     }
 
+    #[test]
+    fn work_checked_arithmetic() {
+        let work = u64_to_work(1000);
+        assert_eq!(work.checked_add(work), Some(u64_to_work(2000)));
+        assert_eq!(Work(U256::MAX).checked_add(u64_to_work(1)), None);
+        assert_eq!(work.checked_sub(u64_to_work(1)), Some(u64_to_work(999)));
+        assert_eq!(Work::ZERO.checked_sub(u64_to_work(1)), None);
+        assert_eq!(work.checked_mul(3), Some(u64_to_work(3000)));
+        assert_eq!(Work(U256::MAX >> 1).checked_mul(3), None);
+        assert_eq!(work.checked_div(3), Some(u64_to_work(333)));
+        assert_eq!(work.checked_div(0), None);
+
+        assert_eq!(work.checked_shl(2), Some(u64_to_work(4000)));
+        assert_eq!(u64_to_work(1).checked_shl(255), Some(Work(U256::ONE << 255)));
+        assert_eq!(u64_to_work(1).checked_shl(256), None);
+        assert_eq!(u64_to_work(2).checked_shl(255), None);
+        assert_eq!(Work::ZERO.checked_shl(300), Some(Work::ZERO));
+        assert_eq!(work.checked_shr(3), Some(u64_to_work(125)));
+        assert_eq!(work.checked_shr(256), None);
+
+        let target = u32_to_target(0xdeadbeef);
+        assert_eq!(target.checked_mul(2), Some(u64_to_target(2 * 0xdeadbeef)));
+
+        let works = [u64_to_work(1), u64_to_work(2), u64_to_work(3)];
+        assert_eq!(works.iter().sum::<Work>(), u64_to_work(6));
+        assert_eq!(works.into_iter().sum::<Work>(), u64_to_work(6));
+        assert_eq!(core::iter::empty::<Work>().sum::<Work>(), Work::ZERO);
+    }
+
     #[test]
     #[cfg(feature = "std")]
     fn work_log2() {
@@ -0,0 +1,487 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Creating PSBTs.
+//!
+//! [`create`] builds the unsigned transaction spending a set of [`Utxo`]s to a list of outputs and
+//! fills in what signers need: the spent outputs or transactions, and the key origins of the inputs
+//! and outputs paying to the [`Descriptor`]s of the wallet.
+
+use core::convert::Infallible;
+use core::fmt;
+use core::ops::Range;
+
+use internals::write_err;
+
+use crate::bip32::{ChildNumber, DerivationError, KeySource, Xpub};
+use crate::descriptor::{Descriptor, ScriptType};
+use crate::locktime::absolute;
+use crate::network::Network;
+use crate::prelude::Vec;
+use crate::psbt::{Input, Output, Psbt};
+use crate::script::{RedeemScriptBuf, ScriptBufExt as _, ScriptPubKeyBuf, ScriptPubKeyExt as _};
+use crate::transaction::{self, Transaction, TxIn, TxOut, Utxo};
+use crate::{Amount, Sequence};
+
+/// The parameters of a PSBT created by [`create`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreateParams {
+    /// The version of the transaction.
+    pub version: transaction::Version,
+    /// The lock time of the transaction.
+    pub lock_time: absolute::LockTime,
+    /// Whether the transaction signals replaceability (BIP-0125).
+    pub rbf: bool,
+    /// The descriptors of the wallet, each with the range of indices of its keys to look for.
+    ///
+    /// Inputs and outputs paying to the address at one of these indices get the key origins of
+    /// the key. The global `xpub` map gets the key of every descriptor.
+    pub descriptors: Vec<(Descriptor, Range<u32>)>,
+    /// The transactions creating the outputs that are spent.
+    ///
+    /// Inputs spending a legacy output, which is neither SegWit nor P2SH, need the transaction
+    /// that created it for their `non_witness_utxo`. Other inputs get it if it is given.
+    pub previous_txs: Vec<Transaction>,
+}
+
+impl CreateParams {
+    /// Constructs new parameters for a version 2 transaction without a lock time, signalling
+    /// replaceability and without descriptors or previous transactions.
+    pub fn new() -> Self {
+        Self {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            rbf: true,
+            descriptors: Vec::new(),
+            previous_txs: Vec::new(),
+        }
+    }
+
+    /// Returns the sequence number of the inputs.
+    ///
+    /// Both sequence numbers enable the lock time.
+    fn sequence(&self) -> Sequence {
+        if self.rbf {
+            Sequence::ENABLE_LOCKTIME_AND_RBF
+        } else {
+            Sequence::ENABLE_LOCKTIME_NO_RBF
+        }
+    }
+}
+
+impl Default for CreateParams {
+    fn default() -> Self { Self::new() }
+}
+
+/// Creates a PSBT spending `inputs` to `outputs`.
+///
+/// The transaction has the inputs and outputs in the given order, the fee is whatever the inputs
+/// have left after paying the outputs. Each input spending a SegWit or P2SH output gets its
+/// `witness_utxo`, inputs whose previous transaction is one of the [`CreateParams::previous_txs`]
+/// get their `non_witness_utxo`. Inputs and outputs paying to a key of one of the
+/// [`CreateParams::descriptors`] get their `bip32_derivation` or `tap_internal_key` and
+/// `tap_key_origins` and the `redeem_script` of nested SegWit.
+///
+/// # Errors
+///
+/// If there are no inputs or outputs, the outputs spend more than the inputs, an input spending a
+/// legacy output has no previous transaction, a previous transaction does not create the output
+/// an input spends or a key of the descriptors cannot be derived.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin::psbt::{self, CreateParams};
+/// use bitcoin::{Address, Amount, OutPoint, TxOut, Utxo};
+///
+/// let address = "bc1qvzvkjn4q3nszqxrv3nraga2r822xjty3ykvkuw".parse::<Address<_>>()?.assume_checked();
+/// let utxo = Utxo {
+///     out_point: OutPoint::COINBASE_PREVOUT,
+///     output: TxOut { amount: Amount::from_sat(100_000)?, script_pubkey: address.script_pubkey() },
+/// };
+/// let outputs = [(address, Amount::from_sat(99_000)?)];
+///
+/// let psbt = psbt::create(&outputs, &[utxo], &CreateParams::new())?;
+/// assert_eq!(psbt.fee()?, Amount::from_sat(1_000)?);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn create<S>(
+    outputs: &[(S, Amount)],
+    inputs: &[Utxo],
+    params: &CreateParams,
+) -> Result<Psbt, CreateError>
+where
+    S: Clone + Into<ScriptPubKeyBuf>,
+{
+    if inputs.is_empty() {
+        return Err(CreateError::NoInputs);
+    }
+    if outputs.is_empty() {
+        return Err(CreateError::NoOutputs);
+    }
+
+    let mut input_amount = Amount::ZERO;
+    for utxo in inputs {
+        input_amount =
+            input_amount.checked_add(utxo.output.amount).ok_or(CreateError::AmountOverflow)?;
+    }
+    let mut output_amount = Amount::ZERO;
+    for (_, amount) in outputs {
+        output_amount = output_amount.checked_add(*amount).ok_or(CreateError::AmountOverflow)?;
+    }
+    if output_amount > input_amount {
+        return Err(CreateError::InsufficientFunds {
+            inputs: input_amount,
+            outputs: output_amount,
+        });
+    }
+
+    let sequence = params.sequence();
+    let tx = Transaction {
+        version: params.version,
+        lock_time: params.lock_time,
+        inputs: inputs
+            .iter()
+            .map(|utxo| TxIn { previous_output: utxo.out_point, sequence, ..TxIn::EMPTY_COINBASE })
+            .collect(),
+        outputs: outputs
+            .iter()
+            .map(|(script_pubkey, amount)| TxOut {
+                amount: *amount,
+                script_pubkey: script_pubkey.clone().into(),
+            })
+            .collect(),
+    };
+    let mut psbt = Psbt::from_unsigned_tx(tx).expect("script sigs and witnesses are empty");
+    let txids = params.previous_txs.iter().map(|tx| tx.compute_txid()).collect::<Vec<_>>();
+    for (index, (input, utxo)) in psbt.inputs.iter_mut().zip(inputs).enumerate() {
+        let script_pubkey = &utxo.output.script_pubkey;
+        let legacy = !script_pubkey.is_witness_program() && !script_pubkey.is_p2sh();
+        match txids.iter().position(|txid| *txid == utxo.out_point.txid) {
+            Some(i) => {
+                let tx = &params.previous_txs[i];
+                let vout = usize::try_from(utxo.out_point.vout).ok();
+                if vout.and_then(|vout| tx.outputs.get(vout)) != Some(&utxo.output) {
+                    return Err(CreateError::PreviousTransactionMismatch { index });
+                }
+                input.non_witness_utxo = Some(tx.clone());
+            }
+            None if legacy => return Err(CreateError::MissingPreviousTransaction { index }),
+            None => {}
+        }
+        if !legacy {
+            input.witness_utxo = Some(utxo.output.clone());
+        }
+    }
+
+    for (descriptor, range) in &params.descriptors {
+        let key = descriptor.key();
+        psbt.xpub.insert(*key.xpub(), key.key_source());
+
+        // The script pubkey of an address does not depend on the network.
+        for (index, address) in descriptor.addresses(Network::Bitcoin, range.clone())? {
+            let script_pubkey = address.script_pubkey();
+            let spent = inputs.iter().any(|utxo| utxo.output.script_pubkey == script_pubkey);
+            let paid =
+                psbt.unsigned_tx.outputs.iter().any(|txout| txout.script_pubkey == script_pubkey);
+            if !spent && !paid {
+                continue;
+            }
+            let origin = KeyOrigin::derive(descriptor, index)?;
+            for (input, utxo) in psbt.inputs.iter_mut().zip(inputs) {
+                if utxo.output.script_pubkey == script_pubkey {
+                    origin.add_to_input(input);
+                }
+            }
+            for (output, txout) in psbt.outputs.iter_mut().zip(&psbt.unsigned_tx.outputs) {
+                if txout.script_pubkey == script_pubkey {
+                    origin.add_to_output(output);
+                }
+            }
+        }
+    }
+    Ok(psbt)
+}
+
+/// The key of a descriptor at an index, with its origin.
+struct KeyOrigin {
+    script_type: ScriptType,
+    xpub: Xpub,
+    key_source: KeySource,
+}
+
+impl KeyOrigin {
+    fn derive(descriptor: &Descriptor, index: u32) -> Result<Self, DerivationError> {
+        let child = ChildNumber::from_normal_idx(index)
+            .map_err(|_| DerivationError::CannotDeriveHardenedChild)?;
+        let (xpub, key_source) = descriptor.key().derive(descriptor.branch().child(child))?;
+        Ok(Self { script_type: descriptor.script_type(), xpub, key_source })
+    }
+
+    /// Returns the redeem script of nested SegWit.
+    fn redeem_script(&self) -> Option<RedeemScriptBuf> {
+        match self.script_type {
+            ScriptType::ShWpkh =>
+                Some(RedeemScriptBuf::new_p2wpkh(self.xpub.to_public_key().wpubkey_hash())),
+            _ => None,
+        }
+    }
+
+    fn add_to_input(&self, input: &mut Input) {
+        if self.script_type == ScriptType::Tr {
+            let x_only = self.xpub.to_x_only_public_key();
+            input.tap_internal_key = Some(x_only);
+            input.tap_key_origins.insert(x_only, (Vec::new(), self.key_source.clone()));
+        } else {
+            input.bip32_derivation.insert(self.xpub.public_key, self.key_source.clone());
+            input.redeem_script = self.redeem_script();
+        }
+    }
+
+    fn add_to_output(&self, output: &mut Output) {
+        if self.script_type == ScriptType::Tr {
+            let x_only = self.xpub.to_x_only_public_key();
+            output.tap_internal_key = Some(x_only);
+            output.tap_key_origins.insert(x_only, (Vec::new(), self.key_source.clone()));
+        } else {
+            output.bip32_derivation.insert(self.xpub.public_key, self.key_source.clone());
+            output.redeem_script = self.redeem_script();
+        }
+    }
+}
+
+/// Error creating a PSBT, see [`create`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CreateError {
+    /// There are no inputs.
+    NoInputs,
+    /// There are no outputs.
+    NoOutputs,
+    /// The sum of the input or output amounts overflows.
+    AmountOverflow,
+    /// The outputs spend more than the inputs.
+    InsufficientFunds {
+        /// The sum of the input amounts.
+        inputs: Amount,
+        /// The sum of the output amounts.
+        outputs: Amount,
+    },
+    /// An input spends a legacy output but its previous transaction is not given.
+    MissingPreviousTransaction {
+        /// The index of the input.
+        index: usize,
+    },
+    /// The previous transaction of an input does not create the output it spends.
+    PreviousTransactionMismatch {
+        /// The index of the input.
+        index: usize,
+    },
+    /// A key of a descriptor cannot be derived.
+    Derivation(DerivationError),
+}
+
+impl From<Infallible> for CreateError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for CreateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::NoInputs => write!(f, "the transaction has no inputs"),
+            Self::NoOutputs => write!(f, "the transaction has no outputs"),
+            Self::AmountOverflow => write!(f, "the sum of the amounts overflows"),
+            Self::InsufficientFunds { inputs, outputs } => write!(
+                f,
+                "the outputs spend {} but the inputs only have {}",
+                outputs.display_dynamic(),
+                inputs.display_dynamic()
+            ),
+            Self::MissingPreviousTransaction { index } => {
+                write!(f, "input {} spends a legacy output without its previous transaction", index)
+            }
+            Self::PreviousTransactionMismatch { index } => write!(
+                f,
+                "the previous transaction of input {} does not create the output it spends",
+                index
+            ),
+            Self::Derivation(ref e) => write_err!(f, "cannot derive a key of a descriptor"; e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CreateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::Derivation(ref e) => Some(e),
+            Self::NoInputs
+            | Self::NoOutputs
+            | Self::AmountOverflow
+            | Self::InsufficientFunds { .. }
+            | Self::MissingPreviousTransaction { .. }
+            | Self::PreviousTransactionMismatch { .. } => None,
+        }
+    }
+}
+
+impl From<DerivationError> for CreateError {
+    fn from(e: DerivationError) -> Self { Self::Derivation(e) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32::Xpriv;
+    use crate::hww::DeviceKey;
+    use crate::prelude::ToString;
+    use crate::transaction::OutPoint;
+    use crate::{Address, Txid};
+
+    /// The master key of the `abandon abandon ... about` mnemonic.
+    const MASTER: &str = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+    fn descriptor(script_type: ScriptType, account: &str, branch: &str) -> Descriptor {
+        let master = MASTER.parse::<Xpriv>().unwrap();
+        let key = DeviceKey::from_master(&master, account.parse().unwrap()).unwrap();
+        Descriptor::new(script_type, key, branch.parse().unwrap())
+    }
+
+    /// Returns a transaction paying `sat` to the address of `descriptor` at `index`.
+    fn previous_tx(descriptor: &Descriptor, index: u32, sat: u64) -> Transaction {
+        let address = descriptor.address_at(index, Network::Bitcoin).unwrap();
+        Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn::EMPTY_COINBASE],
+            outputs: vec![TxOut {
+                amount: Amount::from_sat(sat).unwrap(),
+                script_pubkey: address.script_pubkey(),
+            }],
+        }
+    }
+
+    fn utxo(vout: u32, descriptor: &Descriptor, index: u32, sat: u64) -> Utxo {
+        let address = descriptor.address_at(index, Network::Bitcoin).unwrap();
+        Utxo {
+            out_point: OutPoint { txid: Txid::from_byte_array([1; 32]), vout },
+            output: TxOut {
+                amount: Amount::from_sat(sat).unwrap(),
+                script_pubkey: address.script_pubkey(),
+            },
+        }
+    }
+
+    #[test]
+    fn create_and_sign() {
+        let wpkh = descriptor(ScriptType::Wpkh, "84h/0h/0h", "0");
+        let wpkh_change = descriptor(ScriptType::Wpkh, "84h/0h/0h", "1");
+        let sh_wpkh = descriptor(ScriptType::ShWpkh, "49h/0h/0h", "0");
+        let tr = descriptor(ScriptType::Tr, "86h/0h/0h", "0");
+        let pkh = descriptor(ScriptType::Pkh, "44h/0h/0h", "0");
+        let previous_tx = previous_tx(&pkh, 2, 10_000);
+        let inputs = [
+            utxo(0, &wpkh, 3, 50_000),
+            utxo(1, &sh_wpkh, 0, 30_000),
+            utxo(2, &tr, 1, 20_000),
+            Utxo::from_tx(&previous_tx, 0).unwrap(),
+        ];
+        let payee = "bc1qvzvkjn4q3nszqxrv3nraga2r822xjty3ykvkuw"
+            .parse::<Address<_>>()
+            .unwrap()
+            .assume_checked()
+            .script_pubkey();
+        let change = wpkh_change.address_at(7, Network::Bitcoin).unwrap().script_pubkey();
+        let outputs = [
+            (payee, Amount::from_sat(60_000).unwrap()),
+            (change, Amount::from_sat(49_000).unwrap()),
+        ];
+
+        let mut params = CreateParams::new();
+        params.lock_time = absolute::LockTime::from_consensus(800_000);
+        params.descriptors =
+            vec![(wpkh, 0..10), (wpkh_change, 0..10), (sh_wpkh, 0..10), (tr, 0..10), (pkh, 0..10)];
+        params.previous_txs = vec![previous_tx];
+        let mut psbt = create(&outputs, &inputs, &params).unwrap();
+
+        assert_eq!(psbt.fee().unwrap(), Amount::from_sat(1_000).unwrap());
+        assert_eq!(psbt.unsigned_tx.lock_time, params.lock_time);
+        assert!(psbt.unsigned_tx.inputs.iter().all(|txin| txin.sequence.is_rbf()));
+        assert_eq!(psbt.xpub.len(), 4);
+
+        assert_eq!(psbt.inputs[0].bip32_derivation.len(), 1);
+        assert_eq!(
+            psbt.inputs[0].bip32_derivation.values().next().unwrap().1.to_string(),
+            "84'/0'/0'/0/3"
+        );
+        assert!(psbt.inputs[0].redeem_script.is_none());
+        assert_eq!(psbt.inputs[1].bip32_derivation.len(), 1);
+        assert!(psbt.inputs[1].redeem_script.is_some());
+        assert!(psbt.inputs[2].tap_internal_key.is_some());
+        assert_eq!(psbt.inputs[2].tap_key_origins.len(), 1);
+        assert!(psbt.inputs[..3].iter().all(|input| input.witness_utxo.is_some()));
+        assert!(psbt.inputs[..3].iter().all(|input| input.non_witness_utxo.is_none()));
+        assert_eq!(psbt.inputs[3].non_witness_utxo, Some(params.previous_txs[0].clone()));
+        assert!(psbt.inputs[3].witness_utxo.is_none());
+        assert_eq!(
+            psbt.inputs[3].bip32_derivation.values().next().unwrap().1.to_string(),
+            "44'/0'/0'/0/2"
+        );
+        assert!(psbt.outputs[0].bip32_derivation.is_empty());
+        assert_eq!(
+            psbt.outputs[1].bip32_derivation.values().next().unwrap().1.to_string(),
+            "84'/0'/0'/1/7"
+        );
+
+        let master = MASTER.parse::<Xpriv>().unwrap();
+        let signed = psbt.sign(&master).unwrap();
+        assert_eq!(signed.len(), 4);
+    }
+
+    #[test]
+    fn create_errors() {
+        let wpkh = descriptor(ScriptType::Wpkh, "84h/0h/0h", "0");
+        let inputs = [utxo(0, &wpkh, 0, 1_000)];
+        let script_pubkey = inputs[0].output.script_pubkey.clone();
+        let params = CreateParams::new();
+
+        let outputs = [(script_pubkey.clone(), Amount::from_sat(1_001).unwrap())];
+        assert_eq!(
+            create(&outputs, &inputs, &params),
+            Err(CreateError::InsufficientFunds {
+                inputs: Amount::from_sat(1_000).unwrap(),
+                outputs: Amount::from_sat(1_001).unwrap(),
+            })
+        );
+        assert_eq!(create(&outputs, &[], &params), Err(CreateError::NoInputs));
+        assert_eq!(create::<ScriptPubKeyBuf>(&[], &inputs, &params), Err(CreateError::NoOutputs));
+
+        let outputs = [(script_pubkey, Amount::from_sat(1_000).unwrap())];
+        let mut params = CreateParams::new();
+        params.rbf = false;
+        params.descriptors = vec![(wpkh, 0x8000_0000..0x8000_0001)];
+        assert_eq!(
+            create(&outputs, &inputs, &params),
+            Err(CreateError::Derivation(DerivationError::CannotDeriveHardenedChild))
+        );
+
+        params.descriptors.clear();
+        let psbt = create(&outputs, &inputs, &params).unwrap();
+        assert_eq!(psbt.unsigned_tx.inputs[0].sequence, Sequence::ENABLE_LOCKTIME_NO_RBF);
+
+        let pkh = descriptor(ScriptType::Pkh, "44h/0h/0h", "0");
+        let previous_tx = previous_tx(&pkh, 0, 1_000);
+        let inputs = [Utxo::from_tx(&previous_tx, 0).unwrap()];
+        assert_eq!(
+            create(&outputs, &inputs, &params),
+            Err(CreateError::MissingPreviousTransaction { index: 0 })
+        );
+        // The previous transaction has no output at index 1.
+        let inputs =
+            [Utxo { out_point: OutPoint { vout: 1, ..inputs[0].out_point }, ..inputs[0].clone() }];
+        params.previous_txs = vec![previous_tx];
+        assert_eq!(
+            create(&outputs, &inputs, &params),
+            Err(CreateError::PreviousTransactionMismatch { index: 0 })
+        );
+    }
+}
@@ -0,0 +1,640 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Finalization of PSBT inputs.
+//!
+//! Implements the finalizer role of BIP-0174 for Taproot inputs spent by the key path or by leaf
+//! scripts of the `pk` and `multi_a` templates, and reports what is still required to finalize
+//! the inputs of a PSBT.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use crate::crypto::key::{PubkeyHash, PublicKey, XOnlyPublicKey};
+use crate::opcodes::all::*;
+use crate::prelude::{BTreeMap, Vec};
+use crate::psbt::{Input, Psbt};
+use crate::script::{
+    Instruction, Script, ScriptExt as _, ScriptPubKey, ScriptPubKeyExt as _, TapScript,
+};
+use crate::taproot::{ControlBlock, LeafVersion, TapLeafHash};
+use crate::witness::Witness;
+use crate::witness_program::WitnessProgramKind;
+
+impl Psbt {
+    /// Returns what is still required to finalize each input, indexed by input.
+    ///
+    /// An empty list means the input has all it needs to be finalized or is already finalized.
+    /// All requirements of an input have to be met, except for Taproot inputs: those list the
+    /// alternative ways to spend them, the key path signature and the signatures for each leaf
+    /// script, and meeting any one of them is enough.
+    ///
+    /// Scripts are recognized if they are a single key checked with `OP_CHECKSIG`, a bare
+    /// `OP_CHECKMULTISIG` or, for Taproot leaves, the `multi_a` template. Leaf scripts of other
+    /// forms are not listed.
+    pub fn missing_requirements(&self) -> Vec<Vec<Requirement>> {
+        (0..self.inputs.len()).map(|index| self.input_requirements(index)).collect()
+    }
+
+    /// Returns what is still required to finalize the input at `index`.
+    fn input_requirements(&self, index: usize) -> Vec<Requirement> {
+        let input = &self.inputs[index];
+        if input.final_script_sig.is_some() || input.final_script_witness.is_some() {
+            return Vec::new();
+        }
+        match self.spend_utxo(index) {
+            Ok(utxo) => input.requirements(&utxo.script_pubkey),
+            Err(_) => vec![Requirement::SpentOutput],
+        }
+    }
+}
+
+impl Input {
+    /// Finalizes a Taproot input, setting `final_script_witness`.
+    ///
+    /// A key path signature is always used if present. Otherwise the leaf scripts in
+    /// `tap_scripts` that can be satisfied with the signatures in `tap_script_sigs` are
+    /// considered and the one with the smallest witness is used. Supported leaf scripts are
+    /// `<key> OP_CHECKSIG` and the `multi_a` template `<key> OP_CHECKSIG (<key>
+    /// OP_CHECKSIGADD)* <k> OP_NUMEQUAL`.
+    ///
+    /// As required by BIP-0371 the signatures, scripts and key origins are removed afterwards.
+    ///
+    /// # Errors
+    ///
+    /// If the input has neither a key path signature nor leaf scripts, or none of the leaf
+    /// scripts can be satisfied. In the latter case the error lists the reason for each leaf.
+    pub fn finalize_taproot(&mut self) -> Result<(), TaprootFinalizeError> {
+        let witness = match self.tap_key_sig {
+            Some(sig) => Witness::from_slice(&[sig.to_vec()]),
+            None => self.cheapest_leaf_witness()?,
+        };
+        self.final_script_witness = Some(witness);
+
+        self.partial_sigs.clear();
+        self.sighash_type = None;
+        self.redeem_script = None;
+        self.witness_script = None;
+        self.bip32_derivation.clear();
+        self.tap_key_sig = None;
+        self.tap_script_sigs.clear();
+        self.tap_scripts.clear();
+        self.tap_key_origins.clear();
+        self.tap_internal_key = None;
+        self.tap_merkle_root = None;
+        Ok(())
+    }
+
+    /// Returns the smallest witness satisfying one of the leaf scripts.
+    fn cheapest_leaf_witness(&self) -> Result<Witness, TaprootFinalizeError> {
+        if self.tap_scripts.is_empty() {
+            return Err(TaprootFinalizeError::MissingSpendInfo);
+        }
+        let mut failures = Vec::new();
+        let mut best: Option<Witness> = None;
+        for (control_block, (script, leaf_version)) in &self.tap_scripts {
+            let leaf_hash = TapLeafHash::from_script(script, *leaf_version);
+            match self.leaf_witness(control_block, script, *leaf_version, leaf_hash) {
+                Ok(witness) =>
+                    if best.as_ref().map_or(true, |best| witness.size() < best.size()) {
+                        best = Some(witness);
+                    },
+                Err(e) => failures.push((leaf_hash, e)),
+            }
+        }
+        best.ok_or(TaprootFinalizeError::NoSatisfiableLeaf(failures))
+    }
+
+    /// Returns the witness satisfying a single leaf script.
+    fn leaf_witness(
+        &self,
+        control_block: &ControlBlock,
+        script: &TapScript,
+        leaf_version: LeafVersion,
+        leaf_hash: TapLeafHash,
+    ) -> Result<Witness, LeafError> {
+        if leaf_version != LeafVersion::TapScript {
+            return Err(LeafError::UnsupportedLeafVersion(leaf_version));
+        }
+        let (keys, threshold) = multi_a_keys(script).ok_or(LeafError::UnknownScript)?;
+
+        // Keys are checked in script order, their signatures are consumed from the top of the
+        // stack. Exactly `threshold` signatures must be provided, the others are left empty.
+        let sigs = self
+            .tap_script_sigs
+            .iter()
+            .filter(|((_, hash), _)| *hash == leaf_hash)
+            .map(|((key, _), sig)| (*key, sig))
+            .collect::<BTreeMap<_, _>>();
+        let mut witness = Witness::new();
+        let mut used = 0;
+        for key in keys.iter().rev() {
+            match sigs.get(key) {
+                Some(sig) if used < threshold => {
+                    witness.push(sig.to_vec());
+                    used += 1;
+                }
+                _ => witness.push([]),
+            }
+        }
+        if used < threshold {
+            let available = keys.iter().filter(|key| sigs.contains_key(key)).count();
+            return Err(LeafError::MissingSignatures { required: threshold, available });
+        }
+        witness.push(script.as_bytes());
+        witness.push(control_block.serialize());
+        Ok(witness)
+    }
+
+    /// Returns what is still required to spend an output with `script_pubkey`.
+    fn requirements(&self, script_pubkey: &ScriptPubKey) -> Vec<Requirement> {
+        match WitnessProgramKind::from_script_pubkey(script_pubkey) {
+            Some(WitnessProgramKind::V0Keyhash(hash)) => self
+                .key_hash_requirements(PubkeyHash::from_byte_array(hash.to_byte_array()), |key| {
+                    key.wpubkey_hash().ok() == Some(hash)
+                }),
+            Some(WitnessProgramKind::V0Scripthash(_)) => match self.witness_script {
+                Some(ref witness_script) => self.script_requirements(witness_script),
+                None => vec![Requirement::WitnessScript],
+            },
+            Some(WitnessProgramKind::V1Taproot(_)) => self.taproot_requirements(),
+            Some(_) => vec![Requirement::UnknownScript],
+            None if script_pubkey.is_p2sh() => match self.redeem_script {
+                Some(ref redeem_script) => {
+                    let nested = ScriptPubKey::from_bytes(redeem_script.as_bytes());
+                    if nested.is_p2wpkh() || nested.is_p2wsh() {
+                        self.requirements(nested)
+                    } else {
+                        self.script_requirements(redeem_script)
+                    }
+                }
+                None => vec![Requirement::RedeemScript],
+            },
+            None if script_pubkey.is_p2pkh() => {
+                let hash = script_pubkey.as_bytes()[3..23].try_into().expect("checked length");
+                let hash = PubkeyHash::from_byte_array(hash);
+                self.key_hash_requirements(hash, |key| key.pubkey_hash() == hash)
+            }
+            None => self.script_requirements(script_pubkey),
+        }
+    }
+
+    /// Returns the signature required to spend a key hash output, if it is missing.
+    ///
+    /// The key is looked up in the key origins if there is no signature yet.
+    fn key_hash_requirements(
+        &self,
+        hash: PubkeyHash,
+        matches: impl Fn(&PublicKey) -> bool,
+    ) -> Vec<Requirement> {
+        if self.partial_sigs.keys().any(&matches) {
+            return Vec::new();
+        }
+        let key = self.bip32_derivation.keys().map(|&key| PublicKey::new(key)).find(matches);
+        match key {
+            Some(key) => vec![Requirement::Signatures { count: 1, keys: vec![key] }],
+            None => vec![Requirement::UnknownKey(hash)],
+        }
+    }
+
+    /// Returns the signatures missing to satisfy the ECDSA `script`.
+    fn script_requirements<T>(&self, script: &Script<T>) -> Vec<Requirement> {
+        let Some((keys, threshold)) = multisig_keys(script) else {
+            return vec![Requirement::UnknownScript];
+        };
+        let (signed, unsigned) =
+            keys.into_iter().partition::<Vec<_>, _>(|key| self.partial_sigs.contains_key(key));
+        if signed.len() >= threshold {
+            return Vec::new();
+        }
+        vec![Requirement::Signatures { count: threshold - signed.len(), keys: unsigned }]
+    }
+
+    /// Returns the signatures missing for the key path and for each supported leaf script, or
+    /// nothing if one of them is complete.
+    fn taproot_requirements(&self) -> Vec<Requirement> {
+        if self.tap_key_sig.is_some() {
+            return Vec::new();
+        }
+        let mut requirements = vec![Requirement::TapKeySignature];
+        if self.tap_scripts.is_empty() {
+            requirements.push(Requirement::ControlBlock);
+        }
+        for (script, leaf_version) in self.tap_scripts.values() {
+            if *leaf_version != LeafVersion::TapScript {
+                continue;
+            }
+            let Some((keys, threshold)) = multi_a_keys(script) else { continue };
+            let leaf_hash = TapLeafHash::from_script(script, *leaf_version);
+            let (signed, unsigned) = keys.into_iter().partition::<Vec<_>, _>(|key| {
+                self.tap_script_sigs.contains_key(&(*key, leaf_hash))
+            });
+            if signed.len() >= threshold {
+                return Vec::new();
+            }
+            requirements.push(Requirement::TapScriptSignatures {
+                leaf_hash,
+                count: threshold - signed.len(),
+                keys: unsigned,
+            });
+        }
+        requirements
+    }
+}
+
+/// Returns the keys and the signature threshold of a `pk` or bare multisig script.
+fn multisig_keys<T>(script: &Script<T>) -> Option<(Vec<PublicKey>, usize)> {
+    let instructions = script.instructions().collect::<Result<Vec<_>, _>>().ok()?;
+    match instructions[..] {
+        [Instruction::PushBytes(key), Instruction::Op(OP_CHECKSIG)] =>
+            Some((vec![PublicKey::from_slice(key.as_bytes()).ok()?], 1)),
+        [Instruction::Op(threshold), ref keys @ .., Instruction::Op(count), Instruction::Op(OP_CHECKMULTISIG)] =>
+        {
+            let threshold = usize::from(threshold.decode_pushnum()?);
+            let keys = keys
+                .iter()
+                .map(|key| match key {
+                    Instruction::PushBytes(key) => PublicKey::from_slice(key.as_bytes()).ok(),
+                    Instruction::Op(_) => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            if usize::from(count.decode_pushnum()?) != keys.len() || threshold > keys.len() {
+                return None;
+            }
+            Some((keys, threshold))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the keys and the signature threshold of a `pk` or `multi_a` leaf script.
+fn multi_a_keys(script: &TapScript) -> Option<(Vec<XOnlyPublicKey>, usize)> {
+    let instructions = script.instructions().collect::<Result<Vec<_>, _>>().ok()?;
+    let (key_checks, threshold) = match instructions[..] {
+        [Instruction::PushBytes(_), Instruction::Op(OP_CHECKSIG)] => (&instructions[..], 1),
+        [ref key_checks @ .., threshold, Instruction::Op(OP_NUMEQUAL)] => {
+            let threshold = match threshold {
+                Instruction::Op(op) => usize::from(op.decode_pushnum()?),
+                Instruction::PushBytes(bytes) =>
+                    usize::try_from(bytes.read_scriptint().ok()?).ok()?,
+            };
+            (key_checks, threshold)
+        }
+        _ => return None,
+    };
+    if key_checks.is_empty() || key_checks.len() % 2 != 0 {
+        return None;
+    }
+
+    let mut keys = Vec::with_capacity(key_checks.len() / 2);
+    for (i, pair) in key_checks.chunks_exact(2).enumerate() {
+        let expected = if i == 0 { OP_CHECKSIG } else { OP_CHECKSIGADD };
+        match pair {
+            [Instruction::PushBytes(key), Instruction::Op(op)] if *op == expected => {
+                let key = key.as_bytes().try_into().ok()?;
+                keys.push(XOnlyPublicKey::from_byte_array(key).ok()?);
+            }
+            _ => return None,
+        }
+    }
+    if threshold == 0 || threshold > keys.len() {
+        return None;
+    }
+    Some((keys, threshold))
+}
+
+/// Something still required to finalize a PSBT input, see [`Psbt::missing_requirements`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Requirement {
+    /// The output spent by the input, `witness_utxo` or `non_witness_utxo`.
+    SpentOutput,
+    /// The redeem script of a P2SH output.
+    RedeemScript,
+    /// The witness script of a P2WSH output.
+    WitnessScript,
+    /// Signatures by some of the keys.
+    Signatures {
+        /// The number of signatures still required.
+        count: usize,
+        /// The keys that have not signed yet.
+        keys: Vec<PublicKey>,
+    },
+    /// A signature by the key with this hash, the key itself is not part of the PSBT.
+    ///
+    /// For P2WPKH outputs this is the same HASH160 of the key as for P2PKH ones.
+    UnknownKey(PubkeyHash),
+    /// The script is not one of the supported templates.
+    UnknownScript,
+    /// The signature for a Taproot key path spend.
+    TapKeySignature,
+    /// A leaf script with its control block, to spend a Taproot output by the script path.
+    ControlBlock,
+    /// Signatures for a Taproot leaf script.
+    TapScriptSignatures {
+        /// The hash of the leaf.
+        leaf_hash: TapLeafHash,
+        /// The number of signatures still required.
+        count: usize,
+        /// The keys of the leaf that have not signed yet.
+        keys: Vec<XOnlyPublicKey>,
+    },
+}
+
+/// Error finalizing a Taproot input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TaprootFinalizeError {
+    /// The input has neither a key path signature nor leaf scripts.
+    MissingSpendInfo,
+    /// None of the leaf scripts can be satisfied, the reason for each leaf.
+    NoSatisfiableLeaf(Vec<(TapLeafHash, LeafError)>),
+}
+
+impl From<Infallible> for TaprootFinalizeError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for TaprootFinalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::MissingSpendInfo =>
+                f.write_str("neither a key path signature nor leaf scripts are present"),
+            Self::NoSatisfiableLeaf(ref failures) => {
+                f.write_str("no leaf script can be satisfied")?;
+                for (leaf_hash, e) in failures {
+                    write!(f, ", leaf {}: {}", leaf_hash, e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TaprootFinalizeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::MissingSpendInfo | Self::NoSatisfiableLeaf(_) => None,
+        }
+    }
+}
+
+/// The reason a leaf script cannot be satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LeafError {
+    /// The leaf is not a tapscript leaf.
+    UnsupportedLeafVersion(LeafVersion),
+    /// The script is not one of the supported templates.
+    UnknownScript,
+    /// Not enough signatures are present.
+    MissingSignatures {
+        /// The number of signatures the script requires.
+        required: usize,
+        /// The number of signatures present.
+        available: usize,
+    },
+}
+
+impl From<Infallible> for LeafError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for LeafError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::UnsupportedLeafVersion(version) =>
+                write!(f, "unsupported leaf version {}", version),
+            Self::UnknownScript => f.write_str("unsupported leaf script"),
+            Self::MissingSignatures { required, available } =>
+                write!(f, "{} signatures required but {} present", required, available),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LeafError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::UnsupportedLeafVersion(_)
+            | Self::UnknownScript
+            | Self::MissingSignatures { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_lit::hex;
+    use secp256k1::SecretKey;
+
+    use super::*;
+    use crate::crypto::key::PrivateKey;
+    use crate::script::{
+        ScriptBufExt as _, ScriptPubKeyBuf, ScriptPubKeyBufExt as _, WitnessScriptBuf,
+        WitnessScriptExt as _,
+    };
+    use crate::transaction::{self, Transaction, TxIn, TxOut};
+    use crate::{absolute, Amount, NetworkKind, TapScriptBuf};
+
+    /// The signed script path spend of the BIP-0371 test vectors, three `pk` leaves at depths one
+    /// and two with a signature for each.
+    fn signed_script_path() -> Input {
+        let psbt = Psbt::deserialize(&hex!(
+            "70736274ff01005e02000000019bd48765230bf9a72e662001f972556e54f0c6f97feb56bcb5600d817f699526010000\
+            0000ffffffff0148e6052a0100000022512083698e458c6664e1595d75da2597de1e22ee97d798e706c4c0a4b5a9823c\
+            d743000000000001012b00f2052a01000000225120c2247efbfd92ac47f6f40b8d42d169175a19fa9fa10e4a25d7f35e\
+            b4dd85b69241142cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde865bb6d2cd970e15f53fc0c82f\
+            950fd560ffa919b76172be017368a89913af074f400b0940bf818d9757d6ffeb538ba057fb4c1fc4e0f5ef186e765beb\
+            564791e02af5fd3d5e2551d4e34e33d86f276b82c99c79aed3f0395a081efcd2cc2c65dd7e693d7941144320b0bf16f0\
+            11b53ea7be615924aa7f27e5d29ad20ea1155d848676c3bad1b2115f2e490af7cc45c4f78511f36057ce5c5a5c56325a\
+            29fb44dfc203f356e1f840e1f1ab6fabfa26b236f21833719dc1d428ab768d80f91f9988d8abef47bfb863bb1f2a529f\
+            768c15f00ce34ec283cdc07e88f8428be28f6ef64043c32911811a4114fa0f7a3cef3b1d0c0a6ce7d26e17ada0b2e5c9\
+            2d19efad48b41859cb8a451ca96f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b939ce7f91ae97040ec1f\
+            0379206461c83342285423326708ab031f0da4a253ee45aafa5b8c92034d8b605490f8cd13e00f989989b97e215faa36\
+            f12dee3693d2daccf3781c1757f66215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803a\
+            c06f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b939ce7f91ae970115f2e490af7cc45c4f78511f36057\
+            ce5c5a5c56325a29fb44dfc203f356e1f823202cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde86\
+            5bb6d2acc04215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac097c6e6fea5ff714f\
+            f5724499990810e406e98aa10f5bf7e5f6784bc1d0a9a6ce23204320b0bf16f011b53ea7be615924aa7f27e5d29ad20e\
+            a1155d848676c3bad1b2acc06215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0cd\
+            970e15f53fc0c82f950fd560ffa919b76172be017368a89913af074f400b09115f2e490af7cc45c4f78511f36057ce5c\
+            5a5c56325a29fb44dfc203f356e1f82320fa0f7a3cef3b1d0c0a6ce7d26e17ada0b2e5c92d19efad48b41859cb8a451c\
+            a9acc021162cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde865bb6d23901cd970e15f53fc0c82f\
+            950fd560ffa919b76172be017368a89913af074f400b09772b2da7560000800100008002000080000000000000000021\
+            164320b0bf16f011b53ea7be615924aa7f27e5d29ad20ea1155d848676c3bad1b23901115f2e490af7cc45c4f78511f3\
+            6057ce5c5a5c56325a29fb44dfc203f356e1f8772b2da75600008001000080010000800000000000000000211650929b\
+            74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac005007c461e5d2116fa0f7a3cef3b1d0c0a6ce7\
+            d26e17ada0b2e5c92d19efad48b41859cb8a451ca939016f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b\
+            939ce7f91ae970772b2da7560000800100008003000080000000000000000001172050929b74c1a04954b78b4b6035e9\
+            7a5e078a5a0f28ec96d547bfee9ace803ac0011820f0362e2f75a6f420a5bde3eb221d96ae6720cf25f81890c95b1d77\
+            5acb515e65000105201124da7aec92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e67121071124da7aec\
+            92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e6711900772b2da7560000800100008000000080000000\
+            000500000000"
+        ))
+        .unwrap();
+        psbt.inputs[0].clone()
+    }
+
+    #[test]
+    fn cheapest_leaf() {
+        let mut input = signed_script_path();
+        assert_eq!(input.tap_scripts.len(), 3);
+        input.finalize_taproot().unwrap();
+
+        let witness = input.final_script_witness.as_ref().unwrap();
+        assert_eq!(witness.len(), 3);
+        // The only leaf at depth one.
+        assert_eq!(witness.last().unwrap().len(), 33 + 32);
+        assert!(input.tap_scripts.is_empty());
+        assert!(input.tap_script_sigs.is_empty());
+        assert!(input.tap_internal_key.is_none());
+    }
+
+    #[test]
+    fn unsatisfiable_leaves() {
+        let mut input = signed_script_path();
+        input.tap_script_sigs.clear();
+        match input.finalize_taproot() {
+            Err(TaprootFinalizeError::NoSatisfiableLeaf(failures)) => {
+                assert_eq!(failures.len(), 3);
+                for (_, e) in failures {
+                    assert_eq!(e, LeafError::MissingSignatures { required: 1, available: 0 });
+                }
+            }
+            res => panic!("unexpected result {:?}", res),
+        }
+        assert!(input.final_script_witness.is_none());
+
+        assert_eq!(
+            Input::default().finalize_taproot(),
+            Err(TaprootFinalizeError::MissingSpendInfo)
+        );
+    }
+
+    #[test]
+    fn multi_a() {
+        let keys = signed_script_path().tap_key_origins.into_keys().take(3).collect::<Vec<_>>();
+        let script = TapScriptBuf::builder()
+            .push_x_only_key(keys[0])
+            .push_opcode(OP_CHECKSIG)
+            .push_x_only_key(keys[1])
+            .push_opcode(OP_CHECKSIGADD)
+            .push_x_only_key(keys[2])
+            .push_opcode(OP_CHECKSIGADD)
+            .push_int_unchecked(2)
+            .push_opcode(OP_NUMEQUAL)
+            .into_script();
+        assert_eq!(multi_a_keys(&script), Some((keys.clone(), 2)));
+
+        let pk = TapScriptBuf::builder().push_x_only_key(keys[0]).push_opcode(OP_CHECKSIG);
+        assert_eq!(multi_a_keys(&pk.into_script()), Some((vec![keys[0]], 1)));
+
+        let too_many = TapScriptBuf::builder()
+            .push_x_only_key(keys[0])
+            .push_opcode(OP_CHECKSIG)
+            .push_int_unchecked(2)
+            .push_opcode(OP_NUMEQUAL);
+        assert_eq!(multi_a_keys(&too_many.into_script()), None);
+        assert_eq!(multi_a_keys(&TapScriptBuf::new()), None);
+    }
+
+    fn private_key(byte: u8) -> PrivateKey {
+        PrivateKey::new(SecretKey::from_secret_bytes([byte; 32]).unwrap(), NetworkKind::Test)
+    }
+
+    fn key(byte: u8) -> PublicKey { PublicKey::from_private_key(private_key(byte)) }
+
+    fn psbt_spending(script_pubkeys: Vec<ScriptPubKeyBuf>) -> Psbt {
+        let unsigned_tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn::EMPTY_COINBASE; script_pubkeys.len()],
+            outputs: vec![],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+        for (input, script_pubkey) in psbt.inputs.iter_mut().zip(script_pubkeys) {
+            input.witness_utxo = Some(TxOut { amount: Amount::ONE_SAT, script_pubkey });
+        }
+        psbt
+    }
+
+    #[test]
+    fn missing_requirements() {
+        let keys = [key(1), key(2), key(3)];
+        let multisig = WitnessScriptBuf::builder()
+            .push_int_unchecked(2)
+            .push_key(keys[0])
+            .push_key(keys[1])
+            .push_key(keys[2])
+            .push_int_unchecked(3)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        let p2pkh = ScriptPubKeyBuf::new_p2pkh(keys[1].pubkey_hash());
+        let mut psbt = psbt_spending(vec![
+            ScriptPubKeyBuf::new_p2wpkh(keys[0].wpubkey_hash().unwrap()),
+            ScriptPubKeyBuf::new_p2wsh(multisig.wscript_hash().unwrap()),
+            p2pkh.clone(),
+            ScriptPubKeyBuf::new_p2wpkh(keys[2].wpubkey_hash().unwrap()),
+        ]);
+        psbt.inputs[0].bip32_derivation.insert(keys[0].inner, Default::default());
+        psbt.inputs[3].witness_utxo = None;
+        assert_eq!(
+            psbt.missing_requirements(),
+            [
+                vec![Requirement::Signatures { count: 1, keys: vec![keys[0]] }],
+                vec![Requirement::WitnessScript],
+                vec![Requirement::UnknownKey(keys[1].pubkey_hash())],
+                vec![Requirement::SpentOutput],
+            ]
+        );
+
+        // Signatures are not verified, any will do.
+        let mut signing = psbt_spending(vec![p2pkh]);
+        signing.inputs[0].bip32_derivation.insert(keys[1].inner, Default::default());
+        let key_map = [(keys[1], private_key(2))].into_iter().collect::<BTreeMap<_, _>>();
+        signing.sign(&key_map).unwrap();
+        let sig = signing.inputs[0].partial_sigs[&keys[1]];
+
+        psbt.inputs[0].partial_sigs.insert(keys[0], sig);
+        psbt.inputs[1].witness_script = Some(multisig);
+        psbt.inputs[1].partial_sigs.insert(keys[1], sig);
+        psbt.inputs[2].final_script_sig = Some(Default::default());
+        let requirements = psbt.missing_requirements();
+        assert!(requirements[0].is_empty());
+        assert_eq!(
+            requirements[1],
+            [Requirement::Signatures { count: 1, keys: vec![keys[0], keys[2]] }]
+        );
+        assert!(requirements[2].is_empty());
+
+        psbt.inputs[1].partial_sigs.insert(keys[2], sig);
+        assert!(psbt.missing_requirements()[1].is_empty());
+    }
+
+    #[test]
+    fn missing_taproot_requirements() {
+        let input = signed_script_path();
+        let mut psbt = psbt_spending(vec![input.witness_utxo.clone().unwrap().script_pubkey]);
+        psbt.inputs[0] = input;
+        assert_eq!(psbt.missing_requirements(), [vec![]]);
+
+        psbt.inputs[0].tap_script_sigs.clear();
+        let requirements = psbt.missing_requirements().remove(0);
+        assert_eq!(requirements.len(), 4);
+        assert_eq!(requirements[0], Requirement::TapKeySignature);
+        for requirement in &requirements[1..] {
+            match requirement {
+                Requirement::TapScriptSignatures { count: 1, keys, .. } =>
+                    assert_eq!(keys.len(), 1),
+                requirement => panic!("unexpected requirement {:?}", requirement),
+            }
+        }
+
+        psbt.inputs[0].tap_scripts.clear();
+        assert_eq!(
+            psbt.missing_requirements(),
+            [vec![Requirement::TapKeySignature, Requirement::ControlBlock]]
+        );
+    }
+}
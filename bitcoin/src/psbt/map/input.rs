@@ -14,8 +14,8 @@
 use crate::psbt::{error, raw, Error};
 use crate::script::{RedeemScriptBuf, ScriptSigBuf, TapScriptBuf, WitnessScriptBuf};
 use crate::sighash::{
-    EcdsaSighashType, InvalidSighashTypeError, NonStandardSighashTypeError, SighashTypeParseError,
-    TapSighashType,
+    EcdsaSighashType, InvalidSighashTypeError, NonStandardSighashTypeError, SighashTypeAny,
+    SighashTypeParseError, TapSighashType,
 };
 use crate::taproot::{ControlBlock, LeafVersion, TapLeafHash, TapNodeHash};
 use crate::transaction::{Transaction, TxOut};
@@ -183,6 +183,10 @@ impl From<TapSighashType> for PsbtSighashType {
     fn from(taproot_hash_ty: TapSighashType) -> Self { Self { inner: taproot_hash_ty as u32 } }
 }
 
+impl From<SighashTypeAny> for PsbtSighashType {
+    fn from(hash_ty: SighashTypeAny) -> Self { Self { inner: hash_ty.to_u32() } }
+}
+
 impl PsbtSighashType {
     /// Ambiguous `ALL` sighash type, may refer to either [`EcdsaSighashType::All`]
     /// or [`TapSighashType::All`].
@@ -409,6 +413,18 @@ pub fn combine(&mut self, other: Self) {
     }
 }
 
+/// Erases and removes the ECDSA partial signatures and the Taproot key and script path signatures.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Input {
+    fn zeroize(&mut self) {
+        self.partial_sigs.values_mut().for_each(zeroize::Zeroize::zeroize);
+        self.partial_sigs.clear();
+        self.tap_key_sig.zeroize();
+        self.tap_script_sigs.values_mut().for_each(zeroize::Zeroize::zeroize);
+        self.tap_script_sigs.clear();
+    }
+}
+
 impl Map for Input {
     fn get_pairs(&self) -> Vec<raw::Pair> {
         let mut rv: Vec<raw::Pair> = Default::default();
@@ -548,6 +564,15 @@ fn psbt_sighash_type_taproot() {
         }
     }
 
+    #[test]
+    fn psbt_sighash_type_any() {
+        let default = SighashTypeAny::Taproot(TapSighashType::Default);
+        assert_eq!(PsbtSighashType::from(default).taproot_hash_ty(), Ok(TapSighashType::Default));
+        assert!(PsbtSighashType::from(default).ecdsa_hash_ty().is_err());
+        let all = SighashTypeAny::Ecdsa(EcdsaSighashType::All);
+        assert_eq!(PsbtSighashType::from(all), PsbtSighashType::ALL);
+    }
+
     #[test]
     fn psbt_sighash_type_notstd() {
         let nonstd = 0xdddddddd;
@@ -8,8 +8,11 @@
 
 #[macro_use]
 mod macros;
+mod create;
 mod error;
+mod finalize;
 mod map;
+pub mod payjoin;
 pub mod raw;
 pub mod serialize;
 
@@ -25,17 +28,28 @@
 use crate::crypto::key::{PrivateKey, PublicKey};
 use crate::crypto::{ecdsa, taproot};
 use crate::key::{Keypair, TapTweak, XOnlyPublicKey};
-use crate::prelude::{btree_map, BTreeMap, BTreeSet, Borrow, Box, Vec};
-use crate::script::{ScriptExt as _, ScriptPubKeyExt as _};
+use crate::prelude::{btree_map, BTreeMap, BTreeSet, Borrow, Box, String, ToOwned, Vec};
+use crate::random::RandomSource;
+use crate::script::{
+    ScriptExt as _, ScriptPubKeyBuf, ScriptPubKeyBufExt as _, ScriptPubKeyExt as _,
+    ScriptSigExt as _,
+};
 use crate::sighash::{self, EcdsaSighashType, Prevouts, SighashCache};
-use crate::transaction::{self, Transaction, TransactionExt as _, TxOut};
-use crate::{Amount, FeeRate, TapLeafHash, TapSighash, TapSighashType};
+use crate::taproot::ControlBlock;
+use crate::transaction::{
+    self, SpendKind, SpendSignature, Transaction, TransactionExt as _, TxInExt as _, TxOut,
+};
+use crate::witness::WitnessExt as _;
+use crate::witness_program::{WitnessProgram, WitnessProgramKind};
+use crate::{Amount, FeeRate, TapLeafHash, TapNodeHash, TapSighash, TapSighashType};
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
 pub use self::{
     map::{Input, Output, PsbtSighashType},
+    create::{create, CreateError, CreateParams},
     error::Error,
+    finalize::{LeafError, Requirement, TaprootFinalizeError},
 };
 
 /// A Partially Signed Transaction.
@@ -121,6 +135,79 @@ pub fn from_unsigned_tx(tx: Transaction) -> Result<Self, Error> {
         Ok(psbt)
     }
 
+    /// Constructs a new PSBT from a signed transaction, to sign it again.
+    ///
+    /// The signatures are removed with [`TransactionExt::clone_unsigned`]. `spent` is called
+    /// with each outpoint being spent and should return the spent output. For the inputs whose
+    /// spent output is found, the scripts revealed by the signed input and the non-default
+    /// sighash type of its signatures are kept, and the spent output becomes the witness UTXO of
+    /// SegWit inputs. Legacy inputs need a non-witness UTXO, which has to be added by the caller.
+    ///
+    /// [`TransactionExt::clone_unsigned`]: crate::transaction::TransactionExt::clone_unsigned
+    pub fn from_signed_tx<S>(tx: &Transaction, mut spent: S) -> Self
+    where
+        S: FnMut(&transaction::OutPoint) -> Option<TxOut>,
+    {
+        fn ecdsa_sighash(
+            signature: Option<&SpendSignature<'_, ecdsa::SignatureInfo>>,
+        ) -> Option<PsbtSighashType> {
+            let sighash_type = signature?.info.sighash_type()?;
+            (sighash_type != EcdsaSighashType::All).then(|| sighash_type.into())
+        }
+
+        fn taproot_sighash(
+            signature: Option<&SpendSignature<'_, taproot::SignatureInfo>>,
+        ) -> Option<PsbtSighashType> {
+            let sighash_type = signature?.info.sighash_type()?;
+            (sighash_type != TapSighashType::Default).then(|| sighash_type.into())
+        }
+
+        let mut psbt =
+            Self::from_unsigned_tx(tx.clone_unsigned()).expect("the transaction is unsigned");
+        for (input, psbt_input) in tx.inputs.iter().zip(&mut psbt.inputs) {
+            let Some(prevout) = spent(&input.previous_output) else { continue };
+            let (segwit, sighash_type) = match input.classify_spend(&prevout.script_pubkey) {
+                SpendKind::P2pk { signature } | SpendKind::P2pkh { signature, .. } =>
+                    (false, ecdsa_sighash(signature.as_ref())),
+                SpendKind::P2sh { redeem_script, signatures, .. } => {
+                    psbt_input.redeem_script = Some(redeem_script.to_owned());
+                    (false, ecdsa_sighash(signatures.first()))
+                }
+                SpendKind::Bare { signatures, .. } => (false, ecdsa_sighash(signatures.first())),
+                SpendKind::P2wpkh { signature, .. } => (true, ecdsa_sighash(signature.as_ref())),
+                SpendKind::P2wsh { witness_script, signatures, .. } => {
+                    psbt_input.witness_script = Some(witness_script.to_owned());
+                    (true, ecdsa_sighash(signatures.first()))
+                }
+                SpendKind::TaprootKeyPath { signature } =>
+                    (true, taproot_sighash(signature.as_ref())),
+                SpendKind::TaprootScriptPath { leaf_script, signatures, .. } => {
+                    // The control block is the last element, or the one before the annex.
+                    let annex = usize::from(input.witness.taproot_annex().is_some());
+                    let control_block = input.witness.get_back(annex).map(ControlBlock::decode);
+                    if let Some(Ok(control_block)) = control_block {
+                        psbt_input.tap_internal_key = Some(control_block.internal_key);
+                        psbt_input.tap_scripts.insert(
+                            control_block,
+                            (leaf_script.script.to_owned(), leaf_script.version),
+                        );
+                    }
+                    (true, taproot_sighash(signatures.first()))
+                }
+                _ => (prevout.script_pubkey.is_witness_program(), None),
+            };
+            // A P2SH-wrapped SegWit input reveals its redeem script in the script sig.
+            if segwit && prevout.script_pubkey.is_p2sh() {
+                psbt_input.redeem_script = input.script_sig.redeem_script().map(ToOwned::to_owned);
+            }
+            psbt_input.sighash_type = sighash_type;
+            if segwit {
+                psbt_input.witness_utxo = Some(prevout);
+            }
+        }
+        psbt
+    }
+
     /// The default `max_fee_rate` value used for extracting transactions with [`extract_tx`]
     ///
     /// As of 2023, even the biggest overpayers during the highest fee markets only paid around
@@ -175,7 +262,7 @@ pub fn extract_tx_unchecked_fee_rate(self) -> Transaction { self.internal_extrac
     fn internal_extract_tx(self) -> Transaction {
         let mut tx: Transaction = self.unsigned_tx;
 
-        for (vin, psbtin) in tx.inputs.iter_mut().zip(self.inputs.into_iter()) {
+        for (vin, psbtin) in tx.inputs.iter_mut().zip(self.inputs) {
             vin.script_sig = psbtin.final_script_sig.unwrap_or_default();
             vin.witness = psbtin.final_script_witness.unwrap_or_default();
         }
@@ -268,17 +355,85 @@ pub fn combine(&mut self, other: Self) -> Result<(), Error> {
         self.proprietary.extend(other.proprietary);
         self.unknown.extend(other.unknown);
 
-        for (self_input, other_input) in self.inputs.iter_mut().zip(other.inputs.into_iter()) {
+        for (self_input, other_input) in self.inputs.iter_mut().zip(other.inputs) {
             self_input.combine(other_input);
         }
 
-        for (self_output, other_output) in self.outputs.iter_mut().zip(other.outputs.into_iter()) {
+        for (self_output, other_output) in self.outputs.iter_mut().zip(other.outputs) {
             self_output.combine(other_output);
         }
 
         Ok(())
     }
 
+    /// Returns the indices of the inputs spending the same outpoint as an earlier input.
+    ///
+    /// A transaction spending an outpoint twice is invalid, which easily happens when a
+    /// coordinator concatenates the inputs of several PSBTs.
+    pub fn duplicate_inputs(&self) -> Vec<usize> {
+        let mut seen = BTreeSet::new();
+        self.unsigned_tx
+            .inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, txin)| !seen.insert(txin.previous_output))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Removes the inputs spending the same outpoint as an earlier input.
+    ///
+    /// The map of each removed input is combined into the map of the earlier input. Returns the
+    /// indices the removed inputs had, see [`Psbt::duplicate_inputs`].
+    ///
+    /// Removing inputs invalidates the signatures already made, except those using
+    /// `SIGHASH_ANYONECANPAY`.
+    ///
+    /// # Panics
+    ///
+    /// If the number of inputs of the unsigned transaction and the PSBT differ.
+    pub fn dedup_inputs(&mut self) -> Vec<usize> {
+        assert_eq!(self.inputs.len(), self.unsigned_tx.inputs.len());
+        let duplicates = self.duplicate_inputs();
+        // Remove from the back so the earlier inputs keep their indices.
+        for &index in duplicates.iter().rev() {
+            let txin = self.unsigned_tx.inputs.remove(index);
+            let input = self.inputs.remove(index);
+            let first = self
+                .unsigned_tx
+                .inputs
+                .iter()
+                .position(|other| other.previous_output == txin.previous_output)
+                .expect("a duplicate has an earlier input spending the same outpoint");
+            self.inputs[first].combine(input);
+        }
+        duplicates
+    }
+
+    /// Sorts the inputs in the canonical order of BIP-0069, keeping the maps of the inputs in sync.
+    ///
+    /// Inputs are ordered by the TXID of the previous output, compared in the byte order it is
+    /// displayed in, and then by its output index. Sorting is stable so duplicate inputs keep their
+    /// relative order.
+    ///
+    /// Reordering inputs invalidates the signatures already made, except those using
+    /// `SIGHASH_ANYONECANPAY`.
+    ///
+    /// # Panics
+    ///
+    /// If the number of inputs of the unsigned transaction and the PSBT differ.
+    pub fn sort_inputs_canonical(&mut self) {
+        assert_eq!(self.inputs.len(), self.unsigned_tx.inputs.len());
+        let mut inputs = core::mem::take(&mut self.unsigned_tx.inputs)
+            .into_iter()
+            .zip(core::mem::take(&mut self.inputs))
+            .collect::<Vec<_>>();
+        inputs.sort_by_key(|(txin, _)| {
+            (txin.previous_output.txid.to_display_bytes(), txin.previous_output.vout)
+        });
+        (self.unsigned_tx.inputs, self.inputs) = inputs.into_iter().unzip();
+    }
+
     /// Attempts to create _all_ the required signatures for this PSBT using `k`.
     ///
     /// If you just want to sign an input with one specific key consider using `sighash_ecdsa` or
@@ -291,6 +446,21 @@ pub fn combine(&mut self, other: Self) -> Result<(), Error> {
     /// If an error is returned some signatures may already have been added to the PSBT. Since
     /// `partial_sigs` is a [`BTreeMap`] it is safe to retry, previous sigs will be overwritten.
     pub fn sign<K>(&mut self, k: &K) -> Result<SigningKeysMap, (SigningKeysMap, SigningErrors)>
+    where
+        K: GetKey,
+    {
+        self.sign_with_options(k, &SignOptions::default())
+    }
+
+    /// Attempts to create _all_ the required signatures for this PSBT using `k`, creating the
+    /// signatures as configured by `options`.
+    ///
+    /// See [`Psbt::sign`] for details.
+    pub fn sign_with_options<K>(
+        &mut self,
+        k: &K,
+        options: &SignOptions,
+    ) -> Result<SigningKeysMap, (SigningKeysMap, SigningErrors)>
     where
         K: GetKey,
     {
@@ -302,22 +472,24 @@ pub fn sign<K>(&mut self, k: &K) -> Result<SigningKeysMap, (SigningKeysMap, Sign
 
         for i in 0..self.inputs.len() {
             match self.signing_algorithm(i) {
-                Ok(SigningAlgorithm::Ecdsa) => match self.bip32_sign_ecdsa(k, i, &mut cache) {
-                    Ok(v) => {
-                        used.insert(i, SigningKeys::Ecdsa(v));
-                    }
-                    Err(e) => {
-                        errors.insert(i, e);
-                    }
-                },
-                Ok(SigningAlgorithm::Schnorr) => match self.bip32_sign_schnorr(k, i, &mut cache) {
-                    Ok(v) => {
-                        used.insert(i, SigningKeys::Schnorr(v));
-                    }
-                    Err(e) => {
-                        errors.insert(i, e);
-                    }
-                },
+                Ok(SigningAlgorithm::Ecdsa) =>
+                    match self.bip32_sign_ecdsa(k, i, &mut cache, options) {
+                        Ok(v) => {
+                            used.insert(i, SigningKeys::Ecdsa(v));
+                        }
+                        Err(e) => {
+                            errors.insert(i, e);
+                        }
+                    },
+                Ok(SigningAlgorithm::Schnorr) =>
+                    match self.bip32_sign_schnorr(k, i, &mut cache, options) {
+                        Ok(v) => {
+                            used.insert(i, SigningKeys::Schnorr(v));
+                        }
+                        Err(e) => {
+                            errors.insert(i, e);
+                        }
+                    },
                 Err(e) => {
                     errors.insert(i, e);
                 }
@@ -330,6 +502,159 @@ pub fn sign<K>(&mut self, k: &K) -> Result<SigningKeysMap, (SigningKeysMap, Sign
         }
     }
 
+    /// Creates the key spend signatures of all Taproot inputs with `keypair` as internal key.
+    ///
+    /// A faster alternative to [`Psbt::sign`] for wallets with a single Taproot key. Only the
+    /// `tap_internal_key`, `tap_merkle_root`, `tap_key_sig` and `sighash_type` fields are used,
+    /// inputs already having a key spend signature are skipped. Signatures are created like with
+    /// the default [`SignOptions`].
+    ///
+    /// # Returns
+    ///
+    /// The indices of the inputs signed.
+    ///
+    /// # Errors
+    ///
+    /// If the spent output of an input is missing, is not the P2TR output of the internal key and
+    /// merkle root of an input to be signed or the sighash of such an input cannot be computed, in
+    /// which case no input is signed.
+    pub fn sign_taproot_key_spends(&mut self, keypair: &Keypair) -> Result<Vec<usize>, SignError> {
+        let (internal_key, _parity) = keypair.to_x_only_public_key();
+        let to_sign = (0..self.inputs.len())
+            .filter(|&i| {
+                let input = &self.inputs[i];
+                input.tap_internal_key == Some(internal_key) && input.tap_key_sig.is_none()
+            })
+            .collect::<Vec<_>>();
+        if to_sign.is_empty() {
+            return Ok(to_sign);
+        }
+
+        let spend_utxos = (0..self.inputs.len())
+            .map(|i| self.spend_utxo(i).cloned())
+            .collect::<Result<Vec<_>, _>>()?;
+        let prevouts = Prevouts::All(&spend_utxos);
+        let mut cache = SighashCache::new(&self.unsigned_tx);
+
+        // Wallets with a single key usually use the same merkle root, most often none, for all inputs.
+        let mut tweaked: Option<(Option<TapNodeHash>, Keypair, ScriptPubKeyBuf)> = None;
+        let mut signatures = Vec::with_capacity(to_sign.len());
+        for &i in &to_sign {
+            let input = &self.inputs[i];
+            let (key, script_pubkey) = match tweaked {
+                Some((merkle_root, key, ref script_pubkey))
+                    if merkle_root == input.tap_merkle_root =>
+                    (key, script_pubkey),
+                _ => {
+                    let key = keypair.tap_tweak(input.tap_merkle_root);
+                    let script_pubkey = ScriptPubKeyBuf::new_p2tr_tweaked(key.public_parts().0);
+                    let (_, key, script_pubkey) =
+                        tweaked.insert((input.tap_merkle_root, key.to_keypair(), script_pubkey));
+                    (*key, &*script_pubkey)
+                }
+            };
+            // Never sign for an output the tweaked key cannot spend.
+            if spend_utxos[i].script_pubkey != *script_pubkey {
+                return Err(SignError::NotP2tr);
+            }
+
+            let sighash_type = input
+                .sighash_type
+                .unwrap_or_else(|| TapSighashType::Default.into())
+                .taproot_hash_ty()
+                .map_err(|_| SignError::InvalidSighashType)?;
+            let sighash = cache.taproot_key_spend_signature_hash(i, &prevouts, sighash_type)?;
+            let signature = SignOptions::default().sign_schnorr(sighash, &key.to_inner());
+            signatures.push(taproot::Signature { signature, sighash_type });
+        }
+
+        for (&i, signature) in to_sign.iter().zip(signatures) {
+            self.inputs[i].tap_key_sig = Some(signature);
+        }
+        Ok(to_sign)
+    }
+
+    /// Verifies the signatures in the `partial_sigs`, `tap_key_sig` and `tap_script_sigs` fields
+    /// of all inputs.
+    ///
+    /// Each signature is checked against the sighash of its input, computed with the sighash type
+    /// of the signature, which has to match the `sighash_type` of the input if one is set. Whether
+    /// the signatures are sufficient to spend the inputs is not checked, that is up to the
+    /// finalizer.
+    ///
+    /// # Errors
+    ///
+    /// A map of input index -> the first invalid signature found in that input.
+    pub fn verify_partial_sigs(&self) -> Result<(), PartialSigErrors> {
+        let mut cache = SighashCache::new(&self.unsigned_tx);
+        let mut errors = BTreeMap::new();
+        for i in 0..self.inputs.len() {
+            if let Err(e) = self.verify_input_sigs(i, &mut cache) {
+                errors.insert(i, e);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Verifies the signatures of the input at `input_index`.
+    fn verify_input_sigs<T: Borrow<Transaction>>(
+        &self,
+        input_index: usize,
+        cache: &mut SighashCache<T>,
+    ) -> Result<(), PartialSigError> {
+        let input = &self.inputs[input_index];
+        let check_sighash_type = |sighash_type: PsbtSighashType| match input.sighash_type {
+            Some(expected) if expected != sighash_type =>
+                Err(PartialSigError::MismatchedSighashType),
+            _ => Ok(()),
+        };
+
+        for (public_key, sig) in &input.partial_sigs {
+            check_sighash_type(sig.sighash_type.into())?;
+            let msg = self.sighash_ecdsa_with_type(input_index, cache, sig.sighash_type)?;
+            public_key
+                .verify(msg, *sig)
+                .map_err(|_| PartialSigError::InvalidEcdsaSignature(*public_key))?;
+        }
+
+        if let Some(sig) = input.tap_key_sig {
+            check_sighash_type(sig.sighash_type.into())?;
+            let sighash =
+                self.sighash_taproot_with_type(input_index, cache, None, sig.sighash_type)?;
+            let program =
+                WitnessProgram::from_script_pubkey(&self.spend_utxo(input_index)?.script_pubkey);
+            let output_key = match program.as_ref().map(WitnessProgram::kind) {
+                Some(WitnessProgramKind::V1Taproot(output_key)) => output_key,
+                // The sighash is only calculated for P2TR outputs so the output key is not a
+                // valid point, and no signature can be valid for it.
+                _ => return Err(PartialSigError::InvalidTapKeySignature),
+            };
+            secp256k1::schnorr::verify(
+                &sig.signature,
+                &sighash.to_byte_array(),
+                &output_key.to_inner(),
+            )
+            .map_err(|_| PartialSigError::InvalidTapKeySignature)?;
+        }
+
+        for (&(xonly, leaf_hash), sig) in &input.tap_script_sigs {
+            check_sighash_type(sig.sighash_type.into())?;
+            let sighash = self.sighash_taproot_with_type(
+                input_index,
+                cache,
+                Some(leaf_hash),
+                sig.sighash_type,
+            )?;
+            secp256k1::schnorr::verify(&sig.signature, &sighash.to_byte_array(), &xonly.to_inner())
+                .map_err(|_| PartialSigError::InvalidTapScriptSignature(xonly, leaf_hash))?;
+        }
+        Ok(())
+    }
+
     /// Attempts to create all signatures required by this PSBT's `bip32_derivation` field, adding
     /// them to `partial_sigs`.
     ///
@@ -342,6 +667,7 @@ fn bip32_sign_ecdsa<K, T>(
         k: &K,
         input_index: usize,
         cache: &mut SighashCache<T>,
+        options: &SignOptions,
     ) -> Result<Vec<PublicKey>, SignError>
     where
         T: Borrow<Transaction>,
@@ -369,7 +695,7 @@ fn bip32_sign_ecdsa<K, T>(
             };
 
             let sig = ecdsa::Signature {
-                signature: secp256k1::ecdsa::sign(msg, &sk.inner),
+                signature: options.sign_ecdsa(msg, &sk.inner),
                 sighash_type: sighash_ty,
             };
 
@@ -395,6 +721,7 @@ fn bip32_sign_schnorr<K, T>(
         k: &K,
         input_index: usize,
         cache: &mut SighashCache<T>,
+        options: &SignOptions,
     ) -> Result<Vec<XOnlyPublicKey>, SignError>
     where
         T: Borrow<Transaction>,
@@ -431,14 +758,7 @@ fn bip32_sign_schnorr<K, T>(
                         .tap_tweak(input.tap_merkle_root)
                         .to_keypair();
 
-                    #[cfg(all(feature = "rand", feature = "std"))]
-                    let signature =
-                        secp256k1::schnorr::sign(&sighash.to_byte_array(), &key_pair.to_inner());
-                    #[cfg(not(all(feature = "rand", feature = "std")))]
-                    let signature = secp256k1::schnorr::sign_no_aux_rand(
-                        &sighash.to_byte_array(),
-                        &key_pair.to_inner(),
-                    );
+                    let signature = options.sign_schnorr(sighash, &key_pair.to_inner());
 
                     let signature = taproot::Signature { signature, sighash_type };
                     input.tap_key_sig = Some(signature);
@@ -462,16 +782,7 @@ fn bip32_sign_schnorr<K, T>(
                         let (sighash, sighash_type) =
                             self.sighash_taproot(input_index, cache, Some(lh))?;
 
-                        #[cfg(all(feature = "rand", feature = "std"))]
-                        let signature = secp256k1::schnorr::sign(
-                            &sighash.to_byte_array(),
-                            &key_pair.to_inner(),
-                        );
-                        #[cfg(not(all(feature = "rand", feature = "std")))]
-                        let signature = secp256k1::schnorr::sign_no_aux_rand(
-                            &sighash.to_byte_array(),
-                            &key_pair.to_inner(),
-                        );
+                        let signature = options.sign_schnorr(sighash, &key_pair.to_inner());
 
                         let signature = taproot::Signature { signature, sighash_type };
                         input.tap_script_sigs.insert((xonly, lh), signature);
@@ -497,6 +808,23 @@ pub fn sighash_ecdsa<T: Borrow<Transaction>>(
         input_index: usize,
         cache: &mut SighashCache<T>,
     ) -> Result<(Message, EcdsaSighashType), SignError> {
+        if self.signing_algorithm(input_index)? != SigningAlgorithm::Ecdsa {
+            return Err(SignError::WrongSigningAlgorithm);
+        }
+
+        let input = self.checked_input(input_index)?;
+        let hash_ty = input.ecdsa_hash_ty().map_err(|_| SignError::InvalidSighashType)?; // Only support standard sighash types.
+        let msg = self.sighash_ecdsa_with_type(input_index, cache, hash_ty)?;
+        Ok((msg, hash_ty))
+    }
+
+    /// Returns the sighash message to sign an ECDSA input with `hash_ty`.
+    fn sighash_ecdsa_with_type<T: Borrow<Transaction>>(
+        &self,
+        input_index: usize,
+        cache: &mut SighashCache<T>,
+        hash_ty: EcdsaSighashType,
+    ) -> Result<Message, SignError> {
         use OutputType::*;
 
         if self.signing_algorithm(input_index)? != SigningAlgorithm::Ecdsa {
@@ -507,14 +835,12 @@ pub fn sighash_ecdsa<T: Borrow<Transaction>>(
         let utxo = self.spend_utxo(input_index)?;
         let spk = &utxo.script_pubkey; // scriptPubkey for input spend utxo.
 
-        let hash_ty = input.ecdsa_hash_ty().map_err(|_| SignError::InvalidSighashType)?; // Only support standard sighash types.
-
         match self.output_type(input_index)? {
             Bare => {
                 let sighash = cache
                     .legacy_signature_hash(input_index, spk, hash_ty.to_u32())
                     .expect("input checked above");
-                Ok((Message::from(sighash), hash_ty))
+                Ok(Message::from(sighash))
             }
             Sh => {
                 let script_code =
@@ -522,12 +848,12 @@ pub fn sighash_ecdsa<T: Borrow<Transaction>>(
                 let sighash = cache
                     .legacy_signature_hash(input_index, script_code, hash_ty.to_u32())
                     .expect("input checked above");
-                Ok((Message::from(sighash), hash_ty))
+                Ok(Message::from(sighash))
             }
             Wpkh => {
                 let sighash =
                     cache.p2wpkh_signature_hash(input_index, spk, utxo.amount, hash_ty)?;
-                Ok((Message::from(sighash), hash_ty))
+                Ok(Message::from(sighash))
             }
             ShWpkh => {
                 let redeem_script = input.redeem_script.as_ref().expect("checked above");
@@ -537,7 +863,7 @@ pub fn sighash_ecdsa<T: Borrow<Transaction>>(
                     utxo.amount,
                     hash_ty,
                 )?;
-                Ok((Message::from(sighash), hash_ty))
+                Ok(Message::from(sighash))
             }
             Wsh | ShWsh => {
                 let witness_script =
@@ -545,7 +871,7 @@ pub fn sighash_ecdsa<T: Borrow<Transaction>>(
                 let sighash = cache
                     .p2wsh_signature_hash(input_index, witness_script, utxo.amount, hash_ty)
                     .map_err(SignError::SegwitV0Sighash)?;
-                Ok((Message::from(sighash), hash_ty))
+                Ok(Message::from(sighash))
             }
             Tr => {
                 // This PSBT signing API is WIP, Taproot to come shortly.
@@ -564,22 +890,36 @@ fn sighash_taproot<T: Borrow<Transaction>>(
         cache: &mut SighashCache<T>,
         leaf_hash: Option<TapLeafHash>,
     ) -> Result<(TapSighash, TapSighashType), SignError> {
-        use OutputType::*;
-
         if self.signing_algorithm(input_index)? != SigningAlgorithm::Schnorr {
             return Err(SignError::WrongSigningAlgorithm);
         }
 
         let input = self.checked_input(input_index)?;
+        let hash_ty = input
+            .sighash_type
+            .unwrap_or_else(|| TapSighashType::Default.into())
+            .taproot_hash_ty()
+            .map_err(|_| SignError::InvalidSighashType)?;
+        let sighash = self.sighash_taproot_with_type(input_index, cache, leaf_hash, hash_ty)?;
+        Ok((sighash, hash_ty))
+    }
+
+    /// Returns the sighash to sign a Taproot input with `hash_ty`.
+    fn sighash_taproot_with_type<T: Borrow<Transaction>>(
+        &self,
+        input_index: usize,
+        cache: &mut SighashCache<T>,
+        leaf_hash: Option<TapLeafHash>,
+        hash_ty: TapSighashType,
+    ) -> Result<TapSighash, SignError> {
+        use OutputType::*;
+
+        if self.signing_algorithm(input_index)? != SigningAlgorithm::Schnorr {
+            return Err(SignError::WrongSigningAlgorithm);
+        }
 
         match self.output_type(input_index)? {
             Tr => {
-                let hash_ty = input
-                    .sighash_type
-                    .unwrap_or_else(|| TapSighashType::Default.into())
-                    .taproot_hash_ty()
-                    .map_err(|_| SignError::InvalidSighashType)?;
-
                 let spend_utxos =
                     (0..self.inputs.len()).map(|i| self.spend_utxo(i).ok()).collect::<Vec<_>>();
                 let all_spend_utxos;
@@ -608,7 +948,7 @@ fn sighash_taproot<T: Borrow<Transaction>>(
                 } else {
                     cache.taproot_key_spend_signature_hash(input_index, &prev_outs, hash_ty)?
                 };
-                Ok((sighash, hash_ty))
+                Ok(sighash)
             }
             _ => Err(SignError::Unsupported),
         }
@@ -725,6 +1065,15 @@ pub fn fee(&self) -> Result<Amount, Error> {
         }
         inputs.checked_sub(outputs).ok_or(Error::NegativeFee)
     }
+
+    /// Returns a JSON representation of this PSBT for logs and debugging.
+    ///
+    /// Contains the unsigned transaction as returned by
+    /// [`TransactionExt::to_debug_json`](crate::transaction::TransactionExt::to_debug_json), the
+    /// fee if the spent outputs are known and for each input and output the scripts, keys and
+    /// signatures with their sighash type. Hash preimages, proprietary and unknown fields are
+    /// not included.
+    pub fn to_debug_json(&self) -> String { crate::debug_json::psbt(self) }
 }
 
 #[cfg(feature = "serde")]
@@ -772,6 +1121,87 @@ fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
     }
 }
 
+/// Options controlling how [`Psbt::sign_with_options`] creates signatures.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub struct SignOptions {
+    /// Whether ECDSA signatures are ground to have a low R value.
+    pub ecdsa_low_r: bool,
+    /// The auxiliary randomness used for Schnorr signatures.
+    pub schnorr_aux_rand: AuxRand,
+}
+
+impl SignOptions {
+    /// Constructs the default options, same as used by [`Psbt::sign`].
+    pub fn new() -> Self { Self::default() }
+
+    /// Sets whether ECDSA signatures are ground to have a low R value.
+    ///
+    /// Grinding makes every DER encoded signature 70 bytes or shorter (71 with the sighash type),
+    /// as created by Bitcoin Core. This makes transaction weights more predictable and saves a
+    /// byte in half of the signatures, at the cost of signing taking twice as long on average.
+    #[must_use]
+    pub fn with_ecdsa_low_r(self, ecdsa_low_r: bool) -> Self { Self { ecdsa_low_r, ..self } }
+
+    /// Sets the auxiliary randomness used for Schnorr signatures.
+    #[must_use]
+    pub fn with_schnorr_aux_rand(self, schnorr_aux_rand: AuxRand) -> Self {
+        Self { schnorr_aux_rand, ..self }
+    }
+
+    /// Creates an ECDSA signature as configured by these options.
+    fn sign_ecdsa(&self, msg: Message, sk: &secp256k1::SecretKey) -> secp256k1::ecdsa::Signature {
+        if self.ecdsa_low_r {
+            secp256k1::ecdsa::sign_low_r(msg, sk)
+        } else {
+            secp256k1::ecdsa::sign(msg, sk)
+        }
+    }
+
+    /// Creates a Schnorr signature as configured by these options.
+    fn sign_schnorr(
+        &self,
+        sighash: TapSighash,
+        keypair: &secp256k1::Keypair,
+    ) -> secp256k1::schnorr::Signature {
+        let msg = sighash.to_byte_array();
+        match self.schnorr_aux_rand {
+            #[cfg(all(feature = "rand", feature = "std"))]
+            AuxRand::Auto => secp256k1::schnorr::sign(&msg, keypair),
+            #[cfg(not(all(feature = "rand", feature = "std")))]
+            AuxRand::Auto => secp256k1::schnorr::sign_no_aux_rand(&msg, keypair),
+            AuxRand::Deterministic => secp256k1::schnorr::sign_no_aux_rand(&msg, keypair),
+            AuxRand::Bytes(ref aux_rand) =>
+                secp256k1::schnorr::sign_with_aux_rand(&msg, keypair, aux_rand),
+        }
+    }
+}
+
+/// The auxiliary randomness used when creating a BIP-0340 Schnorr signature.
+///
+/// Auxiliary randomness protects against side-channel attacks, signatures are secure without it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum AuxRand {
+    /// Fresh randomness from the thread-local RNG if the `rand` and `std` features are enabled,
+    /// no auxiliary randomness otherwise.
+    #[default]
+    Auto,
+    /// No auxiliary randomness, the same key and message always produce the same signature.
+    Deterministic,
+    /// The given auxiliary randomness, for example from a hardware RNG.
+    Bytes([u8; 32]),
+}
+
+impl AuxRand {
+    /// Draws fresh auxiliary randomness from `rng`.
+    ///
+    /// BIP-0340 nonces are derived from the key and message as well, so signatures stay secure
+    /// even if `rng` is deterministic, for example a [`DeterministicRng`] in tests.
+    ///
+    /// [`DeterministicRng`]: crate::random::DeterministicRng
+    pub fn random<R: RandomSource + ?Sized>(rng: &mut R) -> Self { Self::Bytes(rng.random_array()) }
+}
+
 /// Data required to call [`GetKey`] to get the private key to sign an input.
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[non_exhaustive]
@@ -843,6 +1273,10 @@ pub enum SigningKeys {
 /// Map of input index -> the error encountered while attempting to sign that input.
 pub type SigningErrors = BTreeMap<usize, SignError>;
 
+/// Map of input index -> the invalid signature found in the input, see
+/// [`Psbt::verify_partial_sigs`].
+pub type PartialSigErrors = BTreeMap<usize, PartialSigError>;
+
 #[rustfmt::skip]
 macro_rules! impl_get_key_for_set {
     ($set:ident) => {
@@ -1041,6 +1475,8 @@ pub enum SignError {
     NotEcdsa,
     /// The `scriptPubkey` is not a P2WPKH script.
     NotWpkh,
+    /// The `scriptPubkey` is not the P2TR script of the internal key and merkle root.
+    NotP2tr,
     /// Sighash computation error (SegWit v0 input).
     SegwitV0Sighash(transaction::InputsIndexError),
     /// Sighash computation error (p2wpkh input).
@@ -1073,6 +1509,8 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             Self::MismatchedAlgoKey => write!(f, "signing algorithm and key type does not match"),
             Self::NotEcdsa => write!(f, "attempted to ECDSA sign a non-ECDSA input"),
             Self::NotWpkh => write!(f, "the scriptPubkey is not a P2WPKH script"),
+            Self::NotP2tr =>
+                write!(f, "the scriptPubkey is not the P2TR script of the internal key"),
             Self::SegwitV0Sighash(ref e) => write_err!(f, "SegWit v0 sighash"; e),
             Self::P2wpkhSighash(ref e) => write_err!(f, "p2wpkh sighash"; e),
             Self::TaprootError(ref e) => write_err!(f, "Taproot sighash"; e),
@@ -1101,6 +1539,7 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
             | Self::MismatchedAlgoKey
             | Self::NotEcdsa
             | Self::NotWpkh
+            | Self::NotP2tr
             | Self::UnknownOutputType
             | Self::KeyNotFound
             | Self::WrongSigningAlgorithm
@@ -1121,6 +1560,64 @@ impl From<sighash::TaprootError> for SignError {
     fn from(e: sighash::TaprootError) -> Self { Self::TaprootError(e) }
 }
 
+/// Error verifying the signatures of a PSBT input, see [`Psbt::verify_partial_sigs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PartialSigError {
+    /// The sighash of a signature could not be calculated.
+    Sighash(SignError),
+    /// The sighash type of a signature does not match the sighash type of the input.
+    MismatchedSighashType,
+    /// The signature in `partial_sigs` for the public key is invalid.
+    InvalidEcdsaSignature(PublicKey),
+    /// The signature in `tap_key_sig` is invalid.
+    InvalidTapKeySignature,
+    /// The signature in `tap_script_sigs` for the key and leaf is invalid.
+    InvalidTapScriptSignature(XOnlyPublicKey, TapLeafHash),
+}
+
+impl From<Infallible> for PartialSigError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for PartialSigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sighash(ref e) => write_err!(f, "failed to calculate sighash"; e),
+            Self::MismatchedSighashType =>
+                write!(f, "signature sighash type does not match the input sighash type"),
+            Self::InvalidEcdsaSignature(ref pk) => write!(f, "invalid signature for key {}", pk),
+            Self::InvalidTapKeySignature => write!(f, "invalid Taproot key path signature"),
+            Self::InvalidTapScriptSignature(ref xonly, ref leaf_hash) => write!(
+                f,
+                "invalid Taproot script path signature for key {} and leaf {}",
+                xonly, leaf_hash
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PartialSigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Sighash(ref e) => Some(e),
+            Self::MismatchedSighashType
+            | Self::InvalidEcdsaSignature(_)
+            | Self::InvalidTapKeySignature
+            | Self::InvalidTapScriptSignature(..) => None,
+        }
+    }
+}
+
+impl From<SignError> for PartialSigError {
+    fn from(e: SignError) -> Self { Self::Sighash(e) }
+}
+
+impl From<IndexOutOfBoundsError> for PartialSigError {
+    fn from(e: IndexOutOfBoundsError) -> Self { Self::Sighash(e.into()) }
+}
+
 /// This error is returned when extracting a [`Transaction`] from a [`Psbt`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -1298,16 +1795,14 @@ mod tests {
     use std::str::FromStr;
 
     use hashes::{hash160, ripemd160, sha256};
-    use hex_unstable::FromHex;
     use hex_lit::hex;
+    use hex_unstable::FromHex;
+    use secp256k1::SecretKey;
     #[cfg(all(feature = "rand", feature = "std"))]
-    use {
-        crate::bip32::Fingerprint, crate::locktime, crate::script::ScriptPubKeyBufExt as _,
-        crate::witness_version::WitnessVersion, crate::WitnessProgram, secp256k1::SecretKey,
-    };
+    use {crate::locktime, crate::witness_version::WitnessVersion, crate::WitnessProgram};
 
     use super::*;
-    use crate::bip32::{ChildNumber, DerivationPath};
+    use crate::bip32::{ChildNumber, DerivationPath, Fingerprint};
     use crate::locktime::absolute;
     use crate::network::NetworkKind;
     use crate::psbt::serialize::{Deserialize, Serialize};
@@ -2344,6 +2839,52 @@ fn combine_psbt_fuzz_3628() {
         ));
     }
 
+    #[test]
+    fn dedup_and_sort_inputs() {
+        // Ordered differently in display and in internal byte order.
+        let high =
+            "0200000000000000000000000000000000000000000000000000000000000000".parse().unwrap();
+        let low =
+            "01000000000000000000000000000000000000000000000000000000000000ff".parse().unwrap();
+        let outpoints = [
+            OutPoint { txid: high, vout: 0 },
+            OutPoint { txid: low, vout: 1 },
+            OutPoint { txid: high, vout: 0 },
+            OutPoint { txid: low, vout: 0 },
+        ];
+        let tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: outpoints
+                .iter()
+                .map(|&previous_output| TxIn { previous_output, ..TxIn::EMPTY_COINBASE })
+                .collect(),
+            outputs: vec![],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (index, input) in psbt.inputs.iter_mut().enumerate() {
+            input
+                .unknown
+                .insert(raw::Key { type_value: 0xf0, key_data: vec![index as u8] }, vec![]);
+        }
+        let keys = |psbt: &Psbt| {
+            psbt.inputs
+                .iter()
+                .map(|input| input.unknown.keys().map(|key| key.key_data[0]).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(psbt.duplicate_inputs(), [2]);
+        assert_eq!(psbt.dedup_inputs(), [2]);
+        assert!(psbt.duplicate_inputs().is_empty());
+        assert_eq!(keys(&psbt), [vec![0, 2], vec![1], vec![3]]);
+
+        psbt.sort_inputs_canonical();
+        let sorted = psbt.unsigned_tx.inputs.iter().map(|txin| txin.previous_output);
+        assert!(sorted.eq([outpoints[3], outpoints[1], outpoints[0]]));
+        assert_eq!(keys(&psbt), [vec![3], vec![1], vec![0, 2]]);
+    }
+
     #[cfg(all(feature = "rand", feature = "std"))]
     fn gen_keys() -> (PrivateKey, PublicKey) {
         use secp256k1::rand;
@@ -2726,4 +3267,324 @@ fn sign_psbt() {
         assert_eq!(signing_keys.len(), 1);
         assert_eq!(signing_keys[&0], SigningKeys::Ecdsa(vec![pk]));
     }
+
+    #[test]
+    fn sign_with_options() {
+        let sk = SecretKey::from_secret_bytes([0x11; 32]).unwrap();
+        let priv_key = PrivateKey::new(sk, NetworkKind::Test);
+        let pk = PublicKey::from_private_key(priv_key);
+        let internal_key: XOnlyPublicKey = pk.inner.into();
+        let key_map: BTreeMap<_, _> = [(pk, priv_key)].into_iter().collect();
+        let key_source = (Fingerprint::default(), DerivationPath::default());
+
+        let unsigned_tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn::EMPTY_COINBASE, TxIn::EMPTY_COINBASE],
+            outputs: vec![TxOut { amount: Amount::ZERO, script_pubkey: ScriptPubKeyBuf::new() }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            amount: Amount::from_sat_u32(10),
+            script_pubkey: ScriptPubKeyBuf::new_p2wpkh(pk.wpubkey_hash().unwrap()),
+        });
+        psbt.inputs[0].bip32_derivation.insert(pk.inner, key_source.clone());
+        psbt.inputs[1].witness_utxo = Some(TxOut {
+            amount: Amount::from_sat_u32(10),
+            script_pubkey: ScriptPubKeyBuf::new_p2tr(internal_key, None),
+        });
+        psbt.inputs[1].tap_internal_key = Some(internal_key);
+        psbt.inputs[1].tap_key_origins.insert(internal_key, (vec![], key_source));
+
+        let sign = |options: SignOptions| {
+            let mut psbt = psbt.clone();
+            psbt.sign_with_options(&key_map, &options).unwrap();
+            (psbt.inputs[0].partial_sigs[&pk], psbt.inputs[1].tap_key_sig.unwrap())
+        };
+
+        let (ecdsa, schnorr) = sign(SignOptions::new().with_ecdsa_low_r(true));
+        assert!(ecdsa.serialize().len() <= 71);
+        assert!(ecdsa.signature.serialize_der().len() <= 70);
+        assert_eq!(ecdsa, sign(SignOptions::new().with_ecdsa_low_r(true)).0);
+
+        let deterministic = SignOptions::new().with_schnorr_aux_rand(AuxRand::Deterministic);
+        assert_eq!(sign(deterministic).1, sign(deterministic).1);
+        let aux_rand = SignOptions::new().with_schnorr_aux_rand(AuxRand::Bytes([0x22; 32]));
+        assert_eq!(sign(aux_rand).1, sign(aux_rand).1);
+        assert_ne!(sign(aux_rand).1, sign(deterministic).1);
+
+        #[cfg(not(all(feature = "rand", feature = "std")))]
+        assert_eq!(schnorr, sign(deterministic).1);
+        #[cfg(all(feature = "rand", feature = "std"))]
+        let _ = schnorr;
+    }
+
+    #[test]
+    fn verify_partial_sigs() {
+        let sk = SecretKey::from_secret_bytes([0x11; 32]).unwrap();
+        let priv_key = PrivateKey::new(sk, NetworkKind::Test);
+        let pk = PublicKey::from_private_key(priv_key);
+        let internal_key: XOnlyPublicKey = pk.inner.into();
+        let key_map: BTreeMap<_, _> = [(pk, priv_key)].into_iter().collect();
+        let key_source = (Fingerprint::default(), DerivationPath::default());
+        let leaf_hash = TapLeafHash::from_byte_array([0x33; 32]);
+
+        let unsigned_tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn::EMPTY_COINBASE; 3],
+            outputs: vec![TxOut { amount: Amount::ZERO, script_pubkey: ScriptPubKeyBuf::new() }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+        let p2tr = TxOut {
+            amount: Amount::from_sat_u32(10),
+            script_pubkey: ScriptPubKeyBuf::new_p2tr(internal_key, None),
+        };
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            amount: Amount::from_sat_u32(10),
+            script_pubkey: ScriptPubKeyBuf::new_p2wpkh(pk.wpubkey_hash().unwrap()),
+        });
+        psbt.inputs[0].bip32_derivation.insert(pk.inner, key_source.clone());
+        // Key path spend.
+        psbt.inputs[1].witness_utxo = Some(p2tr.clone());
+        psbt.inputs[1].tap_internal_key = Some(internal_key);
+        psbt.inputs[1].tap_key_origins.insert(internal_key, (vec![], key_source.clone()));
+        // Script path spend.
+        psbt.inputs[2].witness_utxo = Some(p2tr);
+        psbt.inputs[2].tap_key_origins.insert(internal_key, (vec![leaf_hash], key_source));
+
+        psbt.sign(&key_map).unwrap();
+        assert_eq!(psbt.inputs[2].tap_script_sigs.len(), 1);
+        assert_eq!(psbt.verify_partial_sigs(), Ok(()));
+
+        // The signatures commit to the outputs.
+        let mut tampered = psbt.clone();
+        tampered.unsigned_tx.outputs[0].amount = Amount::ONE_SAT;
+        let errors = tampered.verify_partial_sigs().unwrap_err();
+        assert_eq!(errors[&0], PartialSigError::InvalidEcdsaSignature(pk));
+        assert_eq!(errors[&1], PartialSigError::InvalidTapKeySignature);
+        assert_eq!(errors[&2], PartialSigError::InvalidTapScriptSignature(internal_key, leaf_hash));
+
+        let mut mismatched = psbt.clone();
+        mismatched.inputs[0].sighash_type = Some(EcdsaSighashType::None.into());
+        let errors = mismatched.verify_partial_sigs().unwrap_err();
+        assert_eq!(
+            errors.into_iter().collect::<Vec<_>>(),
+            [(0, PartialSigError::MismatchedSighashType)]
+        );
+
+        let mut wrong_output_type = psbt.clone();
+        wrong_output_type.inputs[0].tap_key_sig = psbt.inputs[1].tap_key_sig;
+        let errors = wrong_output_type.verify_partial_sigs().unwrap_err();
+        assert_eq!(
+            errors.into_iter().collect::<Vec<_>>(),
+            [(0, PartialSigError::Sighash(SignError::WrongSigningAlgorithm))]
+        );
+
+        // The signing algorithm is checked before the sighash type.
+        let mut cache = SighashCache::new(&psbt.unsigned_tx);
+        let mut wrong_sighash_type = psbt.clone();
+        wrong_sighash_type.inputs[0].sighash_type = Some(PsbtSighashType::from_u32(0x04));
+        wrong_sighash_type.inputs[1].sighash_type = Some(TapSighashType::Default.into());
+        assert_eq!(
+            wrong_sighash_type.sighash_ecdsa(1, &mut cache),
+            Err(SignError::WrongSigningAlgorithm)
+        );
+        assert_eq!(
+            wrong_sighash_type.sighash_taproot(0, &mut cache, None),
+            Err(SignError::WrongSigningAlgorithm)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn zeroize_input_signatures() {
+        use zeroize::Zeroize as _;
+
+        const SIGNATURE: &str = "304402204f67e2afb76142d44fae58a2495d33a3419daa26cd0db8d04f3452b63289ac0f022010762a9fb67e94cc5cad9026f6dc99ff7f070f4278d30fbc7d0c869dd38c7fe701";
+
+        let sk = SecretKey::from_secret_bytes([0x11; 32]).unwrap();
+        let pk = PublicKey::from_private_key(PrivateKey::new(sk, NetworkKind::Test));
+        let signature = taproot::Signature {
+            signature: secp256k1::schnorr::Signature::from_byte_array([0x22; 64]),
+            sighash_type: TapSighashType::Default,
+        };
+        let mut input = Input {
+            partial_sigs: [(pk, ecdsa::Signature::from_str(SIGNATURE).unwrap())]
+                .into_iter()
+                .collect(),
+            tap_key_sig: Some(signature),
+            ..Default::default()
+        };
+        input
+            .tap_script_sigs
+            .insert((pk.inner.into(), TapLeafHash::from_byte_array([0x33; 32])), signature);
+
+        input.zeroize();
+        assert_eq!(input, Input::default());
+    }
+
+    #[test]
+    fn sign_taproot_key_spends() {
+        let keypair = Keypair::from_secret_key(&SecretKey::from_secret_bytes([0x11; 32]).unwrap());
+        let (internal_key, _) = keypair.to_x_only_public_key();
+        let other_key = XOnlyPublicKey::from_keypair(&Keypair::from_secret_key(
+            &SecretKey::from_secret_bytes([0x22; 32]).unwrap(),
+        ))
+        .0;
+        let merkle_root = TapNodeHash::from_byte_array([0x33; 32]);
+
+        let unsigned_tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn::EMPTY_COINBASE; 4],
+            outputs: vec![TxOut { amount: Amount::ZERO, script_pubkey: ScriptPubKeyBuf::new() }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+        let p2tr = |key, merkle_root| TxOut {
+            amount: Amount::from_sat_u32(10),
+            script_pubkey: ScriptPubKeyBuf::new_p2tr(key, merkle_root),
+        };
+        for input in &mut psbt.inputs {
+            input.witness_utxo = Some(p2tr(internal_key, None));
+            input.tap_internal_key = Some(internal_key);
+        }
+        psbt.inputs[1].witness_utxo = Some(p2tr(other_key, None));
+        psbt.inputs[1].tap_internal_key = Some(other_key);
+        psbt.inputs[2].witness_utxo = Some(p2tr(internal_key, Some(merkle_root)));
+        psbt.inputs[2].tap_merkle_root = Some(merkle_root);
+        psbt.inputs[3].sighash_type = Some(TapSighashType::AllPlusAnyoneCanPay.into());
+
+        let mut missing_utxo = psbt.clone();
+        missing_utxo.inputs[1].witness_utxo = None;
+        assert_eq!(
+            missing_utxo.sign_taproot_key_spends(&keypair),
+            Err(SignError::MissingSpendUtxo)
+        );
+        assert!(missing_utxo.inputs.iter().all(|input| input.tap_key_sig.is_none()));
+
+        // The spent output must be spendable with the tweaked key.
+        let mut wrong_merkle_root = psbt.clone();
+        wrong_merkle_root.inputs[0].tap_merkle_root = Some(merkle_root);
+        assert_eq!(wrong_merkle_root.sign_taproot_key_spends(&keypair), Err(SignError::NotP2tr));
+        let mut wrong_output = psbt.clone();
+        wrong_output.inputs[3].witness_utxo = Some(p2tr(other_key, None));
+        assert_eq!(wrong_output.sign_taproot_key_spends(&keypair), Err(SignError::NotP2tr));
+        assert!(wrong_output.inputs.iter().all(|input| input.tap_key_sig.is_none()));
+
+        assert_eq!(psbt.sign_taproot_key_spends(&keypair), Ok(vec![0, 2, 3]));
+        assert!(psbt.inputs[1].tap_key_sig.is_none());
+        let sighash_type = psbt.inputs[3].tap_key_sig.unwrap().sighash_type;
+        assert_eq!(sighash_type, TapSighashType::AllPlusAnyoneCanPay);
+        assert_eq!(psbt.verify_partial_sigs(), Ok(()));
+
+        // Inputs already signed are skipped.
+        assert_eq!(psbt.sign_taproot_key_spends(&keypair), Ok(vec![]));
+    }
+
+    #[test]
+    fn from_signed_tx() {
+        use crate::opcodes::all::OP_CHECKSIG;
+        use crate::script::{Builder, PushBytes, TapScriptBuf};
+        use crate::taproot::LeafVersion;
+        use crate::Txid;
+
+        let sk = SecretKey::from_secret_bytes([0x11; 32]).unwrap();
+        let pk = PublicKey::from_private_key(PrivateKey::new(sk, NetworkKind::Test));
+        let internal_key: XOnlyPublicKey = pk.inner.into();
+        let signature = |sighash_type| ecdsa::Signature {
+            signature: secp256k1::ecdsa::sign(Message::from_digest([2; 32]), &sk),
+            sighash_type,
+        };
+        let out_point = |vout| OutPoint { txid: Txid::from_byte_array([1; 32]), vout };
+        let input = |vout, script_sig, witness| TxIn {
+            previous_output: out_point(vout),
+            script_sig,
+            sequence: Sequence::ENABLE_LOCKTIME_AND_RBF,
+            witness,
+        };
+
+        let redeem_script = RedeemScriptBuf::new_p2wpkh(pk.wpubkey_hash().unwrap());
+        let tapscript: TapScriptBuf = Builder::new()
+            .push_slice(internal_key.serialize())
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let mut control_block = vec![0xc0];
+        control_block.extend(internal_key.serialize());
+        let tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::from_consensus(800_000),
+            inputs: vec![
+                input(
+                    0,
+                    ScriptSigBuf::new(),
+                    Witness::p2wpkh(signature(EcdsaSighashType::All), pk.inner),
+                ),
+                input(
+                    1,
+                    Builder::new()
+                        .push_slice(<&PushBytes>::try_from(redeem_script.as_bytes()).unwrap())
+                        .into_script(),
+                    Witness::p2wpkh(signature(EcdsaSighashType::Single), pk.inner),
+                ),
+                input(
+                    2,
+                    Builder::new()
+                        .push_slice(signature(EcdsaSighashType::All).serialize())
+                        .push_key(pk)
+                        .into_script(),
+                    Witness::new(),
+                ),
+                input(
+                    3,
+                    ScriptSigBuf::new(),
+                    Witness::from([[7; 64].as_slice(), tapscript.as_bytes(), &control_block]),
+                ),
+                input(
+                    4,
+                    ScriptSigBuf::new(),
+                    Witness::from_slice(&[[[7; 64].as_slice(), &[0x01]].concat()]),
+                ),
+                input(5, ScriptSigBuf::new(), Witness::from([[7; 64]])),
+            ],
+            outputs: vec![TxOut { amount: Amount::ONE_SAT, script_pubkey: ScriptPubKeyBuf::new() }],
+        };
+        let prevouts = [
+            ScriptPubKeyBuf::new_p2wpkh(pk.wpubkey_hash().unwrap()),
+            redeem_script.to_p2sh().unwrap(),
+            ScriptPubKeyBuf::new_p2pkh(pk.pubkey_hash()),
+            ScriptPubKeyBuf::new_p2tr(internal_key, None),
+            ScriptPubKeyBuf::new_p2tr(internal_key, None),
+        ]
+        .map(|script_pubkey| TxOut { amount: Amount::from_sat_u32(1_000), script_pubkey });
+
+        let psbt =
+            Psbt::from_signed_tx(&tx, |out_point| prevouts.get(out_point.vout as usize).cloned());
+        assert_eq!(psbt.unsigned_tx, tx.clone_unsigned());
+        assert_eq!(psbt.unsigned_tx.lock_time, tx.lock_time);
+        assert!(psbt.unsigned_tx.inputs.iter().all(|input| input.script_sig.is_empty()
+            && input.witness.is_empty()
+            && input.sequence == Sequence::ENABLE_LOCKTIME_AND_RBF));
+
+        assert_eq!(psbt.inputs[0].witness_utxo.as_ref(), Some(&prevouts[0]));
+        assert_eq!(psbt.inputs[0].sighash_type, None);
+        assert_eq!(psbt.inputs[0].redeem_script, None);
+        assert_eq!(psbt.inputs[1].witness_utxo.as_ref(), Some(&prevouts[1]));
+        assert_eq!(psbt.inputs[1].redeem_script, Some(redeem_script));
+        assert_eq!(psbt.inputs[1].sighash_type, Some(EcdsaSighashType::Single.into()));
+        // Legacy inputs need a non-witness UTXO.
+        assert_eq!(psbt.inputs[2], Input::default());
+        assert_eq!(psbt.inputs[3].witness_utxo.as_ref(), Some(&prevouts[3]));
+        assert_eq!(psbt.inputs[3].tap_internal_key, Some(internal_key));
+        assert_eq!(
+            psbt.inputs[3].tap_scripts.values().collect::<Vec<_>>(),
+            [&(tapscript, LeafVersion::TapScript)]
+        );
+        assert_eq!(psbt.inputs[3].sighash_type, None);
+        // An explicit `SIGHASH_ALL` is not the Taproot default.
+        assert_eq!(psbt.inputs[4].witness_utxo.as_ref(), Some(&prevouts[4]));
+        assert_eq!(psbt.inputs[4].sighash_type, Some(TapSighashType::All.into()));
+        // The spent output of the last input is not known.
+        assert_eq!(psbt.inputs[5], Input::default());
+    }
 }
@@ -0,0 +1,786 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Payjoin PSBT checks.
+//!
+//! Implementation of the PSBT validation rules of BIP-0078 Payjoin as defined at
+//! <https://github.com/bitcoin/bips/blob/master/bip-0078.mediawiki>.
+//!
+//! The receiver checks the sender's Original PSBT with [`check_original`], the sender checks the
+//! receiver's Payjoin Proposal with [`check_proposal`]. Checks that need a wallet or a node (input
+//! ownership, mempool acceptance) and the transport are left to the payjoin implementation.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use internals::write_err;
+
+use crate::psbt::{Input, Psbt};
+use crate::script::{ScriptExt as _, ScriptPubKey, ScriptPubKeyBuf, ScriptPubKeyExt as _};
+use crate::transaction::{InputWeightPrediction, OutPoint, TransactionExt as _, TxIn, TxOut};
+use crate::{Amount, FeeRate, Weight};
+
+/// The parameters the sender used when requesting a payjoin.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SenderParams {
+    /// The script pubkey of the output paying the receiver.
+    pub payee: ScriptPubKeyBuf,
+    /// Whether the receiver may substitute the payee output (`pjos=1`, the default).
+    pub output_substitution: bool,
+    /// The output the receiver may take additional fees from, if any.
+    pub fee_contribution: Option<FeeContribution>,
+}
+
+impl SenderParams {
+    /// Constructs new parameters paying to `payee`, allowing output substitution and without a fee
+    /// contribution.
+    pub fn new(payee: ScriptPubKeyBuf) -> Self {
+        Self { payee, output_substitution: true, fee_contribution: None }
+    }
+}
+
+/// The `maxadditionalfeecontribution` and `additionalfeeoutputindex` parameters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FeeContribution {
+    /// The maximum amount the receiver may subtract from the output.
+    pub max_amount: Amount,
+    /// The index of the output in the Original PSBT, usually the sender's change.
+    pub output_index: usize,
+}
+
+/// Checks the Original PSBT as required of the receiver.
+///
+/// All inputs must be finalized, have UTXO information and spend the same type of script.
+///
+/// # Errors
+///
+/// Returns an error describing the first rule the PSBT violates.
+pub fn check_original(original: &Psbt) -> Result<(), OriginalPsbtError> {
+    if !has_all_maps(original) {
+        return Err(OriginalPsbtError::MapCountMismatch);
+    }
+
+    let mut input_type = None;
+    for (index, (txin, input)) in
+        original.unsigned_tx.inputs.iter().zip(&original.inputs).enumerate()
+    {
+        if !is_finalized(input) {
+            return Err(OriginalPsbtError::NotFinalized { index });
+        }
+        let utxo = utxo(txin, input).ok_or(OriginalPsbtError::MissingUtxo { index })?;
+        let this = InputType::from_script_pubkey(&utxo.script_pubkey);
+        if *input_type.get_or_insert(this) != this {
+            return Err(OriginalPsbtError::MixedInputTypes { index });
+        }
+    }
+    Ok(())
+}
+
+/// Checks the receiver's Payjoin Proposal against the sender's Original PSBT.
+///
+/// Implements the checks the sender must do before signing the proposal: the transaction version,
+/// lock time and the sender's inputs are unchanged, no key paths or signatures are leaked, the
+/// receiver's inputs are finalized, the fee taken from the sender is within the bounds of
+/// `params.fee_contribution` and the sender's outputs are preserved. The payee output may only
+/// be changed if `params.output_substitution` is set.
+///
+/// The minimum fee rate (`minfeerate`) can only be checked once the proposal is signed.
+///
+/// # Errors
+///
+/// Returns an error describing the first rule the proposal violates.
+pub fn check_proposal(
+    original: &Psbt,
+    proposal: &Psbt,
+    params: &SenderParams,
+) -> Result<(), ProposalError> {
+    check_original(original)?;
+    if !has_all_maps(proposal) {
+        return Err(ProposalError::MapCountMismatch);
+    }
+
+    let original_tx = &original.unsigned_tx;
+    let proposal_tx = &proposal.unsigned_tx;
+    if proposal_tx.version != original_tx.version {
+        return Err(ProposalError::VersionChanged);
+    }
+    if proposal_tx.lock_time != original_tx.lock_time {
+        return Err(ProposalError::LockTimeChanged);
+    }
+
+    // Inputs.
+    let sequence = original_tx.inputs.first().map(|txin| txin.sequence);
+    let original_inputs = original_tx.inputs.iter().zip(&original.inputs);
+    let input_type = original_inputs.clone().next().and_then(|(txin, input)| utxo(txin, input));
+    let input_type = input_type.map(|utxo| InputType::from_script_pubkey(&utxo.script_pubkey));
+
+    let mut input_amount = Amount::ZERO;
+    let mut sender_inputs = 0;
+    for (index, (txin, input)) in proposal_tx.inputs.iter().zip(&proposal.inputs).enumerate() {
+        if proposal_tx.inputs[..index].iter().any(|p| p.previous_output == txin.previous_output) {
+            return Err(ProposalError::DuplicateInput { index });
+        }
+        if !input.bip32_derivation.is_empty() || !input.tap_key_origins.is_empty() {
+            return Err(ProposalError::InputKeyPath { index });
+        }
+        if !input.partial_sigs.is_empty()
+            || input.tap_key_sig.is_some()
+            || !input.tap_script_sigs.is_empty()
+        {
+            return Err(ProposalError::PartialSignature { index });
+        }
+        if Some(txin.sequence) != sequence {
+            return Err(ProposalError::SequenceMismatch { index });
+        }
+
+        let utxo = match original_inputs
+            .clone()
+            .find(|(o, _)| o.previous_output == txin.previous_output)
+        {
+            Some((original_txin, original_input)) => {
+                if is_finalized(input) {
+                    return Err(ProposalError::SenderInputFinalized { index });
+                }
+                if input.witness_utxo.is_some() || input.non_witness_utxo.is_some() {
+                    return Err(ProposalError::SenderInputUtxo { index });
+                }
+                sender_inputs += 1;
+                utxo(original_txin, original_input).expect("checked by check_original")
+            }
+            None => {
+                if !is_finalized(input) {
+                    return Err(ProposalError::ReceiverInputNotFinalized { index });
+                }
+                utxo(txin, input).ok_or(ProposalError::ReceiverInputMissingUtxo { index })?
+            }
+        };
+        if Some(InputType::from_script_pubkey(&utxo.script_pubkey)) != input_type {
+            return Err(ProposalError::MixedInputTypes { index });
+        }
+        input_amount = input_amount.checked_add(utxo.amount).ok_or(ProposalError::InvalidFee)?;
+    }
+    if sender_inputs != original_tx.inputs.len() {
+        let missing = original_tx
+            .inputs
+            .iter()
+            .find(|o| !proposal_tx.inputs.iter().any(|p| p.previous_output == o.previous_output))
+            .map_or(OutPoint::COINBASE_PREVOUT, |txin| txin.previous_output);
+        return Err(ProposalError::MissingSenderInput { out_point: missing });
+    }
+
+    // Outputs.
+    let mut output_amount = Amount::ZERO;
+    for (index, (txout, output)) in proposal_tx.outputs.iter().zip(&proposal.outputs).enumerate() {
+        if !output.bip32_derivation.is_empty() || !output.tap_key_origins.is_empty() {
+            return Err(ProposalError::OutputKeyPath { index });
+        }
+        output_amount = output_amount.checked_add(txout.amount).ok_or(ProposalError::InvalidFee)?;
+    }
+
+    let original_fee = original.fee().map_err(|_| ProposalError::InvalidFee)?;
+    let proposal_fee = input_amount.checked_sub(output_amount);
+    let fee_increase = match proposal_fee.and_then(|fee| fee.checked_sub(original_fee)) {
+        Some(fee_increase) => fee_increase,
+        None => return Err(ProposalError::FeeDecreased),
+    };
+
+    let fee_output = params.fee_contribution.map(|c| c.output_index);
+    if let Some(index) = fee_output {
+        if index >= original_tx.outputs.len() {
+            return Err(ProposalError::InvalidFeeOutputIndex { index });
+        }
+    }
+
+    for (index, original_txout) in original_tx.outputs.iter().enumerate() {
+        let is_payee = original_txout.script_pubkey == params.payee;
+        if is_payee && params.output_substitution {
+            continue;
+        }
+        let txout = proposal_tx
+            .outputs
+            .iter()
+            .find(|txout| txout.script_pubkey == original_txout.script_pubkey)
+            .ok_or(ProposalError::MissingOutput { index })?;
+
+        if Some(index) == fee_output {
+            let contribution =
+                original_txout.amount.checked_sub(txout.amount).unwrap_or(Amount::ZERO);
+            let params = params.fee_contribution.expect("fee output is set");
+            check_fee_contribution(
+                original,
+                input_type.expect("the original has an input if it has a fee output"),
+                proposal_tx.inputs.len() - original_tx.inputs.len(),
+                contribution,
+                params.max_amount,
+                fee_increase,
+            )?;
+        } else if txout.amount < original_txout.amount {
+            return Err(ProposalError::OutputDecreased { index });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `contribution` only pays for the fees of the receiver's inputs.
+fn check_fee_contribution(
+    original: &Psbt,
+    input_type: InputType,
+    added_inputs: usize,
+    contribution: Amount,
+    max_amount: Amount,
+    fee_increase: Amount,
+) -> Result<(), ProposalError> {
+    if contribution == Amount::ZERO {
+        return Ok(());
+    }
+    if contribution > max_amount {
+        return Err(ProposalError::FeeContributionExceedsMaximum { contribution, max_amount });
+    }
+    if contribution > fee_increase {
+        return Err(ProposalError::FeeContributionExceedsFeeIncrease {
+            contribution,
+            fee_increase,
+        });
+    }
+
+    let input_weight = input_type.weight_prediction().ok_or(ProposalError::UnsupportedInputType)?;
+    let original_weight = original.clone().extract_tx_unchecked_fee_rate().weight();
+    let original_fee = original.fee().map_err(|_| ProposalError::InvalidFee)?;
+    let original_fee_rate = (original_fee / original_weight).unwrap_or(FeeRate::MAX);
+    let added_weight = u64::try_from(added_inputs)
+        .ok()
+        .and_then(|n| input_weight.total_weight().to_wu().checked_mul(n))
+        .map_or(Weight::MAX, Weight::from_wu);
+    let max_amount = original_fee_rate.to_fee(added_weight);
+    if contribution > max_amount {
+        return Err(ProposalError::FeeContributionExceedsInputCost { contribution, max_amount });
+    }
+    Ok(())
+}
+
+/// Returns `true` if `psbt` has exactly one input and output map per transaction input and output.
+fn has_all_maps(psbt: &Psbt) -> bool {
+    psbt.inputs.len() == psbt.unsigned_tx.inputs.len()
+        && psbt.outputs.len() == psbt.unsigned_tx.outputs.len()
+}
+
+/// Returns `true` if `input` has a final scriptSig or witness.
+fn is_finalized(input: &Input) -> bool {
+    input.final_script_sig.is_some() || input.final_script_witness.is_some()
+}
+
+/// Returns the output spent by `txin`, taken from the PSBT `input`.
+fn utxo<'a>(txin: &TxIn, input: &'a Input) -> Option<&'a TxOut> {
+    if let Some(ref utxo) = input.witness_utxo {
+        return Some(utxo);
+    }
+    let tx = input.non_witness_utxo.as_ref()?;
+    let vout = usize::try_from(txin.previous_output.vout).ok()?;
+    tx.outputs.get(vout)
+}
+
+/// The script type of an input, as far as BIP-0078 distinguishes them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum InputType {
+    P2pkh,
+    /// Assumed to be P2SH-P2WPKH.
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    Other,
+}
+
+impl InputType {
+    fn from_script_pubkey(script_pubkey: &ScriptPubKey) -> Self {
+        if script_pubkey.is_p2pkh() {
+            Self::P2pkh
+        } else if script_pubkey.is_p2sh() {
+            Self::P2sh
+        } else if script_pubkey.is_p2wpkh() {
+            Self::P2wpkh
+        } else if script_pubkey.is_p2wsh() {
+            Self::P2wsh
+        } else if script_pubkey.is_p2tr() {
+            Self::P2tr
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Returns the weight prediction of an input of this type, as listed in BIP-0078.
+    fn weight_prediction(self) -> Option<InputWeightPrediction> {
+        match self {
+            Self::P2pkh => Some(InputWeightPrediction::P2PKH_COMPRESSED_MAX),
+            Self::P2sh => Some(InputWeightPrediction::NESTED_P2WPKH_MAX),
+            Self::P2wpkh => Some(InputWeightPrediction::P2WPKH_MAX),
+            Self::P2tr => Some(InputWeightPrediction::P2TR_KEY_DEFAULT_SIGHASH),
+            Self::P2wsh | Self::Other => None,
+        }
+    }
+}
+
+/// An error returned by [`check_original`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OriginalPsbtError {
+    /// The number of input or output maps differs from the number of transaction inputs or outputs.
+    MapCountMismatch,
+    /// An input is not finalized.
+    NotFinalized {
+        /// The index of the input.
+        index: usize,
+    },
+    /// An input has no UTXO information.
+    MissingUtxo {
+        /// The index of the input.
+        index: usize,
+    },
+    /// An input spends a different script type than the first input.
+    MixedInputTypes {
+        /// The index of the input.
+        index: usize,
+    },
+}
+
+impl From<Infallible> for OriginalPsbtError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for OriginalPsbtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::MapCountMismatch =>
+                f.write_str("number of input or output maps does not match the transaction"),
+            Self::NotFinalized { index } => write!(f, "input {} is not finalized", index),
+            Self::MissingUtxo { index } => write!(f, "input {} has no UTXO information", index),
+            Self::MixedInputTypes { index } =>
+                write!(f, "input {} spends a different script type than the first input", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OriginalPsbtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::MapCountMismatch
+            | Self::NotFinalized { .. }
+            | Self::MissingUtxo { .. }
+            | Self::MixedInputTypes { .. } => None,
+        }
+    }
+}
+
+/// An error returned by [`check_proposal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProposalError {
+    /// The Original PSBT itself is invalid.
+    Original(OriginalPsbtError),
+    /// The number of input or output maps differs from the number of transaction inputs or outputs.
+    MapCountMismatch,
+    /// The transaction version was changed.
+    VersionChanged,
+    /// The transaction lock time was changed.
+    LockTimeChanged,
+    /// An input of the Original PSBT is missing from the proposal.
+    MissingSenderInput {
+        /// The outpoint spent by the missing input.
+        out_point: OutPoint,
+    },
+    /// An input spends the same outpoint as an earlier input.
+    DuplicateInput {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// An input has a different sequence number than the inputs of the Original PSBT.
+    SequenceMismatch {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// An input contains key paths.
+    InputKeyPath {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// An input contains a partial signature.
+    PartialSignature {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// One of the sender's inputs is finalized.
+    SenderInputFinalized {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// One of the sender's inputs contains UTXO information.
+    SenderInputUtxo {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// One of the receiver's inputs is not finalized.
+    ReceiverInputNotFinalized {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// One of the receiver's inputs has no UTXO information.
+    ReceiverInputMissingUtxo {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// An input spends a different script type than the inputs of the Original PSBT.
+    MixedInputTypes {
+        /// The index of the input in the proposal.
+        index: usize,
+    },
+    /// An output contains key paths.
+    OutputKeyPath {
+        /// The index of the output in the proposal.
+        index: usize,
+    },
+    /// An output of the Original PSBT is missing from the proposal.
+    MissingOutput {
+        /// The index of the output in the Original PSBT.
+        index: usize,
+    },
+    /// An output of the Original PSBT has a lower value in the proposal.
+    OutputDecreased {
+        /// The index of the output in the Original PSBT.
+        index: usize,
+    },
+    /// The proposal pays a lower absolute fee than the Original PSBT.
+    FeeDecreased,
+    /// The fee contribution output index is out of bounds.
+    InvalidFeeOutputIndex {
+        /// The index of the output in the Original PSBT.
+        index: usize,
+    },
+    /// More than `maxadditionalfeecontribution` was taken from the fee output.
+    FeeContributionExceedsMaximum {
+        /// The amount taken from the fee output.
+        contribution: Amount,
+        /// The maximum contribution the sender allowed.
+        max_amount: Amount,
+    },
+    /// The fee contribution is larger than the increase in absolute fee.
+    FeeContributionExceedsFeeIncrease {
+        /// The amount taken from the fee output.
+        contribution: Amount,
+        /// The fee of the proposal minus the fee of the Original PSBT.
+        fee_increase: Amount,
+    },
+    /// The fee contribution pays for more than the receiver's inputs at the original fee rate.
+    FeeContributionExceedsInputCost {
+        /// The amount taken from the fee output.
+        contribution: Amount,
+        /// The cost of the receiver's inputs at the fee rate of the Original PSBT.
+        max_amount: Amount,
+    },
+    /// The sender's input type has no known weight, so the fee contribution cannot be checked.
+    UnsupportedInputType,
+    /// Summing amounts overflowed or the Original PSBT has a negative fee.
+    InvalidFee,
+}
+
+impl From<Infallible> for ProposalError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl From<OriginalPsbtError> for ProposalError {
+    fn from(e: OriginalPsbtError) -> Self { Self::Original(e) }
+}
+
+impl fmt::Display for ProposalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Original(ref e) => write_err!(f, "invalid original PSBT"; e),
+            Self::MapCountMismatch =>
+                f.write_str("number of input or output maps does not match the transaction"),
+            Self::VersionChanged => f.write_str("transaction version was changed"),
+            Self::LockTimeChanged => f.write_str("transaction lock time was changed"),
+            Self::MissingSenderInput { out_point } =>
+                write!(f, "sender input spending {} is missing", out_point),
+            Self::DuplicateInput { index } =>
+                write!(f, "input {} spends the same outpoint as an earlier input", index),
+            Self::SequenceMismatch { index } =>
+                write!(f, "input {} has a different sequence number", index),
+            Self::InputKeyPath { index } => write!(f, "input {} contains key paths", index),
+            Self::PartialSignature { index } =>
+                write!(f, "input {} contains a partial signature", index),
+            Self::SenderInputFinalized { index } =>
+                write!(f, "sender input {} is finalized", index),
+            Self::SenderInputUtxo { index } =>
+                write!(f, "sender input {} contains UTXO information", index),
+            Self::ReceiverInputNotFinalized { index } =>
+                write!(f, "receiver input {} is not finalized", index),
+            Self::ReceiverInputMissingUtxo { index } =>
+                write!(f, "receiver input {} has no UTXO information", index),
+            Self::MixedInputTypes { index } =>
+                write!(f, "input {} spends a different script type than the sender inputs", index),
+            Self::OutputKeyPath { index } => write!(f, "output {} contains key paths", index),
+            Self::MissingOutput { index } => write!(f, "original output {} is missing", index),
+            Self::OutputDecreased { index } =>
+                write!(f, "value of original output {} was decreased", index),
+            Self::FeeDecreased => f.write_str("absolute fee was decreased"),
+            Self::InvalidFeeOutputIndex { index } =>
+                write!(f, "fee output index {} is out of bounds", index),
+            Self::FeeContributionExceedsMaximum { contribution, max_amount } => write!(
+                f,
+                "fee contribution {} exceeds the maximum additional fee contribution {}",
+                contribution, max_amount
+            ),
+            Self::FeeContributionExceedsFeeIncrease { contribution, fee_increase } => write!(
+                f,
+                "fee contribution {} exceeds the fee increase {}",
+                contribution, fee_increase
+            ),
+            Self::FeeContributionExceedsInputCost { contribution, max_amount } => write!(
+                f,
+                "fee contribution {} exceeds the cost {} of the added inputs",
+                contribution, max_amount
+            ),
+            Self::UnsupportedInputType =>
+                f.write_str("fee contribution for an unsupported input type"),
+            Self::InvalidFee => f.write_str("amount overflow or negative fee"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProposalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::Original(ref e) => Some(e),
+            Self::MapCountMismatch
+            | Self::VersionChanged
+            | Self::LockTimeChanged
+            | Self::MissingSenderInput { .. }
+            | Self::DuplicateInput { .. }
+            | Self::SequenceMismatch { .. }
+            | Self::InputKeyPath { .. }
+            | Self::PartialSignature { .. }
+            | Self::SenderInputFinalized { .. }
+            | Self::SenderInputUtxo { .. }
+            | Self::ReceiverInputNotFinalized { .. }
+            | Self::ReceiverInputMissingUtxo { .. }
+            | Self::MixedInputTypes { .. }
+            | Self::OutputKeyPath { .. }
+            | Self::MissingOutput { .. }
+            | Self::OutputDecreased { .. }
+            | Self::FeeDecreased
+            | Self::InvalidFeeOutputIndex { .. }
+            | Self::FeeContributionExceedsMaximum { .. }
+            | Self::FeeContributionExceedsFeeIncrease { .. }
+            | Self::FeeContributionExceedsInputCost { .. }
+            | Self::UnsupportedInputType
+            | Self::InvalidFee => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{self, Transaction, Txid};
+    use crate::{absolute, Sequence, Witness};
+
+    fn p2wpkh(byte: u8) -> ScriptPubKeyBuf {
+        let mut bytes = vec![0x00, 0x14];
+        bytes.extend_from_slice(&[byte; 20]);
+        ScriptPubKeyBuf::from_bytes(bytes)
+    }
+
+    fn txin(byte: u8) -> TxIn {
+        TxIn {
+            previous_output: OutPoint { txid: Txid::from_byte_array([byte; 32]), vout: 0 },
+            sequence: Sequence::ENABLE_LOCKTIME_AND_RBF,
+            ..TxIn::EMPTY_COINBASE
+        }
+    }
+
+    fn finalized(amount: u64, byte: u8) -> Input {
+        Input {
+            witness_utxo: Some(TxOut {
+                amount: Amount::from_sat(amount).unwrap(),
+                script_pubkey: p2wpkh(byte),
+            }),
+            final_script_witness: Some(Witness::from_slice(&[vec![0; 72], vec![2; 33]])),
+            ..Default::default()
+        }
+    }
+
+    fn txout(amount: u64, byte: u8) -> TxOut {
+        TxOut { amount: Amount::from_sat(amount).unwrap(), script_pubkey: p2wpkh(byte) }
+    }
+
+    fn psbt(inputs: Vec<(TxIn, Input)>, outputs: Vec<TxOut>) -> Psbt {
+        let (txins, inputs): (Vec<_>, Vec<_>) = inputs.into_iter().unzip();
+        let tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: txins,
+            outputs,
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs = inputs;
+        psbt
+    }
+
+    // Sender pays 50_000 to the receiver (0xbb) from a 100_000 input, with 40_000 change (0xcc).
+    fn original() -> Psbt {
+        psbt(
+            vec![(txin(1), finalized(100_000, 0xaa))],
+            vec![txout(50_000, 0xbb), txout(40_000, 0xcc)],
+        )
+    }
+
+    // The receiver adds a 30_000 input and takes `contribution` from the change.
+    fn proposal(contribution: u64) -> Psbt {
+        psbt(
+            vec![(txin(1), Input::default()), (txin(2), finalized(30_000, 0xbb))],
+            vec![txout(80_000, 0xbb), txout(40_000 - contribution, 0xcc)],
+        )
+    }
+
+    fn params() -> SenderParams {
+        SenderParams {
+            fee_contribution: Some(FeeContribution {
+                max_amount: Amount::from_sat_u32(1_000),
+                output_index: 1,
+            }),
+            ..SenderParams::new(p2wpkh(0xbb))
+        }
+    }
+
+    #[test]
+    fn original_checks() {
+        assert_eq!(check_original(&original()), Ok(()));
+
+        let mut psbt = original();
+        psbt.inputs[0].final_script_witness = None;
+        assert_eq!(check_original(&psbt), Err(OriginalPsbtError::NotFinalized { index: 0 }));
+
+        let mut psbt = original();
+        psbt.inputs[0].witness_utxo = None;
+        assert_eq!(check_original(&psbt), Err(OriginalPsbtError::MissingUtxo { index: 0 }));
+
+        let mut psbt = original();
+        psbt.unsigned_tx.inputs.push(txin(3));
+        let mut input = finalized(1_000, 0xdd);
+        input.witness_utxo.as_mut().unwrap().script_pubkey =
+            ScriptPubKeyBuf::from_bytes(vec![0x6a]);
+        psbt.inputs.push(input);
+        assert_eq!(check_original(&psbt), Err(OriginalPsbtError::MixedInputTypes { index: 1 }));
+
+        let mut psbt = original();
+        psbt.outputs.pop();
+        assert_eq!(check_original(&psbt), Err(OriginalPsbtError::MapCountMismatch));
+    }
+
+    #[test]
+    fn valid_proposal() {
+        assert_eq!(check_proposal(&original(), &proposal(300), &params()), Ok(()));
+        assert_eq!(
+            check_proposal(&original(), &proposal(0), &SenderParams::new(p2wpkh(0xbb))),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn proposal_input_rules() {
+        let original = original();
+
+        let mut p = proposal(0);
+        p.unsigned_tx.lock_time = absolute::LockTime::from_consensus(1);
+        assert_eq!(check_proposal(&original, &p, &params()), Err(ProposalError::LockTimeChanged));
+
+        let mut p = proposal(0);
+        p.unsigned_tx.inputs[0].previous_output.vout = 1;
+        assert_eq!(
+            check_proposal(&original, &p, &params()),
+            Err(ProposalError::ReceiverInputNotFinalized { index: 0 })
+        );
+
+        let mut p = proposal(0);
+        p.inputs[0].witness_utxo = original.inputs[0].witness_utxo.clone();
+        assert_eq!(
+            check_proposal(&original, &p, &params()),
+            Err(ProposalError::SenderInputUtxo { index: 0 })
+        );
+
+        // The sender's input is spent twice.
+        let mut p = proposal(0);
+        p.unsigned_tx.inputs.push(txin(1));
+        p.inputs.push(Input::default());
+        assert_eq!(
+            check_proposal(&original, &p, &params()),
+            Err(ProposalError::DuplicateInput { index: 2 })
+        );
+
+        let mut p = proposal(0);
+        p.inputs.pop();
+        assert_eq!(check_proposal(&original, &p, &params()), Err(ProposalError::MapCountMismatch));
+        let mut p = proposal(0);
+        p.outputs.push(Default::default());
+        assert_eq!(check_proposal(&original, &p, &params()), Err(ProposalError::MapCountMismatch));
+
+        let mut p = proposal(0);
+        p.unsigned_tx.inputs[1].sequence = Sequence::MAX;
+        assert_eq!(
+            check_proposal(&original, &p, &params()),
+            Err(ProposalError::SequenceMismatch { index: 1 })
+        );
+
+        let mut p = proposal(0);
+        p.outputs[0].bip32_derivation = original.inputs[0].bip32_derivation.clone();
+        p.outputs[1].tap_key_origins.insert(
+            "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d".parse().unwrap(),
+            Default::default(),
+        );
+        assert_eq!(
+            check_proposal(&original, &p, &params()),
+            Err(ProposalError::OutputKeyPath { index: 1 })
+        );
+    }
+
+    #[test]
+    fn proposal_output_rules() {
+        let original = original();
+
+        assert_eq!(
+            check_proposal(&original, &proposal(1_001), &params()),
+            Err(ProposalError::FeeContributionExceedsMaximum {
+                contribution: Amount::from_sat_u32(1_001),
+                max_amount: Amount::from_sat_u32(1_000),
+            })
+        );
+
+        // The receiver keeps part of the contribution for itself.
+        let mut p = proposal(300);
+        p.unsigned_tx.outputs[0].amount = Amount::from_sat_u32(80_200);
+        assert_eq!(
+            check_proposal(&original, &p, &params()),
+            Err(ProposalError::FeeContributionExceedsFeeIncrease {
+                contribution: Amount::from_sat_u32(300),
+                fee_increase: Amount::from_sat_u32(100),
+            })
+        );
+
+        // The receiver may not take the change without a fee contribution.
+        assert_eq!(
+            check_proposal(&original, &proposal(300), &SenderParams::new(p2wpkh(0xbb))),
+            Err(ProposalError::OutputDecreased { index: 1 })
+        );
+
+        // Output substitution replaces the payee output.
+        let mut p = proposal(0);
+        p.unsigned_tx.outputs[0].script_pubkey = p2wpkh(0xee);
+        assert_eq!(check_proposal(&original, &p, &params()), Ok(()));
+        let params = SenderParams { output_substitution: false, ..params() };
+        assert_eq!(
+            check_proposal(&original, &p, &params),
+            Err(ProposalError::MissingOutput { index: 0 })
+        );
+    }
+}
@@ -235,7 +235,8 @@ fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
         Self::from_slice(bytes).map_err(|e| match e {
             ecdsa::DecodeError::EmptySignature => Error::InvalidEcdsaSignature(e),
             ecdsa::DecodeError::SighashType(err) => Error::NonStandardSighashType(err.0),
-            ecdsa::DecodeError::Secp256k1(..) => Error::InvalidEcdsaSignature(e),
+            ecdsa::DecodeError::Secp256k1(..) | ecdsa::DecodeError::StrictDer(..) =>
+                Error::InvalidEcdsaSignature(e),
         })
     }
 }
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Sources of randomness.
+//!
+//! Everything in this crate that makes a random choice, such as the auxiliary randomness of
+//! Schnorr signatures, the position of change outputs and the anti fee sniping lock time, draws
+//! from a [`RandomSource`] passed in by the caller. With the `rand` feature enabled any
+//! [`rand::RngCore`] is a random source, and [`DeterministicRng`] makes all of these choices
+//! reproducible in tests.
+//!
+//! [`rand::RngCore`]: secp256k1::rand::RngCore
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::random::{DeterministicRng, RandomSource as _};
+//!
+//! let mut rng = DeterministicRng::new([0x42; 32]);
+//! let mut other = DeterministicRng::new([0x42; 32]);
+//! assert_eq!(rng.random_u64(), other.random_u64());
+//! assert!(rng.random_below(10) < 10);
+//! ```
+
+use hashes::{sha256, HashEngine as _};
+
+/// A source of random bytes.
+pub trait RandomSource {
+    /// Fills `dest` with random bytes.
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+
+    /// Returns a random byte array.
+    fn random_array<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        self.fill_bytes(&mut bytes);
+        bytes
+    }
+
+    /// Returns a random `u64`.
+    fn random_u64(&mut self) -> u64 { u64::from_le_bytes(self.random_array()) }
+
+    /// Returns a uniformly random number less than `bound`.
+    ///
+    /// # Panics
+    ///
+    /// If `bound` is zero.
+    fn random_below(&mut self, bound: u64) -> u64 {
+        assert!(bound != 0, "the bound of a random number must not be zero");
+        // Reject the values above the largest multiple of `bound` so the result is not biased.
+        let limit = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.random_u64();
+            if value < limit {
+                return value % bound;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<R: secp256k1::rand::RngCore + ?Sized> RandomSource for R {
+    fn fill_bytes(&mut self, dest: &mut [u8]) { secp256k1::rand::RngCore::fill_bytes(self, dest) }
+}
+
+/// A deterministic random source, for tests and reproducible runs.
+///
+/// Produces the SHA-256 hashes of the seed followed by a block counter. The same seed always
+/// produces the same bytes, so use a secret seed of fresh randomness for anything but tests.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeterministicRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    /// The number of bytes of `block` already returned.
+    used: usize,
+}
+
+impl DeterministicRng {
+    /// Constructs a new random source producing the bytes derived from `seed`.
+    pub const fn new(seed: [u8; 32]) -> Self { Self { seed, counter: 0, block: [0; 32], used: 32 } }
+
+    fn next_block(&mut self) {
+        let mut engine = sha256::Hash::engine();
+        engine.input(&self.seed);
+        engine.input(&self.counter.to_le_bytes());
+        self.block = sha256::Hash::from_engine(engine).to_byte_array();
+        self.counter = self.counter.wrapping_add(1);
+        self.used = 0;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for DeterministicRng {
+    /// Erases the seed and the bytes derived from it.
+    fn zeroize(&mut self) {
+        self.seed.zeroize();
+        self.block.zeroize();
+        self.counter.zeroize();
+        self.used = self.block.len();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for DeterministicRng {
+    fn drop(&mut self) { zeroize::Zeroize::zeroize(self); }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for DeterministicRng {}
+
+impl RandomSource for DeterministicRng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.used == self.block.len() {
+                self.next_block();
+            }
+            *byte = self.block[self.used];
+            self.used += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic_rng() {
+        let mut rng = DeterministicRng::new([1; 32]);
+        let bytes: [u8; 40] = rng.random_array();
+        assert_ne!(bytes, [0; 40]);
+
+        // Splitting a request does not change the bytes produced.
+        let mut split = DeterministicRng::new([1; 32]);
+        let mut first = [0; 7];
+        let mut second = [0; 33];
+        split.fill_bytes(&mut first);
+        split.fill_bytes(&mut second);
+        assert_eq!(first, bytes[..7]);
+        assert_eq!(second, bytes[7..]);
+        assert_eq!(rng, split);
+
+        assert_ne!(
+            DeterministicRng::new([2; 32]).random_u64(),
+            DeterministicRng::new([1; 32]).random_u64()
+        );
+    }
+
+    #[test]
+    fn random_below() {
+        let mut rng = DeterministicRng::new([0; 32]);
+        let mut seen = [false; 3];
+        for _ in 0..100 {
+            seen[rng.random_below(3) as usize] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+        assert_eq!(rng.random_below(1), 0);
+    }
+}
@@ -0,0 +1,949 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Bitcoin Core compatible JSON types.
+//!
+//! This module provides `serde` types that match the verbose JSON returned by Bitcoin Core's
+//! `getrawtransaction` (with `verbose=2`) and `getblock` (with `verbosity=2`) RPC calls.
+//!
+//! Each type can be built from the corresponding rust-bitcoin type and converted back again.
+//! Fields that depend on chain state (confirmations, median time past, chainwork etc.) cannot be
+//! computed from a transaction or block alone and are therefore optional, callers that have
+//! this information can set the fields directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::constants::genesis_block;
+//! use bitcoin::rpc_json::VerboseBlock;
+//! use bitcoin::{BlockHeight, Network};
+//!
+//! let genesis = genesis_block(Network::Bitcoin);
+//! let verbose = VerboseBlock::from_block(&genesis, BlockHeight::ZERO, Network::Bitcoin);
+//! assert_eq!(verbose.tx[0].vout[0].script_pubkey.script_type.as_str(), "pubkey");
+//!
+//! let json = serde_json::to_string(&verbose).unwrap();
+//! let back = serde_json::from_str::<VerboseBlock>(&json).unwrap();
+//! assert_eq!(back.to_block().unwrap().block_hash(), genesis.block_hash());
+//! ```
+
+use core::fmt;
+
+use hex_unstable::DisplayHex as _;
+use internals::write_err;
+use serde::{Deserialize, Serialize};
+
+use crate::address::{Address, NetworkUnchecked};
+use crate::block::{self, Block, BlockCheckedExt as _, BlockHash, Header, HeaderExt as _};
+use crate::constants::MAX_SCRIPT_SIZE;
+use crate::network::Params;
+use crate::opcodes::all::*;
+use crate::opcodes::Opcode;
+use crate::prelude::{String, ToString, Vec};
+use crate::script::{self, Instruction, Script, ScriptExt as _, ScriptPubKeyExt as _};
+use crate::transaction::{self, OutPoint, Transaction, TransactionExt as _, TxIn, TxOut};
+use crate::witness::Witness;
+use crate::witness_program::{WitnessProgram, WitnessProgramKind};
+use crate::{
+    absolute, ecdsa, Amount, BlockHeight, BlockTime, CompactTarget, EcdsaSighashType, ScriptPubKey,
+    ScriptPubKeyBuf, ScriptSigBuf, Sequence, TxMerkleNode, Txid, Work, Wtxid,
+};
+
+/// The verbose form of a transaction as returned by `getrawtransaction <txid> 2`.
+///
+/// Also used for the entries of [`VerboseBlock::tx`], in which case the block context fields
+/// (`blockhash`, `confirmations`, `time` and `blocktime`) are omitted.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VerboseTransaction {
+    /// The transaction ID.
+    pub txid: Txid,
+    /// The witness transaction ID (Core calls this `hash`).
+    #[serde(rename = "hash")]
+    pub wtxid: Wtxid,
+    /// The transaction version.
+    pub version: u32,
+    /// The serialized transaction size in bytes.
+    pub size: usize,
+    /// The virtual transaction size.
+    pub vsize: usize,
+    /// The transaction weight.
+    pub weight: u64,
+    /// The transaction lock time.
+    pub locktime: absolute::LockTime,
+    /// The transaction inputs.
+    pub vin: Vec<VerboseInput>,
+    /// The transaction outputs.
+    pub vout: Vec<VerboseOutput>,
+    /// The transaction fee, only present if all prevouts are known.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::amount::serde::as_btc::opt"
+    )]
+    pub fee: Option<Amount>,
+    /// The consensus encoded transaction.
+    #[serde(with = "crate::consensus::serde::With::<crate::consensus::serde::Hex>")]
+    pub hex: Transaction,
+    /// The hash of the block containing this transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockhash: Option<BlockHash>,
+    /// The number of confirmations of the block containing this transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmations: Option<u64>,
+    /// The block time, same as `blocktime`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<BlockTime>,
+    /// The time of the block containing this transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocktime: Option<BlockTime>,
+}
+
+impl VerboseTransaction {
+    /// Constructs the verbose form of `tx` without any prevout or block information.
+    ///
+    /// Addresses in output scripts are rendered for the network in `params`.
+    pub fn from_transaction(tx: &Transaction, params: impl AsRef<Params>) -> Self {
+        let params = params.as_ref();
+        Self {
+            txid: tx.compute_txid(),
+            wtxid: tx.compute_wtxid(),
+            version: tx.version.to_u32(),
+            size: tx.total_size(),
+            vsize: tx.vsize(),
+            weight: tx.weight().to_wu(),
+            locktime: tx.lock_time,
+            vin: tx.inputs.iter().map(VerboseInput::from_tx_in).collect(),
+            vout: tx
+                .outputs
+                .iter()
+                .enumerate()
+                .map(|(n, txout)| VerboseOutput::from_tx_out(txout, n as u32, params))
+                .collect(),
+            fee: None,
+            hex: tx.clone(),
+            blockhash: None,
+            confirmations: None,
+            time: None,
+            blocktime: None,
+        }
+    }
+
+    /// Attaches the outputs spent by this transaction and computes the fee.
+    ///
+    /// `prevouts` must contain one entry per input in input order. Coinbase transactions do not
+    /// spend any outputs, Core omits `prevout` and `fee` for them and so `prevouts` must be empty.
+    ///
+    /// # Errors
+    ///
+    /// If the number of prevouts does not match the number of (non-coinbase) inputs or if the
+    /// outputs are worth more than the inputs.
+    pub fn set_prevouts(&mut self, prevouts: Vec<VerbosePrevout>) -> Result<(), Error> {
+        let expected = if self.hex.is_coinbase() { 0 } else { self.vin.len() };
+        if prevouts.len() != expected {
+            return Err(Error::PrevoutCount { expected, got: prevouts.len() });
+        }
+        if expected == 0 {
+            return Ok(());
+        }
+
+        let input_value =
+            prevouts.iter().map(|p| p.value).checked_sum().ok_or(Error::FeeOverflow)?;
+        let output_value =
+            self.vout.iter().map(|o| o.value).checked_sum().ok_or(Error::FeeOverflow)?;
+        let fee = input_value.checked_sub(output_value).ok_or(Error::NegativeFee)?;
+
+        for (input, prevout) in self.vin.iter_mut().zip(prevouts) {
+            input.prevout = Some(prevout);
+        }
+        self.fee = Some(fee);
+        Ok(())
+    }
+
+    /// Sets the block context fields for a transaction that was included in a block.
+    pub fn set_block(&mut self, block_hash: BlockHash, time: BlockTime, confirmations: u64) {
+        self.blockhash = Some(block_hash);
+        self.time = Some(time);
+        self.blocktime = Some(time);
+        self.confirmations = Some(confirmations);
+    }
+
+    /// Reconstructs the transaction from the decoded fields.
+    ///
+    /// The `hex` field is ignored, instead the transaction is rebuilt from the inputs and outputs.
+    ///
+    /// # Errors
+    ///
+    /// If an input is missing its outpoint or the reconstructed transaction does not hash to
+    /// `txid`/`hash`.
+    pub fn to_transaction(&self) -> Result<Transaction, Error> {
+        let inputs = self
+            .vin
+            .iter()
+            .enumerate()
+            .map(|(index, input)| input.to_tx_in().ok_or(Error::MissingOutPoint { index }))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = self.vout.iter().map(VerboseOutput::to_tx_out).collect();
+
+        let tx = Transaction {
+            version: transaction::Version::maybe_non_standard(self.version),
+            lock_time: self.locktime,
+            inputs,
+            outputs,
+        };
+
+        let txid = tx.compute_txid();
+        if txid != self.txid {
+            return Err(Error::TxidMismatch { expected: self.txid, computed: txid });
+        }
+        let wtxid = tx.compute_wtxid();
+        if wtxid != self.wtxid {
+            return Err(Error::WtxidMismatch { expected: self.wtxid, computed: wtxid });
+        }
+        Ok(tx)
+    }
+}
+
+/// A transaction input in [`VerboseTransaction::vin`].
+///
+/// Coinbase inputs have `coinbase` set and no `txid`, `vout` or `scriptSig`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerboseInput {
+    /// The coinbase scriptSig, only present for coinbase inputs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coinbase: Option<ScriptSigBuf>,
+    /// The ID of the transaction being spent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txid: Option<Txid>,
+    /// The index of the output being spent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vout: Option<u32>,
+    /// The input script.
+    #[serde(rename = "scriptSig", default, skip_serializing_if = "Option::is_none")]
+    pub script_sig: Option<VerboseScriptSig>,
+    /// The witness stack, omitted if empty.
+    #[serde(rename = "txinwitness", default, skip_serializing_if = "Witness::is_empty")]
+    pub witness: Witness,
+    /// The output being spent, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prevout: Option<VerbosePrevout>,
+    /// The input sequence number.
+    pub sequence: Sequence,
+}
+
+impl VerboseInput {
+    /// Constructs the verbose form of a transaction input.
+    pub fn from_tx_in(txin: &TxIn) -> Self {
+        let witness = txin.witness.clone();
+        if txin.previous_output == OutPoint::COINBASE_PREVOUT {
+            Self {
+                coinbase: Some(txin.script_sig.clone()),
+                txid: None,
+                vout: None,
+                script_sig: None,
+                witness,
+                prevout: None,
+                sequence: txin.sequence,
+            }
+        } else {
+            Self {
+                coinbase: None,
+                txid: Some(txin.previous_output.txid),
+                vout: Some(txin.previous_output.vout),
+                script_sig: Some(VerboseScriptSig::new(txin.script_sig.clone())),
+                witness,
+                prevout: None,
+                sequence: txin.sequence,
+            }
+        }
+    }
+
+    /// Converts back into a [`TxIn`], returns `None` if a non-coinbase input lacks an outpoint.
+    pub fn to_tx_in(&self) -> Option<TxIn> {
+        let (previous_output, script_sig) = match self.coinbase {
+            Some(ref script_sig) => (OutPoint::COINBASE_PREVOUT, script_sig.clone()),
+            None => {
+                let outpoint = OutPoint { txid: self.txid?, vout: self.vout? };
+                let script_sig =
+                    self.script_sig.as_ref().map(|s| s.hex.clone()).unwrap_or_default();
+                (outpoint, script_sig)
+            }
+        };
+        Some(TxIn {
+            previous_output,
+            script_sig,
+            sequence: self.sequence,
+            witness: self.witness.clone(),
+        })
+    }
+}
+
+/// A transaction output in [`VerboseTransaction::vout`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerboseOutput {
+    /// The output value.
+    #[serde(with = "crate::amount::serde::as_btc")]
+    pub value: Amount,
+    /// The index of this output in the transaction.
+    pub n: u32,
+    /// The output script.
+    #[serde(rename = "scriptPubKey")]
+    pub script_pubkey: VerboseScriptPubKey,
+}
+
+impl VerboseOutput {
+    /// Constructs the verbose form of the output at index `n`.
+    pub fn from_tx_out(txout: &TxOut, n: u32, params: impl AsRef<Params>) -> Self {
+        Self {
+            value: txout.amount,
+            n,
+            script_pubkey: VerboseScriptPubKey::new(txout.script_pubkey.clone(), params),
+        }
+    }
+
+    /// Converts back into a [`TxOut`].
+    pub fn to_tx_out(&self) -> TxOut {
+        TxOut { amount: self.value, script_pubkey: self.script_pubkey.hex.clone() }
+    }
+}
+
+/// An output spent by an input, see [`VerboseInput::prevout`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerbosePrevout {
+    /// Whether the spent output was created by a coinbase transaction.
+    pub generated: bool,
+    /// The height of the block that created the spent output.
+    pub height: BlockHeight,
+    /// The value of the spent output.
+    #[serde(with = "crate::amount::serde::as_btc")]
+    pub value: Amount,
+    /// The script of the spent output.
+    #[serde(rename = "scriptPubKey")]
+    pub script_pubkey: VerboseScriptPubKey,
+}
+
+impl VerbosePrevout {
+    /// Constructs a new prevout from the spent output and the block it was created in.
+    pub fn new(
+        txout: &TxOut,
+        height: BlockHeight,
+        generated: bool,
+        params: impl AsRef<Params>,
+    ) -> Self {
+        Self {
+            generated,
+            height,
+            value: txout.amount,
+            script_pubkey: VerboseScriptPubKey::new(txout.script_pubkey.clone(), params),
+        }
+    }
+
+    /// Converts back into a [`TxOut`].
+    pub fn to_tx_out(&self) -> TxOut {
+        TxOut { amount: self.value, script_pubkey: self.script_pubkey.hex.clone() }
+    }
+}
+
+/// An input script with its Core style assembly representation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerboseScriptSig {
+    /// Assembly representation, signatures have their sighash type decoded (e.g. `[ALL]`).
+    pub asm: String,
+    /// The raw script.
+    pub hex: ScriptSigBuf,
+}
+
+impl VerboseScriptSig {
+    /// Constructs a new `VerboseScriptSig` from an input script.
+    pub fn new(script_sig: ScriptSigBuf) -> Self {
+        Self { asm: core_asm(&script_sig, true), hex: script_sig }
+    }
+}
+
+/// An output script with its Core style assembly representation, type and address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerboseScriptPubKey {
+    /// Assembly representation.
+    pub asm: String,
+    /// The inferred output descriptor.
+    ///
+    /// This crate does not infer descriptors so this is `None` unless deserialized from Core.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    /// The raw script.
+    pub hex: ScriptPubKeyBuf,
+    /// The address, only present for scripts with an address form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address<NetworkUnchecked>>,
+    /// The script type.
+    #[serde(rename = "type")]
+    pub script_type: ScriptType,
+}
+
+impl VerboseScriptPubKey {
+    /// Constructs a new `VerboseScriptPubKey`, rendering the address for the network in `params`.
+    pub fn new(script_pubkey: ScriptPubKeyBuf, params: impl AsRef<Params>) -> Self {
+        let address = Address::from_script(&script_pubkey, params).ok().map(Address::to_unchecked);
+        Self {
+            asm: core_asm(&script_pubkey, false),
+            desc: None,
+            script_type: ScriptType::from_script(&script_pubkey),
+            address,
+            hex: script_pubkey,
+        }
+    }
+}
+
+/// The output script type as reported by Bitcoin Core's `type` field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ScriptType {
+    /// Script does not match any standard template.
+    Nonstandard,
+    /// Pay to public key.
+    Pubkey,
+    /// Pay to public key hash.
+    Pubkeyhash,
+    /// Pay to script hash.
+    Scripthash,
+    /// Bare multisig.
+    Multisig,
+    /// `OP_RETURN` followed by data pushes.
+    Nulldata,
+    /// Pay to witness public key hash.
+    #[serde(rename = "witness_v0_keyhash")]
+    WitnessV0Keyhash,
+    /// Pay to witness script hash.
+    #[serde(rename = "witness_v0_scripthash")]
+    WitnessV0Scripthash,
+    /// Pay to taproot.
+    #[serde(rename = "witness_v1_taproot")]
+    WitnessV1Taproot,
+    /// Pay to anchor.
+    Anchor,
+    /// Witness program with an unknown version or length.
+    WitnessUnknown,
+}
+
+impl ScriptType {
+    /// Classifies `script` the same way Bitcoin Core's `Solver` does.
+    pub fn from_script(script: &ScriptPubKey) -> Self {
+        let bytes = script.as_bytes();
+        if let Some(program) = WitnessProgram::from_script_pubkey(script) {
+            return match program.kind() {
+                WitnessProgramKind::V0Keyhash(_) => Self::WitnessV0Keyhash,
+                WitnessProgramKind::V0Scripthash(_) => Self::WitnessV0Scripthash,
+                WitnessProgramKind::V1Taproot(_) => Self::WitnessV1Taproot,
+                WitnessProgramKind::V1Anchor => Self::Anchor,
+                // Core classifies version 1 programs of 32 bytes by their length, even if they
+                // are not a valid key.
+                WitnessProgramKind::Future { program } if program.is_p2tr() =>
+                    Self::WitnessV1Taproot,
+                _ => Self::WitnessUnknown,
+            };
+        }
+        // Version 0 programs of other lengths are not witness programs.
+        if script.witness_version().is_some() {
+            return Self::Nonstandard;
+        }
+
+        if script.is_p2pkh() {
+            Self::Pubkeyhash
+        } else if script.is_p2sh() {
+            Self::Scripthash
+        } else if script.is_p2pk() {
+            Self::Pubkey
+        } else if script.is_multisig() {
+            Self::Multisig
+        } else if bytes.first() == Some(&OP_RETURN.to_u8())
+            && ScriptPubKey::from_bytes(&bytes[1..]).is_push_only()
+        {
+            Self::Nulldata
+        } else {
+            Self::Nonstandard
+        }
+    }
+
+    /// Returns the string used by Bitcoin Core for this script type.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Nonstandard => "nonstandard",
+            Self::Pubkey => "pubkey",
+            Self::Pubkeyhash => "pubkeyhash",
+            Self::Scripthash => "scripthash",
+            Self::Multisig => "multisig",
+            Self::Nulldata => "nulldata",
+            Self::WitnessV0Keyhash => "witness_v0_keyhash",
+            Self::WitnessV0Scripthash => "witness_v0_scripthash",
+            Self::WitnessV1Taproot => "witness_v1_taproot",
+            Self::Anchor => "anchor",
+            Self::WitnessUnknown => "witness_unknown",
+        }
+    }
+}
+
+impl fmt::Display for ScriptType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// The verbose form of a block as returned by `getblock <hash> 2`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub struct VerboseBlock {
+    /// The block hash.
+    pub hash: BlockHash,
+    /// The number of confirmations, requires chain state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmations: Option<i64>,
+    /// The serialized block size in bytes.
+    pub size: usize,
+    /// The block size excluding witness data.
+    pub strippedsize: usize,
+    /// The block weight.
+    pub weight: u64,
+    /// The block height.
+    pub height: BlockHeight,
+    /// The block version.
+    pub version: i32,
+    /// The block version as hex.
+    #[serde(rename = "versionHex")]
+    pub version_hex: String,
+    /// The merkle root of the transactions.
+    pub merkleroot: TxMerkleNode,
+    /// The transactions in the block.
+    pub tx: Vec<VerboseTransaction>,
+    /// The block time.
+    pub time: BlockTime,
+    /// The median time past, requires chain state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mediantime: Option<u32>,
+    /// The header nonce.
+    pub nonce: u32,
+    /// The compact target.
+    #[serde(with = "bits_as_hex")]
+    pub bits: CompactTarget,
+    /// The difficulty.
+    pub difficulty: f64,
+    /// The total work in the chain up to and including this block, requires chain state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chainwork: Option<Work>,
+    /// The number of transactions.
+    #[serde(rename = "nTx")]
+    pub n_tx: usize,
+    /// The hash of the previous block, omitted for the genesis block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previousblockhash: Option<BlockHash>,
+    /// The hash of the next block, requires chain state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nextblockhash: Option<BlockHash>,
+}
+
+impl VerboseBlock {
+    /// Constructs the verbose form of `block` at `height` without any chain state information.
+    pub fn from_block(
+        block: &Block<block::Checked>,
+        height: BlockHeight,
+        params: impl AsRef<Params>,
+    ) -> Self {
+        let params = params.as_ref();
+        let header = block.header();
+        let prev = header.prev_blockhash;
+        let stripped_size = Header::SIZE
+            + encoding::CompactSizeEncoder::encoded_size(block.transactions().len())
+            + block.transactions().iter().map(|tx| tx.base_size()).sum::<usize>();
+
+        Self {
+            hash: block.block_hash(),
+            confirmations: None,
+            size: block.total_size(),
+            strippedsize: stripped_size,
+            weight: block.weight().to_wu(),
+            height,
+            version: header.version.to_consensus(),
+            version_hex: format!("{:08x}", header.version.to_consensus()),
+            merkleroot: header.merkle_root,
+            tx: block
+                .transactions()
+                .iter()
+                .map(|tx| VerboseTransaction::from_transaction(tx, params))
+                .collect(),
+            time: header.time,
+            mediantime: None,
+            nonce: header.nonce,
+            bits: header.bits,
+            difficulty: header.difficulty_float(params),
+            chainwork: None,
+            n_tx: block.transactions().len(),
+            previousblockhash: if prev == BlockHash::GENESIS_PREVIOUS_BLOCK_HASH {
+                None
+            } else {
+                Some(prev)
+            },
+            nextblockhash: None,
+        }
+    }
+
+    /// Reconstructs the block from the decoded fields.
+    ///
+    /// # Errors
+    ///
+    /// If any transaction fails to reconstruct, or if the block does not hash to `hash` or its
+    /// merkle root does not match the transactions.
+    pub fn to_block(&self) -> Result<Block<block::Checked>, Error> {
+        let header = Header {
+            version: block::Version::from_consensus(self.version),
+            prev_blockhash: self
+                .previousblockhash
+                .unwrap_or(BlockHash::GENESIS_PREVIOUS_BLOCK_HASH),
+            merkle_root: self.merkleroot,
+            time: self.time,
+            bits: self.bits,
+            nonce: self.nonce,
+        };
+        let block_hash = header.block_hash();
+        if block_hash != self.hash {
+            return Err(Error::BlockHashMismatch { expected: self.hash, computed: block_hash });
+        }
+
+        let transactions = self
+            .tx
+            .iter()
+            .map(VerboseTransaction::to_transaction)
+            .collect::<Result<Vec<_>, _>>()?;
+        Block::new_unchecked(header, transactions).validate().map_err(Error::InvalidBlock)
+    }
+}
+
+/// Error converting the verbose JSON types back into rust-bitcoin types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A non-coinbase input has no `txid` or `vout`.
+    MissingOutPoint {
+        /// The index of the offending input.
+        index: usize,
+    },
+    /// Number of prevouts does not match the number of inputs.
+    PrevoutCount {
+        /// The number of prevouts required.
+        expected: usize,
+        /// The number of prevouts supplied.
+        got: usize,
+    },
+    /// The input or output value sum overflowed.
+    FeeOverflow,
+    /// The outputs are worth more than the inputs.
+    NegativeFee,
+    /// The reconstructed transaction has a different txid.
+    TxidMismatch {
+        /// The txid in the JSON.
+        expected: Txid,
+        /// The txid of the reconstructed transaction.
+        computed: Txid,
+    },
+    /// The reconstructed transaction has a different wtxid.
+    WtxidMismatch {
+        /// The wtxid in the JSON.
+        expected: Wtxid,
+        /// The wtxid of the reconstructed transaction.
+        computed: Wtxid,
+    },
+    /// The reconstructed header has a different block hash.
+    BlockHashMismatch {
+        /// The block hash in the JSON.
+        expected: BlockHash,
+        /// The block hash of the reconstructed header.
+        computed: BlockHash,
+    },
+    /// The reconstructed block failed validation.
+    InvalidBlock(block::InvalidBlockError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::MissingOutPoint { index } =>
+                write!(f, "input {} has neither a coinbase nor a txid and vout", index),
+            Self::PrevoutCount { expected, got } =>
+                write!(f, "expected {} prevouts but got {}", expected, got),
+            Self::FeeOverflow => f.write_str("value sum overflowed"),
+            Self::NegativeFee => f.write_str("output value exceeds input value"),
+            Self::TxidMismatch { expected, computed } =>
+                write!(f, "txid mismatch, expected {} computed {}", expected, computed),
+            Self::WtxidMismatch { expected, computed } =>
+                write!(f, "wtxid mismatch, expected {} computed {}", expected, computed),
+            Self::BlockHashMismatch { expected, computed } =>
+                write!(f, "block hash mismatch, expected {} computed {}", expected, computed),
+            Self::InvalidBlock(ref e) => write_err!(f, "invalid block"; e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::InvalidBlock(ref e) => Some(e),
+            Self::MissingOutPoint { .. }
+            | Self::PrevoutCount { .. }
+            | Self::FeeOverflow
+            | Self::NegativeFee
+            | Self::TxidMismatch { .. }
+            | Self::WtxidMismatch { .. }
+            | Self::BlockHashMismatch { .. } => None,
+        }
+    }
+}
+
+/// Sum of amounts that returns `None` on overflow.
+trait CheckedSum {
+    fn checked_sum(self) -> Option<Amount>;
+}
+
+impl<I: Iterator<Item = Amount>> CheckedSum for I {
+    fn checked_sum(mut self) -> Option<Amount> { self.try_fold(Amount::ZERO, Amount::checked_add) }
+}
+
+/// Formats `script` the same way as Bitcoin Core's `ScriptToAsmStr`.
+///
+/// Pushes of up to four bytes are rendered as script numbers, longer pushes as hex. If
+/// `decode_sighash` is set, pushes that are valid DER signatures have their sighash type
+/// suffix rendered in brackets (e.g. `[ALL]`), which Core does for input scripts only.
+fn core_asm<T>(script: &Script<T>, decode_sighash: bool) -> String {
+    use core::fmt::Write as _;
+
+    let unspendable =
+        script.as_bytes().first() == Some(&OP_RETURN.to_u8()) || script.len() > MAX_SCRIPT_SIZE;
+    let mut asm = String::new();
+    for instruction in script.instructions() {
+        if !asm.is_empty() {
+            asm.push(' ');
+        }
+        match instruction {
+            Ok(Instruction::PushBytes(push)) => {
+                let bytes = push.as_bytes();
+                if bytes.len() <= 4 {
+                    let num = script::read_scriptint_non_minimal(bytes)
+                        .expect("four bytes or less never overflow");
+                    write!(asm, "{}", num).expect("writing to string never fails");
+                } else if let Some(sighash) = decode_sighash
+                    .then(|| ecdsa::Signature::from_slice(bytes).ok())
+                    .flatten()
+                    .filter(|_| !unspendable)
+                {
+                    let (_, der) = bytes.split_last().expect("valid signature is not empty");
+                    write!(asm, "{:x}[{}]", der.as_hex(), core_sighash_name(sighash.sighash_type))
+                        .expect("writing to string never fails");
+                } else {
+                    write!(asm, "{:x}", bytes.as_hex()).expect("writing to string never fails");
+                }
+            }
+            Ok(Instruction::Op(op)) => asm.push_str(&core_opcode_name(op)),
+            Err(_) => {
+                asm.push_str("[error]");
+                break;
+            }
+        }
+    }
+    asm
+}
+
+/// Returns the sighash type name used by Bitcoin Core's `mapSigHashTypes`.
+fn core_sighash_name(sighash_type: EcdsaSighashType) -> &'static str {
+    use EcdsaSighashType::*;
+
+    match sighash_type {
+        All => "ALL",
+        None => "NONE",
+        Single => "SINGLE",
+        AllPlusAnyoneCanPay => "ALL|ANYONECANPAY",
+        NonePlusAnyoneCanPay => "NONE|ANYONECANPAY",
+        SinglePlusAnyoneCanPay => "SINGLE|ANYONECANPAY",
+    }
+}
+
+/// Returns the opcode name used by Bitcoin Core's `GetOpName`.
+fn core_opcode_name(op: Opcode) -> String {
+    match op {
+        OP_PUSHBYTES_0 => "0".to_string(),
+        OP_1NEGATE => "-1".to_string(),
+        op if op.decode_pushnum().is_some() =>
+            op.decode_pushnum().expect("checked above").to_string(),
+        OP_CLTV => "OP_CHECKLOCKTIMEVERIFY".to_string(),
+        OP_CSV => "OP_CHECKSEQUENCEVERIFY".to_string(),
+        OP_INVALIDOPCODE => "OP_INVALIDOPCODE".to_string(),
+        op if op.to_u8() > OP_CHECKSIGADD.to_u8() => "OP_UNKNOWN".to_string(),
+        op => op.to_string(),
+    }
+}
+
+mod bits_as_hex {
+    //! Serializes [`CompactTarget`] as eight hex digits like Bitcoin Core does.
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::prelude::String;
+    use crate::CompactTarget;
+
+    pub fn serialize<S: Serializer>(bits: &CompactTarget, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(&format_args!("{:08x}", bits.to_consensus()))
+    }
+
+    pub fn deserialize<'d, D: Deserializer<'d>>(d: D) -> Result<CompactTarget, D::Error> {
+        let s = String::deserialize(d)?;
+        u32::from_str_radix(&s, 16)
+            .map(CompactTarget::from_consensus)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_lit::hex;
+
+    use super::*;
+    use crate::consensus::encode::deserialize;
+    use crate::constants::genesis_block;
+    use crate::Network;
+
+    const SOME_TX: &str = "0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000";
+
+    #[test]
+    fn verbose_transaction() {
+        let tx: Transaction = deserialize(&hex!(SOME_TX)).unwrap();
+        let verbose = VerboseTransaction::from_transaction(&tx, Network::Bitcoin);
+
+        assert_eq!(verbose.txid, tx.compute_txid());
+        assert_eq!(verbose.size, SOME_TX.len() / 2);
+        assert_eq!(verbose.vsize, verbose.size);
+
+        let script_sig = verbose.vin[0].script_sig.as_ref().unwrap();
+        assert_eq!(
+            script_sig.asm,
+            "3046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c[ALL] \
+             033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52"
+        );
+
+        let spk = &verbose.vout[0].script_pubkey;
+        assert_eq!(
+            spk.asm,
+            "OP_DUP OP_HASH160 0389035a9225b3839e2bbf32d826a1e222031fd8 OP_EQUALVERIFY OP_CHECKSIG"
+        );
+        assert_eq!(spk.script_type, ScriptType::Pubkeyhash);
+        assert_eq!(
+            spk.address.as_ref().unwrap().assume_checked_ref().to_string(),
+            "1KhAQw5BU3i4G8BDGpw8YqUKRLuW3mQdh"
+        );
+        assert_eq!(verbose.to_transaction().unwrap(), tx);
+    }
+
+    #[test]
+    fn verbose_transaction_json_field_names() {
+        let tx: Transaction = deserialize(&hex!(SOME_TX)).unwrap();
+        let verbose = VerboseTransaction::from_transaction(&tx, Network::Bitcoin);
+        let json = serde_json::to_value(&verbose).unwrap();
+
+        assert_eq!(json["hash"], json["txid"]);
+        assert_eq!(json["hex"], SOME_TX);
+        assert_eq!(json["vout"][0]["value"], 1.0);
+        assert_eq!(json["vout"][0]["scriptPubKey"]["type"], "pubkeyhash");
+        assert_eq!(json["vin"][0]["sequence"], 0xffff_ffff_u32);
+        assert!(json["vin"][0].get("txinwitness").is_none());
+        assert!(json.get("fee").is_none());
+
+        let back = serde_json::from_value::<VerboseTransaction>(json).unwrap();
+        assert_eq!(back, verbose);
+    }
+
+    #[test]
+    fn prevouts_and_fee() {
+        let tx: Transaction = deserialize(&hex!(SOME_TX)).unwrap();
+        let mut verbose = VerboseTransaction::from_transaction(&tx, Network::Bitcoin);
+
+        assert_eq!(verbose.set_prevouts(vec![]), Err(Error::PrevoutCount { expected: 1, got: 0 }));
+
+        let spent = TxOut {
+            amount: Amount::from_sat(100_010_000).unwrap(),
+            script_pubkey: tx.outputs[0].script_pubkey.clone(),
+        };
+        let prevout = VerbosePrevout::new(&spent, BlockHeight::from(100), false, Network::Bitcoin);
+        verbose.set_prevouts(vec![prevout]).unwrap();
+        assert_eq!(verbose.fee, Some(Amount::from_sat(10_000).unwrap()));
+        assert_eq!(verbose.vin[0].prevout.as_ref().unwrap().to_tx_out(), spent);
+
+        let json = serde_json::to_value(&verbose).unwrap();
+        assert_eq!(json["fee"], 0.0001);
+        assert_eq!(json["vin"][0]["prevout"]["height"], 100);
+    }
+
+    #[test]
+    fn verbose_genesis_block() {
+        let genesis = genesis_block(Network::Bitcoin);
+        let verbose = VerboseBlock::from_block(&genesis, BlockHeight::ZERO, Network::Bitcoin);
+
+        assert_eq!(verbose.size, 285);
+        assert_eq!(verbose.strippedsize, 285);
+        assert_eq!(verbose.weight, 1140);
+        assert_eq!(verbose.version_hex, "00000001");
+        assert_eq!(verbose.difficulty, 1.0);
+        assert_eq!(verbose.previousblockhash, None);
+
+        let coinbase = &verbose.tx[0];
+        assert!(coinbase.vin[0].coinbase.is_some());
+        assert!(coinbase.vin[0].txid.is_none());
+        assert_eq!(coinbase.vout[0].script_pubkey.script_type, ScriptType::Pubkey);
+        assert!(coinbase.vout[0].script_pubkey.asm.ends_with(" OP_CHECKSIG"));
+
+        let json = serde_json::to_value(&verbose).unwrap();
+        assert_eq!(json["bits"], "1d00ffff");
+        assert_eq!(json["nTx"], 1);
+        assert_eq!(json["versionHex"], "00000001");
+        assert_eq!(json["tx"][0]["vout"][0]["value"], 50.0);
+
+        let back = serde_json::from_value::<VerboseBlock>(json).unwrap().to_block().unwrap();
+        assert_eq!(back.header(), genesis.header());
+        assert_eq!(back.transactions(), genesis.transactions());
+    }
+
+    #[test]
+    fn to_block_detects_tampering() {
+        let genesis = genesis_block(Network::Bitcoin);
+        let mut verbose = VerboseBlock::from_block(&genesis, BlockHeight::ZERO, Network::Bitcoin);
+        verbose.nonce += 1;
+        assert!(matches!(verbose.to_block(), Err(Error::BlockHashMismatch { .. })));
+    }
+
+    #[test]
+    fn script_type_classification() {
+        let cases: &[(&str, ScriptType)] = &[
+            ("0014751e76e8199196d454941c45d1b3a323f1433bd6", ScriptType::WitnessV0Keyhash),
+            ("00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262", ScriptType::WitnessV0Scripthash),
+            ("5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c", ScriptType::WitnessV1Taproot),
+            // Not a valid key.
+            ("5120ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", ScriptType::WitnessV1Taproot),
+            ("51024e73", ScriptType::Anchor),
+            ("5228751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6", ScriptType::WitnessUnknown),
+            ("a914751e76e8199196d454941c45d1b3a323f1433bd687", ScriptType::Scripthash),
+            ("6a0b68656c6c6f20776f726c64", ScriptType::Nulldata),
+            ("6a", ScriptType::Nulldata),
+            ("6aab", ScriptType::Nonstandard),
+            ("0015751e76e8199196d454941c45d1b3a323f1433bd600", ScriptType::Nonstandard),
+        ];
+        for (script, want) in cases {
+            let script =
+                ScriptPubKeyBuf::from_bytes(hex_unstable::FromHex::from_hex(script).unwrap());
+            assert_eq!(ScriptType::from_script(&script), *want, "{}", script);
+        }
+    }
+
+    #[test]
+    fn asm_matches_core() {
+        let script = ScriptPubKeyBuf::from_bytes(hex!("005103e80300b1b2babbff6a4f").to_vec());
+        assert_eq!(
+            core_asm(&script, false),
+            "0 1 1000 OP_CHECKLOCKTIMEVERIFY OP_CHECKSEQUENCEVERIFY OP_CHECKSIGADD OP_UNKNOWN \
+             OP_INVALIDOPCODE OP_RETURN -1"
+        );
+        let truncated = ScriptPubKeyBuf::from_bytes(hex!("76a914").to_vec());
+        assert_eq!(core_asm(&truncated, false), "OP_DUP OP_HASH160 [error]");
+    }
+}
@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Scanning blocks for wallet activity.
+//!
+//! A [`Scanner`] walks the transactions of a block in order and reports to a [`Visitor`] every
+//! input spending a watched outpoint and every output paying to a watched script. Outputs found
+//! while scanning are watched from then on, so spends later in the same block (or in later blocks)
+//! are reported as well.
+//!
+//! A [`BlockIndexer`] instead records all outputs and spends of the blocks it is given, by script
+//! and by outpoint, as an explorer backend would.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::scan::{Payment, Scanner, Visitor};
+//! use bitcoin::script::ScriptSet;
+//! # use bitcoin::{Block, BlockHeight, block::Checked};
+//! # fn get_block() -> (Block<Checked>, BlockHeight) {
+//! #     (bitcoin::constants::genesis_block(bitcoin::Network::Bitcoin), BlockHeight::ZERO)
+//! # }
+//!
+//! #[derive(Default)]
+//! struct Balance(u64);
+//!
+//! impl Visitor for Balance {
+//!     fn payment(&mut self, payment: &Payment<'_>) { self.0 += payment.output.amount.to_sat() }
+//! }
+//!
+//! let (block, height) = get_block();
+//! let script_pubkey = block.transactions()[0].outputs[0].script_pubkey.clone();
+//! let mut scanner = Scanner::new([script_pubkey].into_iter().collect::<ScriptSet>());
+//!
+//! let mut balance = Balance::default();
+//! scanner.scan_block(&block, height, &mut balance);
+//! assert_eq!(balance.0, 50 * 100_000_000);
+//! ```
+
+use crate::block::{Block, Checked};
+use crate::constants::COINBASE_MATURITY;
+use crate::prelude::{BTreeMap, BTreeSet, Vec};
+use crate::script::{ScriptPubKey, ScriptPubKeyBuf, ScriptSet};
+use crate::transaction::{OutPoint, Transaction, TxOut, Txid};
+use crate::{Amount, BlockHeight};
+
+/// The position of a transaction in the chain.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TxPosition {
+    /// The height of the block containing the transaction.
+    pub height: BlockHeight,
+    /// The index of the transaction in its block, the coinbase has index 0.
+    pub index: usize,
+    /// The TXID of the transaction.
+    pub txid: Txid,
+}
+
+impl TxPosition {
+    /// Returns `true` if the transaction is the coinbase of its block.
+    pub fn is_coinbase(&self) -> bool { self.index == 0 }
+}
+
+/// An input spending a watched outpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spend<'a> {
+    /// The position of the spending transaction.
+    pub position: TxPosition,
+    /// The spending transaction.
+    pub tx: &'a Transaction,
+    /// The index of the spending input in `tx`.
+    pub input_index: usize,
+    /// The outpoint being spent.
+    pub out_point: OutPoint,
+}
+
+/// An output paying to a watched script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment<'a> {
+    /// The position of the paying transaction.
+    pub position: TxPosition,
+    /// The paying transaction.
+    pub tx: &'a Transaction,
+    /// The outpoint of the new output.
+    pub out_point: OutPoint,
+    /// The new output.
+    pub output: &'a TxOut,
+}
+
+impl Payment<'_> {
+    /// Returns `true` if the output was created by a coinbase transaction.
+    pub fn is_coinbase(&self) -> bool { self.position.is_coinbase() }
+
+    /// Returns the height of the first block that may spend the output.
+    ///
+    /// Coinbase outputs can only be spent after [`COINBASE_MATURITY`] blocks, other outputs can be
+    /// spent in the block that created them.
+    pub fn mature_height(&self) -> BlockHeight {
+        if self.is_coinbase() {
+            BlockHeight::from_u32(self.position.height.to_u32().saturating_add(COINBASE_MATURITY))
+        } else {
+            self.position.height
+        }
+    }
+}
+
+/// Receives the activity found by a [`Scanner`].
+///
+/// Within a block, events are reported in transaction order. For each transaction all spends are
+/// reported before the payments.
+pub trait Visitor {
+    /// Called for every input spending a watched outpoint.
+    fn spend(&mut self, spend: &Spend<'_>) { let _ = spend; }
+
+    /// Called for every output paying to a watched script.
+    fn payment(&mut self, payment: &Payment<'_>) { let _ = payment; }
+}
+
+/// Scans blocks for spends of watched outpoints and payments to watched scripts.
+#[derive(Debug, Clone, Default)]
+pub struct Scanner {
+    scripts: ScriptSet,
+    out_points: BTreeSet<OutPoint>,
+}
+
+impl Scanner {
+    /// Constructs a new scanner watching `scripts` and no outpoints.
+    pub fn new(scripts: ScriptSet) -> Self { Self { scripts, out_points: BTreeSet::new() } }
+
+    /// Returns the watched scripts.
+    pub fn scripts(&self) -> &ScriptSet { &self.scripts }
+
+    /// Returns the watched scripts mutably, for example to add newly derived addresses.
+    pub fn scripts_mut(&mut self) -> &mut ScriptSet { &mut self.scripts }
+
+    /// Returns the watched outpoints.
+    ///
+    /// This includes all unspent outputs found while scanning.
+    pub fn out_points(&self) -> &BTreeSet<OutPoint> { &self.out_points }
+
+    /// Watches `out_point` for spends.
+    ///
+    /// Returns `true` if the outpoint was not watched already.
+    pub fn watch_out_point(&mut self, out_point: OutPoint) -> bool {
+        self.out_points.insert(out_point)
+    }
+
+    /// Stops watching `out_point`.
+    ///
+    /// Returns `true` if the outpoint was watched.
+    pub fn unwatch_out_point(&mut self, out_point: &OutPoint) -> bool {
+        self.out_points.remove(out_point)
+    }
+
+    /// Scans `block`, which is at `height`, and reports all activity to `visitor`.
+    ///
+    /// Spent outpoints are no longer watched afterwards, new outputs paying to a watched script
+    /// are.
+    pub fn scan_block<V: Visitor + ?Sized>(
+        &mut self,
+        block: &Block<Checked>,
+        height: BlockHeight,
+        visitor: &mut V,
+    ) {
+        for (index, tx) in block.transactions().iter().enumerate() {
+            self.scan_tx(tx, index, height, visitor);
+        }
+    }
+
+    fn scan_tx<V: Visitor + ?Sized>(
+        &mut self,
+        tx: &Transaction,
+        index: usize,
+        height: BlockHeight,
+        visitor: &mut V,
+    ) {
+        // Only compute the TXID if the transaction is relevant.
+        let mut txid = None;
+        let mut position =
+            || TxPosition { height, index, txid: *txid.get_or_insert_with(|| tx.compute_txid()) };
+
+        // The coinbase input does not spend anything.
+        if index != 0 {
+            for (input_index, input) in tx.inputs.iter().enumerate() {
+                if self.out_points.remove(&input.previous_output) {
+                    let out_point = input.previous_output;
+                    visitor.spend(&Spend { position: position(), tx, input_index, out_point });
+                }
+            }
+        }
+
+        for (vout, output) in tx.outputs.iter().enumerate() {
+            if self.scripts.contains(&output.script_pubkey) {
+                let position = position();
+                let vout = u32::try_from(vout).expect("less than 2^32 outputs");
+                let out_point = OutPoint { txid: position.txid, vout };
+                self.out_points.insert(out_point);
+                visitor.payment(&Payment { position, tx, out_point, output });
+            }
+        }
+    }
+}
+
+/// An output recorded by a [`BlockIndexer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct IndexedOutput {
+    /// The TXID of the transaction creating the output.
+    pub txid: Txid,
+    /// The index of the output in the transaction.
+    pub vout: u32,
+    /// The amount of the output.
+    pub amount: Amount,
+}
+
+impl IndexedOutput {
+    /// Returns the outpoint of the output.
+    pub fn out_point(&self) -> OutPoint { OutPoint { txid: self.txid, vout: self.vout } }
+}
+
+/// A spend recorded by a [`BlockIndexer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct IndexedSpend {
+    /// The TXID of the spending transaction.
+    pub txid: Txid,
+    /// The index of the spending input in the transaction.
+    pub vin: u32,
+}
+
+/// Records the outputs and spends of blocks.
+///
+/// Outputs are indexed by their script pubkey, spends by the outpoint they spend. Blocks are
+/// expected in chain order, the outputs of each script are kept in the order they were created.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockIndexer {
+    outputs: BTreeMap<ScriptPubKeyBuf, Vec<IndexedOutput>>,
+    spends: BTreeMap<OutPoint, IndexedSpend>,
+}
+
+impl BlockIndexer {
+    /// Constructs a new, empty, indexer.
+    pub fn new() -> Self { Self::default() }
+
+    /// Constructs a new indexer holding the outputs and spends of `block`.
+    pub fn from_block(block: &Block<Checked>) -> Self {
+        let mut indexer = Self::new();
+        indexer.index_block(block);
+        indexer
+    }
+
+    /// Records the outputs and spends of `block`.
+    pub fn index_block(&mut self, block: &Block<Checked>) {
+        for (index, tx) in block.transactions().iter().enumerate() {
+            let txid = tx.compute_txid();
+
+            // The coinbase input does not spend anything.
+            if index != 0 {
+                for (vin, input) in tx.inputs.iter().enumerate() {
+                    let vin = u32::try_from(vin).expect("less than 2^32 inputs");
+                    self.spends.insert(input.previous_output, IndexedSpend { txid, vin });
+                }
+            }
+
+            for (vout, output) in tx.outputs.iter().enumerate() {
+                let vout = u32::try_from(vout).expect("less than 2^32 outputs");
+                self.outputs.entry(output.script_pubkey.clone()).or_default().push(IndexedOutput {
+                    txid,
+                    vout,
+                    amount: output.amount,
+                });
+            }
+        }
+    }
+
+    /// Returns the outputs paying to `script_pubkey`, in the order they were created.
+    pub fn outputs_to(&self, script_pubkey: &ScriptPubKey) -> &[IndexedOutput] {
+        self.outputs.get(script_pubkey).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the spend of `out_point`, if it was spent in an indexed block.
+    pub fn spend_of(&self, out_point: &OutPoint) -> Option<IndexedSpend> {
+        self.spends.get(out_point).copied()
+    }
+
+    /// Returns the outputs of the indexed blocks by script pubkey.
+    pub fn outputs(&self) -> &BTreeMap<ScriptPubKeyBuf, Vec<IndexedOutput>> { &self.outputs }
+
+    /// Returns the spends of the indexed blocks by spent outpoint.
+    pub fn spends(&self) -> &BTreeMap<OutPoint, IndexedSpend> { &self.spends }
+
+    /// Converts the indexer into the outputs by script pubkey and the spends by spent outpoint.
+    pub fn into_maps(
+        self,
+    ) -> (BTreeMap<ScriptPubKeyBuf, Vec<IndexedOutput>>, BTreeMap<OutPoint, IndexedSpend>) {
+        (self.outputs, self.spends)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{block, script, tx};
+
+    #[derive(Default)]
+    struct Recorder {
+        spends: Vec<(TxPosition, usize, OutPoint)>,
+        payments: Vec<(TxPosition, OutPoint, BlockHeight)>,
+    }
+
+    impl Visitor for Recorder {
+        fn spend(&mut self, spend: &Spend<'_>) {
+            self.spends.push((spend.position, spend.input_index, spend.out_point));
+        }
+
+        fn payment(&mut self, payment: &Payment<'_>) {
+            self.payments.push((payment.position, payment.out_point, payment.mature_height()));
+        }
+    }
+
+    #[test]
+    fn reports_payments_and_spends_in_order() {
+        let watched_before = OutPoint { txid: Txid::from_byte_array([7; 32]), vout: 3 };
+        let coinbase = tx(&[OutPoint::COINBASE_PREVOUT], &[script(1)]);
+        let pay = tx(&[watched_before], &[script(2), script(1)]);
+        let pay_txid = pay.compute_txid();
+        let spend = tx(&[OutPoint { txid: pay_txid, vout: 1 }], &[script(3)]);
+        let unrelated = tx(&[OutPoint { txid: pay_txid, vout: 0 }], &[script(3)]);
+        let block = block(vec![coinbase.clone(), pay, spend.clone(), unrelated]);
+
+        let mut scanner = Scanner::new([script(1)].into_iter().collect());
+        assert!(scanner.watch_out_point(watched_before));
+        let mut recorder = Recorder::default();
+        let height = BlockHeight::from_u32(500);
+        scanner.scan_block(&block, height, &mut recorder);
+
+        let coinbase_txid = coinbase.compute_txid();
+        let position = |index, txid| TxPosition { height, index, txid };
+        assert_eq!(
+            recorder.spends,
+            [
+                (position(1, pay_txid), 0, watched_before),
+                (position(2, spend.compute_txid()), 0, OutPoint { txid: pay_txid, vout: 1 }),
+            ]
+        );
+        assert_eq!(
+            recorder.payments,
+            [
+                (
+                    position(0, coinbase_txid),
+                    OutPoint { txid: coinbase_txid, vout: 0 },
+                    BlockHeight::from_u32(600)
+                ),
+                (position(1, pay_txid), OutPoint { txid: pay_txid, vout: 1 }, height),
+            ]
+        );
+
+        // Only the unspent coinbase output is left.
+        assert_eq!(
+            scanner.out_points().iter().collect::<Vec<_>>(),
+            [&OutPoint { txid: coinbase_txid, vout: 0 }]
+        );
+    }
+
+    #[test]
+    fn coinbase_input_is_not_a_spend() {
+        let coinbase = tx(&[OutPoint::COINBASE_PREVOUT], &[script(9)]);
+        let block = block(vec![coinbase]);
+
+        let mut scanner = Scanner::default();
+        scanner.watch_out_point(OutPoint::COINBASE_PREVOUT);
+        let mut recorder = Recorder::default();
+        scanner.scan_block(&block, BlockHeight::ZERO, &mut recorder);
+        assert!(recorder.spends.is_empty());
+        assert!(recorder.payments.is_empty());
+        assert!(scanner.unwatch_out_point(&OutPoint::COINBASE_PREVOUT));
+    }
+
+    #[test]
+    fn block_indexer() {
+        let earlier = OutPoint { txid: Txid::from_byte_array([7; 32]), vout: 3 };
+        let coinbase = tx(&[OutPoint::COINBASE_PREVOUT], &[script(1)]);
+        let pay = tx(&[earlier], &[script(2), script(1)]);
+        let pay_txid = pay.compute_txid();
+        let spend = tx(&[OutPoint { txid: pay_txid, vout: 0 }], &[script(3)]);
+        let spend_txid = spend.compute_txid();
+        let block = block(vec![coinbase.clone(), pay, spend]);
+
+        let indexer = BlockIndexer::from_block(&block);
+        let coinbase_txid = coinbase.compute_txid();
+        let output = |txid, vout| IndexedOutput { txid, vout, amount: Amount::ONE_SAT };
+        assert_eq!(indexer.outputs_to(&script(1)), [output(coinbase_txid, 0), output(pay_txid, 1)]);
+        assert_eq!(indexer.outputs_to(&script(2)), [output(pay_txid, 0)]);
+        assert_eq!(indexer.outputs_to(&script(3)), [output(spend_txid, 0)]);
+        assert!(indexer.outputs_to(&script(4)).is_empty());
+        assert_eq!(
+            indexer.outputs_to(&script(2))[0].out_point(),
+            OutPoint { txid: pay_txid, vout: 0 }
+        );
+
+        assert_eq!(indexer.spend_of(&earlier), Some(IndexedSpend { txid: pay_txid, vin: 0 }));
+        assert_eq!(
+            indexer.spend_of(&OutPoint { txid: pay_txid, vout: 0 }),
+            Some(IndexedSpend { txid: spend_txid, vin: 0 })
+        );
+        assert_eq!(indexer.spend_of(&OutPoint::COINBASE_PREVOUT), None);
+
+        let (outputs, spends) = indexer.into_maps();
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(spends.len(), 2);
+    }
+}
@@ -14,7 +14,7 @@
 #[rustfmt::skip]
 #[doc(inline)]
 #[cfg(feature = "secp-recovery")]
-pub use self::message_signing::{MessageSignature, MessageSignatureError};
+pub use self::message_signing::{KeyType, MessageSignature, MessageSignatureError};
 
 /// The prefix for signed messages using Bitcoin's message signing protocol.
 pub const BITCOIN_SIGNED_MSG_PREFIX: &[u8] = b"\x18Bitcoin Signed Message:\n";
@@ -29,7 +29,8 @@ mod message_signing {
     use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
 
     use crate::address::{Address, AddressType};
-    use crate::crypto::key::PublicKey;
+    use crate::crypto::key::{CompressedPublicKey, PublicKey};
+    use crate::network::NetworkKind;
 
     /// An error used for dealing with Bitcoin Signed Messages.
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -75,6 +76,51 @@ impl From<secp256k1::Error> for MessageSignatureError {
         fn from(e: secp256k1::Error) -> Self { Self::InvalidEncoding(e) }
     }
 
+    /// The type of key, and address, a message signature was created for.
+    ///
+    /// The type is encoded in the header byte of a serialized signature, together with the
+    /// recovery ID: the header byte is the base value of the type plus the recovery ID.
+    #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+    pub enum KeyType {
+        /// An uncompressed key for a P2PKH address, header bytes 27 to 30.
+        Uncompressed,
+        /// A compressed key for a P2PKH address, header bytes 31 to 34.
+        ///
+        /// Some wallets also use this type for SegWit addresses.
+        Compressed,
+        /// A key for a P2SH-P2WPKH address as defined in BIP-0137, header bytes 35 to 38.
+        P2shP2wpkh,
+        /// A key for a P2WPKH address as defined in BIP-0137, header bytes 39 to 42.
+        P2wpkh,
+    }
+
+    impl KeyType {
+        /// Returns the header byte for this type and recovery ID 0.
+        pub fn header_base(self) -> u8 {
+            match self {
+                Self::Uncompressed => 27,
+                Self::Compressed => 31,
+                Self::P2shP2wpkh => 35,
+                Self::P2wpkh => 39,
+            }
+        }
+
+        /// Returns the key type encoded in a header byte, or `None` if the header byte is not in
+        /// the range 27 to 42.
+        pub fn from_header(header: u8) -> Option<Self> {
+            match header {
+                27..=30 => Some(Self::Uncompressed),
+                31..=34 => Some(Self::Compressed),
+                35..=38 => Some(Self::P2shP2wpkh),
+                39..=42 => Some(Self::P2wpkh),
+                _ => None,
+            }
+        }
+
+        /// Returns `true` if keys of this type are serialized in compressed form.
+        pub fn is_compressed(self) -> bool { !matches!(self, Self::Uncompressed) }
+    }
+
     /// A signature on a Bitcoin Signed Message.
     ///
     /// In order to use the `to_base64` and `from_base64` methods, as well as the
@@ -94,25 +140,41 @@ pub fn new(signature: RecoverableSignature, compressed: bool) -> Self {
             Self { signature, compressed }
         }
 
+        /// Returns the P2PKH key type of this signature, the one used by [`Self::serialize`].
+        pub fn key_type(&self) -> KeyType {
+            if self.compressed {
+                KeyType::Compressed
+            } else {
+                KeyType::Uncompressed
+            }
+        }
+
         /// Serializes to bytes.
-        pub fn serialize(&self) -> [u8; 65] {
+        pub fn serialize(&self) -> [u8; 65] { self.serialize_with_key_type(self.key_type()) }
+
+        /// Serializes to bytes with the header byte of `key_type`.
+        ///
+        /// Use this to create the BIP-0137 signatures for SegWit addresses. The key type is not
+        /// checked against `compressed`.
+        pub fn serialize_with_key_type(&self, key_type: KeyType) -> [u8; 65] {
             let (recid, raw) = self.signature.serialize_compact();
             let mut serialized = [0u8; 65];
-            serialized[0] = recid.to_u8() + if self.compressed { 31 } else { 27 };
+            serialized[0] = key_type.header_base() + recid.to_u8();
             serialized[1..].copy_from_slice(&raw[..]);
             serialized
         }
 
         /// Constructs a new `MessageSignature` from a fixed-length array.
+        ///
+        /// Accepts the header bytes 27 to 42, see [`KeyType`]. The SegWit key types of BIP-0137
+        /// are read as compressed, use [`KeyType::from_header`] to get the exact type.
         pub fn from_byte_array(bytes: &[u8; 65]) -> Result<Self, secp256k1::Error> {
-            // We just check this here so we can safely subtract further.
-            if bytes[0] < 27 {
-                return Err(secp256k1::Error::InvalidRecoveryId);
-            };
-            let recid = RecoveryId::try_from(((bytes[0] - 27) & 0x03) as i32)?;
+            let key_type =
+                KeyType::from_header(bytes[0]).ok_or(secp256k1::Error::InvalidRecoveryId)?;
+            let recid = RecoveryId::try_from(i32::from(bytes[0] - key_type.header_base()))?;
             Ok(Self {
                 signature: RecoverableSignature::from_compact(&bytes[1..], recid)?,
-                compressed: ((bytes[0] - 27) & 0x04) != 0,
+                compressed: key_type.is_compressed(),
             })
         }
 
@@ -154,6 +216,40 @@ pub fn is_signed_by_address(
                 None => Ok(false),
             }
         }
+
+        /// Verifies that the signature signs the message and was signed by the given address,
+        /// also accepting the SegWit addresses of BIP-0137.
+        ///
+        /// Unlike [`Self::is_signed_by_address`], which matches Bitcoin Core, this accepts
+        /// compressed signatures for P2SH-P2WPKH and P2WPKH addresses. The header byte isn't part
+        /// of a `MessageSignature`, use [`super::verify_message`] to also check it.
+        ///
+        /// To get the message hash from a message, use [super::signed_msg_hash].
+        pub fn is_signed_by_address_bip137(
+            &self,
+            address: &Address,
+            msg_hash: sha256d::Hash,
+        ) -> Result<bool, MessageSignatureError> {
+            let expected = match address.address_type() {
+                Some(AddressType::P2pkh) => return self.is_signed_by_address(address, msg_hash),
+                Some(AddressType::P2sh) => KeyType::P2shP2wpkh,
+                Some(AddressType::P2wpkh) => KeyType::P2wpkh,
+                Some(address_type) =>
+                    return Err(MessageSignatureError::UnsupportedAddressType(address_type)),
+                None => return Ok(false),
+            };
+            if !self.compressed {
+                return Ok(false);
+            }
+
+            let pubkey = self.recover_pubkey(msg_hash)?;
+            let pubkey = CompressedPublicKey::try_from(pubkey).expect("key type is compressed");
+            let script_pubkey = match expected {
+                KeyType::P2shP2wpkh => Address::p2shwpkh(pubkey, NetworkKind::Main).script_pubkey(),
+                _ => Address::p2wpkh(pubkey, crate::KnownHrp::Mainnet).script_pubkey(),
+            };
+            Ok(address.matches_script_pubkey(&script_pubkey))
+        }
     }
 
     #[cfg(feature = "base64")]
@@ -178,6 +274,13 @@ pub fn from_base64(s: &str) -> Result<Self, MessageSignatureError> {
 
             /// Converts to base64 encoding.
             pub fn to_base64(self) -> String { BASE64_STANDARD.encode(self.serialize()) }
+
+            /// Converts to base64 encoding with the header byte of `key_type`.
+            ///
+            /// See [`Self::serialize_with_key_type`].
+            pub fn to_base64_with_key_type(self, key_type: KeyType) -> String {
+                BASE64_STANDARD.encode(self.serialize_with_key_type(key_type))
+            }
         }
 
         impl fmt::Display for MessageSignature {
@@ -216,6 +319,50 @@ pub fn sign(msg: impl AsRef<[u8]>, privkey: SecretKey) -> MessageSignature {
     MessageSignature { signature: secp_sig, compressed: true }
 }
 
+/// Signs a message using Bitcoin's message signing format and serializes the signature with the
+/// header byte of `key_type`.
+///
+/// Use [`KeyType::Compressed`] for the widest compatibility, Bitcoin Core only verifies
+/// signatures for P2PKH addresses.
+#[cfg(feature = "secp-recovery")]
+pub fn sign_message(msg: impl AsRef<[u8]>, privkey: SecretKey, key_type: KeyType) -> [u8; 65] {
+    let signature = MessageSignature { compressed: key_type.is_compressed(), ..sign(msg, privkey) };
+    signature.serialize_with_key_type(key_type)
+}
+
+/// Verifies that the serialized `signature` signs `msg` and was created by the key of `address`.
+///
+/// Supports P2PKH addresses and the SegWit addresses of BIP-0137, see
+/// [`MessageSignature::is_signed_by_address_bip137`]. A SegWit header byte is only accepted for
+/// its own address type, a compressed P2PKH header byte for all of them since not all wallets use
+/// the BIP-0137 header bytes.
+///
+/// # Errors
+///
+/// If the signature is invalid or the address type is not supported.
+#[cfg(feature = "secp-recovery")]
+pub fn verify_message(
+    address: &crate::Address,
+    msg: impl AsRef<[u8]>,
+    signature: &[u8; 65],
+) -> Result<bool, MessageSignatureError> {
+    use crate::address::AddressType;
+
+    let key_type = KeyType::from_header(signature[0]);
+    let signature = MessageSignature::from_byte_array(signature)?;
+    let header_matches = match (address.address_type(), key_type) {
+        (_, Some(KeyType::Compressed)) => true,
+        (Some(AddressType::P2pkh), key_type) => key_type == Some(KeyType::Uncompressed),
+        (Some(AddressType::P2sh), key_type) => key_type == Some(KeyType::P2shP2wpkh),
+        (Some(AddressType::P2wpkh), key_type) => key_type == Some(KeyType::P2wpkh),
+        _ => true,
+    };
+    if !header_matches {
+        return Ok(false);
+    }
+    signature.is_signed_by_address_bip137(address, signed_msg_hash(msg))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,4 +446,67 @@ fn incorrect_message_signature() {
         let p2pkh = Address::p2pkh(pubkey, NetworkKind::Main);
         assert_eq!(signature.is_signed_by_address(&p2pkh, msg_hash), Ok(false));
     }
+
+    #[test]
+    #[cfg(feature = "secp-recovery")]
+    fn header_bytes() {
+        let privkey = SecretKey::from_secret_bytes([0x33; 32]).unwrap();
+        for (key_type, base) in [
+            (KeyType::Uncompressed, 27),
+            (KeyType::Compressed, 31),
+            (KeyType::P2shP2wpkh, 35),
+            (KeyType::P2wpkh, 39),
+        ] {
+            let bytes = sign_message("message", privkey, key_type);
+            assert!((base..base + 4).contains(&bytes[0]));
+            assert_eq!(KeyType::from_header(bytes[0]), Some(key_type));
+
+            let signature = MessageSignature::from_byte_array(&bytes).unwrap();
+            assert_eq!(signature.compressed, key_type.is_compressed());
+            assert_eq!(signature.serialize_with_key_type(key_type), bytes);
+        }
+
+        let mut bytes = sign("message", privkey).serialize();
+        for header in [0, 26, 43, 255] {
+            bytes[0] = header;
+            assert_eq!(
+                MessageSignature::from_byte_array(&bytes),
+                Err(secp256k1::Error::InvalidRecoveryId)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "secp-recovery")]
+    fn verify_segwit_addresses() {
+        use crate::crypto::key::CompressedPublicKey;
+        use crate::{Address, Network, NetworkKind};
+
+        let privkey = SecretKey::from_secret_bytes([0x33; 32]).unwrap();
+        let pubkey =
+            CompressedPublicKey::from_secp(secp256k1::PublicKey::from_secret_key(&privkey));
+        let p2pkh = Address::p2pkh(pubkey, NetworkKind::Main);
+        let p2shwpkh = Address::p2shwpkh(pubkey, NetworkKind::Main);
+        let p2wpkh = Address::p2wpkh(pubkey, Network::Bitcoin);
+        let message = "address proof";
+
+        let signature = sign_message(message, privkey, KeyType::P2wpkh);
+        assert_eq!(verify_message(&p2wpkh, message, &signature), Ok(true));
+        assert_eq!(verify_message(&p2wpkh, "other message", &signature), Ok(false));
+        assert_eq!(verify_message(&p2shwpkh, message, &signature), Ok(false));
+
+        let signature = sign_message(message, privkey, KeyType::P2shP2wpkh);
+        assert_eq!(verify_message(&p2shwpkh, message, &signature), Ok(true));
+        assert_eq!(verify_message(&p2pkh, message, &signature), Ok(false));
+
+        // Electrum style signatures use the P2PKH header for all addresses.
+        let signature = sign(message, privkey).serialize();
+        assert_eq!(verify_message(&p2pkh, message, &signature), Ok(true));
+        assert_eq!(verify_message(&p2shwpkh, message, &signature), Ok(true));
+        assert_eq!(verify_message(&p2wpkh, message, &signature), Ok(true));
+
+        let signature = sign_message(message, privkey, KeyType::Uncompressed);
+        assert_eq!(verify_message(&p2pkh, message, &signature), Ok(false));
+        assert_eq!(verify_message(&p2wpkh, message, &signature), Ok(false));
+    }
 }
@@ -0,0 +1,737 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Stratum V2 mining protocol messages.
+//!
+//! Stratum V2 frames each message with a 6-byte header, the extension type, the message type and
+//! the length of the payload. This module implements the framing and the messages used to hand
+//! out and submit work: [`NewMiningJob`], [`NewExtendedMiningJob`], [`SetNewPrevHash`],
+//! [`SetTarget`] and [`SubmitSharesStandard`]. Setting up connections and channels, and the noise
+//! encryption of the transport, are out of scope.
+//!
+//! Fields use the types of this crate, so a job and a previous hash combine into a block
+//! [`Header`] directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::block::{BlockHash, Version};
+//! use bitcoin::stratum::{self, NewMiningJob, SetNewPrevHash};
+//! use bitcoin::{BlockTime, CompactTarget, TxMerkleNode};
+//!
+//! let job = NewMiningJob {
+//!     channel_id: 1,
+//!     job_id: 7,
+//!     min_ntime: None,
+//!     version: Version::TWO,
+//!     merkle_root: TxMerkleNode::from_byte_array([1; 32]),
+//! };
+//! let frame = stratum::encode_frame(&job).expect("no variable length fields");
+//! let (decoded, rest) = stratum::decode_frame::<NewMiningJob>(&frame).expect("valid frame");
+//! assert_eq!(decoded, job);
+//! assert!(rest.is_empty());
+//!
+//! let prev_hash = SetNewPrevHash {
+//!     channel_id: 1,
+//!     job_id: 7,
+//!     prev_hash: BlockHash::from_byte_array([2; 32]),
+//!     min_ntime: BlockTime::from_u32(1_700_000_000),
+//!     nbits: CompactTarget::from_consensus(0x1703_2f14),
+//! };
+//! let header = job.header(&prev_hash, prev_hash.min_ntime, 42);
+//! assert_eq!(header.nonce, 42);
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use hashes::{sha256d, HashEngine as _};
+
+use crate::block::{BlockHash, Header, Version};
+use crate::pow::Target;
+use crate::prelude::Vec;
+use crate::{BlockTime, CompactTarget, TxMerkleNode, Txid};
+
+/// The extension type of the mining protocol.
+const MINING_EXTENSION: u16 = 0;
+
+/// The flag in the extension type marking messages addressed to a channel.
+const CHANNEL_MSG_FLAG: u16 = 0x8000;
+
+/// The maximum payload length, the length is encoded in 24 bits.
+const MAX_PAYLOAD: usize = 0x00ff_ffff;
+
+/// The header framing every message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameHeader {
+    /// The protocol extension the message belongs to, zero for the mining protocol.
+    pub extension_type: u16,
+    /// Whether the message is addressed to a channel, the first field is then the channel id.
+    pub channel_msg: bool,
+    /// The type of the message within the extension.
+    pub msg_type: u8,
+    /// The length of the payload following the header.
+    pub msg_length: u32,
+}
+
+impl FrameHeader {
+    /// The size of a serialized header.
+    pub const SIZE: usize = 6;
+
+    /// Serializes the header.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let extension = self.extension_type | if self.channel_msg { CHANNEL_MSG_FLAG } else { 0 };
+        let [e0, e1] = extension.to_le_bytes();
+        let [l0, l1, l2, _] = self.msg_length.to_le_bytes();
+        [e0, e1, self.msg_type, l0, l1, l2]
+    }
+
+    /// Deserializes a header.
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        let extension = u16::from_le_bytes([bytes[0], bytes[1]]);
+        Self {
+            extension_type: extension & !CHANNEL_MSG_FLAG,
+            channel_msg: extension & CHANNEL_MSG_FLAG != 0,
+            msg_type: bytes[2],
+            msg_length: u32::from_le_bytes([bytes[3], bytes[4], bytes[5], 0]),
+        }
+    }
+}
+
+/// A message of the mining protocol.
+pub trait Message: Sized {
+    /// The message type in the frame header.
+    const MSG_TYPE: u8;
+
+    /// Whether the message is addressed to a channel.
+    const CHANNEL_MSG: bool;
+
+    /// Appends the serialized payload of the message to `out`.
+    ///
+    /// # Errors
+    ///
+    /// If a variable length field exceeds its maximum length.
+    fn encode_payload(&self, out: &mut Vec<u8>) -> Result<(), EncodeError>;
+
+    /// Deserializes the payload of a message, which must be consumed completely.
+    ///
+    /// # Errors
+    ///
+    /// If the payload is invalid.
+    fn decode_payload(payload: &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Serializes `msg` as a frame, the header followed by the payload.
+///
+/// # Errors
+///
+/// If a variable length field exceeds its maximum length.
+pub fn encode_frame<M: Message>(msg: &M) -> Result<Vec<u8>, EncodeError> {
+    let mut frame = Vec::from([0; FrameHeader::SIZE]);
+    msg.encode_payload(&mut frame)?;
+    let len = frame.len() - FrameHeader::SIZE;
+    let header = FrameHeader {
+        extension_type: MINING_EXTENSION,
+        channel_msg: M::CHANNEL_MSG,
+        msg_type: M::MSG_TYPE,
+        msg_length: check_len(len, MAX_PAYLOAD)? as u32,
+    };
+    frame[..FrameHeader::SIZE].copy_from_slice(&header.to_bytes());
+    Ok(frame)
+}
+
+/// Deserializes the frame at the start of `bytes` as a message `M`.
+///
+/// Returns the message and the bytes following the frame.
+///
+/// # Errors
+///
+/// If `bytes` is shorter than the frame, the header is not that of `M` or the payload is invalid.
+pub fn decode_frame<M: Message>(bytes: &[u8]) -> Result<(M, &[u8]), DecodeError> {
+    let (header, payload, rest) = split_frame(bytes)?;
+    if header.extension_type != MINING_EXTENSION
+        || header.msg_type != M::MSG_TYPE
+        || header.channel_msg != M::CHANNEL_MSG
+    {
+        return Err(DecodeError::UnexpectedHeader(header));
+    }
+    Ok((M::decode_payload(payload)?, rest))
+}
+
+/// Splits the frame at the start of `bytes` into its header and payload.
+///
+/// Returns the header, the payload and the bytes following the frame. Use the message type of the
+/// header to choose the [`Message`] to decode the payload as.
+///
+/// # Errors
+///
+/// If `bytes` is shorter than the frame.
+pub fn split_frame(bytes: &[u8]) -> Result<(FrameHeader, &[u8], &[u8]), DecodeError> {
+    if bytes.len() < FrameHeader::SIZE {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let (header, bytes) = bytes.split_at(FrameHeader::SIZE);
+    let header = FrameHeader::from_bytes(header.try_into().expect("split at the header size"));
+    let len = header.msg_length as usize;
+    if bytes.len() < len {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let (payload, rest) = bytes.split_at(len);
+    Ok((header, payload, rest))
+}
+
+/// Sent to a standard channel, a job with a fixed Merkle root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewMiningJob {
+    /// The channel the job is for.
+    pub channel_id: u32,
+    /// The identifier of the job, referenced by submitted shares.
+    pub job_id: u32,
+    /// The smallest time the job may be mined with, `None` for a future job that is activated by
+    /// a later [`SetNewPrevHash`].
+    pub min_ntime: Option<BlockTime>,
+    /// The block version.
+    pub version: Version,
+    /// The Merkle root of the block.
+    pub merkle_root: TxMerkleNode,
+}
+
+impl NewMiningJob {
+    /// Returns the block header mining this job on top of `prev_hash` with `time` and `nonce`.
+    pub fn header(&self, prev_hash: &SetNewPrevHash, time: BlockTime, nonce: u32) -> Header {
+        Header {
+            version: self.version,
+            prev_blockhash: prev_hash.prev_hash,
+            merkle_root: self.merkle_root,
+            time,
+            bits: prev_hash.nbits,
+            nonce,
+        }
+    }
+}
+
+impl Message for NewMiningJob {
+    const MSG_TYPE: u8 = 0x15;
+    const CHANNEL_MSG: bool = true;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        out.extend_from_slice(&self.channel_id.to_le_bytes());
+        out.extend_from_slice(&self.job_id.to_le_bytes());
+        encode_option_u32(out, self.min_ntime.map(BlockTime::to_u32));
+        out.extend_from_slice(&self.version.to_consensus().to_le_bytes());
+        out.extend_from_slice(self.merkle_root.as_byte_array());
+        Ok(())
+    }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader(payload);
+        let msg = Self {
+            channel_id: r.u32()?,
+            job_id: r.u32()?,
+            min_ntime: r.option_u32()?.map(BlockTime::from_u32),
+            version: Version::from_consensus(r.u32()? as i32),
+            merkle_root: TxMerkleNode::from_byte_array(r.array()?),
+        };
+        r.finish(msg)
+    }
+}
+
+/// Sent to an extended channel, a job the miner completes the coinbase transaction of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewExtendedMiningJob {
+    /// The channel the job is for.
+    pub channel_id: u32,
+    /// The identifier of the job, referenced by submitted shares.
+    pub job_id: u32,
+    /// The smallest time the job may be mined with, `None` for a future job that is activated by
+    /// a later [`SetNewPrevHash`].
+    pub min_ntime: Option<BlockTime>,
+    /// The block version.
+    pub version: Version,
+    /// Whether the miner may roll the version bits allowed by BIP-0320.
+    pub version_rolling_allowed: bool,
+    /// The Merkle branch of the coinbase transaction, at most 255 hashes.
+    pub merkle_path: Vec<TxMerkleNode>,
+    /// The coinbase transaction before the extranonce, without witness, at most 65535 bytes.
+    pub coinbase_tx_prefix: Vec<u8>,
+    /// The coinbase transaction after the extranonce, without witness, at most 65535 bytes.
+    pub coinbase_tx_suffix: Vec<u8>,
+}
+
+impl NewExtendedMiningJob {
+    /// Returns the Merkle root of the block with the coinbase transaction using `extranonce`.
+    pub fn merkle_root(&self, extranonce: &[u8]) -> TxMerkleNode {
+        let mut engine = sha256d::Hash::engine();
+        engine.input(&self.coinbase_tx_prefix);
+        engine.input(extranonce);
+        engine.input(&self.coinbase_tx_suffix);
+        let txid = Txid::from_byte_array(sha256d::Hash::from_engine(engine).to_byte_array());
+        self.merkle_path
+            .iter()
+            .fold(TxMerkleNode::from_leaf(txid), |node, branch| node.combine(branch))
+    }
+}
+
+impl Message for NewExtendedMiningJob {
+    const MSG_TYPE: u8 = 0x1f;
+    const CHANNEL_MSG: bool = true;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        out.extend_from_slice(&self.channel_id.to_le_bytes());
+        out.extend_from_slice(&self.job_id.to_le_bytes());
+        encode_option_u32(out, self.min_ntime.map(BlockTime::to_u32));
+        out.extend_from_slice(&self.version.to_consensus().to_le_bytes());
+        out.push(u8::from(self.version_rolling_allowed));
+        out.push(check_len(self.merkle_path.len(), 0xff)? as u8);
+        for node in &self.merkle_path {
+            out.extend_from_slice(node.as_byte_array());
+        }
+        encode_b0_64k(out, &self.coinbase_tx_prefix)?;
+        encode_b0_64k(out, &self.coinbase_tx_suffix)
+    }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader(payload);
+        let channel_id = r.u32()?;
+        let job_id = r.u32()?;
+        let min_ntime = r.option_u32()?.map(BlockTime::from_u32);
+        let version = Version::from_consensus(r.u32()? as i32);
+        let version_rolling_allowed = r.bool()?;
+        let len = r.u8()?;
+        let merkle_path = (0..len)
+            .map(|_| r.array().map(TxMerkleNode::from_byte_array))
+            .collect::<Result<_, _>>()?;
+        let msg = Self {
+            channel_id,
+            job_id,
+            min_ntime,
+            version,
+            version_rolling_allowed,
+            merkle_path,
+            coinbase_tx_prefix: r.b0_64k()?,
+            coinbase_tx_suffix: r.b0_64k()?,
+        };
+        r.finish(msg)
+    }
+}
+
+/// Sent to a channel when the chain tip changes, activating a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetNewPrevHash {
+    /// The channel the message is for.
+    pub channel_id: u32,
+    /// The job to mine on top of the new previous block.
+    pub job_id: u32,
+    /// The hash of the previous block.
+    pub prev_hash: BlockHash,
+    /// The smallest time the job may be mined with.
+    pub min_ntime: BlockTime,
+    /// The network target of the block.
+    pub nbits: CompactTarget,
+}
+
+impl Message for SetNewPrevHash {
+    const MSG_TYPE: u8 = 0x20;
+    const CHANNEL_MSG: bool = true;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        out.extend_from_slice(&self.channel_id.to_le_bytes());
+        out.extend_from_slice(&self.job_id.to_le_bytes());
+        out.extend_from_slice(self.prev_hash.as_byte_array());
+        out.extend_from_slice(&self.min_ntime.to_u32().to_le_bytes());
+        out.extend_from_slice(&self.nbits.to_consensus().to_le_bytes());
+        Ok(())
+    }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader(payload);
+        let msg = Self {
+            channel_id: r.u32()?,
+            job_id: r.u32()?,
+            prev_hash: BlockHash::from_byte_array(r.array()?),
+            min_ntime: BlockTime::from_u32(r.u32()?),
+            nbits: CompactTarget::from_consensus(r.u32()?),
+        };
+        r.finish(msg)
+    }
+}
+
+/// Sent to a channel, the maximum target of shares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetTarget {
+    /// The channel the message is for.
+    pub channel_id: u32,
+    /// The target shares must meet.
+    pub maximum_target: Target,
+}
+
+impl Message for SetTarget {
+    const MSG_TYPE: u8 = 0x21;
+    const CHANNEL_MSG: bool = true;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        out.extend_from_slice(&self.channel_id.to_le_bytes());
+        out.extend_from_slice(&self.maximum_target.to_le_bytes());
+        Ok(())
+    }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader(payload);
+        let msg = Self { channel_id: r.u32()?, maximum_target: Target::from_le_bytes(r.array()?) };
+        r.finish(msg)
+    }
+}
+
+/// Sent by a miner on a standard channel, a share of a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitSharesStandard {
+    /// The channel the share is for.
+    pub channel_id: u32,
+    /// The sequence number of the share, acknowledged by the pool.
+    pub sequence_number: u32,
+    /// The job the share is for.
+    pub job_id: u32,
+    /// The nonce of the block header.
+    pub nonce: u32,
+    /// The time of the block header.
+    pub ntime: BlockTime,
+    /// The version of the block header.
+    pub version: Version,
+}
+
+impl SubmitSharesStandard {
+    /// Returns the block header of the share of `job` mined on top of `prev_hash`.
+    pub fn header(&self, job: &NewMiningJob, prev_hash: &SetNewPrevHash) -> Header {
+        Header { version: self.version, ..job.header(prev_hash, self.ntime, self.nonce) }
+    }
+}
+
+impl Message for SubmitSharesStandard {
+    const MSG_TYPE: u8 = 0x1a;
+    const CHANNEL_MSG: bool = true;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        out.extend_from_slice(&self.channel_id.to_le_bytes());
+        out.extend_from_slice(&self.sequence_number.to_le_bytes());
+        out.extend_from_slice(&self.job_id.to_le_bytes());
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out.extend_from_slice(&self.ntime.to_u32().to_le_bytes());
+        out.extend_from_slice(&self.version.to_consensus().to_le_bytes());
+        Ok(())
+    }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader(payload);
+        let msg = Self {
+            channel_id: r.u32()?,
+            sequence_number: r.u32()?,
+            job_id: r.u32()?,
+            nonce: r.u32()?,
+            ntime: BlockTime::from_u32(r.u32()?),
+            version: Version::from_consensus(r.u32()? as i32),
+        };
+        r.finish(msg)
+    }
+}
+
+fn check_len(len: usize, max: usize) -> Result<usize, EncodeError> {
+    if len > max {
+        Err(EncodeError { len, max })
+    } else {
+        Ok(len)
+    }
+}
+
+fn encode_option_u32(out: &mut Vec<u8>, value: Option<u32>) {
+    match value {
+        Some(value) => {
+            out.push(1);
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+}
+
+fn encode_b0_64k(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), EncodeError> {
+    out.extend_from_slice(&(check_len(bytes.len(), 0xffff)? as u16).to_le_bytes());
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Reads the fields of a payload.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> { Ok(self.take(1)?[0]) }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> { Ok(u32::from_le_bytes(self.array()?)) }
+
+    fn bool(&mut self) -> Result<bool, DecodeError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(DecodeError::InvalidBool(b)),
+        }
+    }
+
+    fn option_u32(&mut self) -> Result<Option<u32>, DecodeError> {
+        if self.bool()? {
+            self.u32().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn b0_64k(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let len = u16::from_le_bytes(self.array()?);
+        Ok(self.take(len.into())?.to_vec())
+    }
+
+    fn finish<T>(self, msg: T) -> Result<T, DecodeError> {
+        if self.0.is_empty() {
+            Ok(msg)
+        } else {
+            Err(DecodeError::TrailingBytes)
+        }
+    }
+}
+
+/// A variable length field exceeds its maximum length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EncodeError {
+    /// The length of the field.
+    pub len: usize,
+    /// The maximum length of the field.
+    pub max: usize,
+}
+
+impl From<Infallible> for EncodeError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "field of length {} exceeds the maximum length {}", self.len, self.max)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
+/// An error decoding a frame or message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The data ends before the frame or field.
+    UnexpectedEnd,
+    /// The payload continues after the last field.
+    TrailingBytes,
+    /// A boolean is neither zero nor one.
+    InvalidBool(u8),
+    /// The frame header is not that of the expected message.
+    UnexpectedHeader(FrameHeader),
+}
+
+impl From<Infallible> for DecodeError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of data"),
+            Self::TrailingBytes => f.write_str("trailing bytes after the last field"),
+            Self::InvalidBool(b) => write!(f, "invalid boolean {}", b),
+            Self::UnexpectedHeader(ref header) => write!(
+                f,
+                "unexpected message type {:#04x} of extension {}",
+                header.msg_type, header.extension_type
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_header() {
+        let header = FrameHeader {
+            extension_type: 0,
+            channel_msg: true,
+            msg_type: 0x15,
+            msg_length: 0x0102,
+        };
+        assert_eq!(header.to_bytes(), [0x00, 0x80, 0x15, 0x02, 0x01, 0x00]);
+        assert_eq!(FrameHeader::from_bytes(header.to_bytes()), header);
+    }
+
+    #[test]
+    fn roundtrip() {
+        fn roundtrip<M: Message + PartialEq + fmt::Debug>(msg: M) {
+            let mut frame = encode_frame(&msg).unwrap();
+            frame.push(0xab);
+            let (decoded, rest) = decode_frame::<M>(&frame).unwrap();
+            assert_eq!(decoded, msg);
+            assert_eq!(rest, [0xab]);
+
+            let (header, payload, _) = split_frame(&frame).unwrap();
+            assert_eq!(header.msg_type, M::MSG_TYPE);
+            assert_eq!(header.msg_length as usize, payload.len());
+            assert_eq!(
+                M::decode_payload(&payload[..payload.len() - 1]),
+                Err(DecodeError::UnexpectedEnd)
+            );
+            assert_eq!(
+                decode_frame::<M>(&frame[..frame.len() - 2]).unwrap_err(),
+                DecodeError::UnexpectedEnd
+            );
+            let mut trailing = payload.to_vec();
+            trailing.push(0);
+            assert_eq!(M::decode_payload(&trailing), Err(DecodeError::TrailingBytes));
+        }
+
+        roundtrip(NewMiningJob {
+            channel_id: 1,
+            job_id: 2,
+            min_ntime: Some(BlockTime::from_u32(3)),
+            version: Version::TWO,
+            merkle_root: TxMerkleNode::from_byte_array([4; 32]),
+        });
+        roundtrip(NewExtendedMiningJob {
+            channel_id: 1,
+            job_id: 2,
+            min_ntime: None,
+            version: Version::TWO,
+            version_rolling_allowed: true,
+            merkle_path: vec![TxMerkleNode::from_byte_array([4; 32]); 3],
+            coinbase_tx_prefix: vec![5; 40],
+            coinbase_tx_suffix: vec![6; 50],
+        });
+        roundtrip(SetNewPrevHash {
+            channel_id: 1,
+            job_id: 2,
+            prev_hash: BlockHash::from_byte_array([3; 32]),
+            min_ntime: BlockTime::from_u32(4),
+            nbits: CompactTarget::from_consensus(5),
+        });
+        roundtrip(SetTarget { channel_id: 1, maximum_target: Target::MAX_ATTAINABLE_MAINNET });
+        roundtrip(SubmitSharesStandard {
+            channel_id: 1,
+            sequence_number: 2,
+            job_id: 3,
+            nonce: 4,
+            ntime: BlockTime::from_u32(5),
+            version: Version::TWO,
+        });
+    }
+
+    #[test]
+    fn decode_errors() {
+        let job = NewMiningJob {
+            channel_id: 1,
+            job_id: 2,
+            min_ntime: None,
+            version: Version::TWO,
+            merkle_root: TxMerkleNode::from_byte_array([4; 32]),
+        };
+        let mut frame = encode_frame(&job).unwrap();
+        let header = FrameHeader::from_bytes(frame[..6].try_into().unwrap());
+        assert_eq!(
+            decode_frame::<SetNewPrevHash>(&frame),
+            Err(DecodeError::UnexpectedHeader(header))
+        );
+        frame[14] = 2;
+        assert_eq!(decode_frame::<NewMiningJob>(&frame), Err(DecodeError::InvalidBool(2)));
+
+        let job = NewExtendedMiningJob {
+            channel_id: 1,
+            job_id: 2,
+            min_ntime: None,
+            version: Version::TWO,
+            version_rolling_allowed: false,
+            merkle_path: vec![],
+            coinbase_tx_prefix: vec![0; 0x10000],
+            coinbase_tx_suffix: vec![],
+        };
+        assert_eq!(encode_frame(&job), Err(EncodeError { len: 0x10000, max: 0xffff }));
+    }
+
+    #[test]
+    fn extended_job_header() {
+        let (prefix, extranonce, suffix) = ([1u8; 42], [2u8; 8], [3u8; 60]);
+        let coinbase = [&prefix[..], &extranonce, &suffix].concat();
+        let coinbase_txid = Txid::from_byte_array(sha256d::Hash::hash(&coinbase).to_byte_array());
+        let txids = [
+            coinbase_txid,
+            Txid::from_byte_array([4; 32]),
+            Txid::from_byte_array([5; 32]),
+            Txid::from_byte_array([6; 32]),
+        ];
+        let txid_2_3 =
+            TxMerkleNode::from_leaf(txids[2]).combine(&TxMerkleNode::from_leaf(txids[3]));
+        let job = NewExtendedMiningJob {
+            channel_id: 1,
+            job_id: 2,
+            min_ntime: None,
+            version: Version::TWO,
+            version_rolling_allowed: false,
+            merkle_path: vec![TxMerkleNode::from_leaf(txids[1]), txid_2_3],
+            coinbase_tx_prefix: prefix.to_vec(),
+            coinbase_tx_suffix: suffix.to_vec(),
+        };
+        let merkle_root = job.merkle_root(&extranonce);
+        assert_eq!(Some(merkle_root), TxMerkleNode::calculate_root(txids.into_iter()));
+
+        let prev_hash = SetNewPrevHash {
+            channel_id: 1,
+            job_id: 2,
+            prev_hash: BlockHash::from_byte_array([7; 32]),
+            min_ntime: BlockTime::from_u32(1_700_000_000),
+            nbits: CompactTarget::from_consensus(0x1703_2f14),
+        };
+        let standard = NewMiningJob {
+            channel_id: 1,
+            job_id: 2,
+            min_ntime: None,
+            version: job.version,
+            merkle_root,
+        };
+        let share = SubmitSharesStandard {
+            channel_id: 1,
+            sequence_number: 0,
+            job_id: 2,
+            nonce: 42,
+            ntime: BlockTime::from_u32(1_700_000_100),
+            version: Version::from_consensus(0x2000_4000),
+        };
+        let header = share.header(&standard, &prev_hash);
+        assert_eq!(header.version, share.version);
+        assert_eq!(header.prev_blockhash, prev_hash.prev_hash);
+        assert_eq!(header.merkle_root, merkle_root);
+        assert_eq!(header.time, share.ntime);
+        assert_eq!(header.bits, prev_hash.nbits);
+        assert_eq!(header.nonce, 42);
+    }
+}
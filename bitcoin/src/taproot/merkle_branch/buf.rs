@@ -2,6 +2,8 @@
 
 //! Contains `TaprootMerkleBranchBuf` and its associated types.
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
 use hashes::Hash;
 
 use super::{
@@ -108,6 +110,21 @@ pub fn into_inner(self) -> Vec<TapNodeHash> { self.0 }
     pub fn into_vec(self) -> Vec<TapNodeHash> { self.0 }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for TaprootMerkleBranchBuf {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        // Real trees are shallow, keep most branches short but allow the maximum depth.
+        let len = if u.ratio(1, 16)? {
+            u.int_in_range(0..=TAPROOT_CONTROL_MAX_NODE_COUNT)?
+        } else {
+            u.int_in_range(0..=8)?
+        };
+        let branch =
+            (0..len).map(|_| TapNodeHash::arbitrary(u)).collect::<arbitrary::Result<_>>()?;
+        Ok(Self(branch))
+    }
+}
+
 macro_rules! impl_try_from {
     ($from:ty) => {
         impl TryFrom<$from> for TaprootMerkleBranchBuf {
@@ -11,6 +11,8 @@
 use core::fmt;
 use core::iter::FusedIterator;
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
 use hashes::{hash_newtype, sha256t, sha256t_tag, HashEngine};
 use hex_unstable::{FromHex, HexToBytesError};
 use internals::array::ArrayExt;
@@ -31,7 +33,7 @@
 // Re-export these so downstream only has to use one `taproot` module.
 #[rustfmt::skip]
 #[doc(inline)]
-pub use crate::crypto::taproot::{SerializedSignature, SigFromSliceError, Signature};
+pub use crate::crypto::taproot::{SerializedSignature, SigFromSliceError, Signature, SignatureInfo};
 #[doc(inline)]
 pub use merkle_branch::TaprootMerkleBranch;
 #[doc(inline)]
@@ -1651,6 +1653,100 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 #[cfg(feature = "std")]
 impl std::error::Error for InvalidControlBlockSizeError {}
 
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for TapLeafHash {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::from_byte_array(u.arbitrary()?))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for TapNodeHash {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::from_byte_array(u.arbitrary()?))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for LeafVersion {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        // Tapscript is the only leaf version in use, weight it the same as all future versions.
+        if u.arbitrary()? {
+            return Ok(Self::TapScript);
+        }
+        let version = u.arbitrary::<u8>()? & TAPROOT_LEAF_MASK;
+        Ok(Self::from_consensus(version).unwrap_or(Self::TapScript))
+    }
+}
+
+/// Generates a tree with between 1 and 16 leaves and a randomly chosen shape.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_node_info(
+    u: &mut Unstructured<'_>,
+    allow_hidden: bool,
+) -> arbitrary::Result<NodeInfo> {
+    let mut nodes = Vec::new();
+    for _ in 0..u.int_in_range(1..=16)? {
+        if allow_hidden && u.ratio(1, 4)? {
+            nodes.push(NodeInfo::new_hidden_node(TapNodeHash::arbitrary(u)?));
+        } else {
+            let script = TapScriptBuf::arbitrary(u)?;
+            nodes.push(NodeInfo::new_leaf_with_ver(script, LeafVersion::arbitrary(u)?));
+        }
+    }
+    // Picking which neighbours to combine next makes any tree shape possible. Depth is at most
+    // 15 so combining can never fail.
+    while nodes.len() > 1 {
+        let i = u.choose_index(nodes.len() - 1)?;
+        let b = nodes.remove(i + 1);
+        let a = nodes.remove(i);
+        nodes.insert(i, NodeInfo::combine(a, b).expect("tree depth is at most 15"));
+    }
+    Ok(nodes.pop().expect("there is at least one node"))
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for NodeInfo {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_node_info(u, true)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for TapTree {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(arbitrary_node_info(u, false)?))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for TaprootSpendInfo {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let internal_key = UntweakedPublicKey::arbitrary(u)?;
+        if u.ratio(1, 4)? {
+            Ok(Self::new_key_spend(internal_key, None))
+        } else {
+            Ok(Self::from_node_info(internal_key, NodeInfo::arbitrary(u)?))
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for ControlBlock {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            leaf_version: LeafVersion::arbitrary(u)?,
+            output_key_parity: if u.arbitrary()? {
+                secp256k1::Parity::Odd
+            } else {
+                secp256k1::Parity::Even
+            },
+            internal_key: UntweakedPublicKey::arbitrary(u)?,
+            merkle_branch: TaprootMerkleBranchBuf::arbitrary(u)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use hashes::sha256;
@@ -2062,4 +2158,26 @@ fn bip_341_read_json() -> serde_json::Value {
         let json_str = include_str!("../../tests/data/bip341_tests.json");
         serde_json::from_str(json_str).expect("JSON was not well-formatted")
     }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_spend_info_commits_to_leaves() {
+        for seed in 0..32_u8 {
+            let data = (0..1024_u32).map(|i| (i as u8).wrapping_mul(seed)).collect::<Vec<_>>();
+            let mut u = Unstructured::new(&data);
+
+            let info = TaprootSpendInfo::arbitrary(&mut u).unwrap();
+            let output_key = info.output_key().to_x_only_public_key();
+            for script_ver in info.script_map().keys() {
+                let control_block = info.control_block(script_ver).unwrap();
+                assert!(control_block.verify_taproot_commitment(output_key, &script_ver.0));
+            }
+
+            let control_block = ControlBlock::arbitrary(&mut u).unwrap();
+            assert_eq!(ControlBlock::decode(&control_block.serialize()).unwrap(), control_block);
+
+            let tree = TapTree::arbitrary(&mut u).unwrap();
+            assert!(tree.script_leaves().count() >= 1);
+        }
+    }
 }
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Transaction and block fixtures shared by the unit tests.
+//!
+//! None of these are valid by consensus rules, use [`crate::testutil`] for that.
+
+use crate::block::{self, Block, Checked, Header};
+use crate::constants::genesis_block;
+use crate::network::Network;
+use crate::prelude::Vec;
+use crate::script::ScriptPubKeyBuf;
+use crate::transaction::{self, OutPoint, Transaction, TxIn, TxOut};
+use crate::{absolute, Amount, BlockTime, CompactTarget, TxMerkleNode, Txid};
+
+/// Returns a unique script for `byte`.
+pub(crate) fn script(byte: u8) -> ScriptPubKeyBuf { ScriptPubKeyBuf::from_bytes(vec![0x51, byte]) }
+
+/// Returns an output of a confirmed transaction that isn't part of the test.
+pub(crate) fn confirmed(byte: u8) -> OutPoint {
+    OutPoint { txid: Txid::from_byte_array([byte; 32]), vout: 0 }
+}
+
+/// Builds a transaction spending `inputs` with a one satoshi output to each of `outputs`.
+pub(crate) fn tx(inputs: &[OutPoint], outputs: &[ScriptPubKeyBuf]) -> Transaction {
+    Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        inputs: inputs
+            .iter()
+            .map(|&previous_output| TxIn { previous_output, ..TxIn::EMPTY_COINBASE })
+            .collect(),
+        outputs: outputs
+            .iter()
+            .map(|script_pubkey| TxOut {
+                amount: Amount::ONE_SAT,
+                script_pubkey: script_pubkey.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Builds a transaction spending `inputs` with `outputs` one satoshi outputs to an empty script.
+pub(crate) fn tx_with_outputs(inputs: &[OutPoint], outputs: usize) -> Transaction {
+    tx(inputs, &vec![ScriptPubKeyBuf::new(); outputs])
+}
+
+/// Builds a regtest block on top of the genesis block, without a valid merkle root or work.
+pub(crate) fn block(transactions: Vec<Transaction>) -> Block<Checked> {
+    let header = Header {
+        version: block::Version::TWO,
+        prev_blockhash: genesis_block(Network::Regtest).block_hash(),
+        merkle_root: TxMerkleNode::from_byte_array([0; 32]),
+        time: BlockTime::from_u32(0),
+        bits: CompactTarget::from_consensus(0x207fffff),
+        nonce: 0,
+    };
+    Block::new_unchecked(header, transactions).assume_checked(None)
+}
@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Builder for regtest block chains.
+
+use crate::block::{self, Block, Checked, HeaderExt as _};
+use crate::constants::{genesis_block, COINBASE_MATURITY};
+use crate::merkle_tree::TxMerkleNode;
+use crate::network::Network;
+use crate::opcodes::all::OP_PUSHBYTES_0;
+use crate::pow::Target;
+use crate::prelude::Vec;
+use crate::script::{self, ScriptPubKeyBuf, ScriptSigBuf};
+use crate::testutil::keys;
+use crate::transaction::{self, OutPoint, Transaction, TxIn, TxOut, Utxo};
+use crate::{absolute, Amount, BlockHeight, BlockTime, Sequence};
+
+/// The number of blocks between subsidy halvings on regtest (`nSubsidyHalvingInterval`).
+const REGTEST_SUBSIDY_HALVING_INTERVAL: u32 = 150;
+
+/// Number of seconds between block timestamps.
+const BLOCK_INTERVAL: u32 = 600;
+
+/// Builds a regtest block chain on top of the regtest genesis block.
+///
+/// Every mined block has a coinbase claiming the block subsidy (fees are not claimed since input
+/// amounts are unknown), a BIP-0034 height, a witness commitment and valid proof of work.
+#[derive(Debug, Clone)]
+pub struct ChainBuilder {
+    blocks: Vec<Block<Checked>>,
+}
+
+impl ChainBuilder {
+    /// Constructs a new chain containing only the regtest genesis block.
+    pub fn new() -> Self { Self { blocks: vec![genesis_block(Network::Regtest)] } }
+
+    /// Returns the height of the tip.
+    pub fn height(&self) -> BlockHeight {
+        BlockHeight::from_u32(u32::try_from(self.blocks.len() - 1).expect("fewer than 2^32 blocks"))
+    }
+
+    /// Returns the tip of the chain.
+    pub fn tip(&self) -> &Block<Checked> { self.blocks.last().expect("chain contains genesis") }
+
+    /// Returns all the blocks, starting with genesis.
+    pub fn blocks(&self) -> &[Block<Checked>] { &self.blocks }
+
+    /// Consumes the builder and returns all the blocks, starting with genesis.
+    pub fn into_blocks(self) -> Vec<Block<Checked>> { self.blocks }
+
+    /// Mines a block containing `transactions` on top of the tip.
+    ///
+    /// The coinbase pays the block subsidy to `coinbase_script_pubkey`.
+    ///
+    /// # Panics
+    ///
+    /// If `transactions` contains a coinbase or duplicates. Transactions are otherwise not checked
+    /// against the chain, it is up to the caller to only spend existing outputs.
+    pub fn mine(
+        &mut self,
+        coinbase_script_pubkey: ScriptPubKeyBuf,
+        transactions: Vec<Transaction>,
+    ) -> &Block<Checked> {
+        assert!(
+            transactions.iter().all(|tx| !tx.is_coinbase()),
+            "only the builder adds a coinbase"
+        );
+
+        let height = self.height().to_u32() + 1;
+        let mut txs = Vec::with_capacity(transactions.len() + 1);
+        txs.push(coinbase(height, coinbase_script_pubkey));
+        txs.extend(transactions);
+
+        let tip = self.tip().header();
+        let header = block::Header {
+            version: block::Version::NO_SOFT_FORK_SIGNALLING,
+            prev_blockhash: tip.block_hash(),
+            merkle_root: TxMerkleNode::from_byte_array([0; 32]), // Computed below.
+            time: BlockTime::from_u32(tip.time.to_u32() + BLOCK_INTERVAL),
+            bits: tip.bits,
+            nonce: 0,
+        };
+
+        block::update_witness_commitment(&mut txs).expect("starts with a coinbase");
+
+        let mut header = block::Header {
+            merkle_root: block::compute_merkle_root(&txs).expect("no duplicates"),
+            ..header
+        };
+        let target = Target::from_compact(header.bits);
+        // On regtest roughly every second nonce is valid.
+        while header.validate_pow(target).is_err() {
+            header.nonce += 1;
+        }
+
+        let block = Block::new_unchecked(header, txs).validate().expect("block is valid");
+        self.blocks.push(block);
+        self.tip()
+    }
+
+    /// Mines `count` blocks without transactions, paying the subsidy to `coinbase_script_pubkey`.
+    pub fn mine_empty(&mut self, count: u32, coinbase_script_pubkey: &ScriptPubKeyBuf) {
+        for _ in 0..count {
+            self.mine(coinbase_script_pubkey.clone(), Vec::new());
+        }
+    }
+
+    /// Mines a block paying to the P2WPKH address of [`keys::public_key`] `key` and another
+    /// [`COINBASE_MATURITY`] blocks on top of it, so that the coinbase output can be spent in the
+    /// next block.
+    pub fn mine_mature_coinbase(&mut self, key: u32) -> Utxo {
+        let script_pubkey = keys::p2wpkh_address(key).script_pubkey();
+        let coinbase = &self.mine(script_pubkey.clone(), Vec::new()).transactions()[0];
+        let utxo = Utxo {
+            out_point: OutPoint { txid: coinbase.compute_txid(), vout: 0 },
+            output: coinbase.outputs[0].clone(),
+        };
+        self.mine_empty(COINBASE_MATURITY, &script_pubkey);
+        utxo
+    }
+}
+
+impl Default for ChainBuilder {
+    fn default() -> Self { Self::new() }
+}
+
+/// Returns the block subsidy at `height` on regtest.
+fn subsidy(height: u32) -> Amount {
+    let halvings = height / REGTEST_SUBSIDY_HALVING_INTERVAL;
+    if halvings >= 64 {
+        return Amount::ZERO;
+    }
+    Amount::from_sat(Amount::FIFTY_BTC.to_sat() >> halvings).expect("less than fifty bitcoin")
+}
+
+/// Constructs a coinbase transaction for the block at `height`, without witness commitment.
+fn coinbase(height: u32, script_pubkey: ScriptPubKeyBuf) -> Transaction {
+    // Like Bitcoin Core, append `OP_0` so that the script sig is at least two bytes.
+    let script_sig: ScriptSigBuf = script::Builder::new()
+        .push_int_unchecked(height.into())
+        .push_opcode(OP_PUSHBYTES_0)
+        .into_script();
+    Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        inputs: vec![TxIn {
+            previous_output: OutPoint::COINBASE_PREVOUT,
+            script_sig,
+            sequence: Sequence::MAX,
+            witness: block::coinbase_witness(block::DEFAULT_WITNESS_RESERVED_VALUE),
+        }],
+        outputs: vec![TxOut { amount: subsidy(height), script_pubkey }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockCheckedExt as _;
+
+    #[test]
+    fn blocks_are_linked_and_valid() {
+        let mut chain = ChainBuilder::new();
+        let script_pubkey = keys::p2wpkh_address(0).script_pubkey();
+        chain.mine_empty(3, &script_pubkey);
+
+        assert_eq!(chain.height(), BlockHeight::from_u32(3));
+        for (height, pair) in chain.blocks().windows(2).enumerate() {
+            let header = pair[1].header();
+            assert_eq!(header.prev_blockhash, pair[0].block_hash());
+            assert!(header.validate_pow(Target::from_compact(header.bits)).is_ok());
+            assert_eq!(pair[1].bip34_block_height().unwrap(), height as u64 + 1);
+            assert_eq!(pair[1].transactions()[0].outputs[0].script_pubkey, script_pubkey);
+        }
+    }
+
+    #[test]
+    fn mining_is_deterministic() {
+        let mut a = ChainBuilder::new();
+        let mut b = ChainBuilder::new();
+        a.mine_mature_coinbase(0);
+        b.mine_mature_coinbase(0);
+        assert_eq!(a.tip().block_hash(), b.tip().block_hash());
+    }
+
+    #[test]
+    fn subsidy_halves() {
+        assert_eq!(subsidy(1), Amount::FIFTY_BTC);
+        assert_eq!(subsidy(150), Amount::from_sat_u32(2_500_000_000));
+        assert_eq!(subsidy(150 * 64), Amount::ZERO);
+    }
+}
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Fixed, publicly known keys.
+//!
+//! Keys are identified by an index and derived deterministically so that tests produce the same
+//! transactions and blocks on every run.
+//!
+//! **These keys are not secret, never send funds to them on mainnet.**
+
+use hashes::{sha256, HashEngine as _};
+
+use crate::address::{Address, KnownHrp};
+use crate::crypto::key::{CompressedPublicKey, Keypair, PrivateKey, XOnlyPublicKey};
+use crate::network::NetworkKind;
+
+/// Tag hashed together with the index to derive a private key.
+const TAG: &[u8] = b"rust-bitcoin testutil key";
+
+/// Returns the (regtest) private key with the given `index`.
+pub fn private_key(index: u32) -> PrivateKey {
+    let mut engine = sha256::Hash::engine();
+    engine.input(TAG);
+    engine.input(&index.to_be_bytes());
+    let bytes = sha256::Hash::from_engine(engine).to_byte_array();
+    PrivateKey::from_byte_array(bytes, NetworkKind::Test).expect("a hash is a valid scalar")
+}
+
+/// Returns the key pair with the given `index`.
+pub fn keypair(index: u32) -> Keypair { Keypair::from_secret_key(&private_key(index).inner) }
+
+/// Returns the compressed public key with the given `index`.
+pub fn public_key(index: u32) -> CompressedPublicKey {
+    CompressedPublicKey::from_private_key(private_key(index)).expect("key is compressed")
+}
+
+/// Returns the x-only public key with the given `index`.
+pub fn x_only_public_key(index: u32) -> XOnlyPublicKey { public_key(index).into() }
+
+/// Returns the regtest P2WPKH address for the key with the given `index`.
+pub fn p2wpkh_address(index: u32) -> Address {
+    Address::p2wpkh(public_key(index), KnownHrp::Regtest)
+}
+
+/// Returns the regtest key-path-only (BIP-0086) P2TR address for the key with the given `index`.
+pub fn p2tr_address(index: u32) -> Address {
+    Address::p2tr(x_only_public_key(index), None, KnownHrp::Regtest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_deterministic_and_distinct() {
+        assert_eq!(private_key(7), private_key(7));
+        assert_ne!(private_key(0), private_key(1));
+        assert_eq!(public_key(3), CompressedPublicKey::from_private_key(private_key(3)).unwrap());
+        assert_eq!(keypair(3).to_x_only_public_key().0, x_only_public_key(3));
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Utilities for testing code that uses this library.
+//!
+//! Nothing in here is needed to use the library, the module is feature gated so that it does not
+//! end up in production builds by accident.
+//!
+//! The builders construct regtest data that is valid by consensus rules (as far as this library
+//! can check it): blocks have valid proof of work, Merkle roots, BIP-0034 heights and witness
+//! commitments, and transactions are signed with the fixed [`keys`]. This allows integration tests
+//! to construct chains without copying fixture data around.
+//!
+//! **None of the keys in this module are secret, never send funds to them on mainnet.**
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::testutil::{keys, ChainBuilder, TxBuilder};
+//! use bitcoin::Amount;
+//!
+//! let mut chain = ChainBuilder::new();
+//! // Mines a block paying to key 0 and enough blocks on top for the coinbase to mature.
+//! let utxo = chain.mine_mature_coinbase(0);
+//!
+//! let tx = TxBuilder::new()
+//!     .spend(utxo, 0)
+//!     .pay(keys::p2tr_address(1).script_pubkey(), Amount::from_sat_u32(100_000))
+//!     .build();
+//! chain.mine(keys::p2wpkh_address(0).script_pubkey(), vec![tx]);
+//!
+//! assert_eq!(chain.height().to_u32(), 102);
+//! ```
+
+mod chain;
+pub mod keys;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+mod tx;
+
+#[rustfmt::skip]                // Keep public re-exports separate.
+#[doc(inline)]
+pub use self::{
+    chain::ChainBuilder,
+    tx::TxBuilder,
+};
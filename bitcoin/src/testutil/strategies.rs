@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! [`proptest`](mod@proptest) strategies for Bitcoin types.
+//!
+//! All strategies generate values that are valid by construction: amounts never exceed
+//! [`Amount::MAX_MONEY`], public keys are on the curve, addresses can be parsed back from their
+//! string form and transactions round-trip through consensus encoding. The strategies are intended
+//! to be composed with strategies for downstream types.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::testutil::strategies;
+//! use bitcoin::{Address, Network};
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(address in strategies::address(Network::Regtest))| {
+//!     let parsed = address.to_string().parse::<Address<_>>().unwrap();
+//!     prop_assert!(parsed.is_valid_for_network(Network::Regtest));
+//! });
+//! ```
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::address::{Address, KnownHrp};
+use crate::crypto::key::{CompressedPublicKey, PrivateKey, PubkeyHash, XOnlyPublicKey};
+use crate::network::{Network, NetworkKind, TestnetVersion};
+use crate::script::{
+    PushBytesBuf, ScriptBuf, ScriptHash, ScriptPubKeyBuf, ScriptPubKeyBufExt as _, ScriptSigTag,
+    WScriptHash,
+};
+use crate::taproot::TapNodeHash;
+use crate::transaction::{self, OutPoint, Transaction, TxIn, TxOut, Txid};
+use crate::{absolute, Amount, FeeRate, Sequence, Witness};
+
+/// The maximum number of inputs in a transaction generated by [`transaction()`].
+pub const MAX_INPUTS: usize = 8;
+
+/// The maximum number of outputs in a transaction generated by [`transaction()`].
+pub const MAX_OUTPUTS: usize = 8;
+
+/// Generates an amount between zero and [`Amount::MAX_MONEY`] inclusive.
+pub fn amount() -> impl Strategy<Value = Amount> { amount_up_to(Amount::MAX_MONEY) }
+
+/// Generates an amount between zero and `max` inclusive.
+pub fn amount_up_to(max: Amount) -> impl Strategy<Value = Amount> {
+    (0..=max.to_sat()).prop_map(|sat| Amount::from_sat(sat).expect("at most `max`"))
+}
+
+/// Generates a fee rate.
+///
+/// Most generated fee rates are between 1 and 1,000 sat/vB, the rest is spread over the whole
+/// range representable in sat/kwu so that overflow handling gets exercised too.
+pub fn fee_rate() -> impl Strategy<Value = FeeRate> {
+    prop_oneof![
+        3 => (1..=1_000_u32).prop_map(FeeRate::from_sat_per_vb),
+        1 => any::<u32>().prop_map(FeeRate::from_sat_per_kwu),
+    ]
+}
+
+/// Generates a script containing up to `max_len` random bytes.
+///
+/// The script is not necessarily well formed, i.e., it may contain truncated pushes.
+pub fn script_bytes<T>(max_len: usize) -> impl Strategy<Value = ScriptBuf<T>> {
+    vec(any::<u8>(), 0..=max_len).prop_map(ScriptBuf::from_bytes)
+}
+
+/// Generates a script pubkey paying to one of the address types, or an `OP_RETURN` output.
+pub fn script_pubkey() -> impl Strategy<Value = ScriptPubKeyBuf> {
+    prop_oneof![
+        9 => address(Network::Bitcoin).prop_map(|address| address.script_pubkey()),
+        1 => vec(any::<u8>(), 0..=80).prop_map(|data| {
+            let data = PushBytesBuf::try_from(data).expect("at most 80 bytes");
+            ScriptPubKeyBuf::new_op_return(data)
+        }),
+    ]
+}
+
+/// Generates one of the known networks.
+pub fn network() -> impl Strategy<Value = Network> {
+    prop_oneof![
+        Just(Network::Bitcoin),
+        Just(Network::Testnet(TestnetVersion::V3)),
+        Just(Network::Testnet(TestnetVersion::V4)),
+        Just(Network::Signet),
+        Just(Network::Regtest),
+    ]
+}
+
+/// Generates a private key for the given network.
+pub fn private_key(network: impl Into<NetworkKind>) -> impl Strategy<Value = PrivateKey> {
+    let network = network.into();
+    // Invalid scalars are astronomically unlikely so filtering never rejects in practice.
+    any::<[u8; 32]>().prop_filter_map("invalid secret key", move |bytes| {
+        PrivateKey::from_byte_array(bytes, network).ok()
+    })
+}
+
+/// Generates a compressed public key.
+pub fn compressed_public_key() -> impl Strategy<Value = CompressedPublicKey> {
+    private_key(NetworkKind::Main).prop_map(|sk| {
+        CompressedPublicKey::from_private_key(sk).expect("private keys are compressed by default")
+    })
+}
+
+/// Generates an x-only public key.
+pub fn x_only_public_key() -> impl Strategy<Value = XOnlyPublicKey> {
+    compressed_public_key().prop_map(XOnlyPublicKey::from)
+}
+
+/// Generates an address of any type for `network`.
+///
+/// Addresses are either built from random hashes or, for key-based types, from valid public keys.
+pub fn address(network: Network) -> impl Strategy<Value = Address> {
+    let kind = NetworkKind::from(network);
+    let hrp = KnownHrp::from(network);
+    prop_oneof![
+        any::<[u8; 20]>()
+            .prop_map(move |hash| Address::p2pkh(PubkeyHash::from_byte_array(hash), kind)),
+        any::<[u8; 20]>()
+            .prop_map(move |hash| Address::p2sh_from_hash(ScriptHash::from_byte_array(hash), kind)),
+        compressed_public_key().prop_map(move |pk| Address::p2wpkh(pk, hrp)),
+        compressed_public_key().prop_map(move |pk| Address::p2shwpkh(pk, kind)),
+        any::<[u8; 32]>().prop_map(move |hash| Address::p2wsh_from_hash(
+            WScriptHash::from_byte_array(hash),
+            hrp
+        )),
+        (x_only_public_key(), proptest::option::of(any::<[u8; 32]>())).prop_map(
+            move |(internal_key, merkle_root)| {
+                let merkle_root = merkle_root.map(TapNodeHash::from_byte_array);
+                Address::p2tr(internal_key, merkle_root, hrp)
+            }
+        ),
+        Just(Address::p2a(hrp)),
+    ]
+}
+
+/// Generates an address of any type for any of the known networks.
+pub fn any_address() -> impl Strategy<Value = Address> { network().prop_flat_map(address) }
+
+/// Generates an outpoint with a random txid.
+pub fn out_point() -> impl Strategy<Value = OutPoint> {
+    (any::<[u8; 32]>(), any::<u32>())
+        .prop_map(|(txid, vout)| OutPoint { txid: Txid::from_byte_array(txid), vout })
+}
+
+/// Generates a witness with up to `max_elements` elements of up to 80 bytes each.
+pub fn witness(max_elements: usize) -> impl Strategy<Value = Witness> {
+    vec(vec(any::<u8>(), 0..=80), 0..=max_elements).prop_map(Witness::from)
+}
+
+/// Generates a transaction input spending a random outpoint.
+pub fn tx_in() -> impl Strategy<Value = TxIn> {
+    (out_point(), script_bytes::<ScriptSigTag>(100), any::<u32>(), witness(4)).prop_map(
+        |(previous_output, script_sig, sequence, witness)| TxIn {
+            previous_output,
+            script_sig,
+            sequence: Sequence::from_consensus(sequence),
+            witness,
+        },
+    )
+}
+
+/// Generates a transaction output with an amount of up to `max_amount`.
+pub fn tx_out(max_amount: Amount) -> impl Strategy<Value = TxOut> {
+    (amount_up_to(max_amount), script_pubkey())
+        .prop_map(|(amount, script_pubkey)| TxOut { amount, script_pubkey })
+}
+
+/// Generates a transaction with up to [`MAX_INPUTS`] inputs and [`MAX_OUTPUTS`] outputs.
+///
+/// The transaction always has at least one input and one output, and the total output value never
+/// exceeds [`Amount::MAX_MONEY`]. Inputs are not signed and spend random outpoints.
+pub fn transaction() -> impl Strategy<Value = Transaction> {
+    let max_amount = Amount::from_sat(Amount::MAX_MONEY.to_sat() / MAX_OUTPUTS as u64)
+        .expect("less than MAX_MONEY");
+    (
+        prop_oneof![
+            Just(transaction::Version::ONE),
+            Just(transaction::Version::TWO),
+            Just(transaction::Version::THREE),
+        ],
+        any::<u32>(),
+        vec(tx_in(), 1..=MAX_INPUTS),
+        vec(tx_out(max_amount), 1..=MAX_OUTPUTS),
+    )
+        .prop_map(|(version, lock_time, inputs, outputs)| Transaction {
+            version,
+            lock_time: absolute::LockTime::from_consensus(lock_time),
+            inputs,
+            outputs,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::NetworkUnchecked;
+    use crate::consensus::encode;
+    use crate::script::ScriptPubKeyExt as _;
+
+    proptest! {
+        #[test]
+        fn amount_is_in_range(amount in amount()) {
+            prop_assert!(amount <= Amount::MAX_MONEY);
+        }
+
+        #[test]
+        fn address_roundtrips((network, address) in network().prop_flat_map(|n| (Just(n), address(n)))) {
+            let parsed = address.to_string().parse::<Address<NetworkUnchecked>>().unwrap();
+            prop_assert!(parsed.is_valid_for_network(network));
+            prop_assert_eq!(parsed.assume_checked(), address);
+
+            let script_pubkey = address.script_pubkey();
+            let from_script = Address::from_script(&script_pubkey, network).unwrap();
+            prop_assert_eq!(from_script, address);
+        }
+
+        #[test]
+        fn script_pubkey_is_standard(script_pubkey in script_pubkey()) {
+            let is_address = Address::from_script(&script_pubkey, Network::Bitcoin).is_ok();
+            prop_assert!(script_pubkey.is_op_return() || is_address);
+        }
+
+        #[test]
+        fn transaction_roundtrips(tx in transaction()) {
+            let total = tx.outputs.iter().try_fold(Amount::ZERO, |acc, output| acc.checked_add(output.amount));
+            prop_assert!(total.is_some());
+
+            let serialized = encode::serialize(&tx);
+            prop_assert_eq!(encode::deserialize::<Transaction>(&serialized).unwrap(), tx);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Builder for signed transactions spending outputs locked to the fixed test keys.
+
+use secp256k1::Message;
+
+use crate::crypto::key::TapTweak as _;
+use crate::crypto::{ecdsa, taproot};
+use crate::prelude::Vec;
+use crate::script::ScriptPubKeyBuf;
+use crate::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use crate::testutil::keys;
+use crate::transaction::{self, Transaction, TxIn, TxOut, Utxo};
+use crate::witness::WitnessExt as _;
+use crate::{absolute, Amount, Sequence, Witness};
+
+/// Builds a transaction and signs all of its inputs with the fixed [`keys`].
+///
+/// Inputs may be locked to the P2WPKH or key-path-only P2TR address of a key (using
+/// [`keys::p2wpkh_address`] and [`keys::p2tr_address`]). All inputs are signed with
+/// `SIGHASH_ALL` (or `SIGHASH_DEFAULT` for Taproot).
+#[derive(Debug, Clone)]
+pub struct TxBuilder {
+    version: transaction::Version,
+    lock_time: absolute::LockTime,
+    inputs: Vec<(Utxo, u32)>,
+    outputs: Vec<TxOut>,
+}
+
+impl TxBuilder {
+    /// Constructs a new builder for a version 2 transaction without inputs and outputs.
+    pub fn new() -> Self {
+        Self {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Sets the transaction version.
+    #[must_use]
+    pub fn version(mut self, version: transaction::Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the transaction lock time.
+    #[must_use]
+    pub fn lock_time(mut self, lock_time: absolute::LockTime) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Adds an input spending `utxo`, which is locked to the key with index `key`.
+    #[must_use]
+    pub fn spend(mut self, utxo: Utxo, key: u32) -> Self {
+        self.inputs.push((utxo, key));
+        self
+    }
+
+    /// Adds an output paying `amount` to `script_pubkey`.
+    #[must_use]
+    pub fn pay(mut self, script_pubkey: ScriptPubKeyBuf, amount: Amount) -> Self {
+        self.outputs.push(TxOut { amount, script_pubkey });
+        self
+    }
+
+    /// Builds and signs the transaction.
+    ///
+    /// The fee is whatever remains after paying the outputs, the builder does not add change.
+    ///
+    /// # Panics
+    ///
+    /// If an input is not locked to the P2WPKH or P2TR address of its key, or if the outputs
+    /// spend more than the inputs.
+    pub fn build(self) -> Transaction {
+        let input_value = self
+            .inputs
+            .iter()
+            .try_fold(Amount::ZERO, |acc, (utxo, _)| acc.checked_add(utxo.output.amount));
+        let output_value = self
+            .outputs
+            .iter()
+            .try_fold(Amount::ZERO, |acc, output| acc.checked_add(output.amount));
+        assert!(
+            matches!((input_value, output_value), (Some(input), Some(output)) if output <= input),
+            "outputs spend more than the inputs"
+        );
+
+        let mut tx = Transaction {
+            version: self.version,
+            lock_time: self.lock_time,
+            inputs: self
+                .inputs
+                .iter()
+                .map(|(utxo, _)| TxIn {
+                    previous_output: utxo.out_point,
+                    sequence: Sequence::ENABLE_LOCKTIME_AND_RBF,
+                    ..TxIn::EMPTY_COINBASE
+                })
+                .collect(),
+            outputs: self.outputs,
+        };
+
+        let prevouts = self.inputs.iter().map(|(utxo, _)| utxo.output.clone()).collect::<Vec<_>>();
+        let prevouts = Prevouts::All(&prevouts);
+        let mut cache = SighashCache::new(&mut tx);
+        for (index, (utxo, key)) in self.inputs.iter().enumerate() {
+            let script_pubkey = &utxo.output.script_pubkey;
+            let witness = if *script_pubkey == keys::p2wpkh_address(*key).script_pubkey() {
+                let sighash_type = EcdsaSighashType::All;
+                let sighash = cache
+                    .p2wpkh_signature_hash(index, script_pubkey, utxo.output.amount, sighash_type)
+                    .expect("valid input index");
+                let sk = keys::private_key(*key).inner;
+                let signature = secp256k1::ecdsa::sign(Message::from(sighash), &sk);
+                let signature = ecdsa::Signature { signature, sighash_type };
+                Witness::p2wpkh(signature, sk.public_key())
+            } else if *script_pubkey == keys::p2tr_address(*key).script_pubkey() {
+                let sighash_type = TapSighashType::Default;
+                let sighash = cache
+                    .taproot_key_spend_signature_hash(index, &prevouts, sighash_type)
+                    .expect("valid input index");
+                let keypair = keys::keypair(*key).tap_tweak(None).to_keypair();
+                let signature = secp256k1::schnorr::sign_no_aux_rand(
+                    &sighash.to_byte_array(),
+                    &keypair.to_inner(),
+                );
+                Witness::p2tr_key_spend(&taproot::Signature { signature, sighash_type })
+            } else {
+                panic!("input {} is not locked to key {}", index, key);
+            };
+            *cache.witness_mut(index).expect("valid input index") = witness;
+        }
+        tx
+    }
+}
+
+impl Default for TxBuilder {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockCheckedExt as _;
+    use crate::testutil::ChainBuilder;
+    use crate::transaction::OutPoint;
+
+    #[test]
+    fn funded_chain() {
+        let mut chain = ChainBuilder::new();
+        let utxo = chain.mine_mature_coinbase(0);
+
+        let pay = Amount::from_sat_u32(100_000);
+        let change = (utxo.output.amount - pay - Amount::from_sat_u32(1_000)).unwrap();
+        let tx = TxBuilder::new()
+            .spend(utxo.clone(), 0)
+            .pay(keys::p2tr_address(1).script_pubkey(), pay)
+            .pay(keys::p2wpkh_address(0).script_pubkey(), change)
+            .build();
+        let spend = TxBuilder::new()
+            .spend(Utxo::from_tx(&tx, 0).unwrap(), 1)
+            .spend(Utxo::from_tx(&tx, 1).unwrap(), 0)
+            .pay(keys::p2wpkh_address(2).script_pubkey(), Amount::from_sat_u32(50_000))
+            .build();
+
+        assert_eq!(tx.inputs[0].previous_output, utxo.out_point);
+        assert_eq!(tx.inputs[0].witness.len(), 2);
+        assert_eq!(spend.inputs[0].witness.len(), 1);
+
+        #[cfg(feature = "bitcoinconsensus")]
+        {
+            use crate::consensus_validation::TransactionExt as _;
+
+            tx.verify(|out_point| (*out_point == utxo.out_point).then(|| utxo.output.clone()))
+                .unwrap();
+            spend.verify(|out_point| tx.outputs.get(out_point.vout as usize).cloned()).unwrap();
+        }
+
+        let block = chain.mine(keys::p2wpkh_address(0).script_pubkey(), vec![tx, spend]);
+        assert_eq!(block.transactions().len(), 3);
+        assert_eq!(block.bip34_block_height().unwrap(), 102);
+    }
+
+    #[test]
+    fn signatures_are_deterministic() {
+        let utxo = Utxo {
+            out_point: OutPoint { txid: crate::Txid::from_byte_array([1; 32]), vout: 0 },
+            output: TxOut {
+                amount: Amount::ONE_BTC,
+                script_pubkey: keys::p2wpkh_address(5).script_pubkey(),
+            },
+        };
+        let build = || {
+            TxBuilder::new()
+                .spend(utxo.clone(), 5)
+                .pay(keys::p2tr_address(5).script_pubkey(), Amount::from_sat_u32(1_000))
+                .build()
+        };
+        assert_eq!(build(), build());
+    }
+
+    #[test]
+    #[should_panic(expected = "not locked to key")]
+    fn wrong_key_panics() {
+        let utxo = Utxo {
+            out_point: OutPoint { txid: crate::Txid::from_byte_array([1; 32]), vout: 0 },
+            output: TxOut {
+                amount: Amount::ONE_BTC,
+                script_pubkey: keys::p2wpkh_address(5).script_pubkey(),
+            },
+        };
+        let _ = TxBuilder::new().spend(utxo, 6).build();
+    }
+}
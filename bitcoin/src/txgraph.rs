@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Dependencies within a set of transactions.
+//!
+//! Transactions received together, for example a package, the transactions of a disconnected
+//! block or a batch of mempool transactions from a peer, usually have to be processed parents
+//! first. A [`TxGraph`] orders them topologically, lists the parents outside of the set and finds
+//! transactions spending the same output.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::txgraph::TxGraph;
+//! # use bitcoin::{absolute, transaction, Amount, OutPoint, ScriptPubKeyBuf, Transaction, TxIn, TxOut, Txid};
+//! # let tx = |previous_output| Transaction {
+//! #     version: transaction::Version::TWO,
+//! #     lock_time: absolute::LockTime::ZERO,
+//! #     inputs: vec![TxIn { previous_output, ..TxIn::EMPTY_COINBASE }],
+//! #     outputs: vec![TxOut { amount: Amount::ONE_SAT, script_pubkey: ScriptPubKeyBuf::new() }],
+//! # };
+//! let confirmed = OutPoint { txid: Txid::from_byte_array([1; 32]), vout: 0 };
+//! let parent = tx(confirmed);
+//! let child = tx(OutPoint { txid: parent.compute_txid(), vout: 0 });
+//!
+//! let txs = [child.clone(), parent.clone()];
+//! let graph = TxGraph::new(&txs);
+//! assert_eq!(graph.sorted(), [&parent, &child]);
+//! assert!(graph.missing_parents().eq([confirmed.txid]));
+//! assert_eq!(graph.conflicts().count(), 0);
+//! ```
+
+use core::cmp::Reverse;
+
+use crate::prelude::{btree_map, BTreeMap, BTreeSet, BinaryHeap, Vec};
+use crate::transaction::{OutPoint, Transaction, Txid};
+
+/// The dependencies between a set of transactions.
+///
+/// Transactions appearing more than once are only considered at their first position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxGraph<'a> {
+    txs: &'a [Transaction],
+    /// The position of each distinct transaction in `txs`.
+    positions: BTreeMap<Txid, usize>,
+    /// The positions of the transactions spending each output.
+    spenders: BTreeMap<OutPoint, Vec<usize>>,
+}
+
+impl<'a> TxGraph<'a> {
+    /// Constructs the graph of `txs`.
+    pub fn new(txs: &'a [Transaction]) -> Self {
+        let mut positions = BTreeMap::new();
+        let mut spenders = BTreeMap::<_, Vec<_>>::new();
+        for (i, tx) in txs.iter().enumerate() {
+            match positions.entry(tx.compute_txid()) {
+                btree_map::Entry::Occupied(_) => continue,
+                btree_map::Entry::Vacant(entry) => entry.insert(i),
+            };
+            for input in &tx.inputs {
+                spenders.entry(input.previous_output).or_default().push(i);
+            }
+        }
+        Self { txs, positions, spenders }
+    }
+
+    /// Returns the number of distinct transactions.
+    pub fn len(&self) -> usize { self.positions.len() }
+
+    /// Returns `true` if there are no transactions.
+    pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+    /// Returns the transaction with `txid`.
+    pub fn get(&self, txid: &Txid) -> Option<&'a Transaction> {
+        self.positions.get(txid).map(|&i| &self.txs[i])
+    }
+
+    /// Returns the positions of the parents of the transaction at position `i` within the set.
+    fn parent_positions(&self, i: usize) -> BTreeSet<usize> {
+        self.txs[i]
+            .inputs
+            .iter()
+            .filter_map(|input| self.positions.get(&input.previous_output.txid).copied())
+            .collect()
+    }
+
+    /// Returns the TXIDs of the parents of the transaction with `txid` that are in the set.
+    pub fn parents(&self, txid: &Txid) -> BTreeSet<Txid> {
+        let Some(&i) = self.positions.get(txid) else { return BTreeSet::new() };
+        self.parent_positions(i).into_iter().map(|parent| self.txs[parent].compute_txid()).collect()
+    }
+
+    /// Returns the TXIDs of the children of the transaction with `txid` that are in the set.
+    pub fn children(&self, txid: &Txid) -> BTreeSet<Txid> {
+        self.spenders
+            .range(OutPoint { txid: *txid, vout: 0 }..=OutPoint { txid: *txid, vout: u32::MAX })
+            .flat_map(|(_, spenders)| spenders)
+            .map(|&i| self.txs[i].compute_txid())
+            .collect()
+    }
+
+    /// Returns the transactions ordered topologically, every transaction after its parents.
+    ///
+    /// Transactions without a dependency between them keep their relative order, a set that is
+    /// already sorted is returned unchanged.
+    pub fn sorted(&self) -> Vec<&'a Transaction> {
+        let mut children = BTreeMap::<usize, Vec<usize>>::new();
+        let mut remaining_parents = BTreeMap::new();
+        let mut ready = BinaryHeap::new();
+        for &i in self.positions.values() {
+            let parents = self.parent_positions(i);
+            for &parent in &parents {
+                children.entry(parent).or_default().push(i);
+            }
+            if parents.is_empty() {
+                ready.push(Reverse(i));
+            } else {
+                remaining_parents.insert(i, parents.len());
+            }
+        }
+
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            sorted.push(&self.txs[i]);
+            for &child in children.get(&i).into_iter().flatten() {
+                let count = remaining_parents.get_mut(&child).expect("children have parents");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(Reverse(child));
+                }
+            }
+        }
+        // TXIDs commit to the parents, transactions can not form a cycle.
+        debug_assert_eq!(sorted.len(), self.len());
+        sorted
+    }
+
+    /// Returns the TXIDs of the parents not in the set, ordered by TXID.
+    ///
+    /// These must be confirmed or otherwise known for the transactions to be valid.
+    pub fn missing_parents(&self) -> impl Iterator<Item = Txid> + '_ {
+        self.spenders
+            .keys()
+            .map(|out_point| out_point.txid)
+            .filter(|txid| !self.positions.contains_key(txid))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+    }
+
+    /// Returns the outputs spent by more than one transaction and the TXIDs of their spenders,
+    /// ordered by outpoint.
+    ///
+    /// At most one transaction of each conflict can be valid.
+    pub fn conflicts(&self) -> impl Iterator<Item = (OutPoint, Vec<Txid>)> + '_ {
+        self.spenders.iter().filter(|(_, spenders)| spenders.len() > 1).map(
+            |(out_point, spenders)| {
+                (*out_point, spenders.iter().map(|&i| self.txs[i].compute_txid()).collect())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{confirmed, tx_with_outputs};
+
+    fn out(tx: &Transaction, vout: u32) -> OutPoint { OutPoint { txid: tx.compute_txid(), vout } }
+
+    #[test]
+    fn sort_diamond() {
+        // a -> (b, c) -> d, e unrelated.
+        let a = tx_with_outputs(&[confirmed(1)], 2);
+        let b = tx_with_outputs(&[out(&a, 0)], 1);
+        let c = tx_with_outputs(&[out(&a, 1)], 1);
+        let d = tx_with_outputs(&[out(&b, 0), out(&c, 0), confirmed(2)], 1);
+        let e = tx_with_outputs(&[confirmed(2)], 1);
+
+        let txs = [d.clone(), e.clone(), c.clone(), a.clone(), b.clone(), a.clone()];
+        let graph = TxGraph::new(&txs);
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.sorted(), [&e, &a, &c, &b, &d]);
+        assert_eq!(graph.parents(&d.compute_txid()), [b.compute_txid(), c.compute_txid()].into());
+        assert_eq!(graph.children(&a.compute_txid()), [b.compute_txid(), c.compute_txid()].into());
+        assert!(graph.parents(&confirmed(1).txid).is_empty());
+
+        let missing = graph.missing_parents().collect::<Vec<_>>();
+        assert_eq!(missing, [confirmed(1).txid, confirmed(2).txid]);
+
+        let conflicts = graph.conflicts().collect::<Vec<_>>();
+        assert_eq!(conflicts, [(confirmed(2), vec![d.compute_txid(), e.compute_txid()])]);
+
+        let sorted = [a, b, c, d, e];
+        assert_eq!(TxGraph::new(&sorted).sorted(), sorted.iter().collect::<Vec<_>>());
+    }
+}
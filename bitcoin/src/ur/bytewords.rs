@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! The minimal Bytewords encoding used in Uniform Resources.
+//!
+//! Each byte is encoded by the first and last letter of its word, a CRC-32 checksum of the data is
+//! appended before encoding.
+
+use crate::prelude::{String, Vec};
+
+/// The Bytewords, one four letter word for each byte value.
+#[rustfmt::skip]
+const WORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt",
+    "away", "axis", "back", "bald", "barn", "belt", "beta", "bias",
+    "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost",
+    "crux", "curl", "cusp", "cyan", "dark", "data", "days", "deli",
+    "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam",
+    "exit", "eyes", "fact", "fair", "fern", "figs", "film", "fish",
+    "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow",
+    "good", "gray", "grim", "guru", "gush", "gyro", "half", "hang",
+    "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into",
+    "iris", "iron", "item", "jade", "jazz", "join", "jolt", "jowl",
+    "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb",
+    "lava", "lazy", "leaf", "legs", "liar", "limp", "lion", "list",
+    "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss",
+    "monk", "nail", "navy", "need", "news", "next", "noon", "note",
+    "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose",
+    "puff", "puma", "purr", "quad", "quiz", "race", "ramp", "real",
+    "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot",
+    "soap", "solo", "song", "stub", "surf", "swan", "taco", "task",
+    "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user",
+    "vast", "very", "veto", "vial", "vibe", "view", "visa", "void",
+    "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell",
+    "yoga", "yurt", "zaps", "zero", "zest", "zinc", "zone", "zoom",
+];
+
+/// Returns the minimal encoding of `data` followed by its checksum.
+pub(super) fn encode(data: &[u8]) -> String {
+    let checksum = crc32(data).to_be_bytes();
+    let mut s = String::with_capacity((data.len() + checksum.len()) * 2);
+    for &byte in data.iter().chain(&checksum) {
+        let word = WORDS[usize::from(byte)].as_bytes();
+        s.push(char::from(word[0]));
+        s.push(char::from(word[3]));
+    }
+    s
+}
+
+/// Decodes the minimal encoding `s`, case insensitive, and verifies the checksum.
+///
+/// Returns `None` if `s` is not valid minimal Bytewords or the checksum does not match.
+pub(super) fn decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    let mut data = s
+        .chunks_exact(2)
+        .map(|pair| {
+            let (first, last) = (pair[0].to_ascii_lowercase(), pair[1].to_ascii_lowercase());
+            let index = WORDS.iter().position(|word| {
+                let word = word.as_bytes();
+                word[0] == first && word[3] == last
+            })?;
+            Some(index as u8) // cast ok, there are 256 words.
+        })
+        .collect::<Option<Vec<u8>>>()?;
+
+    let checksum_start = data.len().checked_sub(4)?;
+    let checksum = u32::from_be_bytes(data[checksum_start..].try_into().expect("four bytes"));
+    data.truncate(checksum_start);
+    if crc32(&data) == checksum {
+        Some(data)
+    } else {
+        None
+    }
+}
+
+/// Computes the CRC-32 (ISO-HDLC) checksum of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vectors() {
+        assert_eq!(crc32(b"Hello, world!"), 0xebe6c6e6);
+
+        let data = [0, 1, 2, 128, 255];
+        // "able acid also lava zoom jade need echo taxi" in the standard encoding.
+        assert_eq!(encode(&data), "aeadaolazmjendeoti");
+        assert_eq!(decode("aeadaolazmjendeoti").unwrap(), data);
+        assert_eq!(decode("AEADAOLAZMJENDEOTI").unwrap(), data);
+
+        assert_eq!(decode("aeadaolazmjendeota"), None);
+        assert_eq!(decode("aeadaolazmjendeot"), None);
+        assert_eq!(decode("jendeo"), None);
+        assert_eq!(decode(""), None);
+    }
+}
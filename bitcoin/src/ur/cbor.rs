@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! The subset of CBOR used by the Uniform Resource types.
+//!
+//! Only definite length items are supported, which is all deterministic CBOR allows.
+
+use crate::prelude::Vec;
+
+const UNSIGNED: u8 = 0;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+const FALSE: u64 = 20;
+const TRUE: u64 = 21;
+
+/// The maximum nesting of arrays, maps and tags skipped by [`Decoder::skip`].
+const MAX_DEPTH: usize = 16;
+
+/// Writes CBOR items to a buffer.
+#[derive(Debug, Default)]
+pub(super) struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub(super) fn new() -> Self { Self::default() }
+
+    pub(super) fn into_bytes(self) -> Vec<u8> { self.buf }
+
+    fn head(&mut self, major: u8, value: u64) -> &mut Self {
+        let major = major << 5;
+        // Casts ok, the value is checked to fit.
+        if value < 24 {
+            self.buf.push(major | value as u8);
+        } else if value <= u64::from(u8::MAX) {
+            self.buf.extend_from_slice(&[major | 24, value as u8]);
+        } else if value <= u64::from(u16::MAX) {
+            self.buf.push(major | 25);
+            self.buf.extend_from_slice(&(value as u16).to_be_bytes());
+        } else if value <= u64::from(u32::MAX) {
+            self.buf.push(major | 26);
+            self.buf.extend_from_slice(&(value as u32).to_be_bytes());
+        } else {
+            self.buf.push(major | 27);
+            self.buf.extend_from_slice(&value.to_be_bytes());
+        }
+        self
+    }
+
+    pub(super) fn uint(&mut self, value: u64) -> &mut Self { self.head(UNSIGNED, value) }
+
+    pub(super) fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.head(BYTES, bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    pub(super) fn bool(&mut self, value: bool) -> &mut Self {
+        self.head(SIMPLE, if value { TRUE } else { FALSE })
+    }
+
+    pub(super) fn array(&mut self, len: usize) -> &mut Self { self.head(ARRAY, len as u64) }
+
+    pub(super) fn map(&mut self, len: usize) -> &mut Self { self.head(MAP, len as u64) }
+
+    pub(super) fn tag(&mut self, tag: u64) -> &mut Self { self.head(TAG, tag) }
+}
+
+/// Error decoding CBOR, the data is either malformed or not of the expected structure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct Error;
+
+/// Reads CBOR items from a slice.
+#[derive(Debug)]
+pub(super) struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self { Self { data } }
+
+    /// Returns `true` if all data was read.
+    pub(super) fn is_empty(&self) -> bool { self.data.is_empty() }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if len > self.data.len() {
+            return Err(Error);
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn head(&mut self) -> Result<(u8, u64), Error> {
+        let initial = self.take(1)?[0];
+        let value = match initial & 0x1f {
+            value @ 0..=23 => u64::from(value),
+            24 => u64::from(self.take(1)?[0]),
+            25 => u64::from(u16::from_be_bytes(self.take(2)?.try_into().expect("two bytes"))),
+            26 => u64::from(u32::from_be_bytes(self.take(4)?.try_into().expect("four bytes"))),
+            27 => u64::from_be_bytes(self.take(8)?.try_into().expect("eight bytes")),
+            _ => return Err(Error),
+        };
+        Ok((initial >> 5, value))
+    }
+
+    fn expect(&mut self, major: u8) -> Result<u64, Error> {
+        match self.head()? {
+            (m, value) if m == major => Ok(value),
+            _ => Err(Error),
+        }
+    }
+
+    /// Returns the major type of the next item without reading it.
+    fn peek_major(&self) -> Option<u8> { self.data.first().map(|initial| initial >> 5) }
+
+    fn len(&mut self, major: u8) -> Result<usize, Error> {
+        usize::try_from(self.expect(major)?).map_err(|_| Error)
+    }
+
+    pub(super) fn uint(&mut self) -> Result<u64, Error> { self.expect(UNSIGNED) }
+
+    pub(super) fn u32(&mut self) -> Result<u32, Error> {
+        u32::try_from(self.uint()?).map_err(|_| Error)
+    }
+
+    pub(super) fn bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.len(BYTES)?;
+        self.take(len)
+    }
+
+    pub(super) fn bool(&mut self) -> Result<bool, Error> {
+        match self.expect(SIMPLE)? {
+            FALSE => Ok(false),
+            TRUE => Ok(true),
+            _ => Err(Error),
+        }
+    }
+
+    pub(super) fn array(&mut self) -> Result<usize, Error> { self.len(ARRAY) }
+
+    pub(super) fn map(&mut self) -> Result<usize, Error> { self.len(MAP) }
+
+    pub(super) fn tag(&mut self) -> Result<u64, Error> { self.expect(TAG) }
+
+    /// Reads the tag of the next item if it has one.
+    pub(super) fn optional_tag(&mut self) -> Result<Option<u64>, Error> {
+        if self.peek_major() == Some(TAG) {
+            self.tag().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Skips the next item, for example the value of an unknown map key.
+    pub(super) fn skip(&mut self) -> Result<(), Error> { self.skip_nested(0) }
+
+    fn skip_nested(&mut self, depth: usize) -> Result<(), Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error);
+        }
+        let (major, value) = self.head()?;
+        let len = usize::try_from(value).map_err(|_| Error);
+        match major {
+            BYTES | TEXT => {
+                self.take(len?)?;
+            }
+            ARRAY =>
+                for _ in 0..len? {
+                    self.skip_nested(depth + 1)?;
+                },
+            MAP =>
+                for _ in 0..len? {
+                    self.skip_nested(depth + 1)?;
+                    self.skip_nested(depth + 1)?;
+                },
+            TAG => self.skip_nested(depth + 1)?,
+            // Unsigned and negative integers and simple values are fully read with the head.
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut encoder = Encoder::new();
+        encoder.map(2).uint(1).uint(500).uint(2).array(2).bytes(&[0xab; 30]).bool(true);
+        encoder.tag(303).uint(u64::from(u32::MAX) + 1);
+        let bytes = encoder.into_bytes();
+        assert_eq!(&bytes[..6], [0xa2, 0x01, 0x19, 0x01, 0xf4, 0x02]);
+        assert_eq!(&bytes[6..9], [0x82, 0x58, 30]);
+
+        let mut decoder = Decoder::new(&bytes);
+        assert_eq!(decoder.map(), Ok(2));
+        assert_eq!(decoder.uint(), Ok(1));
+        assert_eq!(decoder.u32(), Ok(500));
+        assert_eq!(decoder.uint(), Ok(2));
+        decoder.skip().unwrap();
+        assert_eq!(decoder.optional_tag(), Ok(Some(303)));
+        assert_eq!(decoder.u32(), Err(Error));
+        assert!(decoder.is_empty());
+
+        assert_eq!(Decoder::new(&[0x58, 5, 1]).bytes(), Err(Error));
+        assert_eq!(Decoder::new(&[0x9f]).skip(), Err(Error));
+    }
+}
@@ -0,0 +1,499 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Uniform Resources (UR) for air-gapped signing devices.
+//!
+//! Air-gapped hardware wallets (for example Keystone and Passport) exchange PSBTs and account keys
+//! over QR codes using the [Uniform Resources] of Blockchain Commons. This module implements the
+//! `crypto-psbt` and `crypto-account` types and their single-part `ur:` string encoding.
+//!
+//! Large PSBTs are split into multi-part URs using fountain codes, these are not supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::ur::{self, Ur};
+//! # use bitcoin::{absolute, transaction, Psbt, Transaction};
+//! # let tx = Transaction {
+//! #     version: transaction::Version::TWO,
+//! #     lock_time: absolute::LockTime::ZERO,
+//! #     inputs: vec![],
+//! #     outputs: vec![],
+//! # };
+//! # let psbt = Psbt::from_unsigned_tx(tx).unwrap();
+//!
+//! let encoded = ur::encode_psbt(&psbt).to_string();
+//! assert!(encoded.starts_with("ur:crypto-psbt/"));
+//!
+//! let decoded = ur::decode_psbt(&encoded.parse::<Ur>().unwrap()).unwrap();
+//! assert_eq!(decoded, psbt);
+//! ```
+//!
+//! [Uniform Resources]: <https://github.com/BlockchainCommons/Research/blob/master/papers/bcr-2020-005-ur.md>
+
+mod bytewords;
+mod cbor;
+
+use core::fmt;
+use core::str::FromStr;
+
+use internals::write_err;
+
+use self::cbor::{Decoder, Encoder};
+use crate::bip32::{ChainCode, ChildNumber, DerivationPath, Fingerprint, Xpub};
+use crate::hww::DeviceKey;
+use crate::network::NetworkKind;
+use crate::prelude::{String, ToString, Vec};
+use crate::psbt::{self, Psbt};
+
+#[rustfmt::skip]                // Keep public re-exports separate.
+#[doc(no_inline)]
+pub use crate::descriptor::ScriptType;
+
+/// The UR type of PSBTs.
+pub const CRYPTO_PSBT: &str = "crypto-psbt";
+
+/// The UR type of accounts.
+pub const CRYPTO_ACCOUNT: &str = "crypto-account";
+
+const TAG_HDKEY: u64 = 303;
+const TAG_KEYPATH: u64 = 304;
+const TAG_COIN_INFO: u64 = 305;
+const TAG_OUTPUT: u64 = 308;
+const TAG_SH: u64 = 400;
+const TAG_PKH: u64 = 403;
+const TAG_WPKH: u64 = 404;
+const TAG_TR: u64 = 409;
+
+/// A single-part Uniform Resource, a typed CBOR item.
+///
+/// The string representation is `ur:<type>/<minimal bytewords>`, parsing is case insensitive
+/// because QR codes store upper case text more compactly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ur {
+    /// The lower case type of the resource.
+    ur_type: String,
+    /// The CBOR encoded resource.
+    cbor: Vec<u8>,
+}
+
+impl Ur {
+    /// Constructs a new `Ur` of type `ur_type` containing the `cbor` encoded resource.
+    ///
+    /// # Errors
+    ///
+    /// If `ur_type` is empty or contains characters other than lower case ASCII letters, digits
+    /// and `-`.
+    pub fn new(ur_type: &str, cbor: Vec<u8>) -> Result<Self, Error> {
+        let valid = |c: u8| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-';
+        if ur_type.is_empty() || !ur_type.bytes().all(valid) {
+            return Err(Error::InvalidType);
+        }
+        Ok(Self { ur_type: ur_type.to_string(), cbor })
+    }
+
+    /// Returns the type of the resource.
+    pub fn ur_type(&self) -> &str { &self.ur_type }
+
+    /// Returns the CBOR encoded resource.
+    pub fn cbor(&self) -> &[u8] { &self.cbor }
+
+    /// Returns the CBOR of the resource if it is of type `ur_type`.
+    fn expect_type(&self, ur_type: &'static str) -> Result<&[u8], Error> {
+        if self.ur_type == ur_type {
+            Ok(&self.cbor)
+        } else {
+            Err(Error::UnexpectedType { expected: ur_type })
+        }
+    }
+}
+
+impl fmt::Display for Ur {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ur:{}/{}", self.ur_type, bytewords::encode(&self.cbor))
+    }
+}
+
+impl FromStr for Ur {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = match s.get(..3) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("ur:") => &s[3..],
+            _ => return Err(Error::InvalidScheme),
+        };
+        let (ur_type, payload) = s.split_once('/').ok_or(Error::InvalidScheme)?;
+        if payload.contains('/') {
+            return Err(Error::MultiPart);
+        }
+        let cbor = bytewords::decode(payload).ok_or(Error::InvalidBytewords)?;
+        Self::new(&ur_type.to_ascii_lowercase(), cbor)
+    }
+}
+
+/// Encodes `psbt` as a `crypto-psbt` resource.
+pub fn encode_psbt(psbt: &Psbt) -> Ur {
+    let mut encoder = Encoder::new();
+    encoder.bytes(&psbt.serialize());
+    Ur { ur_type: CRYPTO_PSBT.to_string(), cbor: encoder.into_bytes() }
+}
+
+/// Decodes a PSBT from a `crypto-psbt` resource.
+pub fn decode_psbt(ur: &Ur) -> Result<Psbt, Error> {
+    let mut decoder = Decoder::new(ur.expect_type(CRYPTO_PSBT)?);
+    let bytes = decoder.bytes()?;
+    if !decoder.is_empty() {
+        return Err(Error::InvalidCbor);
+    }
+    Psbt::deserialize(bytes).map_err(Error::Psbt)
+}
+
+/// Returns the tags wrapping the key of an output descriptor, outermost first.
+fn script_type_tags(script_type: ScriptType) -> &'static [u64] {
+    match script_type {
+        ScriptType::Pkh => &[TAG_PKH],
+        ScriptType::ShWpkh => &[TAG_SH, TAG_WPKH],
+        ScriptType::Wpkh => &[TAG_WPKH],
+        ScriptType::Tr => &[TAG_TR],
+    }
+}
+
+/// The accounts of a signing device, the `crypto-account` resource.
+///
+/// Devices export one output descriptor for each script type they support, all derived from the
+/// same master key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Account {
+    /// The fingerprint of the master key of the device.
+    pub master_fingerprint: Fingerprint,
+    /// The account keys and the script types they are used with.
+    pub descriptors: Vec<(ScriptType, DeviceKey)>,
+}
+
+impl Account {
+    /// Encodes the account as a `crypto-account` resource.
+    pub fn to_ur(&self) -> Ur {
+        let mut encoder = Encoder::new();
+        encoder.map(2).uint(1).uint(fingerprint_to_u32(self.master_fingerprint));
+        encoder.uint(2).array(self.descriptors.len());
+        for (script_type, key) in &self.descriptors {
+            encoder.tag(TAG_OUTPUT);
+            for &tag in script_type_tags(*script_type) {
+                encoder.tag(tag);
+            }
+            encode_hdkey(&mut encoder, key);
+        }
+        Ur { ur_type: CRYPTO_ACCOUNT.to_string(), cbor: encoder.into_bytes() }
+    }
+
+    /// Decodes an account from a `crypto-account` resource.
+    ///
+    /// # Errors
+    ///
+    /// If a descriptor uses a script type other than the ones of [`ScriptType`], or its key is
+    /// not an extended public key with origin.
+    pub fn from_ur(ur: &Ur) -> Result<Self, Error> {
+        let mut decoder = Decoder::new(ur.expect_type(CRYPTO_ACCOUNT)?);
+        let mut master_fingerprint = None;
+        let mut descriptors = None;
+        for _ in 0..decoder.map()? {
+            match decoder.uint()? {
+                1 => master_fingerprint = Some(fingerprint_from_u32(decoder.u32()?)),
+                2 => {
+                    let len = decoder.array()?;
+                    let mut list = Vec::with_capacity(len.min(16));
+                    for _ in 0..len {
+                        list.push(decode_output(&mut decoder)?);
+                    }
+                    descriptors = Some(list);
+                }
+                _ => decoder.skip()?,
+            }
+        }
+        if !decoder.is_empty() {
+            return Err(Error::InvalidCbor);
+        }
+        match (master_fingerprint, descriptors) {
+            (Some(master_fingerprint), Some(descriptors)) =>
+                Ok(Self { master_fingerprint, descriptors }),
+            _ => Err(Error::InvalidCbor),
+        }
+    }
+}
+
+fn fingerprint_to_u32(fingerprint: Fingerprint) -> u64 {
+    u64::from(u32::from_be_bytes(fingerprint.to_byte_array()))
+}
+
+fn fingerprint_from_u32(fingerprint: u32) -> Fingerprint {
+    Fingerprint::from_byte_array(fingerprint.to_be_bytes())
+}
+
+/// Encodes `key` as a tagged `crypto-hdkey`.
+fn encode_hdkey(encoder: &mut Encoder, key: &DeviceKey) {
+    let xpub = key.xpub();
+    let has_parent = xpub.depth > 0;
+    encoder.tag(TAG_HDKEY).map(if has_parent { 5 } else { 4 });
+    encoder.uint(3).bytes(&xpub.public_key.serialize());
+    encoder.uint(4).bytes(xpub.chain_code.as_bytes());
+    encoder.uint(5).tag(TAG_COIN_INFO).map(1).uint(2);
+    encoder.uint(match xpub.network {
+        NetworkKind::Main => 0,
+        NetworkKind::Test => 1,
+    });
+    encoder.uint(6).tag(TAG_KEYPATH).map(2).uint(1).array(key.path().len() * 2);
+    for child in key.path() {
+        let (index, is_hardened) = match *child {
+            ChildNumber::Normal { index } => (index, false),
+            ChildNumber::Hardened { index } => (index, true),
+        };
+        encoder.uint(u64::from(index)).bool(is_hardened);
+    }
+    encoder.uint(2).uint(fingerprint_to_u32(key.master_fingerprint()));
+    if has_parent {
+        encoder.uint(8).uint(fingerprint_to_u32(xpub.parent_fingerprint));
+    }
+}
+
+/// Decodes a tagged `crypto-output` containing a single key.
+fn decode_output(decoder: &mut Decoder) -> Result<(ScriptType, DeviceKey), Error> {
+    if decoder.tag()? != TAG_OUTPUT {
+        return Err(Error::InvalidCbor);
+    }
+    let mut tags = Vec::new();
+    while let Some(tag) = decoder.optional_tag()? {
+        if tag == TAG_HDKEY {
+            let script_type = match tags[..] {
+                [TAG_PKH] => ScriptType::Pkh,
+                [TAG_SH, TAG_WPKH] => ScriptType::ShWpkh,
+                [TAG_WPKH] => ScriptType::Wpkh,
+                [TAG_TR] => ScriptType::Tr,
+                _ => return Err(Error::UnsupportedDescriptor),
+            };
+            return Ok((script_type, decode_hdkey(decoder)?));
+        }
+        if tags.len() == 2 {
+            return Err(Error::UnsupportedDescriptor);
+        }
+        tags.push(tag);
+    }
+    Err(Error::UnsupportedDescriptor)
+}
+
+/// Decodes the map of a `crypto-hdkey`, after its tag.
+fn decode_hdkey(decoder: &mut Decoder) -> Result<DeviceKey, Error> {
+    let mut public_key = None;
+    let mut chain_code = None;
+    let mut network = NetworkKind::Main;
+    let mut origin = None;
+    let mut parent_fingerprint = Fingerprint::default();
+    for _ in 0..decoder.map()? {
+        match decoder.uint()? {
+            // `is-master` and `is-private`, master keys and private keys are not accepted.
+            1 | 2 =>
+                if decoder.bool()? {
+                    return Err(Error::InvalidKey);
+                },
+            3 =>
+                public_key = Some(
+                    secp256k1::PublicKey::from_slice(decoder.bytes()?)
+                        .map_err(|_| Error::InvalidKey)?,
+                ),
+            4 => {
+                let bytes = decoder.bytes()?.try_into().map_err(|_| Error::InvalidKey)?;
+                chain_code = Some(ChainCode::from_byte_array(bytes));
+            }
+            5 => {
+                expect_tag(decoder, TAG_COIN_INFO)?;
+                for _ in 0..decoder.map()? {
+                    match decoder.uint()? {
+                        1 if decoder.uint()? != 0 => return Err(Error::InvalidKey),
+                        2 if decoder.uint()? != 0 => network = NetworkKind::Test,
+                        1 | 2 => {}
+                        _ => decoder.skip()?,
+                    }
+                }
+            }
+            6 => {
+                expect_tag(decoder, TAG_KEYPATH)?;
+                origin = Some(decode_keypath(decoder)?);
+            }
+            8 => parent_fingerprint = fingerprint_from_u32(decoder.u32()?),
+            _ => decoder.skip()?,
+        }
+    }
+
+    let (master_fingerprint, path) = origin.ok_or(Error::InvalidKey)?;
+    let (Some(public_key), Some(chain_code)) = (public_key, chain_code) else {
+        return Err(Error::InvalidKey);
+    };
+    let xpub = Xpub {
+        network,
+        depth: u8::try_from(path.len()).map_err(|_| Error::InvalidKey)?,
+        parent_fingerprint,
+        child_number: path.as_ref().last().copied().unwrap_or(ChildNumber::ZERO_NORMAL),
+        public_key,
+        chain_code,
+    };
+    Ok(DeviceKey::new(master_fingerprint, path, xpub))
+}
+
+/// Decodes the map of a `crypto-keypath`, after its tag.
+fn decode_keypath(decoder: &mut Decoder) -> Result<(Fingerprint, DerivationPath), Error> {
+    let mut fingerprint = None;
+    let mut path = Vec::new();
+    for _ in 0..decoder.map()? {
+        match decoder.uint()? {
+            1 => {
+                let len = decoder.array()?;
+                if len % 2 != 0 {
+                    return Err(Error::InvalidKey);
+                }
+                for _ in 0..len / 2 {
+                    let index = decoder.u32()?;
+                    let child = if decoder.bool()? {
+                        ChildNumber::from_hardened_idx(index)
+                    } else {
+                        ChildNumber::from_normal_idx(index)
+                    };
+                    path.push(child.map_err(|_| Error::InvalidKey)?);
+                }
+            }
+            2 => fingerprint = Some(fingerprint_from_u32(decoder.u32()?)),
+            _ => decoder.skip()?,
+        }
+    }
+    Ok((fingerprint.ok_or(Error::InvalidKey)?, path.into()))
+}
+
+fn expect_tag(decoder: &mut Decoder, tag: u64) -> Result<(), Error> {
+    if decoder.tag()? == tag {
+        Ok(())
+    } else {
+        Err(Error::InvalidCbor)
+    }
+}
+
+/// Error encoding or decoding a Uniform Resource.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The string does not start with `ur:<type>/`.
+    InvalidScheme,
+    /// The type contains invalid characters.
+    InvalidType,
+    /// The string is a part of a multi-part resource.
+    MultiPart,
+    /// The payload is not valid minimal Bytewords or its checksum does not match.
+    InvalidBytewords,
+    /// The resource is not of the expected type.
+    UnexpectedType {
+        /// The expected type.
+        expected: &'static str,
+    },
+    /// The CBOR is malformed or not of the structure of the resource type.
+    InvalidCbor,
+    /// An account descriptor is not one of the supported single key descriptors.
+    UnsupportedDescriptor,
+    /// An account key is not a valid extended public key with origin.
+    InvalidKey,
+    /// The PSBT could not be deserialized.
+    Psbt(psbt::Error),
+}
+
+impl From<cbor::Error> for Error {
+    fn from(_: cbor::Error) -> Self { Self::InvalidCbor }
+}
+
+impl From<core::convert::Infallible> for Error {
+    fn from(never: core::convert::Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidScheme => f.write_str("not a uniform resource"),
+            Self::InvalidType => f.write_str("invalid uniform resource type"),
+            Self::MultiPart => f.write_str("multi-part uniform resources are not supported"),
+            Self::InvalidBytewords => f.write_str("invalid bytewords or checksum"),
+            Self::UnexpectedType { expected } =>
+                write!(f, "unexpected uniform resource type, expected {}", expected),
+            Self::InvalidCbor => f.write_str("invalid CBOR"),
+            Self::UnsupportedDescriptor => f.write_str("unsupported account descriptor"),
+            Self::InvalidKey => f.write_str("invalid account key"),
+            Self::Psbt(ref e) => write_err!(f, "invalid PSBT"; e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::InvalidScheme
+            | Self::InvalidType
+            | Self::MultiPart
+            | Self::InvalidBytewords
+            | Self::UnexpectedType { .. }
+            | Self::InvalidCbor
+            | Self::UnsupportedDescriptor
+            | Self::InvalidKey => None,
+            Self::Psbt(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32::Xpriv;
+
+    #[test]
+    fn account_roundtrip() {
+        let master = Xpriv::new_master(NetworkKind::Test, &[7; 32]);
+        let key = |path: &str| DeviceKey::from_master(&master, path.parse().unwrap()).unwrap();
+        let account = Account {
+            master_fingerprint: master.fingerprint(),
+            descriptors: vec![
+                (ScriptType::Pkh, key("m/44h/1h/0h")),
+                (ScriptType::ShWpkh, key("m/49h/1h/0h")),
+                (ScriptType::Wpkh, key("m/84h/1h/0h")),
+                (ScriptType::Tr, key("m/86h/1h/0h")),
+            ],
+        };
+
+        let ur = account.to_ur();
+        assert_eq!(ur.ur_type(), CRYPTO_ACCOUNT);
+        let s = ur.to_string();
+        assert_eq!(s.parse::<Ur>().unwrap(), ur);
+        assert_eq!(s.to_ascii_uppercase().parse::<Ur>().unwrap(), ur);
+        assert_eq!(Account::from_ur(&ur).unwrap(), account);
+
+        assert!(matches!(decode_psbt(&ur), Err(Error::UnexpectedType { expected: CRYPTO_PSBT })));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            "crypto-psbt/aeadaolazmjendeoti".parse::<Ur>(),
+            Err(Error::InvalidScheme)
+        ));
+        assert!(matches!("ur:crypto-psbt".parse::<Ur>(), Err(Error::InvalidScheme)));
+        assert!(matches!(
+            "ur:crypto-psbt/1-3/aeadaolazmjendeoti".parse::<Ur>(),
+            Err(Error::MultiPart)
+        ));
+        assert!(matches!(
+            "ur:crypto-psbt/aeadaolazmjendeota".parse::<Ur>(),
+            Err(Error::InvalidBytewords)
+        ));
+        assert!(matches!(
+            "ur:crypto_psbt/aeadaolazmjendeoti".parse::<Ur>(),
+            Err(Error::InvalidType)
+        ));
+
+        // Five bytes but not a CBOR byte string.
+        let ur = "ur:crypto-psbt/aeadaolazmjendeoti".parse::<Ur>().unwrap();
+        assert_eq!(ur.cbor(), [0, 1, 2, 128, 255]);
+        assert!(matches!(decode_psbt(&ur), Err(Error::InvalidCbor)));
+    }
+}
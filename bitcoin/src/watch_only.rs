@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Watch-only wallet state.
+//!
+//! A [`WatchOnlyState`] is a snapshot of what a watch-only wallet knows: its descriptors with the
+//! last used index of each, its unspent outputs and the last block its compact block filters were
+//! checked up to. Signers, watchtowers and coordinators can exchange it to pick up where another
+//! party left off.
+//!
+//! The binary format is the consensus encoding, starting with a version byte. With the `serde`
+//! feature enabled the state can also be serialized with `serde`, with descriptors as strings.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin::consensus::encode;
+//! use bitcoin::descriptor::Descriptor;
+//! use bitcoin::watch_only::{DescriptorState, WatchOnlyState};
+//!
+//! let descriptor = "tr([73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)"
+//!     .parse::<Descriptor>()
+//!     .unwrap();
+//! let mut state = WatchOnlyState::default();
+//! state.descriptors.push(DescriptorState { descriptor, last_used_index: Some(4) });
+//!
+//! let bytes = encode::serialize(&state);
+//! assert_eq!(encode::deserialize::<WatchOnlyState>(&bytes).unwrap(), state);
+//! ```
+
+use hashes::sha256d;
+use io::{BufRead, Write};
+
+use crate::block::BlockHash;
+use crate::consensus::encode::{self, Decodable, Encodable, ReadExt as _, WriteExt as _};
+use crate::consensus::parse_failed_error;
+use crate::descriptor::Descriptor;
+use crate::prelude::{String, ToString, Vec};
+use crate::transaction::Utxo;
+use crate::BlockHeight;
+
+/// The version of the binary format.
+const VERSION: u8 = 1;
+
+/// The state of a watch-only wallet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WatchOnlyState {
+    /// The descriptors of the wallet.
+    pub descriptors: Vec<DescriptorState>,
+    /// The unspent outputs paying to the wallet.
+    pub utxos: Vec<WalletUtxo>,
+    /// The last block the compact block filters were checked up to, if any.
+    pub checkpoint: Option<FilterCheckpoint>,
+}
+
+/// A descriptor of a watch-only wallet and how far it is used.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DescriptorState {
+    /// The descriptor.
+    pub descriptor: Descriptor,
+    /// The highest index of an address of the descriptor that received a payment, if any.
+    pub last_used_index: Option<u32>,
+}
+
+/// An unspent output paying to a watch-only wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WalletUtxo {
+    /// The output and where it is.
+    pub utxo: Utxo,
+    /// The height of the block confirming the output, `None` if it is unconfirmed.
+    pub height: Option<BlockHeight>,
+}
+
+/// A block up to which the compact block filters (BIP-0158) were checked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FilterCheckpoint {
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The filter header (BIP-0157) of the basic filter of the block.
+    pub filter_header: sha256d::Hash,
+}
+
+impl Encodable for WatchOnlyState {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        VERSION.consensus_encode(w)?;
+        let mut len = 1;
+        len += self.descriptors.consensus_encode(w)?;
+        len += self.utxos.consensus_encode(w)?;
+        len += encode_option(self.checkpoint.as_ref(), w)?;
+        Ok(len)
+    }
+}
+
+impl Decodable for WatchOnlyState {
+    fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
+        r: &mut R,
+    ) -> Result<Self, encode::Error> {
+        if u8::consensus_decode_from_finite_reader(r)? != VERSION {
+            return Err(parse_failed_error("unknown watch-only state version"));
+        }
+        Ok(Self {
+            descriptors: Decodable::consensus_decode_from_finite_reader(r)?,
+            utxos: Decodable::consensus_decode_from_finite_reader(r)?,
+            checkpoint: decode_option(r)?,
+        })
+    }
+}
+
+impl Encodable for DescriptorState {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        let mut len = self.descriptor.to_string().consensus_encode(w)?;
+        len += encode_option(self.last_used_index.as_ref(), w)?;
+        Ok(len)
+    }
+}
+
+impl Decodable for DescriptorState {
+    fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
+        r: &mut R,
+    ) -> Result<Self, encode::Error> {
+        let descriptor = String::consensus_decode_from_finite_reader(r)?
+            .parse()
+            .map_err(|_| parse_failed_error("invalid descriptor"))?;
+        Ok(Self { descriptor, last_used_index: decode_option(r)? })
+    }
+}
+
+impl Encodable for WalletUtxo {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        let mut len = self.utxo.consensus_encode(w)?;
+        len += encode_option(self.height.map(BlockHeight::to_u32).as_ref(), w)?;
+        Ok(len)
+    }
+}
+
+impl Decodable for WalletUtxo {
+    fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
+        r: &mut R,
+    ) -> Result<Self, encode::Error> {
+        Ok(Self {
+            utxo: Decodable::consensus_decode_from_finite_reader(r)?,
+            height: decode_option(r)?.map(BlockHeight::from_u32),
+        })
+    }
+}
+
+impl Encodable for FilterCheckpoint {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        let mut len = self.height.to_u32().consensus_encode(w)?;
+        len += self.block_hash.consensus_encode(w)?;
+        len += self.filter_header.consensus_encode(w)?;
+        Ok(len)
+    }
+}
+
+impl Decodable for FilterCheckpoint {
+    fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
+        r: &mut R,
+    ) -> Result<Self, encode::Error> {
+        Ok(Self {
+            height: BlockHeight::from_u32(r.read_u32()?),
+            block_hash: Decodable::consensus_decode_from_finite_reader(r)?,
+            filter_header: Decodable::consensus_decode_from_finite_reader(r)?,
+        })
+    }
+}
+
+/// Encodes an optional value as a presence flag followed by the value.
+fn encode_option<T: Encodable, W: Write + ?Sized>(
+    value: Option<&T>,
+    w: &mut W,
+) -> Result<usize, io::Error> {
+    w.emit_bool(value.is_some())?;
+    match value {
+        Some(value) => Ok(1 + value.consensus_encode(w)?),
+        None => Ok(1),
+    }
+}
+
+fn decode_option<T: Decodable, R: BufRead + ?Sized>(r: &mut R) -> Result<Option<T>, encode::Error> {
+    if r.read_bool()? {
+        Ok(Some(T::consensus_decode_from_finite_reader(r)?))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{OutPoint, TxOut};
+    use crate::{Amount, Txid};
+
+    fn state() -> WatchOnlyState {
+        let descriptor = "tr([73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)"
+            .parse::<Descriptor>()
+            .unwrap();
+        let address = descriptor.address_at(0, crate::Network::Bitcoin).unwrap();
+        let output = TxOut {
+            amount: Amount::from_sat(10_000).unwrap(),
+            script_pubkey: address.script_pubkey(),
+        };
+        WatchOnlyState {
+            descriptors: vec![DescriptorState { descriptor, last_used_index: Some(0) }],
+            utxos: vec![
+                WalletUtxo {
+                    utxo: Utxo {
+                        out_point: OutPoint { txid: Txid::from_byte_array([1; 32]), vout: 0 },
+                        output: output.clone(),
+                    },
+                    height: Some(BlockHeight::from_u32(800_000)),
+                },
+                WalletUtxo {
+                    utxo: Utxo {
+                        out_point: OutPoint { txid: Txid::from_byte_array([2; 32]), vout: 3 },
+                        output,
+                    },
+                    height: None,
+                },
+            ],
+            checkpoint: Some(FilterCheckpoint {
+                height: BlockHeight::from_u32(800_010),
+                block_hash: BlockHash::from_byte_array([3; 32]),
+                filter_header: sha256d::Hash::from_byte_array([4; 32]),
+            }),
+        }
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let state = state();
+        let bytes = encode::serialize(&state);
+        assert_eq!(bytes[0], VERSION);
+        assert_eq!(encode::deserialize::<WatchOnlyState>(&bytes).unwrap(), state);
+
+        let empty = WatchOnlyState::default();
+        assert_eq!(encode::serialize(&empty), [VERSION, 0, 0, 0]);
+
+        let mut bytes = bytes;
+        bytes[0] = 0;
+        assert!(encode::deserialize::<WatchOnlyState>(&bytes).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let state = state();
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains("tr([73c5da0a/86'/0'/0']xpub6BgBgses"));
+        assert_eq!(serde_json::from_str::<WatchOnlyState>(&json).unwrap(), state);
+    }
+}
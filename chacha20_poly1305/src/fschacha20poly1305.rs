@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! `FSChaCha20Poly1305` - forward secure `ChaCha20Poly1305`
+//!
+//! The rekeying wrapper around [`ChaCha20Poly1305`] from BIP-0324. Each packet is encrypted with
+//! a nonce derived from a packet counter and every [`REKEY_INTERVAL`] packets the key is replaced
+//! so that a compromised key cannot be used to decrypt earlier packets.
+
+use crate::{ChaCha20Poly1305, Error, Key, Nonce};
+
+/// Number of packets encrypted with a key before it is replaced.
+pub const REKEY_INTERVAL: u64 = 224;
+
+/// Forward secure `ChaCha20Poly1305` from BIP-0324.
+///
+/// The sending and receiving side must process the same packets in the same order.
+pub struct FSChaCha20Poly1305 {
+    key: Key,
+    packet_counter: u64,
+}
+
+impl FSChaCha20Poly1305 {
+    /// Make a new instance of a `FSChaCha20Poly1305` AEAD starting at the first packet.
+    pub const fn new(key: Key) -> Self { Self { key, packet_counter: 0 } }
+
+    /// Returns the number of packets processed so far.
+    pub const fn packet_counter(&self) -> u64 { self.packet_counter }
+
+    /// Encrypt the content of the next packet in place and return the 16-byte authentication tag.
+    pub fn encrypt(&mut self, content: &mut [u8], aad: &[u8]) -> [u8; 16] {
+        let mut encryptor = self.next_cipher().encryptor();
+        encryptor.aad(aad);
+        encryptor.update(content);
+        encryptor.finalize()
+    }
+
+    /// Decrypt the content of the next packet in place if the authentication tag is correct.
+    ///
+    /// The packet counts as processed even if decryption fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnauthenticatedAdditionalData`] if the computed authentication tag does
+    /// not match the provided tag.
+    pub fn decrypt(&mut self, content: &mut [u8], tag: [u8; 16], aad: &[u8]) -> Result<(), Error> {
+        self.next_cipher().decrypt(content, tag, Some(aad))
+    }
+
+    /// Returns the cipher for the current packet and advances to the next one, rekeying if needed.
+    ///
+    /// Cipher instances are only ever used for a single packet so the rekeying can be done before
+    /// the returned cipher is used.
+    fn next_cipher(&mut self) -> ChaCha20Poly1305 {
+        let nonce = self.nonce();
+        let cipher = ChaCha20Poly1305::new(self.key, Nonce::new(nonce));
+
+        if (self.packet_counter + 1) % REKEY_INTERVAL == 0 {
+            let mut rekey_nonce = nonce;
+            rekey_nonce[..4].copy_from_slice(&[0xff; 4]);
+            let mut key = [0u8; 32];
+            ChaCha20Poly1305::new(self.key, Nonce::new(rekey_nonce)).encrypt(&mut key, None);
+            self.key = Key::new(key);
+        }
+        self.packet_counter += 1;
+
+        cipher
+    }
+
+    /// The nonce is the packet index within the current key, followed by the number of rekeys.
+    fn nonce(&self) -> [u8; 12] {
+        let index = (self.packet_counter % REKEY_INTERVAL) as u32;
+        let rekeys = self.packet_counter / REKEY_INTERVAL;
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&index.to_le_bytes());
+        nonce[4..].copy_from_slice(&rekeys.to_le_bytes());
+        nonce
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_across_rekeys() {
+        let key = Key::new([0x42; 32]);
+        let mut sender = FSChaCha20Poly1305::new(key);
+        let mut receiver = FSChaCha20Poly1305::new(key);
+
+        for i in 0..(2 * REKEY_INTERVAL + 3) {
+            let plaintext = i.to_le_bytes();
+            let mut content = plaintext;
+            let tag = sender.encrypt(&mut content, b"aad");
+            assert_ne!(content, plaintext);
+            receiver.decrypt(&mut content, tag, b"aad").unwrap();
+            assert_eq!(content, plaintext);
+        }
+        assert_eq!(sender.packet_counter(), 2 * REKEY_INTERVAL + 3);
+    }
+
+    #[test]
+    fn nonce_and_rekey() {
+        let key = Key::new([0x42; 32]);
+        let mut cipher = FSChaCha20Poly1305::new(key);
+
+        // The first packet uses the initial key with a zero nonce.
+        let mut content = [0u8; 16];
+        let tag = cipher.encrypt(&mut content, &[]);
+        let mut expected = [0u8; 16];
+        let expected_tag =
+            ChaCha20Poly1305::new(key, Nonce::new([0; 12])).encrypt(&mut expected, None);
+        assert_eq!((content, tag), (expected, expected_tag));
+
+        for _ in 1..REKEY_INTERVAL {
+            cipher.encrypt(&mut [0u8; 16], &[]);
+        }
+
+        // After the interval the key is the encryption of zeros with the `0xffffffff` nonce.
+        let mut rekeyed = [0u8; 32];
+        let mut rekey_nonce = [0; 12];
+        rekey_nonce[..4].copy_from_slice(&[0xff; 4]);
+        ChaCha20Poly1305::new(key, Nonce::new(rekey_nonce)).encrypt(&mut rekeyed, None);
+        let mut nonce = [0; 12];
+        nonce[4] = 1;
+
+        let mut content = [0u8; 16];
+        let tag = cipher.encrypt(&mut content, &[]);
+        let mut expected = [0u8; 16];
+        let expected_tag = ChaCha20Poly1305::new(Key::new(rekeyed), Nonce::new(nonce))
+            .encrypt(&mut expected, None);
+        assert_eq!((content, tag), (expected, expected_tag));
+    }
+
+    #[test]
+    fn wrong_tag() {
+        let key = Key::new([0x42; 32]);
+        let mut sender = FSChaCha20Poly1305::new(key);
+        let mut receiver = FSChaCha20Poly1305::new(key);
+
+        let mut content = *b"packet";
+        let mut tag = sender.encrypt(&mut content, &[]);
+        tag[0] ^= 1;
+        assert_eq!(
+            receiver.decrypt(&mut content, tag, &[]),
+            Err(Error::UnauthenticatedAdditionalData)
+        );
+        assert_eq!(receiver.packet_counter(), 1);
+    }
+}
@@ -19,6 +19,7 @@
 extern crate std;
 
 pub mod chacha20;
+pub mod fschacha20poly1305;
 pub mod poly1305;
 
 use core::fmt;
@@ -27,6 +28,7 @@
 use poly1305::Poly1305;
 
 pub use self::chacha20::{Key, Nonce};
+pub use self::fschacha20poly1305::FSChaCha20Poly1305;
 
 /// Zero array for padding slices.
 const ZEROES: [u8; 16] = [0u8; 16];
@@ -78,29 +80,10 @@ pub const fn new(key: Key, nonce: Nonce) -> Self {
     ///
     /// The 16-byte authentication tag.
     pub fn encrypt(self, content: &mut [u8], aad: Option<&[u8]>) -> [u8; 16] {
-        let mut chacha = ChaCha20::new_from_block(self.key, self.nonce, 1);
-        chacha.apply_keystream(content);
-        let keystream = chacha.get_keystream(0);
-        let mut poly_key = [0u8; 32];
-        poly_key.copy_from_slice(&keystream[..32]);
-        let mut poly = Poly1305::new(poly_key);
-        let aad = aad.unwrap_or(&[]);
-        // AAD and ciphertext are padded if not 16-byte aligned.
-        poly.input(aad);
-        let aad_overflow = aad.len() % 16;
-        if aad_overflow > 0 {
-            poly.input(&ZEROES[0..(16 - aad_overflow)]);
-        }
-
-        poly.input(content);
-        let text_overflow = content.len() % 16;
-        if text_overflow > 0 {
-            poly.input(&ZEROES[0..(16 - text_overflow)]);
-        }
-
-        let len_buffer = encode_lengths(aad.len() as u64, content.len() as u64);
-        poly.input(&len_buffer);
-        poly.tag()
+        let mut encryptor = self.encryptor();
+        encryptor.aad(aad.unwrap_or(&[]));
+        encryptor.update(content);
+        encryptor.finalize()
     }
 
     /// Decrypt the ciphertext in place if authentication tag is correct.
@@ -121,27 +104,10 @@ pub fn decrypt(
         tag: [u8; 16],
         aad: Option<&[u8]>,
     ) -> Result<(), Error> {
-        let chacha = ChaCha20::new_from_block(self.key, self.nonce, 0);
-        let keystream = chacha.get_keystream(0);
-        let mut poly_key = [0u8; 32];
-        poly_key.copy_from_slice(&keystream[..32]);
-        let mut poly = Poly1305::new(poly_key);
-        let aad = aad.unwrap_or(&[]);
-        poly.input(aad);
-        // AAD and ciphertext are padded if not 16-byte aligned.
-        let aad_overflow = aad.len() % 16;
-        if aad_overflow > 0 {
-            poly.input(&ZEROES[0..(16 - aad_overflow)]);
-        }
-        poly.input(content);
-        let msg_overflow = content.len() % 16;
-        if msg_overflow > 0 {
-            poly.input(&ZEROES[0..(16 - msg_overflow)]);
-        }
-
-        let len_buffer = encode_lengths(aad.len() as u64, content.len() as u64);
-        poly.input(&len_buffer);
-        let derived_tag = poly.tag();
+        let mut mac = Authenticator::new(self.key, self.nonce);
+        mac.aad(aad.unwrap_or(&[]));
+        mac.content(content);
+        let derived_tag = mac.tag();
         if derived_tag == tag {
             let mut chacha = ChaCha20::new_from_block(self.key, self.nonce, 1);
             chacha.apply_keystream(content);
@@ -150,6 +116,141 @@ pub fn decrypt(
             Err(Error::UnauthenticatedAdditionalData)
         }
     }
+
+    /// Starts encrypting a message incrementally.
+    ///
+    /// This allows encrypting messages without having all of the content in a single buffer.
+    pub fn encryptor(self) -> Encryptor {
+        Encryptor {
+            chacha: ChaCha20::new_from_block(self.key, self.nonce, 1),
+            mac: Authenticator::new(self.key, self.nonce),
+        }
+    }
+
+    /// Starts decrypting a message incrementally.
+    ///
+    /// This allows decrypting messages without having all of the content in a single buffer.
+    /// Unlike [`ChaCha20Poly1305::decrypt`], the content is decrypted before the authentication
+    /// tag is checked. The decrypted content must not be used before
+    /// [`Decryptor::finalize`] succeeds.
+    pub fn decryptor(self) -> Decryptor {
+        Decryptor {
+            chacha: ChaCha20::new_from_block(self.key, self.nonce, 1),
+            mac: Authenticator::new(self.key, self.nonce),
+        }
+    }
+}
+
+/// Incrementally encrypts a message, see [`ChaCha20Poly1305::encryptor`].
+pub struct Encryptor {
+    chacha: ChaCha20,
+    mac: Authenticator,
+}
+
+impl Encryptor {
+    /// Adds metadata covered by the authentication tag, can be called multiple times.
+    ///
+    /// # Panics
+    ///
+    /// If called after [`Encryptor::update`].
+    pub fn aad(&mut self, aad: &[u8]) { self.mac.aad(aad) }
+
+    /// Encrypts the next part of the content in place.
+    pub fn update(&mut self, content: &mut [u8]) {
+        self.chacha.apply_keystream(content);
+        self.mac.content(content);
+    }
+
+    /// Returns the `Poly1305` 16-byte authentication tag of the whole message.
+    pub fn finalize(self) -> [u8; 16] { self.mac.tag() }
+}
+
+/// Incrementally decrypts a message, see [`ChaCha20Poly1305::decryptor`].
+pub struct Decryptor {
+    chacha: ChaCha20,
+    mac: Authenticator,
+}
+
+impl Decryptor {
+    /// Adds metadata covered by the authentication tag, can be called multiple times.
+    ///
+    /// # Panics
+    ///
+    /// If called after [`Decryptor::update`].
+    pub fn aad(&mut self, aad: &[u8]) { self.mac.aad(aad) }
+
+    /// Decrypts the next part of the content in place.
+    ///
+    /// The decrypted content is not authenticated until [`Decryptor::finalize`] succeeds.
+    pub fn update(&mut self, content: &mut [u8]) {
+        self.mac.content(content);
+        self.chacha.apply_keystream(content);
+    }
+
+    /// Checks the authentication tag of the whole message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnauthenticatedAdditionalData`] if the computed authentication tag does
+    /// not match the provided tag, in which case all decrypted content must be discarded.
+    pub fn finalize(self, tag: [u8; 16]) -> Result<(), Error> {
+        if self.mac.tag() == tag {
+            Ok(())
+        } else {
+            Err(Error::UnauthenticatedAdditionalData)
+        }
+    }
+}
+
+/// Computes the `Poly1305` tag over the padded AAD, ciphertext and their lengths.
+struct Authenticator {
+    poly: Poly1305,
+    aad_len: u64,
+    content_len: u64,
+    /// Whether the AAD has been padded because content was added.
+    has_content: bool,
+}
+
+impl Authenticator {
+    fn new(key: Key, nonce: Nonce) -> Self {
+        let keystream = ChaCha20::new_from_block(key, nonce, 0).get_keystream(0);
+        let mut poly_key = [0u8; 32];
+        poly_key.copy_from_slice(&keystream[..32]);
+        Self { poly: Poly1305::new(poly_key), aad_len: 0, content_len: 0, has_content: false }
+    }
+
+    fn aad(&mut self, aad: &[u8]) {
+        assert!(!self.has_content, "additional data must be added before the content");
+        self.poly.input(aad);
+        self.aad_len += aad.len() as u64;
+    }
+
+    fn content(&mut self, ciphertext: &[u8]) {
+        if !self.has_content {
+            // AAD and ciphertext are padded if not 16-byte aligned.
+            self.pad(self.aad_len);
+            self.has_content = true;
+        }
+        self.poly.input(ciphertext);
+        self.content_len += ciphertext.len() as u64;
+    }
+
+    fn tag(mut self) -> [u8; 16] {
+        if !self.has_content {
+            self.pad(self.aad_len);
+        }
+        self.pad(self.content_len);
+        let len_buffer = encode_lengths(self.aad_len, self.content_len);
+        self.poly.input(&len_buffer);
+        self.poly.tag()
+    }
+
+    fn pad(&mut self, len: u64) {
+        let overflow = (len % 16) as usize;
+        if overflow > 0 {
+            self.poly.input(&ZEROES[0..(16 - overflow)]);
+        }
+    }
 }
 
 /// AAD and content lengths are each encoded in 8-bytes.
@@ -194,4 +295,59 @@ fn rfc7539() {
 
         assert_eq!(&buffer.to_lower_hex_string(), "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b61161ae10b594f09e26a7e902ecbd0600691");
     }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let message = *b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+        let aad = b"some additional data";
+        let key = Key::new([0x80; 32]);
+        let nonce = Nonce::new([0x07; 12]);
+
+        let mut expected = message;
+        let expected_tag = ChaCha20Poly1305::new(key, nonce).encrypt(&mut expected, Some(aad));
+
+        for chunk_size in [1, 7, 16, 63, 64, 65, 200] {
+            let mut content = message;
+            let mut encryptor = ChaCha20Poly1305::new(key, nonce).encryptor();
+            for aad_chunk in aad.chunks(3) {
+                encryptor.aad(aad_chunk);
+            }
+            for chunk in content.chunks_mut(chunk_size) {
+                encryptor.update(chunk);
+            }
+            let tag = encryptor.finalize();
+            assert_eq!(content, expected);
+            assert_eq!(tag, expected_tag);
+
+            let mut decryptor = ChaCha20Poly1305::new(key, nonce).decryptor();
+            decryptor.aad(aad);
+            for chunk in content.chunks_mut(chunk_size) {
+                decryptor.update(chunk);
+            }
+            decryptor.finalize(tag).unwrap();
+            assert_eq!(content, message);
+        }
+    }
+
+    #[test]
+    fn incremental_decrypt_wrong_tag() {
+        let key = Key::new([0x80; 32]);
+        let nonce = Nonce::new([0x07; 12]);
+        let mut content = *b"message";
+        let tag = ChaCha20Poly1305::new(key, nonce).encrypt(&mut content, None);
+
+        let mut decryptor = ChaCha20Poly1305::new(key, nonce).decryptor();
+        decryptor.aad(b"not the aad");
+        decryptor.update(&mut content);
+        assert_eq!(decryptor.finalize(tag), Err(Error::UnauthenticatedAdditionalData));
+    }
+
+    #[test]
+    #[should_panic(expected = "before the content")]
+    fn incremental_aad_after_content() {
+        let mut encryptor =
+            ChaCha20Poly1305::new(Key::new([0; 32]), Nonce::new([0; 12])).encryptor();
+        encryptor.update(&mut [0; 4]);
+        encryptor.aad(b"too late");
+    }
 }
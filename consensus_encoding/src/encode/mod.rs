@@ -4,6 +4,7 @@
 
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+use core::fmt;
 
 pub mod encoders;
 
@@ -162,6 +163,69 @@ pub fn flush_to_vec<T>(encoder: &mut T) -> Vec<u8>
     vec
 }
 
+/// Encodes an object into a caller-provided buffer without allocating.
+///
+/// Returns the number of bytes written to the start of `buffer`.
+///
+/// # Errors
+///
+/// Returns an error if `buffer` is too small to hold the encoding, in which case the contents of
+/// `buffer` are unspecified.
+pub fn encode_to_slice<T>(object: &T, buffer: &mut [u8]) -> Result<usize, BufferTooSmallError>
+where
+    T: Encodable + ?Sized,
+{
+    let mut encoder = object.encoder();
+    flush_to_slice(&mut encoder, buffer)
+}
+
+/// Flushes the output of an [`Encoder`] into a caller-provided buffer.
+///
+/// See [`encode_to_slice`] for more information.
+///
+/// # Errors
+///
+/// Returns an error if `buffer` is too small to hold the output of the encoder.
+pub fn flush_to_slice<T>(encoder: &mut T, buffer: &mut [u8]) -> Result<usize, BufferTooSmallError>
+where
+    T: Encoder + ?Sized,
+{
+    let mut written = 0;
+    loop {
+        let chunk = encoder.current_chunk();
+        let end = written + chunk.len();
+        if end > buffer.len() {
+            return Err(BufferTooSmallError { buffer_len: buffer.len() });
+        }
+        buffer[written..end].copy_from_slice(chunk);
+        written = end;
+        if !encoder.advance() {
+            break;
+        }
+    }
+    Ok(written)
+}
+
+/// The buffer passed to [`encode_to_slice`] is too small to hold the encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferTooSmallError {
+    buffer_len: usize,
+}
+
+impl BufferTooSmallError {
+    /// Returns the length of the buffer that was too small.
+    pub fn buffer_len(&self) -> usize { self.buffer_len }
+}
+
+impl fmt::Display for BufferTooSmallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "buffer of {} bytes is too small for the encoding", self.buffer_len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmallError {}
+
 /// Encodes an object to a standard I/O writer.
 ///
 /// # Performance
@@ -37,8 +37,11 @@
     ArrayEncoder, ArrayRefEncoder, BytesEncoder, CompactSizeEncoder, Encoder2, Encoder3, Encoder4,
     Encoder6, SliceEncoder,
 };
+pub use self::encode::{
+    encode_to_slice, flush_to_slice, BufferTooSmallError, Encodable, EncodableByteIter, Encoder,
+    ExactSizeEncoder,
+};
 #[cfg(feature = "alloc")]
 pub use self::encode::{encode_to_vec, flush_to_vec};
 #[cfg(feature = "std")]
 pub use self::encode::{encode_to_writer, flush_to_writer};
-pub use self::encode::{Encodable, EncodableByteIter, Encoder, ExactSizeEncoder};
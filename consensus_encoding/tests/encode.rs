@@ -219,3 +219,47 @@ fn encode_option_encoder_none() {
     assert!(!encoder.advance());
     assert!(encoder.current_chunk().is_empty());
 }
+
+#[test]
+fn encode_to_slice_fits() {
+    use bitcoin_consensus_encoding::flush_to_slice;
+
+    let mut encoder = Encoder2::new(
+        CompactSizeEncoder::new(3usize),
+        ArrayEncoder::<3>::without_length_prefix([1, 2, 3]),
+    );
+    let mut buffer = [0xff; 8];
+    let written = flush_to_slice(&mut encoder, &mut buffer).unwrap();
+    assert_eq!(written, 4);
+    assert_eq!(buffer, [3, 1, 2, 3, 0xff, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+fn encode_to_slice_exact_and_too_small() {
+    use bitcoin_consensus_encoding::flush_to_slice;
+
+    let encoder = || {
+        Encoder2::new(
+            ArrayEncoder::<2>::without_length_prefix([1, 2]),
+            ArrayEncoder::<2>::without_length_prefix([3, 4]),
+        )
+    };
+    let mut buffer = [0; 4];
+    assert_eq!(flush_to_slice(&mut encoder(), &mut buffer), Ok(4));
+    assert_eq!(buffer, [1, 2, 3, 4]);
+
+    let mut buffer = [0; 3];
+    let err = flush_to_slice(&mut encoder(), &mut buffer).unwrap_err();
+    assert_eq!(err.buffer_len(), 3);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn encode_to_slice_matches_vec() {
+    use bitcoin_consensus_encoding::{encode_to_slice, encode_to_vec};
+
+    let data = TestData(0x1234_5678);
+    let mut buffer = [0; 4];
+    assert_eq!(encode_to_slice(&data, &mut buffer), Ok(4));
+    assert_eq!(buffer[..], encode_to_vec(&data)[..]);
+}
@@ -1,16 +1,6 @@
 use honggfuzz::fuzz;
 
-fn do_test(data: &[u8]) {
-    let block_result: Result<bitcoin::Block, _> = bitcoin::consensus::encode::deserialize(data);
-
-    match block_result {
-        Err(_) => {}
-        Ok(block) => {
-            let ser = bitcoin::consensus::encode::serialize(&block);
-            assert_eq!(&ser[..], data);
-        }
-    }
-}
+fn do_test(data: &[u8]) { let _ = bitcoin::consensus::fuzz_roundtrip::<bitcoin::Block>(data); }
 
 fn main() {
     loop {
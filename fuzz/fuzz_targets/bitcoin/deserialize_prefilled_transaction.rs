@@ -2,16 +2,7 @@
 
 fn do_test(data: &[u8]) {
     // We already fuzz Transactions in `./deserialize_transaction.rs`.
-    let tx_result: Result<p2p::bip152::PrefilledTransaction, _> =
-        bitcoin::consensus::encode::deserialize(data);
-
-    match tx_result {
-        Err(_) => {}
-        Ok(tx) => {
-            let ser = bitcoin::consensus::encode::serialize(&tx);
-            assert_eq!(&ser[..], data);
-        }
-    }
+    let _ = bitcoin::consensus::fuzz_roundtrip::<p2p::bip152::PrefilledTransaction>(data);
 }
 
 fn main() {
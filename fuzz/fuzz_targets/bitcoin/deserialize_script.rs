@@ -1,16 +1,7 @@
 use honggfuzz::fuzz;
 
 fn do_test(data: &[u8]) {
-    let script_result: Result<bitcoin::ScriptPubKeyBuf, _> =
-        bitcoin::consensus::encode::deserialize(data);
-
-    match script_result {
-        Err(_) => {}
-        Ok(script) => {
-            let ser = bitcoin::consensus::encode::serialize(&script);
-            assert_eq!(&ser[..], data);
-        }
-    }
+    let _ = bitcoin::consensus::fuzz_roundtrip::<bitcoin::ScriptPubKeyBuf>(data);
 }
 
 fn main() {
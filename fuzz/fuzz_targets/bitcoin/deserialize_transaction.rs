@@ -1,15 +1,7 @@
 use honggfuzz::fuzz;
 
 fn do_test(data: &[u8]) {
-    let tx_result: Result<bitcoin::Transaction, _> = bitcoin::consensus::encode::deserialize(data);
-
-    match tx_result {
-        Err(_) => {}
-        Ok(tx) => {
-            let ser = bitcoin::consensus::encode::serialize(&tx);
-            assert_eq!(&ser[..], data);
-        }
-    }
+    let _ = bitcoin::consensus::fuzz_roundtrip::<bitcoin::Transaction>(data);
 }
 
 fn main() {
@@ -1,17 +1,7 @@
 use bitcoin::witness::Witness;
 use honggfuzz::fuzz;
 
-fn do_test(data: &[u8]) {
-    let witness_result: Result<Witness, _> = bitcoin::consensus::encode::deserialize(data);
-
-    match witness_result {
-        Err(_) => {}
-        Ok(witness) => {
-            let ser = bitcoin::consensus::encode::serialize(&witness);
-            assert_eq!(&ser[..], data);
-        }
-    }
-}
+fn do_test(data: &[u8]) { let _ = bitcoin::consensus::fuzz_roundtrip::<Witness>(data); }
 
 fn main() {
     loop {
@@ -173,6 +173,17 @@ fn rfc5869_longer_inputs_outputs() {
         );
     }
 
+    #[test]
+    fn type_alias() {
+        let hkdf = crate::HkdfSha256::new(&[0; 13], &[0x0b; 22]);
+        let mut okm = [0u8; 32];
+        hkdf.expand(&[], &mut okm).unwrap();
+
+        let mut expected = [0u8; 32];
+        Hkdf::<sha256::HashEngine>::new(&[0; 13], &[0x0b; 22]).expand(&[], &mut expected).unwrap();
+        assert_eq!(okm, expected);
+    }
+
     #[test]
     fn too_long_okm() {
         let salt = Vec::from_hex("000102030405060708090a0b0c").unwrap();
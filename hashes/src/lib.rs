@@ -100,6 +100,7 @@ pub mod _core {
 #[macro_use]
 pub mod macros;
 pub mod muhash;
+pub mod pbkdf2;
 pub mod ripemd160;
 pub mod sha1;
 pub mod sha256;
@@ -165,10 +166,10 @@ pub mod _core {
 pub type HmacSha512 = Hmac<sha512::Hash>;
 
 /// HKDF-HMAC-SHA-256: Type alias for the [`Hkdf<Sha256>`] type.
-pub type HkdfSha256 = Hkdf<sha256::Hash>;
+pub type HkdfSha256 = Hkdf<sha256::HashEngine>;
 
 /// HKDF-HMAC-SHA-512: Type alias for the [`Hkdf<Sha512>`] type.
-pub type HkdfSha512 = Hkdf<sha512::Hash>;
+pub type HkdfSha512 = Hkdf<sha512::HashEngine>;
 
 /// A hashing engine which bytes can be serialized into.
 pub trait HashEngine: Clone {
@@ -3,6 +3,7 @@
 //! Public macros.
 //!
 //! - [`sha256t_tag`](crate::sha256t_tag)
+//! - [`sha256t_hash_newtype`](crate::sha256t_hash_newtype)
 //! - [`hash_newtype`](crate::hash_newtype)
 //! - [`impl_hex_for_newtype`](crate::impl_hex_for_newtype)
 //! - [`impl_serde_for_newtype`](crate::impl_serde_for_newtype)
@@ -38,6 +39,72 @@ impl $crate::sha256t::Tag for $tag {
     }
 }
 
+/// Macro used to define a tagged hash type together with its tag.
+///
+/// This is a shorthand for calling [`sha256t_tag`](crate::sha256t_tag) followed by
+/// [`hash_newtype`](crate::hash_newtype) with a [`sha256t::Hash`](crate::sha256t::Hash) as the
+/// inner type. The syntax is:
+///
+/// ```
+/// # use bitcoin_hashes::{sha256t_hash_newtype, HashEngine as _};
+/// sha256t_hash_newtype! {
+///     /// Optional documentation details here.
+///     /// Summary is always generated.
+///     pub struct LabelTag = hash_str("BIP0352/Label");
+///
+///     /// A BIP-0352 label tweak.
+///     #[hash_newtype(forward)]
+///     pub struct LabelHash(_);
+/// }
+///
+/// let mut engine = LabelHash::engine();
+/// engine.input(&[0; 32]);
+/// engine.input(&0_u32.to_be_bytes());
+/// let label = LabelHash::from_engine(engine);
+///
+/// assert!(LabelHash::hash(&[0; 36]) == label);
+/// ```
+///
+/// The tag constructor accepts the same markers as [`sha256t_tag`](crate::sha256t_tag). The hash
+/// type accepts the same attributes as [`hash_newtype`](crate::hash_newtype), including the display
+/// direction. The inner field is private.
+///
+/// In addition to the methods generated by [`hash_newtype`](crate::hash_newtype), the hash type
+/// gets `engine`, `from_engine` and `hash` functions so that the tagged engine never needs to be
+/// seeded by hand.
+#[macro_export]
+macro_rules! sha256t_hash_newtype {
+    ($(#[$($tag_attr:tt)*])* $tag_vis:vis struct $tag:ident = $constructor:tt($($tag_value:tt)+); $(#[$($hash_attr:tt)*])* $hash_vis:vis struct $hash_name:ident(_);) => {
+        $crate::sha256t_tag_struct!($tag_vis, $tag, stringify!($hash_name), $(#[$($tag_attr)*])*);
+
+        impl $crate::sha256t::Tag for $tag {
+            const MIDSTATE: $crate::sha256::Midstate = $crate::sha256t_tag_constructor!($constructor, $($tag_value)+);
+        }
+
+        $crate::hash_newtype! {
+            $(#[$($hash_attr)*])*
+            $hash_vis struct $hash_name($crate::sha256t::Hash<$tag>);
+        }
+
+        #[allow(unused)] // Not all functions are used by all hash types.
+        impl $hash_name {
+            /// Constructs a new engine pre-tagged with the tag of this hash.
+            $hash_vis fn engine() -> $crate::sha256t::HashEngine<$tag> { Default::default() }
+
+            /// Produces a hash from the current state of a given engine.
+            $hash_vis fn from_engine(e: $crate::sha256t::HashEngine<$tag>) -> Self {
+                Self::from_byte_array($crate::sha256t::Hash::from_engine(e).to_byte_array())
+            }
+
+            /// Hashes some bytes.
+            #[allow(clippy::self_named_constructors)] // Hash is a noun and a verb.
+            $hash_vis fn hash(data: &[u8]) -> Self {
+                Self::from_byte_array($crate::sha256t::hash::<$tag>(data).to_byte_array())
+            }
+        }
+    }
+}
+
 /// Constructs a new newtype around a [`Hash`] type.
 ///
 /// The syntax is similar to the usual tuple struct syntax:
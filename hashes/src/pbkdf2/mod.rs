@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Password-Based Key Derivation Function 2 (PBKDF2).
+//!
+//! Implementation based on RFC8018 with HMAC as the pseudorandom function. BIP-0039 uses it with
+//! HMAC-SHA512 and 2048 iterations to derive the seed from a mnemonic.
+
+use core::fmt;
+
+use crate::{HashEngine, HmacEngine, IsByteArray};
+
+/// Size of output exceeds maximum length allowed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MaxLengthError {
+    max: usize,
+}
+
+impl fmt::Display for MaxLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "exceeds {} byte max derived key limit", self.max)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MaxLengthError {}
+
+/// Derives a key from `password` and `salt` using PBKDF2 with HMAC over `T`, filling `output`.
+///
+/// The key is as long as `output`, which may be up to `(2^32 - 1) * T::LEN` bytes.
+///
+/// # Errors
+///
+/// If `output` is longer than the maximum length.
+///
+/// # Panics
+///
+/// If `iterations` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin_hashes::{pbkdf2, sha512};
+///
+/// let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+/// let passphrase = "TREZOR";
+/// let mut seed = [0; 64];
+/// pbkdf2::derive_key::<sha512::HashEngine>(
+///     mnemonic.as_bytes(),
+///     format!("mnemonic{}", passphrase).as_bytes(),
+///     2048,
+///     &mut seed,
+/// )
+/// .expect("64 bytes is less than the maximum");
+/// assert_eq!(&seed[..4], &[0xc5, 0x52, 0x57, 0xc3]);
+/// ```
+pub fn derive_key<T>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    output: &mut [u8],
+) -> Result<(), MaxLengthError>
+where
+    T: HashEngine + Default,
+{
+    assert!(iterations > 0, "PBKDF2 requires at least one iteration");
+    let max = (u32::MAX as usize).saturating_mul(T::Bytes::LEN);
+    if output.len() > max {
+        return Err(MaxLengthError { max });
+    }
+
+    // Keying HMAC is relatively expensive and the key is always the password, so do it once.
+    let prf = HmacEngine::<T>::new(password);
+    for (index, block) in output.chunks_mut(T::Bytes::LEN).enumerate() {
+        // Block indices start at one and fit in a `u32` because of the length check above.
+        let index = u32::try_from(index + 1).expect("checked output length");
+
+        let mut engine = prf.clone();
+        engine.input(salt);
+        engine.input(&index.to_be_bytes());
+        let mut u = engine.finalize();
+        block.copy_from_slice(&u.as_ref()[..block.len()]);
+
+        for _ in 1..iterations {
+            let mut engine = prf.clone();
+            engine.input(u.as_ref());
+            u = engine.finalize();
+            for (byte, u_byte) in block.iter_mut().zip(u.as_ref()) {
+                *byte ^= u_byte;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+#[cfg(feature = "hex")]
+mod tests {
+    use hex::prelude::DisplayHex;
+
+    use super::*;
+    use crate::{sha1, sha256, sha512};
+
+    // Test vectors from RFC6070.
+    #[test]
+    fn rfc6070() {
+        struct Test {
+            password: &'static [u8],
+            salt: &'static [u8],
+            iterations: u32,
+            output: &'static str,
+        }
+
+        #[rustfmt::skip]
+        let tests = [
+            Test { password: b"password", salt: b"salt", iterations: 1, output: "0c60c80f961f0e71f3a9b524af6012062fe037a6" },
+            Test { password: b"password", salt: b"salt", iterations: 2, output: "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957" },
+            Test { password: b"password", salt: b"salt", iterations: 4096, output: "4b007901b765489abead49d926f721d065a429c1" },
+            Test {
+                password: b"passwordPASSWORDpassword",
+                salt: b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                iterations: 4096,
+                output: "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+            },
+            Test { password: b"pass\0word", salt: b"sa\0lt", iterations: 4096, output: "56fa6aa75548099dcc37d7f03425e0c3" },
+        ];
+
+        for test in tests {
+            let mut output = [0; 25];
+            let output = &mut output[..test.output.len() / 2];
+            derive_key::<sha1::HashEngine>(test.password, test.salt, test.iterations, output)
+                .unwrap();
+            assert_eq!(output.to_lower_hex_string(), test.output);
+        }
+    }
+
+    #[test]
+    fn sha256_multiple_blocks() {
+        // Each block depends on its index so the blocks of a longer key are a prefix extension.
+        let mut short = [0; 32];
+        let mut long = [0; 70];
+        derive_key::<sha256::HashEngine>(b"password", b"salt", 3, &mut short).unwrap();
+        derive_key::<sha256::HashEngine>(b"password", b"salt", 3, &mut long).unwrap();
+        assert_eq!(long[..32], short);
+        assert_ne!(long[32..64], short);
+    }
+
+    #[test]
+    fn bip39_seed() {
+        let mnemonic =
+            "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        let mut seed = [0; 64];
+        derive_key::<sha512::HashEngine>(mnemonic.as_bytes(), b"mnemonicTREZOR", 2048, &mut seed)
+            .unwrap();
+        assert_eq!(
+            seed.to_lower_hex_string(),
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one iteration")]
+    fn zero_iterations() {
+        let _ = derive_key::<sha256::HashEngine>(b"password", b"salt", 0, &mut [0; 32]);
+    }
+}
@@ -208,12 +208,53 @@ impl Midstate {
         Self { bytes: state, bytes_hashed }
     }
 
+    /// Constructs a new [`Midstate`] from the `state` and the `bytes_hashed` to get to that state.
+    ///
+    /// This is the fallible version of [`Self::new`], for use with untrusted input.
+    ///
+    /// # Errors
+    ///
+    /// If `bytes_hashed` is not a multiple of 64.
+    pub const fn from_parts(
+        state: [u8; 32],
+        bytes_hashed: u64,
+    ) -> Result<Self, MidstateLengthError> {
+        if bytes_hashed % 64 != 0 {
+            return Err(MidstateLengthError { bytes_hashed });
+        }
+        Ok(Self { bytes: state, bytes_hashed })
+    }
+
     /// Deconstructs the [`Midstate`], returning the underlying byte array and number of bytes hashed.
     pub const fn as_parts(&self) -> (&[u8; 32], u64) { (&self.bytes, self.bytes_hashed) }
 
     /// Deconstructs the [`Midstate`], returning the underlying byte array and number of bytes hashed.
     pub const fn to_parts(self) -> ([u8; 32], u64) { (self.bytes, self.bytes_hashed) }
 
+    /// The length of the serialized midstate, see [`Self::to_byte_array`].
+    pub const SERIALIZED_LEN: usize = 40;
+
+    /// Serializes the midstate as the state followed by the number of bytes hashed (as a
+    /// little-endian `u64`).
+    pub fn to_byte_array(self) -> [u8; Self::SERIALIZED_LEN] {
+        let mut bytes = [0; Self::SERIALIZED_LEN];
+        bytes[..32].copy_from_slice(&self.bytes);
+        bytes[32..].copy_from_slice(&self.bytes_hashed.to_le_bytes());
+        bytes
+    }
+
+    /// Deserializes a midstate serialized with [`Self::to_byte_array`].
+    ///
+    /// # Errors
+    ///
+    /// If the number of bytes hashed is not a multiple of 64.
+    pub fn from_byte_array(bytes: [u8; Self::SERIALIZED_LEN]) -> Result<Self, MidstateLengthError> {
+        let (state, bytes_hashed) = bytes.split_at(32);
+        let state = <[u8; 32]>::try_from(state).expect("split at 32");
+        let bytes_hashed = u64::from_le_bytes(bytes_hashed.try_into().expect("40 - 32 = 8"));
+        Self::from_parts(state, bytes_hashed)
+    }
+
     /// Constructs a new midstate for tagged hashes.
     ///
     /// Warning: this function is inefficient. It should be only used in `const` context.
@@ -253,6 +294,47 @@ impl convert::AsRef<[u8]> for Midstate {
     fn as_ref(&self) -> &[u8] { &self.bytes }
 }
 
+// Serialized as a (state, bytes hashed) tuple. The state is serialized like a hash (i.e., as a hex
+// string if human-readable) since it has the same representation.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Midstate {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple as _;
+
+        let mut tuple = s.serialize_tuple(2)?;
+        tuple.serialize_element(&Hash(self.bytes))?;
+        tuple.serialize_element(&self.bytes_hashed)?;
+        tuple.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Midstate {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de;
+
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Midstate;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a (state, bytes hashed) tuple")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Midstate, A::Error> {
+                let state: Hash =
+                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let bytes_hashed: u64 =
+                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Midstate::from_parts(state.0, bytes_hashed).map_err(de::Error::custom)
+            }
+        }
+
+        d.deserialize_tuple(2, Visitor)
+    }
+}
+
 /// `Midstate` invariant violated (not a multiple of 64).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MidstateError {
@@ -285,3 +367,27 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 
 #[cfg(feature = "std")]
 impl std::error::Error for MidstateError {}
+
+/// The number of bytes hashed to get to a [`Midstate`] is not a multiple of 64.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MidstateLengthError {
+    bytes_hashed: u64,
+}
+
+impl MidstateLengthError {
+    /// Returns the invalid number of bytes hashed.
+    pub const fn invalid_bytes_hashed(&self) -> u64 { self.bytes_hashed }
+}
+
+impl fmt::Display for MidstateLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid number of bytes hashed {} (should be a multiple of 64)",
+            self.bytes_hashed
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MidstateLengthError {}
@@ -231,6 +231,70 @@ fn midstate_error_resume_hashing() {
     assert_eq!(sha256::Hash::from_engine(engine1), sha256::Hash::from_engine(engine2));
 }
 
+#[test]
+fn midstate_byte_array_roundtrip() {
+    let mut engine = sha256::Hash::engine();
+    engine.input(&[0xab; 128]);
+    let midstate = engine.midstate().unwrap();
+
+    let bytes = midstate.to_byte_array();
+    assert_eq!(bytes[..32], *midstate.as_parts().0);
+    assert_eq!(bytes[32..], 128_u64.to_le_bytes());
+    assert_eq!(Midstate::from_byte_array(bytes).unwrap(), midstate);
+
+    // Resume hashing in a fresh engine.
+    let mut resumed = sha256::HashEngine::from_midstate(Midstate::from_byte_array(bytes).unwrap());
+    engine.input(b"tail");
+    resumed.input(b"tail");
+    assert_eq!(sha256::Hash::from_engine(resumed), sha256::Hash::from_engine(engine));
+}
+
+#[test]
+fn midstate_invalid_length() {
+    let err = Midstate::from_parts([0; 32], 65).unwrap_err();
+    assert_eq!(err.invalid_bytes_hashed(), 65);
+
+    let mut bytes = [0; Midstate::SERIALIZED_LEN];
+    bytes[32] = 1;
+    assert!(Midstate::from_byte_array(bytes).is_err());
+    assert!(Midstate::from_parts([0; 32], 64).is_ok());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn midstate_serde() {
+    use serde_test::{assert_de_tokens_error, assert_tokens, Configure, Token};
+
+    let midstate = Midstate::new([0x11; 32], 64);
+    assert_tokens(
+        &midstate.readable(),
+        &[
+            Token::Tuple { len: 2 },
+            Token::Str("1111111111111111111111111111111111111111111111111111111111111111"),
+            Token::U64(64),
+            Token::TupleEnd,
+        ],
+    );
+    assert_tokens(
+        &midstate.compact(),
+        &[
+            Token::Tuple { len: 2 },
+            Token::BorrowedBytes(&[0x11; 32]),
+            Token::U64(64),
+            Token::TupleEnd,
+        ],
+    );
+    assert_de_tokens_error::<serde_test::Readable<Midstate>>(
+        &[
+            Token::Tuple { len: 2 },
+            Token::Str("1111111111111111111111111111111111111111111111111111111111111111"),
+            Token::U64(63),
+            Token::TupleEnd,
+        ],
+        "invalid number of bytes hashed 63 (should be a multiple of 64)",
+    );
+}
+
 #[cfg(target_arch = "wasm32")]
 mod wasm_tests {
     use super::*;
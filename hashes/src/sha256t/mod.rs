@@ -192,10 +192,7 @@ macro_rules! sha256t_tag_constructor {
 
 #[cfg(test)]
 mod tests {
-    #[cfg(feature = "alloc")]
-    #[cfg(feature = "hex")]
-    use crate::sha256;
-    use crate::sha256t;
+    use crate::{sha256, sha256t};
 
     const TEST_MIDSTATE: [u8; 32] = [
         156, 224, 228, 230, 124, 17, 108, 57, 56, 179, 202, 242, 195, 15, 80, 137, 211, 243, 147,
@@ -293,4 +290,30 @@ fn macro_created_sha256t_hash_type_prints_forward() {
         // We can also just use the `sha256t::Hash` type directly.
         assert_eq!(sha256t::Hash::<NewTypeTagForward>::hash(&[0]).to_string(), HASH_ZERO_FORWARD);
     }
+
+    // And a macro to create both at once.
+    sha256t_hash_newtype! {
+        struct CombinedTag = hash_str("BIP0340/challenge");
+
+        /// A test hash.
+        struct CombinedHash(_);
+    }
+
+    #[test]
+    fn macro_created_sha256t_hash_newtype() {
+        use crate::HashEngine as _;
+
+        let tag = sha256::Hash::hash(b"BIP0340/challenge");
+        let mut engine = sha256::Hash::engine();
+        engine.input(tag.as_byte_array());
+        engine.input(tag.as_byte_array());
+        engine.input(&[0]);
+        let expected = sha256::Hash::from_engine(engine);
+
+        assert_eq!(CombinedHash::hash(&[0]).to_byte_array(), expected.to_byte_array());
+        assert_eq!(
+            CombinedHash::from_engine(CombinedHash::engine()).to_byte_array(),
+            sha256t::Hash::<CombinedTag>::hash(&[]).to_byte_array()
+        );
+    }
 }
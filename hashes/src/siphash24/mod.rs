@@ -214,6 +214,122 @@ pub fn to_u64(self) -> u64 { u64::from_le_bytes(self.0) }
     pub fn from_u64(hash: u64) -> Self { Self(hash.to_le_bytes()) }
 }
 
+/// SipHash 2-4 as a [`core::hash::Hasher`].
+///
+/// This is the same hash function as [`Hash`](struct@Hash) (which is used, e.g., for BIP-0152
+/// short IDs) but usable anywhere a [`core::hash::Hasher`] is expected, like addrman-style
+/// bucketing or as the hasher of a `HashMap` (see [`BuildHasher`]).
+///
+/// Note that [`core::hash::Hash`] impls are free to feed data into the hasher in whatever way they
+/// like (e.g., integers are written in native byte order and slices are prefixed with their
+/// length). If the output has to match an external specification write the bytes directly using
+/// [`core::hash::Hasher::write`].
+///
+/// # Examples
+///
+/// ```
+/// use core::hash::Hasher as _;
+///
+/// use bitcoin_hashes::siphash24;
+///
+/// let mut hasher = siphash24::Hasher::with_keys(1, 2);
+/// hasher.write(b"some data");
+/// assert_eq!(hasher.finish(), siphash24::Hash::hash_to_u64_with_keys(1, 2, b"some data"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hasher(HashEngine);
+
+impl Hasher {
+    /// Constructs a new hasher with keys.
+    #[inline]
+    pub const fn with_keys(k0: u64, k1: u64) -> Self { Self(HashEngine::with_keys(k0, k1)) }
+
+    /// Constructs a new hasher from a 128-bit key, as used by the SipHash reference implementation.
+    ///
+    /// The first eight bytes are `k0` and the last eight bytes `k1`, both little-endian.
+    #[inline]
+    pub fn from_key(key: [u8; 16]) -> Self {
+        let (k0, k1) = split_key(key);
+        Self::with_keys(k0, k1)
+    }
+
+    /// Retrieves the keys of this hasher.
+    pub fn keys(&self) -> (u64, u64) { self.0.keys() }
+
+    /// Returns the hash of the data written so far.
+    pub fn to_hash(&self) -> Hash { Hash::from_engine(self.0.clone()) }
+}
+
+impl From<HashEngine> for Hasher {
+    #[inline]
+    fn from(engine: HashEngine) -> Self { Self(engine) }
+}
+
+impl core::hash::Hasher for Hasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) { self.0.input(bytes) }
+
+    #[inline]
+    fn finish(&self) -> u64 { Hash::from_engine_to_u64(self.0.clone()) }
+}
+
+/// Constructs SipHash 2-4 [`Hasher`]s with a fixed key.
+///
+/// Use this as the `S` parameter of `HashMap<K, V, S>` to get a keyed hash map. Unlike the
+/// standard library's `RandomState` the key has to be chosen by the caller, if the keys of the map
+/// are attacker controlled it should be chosen at random.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use std::collections::HashMap;
+///
+/// use bitcoin_hashes::siphash24;
+///
+/// let mut map = HashMap::with_hasher(siphash24::BuildHasher::with_keys(0x1234, 0x5678));
+/// map.insert("key", "value");
+/// assert_eq!(map.get("key"), Some(&"value"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BuildHasher {
+    k0: u64,
+    k1: u64,
+}
+
+impl BuildHasher {
+    /// Constructs a new builder with keys.
+    #[inline]
+    pub const fn with_keys(k0: u64, k1: u64) -> Self { Self { k0, k1 } }
+
+    /// Constructs a new builder from a 128-bit key, see [`Hasher::from_key`].
+    #[inline]
+    pub fn from_key(key: [u8; 16]) -> Self {
+        let (k0, k1) = split_key(key);
+        Self::with_keys(k0, k1)
+    }
+
+    /// Retrieves the keys of this builder.
+    pub fn keys(&self) -> (u64, u64) { (self.k0, self.k1) }
+}
+
+impl core::hash::BuildHasher for BuildHasher {
+    type Hasher = Hasher;
+
+    #[inline]
+    fn build_hasher(&self) -> Hasher { Hasher::with_keys(self.k0, self.k1) }
+}
+
+/// Splits a 128-bit key into two little-endian 64-bit keys.
+fn split_key(key: [u8; 16]) -> (u64, u64) {
+    let (k0, k1) = key.split_at(8);
+    (
+        u64::from_le_bytes(k0.try_into().expect("8 bytes")),
+        u64::from_le_bytes(k1.try_into().expect("8 bytes")),
+    )
+}
+
 /// Loads a u64 using up to 7 bytes of a byte slice.
 ///
 /// Unsafe because: unchecked indexing at `start..start+len`.
@@ -328,4 +444,34 @@ fn siphash_2_4() {
             state_inc.input(&[i as u8]);
         }
     }
+
+    #[test]
+    fn hasher_matches_hash() {
+        use core::hash::Hasher as _;
+
+        let mut hasher = Hasher::from_key(core::array::from_fn(|i| i as u8));
+        assert_eq!(hasher.keys(), (0x_07_06_05_04_03_02_01_00, 0x_0f_0e_0d_0c_0b_0a_09_08));
+
+        // Test vector #15 from the reference implementation, input written in two parts.
+        hasher.write(&[0, 1, 2, 3, 4, 5, 6]);
+        hasher.write(&[7, 8, 9, 10, 11, 12, 13, 14]);
+        let expected = Hash::from_byte_array([0xe5, 0x45, 0xbe, 0x49, 0x61, 0xca, 0x29, 0xa1]);
+        assert_eq!(hasher.to_hash(), expected);
+        assert_eq!(hasher.finish(), expected.to_u64());
+        // `finish` does not reset the hasher.
+        assert_eq!(hasher.finish(), expected.to_u64());
+    }
+
+    #[test]
+    fn build_hasher_uses_keys() {
+        use core::hash::BuildHasher as _;
+
+        let build = BuildHasher::from_key([7; 16]);
+        assert_eq!(build.hash_one("foo"), build.hash_one("foo"));
+        assert_ne!(build.hash_one("foo"), build.hash_one("bar"));
+        assert_eq!(build.build_hasher().keys(), build.keys());
+
+        let other = BuildHasher::with_keys(1, 2);
+        assert_ne!(other.hash_one("foo"), build.hash_one("foo"));
+    }
 }
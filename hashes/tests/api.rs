@@ -13,8 +13,8 @@
 
 // Import using module style e.g., `sha256::Hash`.
 use bitcoin_hashes::{
-    hash160, hash_newtype, hkdf, hmac, ripemd160, sha1, sha256, sha256d, sha256t, sha256t_tag,
-    sha384, sha3_256, sha512, sha512_256, siphash24, Hash, HashEngine,
+    hash160, hash_newtype, hkdf, hmac, pbkdf2, ripemd160, sha1, sha256, sha256d, sha256t,
+    sha256t_tag, sha384, sha3_256, sha512, sha512_256, siphash24, Hash, HashEngine,
 };
 // Import using type alias style e.g., `Sha256`.
 use bitcoin_hashes::{
@@ -101,6 +101,7 @@ struct Engines {
     j: sha512_256::HashEngine,
     k: siphash24::HashEngine,
     l: sha3_256::HashEngine,
+    m: siphash24::Hasher,
 }
 
 impl Engines {
@@ -118,6 +119,7 @@ fn new_sha256() -> Self {
             j: sha512_256::HashEngine::new(),
             k: siphash24::HashEngine::with_keys(0, 0),
             l: sha3_256::HashEngine::new(),
+            m: siphash24::Hasher::with_keys(0, 0),
         }
     }
 }
@@ -167,13 +169,15 @@ struct Keyed<T: Hash> {
 struct Errors {
     b: hkdf::MaxLengthError,
     c: sha256::MidstateError,
+    d: sha256::MidstateLengthError,
+    e: pbkdf2::MaxLengthError,
 }
 
 #[test]
 fn api_can_use_modules_from_crate_root() {
     use bitcoin_hashes::{
-        hash160, hkdf, hmac, muhash, ripemd160, sha1, sha256, sha256d, sha256t, sha384, sha512,
-        sha512_256, siphash24,
+        hash160, hkdf, hmac, muhash, pbkdf2, ripemd160, sha1, sha256, sha256d, sha256t, sha384,
+        sha512, sha512_256, siphash24,
     };
 }
 
@@ -88,3 +88,61 @@ macro_rules! cond_const {
     };
 }
 pub use cond_const;
+
+/// Decodes a hex string into a byte array, reversing the bytes if `reverse` is set.
+///
+/// Both lower and upper case hex characters are accepted. This exists so that hex constants can be
+/// checked at compile time, prefer the `hex` crate everywhere else.
+///
+/// # Panics
+///
+/// If `hex` is not exactly `2 * N` characters long or contains a non-hex character. When called in
+/// const context this is a compile time error.
+pub const fn hex_to_array<const N: usize>(hex: &str, reverse: bool) -> [u8; N] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("invalid hex character"),
+        }
+    }
+
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 2 * N, "hex string has the wrong length");
+
+    let mut array = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        let byte = (nibble(hex[2 * i]) << 4) | nibble(hex[2 * i + 1]);
+        if reverse {
+            array[N - 1 - i] = byte;
+        } else {
+            array[i] = byte;
+        }
+        i += 1;
+    }
+    array
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_to_array_decodes() {
+        const FORWARD: [u8; 3] = hex_to_array("00aBff", false);
+        const BACKWARD: [u8; 3] = hex_to_array("00aBff", true);
+        assert_eq!(FORWARD, [0x00, 0xab, 0xff]);
+        assert_eq!(BACKWARD, [0xff, 0xab, 0x00]);
+        assert_eq!(hex_to_array::<0>("", false), [0u8; 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong length")]
+    fn hex_to_array_wrong_length() { let _ = hex_to_array::<2>("abc", false); }
+
+    #[test]
+    #[should_panic(expected = "invalid hex character")]
+    fn hex_to_array_invalid_character() { let _ = hex_to_array::<2>("abcg", false); }
+}
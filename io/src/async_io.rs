@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Asynchronous I/O support using the [`futures_io`] traits.
+//!
+//! Objects are encoded and decoded using the push-based encoders and decoders from
+//! `consensus_encoding` so they are streamed to and from the asynchronous reader or writer
+//! directly, without going through an intermediate buffer.
+//!
+//! The functions are runtime agnostic. Runtimes which use their own traits (e.g. `tokio`) usually
+//! provide compatibility wrappers implementing the [`futures_io`] traits.
+
+use core::future::poll_fn;
+use core::pin::Pin;
+use core::task::Poll;
+
+use encoding::{Decodable, Decoder, Encodable, Encoder};
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+use crate::{ErrorKind, ReadError, Result};
+
+/// Encodes a `consensus_encoding` object to an asynchronous writer.
+///
+/// The writer is not flushed.
+///
+/// # Errors
+///
+/// If an I/O error occurs while writing to the underlying writer.
+pub async fn encode_to_async_writer<T, W>(object: &T, mut writer: W) -> Result<()>
+where
+    T: Encodable + ?Sized,
+    W: AsyncWrite + Unpin,
+{
+    let mut encoder = object.encoder();
+    loop {
+        write_all(&mut writer, encoder.current_chunk()).await?;
+        if !encoder.advance() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes an object from an asynchronous buffered reader.
+///
+/// This is the asynchronous equivalent of [`decode_from_read`](crate::decode_from_read).
+///
+/// # Errors
+///
+/// Returns [`ReadError::Decode`] if the decoder encounters an error while parsing
+/// the data, or [`ReadError::Io`] if an I/O error occurs while reading.
+pub async fn decode_from_async_read<T, R>(
+    mut reader: R,
+) -> core::result::Result<T, ReadError<<T::Decoder as Decoder>::Error>>
+where
+    T: Decodable,
+    R: AsyncBufRead + Unpin,
+{
+    let mut decoder = T::decoder();
+
+    loop {
+        let need_more = poll_fn(|cx| {
+            let mut buffer = match Pin::new(&mut reader).poll_fill_buf(cx) {
+                Poll::Ready(Ok(buffer)) => buffer,
+                // Auto retry read for non-fatal error.
+                Poll::Ready(Err(error)) if error.kind() == std::io::ErrorKind::Interrupted =>
+                    return Poll::Ready(Ok(true)),
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(ReadError::Io(error.into()))),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            if buffer.is_empty() {
+                // EOF, but still try to finalize the decoder.
+                return Poll::Ready(Ok(false));
+            }
+
+            let original_len = buffer.len();
+            let need_more = decoder.push_bytes(&mut buffer).map_err(ReadError::Decode);
+            let consumed = original_len - buffer.len();
+            Pin::new(&mut reader).consume(consumed);
+            Poll::Ready(need_more)
+        })
+        .await?;
+
+        if !need_more {
+            return decoder.end().map_err(ReadError::Decode);
+        }
+    }
+}
+
+/// Decodes an object from an asynchronous unbuffered reader using a fixed-size buffer.
+///
+/// This is the asynchronous equivalent of
+/// [`decode_from_read_unbuffered`](crate::decode_from_read_unbuffered). The 4KB buffer is part
+/// of the returned future. For most use cases, prefer [`decode_from_async_read`] with a buffered
+/// reader.
+///
+/// # Errors
+///
+/// Returns [`ReadError::Decode`] if the decoder encounters an error while parsing
+/// the data, or [`ReadError::Io`] if an I/O error occurs while reading.
+pub async fn decode_from_async_read_unbuffered<T, R>(
+    mut reader: R,
+) -> core::result::Result<T, ReadError<<T::Decoder as Decoder>::Error>>
+where
+    T: Decodable,
+    R: AsyncRead + Unpin,
+{
+    const BUFFER_SIZE: usize = 4096;
+
+    let mut decoder = T::decoder();
+    let mut buffer = [0u8; BUFFER_SIZE];
+
+    while decoder.read_limit() > 0 {
+        // Only read what we need, up to buffer size.
+        let clamped_buffer = &mut buffer[..decoder.read_limit().min(BUFFER_SIZE)];
+        match poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, clamped_buffer)).await {
+            Ok(0) => {
+                // EOF, but still try to finalize the decoder.
+                return decoder.end().map_err(ReadError::Decode);
+            }
+            Ok(bytes_read) => {
+                if !decoder
+                    .push_bytes(&mut &clamped_buffer[..bytes_read])
+                    .map_err(ReadError::Decode)?
+                {
+                    return decoder.end().map_err(ReadError::Decode);
+                }
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {
+                // Auto retry read for non-fatal error.
+            }
+            Err(e) => return Err(ReadError::Io(e.into())),
+        }
+    }
+
+    decoder.end().map_err(ReadError::Decode)
+}
+
+/// Writes all of `buf` to `writer`, like [`Write::write_all`](crate::Write::write_all).
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, mut buf: &[u8]) -> Result<()> {
+    while !buf.is_empty() {
+        match poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, buf)).await {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(len) => buf = &buf[len..],
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use core::future::Future;
+    use core::task::{Context, RawWaker, RawWakerVTable, Waker};
+
+    use encoding::{ArrayDecoder, ArrayEncoder, UnexpectedEofError};
+
+    use super::*;
+
+    /// Polls `future` to completion, there is no need for a real executor because the test
+    /// readers and writers wake up immediately.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        fn clone(_: *const ()) -> RawWaker { RawWaker::new(core::ptr::null(), &VTABLE) }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+        // SAFETY: The vtable functions don't use the data pointer.
+        let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Reads and writes at most two bytes at a time, returning `Pending` in between.
+    struct Trickle<T> {
+        inner: T,
+        pending: bool,
+    }
+
+    impl<T> Trickle<T> {
+        fn new(inner: T) -> Self { Self { inner, pending: true } }
+
+        fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        }
+    }
+
+    impl AsyncRead for Trickle<&[u8]> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            core::task::ready!(self.poll_pending(cx));
+            let len = buf.len().min(2);
+            Pin::new(&mut self.inner).poll_read(cx, &mut buf[..len])
+        }
+    }
+
+    impl AsyncBufRead for Trickle<&[u8]> {
+        fn poll_fill_buf(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<std::io::Result<&[u8]>> {
+            let this = self.get_mut();
+            core::task::ready!(this.poll_pending(cx));
+            let len = this.inner.len().min(2);
+            Poll::Ready(Ok(&this.inner[..len]))
+        }
+
+        fn consume(mut self: Pin<&mut Self>, amount: usize) { self.inner = &self.inner[amount..]; }
+    }
+
+    impl AsyncWrite for Trickle<Vec<u8>> {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            core::task::ready!(self.poll_pending(cx));
+            let len = buf.len().min(2);
+            Pin::new(&mut self.inner).poll_write(cx, &buf[..len])
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct TestArray([u8; 5]);
+
+    impl Encodable for TestArray {
+        type Encoder<'e>
+            = ArrayEncoder<5>
+        where
+            Self: 'e;
+
+        fn encoder(&self) -> Self::Encoder<'_> { ArrayEncoder::without_length_prefix(self.0) }
+    }
+
+    impl Decodable for TestArray {
+        type Decoder = TestArrayDecoder;
+        fn decoder() -> Self::Decoder { TestArrayDecoder { inner: ArrayDecoder::new() } }
+    }
+
+    struct TestArrayDecoder {
+        inner: ArrayDecoder<5>,
+    }
+
+    impl Decoder for TestArrayDecoder {
+        type Output = TestArray;
+        type Error = UnexpectedEofError;
+
+        fn push_bytes(&mut self, bytes: &mut &[u8]) -> core::result::Result<bool, Self::Error> {
+            self.inner.push_bytes(bytes)
+        }
+
+        fn end(self) -> core::result::Result<Self::Output, Self::Error> {
+            self.inner.end().map(TestArray)
+        }
+
+        fn read_limit(&self) -> usize { self.inner.read_limit() }
+    }
+
+    #[test]
+    fn encode_async() {
+        let mut writer = Trickle::new(Vec::new());
+        block_on(encode_to_async_writer(&TestArray([1, 2, 3, 4, 5]), &mut writer)).unwrap();
+        assert_eq!(writer.inner, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn decode_async() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let mut reader = Trickle::new(&data[..]);
+        let decoded: TestArray = block_on(decode_from_async_read(&mut reader)).unwrap();
+        assert_eq!(decoded, TestArray([1, 2, 3, 4, 5]));
+        // Extra data is not consumed.
+        assert_eq!(reader.inner, [6]);
+
+        let reader = Trickle::new(&data[..4]);
+        let result = block_on(decode_from_async_read::<TestArray, _>(reader));
+        assert!(matches!(result, Err(ReadError::Decode(_))));
+    }
+
+    #[test]
+    fn decode_async_unbuffered() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let mut reader = Trickle::new(&data[..]);
+        let decoded: TestArray = block_on(decode_from_async_read_unbuffered(&mut reader)).unwrap();
+        assert_eq!(decoded, TestArray([1, 2, 3, 4, 5]));
+        // Only what the decoder needs is read.
+        assert_eq!(reader.inner, [6]);
+
+        let reader = Trickle::new(&data[..4]);
+        let result = block_on(decode_from_async_read_unbuffered::<TestArray, _>(reader));
+        assert!(matches!(result, Err(ReadError::Decode(_))));
+    }
+}
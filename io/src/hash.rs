@@ -5,6 +5,7 @@
 //! Support for various hashing related things e.g.
 //!
 //! - Hashing to a writer.
+//! - Hashing the data written to another writer.
 //! - Implement I/O traits for hash engines.
 
 use hashes::hmac::HmacEngine;
@@ -13,7 +14,7 @@
     HashEngine as _,
 };
 
-use crate::BufRead;
+use crate::{BufRead, Write};
 
 macro_rules! impl_write {
     ($ty: ty, $write_fn: expr, $flush_fn: expr $(, $bounded_ty: ident : $bounds: path),*) => {
@@ -157,6 +158,56 @@ pub fn hash_reader<T>(reader: &mut impl BufRead) -> Result<T::Hash, crate::Error
     Ok(engine.finalize())
 }
 
+/// Writer adapter which hashes the bytes written to an underlying writer.
+///
+/// This allows encoding data to a sink while simultaneously computing its hash, e.g. writing a
+/// transaction to a file and computing its txid without encoding it twice.
+#[derive(Clone, Debug, Default)]
+pub struct HashWriter<E, W> {
+    engine: E,
+    writer: W,
+}
+
+impl<E: hashes::HashEngine, W: Write> HashWriter<E, W> {
+    /// Constructs a new `HashWriter` which inputs the bytes written to `writer` into `engine`.
+    #[inline]
+    pub const fn new(engine: E, writer: W) -> Self { Self { engine, writer } }
+
+    /// Returns a reference to the hash engine.
+    #[inline]
+    pub const fn engine(&self) -> &E { &self.engine }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub const fn get_ref(&self) -> &W { &self.writer }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Bytes written directly to the underlying writer are not hashed.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W { &mut self.writer }
+
+    /// Returns the hash engine and the underlying writer.
+    #[inline]
+    pub fn into_parts(self) -> (E, W) { (self.engine, self.writer) }
+
+    /// Returns the hash of the bytes written so far and the underlying writer.
+    #[inline]
+    pub fn finalize(self) -> (E::Hash, W) { (self.engine.finalize(), self.writer) }
+}
+
+impl<E: hashes::HashEngine, W: Write> Write for HashWriter<E, W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> crate::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.engine.input(&buf[..written]);
+        Ok(written)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> crate::Result<()> { self.writer.flush() }
+}
+
 #[cfg(test)]
 #[cfg(feature = "alloc")]
 mod tests {
@@ -166,7 +217,7 @@ mod tests {
     use hashes::hmac;
 
     use super::*;
-    use crate::{Cursor, Write as _};
+    use crate::Cursor;
 
     macro_rules! write_test {
         ($mod:ident, $exp_empty:expr, $exp_256:expr, $exp_64k:expr,) => {
@@ -288,6 +339,27 @@ fn siphash24() {
         assert_eq!(format!("{}", siphash24::Hash::from_engine(engine)), "ce456e4e4ecbc5bf");
     }
 
+    #[test]
+    fn hash_writer() {
+        let mut buf = [0_u8; 8];
+        let mut writer = HashWriter::new(sha256::HashEngine::default(), buf.as_mut_slice());
+        writer.write_all(&[1; 6]).unwrap();
+        // Only the bytes that fit are written and hashed.
+        assert_eq!(writer.write(&[2; 4]).unwrap(), 2);
+
+        let (hash, rest) = writer.finalize();
+        assert!(rest.is_empty());
+        assert_eq!(buf, [1, 1, 1, 1, 1, 1, 2, 2]);
+        assert_eq!(hash, sha256::Hash::hash(&buf));
+
+        let mut writer = crate::CountingWriter::new(vec![]);
+        let mut hash_writer = HashWriter::new(sha256d::HashEngine::default(), &mut writer);
+        hash_writer.write_all(DATA.as_bytes()).unwrap();
+        assert_eq!(hash_writer.finalize().0, sha256d::Hash::hash(DATA.as_bytes()));
+        assert_eq!(writer.count(), DATA.len() as u64);
+        assert_eq!(writer.into_inner(), DATA.as_bytes());
+    }
+
     // Data and expected hashes taken from `bitcoin_hashes/tests/regression.rs`.
     const DATA: &str = "arbitrary data to hash as a regression test";
     const HMAC_KEY: &[u8] = b"some key";
@@ -26,6 +26,8 @@
 #[cfg(feature = "hashes")]
 pub extern crate hashes;
 
+#[cfg(feature = "futures-io")]
+mod async_io;
 #[cfg(feature = "std")]
 mod bridge;
 mod error;
@@ -41,10 +43,14 @@
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 pub use self::error::{Error, ErrorKind};
+#[cfg(feature = "futures-io")]
+pub use self::async_io::{
+    decode_from_async_read, decode_from_async_read_unbuffered, encode_to_async_writer,
+};
 #[cfg(feature = "std")]
 pub use self::bridge::{FromStd, ToStd};
 #[cfg(feature = "hashes")]
-pub use self::hash::hash_reader;
+pub use self::hash::{hash_reader, HashWriter};
 
 /// Result type returned by functions in this crate.
 pub type Result<T> = core::result::Result<T, Error>;
@@ -91,6 +97,15 @@ fn take(self, limit: u64) -> Take<Self>
         Take { reader: self, remaining: limit }
     }
 
+    /// Constructs a new adapter which will read all bytes from this reader and then from `next`.
+    #[inline]
+    fn chain<R: Read>(self, next: R) -> Chain<Self, R>
+    where
+        Self: Sized,
+    {
+        Chain { first: self, second: next, done_first: false }
+    }
+
     /// Attempts to read up to limit bytes from the reader, allocating space in `buf` as needed.
     ///
     /// `limit` is used to prevent a denial of service attack vector since an unbounded reader will
@@ -139,6 +154,32 @@ pub struct Take<R> {
     remaining: u64,
 }
 
+impl<R> Take<R> {
+    /// Returns the number of bytes that can still be read before this adapter returns EOF.
+    ///
+    /// This can be used to check that a length-prefixed structure was read completely.
+    #[inline]
+    pub const fn limit(&self) -> u64 { self.remaining }
+
+    /// Sets the number of bytes that can be read before this adapter returns EOF.
+    #[inline]
+    pub fn set_limit(&mut self, limit: u64) { self.remaining = limit; }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    pub const fn get_ref(&self) -> &R { &self.reader }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Bytes read directly from the underlying reader do not count towards the limit.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R { &mut self.reader }
+
+    /// Returns the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R { self.reader }
+}
+
 impl<R: Read> Take<R> {
     /// Reads all bytes until EOF from the underlying reader into `buf`.
     ///
@@ -205,6 +246,65 @@ fn consume(&mut self, amount: usize) {
     }
 }
 
+/// Reader adapter which reads from two readers in sequence.
+///
+/// Created by calling [`Read::chain`]. Chains can be nested to read from more than two readers.
+#[derive(Debug)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+    done_first: bool,
+}
+
+impl<A, B> Chain<A, B> {
+    /// Returns references to the underlying readers.
+    #[inline]
+    pub const fn get_ref(&self) -> (&A, &B) { (&self.first, &self.second) }
+
+    /// Returns mutable references to the underlying readers.
+    #[inline]
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) { (&mut self.first, &mut self.second) }
+
+    /// Returns the underlying readers.
+    #[inline]
+    pub fn into_inner(self) -> (A, B) { (self.first, self.second) }
+}
+
+impl<A: Read, B: Read> Read for Chain<A, B> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if !self.done_first {
+            match self.first.read(buf)? {
+                0 if !buf.is_empty() => self.done_first = true,
+                read => return Ok(read),
+            }
+        }
+        self.second.read(buf)
+    }
+}
+
+impl<A: BufRead, B: BufRead> BufRead for Chain<A, B> {
+    #[inline]
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if !self.done_first {
+            match self.first.fill_buf()? {
+                [] => self.done_first = true,
+                buf => return Ok(buf),
+            }
+        }
+        self.second.fill_buf()
+    }
+
+    #[inline]
+    fn consume(&mut self, amount: usize) {
+        if self.done_first {
+            self.second.consume(amount);
+        } else {
+            self.first.consume(amount);
+        }
+    }
+}
+
 impl<T: Read + ?Sized> Read for &'_ mut T {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> { (**self).read(buf) }
@@ -430,6 +530,51 @@ fn flush(&mut self) -> Result<()> { Ok(()) }
 #[inline]
 pub fn sink() -> Sink { Sink }
 
+/// Writer adapter which counts the bytes written to an underlying writer.
+///
+/// Use [`sink()`] as the underlying writer to only measure the length of encoded data.
+#[derive(Clone, Debug, Default)]
+pub struct CountingWriter<W> {
+    writer: W,
+    count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    /// Constructs a new `CountingWriter` wrapping `writer`.
+    #[inline]
+    pub const fn new(writer: W) -> Self { Self { writer, count: 0 } }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    pub const fn count(&self) -> u64 { self.count }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub const fn get_ref(&self) -> &W { &self.writer }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Bytes written directly to the underlying writer are not counted.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W { &mut self.writer }
+
+    /// Returns the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W { self.writer }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> { self.writer.flush() }
+}
+
 /// Wraps a `std` I/O type to implement the traits from this crate.
 ///
 /// All methods are passed through converting the errors.
@@ -738,6 +883,75 @@ fn take_and_read_to_end() {
         assert_eq!(data[0..32], v[0..32]);
     }
 
+    #[test]
+    fn counting_writer() {
+        let mut buf = [0_u8; 4];
+        let mut writer = CountingWriter::new(buf.as_mut_slice());
+        writer.write_all(&[1, 2, 3]).unwrap();
+        assert_eq!(writer.count(), 3);
+
+        // Only the bytes that fit are written and counted.
+        assert_eq!(writer.write(&[4, 5]).unwrap(), 1);
+        assert_eq!(writer.count(), 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
+
+        let mut writer = CountingWriter::new(sink());
+        encode_to_writer(&TestData(0), &mut writer).unwrap();
+        assert_eq!(writer.count(), 4);
+    }
+
+    #[test]
+    fn take_limit() {
+        let data = [1_u8, 2, 3, 4];
+        let mut take = Read::take(&data[..], 3);
+
+        let mut buf = [0_u8; 2];
+        take.read_exact(&mut buf).unwrap();
+        assert_eq!(take.limit(), 1);
+        assert_eq!(take.fill_buf().unwrap(), [3]);
+
+        take.set_limit(2);
+        assert_eq!(take.fill_buf().unwrap(), [3, 4]);
+        take.consume(2);
+        assert_eq!(take.limit(), 0);
+        assert!(take.into_inner().is_empty());
+    }
+
+    #[test]
+    fn chain_read() {
+        let mut chain = Read::chain(&[1_u8, 2][..], &[3_u8][..]).chain(&[][..]).chain(&[4_u8][..]);
+
+        let mut buf = [0_u8; 4];
+        chain.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(chain.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn chain_fill_and_consume() {
+        let mut chain = Read::chain(&[1_u8, 2][..], &[3_u8, 4][..]);
+
+        assert_eq!(chain.fill_buf().unwrap(), [1, 2]);
+        chain.consume(1);
+        assert_eq!(chain.fill_buf().unwrap(), [2]);
+        chain.consume(1);
+        assert_eq!(chain.fill_buf().unwrap(), [3, 4]);
+        chain.consume(2);
+        assert!(chain.fill_buf().unwrap().is_empty());
+    }
+
+    #[test]
+    fn take_chain_decode() {
+        // A sub-message split over two buffers, followed by unrelated bytes.
+        let chain = Read::chain(&[1_u8, 2, 3][..], &[4_u8, 5, 6][..]);
+        let mut take = Read::take(chain, 5);
+
+        let decoded: TestArray = decode_from_read(&mut take).unwrap();
+        assert_eq!(decoded.0, [1, 2, 3, 4]);
+        assert_eq!(take.limit(), 1);
+        assert_eq!(take.fill_buf().unwrap(), [5]);
+    }
+
     #[test]
     fn cursor_fill_buf_past_end() {
         let data = [1, 2, 3];
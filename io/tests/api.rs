@@ -11,7 +11,11 @@
 use core::convert::Infallible;
 
 // These imports test "typical" usage by user code.
-use bitcoin_io::{self as io, BufRead, Cursor, ErrorKind, Read, Sink, Take, Write};
+use bitcoin_io::{
+    self as io, BufRead, Chain, CountingWriter, Cursor, ErrorKind, Read, Sink, Take, Write,
+};
+#[cfg(feature = "hashes")]
+use bitcoin_io::{hashes::sha256, HashWriter};
 #[cfg(feature = "std")]
 use bitcoin_io::{FromStd, ToStd};
 
@@ -29,7 +33,7 @@ impl Enums {
     fn new() -> Self { Self { a: ERROR_KIND } }
 }
 
-/// A struct that includes all public non-error structs except `Take`.
+/// A struct that includes all public non-error structs except `Take` and `Chain`.
 #[derive(Debug)] // All public types implement Debug (C-DEBUG).
 struct Structs {
     #[cfg(feature = "std")]
@@ -38,6 +42,9 @@ struct Structs {
     b: ToStd<Dummy>,
     c: Cursor<Dummy>,
     d: Sink,
+    e: CountingWriter<Dummy>,
+    #[cfg(feature = "hashes")]
+    f: HashWriter<sha256::HashEngine, Dummy>,
 }
 
 impl Structs {
@@ -49,13 +56,17 @@ fn new() -> Self {
             b: ToStd::new(DUMMY),
             c: Cursor::new(DUMMY),
             d: Sink,
+            e: CountingWriter::new(DUMMY),
+            #[cfg(feature = "hashes")]
+            f: HashWriter::new(sha256::HashEngine::default(), DUMMY),
         }
     }
 }
 
-#[derive(Debug)] // `Take` implements Debug (C-DEBUG).
+#[derive(Debug)] // `Take` and `Chain` implement Debug (C-DEBUG).
 struct Taker<Dummy> {
     a: Take<Dummy>,
+    b: Chain<Dummy, Dummy>,
 }
 
 /// An arbitrary `Dummy` instance.
@@ -127,6 +138,13 @@ fn api_all_non_error_types_have_non_empty_debug() {
     assert!(!debug.is_empty());
     let debug = format!("{:?}", t.b.d);
     assert!(!debug.is_empty());
+    let debug = format!("{:?}", t.b.e);
+    assert!(!debug.is_empty());
+    #[cfg(feature = "hashes")]
+    {
+        let debug = format!("{:?}", t.b.f);
+        assert!(!debug.is_empty());
+    }
 }
 
 #[test]
@@ -0,0 +1,545 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! BIP-0157 Client Side Block Filtering.
+//!
+//! A [`FilterHeaderChain`] follows the filter headers of the blocks of the best chain. It checks
+//! the `cfcheckpt` and `cfheaders` responses of peers against each other, the filters received in
+//! `cfilter` messages against the filter headers, and keeps track of the blocks whose filters are
+//! still missing.
+//!
+//! Light clients usually first ask several peers for the checkpoints of the chain, the filter
+//! headers every 1,000 blocks. Peers disagreeing on a checkpoint report different filters for a
+//! block, so at least one of them is lying. The filter headers are then downloaded in batches of
+//! up to 2,000 and checked against the checkpoints.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt;
+use core::ops::Range;
+
+use bitcoin::BlockHash;
+use units::BlockHeight;
+
+use crate::message_filter::{
+    CFCheckpt, CFHeaders, CFilter, FilterHeader, GetCFCheckpt, GetCFHeaders, GetCFilters,
+};
+
+/// The type of the basic filter defined in BIP-0158.
+pub const BASIC_FILTER_TYPE: u8 = 0;
+
+/// The interval between the filter headers of a `cfcheckpt` message.
+pub const CHECKPOINT_INTERVAL: u32 = 1_000;
+
+/// The maximum number of filter hashes in a `cfheaders` message.
+pub const MAX_CFHEADERS: u32 = 2_000;
+
+/// The maximum number of filters requested by a `getcfilters` message.
+pub const MAX_CFILTERS: u32 = 1_000;
+
+/// The previous filter header of the genesis block.
+const GENESIS_PREVIOUS_HEADER: FilterHeader = FilterHeader::from_byte_array([0; 32]);
+
+/// The filter headers of the blocks of the best chain.
+///
+/// Blocks are added by the caller as its header chain grows, filter headers are then accepted from
+/// `cfheaders` messages in order starting with the genesis block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterHeaderChain {
+    filter_type: u8,
+    /// The hashes of the blocks of the best chain, by height.
+    block_hashes: Vec<BlockHash>,
+    heights: BTreeMap<BlockHash, u32>,
+    /// The checked filter headers, by height, at most one for each block.
+    filter_headers: Vec<FilterHeader>,
+    /// The filter headers at the heights 1,000, 2,000 and so on.
+    checkpoints: Vec<FilterHeader>,
+    /// Whether the filter of the block at each height with a filter header has been received.
+    filters: Vec<bool>,
+}
+
+impl FilterHeaderChain {
+    /// Constructs a new empty chain of filter headers of the filters with `filter_type`.
+    pub fn new(filter_type: u8) -> Self {
+        Self {
+            filter_type,
+            block_hashes: Vec::new(),
+            heights: BTreeMap::new(),
+            filter_headers: Vec::new(),
+            checkpoints: Vec::new(),
+            filters: Vec::new(),
+        }
+    }
+
+    /// Returns the type of the filters.
+    pub fn filter_type(&self) -> u8 { self.filter_type }
+
+    /// Returns the number of blocks in the chain.
+    pub fn block_count(&self) -> u32 { to_u32(self.block_hashes.len()) }
+
+    /// Returns the height of the block with `block_hash`, if it is in the chain.
+    pub fn block_height(&self, block_hash: &BlockHash) -> Option<BlockHeight> {
+        self.heights.get(block_hash).map(|height| BlockHeight::from_u32(*height))
+    }
+
+    /// Returns the filter header of the block at `height`, if it has been received.
+    pub fn filter_header(&self, height: BlockHeight) -> Option<FilterHeader> {
+        self.filter_headers.get(to_usize(height.to_u32())).copied()
+    }
+
+    /// Returns the number of blocks with a checked filter header, starting at the genesis block.
+    pub fn filter_header_count(&self) -> u32 { to_u32(self.filter_headers.len()) }
+
+    /// Returns the checkpoints, the filter headers at the heights 1,000, 2,000 and so on.
+    pub fn checkpoints(&self) -> &[FilterHeader] { &self.checkpoints }
+
+    /// Appends the block with `block_hash` to the chain.
+    pub fn push_block(&mut self, block_hash: BlockHash) {
+        self.heights.insert(block_hash, self.block_count());
+        self.block_hashes.push(block_hash);
+    }
+
+    /// Removes the blocks above `height`, for example in a reorganization.
+    ///
+    /// The filter headers, checkpoints and filters of the removed blocks are removed as well.
+    pub fn truncate(&mut self, height: BlockHeight) {
+        let len = to_usize(height.to_u32()).saturating_add(1);
+        for block_hash in self.block_hashes.iter().skip(len) {
+            self.heights.remove(block_hash);
+        }
+        self.block_hashes.truncate(len);
+        self.filter_headers.truncate(len);
+        self.filters.truncate(len);
+        self.checkpoints.truncate(to_usize(height.to_u32() / CHECKPOINT_INTERVAL));
+    }
+
+    /// Returns the request for the checkpoints up to the tip, if there are any blocks.
+    pub fn checkpoint_request(&self) -> Option<GetCFCheckpt> {
+        let stop_hash = *self.block_hashes.last()?;
+        Some(GetCFCheckpt { filter_type: self.filter_type, stop_hash })
+    }
+
+    /// Accepts the checkpoints of a `cfcheckpt` message.
+    ///
+    /// The checkpoints are checked against the checkpoints and filter headers already accepted,
+    /// for example from another peer. The message may have fewer checkpoints than already
+    /// accepted, if its stop hash is lower, but the accepted checkpoints are never shortened.
+    ///
+    /// # Errors
+    ///
+    /// If the message is not an answer for this chain or conflicts with what was already accepted.
+    pub fn accept_checkpoints(&mut self, message: &CFCheckpt) -> Result<(), FilterChainError> {
+        self.check_filter_type(message.filter_type)?;
+        let stop =
+            self.block_height(&message.stop_hash).ok_or(FilterChainError::UnknownStopHash)?;
+        let expected = stop.to_u32() / CHECKPOINT_INTERVAL;
+        if to_u32(message.filter_headers.len()) != expected {
+            return Err(FilterChainError::CheckpointCount {
+                expected,
+                got: message.filter_headers.len(),
+            });
+        }
+        for (i, checkpoint) in message.filter_headers.iter().enumerate() {
+            let height = checkpoint_height(i);
+            let known = self.checkpoints.get(i).copied().or_else(|| self.filter_header(height));
+            if known.is_some_and(|known| known != *checkpoint) {
+                return Err(FilterChainError::Conflict(height));
+            }
+        }
+        if message.filter_headers.len() > self.checkpoints.len() {
+            self.checkpoints.clone_from(&message.filter_headers);
+        }
+        Ok(())
+    }
+
+    /// Returns the request for the next batch of filter headers, if any headers are missing.
+    pub fn next_headers_request(&self) -> Option<GetCFHeaders> {
+        let start = self.filter_header_count();
+        let stop = self.block_count().checked_sub(1)?.min(start.saturating_add(MAX_CFHEADERS - 1));
+        let stop_hash = *self.block_hashes.get(to_usize(stop)).filter(|_| start <= stop)?;
+        Some(GetCFHeaders {
+            filter_type: self.filter_type,
+            start_height: BlockHeight::from_u32(start),
+            stop_hash,
+        })
+    }
+
+    /// Accepts the filter headers of a `cfheaders` message continuing the chain.
+    ///
+    /// The message must answer the [`next_headers_request`], continuing from the last accepted
+    /// filter header. Returns the heights of the accepted filter headers.
+    ///
+    /// # Errors
+    ///
+    /// If the message does not continue the chain or conflicts with an accepted checkpoint.
+    ///
+    /// [`next_headers_request`]: Self::next_headers_request
+    pub fn accept_headers(
+        &mut self,
+        message: &CFHeaders,
+    ) -> Result<Range<BlockHeight>, FilterChainError> {
+        self.check_filter_type(message.filter_type)?;
+        if to_u32(message.filter_hashes.len()) > MAX_CFHEADERS {
+            return Err(FilterChainError::TooManyHeaders(message.filter_hashes.len()));
+        }
+        let start = self.filter_header_count();
+        let end = start.checked_add(to_u32(message.filter_hashes.len()));
+        let stop_hash = end.and_then(|end| self.block_hashes.get(to_usize(end.checked_sub(1)?)));
+        if stop_hash != Some(&message.stop_hash) {
+            return Err(FilterChainError::UnknownStopHash);
+        }
+        let previous = self.filter_headers.last().copied().unwrap_or(GENESIS_PREVIOUS_HEADER);
+        if message.previous_filter_header != previous {
+            return Err(FilterChainError::PreviousHeaderMismatch);
+        }
+
+        let mut headers = Vec::with_capacity(message.filter_hashes.len());
+        let mut previous = previous;
+        for (height, filter_hash) in (start..).zip(&message.filter_hashes) {
+            let header = filter_hash.filter_header(previous);
+            let height = BlockHeight::from_u32(height);
+            if self.checkpoint(height).is_some_and(|checkpoint| checkpoint != header) {
+                return Err(FilterChainError::Conflict(height));
+            }
+            headers.push(header);
+            previous = header;
+        }
+        self.filter_headers.extend(headers);
+        self.filters.resize(self.filter_headers.len(), false);
+        Ok(BlockHeight::from_u32(start)..BlockHeight::from_u32(self.filter_header_count()))
+    }
+
+    /// Checks the filter of a `cfilter` message against its filter header.
+    ///
+    /// Returns the height of the block the filter is for, the filter is then no longer missing.
+    ///
+    /// # Errors
+    ///
+    /// If the block is not in the chain, its filter header has not been accepted yet or the filter
+    /// does not match it.
+    pub fn accept_filter(&mut self, message: &CFilter) -> Result<BlockHeight, FilterChainError> {
+        self.check_filter_type(message.filter_type)?;
+        let height =
+            self.block_height(&message.block_hash).ok_or(FilterChainError::UnknownBlock)?;
+        let index = to_usize(height.to_u32());
+        let header = self.filter_header(height).ok_or(FilterChainError::MissingHeader(height))?;
+        let previous = match index.checked_sub(1) {
+            Some(previous) => self.filter_headers[previous],
+            None => GENESIS_PREVIOUS_HEADER,
+        };
+        if message.filter_hash().filter_header(previous) != header {
+            return Err(FilterChainError::FilterMismatch(height));
+        }
+        self.filters[index] = true;
+        Ok(height)
+    }
+
+    /// Returns the ranges of heights of the blocks with an accepted filter header but without a
+    /// filter, in ascending order.
+    pub fn missing_filters(&self) -> Vec<Range<BlockHeight>> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+        for (height, received) in (0..).zip(self.filters.iter().chain([&true])) {
+            match (start, *received) {
+                (None, false) => start = Some(height),
+                (Some(first), true) => {
+                    ranges.push(BlockHeight::from_u32(first)..BlockHeight::from_u32(height));
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+        ranges
+    }
+
+    /// Returns the request for the first missing filters, if any.
+    pub fn next_filters_request(&self) -> Option<GetCFilters> {
+        let missing = self.missing_filters().into_iter().next()?;
+        let start = missing.start.to_u32();
+        let stop = (missing.end.to_u32() - 1).min(start + MAX_CFILTERS - 1);
+        Some(GetCFilters {
+            filter_type: self.filter_type,
+            start_height: missing.start,
+            stop_hash: self.block_hashes[to_usize(stop)],
+        })
+    }
+
+    fn check_filter_type(&self, filter_type: u8) -> Result<(), FilterChainError> {
+        if filter_type == self.filter_type {
+            Ok(())
+        } else {
+            Err(FilterChainError::FilterType(filter_type))
+        }
+    }
+
+    /// Returns the checkpoint at `height`, if there is one.
+    fn checkpoint(&self, height: BlockHeight) -> Option<FilterHeader> {
+        let height = height.to_u32();
+        if height == 0 || height % CHECKPOINT_INTERVAL != 0 {
+            return None;
+        }
+        self.checkpoints.get(to_usize(height / CHECKPOINT_INTERVAL - 1)).copied()
+    }
+}
+
+impl Default for FilterHeaderChain {
+    fn default() -> Self { Self::new(BASIC_FILTER_TYPE) }
+}
+
+/// Returns the height of the checkpoint at `index`.
+fn checkpoint_height(index: usize) -> BlockHeight {
+    BlockHeight::from_u32((to_u32(index) + 1) * CHECKPOINT_INTERVAL)
+}
+
+fn to_u32(n: usize) -> u32 { u32::try_from(n).expect("block heights fit in a u32") }
+
+fn to_usize(n: u32) -> usize { usize::try_from(n).expect("usize is at least 32 bits") }
+
+/// Error accepting a message into a [`FilterHeaderChain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FilterChainError {
+    /// The message is for another type of filter.
+    FilterType(u8),
+    /// The stop hash of the message is not the block the message should end at.
+    UnknownStopHash,
+    /// The block of a filter is not in the chain.
+    UnknownBlock,
+    /// The `cfcheckpt` message has the wrong number of checkpoints for its stop hash.
+    CheckpointCount {
+        /// The number of checkpoints up to the stop hash.
+        expected: u32,
+        /// The number of checkpoints in the message.
+        got: usize,
+    },
+    /// The `cfheaders` message has more than [`MAX_CFHEADERS`] filter hashes.
+    TooManyHeaders(usize),
+    /// The previous filter header of a `cfheaders` message is not the last accepted one.
+    PreviousHeaderMismatch,
+    /// The filter header at the height conflicts with an accepted checkpoint or filter header.
+    ///
+    /// Either the peer sending the message or the peer the accepted one came from is lying.
+    Conflict(BlockHeight),
+    /// The filter header of the block at the height has not been accepted yet.
+    MissingHeader(BlockHeight),
+    /// The filter of the block at the height does not match its filter header.
+    FilterMismatch(BlockHeight),
+}
+
+impl From<Infallible> for FilterChainError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for FilterChainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::FilterType(filter_type) => write!(f, "unexpected filter type {}", filter_type),
+            Self::UnknownStopHash => write!(f, "unexpected stop hash"),
+            Self::UnknownBlock => write!(f, "the block of the filter is not in the chain"),
+            Self::CheckpointCount { expected, got } =>
+                write!(f, "expected {} checkpoints but got {}", expected, got),
+            Self::TooManyHeaders(len) =>
+                write!(f, "{} filter hashes exceed the maximum of {}", len, MAX_CFHEADERS),
+            Self::PreviousHeaderMismatch =>
+                write!(f, "the previous filter header is not the last accepted one"),
+            Self::Conflict(height) => write!(f, "conflicting filter header at height {}", height),
+            Self::MissingHeader(height) => write!(f, "no filter header at height {}", height),
+            Self::FilterMismatch(height) =>
+                write!(f, "the filter at height {} does not match its filter header", height),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FilterChainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use hashes::sha256d;
+
+    use super::*;
+    use crate::message_filter::FilterHash;
+
+    fn block_hash(height: u32) -> BlockHash {
+        let mut bytes = [0; 32];
+        bytes[..4].copy_from_slice(&height.to_le_bytes());
+        BlockHash::from_byte_array(bytes)
+    }
+
+    fn filter(height: u32) -> Vec<u8> { height.to_le_bytes().to_vec() }
+
+    /// The filter headers an honest peer reports for blocks up to `tip`.
+    fn filter_headers(tip: u32) -> Vec<FilterHeader> {
+        let mut previous = GENESIS_PREVIOUS_HEADER;
+        (0..=tip)
+            .map(|height| {
+                previous = FilterHash(sha256d::Hash::hash(&filter(height))).filter_header(previous);
+                previous
+            })
+            .collect()
+    }
+
+    fn cfheaders(request: &GetCFHeaders, previous: FilterHeader) -> CFHeaders {
+        let start = request.start_height.to_u32();
+        let stop = u32::from_le_bytes(request.stop_hash.to_byte_array()[..4].try_into().unwrap());
+        let filter_hashes =
+            (start..=stop).map(|height| FilterHash(sha256d::Hash::hash(&filter(height)))).collect();
+        CFHeaders {
+            filter_type: BASIC_FILTER_TYPE,
+            stop_hash: request.stop_hash,
+            previous_filter_header: previous,
+            filter_hashes,
+        }
+    }
+
+    fn chain(tip: u32) -> FilterHeaderChain {
+        let mut chain = FilterHeaderChain::default();
+        for height in 0..=tip {
+            chain.push_block(block_hash(height));
+        }
+        chain
+    }
+
+    #[test]
+    fn sync_filter_headers() {
+        let tip = 2_500;
+        let honest = filter_headers(tip);
+        let mut chain = chain(tip);
+        assert_eq!(chain.block_height(&block_hash(7)), Some(BlockHeight::from_u32(7)));
+
+        let request = chain.checkpoint_request().unwrap();
+        assert_eq!(request.stop_hash, block_hash(tip));
+        let checkpoints = CFCheckpt {
+            filter_type: BASIC_FILTER_TYPE,
+            stop_hash: request.stop_hash,
+            filter_headers: vec![honest[1_000], honest[2_000]],
+        };
+        chain.accept_checkpoints(&checkpoints).unwrap();
+
+        // A peer disagreeing on a checkpoint conflicts.
+        let lying = CFCheckpt { filter_headers: vec![honest[1_000], honest[3]], ..checkpoints };
+        assert_eq!(
+            chain.accept_checkpoints(&lying),
+            Err(FilterChainError::Conflict(BlockHeight::from_u32(2_000)))
+        );
+
+        while let Some(request) = chain.next_headers_request() {
+            let previous = chain.filter_headers.last().copied().unwrap_or(GENESIS_PREVIOUS_HEADER);
+            let message = cfheaders(&request, previous);
+            let range = chain.accept_headers(&message).unwrap();
+            assert_eq!(range.start, request.start_height);
+        }
+        assert_eq!(chain.filter_header_count(), tip + 1);
+        assert_eq!(chain.filter_header(BlockHeight::from_u32(tip)), Some(honest[2_500]));
+
+        // Headers not matching the checkpoints conflict.
+        let mut chain2 = self::chain(tip);
+        chain2
+            .accept_checkpoints(&CFCheckpt {
+                filter_headers: vec![honest[1]],
+                stop_hash: block_hash(1_999),
+                ..lying
+            })
+            .unwrap();
+        let message = cfheaders(&chain2.next_headers_request().unwrap(), GENESIS_PREVIOUS_HEADER);
+        assert_eq!(
+            chain2.accept_headers(&message),
+            Err(FilterChainError::Conflict(BlockHeight::from_u32(1_000)))
+        );
+        assert_eq!(chain2.filter_header_count(), 0);
+    }
+
+    #[test]
+    fn headers_must_continue_the_chain() {
+        let mut chain = chain(10);
+        let request = chain.next_headers_request().unwrap();
+        assert_eq!(request.start_height, BlockHeight::ZERO);
+        assert_eq!(request.stop_hash, block_hash(10));
+
+        let mut message = cfheaders(&request, GENESIS_PREVIOUS_HEADER);
+        message.previous_filter_header = filter_headers(0)[0];
+        assert_eq!(chain.accept_headers(&message), Err(FilterChainError::PreviousHeaderMismatch));
+        message.previous_filter_header = GENESIS_PREVIOUS_HEADER;
+        message.stop_hash = block_hash(9);
+        assert_eq!(chain.accept_headers(&message), Err(FilterChainError::UnknownStopHash));
+        message.stop_hash = block_hash(10);
+        message.filter_type = 1;
+        assert_eq!(chain.accept_headers(&message), Err(FilterChainError::FilterType(1)));
+        message.filter_type = BASIC_FILTER_TYPE;
+        assert_eq!(
+            chain.accept_headers(&message),
+            Ok(BlockHeight::ZERO..BlockHeight::from_u32(11))
+        );
+        assert_eq!(chain.next_headers_request(), None);
+
+        chain.truncate(BlockHeight::from_u32(4));
+        assert_eq!(chain.block_count(), 5);
+        assert_eq!(chain.filter_header_count(), 5);
+        assert_eq!(chain.block_height(&block_hash(5)), None);
+    }
+
+    #[test]
+    fn truncate_across_checkpoint() {
+        let honest = filter_headers(2_500);
+        let mut chain = chain(2_500);
+        let checkpoints = CFCheckpt {
+            filter_type: BASIC_FILTER_TYPE,
+            stop_hash: block_hash(2_500),
+            filter_headers: vec![honest[1_000], honest[2_000]],
+        };
+        chain.accept_checkpoints(&checkpoints).unwrap();
+
+        chain.truncate(BlockHeight::from_u32(2_000));
+        assert_eq!(chain.checkpoints(), [honest[1_000], honest[2_000]]);
+        chain.truncate(BlockHeight::from_u32(1_999));
+        assert_eq!(chain.checkpoints(), [honest[1_000]]);
+        chain.truncate(BlockHeight::from_u32(999));
+        assert!(chain.checkpoints().is_empty());
+
+        // The checkpoint of a block on the new chain at a removed height is accepted.
+        let replacement = BlockHash::from_byte_array([0xff; 32]);
+        chain.push_block(replacement);
+        let checkpoints = CFCheckpt {
+            filter_type: BASIC_FILTER_TYPE,
+            stop_hash: replacement,
+            filter_headers: vec![honest[3]],
+        };
+        assert_eq!(chain.accept_checkpoints(&checkpoints), Ok(()));
+    }
+
+    #[test]
+    fn missing_filters() {
+        let mut chain = chain(2_000);
+        assert!(chain.missing_filters().is_empty());
+        assert_eq!(chain.next_filters_request(), None);
+        while let Some(request) = chain.next_headers_request() {
+            let previous = chain.filter_headers.last().copied().unwrap_or(GENESIS_PREVIOUS_HEADER);
+            chain.accept_headers(&cfheaders(&request, previous)).unwrap();
+        }
+        let height = BlockHeight::from_u32;
+        assert_eq!(chain.missing_filters(), [height(0)..height(2_001)]);
+        let request = chain.next_filters_request().unwrap();
+        assert_eq!(request.start_height, height(0));
+        assert_eq!(request.stop_hash, block_hash(999));
+
+        let cfilter = |height: u32| CFilter {
+            filter_type: BASIC_FILTER_TYPE,
+            block_hash: block_hash(height),
+            filter: filter(height),
+        };
+        assert_eq!(chain.accept_filter(&cfilter(0)), Ok(height(0)));
+        assert_eq!(chain.accept_filter(&cfilter(5)), Ok(height(5)));
+        assert_eq!(chain.accept_filter(&cfilter(2_000)), Ok(height(2_000)));
+        assert_eq!(chain.missing_filters(), [height(1)..height(5), height(6)..height(2_000)]);
+
+        let mut wrong = cfilter(7);
+        wrong.filter.push(0);
+        assert_eq!(chain.accept_filter(&wrong), Err(FilterChainError::FilterMismatch(height(7))));
+        wrong.block_hash = block_hash(2_001);
+        assert_eq!(chain.accept_filter(&wrong), Err(FilterChainError::UnknownBlock));
+    }
+}
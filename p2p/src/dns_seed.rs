@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Peer discovery using DNS seeds.
+//!
+//! DNS seeds answer queries for their host name with the addresses of reachable nodes. Most seeds
+//! also answer queries for `x<flags>.<seed>`, with `<flags>` the service flags in lowercase hex,
+//! with only nodes advertising those services.
+//!
+//! Lookups use the resolver of the operating system and block.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bitcoin_p2p_messages::dns_seed;
+//! use bitcoin_p2p_messages::ServiceFlags;
+//! use network::Network;
+//!
+//! let services = ServiceFlags::NETWORK | ServiceFlags::WITNESS;
+//! for peer in dns_seed::query(Network::Bitcoin, services) {
+//!     println!("{:?}:{} {}", peer.addr, peer.port, peer.services);
+//! }
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::io;
+use std::net::ToSocketAddrs;
+
+use network::{Network, TestnetVersion};
+
+use crate::address::AddrV2;
+use crate::{NetworkExt as _, ServiceFlags};
+
+/// An address returned by a DNS seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedAddress {
+    /// The address of the node.
+    pub addr: AddrV2,
+    /// The default port of the network, seeds only return nodes listening on it.
+    pub port: u16,
+    /// The services queried for, the node is expected but not guaranteed to support them.
+    pub services: ServiceFlags,
+}
+
+/// Returns the DNS seeds of `network`, the same seeds used by Bitcoin Core.
+pub fn seeds(network: Network) -> &'static [&'static str] {
+    match network {
+        Network::Bitcoin => &[
+            "seed.bitcoin.sipa.be",
+            "dnsseed.bluematt.me",
+            "seed.bitcoin.jonasschnelli.ch",
+            "seed.btc.petertodd.net",
+            "seed.bitcoin.sprovoost.nl",
+            "dnsseed.emzy.de",
+            "seed.bitcoin.wiz.biz",
+            "seed.mainnet.achownodes.xyz",
+        ],
+        Network::Testnet(TestnetVersion::V3) => &[
+            "testnet-seed.bitcoin.jonasschnelli.ch",
+            "seed.tbtc.petertodd.net",
+            "seed.testnet.bitcoin.sprovoost.nl",
+            "testnet-seed.bluematt.me",
+            "seed.testnet.achownodes.xyz",
+        ],
+        Network::Testnet(TestnetVersion::V4) =>
+            &["seed.testnet4.bitcoin.sprovoost.nl", "seed.testnet4.wiz.biz"],
+        Network::Signet => &["seed.signet.bitcoin.sprovoost.nl", "seed.signet.achownodes.xyz"],
+        Network::Testnet(_) | Network::Regtest => &[],
+    }
+}
+
+/// Returns the host name to query `seed` for nodes advertising `services`.
+///
+/// With no services this is the seed itself.
+pub fn seed_host(seed: &str, services: ServiceFlags) -> String {
+    if services == ServiceFlags::NONE {
+        String::from(seed)
+    } else {
+        format!("x{:x}.{}", services, seed)
+    }
+}
+
+/// Queries a single DNS seed of `network` for nodes advertising `services`.
+///
+/// # Errors
+///
+/// If the lookup fails.
+pub fn query_seed(
+    seed: &str,
+    network: Network,
+    services: ServiceFlags,
+) -> Result<Vec<SeedAddress>, io::Error> {
+    resolve(&seed_host(seed, services), network.default_p2p_port(), services)
+}
+
+/// Queries all DNS seeds of `network` for nodes advertising `services`.
+///
+/// Seeds that fail to resolve are skipped, an address returned by several seeds appears once.
+pub fn query(network: Network, services: ServiceFlags) -> Vec<SeedAddress> {
+    let mut addresses = Vec::new();
+    for seed in seeds(network) {
+        for address in query_seed(seed, network, services).unwrap_or_default() {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+    addresses
+}
+
+fn resolve(host: &str, port: u16, services: ServiceFlags) -> Result<Vec<SeedAddress>, io::Error> {
+    let addresses = (host, port)
+        .to_socket_addrs()?
+        .map(|socket| SeedAddress { addr: AddrV2::from(socket.ip()), port, services })
+        .collect();
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_hosts() {
+        assert_eq!(seeds(Network::Bitcoin).len(), 8);
+        assert!(seeds(Network::Regtest).is_empty());
+
+        let services = ServiceFlags::NETWORK | ServiceFlags::WITNESS;
+        assert_eq!(seed_host("seed.bitcoin.sipa.be", services), "x9.seed.bitcoin.sipa.be");
+        assert_eq!(
+            seed_host("seed.bitcoin.sipa.be", services | ServiceFlags::NETWORK_LIMITED),
+            "x409.seed.bitcoin.sipa.be"
+        );
+        assert_eq!(seed_host("seed.bitcoin.sipa.be", ServiceFlags::NONE), "seed.bitcoin.sipa.be");
+    }
+
+    #[test]
+    fn resolve_localhost() {
+        let services = ServiceFlags::NETWORK;
+        let addresses = resolve("127.0.0.1", 8333, services).unwrap();
+        assert_eq!(
+            addresses,
+            [SeedAddress { addr: AddrV2::Ipv4([127, 0, 0, 1].into()), port: 8333, services }]
+        );
+        assert!(query(Network::Regtest, services).is_empty());
+    }
+}
@@ -10,10 +10,14 @@
 
 mod consensus;
 mod network_ext;
+mod ping;
 
 #[cfg(feature = "std")]
 pub mod address;
 pub mod bip152;
+pub mod bip157;
+#[cfg(feature = "dns-seeds")]
+pub mod dns_seed;
 pub mod merkle_tree;
 #[cfg(feature = "std")]
 pub mod message;
@@ -45,7 +49,7 @@
 
 #[rustfmt::skip]
 #[doc(inline)]
-pub use self::network_ext::NetworkExt;
+pub use self::{network_ext::NetworkExt, ping::{PingState, PongOutcome}};
 
 #[cfg(feature = "std")]
 #[rustfmt::skip]
@@ -220,7 +224,21 @@ impl ServiceFlags {
     /// See BIP-0324 for details on how this is implemented.
     pub const P2P_V2: Self = Self(1 << 11);
 
-    // NOTE: When adding new flags, remember to update the Display impl accordingly.
+    /// All the flags above.
+    pub const KNOWN: Self = Self(
+        Self::NETWORK.0
+            | Self::GETUTXO.0
+            | Self::BLOOM.0
+            | Self::WITNESS.0
+            | Self::COMPACT_FILTERS.0
+            | Self::NETWORK_LIMITED.0
+            | Self::P2P_V2.0,
+    );
+
+    // NOTE: When adding new flags, remember to update `KNOWN` and the Display impl accordingly.
+
+    /// The number of most recent blocks a `NETWORK_LIMITED` node serves at least, see BIP-0159.
+    pub const NETWORK_LIMITED_MIN_BLOCKS: u32 = 288;
 
     /// Add [`ServiceFlags`] together.
     ///
@@ -245,6 +263,31 @@ pub fn has(self, flags: Self) -> bool { (self.0 | flags.0) == self.0 }
 
     /// Gets the integer representation of this [`ServiceFlags`].
     pub fn to_u64(self) -> u64 { self.0 }
+
+    /// Returns the flags not assigned to a known service.
+    #[must_use]
+    pub fn unknown(self) -> Self { Self(self.0 & !Self::KNOWN.0) }
+
+    /// Checks whether the node serves blocks, either the complete chain or only recent blocks.
+    pub fn serves_blocks(self) -> bool {
+        self.has(Self::NETWORK) || self.has(Self::NETWORK_LIMITED)
+    }
+
+    /// Checks whether the node is a pruned peer, it serves recent blocks but not the complete
+    /// chain.
+    ///
+    /// Use [`Self::serves_block_at_depth`] to check whether such a peer can serve a given block.
+    pub fn supports_pruned_peers(self) -> bool {
+        !self.has(Self::NETWORK) && self.has(Self::NETWORK_LIMITED)
+    }
+
+    /// Checks whether the node serves the block `depth` blocks below the tip of its chain.
+    ///
+    /// The tip itself is at depth zero.
+    pub fn serves_block_at_depth(self, depth: u32) -> bool {
+        self.has(Self::NETWORK)
+            || (self.has(Self::NETWORK_LIMITED) && depth < Self::NETWORK_LIMITED_MIN_BLOCKS)
+    }
 }
 
 impl fmt::LowerHex for ServiceFlags {
@@ -656,6 +699,32 @@ fn serialize_deserialize() {
         );
     }
 
+    #[test]
+    fn service_flags_helpers() {
+        let unknown = ServiceFlags::from(1 << 24);
+        assert_eq!(ServiceFlags::KNOWN.unknown(), ServiceFlags::NONE);
+        assert_eq!((ServiceFlags::WITNESS | unknown).unknown(), unknown);
+        assert_eq!(
+            "ServiceFlags(WITNESS|0x1000000)",
+            (ServiceFlags::WITNESS | unknown).to_string()
+        );
+
+        let full = ServiceFlags::NETWORK | ServiceFlags::WITNESS;
+        let pruned = ServiceFlags::NETWORK_LIMITED | ServiceFlags::WITNESS;
+        // Full nodes may set both bits.
+        let both = full | ServiceFlags::NETWORK_LIMITED;
+        assert!(full.serves_blocks() && pruned.serves_blocks() && both.serves_blocks());
+        assert!(!ServiceFlags::WITNESS.serves_blocks());
+        assert!(!full.supports_pruned_peers());
+        assert!(pruned.supports_pruned_peers());
+        assert!(!both.supports_pruned_peers());
+
+        assert!(full.serves_block_at_depth(u32::MAX));
+        assert!(pruned.serves_block_at_depth(287));
+        assert!(!pruned.serves_block_at_depth(288));
+        assert!(!ServiceFlags::WITNESS.serves_block_at_depth(0));
+    }
+
     #[test]
     fn service_flags_test() {
         let all = [
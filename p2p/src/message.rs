@@ -33,13 +33,77 @@
 
 /// The maximum number of [`super::message_blockdata::Inventory`] items in an `inv` message.
 ///
-/// This limit is not currently enforced by this implementation.
+/// This limit is enforced when decoding messages, see [`P2pLimits`].
 pub const MAX_INV_SIZE: usize = 50_000;
 
 /// Maximum size, in bytes, of an encoded message
 /// This by necessity should be larger than `MAX_VEC_SIZE`
 pub const MAX_MSG_SIZE: usize = 5_000_000;
 
+/// Limits on the size of decoded messages.
+///
+/// The default limits are those of Bitcoin Core and are enforced by all the decoders of this
+/// crate. Private networks and tests may tighten or relax them with
+/// [`RawNetworkMessageDecoder::with_limits`], [`RawNetworkMessageRef::from_slice_with_limits`]
+/// and [`RawNetworkMessageRef::decode_with_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct P2pLimits {
+    /// The maximum size of a payload in bytes.
+    pub max_payload: usize,
+    /// The maximum number of items in an `inv`, `getdata` or `notfound` message.
+    pub max_inv_entries: usize,
+    /// The maximum number of addresses in an `addr` or `addrv2` message.
+    pub max_addr_entries: usize,
+    /// The maximum number of headers in a `headers` message.
+    pub max_headers: usize,
+    /// The maximum number of filter hashes in a `cfheaders` message.
+    pub max_filter_hashes: usize,
+    /// The maximum number of filter headers in a `cfcheckpt` message.
+    pub max_filter_checkpoints: usize,
+}
+
+impl P2pLimits {
+    /// The default limits.
+    pub const DEFAULT: Self = Self {
+        max_payload: MAX_MSG_SIZE,
+        max_inv_entries: MAX_INV_SIZE,
+        max_addr_entries: 1_000,
+        max_headers: 2_000,
+        max_filter_hashes: 2_000,
+        max_filter_checkpoints: 10_000,
+    };
+
+    /// Checks that the number of entries of `msg` is within the limits.
+    fn check(&self, msg: &NetworkMessage) -> Result<(), encode::Error> {
+        if self.allows(msg) {
+            Ok(())
+        } else {
+            Err(encode::ParseError::ParseFailed("payload has more entries than allowed").into())
+        }
+    }
+
+    /// Returns `true` if the number of entries of `msg` is within the limits.
+    fn allows(&self, msg: &NetworkMessage) -> bool {
+        match msg {
+            NetworkMessage::Inv(inv)
+            | NetworkMessage::GetData(inv)
+            | NetworkMessage::NotFound(inv) => inv.0.len() <= self.max_inv_entries,
+            NetworkMessage::Addr(addr) => addr.0.len() <= self.max_addr_entries,
+            NetworkMessage::AddrV2(addr) => addr.0.len() <= self.max_addr_entries,
+            NetworkMessage::Headers(headers) => headers.0.len() <= self.max_headers,
+            NetworkMessage::CFHeaders(headers) =>
+                headers.filter_hashes.len() <= self.max_filter_hashes,
+            NetworkMessage::CFCheckpt(checkpoints) =>
+                checkpoints.filter_headers.len() <= self.max_filter_checkpoints,
+            _ => true,
+        }
+    }
+}
+
+impl Default for P2pLimits {
+    fn default() -> Self { Self::DEFAULT }
+}
+
 /// Serializer for command string
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct CommandString(Cow<'static, str>);
@@ -723,6 +787,139 @@ pub fn cmd(&self) -> &'static str { self.payload.cmd() }
     pub fn command(&self) -> CommandString { self.payload.command() }
 }
 
+/// A v1 network message with its payload borrowed and not yet decoded.
+///
+/// Parsing only reads the header, the checksum is verified by [`Self::verify_checksum`] or when
+/// the payload is decoded with [`Self::decode`]. Encoding writes the payload bytes as received so
+/// relaying a message unchanged neither decodes nor re-serializes it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawNetworkMessageRef<'a> {
+    magic: Magic,
+    command: CommandString,
+    checksum: [u8; 4],
+    payload: &'a [u8],
+}
+
+impl<'a> RawNetworkMessageRef<'a> {
+    /// Parses the message at the start of `bytes`, returning it and the bytes following it.
+    ///
+    /// # Errors
+    ///
+    /// If the header is invalid, the payload is larger than [`MAX_MSG_SIZE`] or `bytes` is
+    /// shorter than the message. The checksum is not verified.
+    pub fn from_slice(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), encode::Error> {
+        Self::from_slice_with_limits(bytes, P2pLimits::DEFAULT)
+    }
+
+    /// Parses the message at the start of `bytes` with a payload of at most
+    /// `limits.max_payload` bytes, returning it and the bytes following it.
+    ///
+    /// # Errors
+    ///
+    /// If the header is invalid, the payload is larger than allowed or `bytes` is shorter than
+    /// the message. The checksum is not verified.
+    pub fn from_slice_with_limits(
+        mut bytes: &'a [u8],
+        limits: P2pLimits,
+    ) -> Result<(Self, &'a [u8]), encode::Error> {
+        let header = V1MessageHeader::consensus_decode(&mut bytes)?;
+        let len = header.length as usize;
+        if len > limits.max_payload {
+            return Err(encode::ParseError::OversizedVectorAllocation {
+                requested: len,
+                max: limits.max_payload,
+            }
+            .into());
+        }
+        if bytes.len() < len {
+            return Err(encode::ParseError::MissingData.into());
+        }
+        let (payload, rest) = bytes.split_at(len);
+        let msg = Self {
+            magic: header.magic,
+            command: header.command,
+            checksum: header.checksum,
+            payload,
+        };
+        Ok((msg, rest))
+    }
+
+    /// Magic bytes to identify the network these messages are meant for
+    pub fn magic(&self) -> &Magic { &self.magic }
+
+    /// Returns the `CommandString` for the message command.
+    pub fn command(&self) -> &CommandString { &self.command }
+
+    /// Returns the checksum in the message header.
+    pub fn checksum(&self) -> [u8; 4] { self.checksum }
+
+    /// Returns the undecoded payload.
+    pub fn payload(&self) -> &'a [u8] { self.payload }
+
+    /// Verifies the checksum in the message header against the payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`encode::ParseError::InvalidChecksum`] if the checksum does not match.
+    pub fn verify_checksum(&self) -> Result<(), encode::Error> {
+        let expected = sha2_checksum(self.payload);
+        if expected == self.checksum {
+            Ok(())
+        } else {
+            Err(encode::ParseError::InvalidChecksum { expected, actual: self.checksum }.into())
+        }
+    }
+
+    /// Verifies the checksum and decodes the payload within the default [`P2pLimits`].
+    ///
+    /// # Errors
+    ///
+    /// If the checksum does not match or the payload is invalid for the command.
+    pub fn decode(&self) -> Result<RawNetworkMessage, encode::Error> {
+        self.decode_with_limits(P2pLimits::DEFAULT)
+    }
+
+    /// Verifies the checksum and decodes the payload within `limits`.
+    ///
+    /// # Errors
+    ///
+    /// If the checksum does not match, the payload is invalid for the command or it is larger
+    /// or has more entries than allowed.
+    pub fn decode_with_limits(
+        &self,
+        limits: P2pLimits,
+    ) -> Result<RawNetworkMessage, encode::Error> {
+        if self.payload.len() > limits.max_payload {
+            return Err(encode::ParseError::OversizedVectorAllocation {
+                requested: self.payload.len(),
+                max: limits.max_payload,
+            }
+            .into());
+        }
+        self.verify_checksum()?;
+        let payload = decode_v1_payload(self.command.clone(), self.payload.to_vec())?;
+        limits.check(&payload)?;
+        Ok(RawNetworkMessage {
+            magic: self.magic,
+            payload,
+            payload_len: self.payload.len() as u32,
+            checksum: self.checksum,
+        })
+    }
+}
+
+impl Encodable for RawNetworkMessageRef<'_> {
+    fn consensus_encode<W: Write + ?Sized>(&self, w: &mut W) -> Result<usize, io::Error> {
+        let mut len = 0;
+        len += self.magic.consensus_encode(w)?;
+        len += self.command.consensus_encode(w)?;
+        len += (self.payload.len() as u32).consensus_encode(w)?;
+        len += self.checksum.consensus_encode(w)?;
+        len += w.emit_slice(self.payload)?;
+        Ok(len)
+    }
+}
+
 impl V2NetworkMessage {
     /// Constructs a new [`V2NetworkMessage`].
     pub fn new(payload: NetworkMessage) -> Self { Self { payload } }
@@ -796,7 +993,11 @@ fn consensus_encode<W: Write + ?Sized>(&self, writer: &mut W) -> Result<usize, i
             | Self::WtxidRelay
             | Self::FilterClear
             | Self::SendAddrV2 => Ok(0),
-            Self::Unknown { payload: ref data, .. } => data.consensus_encode(writer),
+            // The payload of an unknown message is opaque, it has no length prefix.
+            Self::Unknown { payload: ref data, .. } => {
+                writer.write_all(data)?;
+                Ok(data.len())
+            }
         }
     }
 }
@@ -1184,6 +1385,24 @@ enum DecoderState {
 /// to decode the dynamically sized network message.
 pub struct RawNetworkMessageDecoder {
     state: DecoderState,
+    limits: P2pLimits,
+}
+
+impl RawNetworkMessageDecoder {
+    /// Constructs a new decoder rejecting messages exceeding `limits`.
+    pub fn with_limits(limits: P2pLimits) -> Self {
+        Self {
+            state: DecoderState::ReadingHeader {
+                header_decoder: encoding::Decoder4::new(
+                    encoding::ArrayDecoder::new(),
+                    CommandStringDecoder { inner: encoding::ArrayDecoder::new() },
+                    encoding::ArrayDecoder::new(),
+                    encoding::ArrayDecoder::new(),
+                ),
+            },
+            limits,
+        }
+    }
 }
 
 impl encoding::Decoder for RawNetworkMessageDecoder {
@@ -1223,7 +1442,7 @@ fn push_bytes(&mut self, bytes: &mut &[u8]) -> Result<bool, Self::Error> {
                         })?;
 
                     let payload_len = u32::from_le_bytes(payload_len_bytes) as usize;
-                    if payload_len > MAX_MSG_SIZE {
+                    if payload_len > self.limits.max_payload {
                         return Err(RawNetworkMessageDecoderError(
                             RawNetworkMessageDecoderErrorInner::PayloadTooLarge,
                         ));
@@ -1260,6 +1479,11 @@ fn end(self) -> Result<Self::Output, Self::Error> {
                 ..
             } => {
                 let payload = payload_decoder.end()?;
+                if !self.limits.allows(&payload) {
+                    return Err(RawNetworkMessageDecoderError(
+                        RawNetworkMessageDecoderErrorInner::TooManyEntries,
+                    ));
+                }
 
                 Ok(RawNetworkMessage {
                     magic: Magic::from_bytes(magic_bytes),
@@ -1282,18 +1506,7 @@ fn read_limit(&self) -> usize {
 impl encoding::Decodable for RawNetworkMessage {
     type Decoder = RawNetworkMessageDecoder;
 
-    fn decoder() -> Self::Decoder {
-        RawNetworkMessageDecoder {
-            state: DecoderState::ReadingHeader {
-                header_decoder: encoding::Decoder4::new(
-                    encoding::ArrayDecoder::new(),
-                    CommandStringDecoder { inner: encoding::ArrayDecoder::new() },
-                    encoding::ArrayDecoder::new(),
-                    encoding::ArrayDecoder::new(),
-                ),
-            },
-        }
-    }
+    fn decoder() -> Self::Decoder { RawNetworkMessageDecoder::with_limits(P2pLimits::DEFAULT) }
 }
 
 /// Error decoding a raw network message.
@@ -1306,6 +1519,8 @@ enum RawNetworkMessageDecoderErrorInner {
     Header,
     /// Payload length exceeds maximum allowed message size.
     PayloadTooLarge,
+    /// Payload has more entries than allowed for its command.
+    TooManyEntries,
     /// Error decoding the message payload.
     Payload,
 }
@@ -1319,6 +1534,9 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             RawNetworkMessageDecoderErrorInner::PayloadTooLarge => {
                 write!(f, "payload length exceeds maximum allowed message size")
             }
+            RawNetworkMessageDecoderErrorInner::TooManyEntries => {
+                write!(f, "payload has more entries than allowed")
+            }
             RawNetworkMessageDecoderErrorInner::Payload => {
                 write!(f, "error decoding message payload")
             }
@@ -1435,92 +1653,8 @@ fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
         let checksum = checked_data.checksum();
         let raw_payload = checked_data.into_data();
         let payload_len = raw_payload.len() as u32;
-
-        let mut mem_d = raw_payload.as_slice();
-        let payload = match &cmd.0[..] {
-            "version" =>
-                NetworkMessage::Version(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "verack" => NetworkMessage::Verack,
-            "addr" =>
-                NetworkMessage::Addr(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "inv" =>
-                NetworkMessage::Inv(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "getdata" =>
-                NetworkMessage::GetData(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "notfound" => NetworkMessage::NotFound(Decodable::consensus_decode_from_finite_reader(
-                &mut mem_d,
-            )?),
-            "getblocks" => NetworkMessage::GetBlocks(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "getheaders" => NetworkMessage::GetHeaders(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "mempool" => NetworkMessage::MemPool,
-            "block" =>
-                NetworkMessage::Block(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "headers" => NetworkMessage::Headers(
-                HeadersMessage::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "sendheaders" => NetworkMessage::SendHeaders,
-            "getaddr" => NetworkMessage::GetAddr,
-            "ping" =>
-                NetworkMessage::Ping(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "pong" =>
-                NetworkMessage::Pong(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "merkleblock" => NetworkMessage::MerkleBlock(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "filterload" => NetworkMessage::FilterLoad(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "filteradd" => NetworkMessage::FilterAdd(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "filterclear" => NetworkMessage::FilterClear,
-            "tx" => NetworkMessage::Tx(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "getcfilters" => NetworkMessage::GetCFilters(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "cfilter" =>
-                NetworkMessage::CFilter(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "getcfheaders" => NetworkMessage::GetCFHeaders(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "cfheaders" => NetworkMessage::CFHeaders(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "getcfcheckpt" => NetworkMessage::GetCFCheckpt(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "cfcheckpt" => NetworkMessage::CFCheckpt(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "reject" =>
-                NetworkMessage::Reject(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "alert" =>
-                NetworkMessage::Alert(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "feefilter" => NetworkMessage::FeeFilter(
-                FeeFilter::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "sendcmpct" => NetworkMessage::SendCmpct(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "cmpctblock" => NetworkMessage::CmpctBlock(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "getblocktxn" => NetworkMessage::GetBlockTxn(
-                Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
-            ),
-            "blocktxn" => NetworkMessage::BlockTxn(Decodable::consensus_decode_from_finite_reader(
-                &mut mem_d,
-            )?),
-            "wtxidrelay" => NetworkMessage::WtxidRelay,
-            "addrv2" =>
-                NetworkMessage::AddrV2(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
-            "sendaddrv2" => NetworkMessage::SendAddrV2,
-            _ => NetworkMessage::Unknown { command: cmd, payload: raw_payload },
-        };
+        let payload = decode_v1_payload(cmd, raw_payload)?;
+        P2pLimits::DEFAULT.check(&payload)?;
         Ok(Self { magic, payload, payload_len, checksum })
     }
 
@@ -1530,6 +1664,81 @@ fn consensus_decode<R: BufRead + ?Sized>(r: &mut R) -> Result<Self, encode::Erro
     }
 }
 
+/// Decodes the payload of a v1 message with the given command.
+fn decode_v1_payload(
+    cmd: CommandString,
+    raw_payload: Vec<u8>,
+) -> Result<NetworkMessage, encode::Error> {
+    let mut mem_d = raw_payload.as_slice();
+    let payload = match &cmd.0[..] {
+        "version" =>
+            NetworkMessage::Version(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "verack" => NetworkMessage::Verack,
+        "addr" => NetworkMessage::Addr(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "inv" => NetworkMessage::Inv(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "getdata" =>
+            NetworkMessage::GetData(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "notfound" =>
+            NetworkMessage::NotFound(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "getblocks" =>
+            NetworkMessage::GetBlocks(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "getheaders" =>
+            NetworkMessage::GetHeaders(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "mempool" => NetworkMessage::MemPool,
+        "block" =>
+            NetworkMessage::Block(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "headers" => NetworkMessage::Headers(HeadersMessage::consensus_decode_from_finite_reader(
+            &mut mem_d,
+        )?),
+        "sendheaders" => NetworkMessage::SendHeaders,
+        "getaddr" => NetworkMessage::GetAddr,
+        "ping" => NetworkMessage::Ping(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "pong" => NetworkMessage::Pong(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "merkleblock" =>
+            NetworkMessage::MerkleBlock(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "filterload" =>
+            NetworkMessage::FilterLoad(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "filteradd" =>
+            NetworkMessage::FilterAdd(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "filterclear" => NetworkMessage::FilterClear,
+        "tx" => NetworkMessage::Tx(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "getcfilters" =>
+            NetworkMessage::GetCFilters(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "cfilter" =>
+            NetworkMessage::CFilter(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "getcfheaders" => NetworkMessage::GetCFHeaders(
+            Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
+        ),
+        "cfheaders" =>
+            NetworkMessage::CFHeaders(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "getcfcheckpt" => NetworkMessage::GetCFCheckpt(
+            Decodable::consensus_decode_from_finite_reader(&mut mem_d)?,
+        ),
+        "cfcheckpt" =>
+            NetworkMessage::CFCheckpt(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "reject" =>
+            NetworkMessage::Reject(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "alert" =>
+            NetworkMessage::Alert(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "feefilter" =>
+            NetworkMessage::FeeFilter(FeeFilter::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "sendcmpct" =>
+            NetworkMessage::SendCmpct(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "cmpctblock" =>
+            NetworkMessage::CmpctBlock(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "getblocktxn" =>
+            NetworkMessage::GetBlockTxn(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "blocktxn" =>
+            NetworkMessage::BlockTxn(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "wtxidrelay" => NetworkMessage::WtxidRelay,
+        "addrv2" =>
+            NetworkMessage::AddrV2(Decodable::consensus_decode_from_finite_reader(&mut mem_d)?),
+        "sendaddrv2" => NetworkMessage::SendAddrV2,
+        _ => NetworkMessage::Unknown { command: cmd, payload: raw_payload },
+    };
+    Ok(payload)
+}
+
 impl Decodable for V2NetworkMessage {
     fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
         r: &mut R,
@@ -1593,6 +1802,7 @@ fn consensus_decode_from_finite_reader<R: BufRead + ?Sized>(
                     "Unknown short ID",
                 ))),
         };
+        P2pLimits::DEFAULT.check(&payload)?;
         Ok(Self { payload })
     }
 
@@ -1721,7 +1931,14 @@ fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
 #[cfg(feature = "arbitrary")]
 impl<'a> Arbitrary<'a> for CommandString {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        Ok(Self(u.arbitrary::<String>()?.into()))
+        // Commands are at most 12 ASCII characters and must not contain the null padding.
+        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+        let len = u.int_in_range(0..=12)?;
+        let command = (0..len)
+            .map(|_| u.choose(CHARSET).map(|&c| char::from(c)))
+            .collect::<arbitrary::Result<String>>()?;
+        Ok(Self(command.into()))
     }
 }
 
@@ -1770,7 +1987,15 @@ fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
             33 => Ok(Self::WtxidRelay),
             34 => Ok(Self::AddrV2(u.arbitrary()?)),
             35 => Ok(Self::SendAddrV2),
-            _ => Ok(Self::Unknown { command: u.arbitrary()?, payload: Vec::<u8>::arbitrary(u)? }),
+            _ => {
+                // All known commands are lower case, an upper case first character makes sure the
+                // message decodes back to `Unknown`.
+                let rest = CommandString::arbitrary(u)?;
+                let mut command = String::from(char::from(u.int_in_range(b'A'..=b'Z')?));
+                command.push_str(rest.as_ref().get(1..).unwrap_or_default());
+                let command = CommandString::try_from(command).expect("at most 12 characters");
+                Ok(Self::Unknown { command, payload: Vec::<u8>::arbitrary(u)? })
+            }
         }
     }
 }
@@ -2026,6 +2251,19 @@ fn serialize_v2_ping() {
         );
     }
 
+    #[test]
+    fn serialize_unknown() {
+        let command = CommandString::try_from_static("Foo").unwrap();
+        let msg = RawNetworkMessage::new(
+            Magic::BITCOIN,
+            NetworkMessage::Unknown { command, payload: vec![1, 2, 3] },
+        );
+        let bytes = serialize(&msg);
+        // The payload follows the 24 byte header as is.
+        assert_eq!(bytes[24..], [1, 2, 3]);
+        assert_eq!(deserialize::<RawNetworkMessage>(&bytes).unwrap(), msg);
+    }
+
     #[test]
     #[rustfmt::skip]
     fn serialize_mempool() {
@@ -2221,6 +2459,156 @@ fn deserialize_partial_message() {
         }
     }
 
+    #[test]
+    fn raw_network_message_ref() {
+        let ping = RawNetworkMessage::new(Magic::BITCOIN, NetworkMessage::Ping(42));
+        let mut data = serialize(&ping);
+        data.extend_from_slice(&[0xab; 3]);
+
+        let (msg, rest) = RawNetworkMessageRef::from_slice(&data).unwrap();
+        assert_eq!(rest, [0xab; 3]);
+        assert_eq!(*msg.magic(), Magic::BITCOIN);
+        assert_eq!(msg.command().as_ref(), "ping");
+        assert_eq!(msg.payload(), 42_u64.to_le_bytes());
+        assert!(msg.verify_checksum().is_ok());
+        assert_eq!(msg.decode().unwrap(), ping);
+        // Relaying writes the message unchanged.
+        assert_eq!(serialize(&msg), data[..data.len() - 3]);
+
+        // The checksum is only verified on request.
+        data[20] ^= 1;
+        let (msg, _) = RawNetworkMessageRef::from_slice(&data).unwrap();
+        assert!(matches!(
+            msg.verify_checksum(),
+            Err(encode::Error::Parse(encode::ParseError::InvalidChecksum { .. }))
+        ));
+        assert!(msg.decode().is_err());
+
+        assert!(RawNetworkMessageRef::from_slice(&data[..28]).is_err());
+        assert!(RawNetworkMessageRef::from_slice(&data[..20]).is_err());
+    }
+
+    #[test]
+    fn decode_limits() {
+        let header = deserialize(&hex!("010000004ddccd549d28f385ab457e98d1b11ce80bfea2c5ab93015ade4973e400000000bf4473e53794beae34e64fccc471dace6ae544180816f89591894e0f417a914cd74d6e49ffff001d323b3a7b")).unwrap();
+        let headers = NetworkMessage::Headers(HeadersMessage(vec![header; 2_001]));
+        let msg = RawNetworkMessage::new(Magic::BITCOIN, headers.clone());
+        let data = serialize(&msg);
+
+        assert!(deserialize::<RawNetworkMessage>(&data).is_err());
+        assert!(
+            deserialize::<V2NetworkMessage>(&serialize(&V2NetworkMessage::new(headers))).is_err()
+        );
+        let (msg_ref, _) = RawNetworkMessageRef::from_slice(&data).unwrap();
+        assert!(msg_ref.decode().is_err());
+        let limits = P2pLimits { max_headers: 2_001, ..P2pLimits::DEFAULT };
+        assert_eq!(msg_ref.decode_with_limits(limits).unwrap(), msg);
+        let limits = P2pLimits { max_payload: data.len() - 25, ..limits };
+        assert!(msg_ref.decode_with_limits(limits).is_err());
+        assert!(RawNetworkMessageRef::from_slice_with_limits(&data, limits).is_err());
+
+        // Parsing honours a payload limit above the default.
+        let unknown = NetworkMessage::Unknown {
+            command: CommandString::try_from_static("big").unwrap(),
+            payload: vec![0; MAX_MSG_SIZE + 1],
+        };
+        let data = serialize(&RawNetworkMessage::new(Magic::BITCOIN, unknown));
+        assert!(RawNetworkMessageRef::from_slice(&data).is_err());
+        let limits = P2pLimits { max_payload: MAX_MSG_SIZE + 1, ..P2pLimits::DEFAULT };
+        let (msg_ref, rest) = RawNetworkMessageRef::from_slice_with_limits(&data, limits).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(msg_ref.payload().len(), MAX_MSG_SIZE + 1);
+
+        let cfheaders = NetworkMessage::CFHeaders(message_filter::CFHeaders {
+            filter_type: 0,
+            stop_hash: BlockHash::from_byte_array([1; 32]),
+            previous_filter_header: message_filter::FilterHeader::from_byte_array([2; 32]),
+            filter_hashes: vec![message_filter::FilterHash::from_byte_array([3; 32]); 2_001],
+        });
+        let data = serialize(&RawNetworkMessage::new(Magic::BITCOIN, cfheaders.clone()));
+        assert!(deserialize::<RawNetworkMessage>(&data).is_err());
+        assert!(
+            deserialize::<V2NetworkMessage>(&serialize(&V2NetworkMessage::new(cfheaders))).is_err()
+        );
+    }
+
+    #[test]
+    fn decoder_limits() {
+        fn decode(
+            limits: P2pLimits,
+            msg: &RawNetworkMessage,
+        ) -> Result<RawNetworkMessage, RawNetworkMessageDecoderError> {
+            use encoding::Decoder as _;
+
+            let bytes = serialize(msg);
+            let mut decoder = RawNetworkMessageDecoder::with_limits(limits);
+            let mut slice = bytes.as_slice();
+            while !slice.is_empty() && decoder.push_bytes(&mut slice)? {}
+            decoder.end()
+        }
+
+        let inv = vec![Inventory::Block(BlockHash::from_byte_array([1; 32])); 3];
+        let msg =
+            RawNetworkMessage::new(Magic::BITCOIN, NetworkMessage::Inv(InventoryPayload(inv)));
+        assert_eq!(decode(P2pLimits::DEFAULT, &msg).unwrap(), msg);
+        assert_eq!(
+            encoding::decode_from_slice::<RawNetworkMessage>(&serialize(&msg)).unwrap(),
+            msg
+        );
+
+        let limits = P2pLimits { max_inv_entries: 3, ..P2pLimits::DEFAULT };
+        assert_eq!(decode(limits, &msg).unwrap(), msg);
+        let limits = P2pLimits { max_inv_entries: 2, ..P2pLimits::DEFAULT };
+        assert_eq!(
+            decode(limits, &msg),
+            Err(RawNetworkMessageDecoderError(RawNetworkMessageDecoderErrorInner::TooManyEntries))
+        );
+        // Other limits do not apply to `inv`.
+        let limits = P2pLimits { max_addr_entries: 0, max_headers: 0, ..P2pLimits::DEFAULT };
+        assert_eq!(decode(limits, &msg).unwrap(), msg);
+
+        let limits = P2pLimits { max_payload: 100, ..P2pLimits::DEFAULT };
+        assert_eq!(
+            decode(limits, &msg),
+            Err(RawNetworkMessageDecoderError(RawNetworkMessageDecoderErrorInner::PayloadTooLarge))
+        );
+
+        let headers = RawNetworkMessage::new(
+            Magic::BITCOIN,
+            NetworkMessage::Headers(HeadersMessage(vec![
+                deserialize(&hex!("010000004ddccd549d28f385ab457e98d1b11ce80bfea2c5ab93015ade4973e400000000bf4473e53794beae34e64fccc471dace6ae544180816f89591894e0f417a914cd74d6e49ffff001d323b3a7b")).unwrap();
+                2
+            ])),
+        );
+        assert!(decode(P2pLimits { max_headers: 2, ..P2pLimits::DEFAULT }, &headers).is_ok());
+        assert!(decode(P2pLimits { max_headers: 1, ..P2pLimits::DEFAULT }, &headers).is_err());
+
+        let cfheaders = message_filter::CFHeaders {
+            filter_type: 0,
+            stop_hash: BlockHash::from_byte_array([1; 32]),
+            previous_filter_header: message_filter::FilterHeader::from_byte_array([2; 32]),
+            filter_hashes: vec![message_filter::FilterHash::from_byte_array([3; 32]); 2_001],
+        };
+        let msg = RawNetworkMessage::new(Magic::BITCOIN, NetworkMessage::CFHeaders(cfheaders));
+        assert_eq!(
+            decode(P2pLimits::DEFAULT, &msg),
+            Err(RawNetworkMessageDecoderError(RawNetworkMessageDecoderErrorInner::TooManyEntries))
+        );
+        let limits = P2pLimits { max_filter_hashes: 2_001, ..P2pLimits::DEFAULT };
+        assert_eq!(decode(limits, &msg).unwrap(), msg);
+
+        let cfcheckpt = message_filter::CFCheckpt {
+            filter_type: 0,
+            stop_hash: BlockHash::from_byte_array([1; 32]),
+            filter_headers: vec![message_filter::FilterHeader::from_byte_array([2; 32]); 3],
+        };
+        let msg = RawNetworkMessage::new(Magic::BITCOIN, NetworkMessage::CFCheckpt(cfcheckpt));
+        assert!(decode(P2pLimits::DEFAULT, &msg).is_ok());
+        assert!(
+            decode(P2pLimits { max_filter_checkpoints: 2, ..P2pLimits::DEFAULT }, &msg).is_err()
+        );
+    }
+
     #[test]
     fn serialize_checkeddata() {
         let cd = CheckedData::new(vec![1u8, 2, 3, 4, 5]);
@@ -2248,4 +2636,25 @@ fn headers_message() {
         let headers_message = HeadersMessage(vec![block_900_000, block_900_001, block_900_002]);
         assert!(headers_message.is_connected());
     }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_unknown_message_roundtrips() {
+        for seed in 0..=u8::MAX {
+            let data = [seed; 64];
+            let mut u = Unstructured::new(&data);
+
+            let command = CommandString::arbitrary(&mut u).unwrap();
+            assert!(command.as_ref().len() <= 12);
+            assert_eq!(deserialize::<CommandString>(&serialize(&command)).unwrap(), command);
+
+            // The last variant is `Unknown`.
+            let data = [&[36_u8][..], &[seed; 64][..]].concat();
+            let mut u = Unstructured::new(&data);
+            let msg =
+                RawNetworkMessage::new(Magic::BITCOIN, NetworkMessage::arbitrary(&mut u).unwrap());
+            assert!(matches!(msg.payload(), NetworkMessage::Unknown { .. }));
+            assert_eq!(deserialize::<RawNetworkMessage>(&serialize(&msg)).unwrap(), msg);
+        }
+    }
 }
@@ -21,6 +21,7 @@
 use primitives::block::{BlockHashDecoder, BlockHashEncoder};
 use primitives::transaction::{Txid, Wtxid};
 use primitives::BlockHash;
+use units::BlockHeight;
 
 use crate::consensus::impl_consensus_encoding;
 use crate::{ProtocolVersion, ProtocolVersionDecoder, ProtocolVersionEncoder};
@@ -222,6 +223,69 @@ pub struct GetHeadersMessage {
     pub stop_hash: BlockHash,
 }
 
+impl GetBlocksMessage {
+    /// Constructs a new `getblocks` message requesting the blocks after `locator`.
+    pub fn new(version: ProtocolVersion, locator: BlockLocator, stop_hash: BlockHash) -> Self {
+        Self { version, locator_hashes: locator.into_vec(), stop_hash }
+    }
+}
+
+impl GetHeadersMessage {
+    /// Constructs a new `getheaders` message requesting the headers after `locator`.
+    ///
+    /// Use [`BlockHash::GENESIS_PREVIOUS_BLOCK_HASH`] as `stop_hash` to fetch the maximum number
+    /// of headers.
+    pub fn new(version: ProtocolVersion, locator: BlockLocator, stop_hash: BlockHash) -> Self {
+        Self { version, locator_hashes: locator.into_vec(), stop_hash }
+    }
+}
+
+/// A block locator, the hashes a peer uses to find the last block our chains have in common.
+///
+/// The locator contains the hashes of the ten most recent blocks and after that steps back
+/// exponentially, ending with the genesis block. This is the same locator Bitcoin Core sends.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct BlockLocator(Vec<BlockHash>);
+
+impl BlockLocator {
+    /// Constructs the locator for a chain with the given tip height.
+    ///
+    /// `hash_lookup` returns the hash of the block at a height of the chain, it is called once for
+    /// each height in the locator, newest to oldest.
+    pub fn from_heights<F>(tip_height: BlockHeight, hash_lookup: F) -> Self
+    where
+        F: FnMut(BlockHeight) -> BlockHash,
+    {
+        Self(Self::heights(tip_height).map(hash_lookup).collect())
+    }
+
+    /// Returns the heights of the blocks in the locator for a chain with the given tip height.
+    pub fn heights(tip_height: BlockHeight) -> impl Iterator<Item = BlockHeight> {
+        let mut next = Some(tip_height.to_u32());
+        let mut step = 1_u32;
+        let mut count = 0_usize;
+        core::iter::from_fn(move || {
+            let height = next?;
+            count += 1;
+            next = if height == 0 { None } else { Some(height.saturating_sub(step)) };
+            if count > 10 {
+                step = step.saturating_mul(2);
+            }
+            Some(BlockHeight::from_u32(height))
+        })
+    }
+
+    /// Returns the locator hashes, ordered newest to oldest.
+    pub fn as_slice(&self) -> &[BlockHash] { &self.0 }
+
+    /// Returns the locator hashes, ordered newest to oldest.
+    pub fn into_vec(self) -> Vec<BlockHash> { self.0 }
+}
+
+impl From<BlockLocator> for Vec<BlockHash> {
+    fn from(locator: BlockLocator) -> Self { locator.0 }
+}
+
 type GetBlocksOrHeadersInnerEncoder<'e> = Encoder3<
     ProtocolVersionEncoder<'e>,
     Encoder2<CompactSizeEncoder, SliceEncoder<'e, BlockHash>>,
@@ -467,4 +531,31 @@ fn getheaders_message() {
 
         assert_eq!(serialize(&real_decode), from_sat);
     }
+
+    #[test]
+    fn block_locator() {
+        let heights =
+            |tip| BlockLocator::heights(BlockHeight::from_u32(tip)).map(BlockHeight::to_u32);
+
+        assert_eq!(heights(0).collect::<Vec<_>>(), [0]);
+        assert_eq!(heights(5).collect::<Vec<_>>(), [5, 4, 3, 2, 1, 0]);
+        assert_eq!(
+            heights(100).collect::<Vec<_>>(),
+            [100, 99, 98, 97, 96, 95, 94, 93, 92, 91, 90, 89, 87, 83, 75, 59, 27, 0]
+        );
+        // The locator grows logarithmically.
+        assert_eq!(heights(u32::MAX).count(), 43);
+        assert_eq!(heights(u32::MAX).last(), Some(0));
+
+        let hash = |h: BlockHeight| BlockHash::from_byte_array([h.to_u32() as u8; 32]);
+        let locator = BlockLocator::from_heights(BlockHeight::from_u32(12), hash);
+        let want =
+            heights(12).map(|h| BlockHash::from_byte_array([h as u8; 32])).collect::<Vec<_>>();
+        assert_eq!(locator.as_slice(), want);
+
+        let stop_hash = BlockHash::GENESIS_PREVIOUS_BLOCK_HASH;
+        let msg = GetHeadersMessage::new(ProtocolVersion::WTXID_RELAY_VERSION, locator, stop_hash);
+        assert_eq!(msg.locator_hashes, want);
+        assert_eq!(msg.stop_hash, stop_hash);
+    }
 }
@@ -10,6 +10,7 @@
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::{Arbitrary, Unstructured};
+use bitcoin::bip158::BlockFilter;
 use encoding::{
     ArrayDecoder, ArrayEncoder, ByteVecDecoder, BytesEncoder, CompactSizeEncoder, Decoder2,
     Decoder3, Decoder4, Encoder2, Encoder3, Encoder4, SliceEncoder, VecDecoder,
@@ -21,6 +22,7 @@
 use units::block::{BlockHeightDecoder, BlockHeightEncoder};
 use units::BlockHeight;
 
+use crate::bip157::BASIC_FILTER_TYPE;
 use crate::consensus::impl_consensus_encoding;
 
 hashes::hash_newtype! {
@@ -42,6 +44,10 @@ pub fn filter_header(&self, previous_filter_header: FilterHeader) -> FilterHeade
     }
 }
 
+impl From<&BlockFilter> for FilterHash {
+    fn from(filter: &BlockFilter) -> Self { Self(filter.filter_hash()) }
+}
+
 #[rustfmt::skip]
 macro_rules! impl_hashencode {
     ($hashtype:ident) => {
@@ -380,6 +386,26 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(&self.0) }
 
 impl_consensus_encoding!(CFilter, filter_type, block_hash, filter);
 
+impl CFilter {
+    /// Constructs a new `cfilter` message carrying the basic filter of the block `block_hash`.
+    pub fn new_basic(block_hash: BlockHash, filter: BlockFilter) -> Self {
+        Self { filter_type: BASIC_FILTER_TYPE, block_hash, filter: filter.content }
+    }
+
+    /// Returns the hash of the filter, committed to by the filter header of the block.
+    pub fn filter_hash(&self) -> FilterHash { FilterHash(sha256d::Hash::hash(&self.filter)) }
+
+    /// Returns the filter as a [`BlockFilter`] to match scripts against.
+    ///
+    /// Only meaningful for the basic filter type, check [`Self::filter_type`] first.
+    pub fn to_block_filter(&self) -> BlockFilter { BlockFilter::new(&self.filter) }
+
+    /// Converts the message into a [`BlockFilter`] to match scripts against.
+    ///
+    /// Only meaningful for the basic filter type, check [`Self::filter_type`] first.
+    pub fn into_block_filter(self) -> BlockFilter { BlockFilter { content: self.filter } }
+}
+
 /// getcfheaders message
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct GetCFHeaders {
@@ -570,6 +596,20 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(&self.0) }
 }
 impl_consensus_encoding!(CFHeaders, filter_type, stop_hash, previous_filter_header, filter_hashes);
 
+impl CFHeaders {
+    /// Computes the filter headers of the blocks in the range from the filter hashes.
+    pub fn filter_headers(&self) -> Vec<FilterHeader> {
+        let mut previous = self.previous_filter_header;
+        self.filter_hashes
+            .iter()
+            .map(|filter_hash| {
+                previous = filter_hash.filter_header(previous);
+                previous
+            })
+            .collect()
+    }
+}
+
 /// getcfcheckpt message
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct GetCFCheckpt {
@@ -807,3 +847,37 @@ fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn block_filter_pairing() {
+        let block_hash = BlockHash::from_byte_array([1; 32]);
+        let filter = BlockFilter::new(&[0x01, 0x7e, 0x5c, 0xe0]);
+        let message = CFilter::new_basic(block_hash, filter.clone());
+        assert_eq!(message.filter_type, BASIC_FILTER_TYPE);
+        assert_eq!(message.filter_hash(), FilterHash::from(&filter));
+        assert_eq!(message.to_block_filter(), filter);
+        assert_eq!(message.into_block_filter(), filter);
+    }
+
+    #[test]
+    fn cfheaders_filter_headers() {
+        let previous_filter_header = FilterHeader::from_byte_array([2; 32]);
+        let filter_hashes =
+            vec![FilterHash::from_byte_array([3; 32]), FilterHash::from_byte_array([4; 32])];
+        let message = CFHeaders {
+            filter_type: BASIC_FILTER_TYPE,
+            stop_hash: BlockHash::from_byte_array([1; 32]),
+            previous_filter_header,
+            filter_hashes: filter_hashes.clone(),
+        };
+        let first = filter_hashes[0].filter_header(previous_filter_header);
+        assert_eq!(message.filter_headers(), [first, filter_hashes[1].filter_header(first)]);
+        assert!(CFHeaders { filter_hashes: vec![], ..message }.filter_headers().is_empty());
+    }
+}
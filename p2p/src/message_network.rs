@@ -88,6 +88,155 @@ pub fn new(
     }
 }
 
+impl VersionMessage {
+    /// Returns a builder for a `version` message.
+    ///
+    /// See [`VersionMessageBuilder`] for the defaults.
+    pub fn builder() -> VersionMessageBuilder { VersionMessageBuilder::new() }
+}
+
+/// A builder for a [`VersionMessage`].
+///
+/// By default the message advertises [`ProtocolVersion::WTXID_RELAY_VERSION`] and no services,
+/// uses [`Address::useless`] for both addresses, a start height of zero and does not request
+/// relay. Unless set explicitly the timestamp is the current time, the nonce is random and the
+/// user agent names this crate.
+#[derive(Debug, Clone)]
+pub struct VersionMessageBuilder {
+    version: ProtocolVersion,
+    services: ServiceFlags,
+    timestamp: Option<i64>,
+    receiver: Address,
+    sender: Address,
+    nonce: Option<u64>,
+    user_agent: Option<UserAgent>,
+    start_height: i32,
+    relay: bool,
+}
+
+impl VersionMessageBuilder {
+    /// Constructs a new builder with the default values.
+    pub fn new() -> Self {
+        Self {
+            version: ProtocolVersion::WTXID_RELAY_VERSION,
+            services: ServiceFlags::NONE,
+            timestamp: None,
+            receiver: Address::useless(),
+            sender: Address::useless(),
+            nonce: None,
+            user_agent: None,
+            start_height: 0,
+            relay: false,
+        }
+    }
+
+    /// Sets the protocol version.
+    #[must_use]
+    pub fn version(mut self, version: ProtocolVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the services supported by this node.
+    #[must_use]
+    pub fn services(mut self, services: ServiceFlags) -> Self {
+        self.services = services;
+        self
+    }
+
+    /// Sets the timestamp, in seconds since the Unix epoch.
+    #[must_use]
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets the network address of the peer receiving the message.
+    #[must_use]
+    pub fn receiver(mut self, receiver: Address) -> Self {
+        self.receiver = receiver;
+        self
+    }
+
+    /// Sets the network address of this node.
+    #[must_use]
+    pub fn sender(mut self, sender: Address) -> Self {
+        self.sender = sender;
+        self
+    }
+
+    /// Sets the nonce used to detect connections to self.
+    #[must_use]
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Sets the user agent.
+    ///
+    /// Use [`UserAgent::new`] or parse a string to get a user agent in the BIP-0014 format.
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: UserAgent) -> Self {
+        self.user_agent = Some(user_agent);
+        self
+    }
+
+    /// Sets the height of the best chain known to this node.
+    #[must_use]
+    pub fn start_height(mut self, start_height: i32) -> Self {
+        self.start_height = start_height;
+        self
+    }
+
+    /// Sets whether the receiving peer should relay transactions to this node.
+    #[must_use]
+    pub fn relay(mut self, relay: bool) -> Self {
+        self.relay = relay;
+        self
+    }
+
+    /// Builds the `version` message.
+    pub fn build(self) -> VersionMessage {
+        let timestamp = self.timestamp.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+        });
+        let nonce = self.nonce.unwrap_or_else(|| {
+            use std::hash::{BuildHasher as _, Hasher as _};
+
+            // The hasher is randomly seeded per process and per instance.
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+            hasher.write_i64(timestamp);
+            hasher.finish()
+        });
+        let user_agent = self.user_agent.unwrap_or_else(|| {
+            UserAgent::from_nonstandard(&concat!(
+                "/",
+                env!("CARGO_PKG_NAME"),
+                ":",
+                env!("CARGO_PKG_VERSION"),
+                "/"
+            ))
+        });
+        VersionMessage {
+            version: self.version,
+            services: self.services,
+            timestamp,
+            receiver: self.receiver,
+            sender: self.sender,
+            nonce,
+            user_agent,
+            start_height: self.start_height,
+            relay: self.relay,
+        }
+    }
+}
+
+impl Default for VersionMessageBuilder {
+    fn default() -> Self { Self::new() }
+}
+
 impl_consensus_encoding!(
     VersionMessage,
     version,
@@ -256,6 +405,66 @@ impl From<UserAgent> for String {
     fn from(agent: UserAgent) -> Self { agent.user_agent }
 }
 
+impl core::str::FromStr for UserAgent {
+    type Err = ParseUserAgentError;
+
+    /// Parses a user agent in the BIP-0014 format, for example `/Satoshi:0.17.1(Linux)/`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = s.chars().count();
+        if len > Self::MAX_USER_AGENT_LEN {
+            return Err(ParseUserAgentError::TooLong(len));
+        }
+        let Some(clients) = s.strip_prefix('/').and_then(|s| s.strip_suffix('/')) else {
+            return Err(ParseUserAgentError::InvalidFormat);
+        };
+        let valid = |s: &str| !s.is_empty() && !s.contains(['/', '(', ')', ':']);
+        let valid_client = |client: &str| {
+            let Some((name, version)) = client.split_once(':') else { return false };
+            let (version, comments) = match version.split_once('(') {
+                Some((version, comments)) => match comments.strip_suffix(')') {
+                    Some(comments) => (version, Some(comments)),
+                    None => return false,
+                },
+                None => (version, None),
+            };
+            valid(name) && valid(version) && comments.map_or(true, |c| c.split("; ").all(valid))
+        };
+        if clients.split('/').all(valid_client) {
+            Ok(Self { user_agent: s.to_string() })
+        } else {
+            Err(ParseUserAgentError::InvalidFormat)
+        }
+    }
+}
+
+/// An error parsing a [`UserAgent`] in the BIP-0014 format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseUserAgentError {
+    /// The user agent exceeds 256 characters.
+    TooLong(usize),
+    /// The user agent is not of the form `/Name:Version(Comments)/`.
+    InvalidFormat,
+}
+
+impl From<Infallible> for ParseUserAgentError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ParseUserAgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLong(len) => write!(f, "user agent of {} characters exceeds 256", len),
+            Self::InvalidFormat => write!(f, "user agent is not in the BIP-0014 format"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseUserAgentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
 /// A software version field for inclusion in a user agent specified by BIP-0014.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UserAgentVersion {
@@ -794,6 +1003,65 @@ fn version_message_test() {
         assert_eq!(serialize(&real_decode), from_sat);
     }
 
+    #[test]
+    fn version_message_builder() {
+        let msg = VersionMessage::builder().build();
+        assert_eq!(msg.version, ProtocolVersion::WTXID_RELAY_VERSION);
+        assert_eq!(msg.services, ServiceFlags::NONE);
+        assert!(msg.timestamp > 1_700_000_000);
+        assert!(!msg.relay);
+        assert!(msg.user_agent.to_string().parse::<UserAgent>().is_ok());
+
+        let user_agent = "/Satoshi:0.9.99/".parse::<UserAgent>().unwrap();
+        let msg = VersionMessage::builder()
+            .version(ProtocolVersion::from_nonstandard(70002))
+            .services(ServiceFlags::NETWORK)
+            .timestamp(1_401_217_254)
+            .nonce(16_735_069_437_859_780_935)
+            .user_agent(user_agent.clone())
+            .start_height(302_892)
+            .relay(true)
+            .build();
+        let want = VersionMessage {
+            relay: true,
+            ..VersionMessage::new(
+                ProtocolVersion::from_nonstandard(70002),
+                ServiceFlags::NETWORK,
+                1_401_217_254,
+                Address::useless(),
+                Address::useless(),
+                16_735_069_437_859_780_935,
+                user_agent,
+                302_892,
+            )
+        };
+        assert_eq!(msg, want);
+    }
+
+    #[test]
+    fn user_agent_from_str() {
+        for valid in
+            ["/Satoshi:0.17.1/", "/Satoshi:5.64/bitcoin-qt:0.4(Linux; x86_64)/BitcoinJ:0.2(iPad)/"]
+        {
+            assert_eq!(valid.parse::<UserAgent>().unwrap().to_string(), valid);
+        }
+        let user_agent = UserAgent::new(
+            "Satoshi",
+            &UserAgentVersion::new(ClientSoftwareVersion::Date { yyyy: 2024, mm: 1, dd: 2 })
+                .push_comment("Linux")
+                .push_comment("x86_64"),
+        );
+        assert_eq!(user_agent.to_string().parse::<UserAgent>(), Ok(user_agent));
+
+        for invalid in
+            ["", "/", "Satoshi:0.17.1", "/Satoshi/", "/:0.17.1/", "/Satoshi:0.17.1(Linux/"]
+        {
+            assert_eq!(invalid.parse::<UserAgent>(), Err(ParseUserAgentError::InvalidFormat));
+        }
+        let long = format!("/Satoshi:{}/", "1".repeat(256));
+        assert_eq!(long.parse::<UserAgent>(), Err(ParseUserAgentError::TooLong(266)));
+    }
+
     #[test]
     fn reject_message_test() {
         let reject_tx_conflict = hex!("027478121474786e2d6d656d706f6f6c2d636f6e666c69637405df54d3860b3c41806a3546ab48279300affacf4b88591b229141dcf2f47004");
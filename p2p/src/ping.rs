@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Bookkeeping for `ping` and `pong` messages.
+//!
+//! [`PingState`] tracks the ping outstanding with a peer, measures latency when the matching
+//! `pong` arrives and decides when the peer has timed out. Time is passed in by the caller as the
+//! duration since any fixed point, for example the elapsed time of an `Instant` taken at startup.
+
+use core::time::Duration;
+
+/// The state of `ping` and `pong` messages exchanged with a single peer.
+///
+/// Like Bitcoin Core at most one ping is outstanding at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingState {
+    timeout: Duration,
+    /// The nonce and send time of the outstanding ping.
+    outstanding: Option<(u64, Duration)>,
+    last_sent: Option<Duration>,
+    latency: Option<Duration>,
+    min_latency: Option<Duration>,
+}
+
+impl PingState {
+    /// The timeout used by Bitcoin Core, 20 minutes.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(20 * 60);
+
+    /// The interval between pings used by Bitcoin Core, 2 minutes.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(2 * 60);
+
+    /// Constructs a new state for a peer that times out `timeout` after a ping is sent.
+    pub const fn new(timeout: Duration) -> Self {
+        Self { timeout, outstanding: None, last_sent: None, latency: None, min_latency: None }
+    }
+
+    /// Returns `true` if no ping is outstanding and none was sent in the last `interval`.
+    pub fn needs_ping(&self, now: Duration, interval: Duration) -> bool {
+        self.outstanding.is_none()
+            && self.last_sent.map_or(true, |sent| now.saturating_sub(sent) >= interval)
+    }
+
+    /// Records a ping with `nonce` sent at `now`.
+    ///
+    /// Returns `false`, recording nothing, if a ping is already outstanding or `nonce` is zero.
+    /// Peers answer with a zero nonce to cancel a ping so it cannot identify one.
+    pub fn start_ping(&mut self, nonce: u64, now: Duration) -> bool {
+        if self.outstanding.is_some() || nonce == 0 {
+            return false;
+        }
+        self.outstanding = Some((nonce, now));
+        self.last_sent = Some(now);
+        true
+    }
+
+    /// Records a pong with `nonce` received at `now`.
+    pub fn receive_pong(&mut self, nonce: u64, now: Duration) -> PongOutcome {
+        let Some((sent_nonce, sent)) = self.outstanding else { return PongOutcome::Unsolicited };
+        if nonce == sent_nonce {
+            self.outstanding = None;
+            let latency = now.saturating_sub(sent);
+            self.latency = Some(latency);
+            self.min_latency = Some(self.min_latency.map_or(latency, |min| min.min(latency)));
+            PongOutcome::Matched(latency)
+        } else if nonce == 0 {
+            self.outstanding = None;
+            PongOutcome::Cancelled
+        } else {
+            PongOutcome::Mismatched
+        }
+    }
+
+    /// Returns `true` if the outstanding ping was sent more than the timeout before `now`.
+    pub fn is_timed_out(&self, now: Duration) -> bool {
+        match self.outstanding {
+            Some((_, sent)) => now.saturating_sub(sent) > self.timeout,
+            None => false,
+        }
+    }
+
+    /// Returns the nonce of the outstanding ping.
+    pub fn outstanding_nonce(&self) -> Option<u64> { self.outstanding.map(|(nonce, _)| nonce) }
+
+    /// Returns the latency measured by the last answered ping.
+    pub fn latency(&self) -> Option<Duration> { self.latency }
+
+    /// Returns the lowest latency measured.
+    pub fn min_latency(&self) -> Option<Duration> { self.min_latency }
+}
+
+impl Default for PingState {
+    fn default() -> Self { Self::new(Self::DEFAULT_TIMEOUT) }
+}
+
+/// The outcome of receiving a `pong`, see [`PingState::receive_pong`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PongOutcome {
+    /// The pong answers the outstanding ping, with the measured latency.
+    Matched(Duration),
+    /// The pong has a zero nonce, the outstanding ping is cancelled.
+    Cancelled,
+    /// The nonce does not match the outstanding ping, which remains outstanding.
+    Mismatched,
+    /// No ping is outstanding.
+    Unsolicited,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn secs(secs: u64) -> Duration { Duration::from_secs(secs) }
+
+    #[test]
+    fn ping_pong() {
+        let mut state = PingState::new(secs(10));
+        assert!(state.needs_ping(secs(0), secs(5)));
+        assert_eq!(state.receive_pong(1, secs(0)), PongOutcome::Unsolicited);
+
+        assert!(!state.start_ping(0, secs(0)));
+        assert!(state.start_ping(1, secs(0)));
+        assert!(!state.start_ping(2, secs(1)));
+        assert_eq!(state.outstanding_nonce(), Some(1));
+        assert!(!state.needs_ping(secs(100), secs(5)));
+
+        assert_eq!(state.receive_pong(2, secs(2)), PongOutcome::Mismatched);
+        assert_eq!(state.receive_pong(1, secs(3)), PongOutcome::Matched(secs(3)));
+        assert_eq!(state.outstanding_nonce(), None);
+        assert_eq!(state.receive_pong(1, secs(3)), PongOutcome::Unsolicited);
+
+        // The interval counts from when the last ping was sent.
+        assert!(!state.needs_ping(secs(4), secs(5)));
+        assert!(state.needs_ping(secs(5), secs(5)));
+        assert!(state.start_ping(3, secs(5)));
+        assert_eq!(state.receive_pong(3, secs(6)), PongOutcome::Matched(secs(1)));
+        assert_eq!(state.latency(), Some(secs(1)));
+        assert!(state.start_ping(4, secs(10)));
+        assert_eq!(state.receive_pong(4, secs(12)), PongOutcome::Matched(secs(2)));
+        assert_eq!(state.latency(), Some(secs(2)));
+        assert_eq!(state.min_latency(), Some(secs(1)));
+
+        assert!(state.start_ping(5, secs(20)));
+        assert_eq!(state.receive_pong(0, secs(21)), PongOutcome::Cancelled);
+        assert_eq!(state.outstanding_nonce(), None);
+        assert_eq!(state.latency(), Some(secs(2)));
+    }
+
+    #[test]
+    fn timeout() {
+        let mut state = PingState::new(secs(10));
+        assert!(!state.is_timed_out(secs(100)));
+
+        assert!(state.start_ping(1, secs(100)));
+        assert!(!state.is_timed_out(secs(99)));
+        assert!(!state.is_timed_out(secs(110)));
+        assert!(state.is_timed_out(secs(110) + Duration::from_nanos(1)));
+
+        assert_eq!(state.receive_pong(1, secs(200)), PongOutcome::Matched(secs(100)));
+        assert!(!state.is_timed_out(secs(200)));
+    }
+}
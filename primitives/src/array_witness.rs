@@ -0,0 +1,526 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! A fixed-capacity witness.
+//!
+//! This module contains the [`ArrayWitness`] struct, a witness that stores its elements in an
+//! inline buffer of `N` bytes instead of on the heap. It is available without the `alloc` feature
+//! so that firmware without an allocator (e.g. hardware signers) can construct, encode and decode
+//! witnesses.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use encoding::{
+    self, BytesEncoder, CompactSizeDecoder, CompactSizeDecoderError, CompactSizeEncoder, Decoder,
+    Encodable, Encoder, Encoder2,
+};
+use internals::array_vec::ArrayVec;
+use internals::write_err;
+
+#[cfg(feature = "alloc")]
+use crate::Witness;
+
+/// A witness stored in a fixed-capacity buffer of `N` bytes.
+///
+/// `N` bounds the serialized size of the elements, including the [`CompactSize`] length prefix of
+/// each element but excluding the prefix holding the number of elements. A P2WPKH witness for
+/// example needs at most 109 bytes (a 73 byte signature and a 33 byte public key).
+///
+/// [`CompactSize`]: <https://en.bitcoin.it/wiki/Protocol_documentation#Variable_length_integer>
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArrayWitness<const N: usize> {
+    /// The serialized elements, each prefixed with its length.
+    content: ArrayVec<u8, N>,
+    /// The number of elements in the witness.
+    witness_elements: usize,
+}
+
+impl<const N: usize> ArrayWitness<N> {
+    /// Constructs a new empty [`ArrayWitness`].
+    #[inline]
+    pub const fn new() -> Self { Self { content: ArrayVec::new(), witness_elements: 0 } }
+
+    /// Constructs a new [`ArrayWitness`] from a slice of elements.
+    ///
+    /// # Errors
+    ///
+    /// If the elements do not fit into `N` bytes.
+    pub fn from_slice<T: AsRef<[u8]>>(slice: &[T]) -> Result<Self, CapacityError> {
+        let mut witness = Self::new();
+        for element in slice {
+            witness.push(element)?;
+        }
+        Ok(witness)
+    }
+
+    /// Returns the capacity of the buffer in bytes.
+    #[inline]
+    pub const fn capacity(&self) -> usize { N }
+
+    /// Returns `true` if the witness contains no element.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.witness_elements == 0 }
+
+    /// Returns the number of elements this witness holds.
+    #[inline]
+    pub const fn len(&self) -> usize { self.witness_elements }
+
+    /// Returns a struct implementing [`Iterator`].
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { remaining: self.content.as_slice(), elements: self.witness_elements }
+    }
+
+    /// Returns the number of bytes this witness contributes to a transactions total size.
+    pub fn size(&self) -> usize {
+        CompactSizeEncoder::encoded_size(self.witness_elements) + self.content.len()
+    }
+
+    /// Clears the witness.
+    pub fn clear(&mut self) {
+        self.content = ArrayVec::new();
+        self.witness_elements = 0;
+    }
+
+    /// Pushes a new element on the witness.
+    ///
+    /// # Errors
+    ///
+    /// If the element does not fit into the remaining capacity, in which case the witness is left
+    /// unchanged.
+    pub fn push<T: AsRef<[u8]>>(&mut self, new_element: T) -> Result<(), CapacityError> {
+        let new_element = new_element.as_ref();
+        let prefix = crate::compact_size_encode(new_element.len());
+        let required = self.content.len() + prefix.len() + new_element.len();
+        if required > N {
+            return Err(CapacityError { required, capacity: N });
+        }
+        self.content.extend_from_slice(&prefix);
+        self.content.extend_from_slice(new_element);
+        self.witness_elements += 1;
+        Ok(())
+    }
+
+    /// Returns the last element in the witness, if any.
+    pub fn last(&self) -> Option<&[u8]> { self.iter().last() }
+
+    /// Returns the witness element at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&[u8]> { self.iter().nth(index) }
+
+    /// Converts the witness into a heap-allocated [`Witness`].
+    #[cfg(feature = "alloc")]
+    pub fn to_witness(&self) -> Witness { self.iter().collect() }
+}
+
+impl<const N: usize> Default for ArrayWitness<N> {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+/// An iterator returning individual elements of an [`ArrayWitness`].
+#[derive(Clone)]
+pub struct Iter<'a> {
+    remaining: &'a [u8],
+    elements: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.elements == 0 {
+            return None;
+        }
+        let (len, prefix_len) = read_length_prefix(self.remaining);
+        let (element, rest) = self.remaining[prefix_len..].split_at(len);
+        self.remaining = rest;
+        self.elements -= 1;
+        Some(element)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) { (self.elements, Some(self.elements)) }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a, const N: usize> IntoIterator for &'a ArrayWitness<N> {
+    type IntoIter = Iter<'a>;
+    type Item = &'a [u8];
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Reads a length prefix written by [`ArrayWitness::push`], returning the length and the size of
+/// the prefix.
+fn read_length_prefix(bytes: &[u8]) -> (usize, usize) {
+    fn le<const LEN: usize>(bytes: &[u8]) -> u64 {
+        let mut buf = [0_u8; 8];
+        buf[..LEN].copy_from_slice(&bytes[1..=LEN]);
+        u64::from_le_bytes(buf)
+    }
+
+    let (len, prefix_len) = match bytes[0] {
+        0xFD => (le::<2>(bytes), 3),
+        0xFE => (le::<4>(bytes), 5),
+        0xFF => (le::<8>(bytes), 9),
+        n => (u64::from(n), 1),
+    };
+    // The element was pushed from a slice in memory so its length fits in a `usize`.
+    (usize::try_from(len).expect("length of an in-memory element"), prefix_len)
+}
+
+/// The encoder for the [`ArrayWitness`] type.
+pub struct ArrayWitnessEncoder<'e>(Encoder2<CompactSizeEncoder, BytesEncoder<'e>>);
+
+impl<const N: usize> Encodable for ArrayWitness<N> {
+    type Encoder<'e>
+        = ArrayWitnessEncoder<'e>
+    where
+        Self: 'e;
+
+    fn encoder(&self) -> Self::Encoder<'_> {
+        let num_elements = CompactSizeEncoder::new(self.witness_elements);
+        let witness_elements = BytesEncoder::without_length_prefix(self.content.as_slice());
+
+        ArrayWitnessEncoder(Encoder2::new(num_elements, witness_elements))
+    }
+}
+
+impl Encoder for ArrayWitnessEncoder<'_> {
+    #[inline]
+    fn current_chunk(&self) -> &[u8] { self.0.current_chunk() }
+
+    #[inline]
+    fn advance(&mut self) -> bool { self.0.advance() }
+}
+
+/// The decoder for the [`ArrayWitness`] type.
+pub struct ArrayWitnessDecoder<const N: usize> {
+    /// The witness being decoded.
+    witness: ArrayWitness<N>,
+    /// Decoder for the initial witness element count.
+    witness_count_decoder: CompactSizeDecoder,
+    /// Total number of witness elements to decode (None until initial count is read).
+    witness_elements: Option<usize>,
+    /// Decoder for the current element's length.
+    element_length_decoder: CompactSizeDecoder,
+    /// Bytes remaining to read for the current element's data.
+    /// - `None` means we're currently reading the length.
+    /// - `Some(n)` means we're reading element data with `n` bytes remaining.
+    element_bytes_remaining: Option<usize>,
+}
+
+impl<const N: usize> ArrayWitnessDecoder<N> {
+    /// Constructs a new witness decoder.
+    pub const fn new() -> Self {
+        Self {
+            witness: ArrayWitness::new(),
+            witness_count_decoder: CompactSizeDecoder::new(),
+            witness_elements: None,
+            element_length_decoder: CompactSizeDecoder::new(),
+            element_bytes_remaining: None,
+        }
+    }
+}
+
+impl<const N: usize> Default for ArrayWitnessDecoder<N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<const N: usize> Decoder for ArrayWitnessDecoder<N> {
+    type Output = ArrayWitness<N>;
+    type Error = ArrayWitnessDecoderError;
+
+    fn push_bytes(&mut self, bytes: &mut &[u8]) -> Result<bool, Self::Error> {
+        use {ArrayWitnessDecoderError as E, ArrayWitnessDecoderErrorInner as Inner};
+
+        // Read initial witness element count.
+        if self.witness_elements.is_none() {
+            if self
+                .witness_count_decoder
+                .push_bytes(bytes)
+                .map_err(|e| E(Inner::LengthPrefixDecode(e)))?
+            {
+                return Ok(true);
+            }
+            // Take ownership of the decoder in order to consume it.
+            let decoder = core::mem::take(&mut self.witness_count_decoder);
+            let witness_elements = decoder.end().map_err(|e| E(Inner::LengthPrefixDecode(e)))?;
+            self.witness_elements = Some(witness_elements);
+        }
+
+        let Some(witness_elements) = self.witness_elements else {
+            unreachable!("witness_elements must be Some after initial read")
+        };
+
+        // Read witness elements.
+        loop {
+            if self.witness.witness_elements >= witness_elements {
+                return Ok(false);
+            }
+
+            if let Some(bytes_to_read) = self.element_bytes_remaining {
+                if bytes.is_empty() {
+                    return Ok(true);
+                }
+                let can_copy = bytes.len().min(bytes_to_read);
+                // Capacity was checked when the length prefix was read.
+                self.witness.content.extend_from_slice(&bytes[..can_copy]);
+                *bytes = &bytes[can_copy..];
+
+                if can_copy == bytes_to_read {
+                    self.witness.witness_elements += 1;
+                    self.element_bytes_remaining = None;
+                } else {
+                    self.element_bytes_remaining = Some(bytes_to_read - can_copy);
+                }
+            } else {
+                if bytes.is_empty()
+                    || self
+                        .element_length_decoder
+                        .push_bytes(bytes)
+                        .map_err(|e| E(Inner::LengthPrefixDecode(e)))?
+                {
+                    return Ok(true);
+                }
+                let decoder = core::mem::take(&mut self.element_length_decoder);
+                let element_length = decoder.end().map_err(|e| E(Inner::LengthPrefixDecode(e)))?;
+
+                let prefix = crate::compact_size_encode(element_length);
+                let required = self.witness.content.len() + prefix.len() + element_length;
+                if required > N {
+                    return Err(E(Inner::Capacity(CapacityError { required, capacity: N })));
+                }
+                self.witness.content.extend_from_slice(&prefix);
+
+                if element_length == 0 {
+                    self.witness.witness_elements += 1;
+                } else {
+                    self.element_bytes_remaining = Some(element_length);
+                }
+            }
+        }
+    }
+
+    fn end(self) -> Result<Self::Output, Self::Error> {
+        use {ArrayWitnessDecoderError as E, ArrayWitnessDecoderErrorInner as Inner};
+
+        let Some(witness_elements) = self.witness_elements else {
+            // Never read the witness element count.
+            return Err(E(Inner::UnexpectedEof { missing_elements: 0 }));
+        };
+
+        let missing_elements = witness_elements - self.witness.witness_elements;
+        if missing_elements == 0 {
+            Ok(self.witness)
+        } else {
+            Err(E(Inner::UnexpectedEof { missing_elements }))
+        }
+    }
+
+    fn read_limit(&self) -> usize {
+        if self.witness_elements.is_none() {
+            self.witness_count_decoder.read_limit()
+        } else {
+            match self.element_bytes_remaining {
+                None => self.element_length_decoder.read_limit(),
+                Some(remaining) => remaining,
+            }
+        }
+    }
+}
+
+impl<const N: usize> encoding::Decodable for ArrayWitness<N> {
+    type Decoder = ArrayWitnessDecoder<N>;
+    fn decoder() -> Self::Decoder { ArrayWitnessDecoder::default() }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<&ArrayWitness<N>> for Witness {
+    fn from(witness: &ArrayWitness<N>) -> Self { witness.to_witness() }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> TryFrom<&Witness> for ArrayWitness<N> {
+    type Error = CapacityError;
+
+    fn try_from(witness: &Witness) -> Result<Self, Self::Error> {
+        let mut array_witness = Self::new();
+        for element in witness {
+            array_witness.push(element)?;
+        }
+        Ok(array_witness)
+    }
+}
+
+/// The elements of an [`ArrayWitness`] do not fit into its buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    /// The number of bytes required to hold the elements.
+    required: usize,
+    /// The capacity of the buffer.
+    capacity: usize,
+}
+
+impl CapacityError {
+    /// Returns the number of bytes that would have been required to hold the elements.
+    pub fn required(&self) -> usize { self.required }
+
+    /// Returns the capacity of the buffer.
+    pub fn capacity(&self) -> usize { self.capacity }
+}
+
+impl From<Infallible> for CapacityError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "witness requires {} bytes but the buffer only holds {} bytes",
+            self.required, self.capacity
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
+
+/// An error when consensus decoding an [`ArrayWitness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayWitnessDecoderError(ArrayWitnessDecoderErrorInner);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ArrayWitnessDecoderErrorInner {
+    /// Error decoding a length prefix.
+    LengthPrefixDecode(CompactSizeDecoderError),
+    /// The witness does not fit into the buffer.
+    Capacity(CapacityError),
+    /// Not enough bytes given to decoder.
+    UnexpectedEof { missing_elements: usize },
+}
+
+impl From<Infallible> for ArrayWitnessDecoderError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ArrayWitnessDecoderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ArrayWitnessDecoderErrorInner as E;
+
+        match self.0 {
+            E::LengthPrefixDecode(ref e) => write_err!(f, "vec decoder error"; e),
+            E::Capacity(ref e) => write_err!(f, "witness too large"; e),
+            E::UnexpectedEof { missing_elements } =>
+                write!(f, "not enough witness elements for decoder, missing {}", missing_elements),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArrayWitnessDecoderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        use ArrayWitnessDecoderErrorInner as E;
+
+        match self.0 {
+            E::LengthPrefixDecode(ref e) => Some(e),
+            E::Capacity(ref e) => Some(e),
+            E::UnexpectedEof { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding::{decode_from_slice, encode_to_slice};
+
+    use super::*;
+
+    #[test]
+    fn push_and_iterate() {
+        let mut witness = ArrayWitness::<8>::new();
+        assert!(witness.is_empty());
+        witness.push([1, 2]).unwrap();
+        witness.push([]).unwrap();
+        witness.push([3]).unwrap();
+
+        assert_eq!(witness.len(), 3);
+        assert_eq!(witness.size(), 1 + 3 + 1 + 2);
+        assert_eq!(witness.get(0), Some(&[1_u8, 2][..]));
+        assert_eq!(witness.get(1), Some(&[][..]));
+        assert_eq!(witness.last(), Some(&[3_u8][..]));
+        assert_eq!(witness.get(3), None);
+        assert_eq!(witness.iter().len(), 3);
+    }
+
+    #[test]
+    fn push_over_capacity() {
+        let mut witness = ArrayWitness::<5>::new();
+        witness.push([1, 2]).unwrap();
+        let err = witness.push([3, 4]).unwrap_err();
+        assert_eq!(err.required(), 6);
+        assert_eq!(err.capacity(), 5);
+        // A failed push leaves the witness unchanged.
+        assert_eq!(witness.len(), 1);
+        witness.push([3]).unwrap();
+        assert_eq!(witness.len(), 2);
+    }
+
+    #[test]
+    fn long_element_prefix() {
+        let element = [0xab; 300];
+        let witness = ArrayWitness::<303>::from_slice(&[element]).unwrap();
+        assert_eq!(witness.get(0), Some(&element[..]));
+        assert!(ArrayWitness::<302>::from_slice(&[element]).is_err());
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let witness = ArrayWitness::<16>::from_slice(&[&[0xaa; 3][..], &[], &[0xbb; 5]]).unwrap();
+        let mut buffer = [0; 32];
+        let len = encode_to_slice(&witness, &mut buffer).unwrap();
+        assert_eq!(len, witness.size());
+        assert_eq!(buffer[..len], [3, 3, 0xaa, 0xaa, 0xaa, 0, 5, 0xbb, 0xbb, 0xbb, 0xbb, 0xbb]);
+
+        let decoded = decode_from_slice::<ArrayWitness<16>>(&buffer[..len]).unwrap();
+        assert_eq!(decoded, witness);
+    }
+
+    #[test]
+    fn decode_in_chunks() {
+        let bytes = [2, 2, 0xaa, 0xbb, 1, 0xcc];
+        let mut decoder = ArrayWitnessDecoder::<8>::new();
+        for byte in bytes.chunks(1) {
+            let mut chunk = byte;
+            decoder.push_bytes(&mut chunk).unwrap();
+            assert!(chunk.is_empty());
+        }
+        let witness = decoder.end().unwrap();
+        assert_eq!(witness.get(0), Some(&[0xaa_u8, 0xbb][..]));
+        assert_eq!(witness.get(1), Some(&[0xcc_u8][..]));
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(decode_from_slice::<ArrayWitness<8>>(&[]).is_err());
+        // Second element is missing.
+        assert!(decode_from_slice::<ArrayWitness<8>>(&[2, 1, 0xaa]).is_err());
+        // Element does not fit.
+        let mut decoder = ArrayWitnessDecoder::<4>::new();
+        let mut bytes = &[1_u8, 4, 1, 2, 3, 4][..];
+        assert!(decoder.push_bytes(&mut bytes).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn witness_conversions() {
+        let witness = Witness::from_slice(&[&[1_u8, 2][..], &[3]]);
+        let array_witness = ArrayWitness::<8>::try_from(&witness).unwrap();
+        assert_eq!(Witness::from(&array_witness), witness);
+        assert_eq!(encoding::encode_to_vec(&array_witness), encoding::encode_to_vec(&witness));
+        assert!(ArrayWitness::<4>::try_from(&witness).is_err());
+    }
+}
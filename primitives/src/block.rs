@@ -32,9 +32,9 @@
 use crate::time::{BlockTimeDecoder, BlockTimeDecoderError};
 #[cfg(feature = "alloc")]
 use crate::transaction::{TxMerkleNodeDecoder, TxMerkleNodeDecoderError};
-use crate::{BlockTime, CompactTarget, TxMerkleNode};
 #[cfg(feature = "alloc")]
-use crate::{Transaction, WitnessMerkleNode};
+use crate::Transaction;
+use crate::{BlockTime, CompactTarget, TxMerkleNode, WitnessMerkleNode};
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
@@ -169,13 +169,7 @@ pub fn compute_witness_commitment(
         witness_reserved_value: &[u8],
     ) -> Option<(WitnessMerkleNode, WitnessCommitment)> {
         compute_witness_root(&self.transactions).map(|witness_root| {
-            let mut encoder = sha256d::Hash::engine();
-            encoder = hashes::encode_to_engine(&witness_root, encoder);
-            encoder.input(witness_reserved_value);
-            let witness_commitment = WitnessCommitment::from_byte_array(
-                sha256d::Hash::from_engine(encoder).to_byte_array(),
-            );
-            (witness_root, witness_commitment)
+            (witness_root, compute_witness_commitment(witness_root, witness_reserved_value))
         })
     }
 
@@ -340,6 +334,21 @@ fn decoder() -> Self::Decoder {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Block {
+    /// Serializes the consensus encoding, as hex if the serializer is human-readable.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::consensus_serde::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Block {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::consensus_serde::deserialize(deserializer)
+    }
+}
+
 /// An error consensus decoding a [`Block`].
 #[cfg(feature = "alloc")]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -446,21 +455,35 @@ pub fn compute_witness_root(transactions: &[Transaction]) -> Option<WitnessMerkl
     WitnessMerkleNode::calculate_root(hashes)
 }
 
+/// The bytes a witness commitment output script starts with.
+///
+/// Consists of `OP_RETURN`, `OP_PUSHBYTES_36`, and the four "witness header" bytes `0xaa21a9ed`,
+/// the commitment follows.
+pub const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+/// Computes the witness commitment to `witness_root` and the coinbase `witness_reserved_value`.
+///
+/// The commitment is the double SHA-256 of the witness root followed by the reserved value.
+pub fn compute_witness_commitment(
+    witness_root: WitnessMerkleNode,
+    witness_reserved_value: &[u8],
+) -> WitnessCommitment {
+    let mut encoder = sha256d::Hash::engine();
+    encoder = hashes::encode_to_engine(&witness_root, encoder);
+    encoder.input(witness_reserved_value);
+    WitnessCommitment::from_byte_array(sha256d::Hash::from_engine(encoder).to_byte_array())
+}
+
 #[cfg(feature = "alloc")]
 fn witness_commitment_from_coinbase(coinbase: &Transaction) -> Option<WitnessCommitment> {
-    // Consists of OP_RETURN, OP_PUSHBYTES_36, and four "witness header" bytes.
-    const MAGIC: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
-
     if !coinbase.is_coinbase() {
         return None;
     }
 
     // Commitment is in the last output that starts with magic bytes.
-    if let Some(pos) = coinbase
-        .outputs
-        .iter()
-        .rposition(|o| o.script_pubkey.len() >= 38 && o.script_pubkey.as_bytes()[0..6] == MAGIC)
-    {
+    if let Some(pos) = coinbase.outputs.iter().rposition(|o| {
+        o.script_pubkey.len() >= 38 && o.script_pubkey.as_bytes()[0..6] == WITNESS_COMMITMENT_HEADER
+    }) {
         let bytes =
             <[u8; 32]>::try_from(&coinbase.outputs[pos].script_pubkey.as_bytes()[6..38]).unwrap();
         Some(WitnessCommitment::from_byte_array(bytes))
@@ -903,8 +926,32 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(&self.0) }
 #[cfg(feature = "alloc")]
 impl<'a> Arbitrary<'a> for Block {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        let header = Header::arbitrary(u)?;
-        let transactions = Vec::<Transaction>::arbitrary(u)?;
+        use crate::script::ScriptSigBuf;
+        use crate::transaction::{OutPoint, TxIn, TxOut};
+
+        let mut header = Header::arbitrary(u)?;
+
+        // Always start with a coinbase so the block has a chance of passing validation.
+        let script_sig_len = u.int_in_range(2..=100)?;
+        let mut coinbase = Transaction::arbitrary(u)?;
+        coinbase.inputs = alloc::vec![TxIn {
+            previous_output: OutPoint::COINBASE_PREVOUT,
+            script_sig: ScriptSigBuf::from_bytes(crate::arbitrary_bytes(u, script_sig_len)?),
+            sequence: u.arbitrary()?,
+            witness: crate::Witness::new(),
+        }];
+        if coinbase.outputs.is_empty() {
+            coinbase.outputs.push(TxOut::arbitrary(u)?);
+        }
+
+        let mut transactions = alloc::vec![coinbase];
+        transactions.extend(Vec::<Transaction>::arbitrary(u)?);
+
+        // Usually commit to the generated transactions, sometimes keep the arbitrary root.
+        if u.ratio(3, 4)? {
+            header.merkle_root =
+                compute_merkle_root(&transactions).expect("there is at least one transaction");
+        }
         Ok(Self::new_unchecked(header, transactions))
     }
 }
@@ -917,7 +964,12 @@ fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
             prev_blockhash: BlockHash::from_byte_array(u.arbitrary()?),
             merkle_root: TxMerkleNode::from_byte_array(u.arbitrary()?),
             time: u.arbitrary()?,
-            bits: CompactTarget::from_consensus(u.arbitrary()?),
+            // Equally weight the mainnet and regtest proof of work limits and arbitrary targets.
+            bits: match u.int_in_range(0..=2)? {
+                0 => CompactTarget::from_consensus(0x1d00_ffff),
+                1 => CompactTarget::from_consensus(0x207f_ffff),
+                _ => CompactTarget::from_consensus(u.arbitrary()?),
+            },
             nonce: u.arbitrary()?,
         })
     }
@@ -1192,6 +1244,23 @@ fn block_block_hash() {
         assert_eq!(block.block_hash(), header.block_hash());
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn block_serde_roundtrips() {
+        use hex_unstable::DisplayHex as _;
+
+        let block = Block::new_unchecked(dummy_header(), vec![]);
+        let encoded = encoding::encode_to_vec(&block);
+
+        let json = serde_json::to_string(&block).unwrap();
+        assert_eq!(json, format!("\"{}\"", encoded.as_hex()));
+        assert_eq!(serde_json::from_str::<Block>(&json).unwrap(), block);
+
+        let bin = bincode::serialize(&block).unwrap();
+        assert_eq!(bin[8..], encoded[..]);
+        assert_eq!(bincode::deserialize::<Block>(&bin).unwrap(), block);
+    }
+
     #[test]
     fn block_hash_from_header() {
         let header = dummy_header();
@@ -1673,4 +1742,16 @@ fn version_decoder_error() {
         #[cfg(feature = "std")]
         assert!(std::error::Error::source(&err).is_some());
     }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_block_is_structurally_valid() {
+        // The first bytes pick the coinbase and committing to the transactions.
+        let data = [0_u8; 128];
+        let mut u = Unstructured::new(&data);
+        let block = Block::arbitrary(&mut u).unwrap();
+
+        assert!(block.as_parts().1[0].is_coinbase());
+        assert!(block.validate().is_ok());
+    }
 }
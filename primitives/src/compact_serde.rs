@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Compact byte-string serde representations.
+//!
+//! The default `serde` impls pick their representation based on
+//! [`is_human_readable`](serde::Serializer::is_human_readable) and fall back to structs for some
+//! types (e.g. [`OutPoint`]). Several binary formats (notably some CBOR implementations) report
+//! themselves as human-readable, which produces hex strings, and structs become maps keyed by field
+//! name. Neither is compact nor canonical.
+//!
+//! The types and functions in this module always (de)serialize as a single byte string:
+//!
+//! * Hash types: the raw hash bytes (in the same byte order as `as_byte_array`).
+//! * [`OutPoint`]: 36 bytes, the txid followed by the little-endian vout.
+//! * [`ScriptBuf`]: the raw script bytes, without a length prefix.
+//! * [`Transaction`]: the consensus encoding.
+//!
+//! Use [`Compact`] to wrap values, e.g., inside collections, or use this module with
+//! `#[serde(with = "...")]` on a field.
+//!
+//! # Examples
+//!
+//! ```
+//! use bitcoin_primitives::compact_serde::Compact;
+//! use bitcoin_primitives::{OutPoint, Transaction, Txid};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Spend {
+//!     #[serde(with = "bitcoin_primitives::compact_serde")]
+//!     txid: Txid,
+//!     inputs: Vec<Compact<OutPoint>>,
+//!     #[serde(with = "bitcoin_primitives::compact_serde")]
+//!     tx: Transaction,
+//! }
+//! ```
+//!
+//! [`OutPoint`]: crate::OutPoint
+//! [`ScriptBuf`]: crate::script::ScriptBuf
+//! [`Transaction`]: crate::Transaction
+
+// Functions are implementations of a standardized serde-specific signature.
+#![allow(clippy::missing_errors_doc)]
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::hash_types::{ScriptHash, WScriptHash};
+use crate::prelude::{Cow, Vec};
+use crate::script::ScriptBuf;
+use crate::{
+    BlockHash, Ntxid, OutPoint, Transaction, TxMerkleNode, Txid, WitnessCommitment,
+    WitnessMerkleNode, Wtxid,
+};
+
+/// A type that has a compact byte-string representation.
+///
+/// This trait is sealed, it is implemented for the types listed in the [module docs](self).
+pub trait CompactBytes: Sized + sealed::Sealed {
+    /// Returns the compact byte-string representation of `self`.
+    fn to_compact_bytes(&self) -> Cow<'_, [u8]>;
+
+    /// Parses `bytes` as produced by [`CompactBytes::to_compact_bytes`], for use by a deserializer.
+    fn from_compact_bytes<E: de::Error>(bytes: &[u8]) -> Result<Self, E>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Serializes `value` as a byte string.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: CompactBytes,
+    S: Serializer,
+{
+    serializer.serialize_bytes(&value.to_compact_bytes())
+}
+
+/// Deserializes a value from a byte string.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: CompactBytes,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_bytes(CompactVisitor(PhantomData))
+}
+
+/// Wrapper that (de)serializes the inner value using its compact byte-string representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Compact<T>(pub T);
+
+impl<T> Compact<T> {
+    /// Returns the inner value.
+    #[inline]
+    pub fn into_inner(self) -> T { self.0 }
+}
+
+impl<T> From<T> for Compact<T> {
+    #[inline]
+    fn from(value: T) -> Self { Self(value) }
+}
+
+impl<T> Deref for Compact<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T { &self.0 }
+}
+
+impl<T> DerefMut for Compact<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T { &mut self.0 }
+}
+
+impl<T: CompactBytes> Serialize for Compact<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T: CompactBytes> Deserialize<'de> for Compact<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Compact)
+    }
+}
+
+struct CompactVisitor<T>(PhantomData<T>);
+
+impl<'de, T: CompactBytes> de::Visitor<'de> for CompactVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str("a byte string") }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        T::from_compact_bytes(v)
+    }
+
+    // Some formats (e.g. JSON) have no byte strings and serialize bytes as a sequence.
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        T::from_compact_bytes(&bytes)
+    }
+}
+
+macro_rules! impl_compact_bytes_for_hash {
+    ($($ty:ident, $len:expr);* $(;)?) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl CompactBytes for $ty {
+                #[inline]
+                fn to_compact_bytes(&self) -> Cow<'_, [u8]> {
+                    Cow::Borrowed(self.as_byte_array())
+                }
+
+                fn from_compact_bytes<E: de::Error>(bytes: &[u8]) -> Result<Self, E> {
+                    let array = <[u8; $len]>::try_from(bytes)
+                        .map_err(|_| E::invalid_length(bytes.len(), &stringify!($len bytes)))?;
+                    Ok(Self::from_byte_array(array))
+                }
+            }
+        )*
+    };
+}
+impl_compact_bytes_for_hash! {
+    BlockHash, 32;
+    Ntxid, 32;
+    Txid, 32;
+    Wtxid, 32;
+    TxMerkleNode, 32;
+    WitnessMerkleNode, 32;
+    WitnessCommitment, 32;
+    ScriptHash, 20;
+    WScriptHash, 32;
+}
+
+impl sealed::Sealed for OutPoint {}
+
+impl CompactBytes for OutPoint {
+    fn to_compact_bytes(&self) -> Cow<'_, [u8]> { Cow::Owned(encoding::encode_to_vec(self)) }
+
+    fn from_compact_bytes<E: de::Error>(bytes: &[u8]) -> Result<Self, E> {
+        if bytes.len() != Self::SIZE {
+            return Err(E::invalid_length(bytes.len(), &"36 bytes"));
+        }
+        encoding::decode_from_slice(bytes).map_err(E::custom)
+    }
+}
+
+impl<T> sealed::Sealed for ScriptBuf<T> {}
+
+impl<T> CompactBytes for ScriptBuf<T> {
+    #[inline]
+    fn to_compact_bytes(&self) -> Cow<'_, [u8]> { Cow::Borrowed(self.as_bytes()) }
+
+    fn from_compact_bytes<E: de::Error>(bytes: &[u8]) -> Result<Self, E> {
+        Ok(Self::from_bytes(bytes.to_vec()))
+    }
+}
+
+impl sealed::Sealed for Transaction {}
+
+impl CompactBytes for Transaction {
+    fn to_compact_bytes(&self) -> Cow<'_, [u8]> { Cow::Owned(encoding::encode_to_vec(self)) }
+
+    fn from_compact_bytes<E: de::Error>(bytes: &[u8]) -> Result<Self, E> {
+        encoding::decode_from_slice(bytes).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{absolute, Amount, ScriptPubKeyBuf, TxIn, TxOut};
+
+    fn roundtrip_bincode<T>(value: &T) -> Vec<u8>
+    where
+        T: CompactBytes + fmt::Debug + PartialEq + Clone,
+    {
+        let ser = bincode::serialize(&Compact(value.clone())).unwrap();
+        let got = bincode::deserialize::<Compact<T>>(&ser).unwrap();
+        assert_eq!(got.into_inner(), *value);
+        // bincode prefixes byte strings with a u64 length.
+        ser[8..].to_vec()
+    }
+
+    #[test]
+    fn hash_is_raw_bytes() {
+        let txid = Txid::from_byte_array([0xab; 32]);
+        assert_eq!(roundtrip_bincode(&txid), [0xab; 32]);
+
+        let script_hash = ScriptHash::from_byte_array([0xcd; 20]);
+        assert_eq!(roundtrip_bincode(&script_hash), [0xcd; 20]);
+    }
+
+    #[test]
+    fn out_point_is_36_bytes() {
+        let out_point = OutPoint { txid: Txid::from_byte_array([0x11; 32]), vout: 0x0102_0304 };
+        let bytes = roundtrip_bincode(&out_point);
+
+        assert_eq!(bytes.len(), OutPoint::SIZE);
+        assert_eq!(bytes[..32], [0x11; 32]);
+        assert_eq!(bytes[32..], [0x04, 0x03, 0x02, 0x01]);
+    }
+
+    #[test]
+    fn script_has_no_length_prefix() {
+        let script = ScriptPubKeyBuf::from_bytes(vec![0x51, 0x52, 0x93]);
+        assert_eq!(roundtrip_bincode(&script), [0x51, 0x52, 0x93]);
+    }
+
+    #[test]
+    fn transaction_is_consensus_encoded() {
+        let tx = Transaction {
+            version: crate::transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![TxIn {
+                previous_output: OutPoint { txid: Txid::from_byte_array([0x22; 32]), vout: 0 },
+                ..TxIn::EMPTY_COINBASE
+            }],
+            outputs: vec![TxOut {
+                amount: Amount::ONE_BTC,
+                script_pubkey: ScriptPubKeyBuf::from_bytes(vec![0x51]),
+            }],
+        };
+        let bytes = roundtrip_bincode(&tx);
+        assert_eq!(bytes, encoding::encode_to_vec(&tx));
+    }
+
+    #[test]
+    fn field_adapter_ignores_human_readable() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Spend {
+            #[serde(with = "super")]
+            txid: Txid,
+            outputs: Vec<Compact<OutPoint>>,
+        }
+
+        let txid = Txid::from_byte_array([0x01; 32]);
+        let spend = Spend { txid, outputs: vec![Compact(OutPoint { txid, vout: 1 })] };
+
+        // JSON has no byte strings so the bytes show up as an array of numbers, not hex.
+        let json = serde_json::to_value(&spend).unwrap();
+        assert_eq!(json["txid"].as_array().unwrap().len(), 32);
+        assert_eq!(json["outputs"][0].as_array().unwrap().len(), 36);
+        assert_eq!(serde_json::from_value::<Spend>(json).unwrap(), spend);
+    }
+
+    #[test]
+    fn wrong_length_errors() {
+        let ser = bincode::serialize(&Compact(ScriptPubKeyBuf::from_bytes(vec![0; 31]))).unwrap();
+        assert!(bincode::deserialize::<Compact<Txid>>(&ser).is_err());
+        assert!(bincode::deserialize::<Compact<OutPoint>>(&ser).is_err());
+        assert!(bincode::deserialize::<Compact<Transaction>>(&ser).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Serde (de)serialization via consensus encoding.
+//!
+//! The functions in this module (de)serialize any type that is consensus [`Encodable`] and
+//! [`Decodable`] as its consensus encoding. Human-readable serializers get a lower-case hex
+//! string, binary serializers get the raw bytes. This is the strategy used by the `serde` impls
+//! on [`Transaction`], [`Block`] and [`Witness`].
+//!
+//! The submodules [`hex`] and [`bytes`] can be used to force one representation regardless of
+//! what the serializer reports, e.g., to store hex strings in a binary format.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "serde")] {
+//! use bitcoin_primitives::Transaction;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Spend {
+//!     // Hex in JSON, raw bytes in bincode.
+//!     tx: Transaction,
+//!     // Always a hex string.
+//!     #[serde(with = "bitcoin_primitives::consensus_serde::hex")]
+//!     raw: Transaction,
+//! }
+//! # }
+//! ```
+//!
+//! [`Transaction`]: crate::Transaction
+//! [`Block`]: crate::Block
+//! [`Witness`]: crate::Witness
+
+// Functions are implementations of a standardized serde-specific signature.
+#![allow(clippy::missing_errors_doc)]
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use encoding::{Decodable, Decoder, Encodable};
+use serde::de::{self, Unexpected};
+use serde::{Deserializer, Serializer};
+
+use crate::hex_codec::HexPrimitive;
+use crate::prelude::Vec;
+
+/// Serializes `object` as a hex string if the serializer is human-readable, as bytes otherwise.
+pub fn serialize<T, S>(object: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Encodable + Decodable,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        hex::serialize(object, serializer)
+    } else {
+        bytes::serialize(object, serializer)
+    }
+}
+
+/// Deserializes an object from a hex string if the deserializer is human-readable, from bytes
+/// otherwise.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Decodable,
+    <T::Decoder as Decoder>::Error: fmt::Display,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        hex::deserialize(deserializer)
+    } else {
+        bytes::deserialize(deserializer)
+    }
+}
+
+/// (De)serializes consensus encoded objects as hex strings, regardless of the format.
+pub mod hex {
+    use super::{
+        fmt, ConsensusVisitor, Decodable, Decoder, Deserializer, Encodable, HexPrimitive,
+        PhantomData, Serializer,
+    };
+
+    /// Serializes `object` as a lower-case hex string.
+    pub fn serialize<T, S>(object: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Encodable + Decodable,
+        S: Serializer,
+    {
+        serializer.collect_str(&HexPrimitive(object))
+    }
+
+    /// Deserializes an object from a hex string.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Decodable,
+        <T::Decoder as Decoder>::Error: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(ConsensusVisitor(PhantomData))
+    }
+}
+
+/// (De)serializes consensus encoded objects as raw bytes, regardless of the format.
+pub mod bytes {
+    use super::{
+        fmt, ConsensusVisitor, Decodable, Decoder, Deserializer, Encodable, PhantomData, Serializer,
+    };
+
+    /// Serializes `object` as a byte string.
+    pub fn serialize<T, S>(object: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Encodable,
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&encoding::encode_to_vec(object))
+    }
+
+    /// Deserializes an object from a byte string.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Decodable,
+        <T::Decoder as Decoder>::Error: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(ConsensusVisitor(PhantomData))
+    }
+}
+
+/// Visitor that accepts either a hex string or the raw consensus bytes.
+struct ConsensusVisitor<T>(PhantomData<T>);
+
+impl<T> ConsensusVisitor<T>
+where
+    T: Decodable,
+    <T::Decoder as Decoder>::Error: fmt::Display,
+{
+    fn decode<E: de::Error>(bytes: &[u8]) -> Result<T, E> {
+        encoding::decode_from_slice(bytes).map_err(E::custom)
+    }
+}
+
+impl<'de, T> de::Visitor<'de> for ConsensusVisitor<T>
+where
+    T: Decodable,
+    <T::Decoder as Decoder>::Error: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("consensus encoded bytes or a hex string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let bytes = crate::hex::decode_to_vec(v)
+            .map_err(|_| E::invalid_value(Unexpected::Str(v), &"a hex string"))?;
+        Self::decode(&bytes)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> { Self::decode(v) }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        Self::decode(&bytes)
+    }
+}
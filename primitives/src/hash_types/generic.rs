@@ -17,6 +17,52 @@ impl HashType {
         Self(Inner::from_byte_array(bytes))
     }
 
+    /// Constructs a new type from a hex string, in the same byte order as [`Display`].
+    ///
+    /// Intended for constants, an invalid string is a compile time error when this is called in
+    /// const context. To parse untrusted input use `FromStr` instead (requires the "hex" feature).
+    ///
+    /// # Panics
+    ///
+    /// If `hex` is not a valid hex encoding of the hash.
+    ///
+    /// [`Display`]: core::fmt::Display
+    pub const fn from_hex_const(hex: &str) -> Self {
+        let reverse = <Inner as hashes::Hash>::DISPLAY_BACKWARD;
+        Self::from_byte_array(internals::const_tools::hex_to_array(hex, reverse))
+    }
+
+    /// Constructs a new type from bytes in the same byte order as [`Display`].
+    ///
+    /// This is the order used by block explorers and the Bitcoin Core RPC, use
+    /// [`Self::from_byte_array`] for bytes in consensus (internal) order.
+    ///
+    /// [`Display`]: core::fmt::Display
+    pub const fn from_display_bytes(bytes: [u8; LEN]) -> Self {
+        Self::from_byte_array(display_order(bytes))
+    }
+
+    /// Returns the byte array in the same byte order as [`Display`].
+    ///
+    /// [`Display`]: core::fmt::Display
+    pub const fn to_display_bytes(self) -> [u8; LEN] { display_order(self.to_byte_array()) }
+
+    /// Constructs a new type for each of `bytes`, in the same byte order as [`Display`].
+    ///
+    /// [`Display`]: core::fmt::Display
+    #[cfg(feature = "alloc")]
+    pub fn from_display_bytes_slice(bytes: &[[u8; LEN]]) -> alloc::vec::Vec<Self> {
+        bytes.iter().map(|b| Self::from_display_bytes(*b)).collect()
+    }
+
+    /// Returns the byte array of each of `hashes`, in the same byte order as [`Display`].
+    ///
+    /// [`Display`]: core::fmt::Display
+    #[cfg(feature = "alloc")]
+    pub fn to_display_bytes_vec(hashes: &[Self]) -> alloc::vec::Vec<[u8; LEN]> {
+        hashes.iter().map(|h| h.to_display_bytes()).collect()
+    }
+
     /// Returns the underlying byte array.
     pub const fn to_byte_array(self) -> [u8; LEN] { self.0.to_byte_array() }
 
@@ -24,6 +70,20 @@ impl HashType {
     pub const fn as_byte_array(&self) -> &[u8; LEN] { self.0.as_byte_array() }
 }
 
+/// Converts between display and internal byte order, the conversion is its own inverse.
+const fn display_order(mut bytes: [u8; LEN]) -> [u8; LEN] {
+    if <Inner as hashes::Hash>::DISPLAY_BACKWARD {
+        let mut i = 0;
+        while i < LEN / 2 {
+            let tmp = bytes[i];
+            bytes[i] = bytes[LEN - 1 - i];
+            bytes[LEN - 1 - i] = tmp;
+            i += 1;
+        }
+    }
+    bytes
+}
+
 #[cfg(feature = "serde")]
 super::impl_serde!(HashType, LEN);
 super::impl_bytelike_traits!(HashType, LEN);
@@ -6,13 +6,11 @@
 
 mod block_hash;
 mod ntxid;
-#[cfg(feature = "alloc")]
 mod script_hash;
 mod transaction_merkle_node;
 mod txid;
 mod witness_commitment;
 mod witness_merkle_node;
-#[cfg(feature = "alloc")]
 mod witness_script_hash;
 mod wtxid;
 
@@ -26,14 +24,82 @@
     wtxid::Wtxid,
     witness_commitment::WitnessCommitment,
     witness_merkle_node::WitnessMerkleNode,
-};
-#[cfg(feature = "alloc")]
-#[doc(inline)]
-pub use self::{
     script_hash::{RedeemScriptSizeError, ScriptHash},
     witness_script_hash::{WScriptHash, WitnessScriptSizeError},
 };
 
+/// Constructs a [`Txid`](crate::Txid) from a hex string literal, checked at compile time.
+///
+/// The string is in the same byte order as the `Display` output of the type.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin_primitives::{txid, Txid};
+///
+/// // The coinbase transaction of the mainnet genesis block.
+/// const GENESIS_COINBASE: Txid =
+///     txid!("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b");
+/// assert_eq!(GENESIS_COINBASE.as_byte_array()[0], 0x3b);
+/// ```
+///
+/// An invalid string fails to compile.
+///
+/// ```compile_fail
+/// let _ = bitcoin_primitives::txid!("not hex");
+/// ```
+#[macro_export]
+macro_rules! txid {
+    ($hex:literal) => {{
+        const HASH: $crate::Txid = $crate::Txid::from_hex_const($hex);
+        HASH
+    }};
+}
+
+/// Constructs a [`Wtxid`](crate::Wtxid) from a hex string literal, checked at compile time.
+///
+/// The string is in the same byte order as the `Display` output of the type.
+#[macro_export]
+macro_rules! wtxid {
+    ($hex:literal) => {{
+        const HASH: $crate::Wtxid = $crate::Wtxid::from_hex_const($hex);
+        HASH
+    }};
+}
+
+/// Constructs a [`BlockHash`](crate::BlockHash) from a hex string literal, checked at compile time.
+///
+/// The string is in the same byte order as the `Display` output of the type.
+#[macro_export]
+macro_rules! block_hash {
+    ($hex:literal) => {{
+        const HASH: $crate::BlockHash = $crate::BlockHash::from_hex_const($hex);
+        HASH
+    }};
+}
+
+/// Constructs a [`ScriptHash`](crate::script::ScriptHash) from a hex string literal, checked at compile time.
+///
+/// The string is in the same byte order as the `Display` output of the type.
+#[macro_export]
+macro_rules! script_hash {
+    ($hex:literal) => {{
+        const HASH: $crate::script::ScriptHash = $crate::script::ScriptHash::from_hex_const($hex);
+        HASH
+    }};
+}
+
+/// Constructs a [`WScriptHash`](crate::script::WScriptHash) from a hex string literal, checked at compile time.
+///
+/// The string is in the same byte order as the `Display` output of the type.
+#[macro_export]
+macro_rules! wscript_hash {
+    ($hex:literal) => {{
+        const HASH: $crate::script::WScriptHash = $crate::script::WScriptHash::from_hex_const($hex);
+        HASH
+    }};
+}
+
 /// Adds trait impls to a bytelike type.
 ///
 /// Implements:
@@ -258,4 +324,57 @@ fn as_ref_and_borrow_match_as_byte_array() {
         assert_eq!(borrowed, tc.as_byte_array());
         assert_eq!(as_slice, tc.as_byte_array());
     }
+
+    #[test]
+    fn hash_macros() {
+        const HEX: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20";
+
+        // Hashes displayed backwards are reversed, others are not.
+        let txid = crate::txid!("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20");
+        assert_eq!(txid, Txid::from_hex_const(HEX));
+        assert_eq!(txid.as_byte_array()[0], 0x20);
+        let wtxid =
+            crate::wtxid!("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20");
+        assert_eq!(wtxid.as_byte_array()[0], 0x20);
+        let block_hash =
+            crate::block_hash!("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20");
+        assert_eq!(block_hash.as_byte_array()[0], 0x20);
+        let script_hash = crate::script_hash!("0102030405060708090a0b0c0d0e0f1011121314");
+        assert_eq!(script_hash.as_byte_array()[0], 0x01);
+        let wscript_hash = crate::wscript_hash!(
+            "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20"
+        );
+        assert_eq!(wscript_hash.as_byte_array()[0], 0x01);
+
+        #[cfg(feature = "hex")]
+        {
+            assert_eq!(txid, HEX.parse::<Txid>().unwrap());
+            assert_eq!(wscript_hash, HEX.parse::<WScriptHash>().unwrap());
+        }
+    }
+
+    #[test]
+    fn display_bytes() {
+        let mut display = [0; 32];
+        display[0] = 0xff;
+
+        // Hashes displayed backwards are reversed, others are not.
+        let txid = Txid::from_display_bytes(display);
+        assert_eq!(txid.as_byte_array()[31], 0xff);
+        assert_eq!(txid.to_display_bytes(), display);
+        let block_hash = BlockHash::from_display_bytes(display);
+        assert_eq!(block_hash.as_byte_array()[31], 0xff);
+        let wscript_hash = WScriptHash::from_display_bytes(display);
+        assert_eq!(wscript_hash.to_byte_array(), display);
+
+        #[cfg(feature = "hex")]
+        assert_eq!(
+            alloc::format!("{}", txid),
+            "ff00000000000000000000000000000000000000000000000000000000000000"
+        );
+
+        let txids = Txid::from_display_bytes_slice(&[display, [0x11; 32]]);
+        assert_eq!(txids, [txid, Txid::from_byte_array([0x11; 32])]);
+        assert_eq!(Txid::to_display_bytes_vec(&txids), [display, [0x11; 32]]);
+    }
 }
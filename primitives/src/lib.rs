@@ -10,6 +10,10 @@
 //! This crate can be used in a no-std environment but a lot of the functionality requires an
 //! allocator i.e., requires the `alloc` feature to be enabled.
 //!
+//! Without `alloc` the borrowed script types, the hash types and [`ArrayWitness`] are still
+//! available. Together with [`encoding::encode_to_slice`] these allow encoding transaction data
+//! into fixed-capacity buffers, e.g. on hardware signers without an allocator.
+//!
 //! [`rust-bitcoin`]: <https://github.com/rust-bitcoin/rust-bitcoin>
 
 #![no_std]
@@ -41,12 +45,15 @@ pub mod _core {
 }
 
 mod hash_types;
-#[cfg(feature = "alloc")]
 mod opcodes;
 
+pub mod array_witness;
 pub mod block;
+#[cfg(feature = "serde-compact")]
+pub mod compact_serde;
+#[cfg(feature = "serde")]
+pub mod consensus_serde;
 pub mod merkle_tree;
-#[cfg(feature = "alloc")]
 pub mod script;
 pub mod transaction;
 #[cfg(feature = "alloc")]
@@ -70,25 +77,24 @@ pub mod _core {
 #[doc(hidden)]
 pub type BlockInterval = BlockHeightInterval;
 
+#[doc(inline)]
+pub use self::{
+    array_witness::ArrayWitness,
+    block::{BlockHash, Header as BlockHeader, Version as BlockVersion, WitnessCommitment},
+    merkle_tree::{TxMerkleNode, WitnessMerkleNode},
+    script::{RedeemScript, ScriptPubKey, ScriptSig, TapScript, WitnessScript},
+    transaction::{Ntxid, OutPoint, Txid, Version as TransactionVersion, Wtxid},
+};
 #[doc(inline)]
 #[cfg(feature = "alloc")]
 pub use self::{
     block::{
         Block, Checked as BlockChecked, Unchecked as BlockUnchecked, Validation as BlockValidation,
     },
-    script::{
-        RedeemScript, RedeemScriptBuf, ScriptPubKey, ScriptPubKeyBuf, ScriptSig, ScriptSigBuf,
-        TapScript, TapScriptBuf, WitnessScript, WitnessScriptBuf,
-    },
+    script::{RedeemScriptBuf, ScriptPubKeyBuf, ScriptSigBuf, TapScriptBuf, WitnessScriptBuf},
     transaction::{Transaction, TxIn, TxOut},
     witness::Witness,
 };
-#[doc(inline)]
-pub use self::{
-    block::{BlockHash, Header as BlockHeader, Version as BlockVersion, WitnessCommitment},
-    merkle_tree::{TxMerkleNode, WitnessMerkleNode},
-    transaction::{Ntxid, OutPoint, Txid, Version as TransactionVersion, Wtxid},
-};
 
 #[rustfmt::skip]
 #[allow(unused_imports)]
@@ -103,18 +109,24 @@ mod prelude {
     pub use alloc::sync;
 }
 
-#[cfg(feature = "alloc")]
 use encoding::Encoder;
-#[cfg(feature = "alloc")]
 use internals::array_vec::ArrayVec;
 
 // Encode a compact size to a slice without allocating
-#[cfg(feature = "alloc")]
 pub(crate) fn compact_size_encode(value: usize) -> ArrayVec<u8, 9> {
     let encoder = encoding::CompactSizeEncoder::new(value);
     ArrayVec::from_slice(encoder.current_chunk())
 }
 
+/// Returns `len` arbitrary bytes, padding with zeros instead of failing if `u` runs out of data.
+#[cfg(all(feature = "arbitrary", feature = "alloc"))]
+pub(crate) fn arbitrary_bytes(
+    u: &mut arbitrary::Unstructured<'_>,
+    len: usize,
+) -> arbitrary::Result<prelude::Vec<u8>> {
+    (0..len).map(|_| u.arbitrary::<u8>()).collect()
+}
+
 #[cfg(all(feature = "hex", feature = "alloc"))]
 use core::{convert, fmt};
 
@@ -40,6 +40,13 @@ pub(crate) trait MerkleNode: Copy + PartialEq {
     #[must_use]
     fn combine(&self, other: &Self) -> Self;
 
+    /// Converts the node to the underlying hash.
+    #[cfg(feature = "multi-way")]
+    fn to_hash(self) -> sha256d::Hash;
+    /// Converts a hash to a node.
+    #[cfg(feature = "multi-way")]
+    fn from_hash(hash: sha256d::Hash) -> Self;
+
     /// Given an iterator of leaves, compute the Merkle root.
     ///
     /// Returns `None` if the iterator was empty, or if the transaction list contains
@@ -53,6 +60,55 @@ pub(crate) trait MerkleNode: Copy + PartialEq {
     /// Unless you are certain your transaction list is nonempty and has no duplicates,
     /// you should not unwrap the `Option` returned by this method!
     fn calculate_root<I: Iterator<Item = Self::Leaf>>(iter: I) -> Option<Self> {
+        #[cfg(feature = "multi-way")]
+        {
+            Self::calculate_root_by_level(iter)
+        }
+        #[cfg(not(feature = "multi-way"))]
+        {
+            Self::calculate_root_streaming(iter)
+        }
+    }
+
+    /// Computes the Merkle root one level of the tree at a time.
+    ///
+    /// All pairs of nodes of a level are hashed together using [`sha256d::hash_64_batch`] which
+    /// hashes multiple pairs at once on CPUs that support it. Requires memory linear in the number
+    /// of leaves. Returns the same result as [`MerkleNode::calculate_root_streaming`].
+    #[cfg(feature = "multi-way")]
+    fn calculate_root_by_level<I: Iterator<Item = Self::Leaf>>(iter: I) -> Option<Self> {
+        let mut level = iter.map(|leaf| Self::from_leaf(leaf).to_hash()).collect::<Vec<_>>();
+        if level.is_empty() {
+            return None;
+        }
+
+        let mut pairs = Vec::with_capacity(level.len().div_ceil(2));
+        while level.len() > 1 {
+            pairs.clear();
+            for pair in level.chunks(2) {
+                // Odd nodes are combined with themselves, as in `calculate_root_streaming`.
+                let (left, right) = match pair {
+                    [left, right] if left == right => return None, // See CVE 2012-2459.
+                    [left, right] => (left, right),
+                    [single] => (single, single),
+                    _ => unreachable!("chunks of at most two"),
+                };
+                let mut bytes = [0; 64];
+                bytes[..32].copy_from_slice(left.as_byte_array());
+                bytes[32..].copy_from_slice(right.as_byte_array());
+                pairs.push(bytes);
+            }
+            level.truncate(pairs.len());
+            sha256d::hash_64_batch(&pairs, &mut level);
+        }
+        Some(Self::from_hash(level[0]))
+    }
+
+    /// Computes the Merkle root combining nodes as soon as both children are known.
+    ///
+    /// Requires memory logarithmic in the number of leaves.
+    #[cfg_attr(feature = "multi-way", allow(dead_code))] // Only used in tests.
+    fn calculate_root_streaming<I: Iterator<Item = Self::Leaf>>(iter: I) -> Option<Self> {
         {
             #[cfg(feature = "alloc")]
             let mut stack = Vec::<(usize, Self)>::with_capacity(32);
@@ -119,6 +175,11 @@ impl MerkleNode for TxMerkleNode {
     type Leaf = Txid;
     fn from_leaf(leaf: Self::Leaf) -> Self { Self::from_byte_array(leaf.to_byte_array()) }
 
+    #[cfg(feature = "multi-way")]
+    fn to_hash(self) -> sha256d::Hash { sha256d::Hash::from_byte_array(self.to_byte_array()) }
+    #[cfg(feature = "multi-way")]
+    fn from_hash(hash: sha256d::Hash) -> Self { Self::from_byte_array(hash.to_byte_array()) }
+
     fn combine(&self, other: &Self) -> Self {
         let mut encoder = sha256d::Hash::engine();
         encoder.input(self.as_byte_array());
@@ -130,6 +191,11 @@ impl MerkleNode for WitnessMerkleNode {
     type Leaf = Wtxid;
     fn from_leaf(leaf: Self::Leaf) -> Self { Self::from_byte_array(leaf.to_byte_array()) }
 
+    #[cfg(feature = "multi-way")]
+    fn to_hash(self) -> sha256d::Hash { sha256d::Hash::from_byte_array(self.to_byte_array()) }
+    #[cfg(feature = "multi-way")]
+    fn from_hash(hash: sha256d::Hash) -> Self { Self::from_byte_array(hash.to_byte_array()) }
+
     fn combine(&self, other: &Self) -> Self {
         let mut encoder = sha256d::Hash::engine();
         encoder.input(self.as_byte_array());
@@ -138,6 +204,170 @@ fn combine(&self, other: &Self) -> Self {
     }
 }
 
+/// A hash type that can be used for the nodes of a [`MerkleTree`].
+///
+/// Implemented for the block and witness Merkle tree nodes as well as for [`sha256d::Hash`] so that
+/// application-level trees can use the same construction as Bitcoin blocks.
+pub trait MerkleHash: Copy + PartialEq {
+    /// Combines two child nodes into their parent node.
+    #[must_use]
+    fn combine(left: &Self, right: &Self) -> Self;
+}
+
+impl MerkleHash for TxMerkleNode {
+    fn combine(left: &Self, right: &Self) -> Self { MerkleNode::combine(left, right) }
+}
+
+impl MerkleHash for WitnessMerkleNode {
+    fn combine(left: &Self, right: &Self) -> Self { MerkleNode::combine(left, right) }
+}
+
+impl MerkleHash for sha256d::Hash {
+    fn combine(left: &Self, right: &Self) -> Self {
+        let mut engine = Self::engine();
+        engine.input(left.as_byte_array());
+        engine.input(right.as_byte_array());
+        Self::from_engine(engine)
+    }
+}
+
+/// A binary Merkle tree built the same way as the Merkle tree of the transactions in a block.
+///
+/// If a level of the tree has an odd number of nodes the last node is combined with itself. Since
+/// this makes the tree vulnerable to CVE 2012-2459, trees containing consecutive duplicates that
+/// would be combined with each other are rejected.
+///
+/// # Examples
+///
+/// ```
+/// # use bitcoin_primitives::merkle_tree::MerkleTree;
+/// # use bitcoin_primitives::{TxMerkleNode, Txid};
+/// let txids = [Txid::from_byte_array([0xaa; 32]), Txid::from_byte_array([0xff; 32])];
+/// let tree = MerkleTree::from_leaves(txids.iter().map(|txid| TxMerkleNode::from_leaf(*txid)))
+///     .expect("non-empty and no duplicates");
+/// assert_eq!(Some(tree.root()), TxMerkleNode::calculate_root(txids.into_iter()));
+///
+/// let proof = tree.proof(1).expect("index in range");
+/// assert!(proof.verify(TxMerkleNode::from_leaf(txids[1]), tree.root()));
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree<H> {
+    /// All levels of the tree, starting with the leaves and ending with the root.
+    levels: Vec<Vec<H>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<H: MerkleHash> MerkleTree<H> {
+    /// Builds the tree from its leaves.
+    ///
+    /// Returns `None` if there are no leaves, or if the leaves contain consecutive duplicates which
+    /// would trigger CVE 2012-2459.
+    pub fn from_leaves<I: IntoIterator<Item = H>>(leaves: I) -> Option<Self> {
+        let leaves = leaves.into_iter().collect::<Vec<_>>();
+        if leaves.is_empty() {
+            return None;
+        }
+
+        let mut levels = alloc::vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let mut parents = Vec::with_capacity(level.len().div_ceil(2));
+            for pair in level.chunks(2) {
+                let parent = match pair {
+                    [left, right] if left == right => return None,
+                    [left, right] => H::combine(left, right),
+                    [single] => H::combine(single, single),
+                    _ => unreachable!("chunks of at most two"),
+                };
+                parents.push(parent);
+            }
+            levels.push(parents);
+        }
+        Some(Self { levels })
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> H { self.levels[self.levels.len() - 1][0] }
+
+    /// Returns the leaves of the tree.
+    pub fn leaves(&self) -> &[H] { &self.levels[0] }
+
+    /// Returns the number of levels below the root, i.e. the length of every proof.
+    pub fn depth(&self) -> usize { self.levels.len() - 1 }
+
+    /// Returns a proof that the leaf at `index` is part of the tree.
+    ///
+    /// Returns `None` if `index` is out of range.
+    pub fn proof(&self, index: usize) -> Option<MerkleProof<H>> {
+        if index >= self.leaves().len() {
+            return None;
+        }
+
+        let mut position = index;
+        let siblings = self.levels[..self.depth()]
+            .iter()
+            .map(|level| {
+                // The last node of an odd level is its own sibling.
+                let sibling = level.get(position ^ 1).unwrap_or(&level[position]);
+                position >>= 1;
+                *sibling
+            })
+            .collect();
+        Some(MerkleProof { index, siblings })
+    }
+}
+
+/// A proof that a leaf is part of a [`MerkleTree`].
+///
+/// The proof does not commit to the number of leaves in the tree. Since the last node of an odd
+/// level is combined with itself, a proof for that node also verifies for the (non-existent)
+/// node following it. Users that need to rule this out must check the index against the number
+/// of leaves.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof<H> {
+    index: usize,
+    siblings: Vec<H>,
+}
+
+#[cfg(feature = "alloc")]
+impl<H: MerkleHash> MerkleProof<H> {
+    /// Constructs a new proof from the index of the leaf and the siblings along its path to the
+    /// root, starting at the leaf level.
+    pub fn new(index: usize, siblings: Vec<H>) -> Self { Self { index, siblings } }
+
+    /// Returns the index of the leaf this proof is for.
+    pub fn index(&self) -> usize { self.index }
+
+    /// Returns the siblings along the path from the leaf to the root.
+    pub fn siblings(&self) -> &[H] { &self.siblings }
+
+    /// Computes the root of the tree containing `leaf` at the index of this proof.
+    pub fn compute_root(&self, leaf: H) -> H {
+        let mut position = self.index;
+        let mut node = leaf;
+        for sibling in &self.siblings {
+            node = if position & 1 == 0 {
+                H::combine(&node, sibling)
+            } else {
+                H::combine(sibling, &node)
+            };
+            position >>= 1;
+        }
+        node
+    }
+
+    /// Returns whether `leaf` is at the index of this proof in the tree with the given `root`.
+    pub fn verify(&self, leaf: H, root: H) -> bool {
+        // Indices beyond the depth of the proof cannot be part of the tree.
+        let in_range = u32::try_from(self.siblings.len())
+            .ok()
+            .and_then(|depth| 1_usize.checked_shl(depth))
+            .map_or(true, |width| self.index < width);
+        in_range && self.compute_root(leaf) == root
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::hash_types::*;
@@ -261,6 +491,84 @@ fn tx_merkle_node_oversize_tree() {
         assert_ne!(root, None);
     }
 
+    #[test]
+    #[cfg(feature = "multi-way")]
+    fn by_level_matches_streaming() {
+        use alloc::vec::Vec;
+
+        use super::MerkleNode;
+
+        for len in 1..=40 {
+            let leaves = (0..len).map(|i| Txid::from_byte_array([i; 32]));
+            assert_eq!(
+                TxMerkleNode::calculate_root_by_level(leaves.clone()),
+                TxMerkleNode::calculate_root_streaming(leaves),
+            );
+        }
+
+        // Duplicates in a complete pair are rejected, the self-combined odd node is not.
+        let mut leaves = (0..7).map(|i| Txid::from_byte_array([i; 32])).collect::<Vec<_>>();
+        assert!(TxMerkleNode::calculate_root_by_level(leaves.iter().copied()).is_some());
+        leaves[5] = leaves[4];
+        assert_eq!(TxMerkleNode::calculate_root_by_level(leaves.iter().copied()), None);
+        assert_eq!(TxMerkleNode::calculate_root_streaming(leaves.iter().copied()), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn merkle_tree_root_matches_calculate_root() {
+        use alloc::vec::Vec;
+
+        use super::MerkleTree;
+
+        for len in 1..=20 {
+            let txids = (0..len).map(|i| Txid::from_byte_array([i; 32])).collect::<Vec<_>>();
+            let tree =
+                MerkleTree::from_leaves(txids.iter().map(|txid| TxMerkleNode::from_leaf(*txid)))
+                    .unwrap();
+            assert_eq!(Some(tree.root()), TxMerkleNode::calculate_root(txids.into_iter()));
+            assert_eq!(tree.leaves().len(), usize::from(len));
+        }
+
+        assert!(MerkleTree::<TxMerkleNode>::from_leaves(core::iter::empty()).is_none());
+        let leaf = TxMerkleNode::from_byte_array([1; 32]);
+        assert!(MerkleTree::from_leaves([leaf, leaf]).is_none());
+        assert!(MerkleTree::from_leaves([leaf, leaf.combine(&leaf), leaf]).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn merkle_tree_proofs() {
+        use alloc::vec::Vec;
+
+        use hashes::sha256d;
+
+        use super::{MerkleProof, MerkleTree};
+
+        for len in 1..=13 {
+            let leaves = (0..len).map(|i| sha256d::Hash::hash(&[i])).collect::<Vec<_>>();
+            let tree = MerkleTree::from_leaves(leaves.iter().copied()).unwrap();
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(proof.index(), index);
+                assert_eq!(proof.siblings().len(), tree.depth());
+                assert!(proof.verify(*leaf, tree.root()));
+                assert!(!proof.verify(sha256d::Hash::hash(b"other"), tree.root()));
+            }
+            assert!(tree.proof(leaves.len()).is_none());
+            let proof =
+                MerkleProof::new(1 << tree.depth(), tree.proof(0).unwrap().siblings().to_vec());
+            assert!(!proof.verify(leaves[0], tree.root()));
+        }
+
+        // The proof for the last leaf of an odd level also verifies for the index after it.
+        let leaves =
+            [sha256d::Hash::hash(&[0]), sha256d::Hash::hash(&[1]), sha256d::Hash::hash(&[2])];
+        let tree = MerkleTree::from_leaves(leaves).unwrap();
+        let proof = MerkleProof::new(3, tree.proof(2).unwrap().siblings().to_vec());
+        assert!(proof.verify(leaves[2], tree.root()));
+    }
+
     #[test]
     fn witness_merkle_node_single_leaf() {
         let leaf = Wtxid::from_byte_array([1; 32]);
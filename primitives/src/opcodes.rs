@@ -119,3 +119,28 @@ pub fn fmt_opcode(op: u8, f: &mut fmt::Formatter) -> fmt::Result {
         0xff => f.write_str("OP_INVALIDOPCODE"),
     }
 }
+
+/// Parses a script opcode as formatted by [`fmt_opcode`].
+#[cfg(all(feature = "alloc", feature = "hex"))]
+pub(crate) fn parse_opcode(s: &str) -> Option<u8> {
+    struct Opcode(u8);
+
+    impl fmt::Display for Opcode {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt_opcode(self.0, f) }
+    }
+
+    /// Fails as soon as what is written is not the start of the remaining string.
+    struct Prefix<'a>(&'a str);
+
+    impl fmt::Write for Prefix<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 = self.0.strip_prefix(s).ok_or(fmt::Error)?;
+            Ok(())
+        }
+    }
+
+    (0..=u8::MAX).find(|&op| {
+        let mut rest = Prefix(s);
+        fmt::write(&mut rest, format_args!("{}", Opcode(op))).is_ok() && rest.0.is_empty()
+    })
+}
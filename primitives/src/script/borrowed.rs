@@ -9,7 +9,9 @@
 use arbitrary::{Arbitrary, Unstructured};
 use encoding::{BytesEncoder, CompactSizeEncoder, Encodable, Encoder2};
 
+#[cfg(feature = "alloc")]
 use super::ScriptBuf;
+#[cfg(feature = "alloc")]
 use crate::prelude::{Box, ToOwned, Vec};
 
 internals::transparent_newtype! {
@@ -73,8 +75,11 @@ impl<T> Script<T> {
         /// Treat mutable byte slice as `Script`
         pub fn from_bytes_mut(bytes: &mut _) -> &mut Self;
 
+        #[cfg(feature = "alloc")]
         pub(crate) fn from_boxed_bytes(bytes: Box<_>) -> Box<Self>;
+        #[cfg(feature = "alloc")]
         pub(crate) fn from_rc_bytes(bytes: Rc<_>) -> Rc<Self>;
+        #[cfg(feature = "alloc")]
         pub(crate) fn from_arc_bytes(bytes: Arc<_>) -> Arc<Self>;
     }
 }
@@ -84,6 +89,7 @@ impl<T: 'static> Default for &Script<T> {
     fn default() -> Self { Script::new() }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> ToOwned for Script<T> {
     type Owned = ScriptBuf<T>;
 
@@ -111,10 +117,12 @@ pub fn as_mut_bytes(&mut self) -> &mut [u8] { &mut self.1 }
     /// Returns a copy of the script data.
     ///
     /// This is just the script bytes **not** consensus encoding (which includes a length prefix).
+    #[cfg(feature = "alloc")]
     #[inline]
     pub fn to_vec(&self) -> Vec<u8> { self.as_bytes().to_owned() }
 
     /// Returns a copy of the script data.
+    #[cfg(feature = "alloc")]
     #[inline]
     #[deprecated(since = "0.101.0", note = "use to_vec instead")]
     pub fn to_bytes(&self) -> Vec<u8> { self.to_vec() }
@@ -128,6 +136,7 @@ pub const fn len(&self) -> usize { self.as_bytes().len() }
     pub const fn is_empty(&self) -> bool { self.as_bytes().is_empty() }
 
     /// Converts a [`Box<Script>`](Box) into a [`ScriptBuf`] without copying or allocating.
+    #[cfg(feature = "alloc")]
     #[must_use]
     #[inline]
     pub fn into_script_buf(self: Box<Self>) -> ScriptBuf<T> {
@@ -230,6 +239,7 @@ fn script_from_bytes_mut() {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn script_to_vec() {
         let script = Script::from_bytes(&[1, 2, 3]);
         assert_eq!(script.to_vec(), vec![1, 2, 3]);
@@ -251,6 +261,7 @@ fn script_is_empty() {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn script_to_owned() {
         let script = Script::from_bytes(&[1, 2, 3]);
         let script_buf = script.to_owned();
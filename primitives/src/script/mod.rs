@@ -3,9 +3,11 @@
 //! Bitcoin scripts.
 
 mod borrowed;
+#[cfg(feature = "alloc")]
 mod owned;
 mod tag;
 
+#[cfg(feature = "alloc")]
 use core::cmp::Ordering;
 use core::fmt;
 #[cfg(feature = "serde")]
@@ -15,24 +17,32 @@
 use hex_unstable::DisplayHex;
 use internals::script::{self, PushDataLenLen};
 
+#[cfg(feature = "alloc")]
 use crate::prelude::rc::Rc;
-#[cfg(target_has_atomic = "ptr")]
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
 use crate::prelude::sync::Arc;
+#[cfg(feature = "alloc")]
 use crate::prelude::{Borrow, BorrowMut, Box, Cow, ToOwned, Vec};
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 #[doc(inline)]
 pub use self::{
     borrowed::{Script, ScriptEncoder},
-    owned::{ScriptBuf, ScriptBufDecoder, ScriptBufDecoderError},
     tag::{Tag, RedeemScriptTag, ScriptPubKeyTag, ScriptSigTag, TapScriptTag, WitnessScriptTag},
 };
+#[cfg(all(feature = "alloc", feature = "hex"))]
+#[doc(inline)]
+pub use self::owned::ParseScriptError;
+#[cfg(feature = "alloc")]
+#[doc(inline)]
+pub use self::owned::{ScriptBuf, ScriptBufDecoder, ScriptBufDecoderError};
 #[doc(inline)]
 pub use crate::hash_types::{
     RedeemScriptSizeError, ScriptHash, WScriptHash, WitnessScriptSizeError,
 };
 
 /// A P2SH redeem script.
+#[cfg(feature = "alloc")]
 pub type RedeemScriptBuf = ScriptBuf<RedeemScriptTag>;
 
 /// A reference to a P2SH redeem script.
@@ -45,24 +55,30 @@
 pub type ScriptSig = Script<ScriptSigTag>;
 
 /// A `scriptPubKey` (locking script).
+#[cfg(feature = "alloc")]
 pub type ScriptPubKeyBuf = ScriptBuf<ScriptPubKeyTag>;
 
 /// A `scriptPubKey` decoder.
+#[cfg(feature = "alloc")]
 pub type ScriptPubKeyBufDecoder = ScriptBufDecoder<ScriptPubKeyTag>;
 
 /// A script signature (scriptSig).
+#[cfg(feature = "alloc")]
 pub type ScriptSigBuf = ScriptBuf<ScriptSigTag>;
 
 /// A `scriptSig` decoder.
+#[cfg(feature = "alloc")]
 pub type ScriptSigBufDecoder = ScriptBufDecoder<ScriptSigTag>;
 
 /// A Segwit v1 Taproot script.
+#[cfg(feature = "alloc")]
 pub type TapScriptBuf = ScriptBuf<TapScriptTag>;
 
 /// A reference to a Segwit v1 Taproot script.
 pub type TapScript = Script<TapScriptTag>;
 
 /// A Segwit v0 witness script.
+#[cfg(feature = "alloc")]
 pub type WitnessScriptBuf = ScriptBuf<WitnessScriptTag>;
 
 /// A reference to a Segwit v0 witness script.
@@ -94,6 +110,7 @@ impl Sealed for super::RedeemScriptTag {}
     impl Sealed for super::ScriptPubKeyTag {}
 }
 
+#[cfg(feature = "alloc")]
 impl<T: ScriptHashableTag> TryFrom<ScriptBuf<T>> for ScriptHash {
     type Error = RedeemScriptSizeError;
 
@@ -103,6 +120,7 @@ fn try_from(redeem_script: ScriptBuf<T>) -> Result<Self, Self::Error> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: ScriptHashableTag> TryFrom<&ScriptBuf<T>> for ScriptHash {
     type Error = RedeemScriptSizeError;
 
@@ -121,6 +139,7 @@ fn try_from(redeem_script: &Script<T>) -> Result<Self, Self::Error> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl TryFrom<WitnessScriptBuf> for WScriptHash {
     type Error = WitnessScriptSizeError;
 
@@ -130,6 +149,7 @@ fn try_from(witness_script: WitnessScriptBuf) -> Result<Self, Self::Error> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl TryFrom<&WitnessScriptBuf> for WScriptHash {
     type Error = WitnessScriptSizeError;
 
@@ -150,16 +170,19 @@ fn try_from(witness_script: &WitnessScript) -> Result<Self, Self::Error> {
 
 // We keep all the `Script` and `ScriptBuf` impls together since it's easier to see side-by-side.
 
+#[cfg(feature = "alloc")]
 impl<T> From<ScriptBuf<T>> for Box<Script<T>> {
     #[inline]
     fn from(v: ScriptBuf<T>) -> Self { v.into_boxed_script() }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> From<ScriptBuf<T>> for Cow<'_, Script<T>> {
     #[inline]
     fn from(value: ScriptBuf<T>) -> Self { Cow::Owned(value) }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> From<Cow<'a, Script<T>>> for ScriptBuf<T> {
     #[inline]
     fn from(value: Cow<'a, Script<T>>) -> Self {
@@ -170,6 +193,7 @@ fn from(value: Cow<'a, Script<T>>) -> Self {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> From<Cow<'a, Script<T>>> for Box<Script<T>> {
     #[inline]
     fn from(value: Cow<'a, Script<T>>) -> Self {
@@ -180,38 +204,45 @@ fn from(value: Cow<'a, Script<T>>) -> Self {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> From<&'a Script<T>> for Box<Script<T>> {
     #[inline]
     fn from(value: &'a Script<T>) -> Self { value.to_owned().into() }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> From<&'a Script<T>> for ScriptBuf<T> {
     #[inline]
     fn from(value: &'a Script<T>) -> Self { value.to_owned() }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> From<&'a Script<T>> for Cow<'a, Script<T>> {
     #[inline]
     fn from(value: &'a Script<T>) -> Self { Cow::Borrowed(value) }
 }
 
 /// Note: This will fail to compile on old Rust for targets that don't support atomics
+#[cfg(feature = "alloc")]
 #[cfg(target_has_atomic = "ptr")]
 impl<'a, T> From<&'a Script<T>> for Arc<Script<T>> {
     #[inline]
     fn from(value: &'a Script<T>) -> Self { Script::from_arc_bytes(Arc::from(value.as_bytes())) }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> From<&'a Script<T>> for Rc<Script<T>> {
     #[inline]
     fn from(value: &'a Script<T>) -> Self { Script::from_rc_bytes(Rc::from(value.as_bytes())) }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> From<Vec<u8>> for ScriptBuf<T> {
     #[inline]
     fn from(v: Vec<u8>) -> Self { Self::from_bytes(v) }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> From<ScriptBuf<T>> for Vec<u8> {
     #[inline]
     fn from(v: ScriptBuf<T>) -> Self { v.into_bytes() }
@@ -222,6 +253,7 @@ impl<T> AsRef<Self> for Script<T> {
     fn as_ref(&self) -> &Self { self }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> AsRef<Script<T>> for ScriptBuf<T> {
     #[inline]
     fn as_ref(&self) -> &Script<T> { self }
@@ -232,6 +264,7 @@ impl<T> AsRef<[u8]> for Script<T> {
     fn as_ref(&self) -> &[u8] { self.as_bytes() }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> AsRef<[u8]> for ScriptBuf<T> {
     #[inline]
     fn as_ref(&self) -> &[u8] { self.as_bytes() }
@@ -242,6 +275,7 @@ impl<T> AsMut<Self> for Script<T> {
     fn as_mut(&mut self) -> &mut Self { self }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> AsMut<Script<T>> for ScriptBuf<T> {
     #[inline]
     fn as_mut(&mut self) -> &mut Script<T> { self }
@@ -252,6 +286,7 @@ impl<T> AsMut<[u8]> for Script<T> {
     fn as_mut(&mut self) -> &mut [u8] { self.as_mut_bytes() }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> AsMut<[u8]> for ScriptBuf<T> {
     #[inline]
     fn as_mut(&mut self) -> &mut [u8] { self.as_mut_bytes() }
@@ -265,6 +300,7 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> fmt::Debug for ScriptBuf<T> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::Debug::fmt(self.as_script(), f) }
@@ -337,6 +373,7 @@ macro_rules! read_push_data_len {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> fmt::Display for ScriptBuf<T> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::Display::fmt(self.as_script(), f) }
@@ -350,6 +387,7 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
+#[cfg(feature = "alloc")]
 #[cfg(feature = "hex")]
 impl<T> fmt::LowerHex for ScriptBuf<T> {
     #[inline]
@@ -364,32 +402,38 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
+#[cfg(feature = "alloc")]
 #[cfg(feature = "hex")]
 impl<T> fmt::UpperHex for ScriptBuf<T> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::UpperHex::fmt(self.as_script(), f) }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Borrow<Script<T>> for ScriptBuf<T> {
     #[inline]
     fn borrow(&self) -> &Script<T> { self }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> BorrowMut<Script<T>> for ScriptBuf<T> {
     #[inline]
     fn borrow_mut(&mut self) -> &mut Script<T> { self }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: PartialEq> PartialEq<ScriptBuf<T>> for Script<T> {
     #[inline]
     fn eq(&self, other: &ScriptBuf<T>) -> bool { self.eq(other.as_script()) }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: PartialEq> PartialEq<Script<T>> for ScriptBuf<T> {
     #[inline]
     fn eq(&self, other: &Script<T>) -> bool { self.as_script().eq(other) }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: PartialOrd> PartialOrd<Script<T>> for ScriptBuf<T> {
     #[inline]
     fn partial_cmp(&self, other: &Script<T>) -> Option<Ordering> {
@@ -397,6 +441,7 @@ fn partial_cmp(&self, other: &Script<T>) -> Option<Ordering> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: PartialOrd> PartialOrd<ScriptBuf<T>> for Script<T> {
     #[inline]
     fn partial_cmp(&self, other: &ScriptBuf<T>) -> Option<Ordering> {
@@ -521,6 +566,7 @@ fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
 }
 
 #[cfg(test)]
+#[cfg(feature = "alloc")]
 mod tests {
     #[cfg(feature = "alloc")]
     use alloc::{format, vec};
@@ -4,6 +4,8 @@
 use core::fmt;
 use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
+#[cfg(feature = "hex")]
+use core::str::FromStr;
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::{Arbitrary, Unstructured};
@@ -131,6 +133,24 @@ pub fn capacity(&self) -> usize { self.1.capacity() }
     #[inline]
     #[deprecated(since = "1.0.0-rc.0", note = "use `format!(\"{var:x}\")` instead")]
     pub fn to_hex(&self) -> alloc::string::String { alloc::format!("{:x}", self) }
+
+    /// Parses a script from hex, rejecting scripts longer than `max_len` bytes.
+    ///
+    /// Like [`FromStr`] the hex is just the script bytes, without a length prefix. The length is
+    /// checked before decoding so untrusted input cannot cause a large allocation.
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not valid hex or encodes a script longer than `max_len` bytes.
+    #[cfg(feature = "hex")]
+    pub fn from_hex_with_limit(s: &str, max_len: usize) -> Result<Self, ParseScriptError> {
+        let len = s.len() / 2;
+        if len > max_len {
+            return Err(ParseScriptError(ParseScriptErrorInner::TooLong { len, max_len }));
+        }
+        let bytes = hex::decode_to_vec(s).map_err(|e| ParseScriptError(e.into()))?;
+        Ok(Self::from_bytes(bytes))
+    }
 }
 
 // Cannot derive due to generics.
@@ -204,6 +224,123 @@ impl std::error::Error for ScriptBufDecoderError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { Some(&self.0) }
 }
 
+/// Parses a script from hex, as formatted by [`LowerHex`] and [`UpperHex`], or from the
+/// human-readable opcodes formatted by [`Display`].
+///
+/// The hex is just the script bytes, without a length prefix, as used by the `hex` fields of the
+/// Bitcoin Core RPC. A string starting with `OP_` is parsed as opcodes separated by single spaces,
+/// each push followed by its data in hex, e.g. `OP_DUP OP_HASH160 OP_PUSHBYTES_20 <hex> ...`.
+///
+/// Both formats round-trip. Scripts ending in a truncated push, which [`Display`] cannot format
+/// completely, can only be parsed from hex.
+///
+/// [`LowerHex`]: fmt::LowerHex
+/// [`UpperHex`]: fmt::UpperHex
+/// [`Display`]: fmt::Display
+#[cfg(feature = "hex")]
+impl<T> FromStr for ScriptBuf<T> {
+    type Err = ParseScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("OP_") {
+            parse_opcodes(s).map(Self::from_bytes)
+        } else {
+            Self::from_hex_with_limit(s, usize::MAX)
+        }
+    }
+}
+
+/// Parses the script bytes from opcodes as formatted by the `Display` implementation of [`Script`].
+#[cfg(feature = "hex")]
+fn parse_opcodes(s: &str) -> Result<Vec<u8>, ParseScriptError> {
+    use crate::opcodes::{self, OP_PUSHDATA1, OP_PUSHDATA2, OP_PUSHDATA4};
+
+    let invalid_push = || ParseScriptError(ParseScriptErrorInner::InvalidPush);
+    let mut bytes = Vec::new();
+    let mut tokens = s.split(' ').peekable();
+    while let Some(token) = tokens.next() {
+        let opcode = opcodes::parse_opcode(token)
+            .ok_or(ParseScriptError(ParseScriptErrorInner::UnknownOpcode))?;
+        bytes.push(opcode);
+        let len_len = match opcode {
+            0x01..=0x4b => 0,
+            OP_PUSHDATA1 => 1,
+            OP_PUSHDATA2 => 2,
+            OP_PUSHDATA4 => 4,
+            _ => continue,
+        };
+
+        // The data of empty pushes is not formatted, the next token is then an opcode.
+        let data = match tokens.next_if(|token| !token.starts_with("OP_")) {
+            Some(token) => hex::decode_to_vec(token).map_err(|e| ParseScriptError(e.into()))?,
+            None => Vec::new(),
+        };
+        if len_len == 0 {
+            if data.len() != usize::from(opcode) {
+                return Err(invalid_push());
+            }
+        } else {
+            let len = u32::try_from(data.len()).map_err(|_| invalid_push())?.to_le_bytes();
+            if len[len_len..].iter().any(|&b| b != 0) {
+                return Err(invalid_push());
+            }
+            bytes.extend_from_slice(&len[..len_len]);
+        }
+        bytes.extend_from_slice(&data);
+    }
+    Ok(bytes)
+}
+
+/// An error parsing a [`ScriptBuf`] from hex or opcodes.
+#[cfg(feature = "hex")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseScriptError(ParseScriptErrorInner);
+
+#[cfg(feature = "hex")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseScriptErrorInner {
+    Hex(hex::DecodeVariableLengthBytesError),
+    TooLong { len: usize, max_len: usize },
+    UnknownOpcode,
+    InvalidPush,
+}
+
+#[cfg(feature = "hex")]
+impl From<hex::DecodeVariableLengthBytesError> for ParseScriptErrorInner {
+    fn from(e: hex::DecodeVariableLengthBytesError) -> Self { Self::Hex(e) }
+}
+
+#[cfg(feature = "hex")]
+impl From<Infallible> for ParseScriptError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+#[cfg(feature = "hex")]
+impl fmt::Display for ParseScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            ParseScriptErrorInner::Hex(ref e) => write_err!(f, "invalid script hex"; e),
+            ParseScriptErrorInner::TooLong { len, max_len } =>
+                write!(f, "script of {} bytes exceeds the limit of {} bytes", len, max_len),
+            ParseScriptErrorInner::UnknownOpcode => f.write_str("unknown opcode in script"),
+            ParseScriptErrorInner::InvalidPush =>
+                f.write_str("the data of a push in the script does not match its opcode"),
+        }
+    }
+}
+
+#[cfg(all(feature = "hex", feature = "std"))]
+impl std::error::Error for ParseScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.0 {
+            ParseScriptErrorInner::Hex(ref e) => Some(e),
+            ParseScriptErrorInner::TooLong { .. }
+            | ParseScriptErrorInner::UnknownOpcode
+            | ParseScriptErrorInner::InvalidPush => None,
+        }
+    }
+}
+
 #[cfg(feature = "arbitrary")]
 impl<'a, T> Arbitrary<'a> for ScriptBuf<T> {
     #[inline]
@@ -350,4 +487,56 @@ fn decoder_error_display() {
         #[cfg(feature = "std")]
         assert!(err.source().is_some());
     }
+
+    #[test]
+    #[cfg(feature = "hex")]
+    fn script_buf_from_str() {
+        use alloc::format;
+
+        let script = ScriptBuf::from_bytes(vec![0x00, 0x14, 0xab, 0xcd]);
+        assert_eq!(format!("{:x}", script).parse::<ScriptBuf>().unwrap(), script);
+        assert_eq!(format!("{:X}", script).parse::<ScriptBuf>().unwrap(), script);
+        assert_eq!("".parse::<ScriptBuf>().unwrap(), ScriptBuf::new());
+
+        // Strictly hex without a prefix.
+        assert!("0x0014abcd".parse::<ScriptBuf>().is_err());
+        assert!("0014abc".parse::<ScriptBuf>().is_err());
+        assert!(" 0014abcd".parse::<ScriptBuf>().is_err());
+
+        assert_eq!(ScriptBuf::from_hex_with_limit("0014abcd", 4).unwrap(), script);
+        let err = ScriptBuf::from_hex_with_limit("0014abcd", 3).unwrap_err();
+        assert_eq!(err.to_string(), "script of 4 bytes exceeds the limit of 3 bytes");
+        #[cfg(feature = "std")]
+        assert!("zz".parse::<ScriptBuf>().unwrap_err().source().is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "hex")]
+    fn script_buf_from_str_opcodes() {
+        use alloc::string::ToString;
+
+        let p2pkh = "76a914".to_string() + &"ab".repeat(20) + "88ac";
+        let pushdata2 = "4d0001".to_string() + &"cd".repeat(256);
+        for hex in [&p2pkh, &pushdata2, "4c00", "4e00000000", "00", "51ba", "ff"] {
+            let script = hex.parse::<ScriptBuf>().unwrap();
+            assert_eq!(script.to_string().parse::<ScriptBuf>().unwrap(), script, "{}", hex);
+        }
+
+        let parse = |s: &str| s.parse::<ScriptBuf>().unwrap();
+        let script = "OP_DUP OP_HASH160 OP_PUSHBYTES_2 abcd OP_EQUALVERIFY OP_CHECKSIG";
+        assert_eq!(parse(script), parse("76a902abcd88ac"));
+        assert_eq!(parse("OP_PUSHDATA1 OP_1"), parse("4c0051"));
+
+        // Strictly the format of `Display`.
+        assert!("OP_DUP  OP_DUP".parse::<ScriptBuf>().is_err());
+        assert!("OP_DUP op_dup".parse::<ScriptBuf>().is_err());
+        assert!("OP_DUP ".parse::<ScriptBuf>().is_err());
+        assert!("OP_NOP2".parse::<ScriptBuf>().is_err());
+        assert!("OP_PUSHBYTES_2 ab".parse::<ScriptBuf>().is_err());
+        assert!("OP_PUSHBYTES_1".parse::<ScriptBuf>().is_err());
+        assert!("OP_PUSHBYTES_1 <push past end>".parse::<ScriptBuf>().is_err());
+        assert!("OP_PUSHDATA1 <unexpected end>".parse::<ScriptBuf>().is_err());
+        let too_long = "OP_PUSHDATA1 ".to_string() + &"ab".repeat(256);
+        assert!(too_long.parse::<ScriptBuf>().is_err());
+    }
 }
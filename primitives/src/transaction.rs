@@ -29,7 +29,7 @@
 use internals::write_err;
 #[cfg(feature = "serde")]
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
-#[cfg(all(feature = "hex", feature = "alloc"))]
+#[cfg(feature = "hex")]
 use units::parse_int;
 
 #[cfg(feature = "alloc")]
@@ -404,6 +404,21 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for Transaction {
+    /// Serializes the consensus encoding, as hex if the serializer is human-readable.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::consensus_serde::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Transaction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::consensus_serde::deserialize(deserializer)
+    }
+}
+
 /// An error that occurs during parsing of a [`Transaction`] from a hex string.
 #[cfg(all(feature = "hex", feature = "alloc"))]
 pub struct ParseTransactionError(crate::ParsePrimitiveError<Transaction>);
@@ -1156,6 +1171,27 @@ impl OutPoint {
     /// This is used as the dummy input for coinbase transactions because they don't have any
     /// previous outputs. In other words, does not point to a real transaction.
     pub const COINBASE_PREVOUT: Self = Self { txid: Txid::COINBASE_PREVOUT, vout: u32::MAX };
+
+    /// Returns a fixed-length encoding of the outpoint, intended for use as a database key.
+    ///
+    /// The encoding is the txid bytes (in the same order as the consensus encoding) followed by
+    /// the vout as a big-endian integer. Unlike the consensus encoding, comparing two encodings
+    /// byte-wise gives the same ordering as comparing the outpoints themselves, so outpoints of the
+    /// same transaction are stored next to each other and sorted by vout.
+    ///
+    /// This encoding is stable and will not change in future versions of this library.
+    pub fn to_compact_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..32].copy_from_slice(self.txid.as_byte_array());
+        bytes[32..].copy_from_slice(&self.vout.to_be_bytes());
+        bytes
+    }
+
+    /// Parses an outpoint from the encoding returned by [`OutPoint::to_compact_bytes`].
+    pub fn from_compact_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        let (txid, vout) = bytes.split_array::<32, 4>();
+        Self { txid: Txid::from_byte_array(*txid), vout: u32::from_be_bytes(*vout) }
+    }
 }
 
 encoding::encoder_newtype_exact! {
@@ -1185,7 +1221,11 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     }
 }
 
-#[cfg(feature = "alloc")]
+/// Parses an outpoint in the `<txid>:<vout>` format produced by [`Display`](fmt::Display).
+///
+/// Parsing is strict, only the canonical form is accepted: the txid must be lowercase hex and the
+/// vout must not have leading zeros or a sign. Hence parsing and displaying an outpoint always gives
+/// back the original string.
 #[cfg(feature = "hex")]
 impl core::str::FromStr for OutPoint {
     type Err = ParseOutPointError;
@@ -1203,8 +1243,12 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
         if colon == 0 || colon == s.len() - 1 {
             return Err(ParseOutPointError::Format);
         }
+        let txid = &s[..colon];
+        if txid.bytes().any(|b| matches!(b, b'A'..=b'F')) {
+            return Err(ParseOutPointError::TxidNotCanonical);
+        }
         Ok(Self {
-            txid: s[..colon].parse().map_err(ParseOutPointError::Txid)?,
+            txid: txid.parse().map_err(ParseOutPointError::Txid)?,
             vout: parse_vout(&s[colon + 1..])?,
         })
     }
@@ -1213,7 +1257,6 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
 /// Parses a string-encoded transaction index (vout).
 ///
 /// Does not permit leading zeroes or non-digit characters.
-#[cfg(feature = "alloc")]
 #[cfg(feature = "hex")]
 fn parse_vout(s: &str) -> Result<u32, ParseOutPointError> {
     if s.len() > 1 {
@@ -1399,7 +1442,6 @@ fn visit_map<V>(self, mut map: V) -> Result<OutPoint, V::Error>
 /// An error in parsing an [`OutPoint`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
-#[cfg(feature = "alloc")]
 #[cfg(feature = "hex")]
 pub enum ParseOutPointError {
     /// Error in TXID part.
@@ -1412,16 +1454,16 @@ pub enum ParseOutPointError {
     TooLong,
     /// Vout part is not strictly numeric without leading zeroes.
     VoutNotCanonical,
+    /// TXID part contains uppercase hex characters.
+    TxidNotCanonical,
 }
 
-#[cfg(feature = "alloc")]
 #[cfg(feature = "hex")]
 impl From<Infallible> for ParseOutPointError {
     #[inline]
     fn from(never: Infallible) -> Self { match never {} }
 }
 
-#[cfg(feature = "alloc")]
 #[cfg(feature = "hex")]
 impl fmt::Display for ParseOutPointError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -1431,6 +1473,7 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             Self::Format => write!(f, "OutPoint not in <txid>:<vout> format"),
             Self::TooLong => write!(f, "vout should be at most 10 digits"),
             Self::VoutNotCanonical => write!(f, "no leading zeroes or + allowed in vout part"),
+            Self::TxidNotCanonical => write!(f, "TXID must be lowercase hex"),
         }
     }
 }
@@ -1442,7 +1485,7 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::Txid(e) => Some(e),
             Self::Vout(e) => Some(e),
-            Self::Format | Self::TooLong | Self::VoutNotCanonical => None,
+            Self::Format | Self::TooLong | Self::VoutNotCanonical | Self::TxidNotCanonical => None,
         }
     }
 }
@@ -1871,6 +1914,51 @@ fn outpoint_from_str_too_long() {
         assert_eq!(outpoint, Err(ParseOutPointError::TooLong));
     }
 
+    #[test]
+    #[cfg(feature = "hex")]
+    fn outpoint_from_str_canonical_txid() {
+        let lower = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20:1";
+        assert!(lower.parse::<OutPoint>().is_ok());
+
+        let upper = "0102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F20:1";
+        assert_eq!(upper.parse::<OutPoint>(), Err(ParseOutPointError::TxidNotCanonical));
+
+        let invalid = "0102030405060708090x0b0c0d0e0f101112131415161718191a1b1c1d1e1f20:1";
+        assert!(matches!(invalid.parse::<OutPoint>(), Err(ParseOutPointError::Txid(_))));
+    }
+
+    #[test]
+    fn outpoint_compact_bytes() {
+        let mut txid = [0; 32];
+        txid[0] = 0xab;
+        txid[31] = 0xcd;
+        let outpoint = OutPoint { txid: Txid::from_byte_array(txid), vout: 0x0102_0304 };
+
+        let bytes = outpoint.to_compact_bytes();
+        assert_eq!(bytes[..32], txid);
+        assert_eq!(bytes[32..], [0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(OutPoint::from_compact_bytes(bytes), outpoint);
+
+        let coinbase = OutPoint::COINBASE_PREVOUT;
+        assert_eq!(OutPoint::from_compact_bytes(coinbase.to_compact_bytes()), coinbase);
+    }
+
+    #[test]
+    fn outpoint_compact_bytes_ordering() {
+        let txid_a = Txid::from_byte_array([1; 32]);
+        let txid_b = Txid::from_byte_array([2; 32]);
+        let mut outpoints = [
+            OutPoint { txid: txid_b, vout: 0 },
+            OutPoint { txid: txid_a, vout: 256 },
+            OutPoint { txid: txid_a, vout: 1 },
+            OutPoint { txid: txid_a, vout: u32::MAX },
+        ];
+        let mut keys = outpoints.map(|outpoint| outpoint.to_compact_bytes());
+        outpoints.sort_unstable();
+        keys.sort_unstable();
+        assert_eq!(keys.map(OutPoint::from_compact_bytes), outpoints);
+    }
+
     #[test]
     #[cfg(feature = "hex")]
     fn canonical_vout() {
@@ -1966,6 +2054,70 @@ fn out_point_serde_non_human_readable_rountrips() {
         assert_eq!(got, out_point);
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn transaction_serde_human_readable_is_hex() {
+        let tx_hex = "0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000";
+        let tx = Transaction::from_str(tx_hex).unwrap();
+
+        let ser = serde_json::to_string(&tx).unwrap();
+        assert_eq!(ser, format!("\"{}\"", tx_hex));
+
+        let got = serde_json::from_str::<Transaction>(&ser).unwrap();
+        assert_eq!(got, tx);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn transaction_serde_non_human_readable_is_consensus_bytes() {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![segwit_tx_in()],
+            outputs: vec![tx_out()],
+        };
+
+        let ser = bincode::serialize(&tx).unwrap();
+        let encoded = encoding::encode_to_vec(&tx);
+        // bincode prefixes byte strings with a u64 length.
+        assert_eq!(ser[..8], (encoded.len() as u64).to_le_bytes());
+        assert_eq!(ser[8..], encoded[..]);
+
+        let got = bincode::deserialize::<Transaction>(&ser).unwrap();
+        assert_eq!(got, tx);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn transaction_serde_adapters() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Adapted {
+            #[serde(with = "crate::consensus_serde::hex")]
+            hex: Transaction,
+            #[serde(with = "crate::consensus_serde::bytes")]
+            bytes: Transaction,
+        }
+
+        let tx = Transaction {
+            version: Version::ONE,
+            lock_time: absolute::LockTime::ZERO,
+            inputs: vec![segwit_tx_in()],
+            outputs: vec![tx_out()],
+        };
+        let adapted = Adapted { hex: tx.clone(), bytes: tx.clone() };
+
+        let json = serde_json::to_value(&adapted).unwrap();
+        assert_eq!(json["hex"], tx.to_string());
+        assert!(json["bytes"].is_array());
+        assert_eq!(serde_json::from_value::<Adapted>(json).unwrap(), adapted);
+
+        let bin = bincode::serialize(&adapted).unwrap();
+        assert_eq!(bincode::deserialize::<Adapted>(&bin).unwrap(), adapted);
+
+        assert!(serde_json::from_str::<Transaction>("\"zz\"").is_err());
+        assert!(serde_json::from_str::<Transaction>("\"0100\"").is_err());
+    }
+
     #[cfg(feature = "alloc")]
     fn tx_out() -> TxOut { TxOut { amount: Amount::ONE_SAT, script_pubkey: tc_script_pubkey() } }
 
@@ -37,7 +37,9 @@
 /// and [`Witness::to_vec`].
 ///
 /// For serialization and deserialization performance it is stored internally as a single `Vec`,
-/// saving some allocations.
+/// saving some allocations. Elements are borrowed from that buffer by [`Witness::iter`] and
+/// encoding writes it out directly. Building a witness with [`Extend`] or [`FromIterator`] does not
+/// allocate per element.
 ///
 /// [SegWit upgrade]: <https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki>
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -658,93 +660,46 @@ impl<'a> IntoIterator for &'a Witness {
     fn into_iter(self) -> Self::IntoIter { self.iter() }
 }
 
+impl<T: AsRef<[u8]>> Extend<T> for Witness {
+    /// Pushes all elements of `iter` onto the witness.
+    ///
+    /// Unlike calling [`Witness::push`] repeatedly this moves the index area only once and does
+    /// not allocate per element.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut indices = self.content.split_off(self.indices_start);
+        for element in iter {
+            let element = element.as_ref();
+            let position = u32::try_from(self.content.len()).expect("larger than u32");
+            indices.extend_from_slice(&position.to_ne_bytes());
+            self.content.extend_from_slice(&crate::compact_size_encode(element.len()));
+            self.content.extend_from_slice(element);
+            self.witness_elements += 1;
+        }
+        self.indices_start = self.content.len();
+        self.content.extend_from_slice(&indices);
+    }
+}
+
 impl<T: AsRef<[u8]>> FromIterator<T> for Witness {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let v: Vec<Vec<u8>> = iter.into_iter().map(|item| Vec::from(item.as_ref())).collect();
-        Self::from(v)
+        let mut witness = Self::new();
+        witness.extend(iter);
+        witness
     }
 }
 
-// Serde keep backward compatibility with old Vec<Vec<u8>> format
 #[cfg(feature = "serde")]
 impl serde::Serialize for Witness {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        use serde::ser::SerializeSeq;
-
-        let human_readable = serializer.is_human_readable();
-        let mut seq = serializer.serialize_seq(Some(self.witness_elements))?;
-
-        // Note that the `Iter` strips the varints out when iterating.
-        for elem in self {
-            if human_readable {
-                seq.serialize_element(&internals::serde::SerializeBytesAsHex(elem))?;
-            } else {
-                seq.serialize_element(&elem)?;
-            }
-        }
-        seq.end()
+    /// Serializes the consensus encoding, as hex if the serializer is human-readable.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::consensus_serde::serialize(self, serializer)
     }
 }
 
 #[cfg(feature = "serde")]
 impl<'de> serde::Deserialize<'de> for Witness {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        use crate::prelude::String;
-
-        struct Visitor; // Human-readable visitor.
-        impl<'de> serde::de::Visitor<'de> for Visitor {
-            type Value = Witness;
-
-            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-                write!(f, "a sequence of hex arrays")
-            }
-
-            fn visit_seq<A: serde::de::SeqAccess<'de>>(
-                self,
-                mut a: A,
-            ) -> Result<Self::Value, A::Error> {
-                use hex_unstable::{FromHex, HexToBytesError as E};
-                use serde::de::{self, Unexpected};
-
-                let mut ret = match a.size_hint() {
-                    Some(len) => Vec::with_capacity(len),
-                    None => Vec::new(),
-                };
-
-                while let Some(elem) = a.next_element::<String>()? {
-                    let vec = Vec::<u8>::from_hex(&elem).map_err(|e| match e {
-                        E::InvalidChar(ref e) =>
-                            match core::char::from_u32(e.invalid_char().into()) {
-                                Some(c) => de::Error::invalid_value(
-                                    Unexpected::Char(c),
-                                    &"a valid hex character",
-                                ),
-                                None => de::Error::invalid_value(
-                                    Unexpected::Unsigned(e.invalid_char().into()),
-                                    &"a valid hex character",
-                                ),
-                            },
-                        E::OddLengthString(ref e) =>
-                            de::Error::invalid_length(e.length(), &"an even length string"),
-                    })?;
-                    ret.push(vec);
-                }
-                Ok(Witness::from_slice(&ret))
-            }
-        }
-
-        if deserializer.is_human_readable() {
-            deserializer.deserialize_seq(Visitor)
-        } else {
-            let vec: Vec<Vec<u8>> = serde::Deserialize::deserialize(deserializer)?;
-            Ok(Self::from_slice(&vec))
-        }
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::consensus_serde::deserialize(deserializer)
     }
 }
 
@@ -871,8 +826,49 @@ impl std::error::Error for UnexpectedEofError {}
 #[cfg(feature = "arbitrary")]
 impl<'a> Arbitrary<'a> for Witness {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        let arbitrary_bytes = Vec::<Vec<u8>>::arbitrary(u)?;
-        Ok(Self::from_slice(&arbitrary_bytes))
+        use crate::arbitrary_bytes;
+
+        // Mostly generate witnesses shaped like the ones spending standard outputs so that code
+        // inspecting the witness gets past its length checks, fall back to arbitrary elements.
+        match u.int_in_range(0..=5)? {
+            0 => Ok(Self::new()),
+            // P2WPKH: DER signature with sighash byte followed by a compressed public key.
+            1 => {
+                let sig_len = u.int_in_range(71..=73)?;
+                let mut sig = arbitrary_bytes(u, sig_len)?;
+                sig[0] = 0x30;
+                sig[sig_len - 1] = 0x01;
+                let mut pk = arbitrary_bytes(u, 33)?;
+                pk[0] = if u.arbitrary()? { 0x02 } else { 0x03 };
+                Ok(Self::from_slice(&[sig, pk]))
+            }
+            // P2TR key path: Schnorr signature, optionally with a sighash byte.
+            2 => {
+                let sig_len = if u.arbitrary()? { 65 } else { 64 };
+                Ok(Self::from_slice(&[arbitrary_bytes(u, sig_len)?]))
+            }
+            // P2TR script path: script inputs, leaf script and a control block.
+            3 => {
+                let mut witness = Self::from_slice(&Vec::<Vec<u8>>::arbitrary(u)?);
+                witness.push(Vec::<u8>::arbitrary(u)?);
+
+                let depth = u.int_in_range(0..=8)?;
+                let mut control_block = arbitrary_bytes(u, 33 + 32 * depth)?;
+                control_block[0] = 0xc0 | u8::from(u.arbitrary::<bool>()?);
+                witness.push(control_block);
+                Ok(witness)
+            }
+            // P2WSH: script inputs followed by the witness script.
+            4 => {
+                let mut witness = Self::from_slice(&Vec::<Vec<u8>>::arbitrary(u)?);
+                witness.push(Vec::<u8>::arbitrary(u)?);
+                Ok(witness)
+            }
+            _ => {
+                let arbitrary_bytes = Vec::<Vec<u8>>::arbitrary(u)?;
+                Ok(Self::from_slice(&arbitrary_bytes))
+            }
+        }
     }
 }
 
@@ -1209,14 +1205,14 @@ fn partial_eq_len_mismatch() {
 
     #[test]
     #[cfg(feature = "serde")]
-    fn serde_bincode_backward_compatibility() {
-        let old_witness_format = vec![vec![0u8], vec![2]];
-        let new_witness_format = Witness::from_slice(&old_witness_format);
-
-        let old = bincode::serialize(&old_witness_format).unwrap();
-        let new = bincode::serialize(&new_witness_format).unwrap();
+    fn serde_bincode_is_consensus_bytes() {
+        let witness = Witness::from_slice(&[vec![0u8], vec![2]]);
 
-        assert_eq!(old, new);
+        let ser = bincode::serialize(&witness).unwrap();
+        let encoded = encoding::encode_to_vec(&witness);
+        // bincode prefixes byte strings with a u64 length.
+        assert_eq!(ser[..8], (encoded.len() as u64).to_le_bytes());
+        assert_eq!(ser[8..], encoded[..]);
     }
 
     #[cfg(feature = "serde")]
@@ -1253,7 +1249,22 @@ fn serde_human_roundtrips() {
     fn serde_human() {
         let witness = Witness::from_slice(&[vec![0u8, 123, 75], vec![2u8, 6, 3, 7, 8]]);
         let json = serde_json::to_string(&witness).unwrap();
-        assert_eq!(json, r#"["007b4b","0206030708"]"#);
+        assert_eq!(json, r#""0203007b4b050206030708""#);
+        assert_eq!(serde_json::from_str::<Witness>(&json).unwrap(), witness);
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_witness_roundtrips() {
+        for seed in 0..=u8::MAX {
+            let data = [seed; 256];
+            let mut u = Unstructured::new(&data);
+            let witness = Witness::arbitrary(&mut u).unwrap();
+
+            let encoded = encoding::encode_to_vec(&witness);
+            let decoded: Witness = encoding::decode_from_slice(&encoded).unwrap();
+            assert_eq!(decoded, witness);
+        }
     }
 
     #[test]
@@ -1289,6 +1300,23 @@ fn test_witness_from_iterator() {
         assert!(witness4.is_empty());
     }
 
+    #[test]
+    fn witness_extend() {
+        let mut witness = Witness::from_slice(&[[1u8, 2]]);
+        witness.extend([&[3u8][..], &[], &[4, 5, 6]]);
+
+        let mut expected = Witness::from_slice(&[[1u8, 2]]);
+        expected.push([3u8]);
+        expected.push([]);
+        expected.push([4u8, 5, 6]);
+        assert_eq!(witness, expected);
+        assert_eq!(witness.last(), Some(&[4u8, 5, 6][..]));
+
+        witness.extend(core::iter::empty::<&[u8]>());
+        assert_eq!(witness, expected);
+        assert_eq!(expected.iter().collect::<Witness>(), expected);
+    }
+
     #[test]
     #[cfg(feature = "hex")]
     fn test_from_hex() {
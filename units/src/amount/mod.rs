@@ -436,6 +436,30 @@ fn repeat_char(f: &mut dyn fmt::Write, c: char, count: usize) -> fmt::Result {
     Ok(())
 }
 
+/// Writes `num` followed by `exp` zeros, with `separator` between groups of three digits.
+fn write_integer(
+    f: &mut dyn fmt::Write,
+    num: u64,
+    exp: usize,
+    separator: Option<char>,
+) -> fmt::Result {
+    let Some(separator) = separator else {
+        write!(f, "{}", num)?;
+        return repeat_char(f, '0', exp);
+    };
+    let len = dec_width(num);
+    let width = len + exp;
+    for i in 0..width {
+        if i > 0 && (width - i) % 3 == 0 {
+            f.write_char(separator)?;
+        }
+        // Cast ok, a u64 has at most 20 digits.
+        let digit = if i < len { num / 10u64.pow((len - 1 - i) as u32) % 10 } else { 0 };
+        write!(f, "{}", digit)?;
+    }
+    Ok(())
+}
+
 /// Formats the given satoshi amount in the given denomination.
 fn fmt_satoshi_in(
     mut satoshi: u64,
@@ -443,6 +467,7 @@ fn fmt_satoshi_in(
     f: &mut dyn fmt::Write,
     denom: Denomination,
     show_denom: bool,
+    separators: Separators,
     options: FormatOptions,
 ) -> fmt::Result {
     let precision = denom.precision();
@@ -504,7 +529,8 @@ fn fmt_satoshi_in(
     } else {
         0
     };
-    num_width += dec_width(num_before_decimal_point) + exp;
+    let int_width = dec_width(num_before_decimal_point) + exp;
+    num_width += int_width + separators.thousands.map_or(0, |_| (int_width - 1) / 3);
     if options.sign_plus || negative {
         num_width += 1;
     }
@@ -540,12 +566,10 @@ fn fmt_satoshi_in(
         repeat_char(f, '0', left_pad)?;
     }
 
-    write!(f, "{}", num_before_decimal_point)?;
-
-    repeat_char(f, '0', exp)?;
+    write_integer(f, num_before_decimal_point, exp, separators.thousands)?;
 
     if total_decimals > 0 {
-        write!(f, ".")?;
+        f.write_char(separators.decimal)?;
     }
     if norm_nb_decimals > 0 {
         write!(f, "{:0width$}", num_after_decimal_point, width = norm_nb_decimals)?;
@@ -585,6 +609,8 @@ pub struct Display {
     is_negative: bool,
     /// How to display the value
     style: DisplayStyle,
+    /// The separators of the digits
+    separators: Separators,
 }
 
 impl Display {
@@ -598,6 +624,16 @@ pub fn show_denomination(mut self) -> Self {
         }
         self
     }
+
+    /// Makes subsequent calls to `Display::fmt` use `separators`.
+    ///
+    /// Only the default [`Separators::NONE`] round-trips with [`FromStr`], use
+    /// [`Amount::from_str_with_separators`] to parse the output of other separators back.
+    #[must_use]
+    pub fn separators(mut self, separators: Separators) -> Self {
+        self.separators = separators;
+        self
+    }
 }
 
 impl fmt::Display for Display {
@@ -606,13 +642,13 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let format_options = FormatOptions::from_formatter(f);
         match &self.style {
             DisplayStyle::FixedDenomination { show_denomination, denomination } => {
-                fmt_satoshi_in(self.sats_abs, self.is_negative, f, *denomination, *show_denomination, format_options)
+                fmt_satoshi_in(self.sats_abs, self.is_negative, f, *denomination, *show_denomination, self.separators, format_options)
             },
             DisplayStyle::DynamicDenomination if self.sats_abs >= Amount::ONE_BTC.to_sat() => {
-                fmt_satoshi_in(self.sats_abs, self.is_negative, f, Denomination::Bitcoin, true, format_options)
+                fmt_satoshi_in(self.sats_abs, self.is_negative, f, Denomination::Bitcoin, true, self.separators, format_options)
             },
             DisplayStyle::DynamicDenomination => {
-                fmt_satoshi_in(self.sats_abs, self.is_negative, f, Denomination::Satoshi, true, format_options)
+                fmt_satoshi_in(self.sats_abs, self.is_negative, f, Denomination::Satoshi, true, self.separators, format_options)
             },
         }
     }
@@ -624,6 +660,130 @@ enum DisplayStyle {
     DynamicDenomination,
 }
 
+impl From<DenominationPolicy> for DisplayStyle {
+    fn from(policy: DenominationPolicy) -> Self {
+        match policy {
+            DenominationPolicy::Fixed(denomination) =>
+                Self::FixedDenomination { denomination, show_denomination: true },
+            DenominationPolicy::Dynamic => Self::DynamicDenomination,
+        }
+    }
+}
+
+/// How [`Amount::display_dynamic_with`] selects the denomination, which is always shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DenominationPolicy {
+    /// Always use the given denomination.
+    Fixed(Denomination),
+    /// Use BTC for values greater than or equal to 1 BTC and satoshis otherwise, like
+    /// [`Amount::display_dynamic`].
+    Dynamic,
+}
+
+/// The characters separating the digits of a displayed amount.
+///
+/// # Examples
+///
+/// ```
+/// # use bitcoin_units::amount::{self, Amount, DenominationPolicy, Separators};
+/// let amount = Amount::from_sat(123_456_789)?;
+/// let display = amount.display_dynamic_with(DenominationPolicy::Dynamic, Separators::COMMA_THOUSANDS);
+/// assert_eq!(display.to_string(), "1.23456789 BTC");
+///
+/// let amount = Amount::from_sat(1_234_567)?;
+/// let display = amount.display_dynamic_with(DenominationPolicy::Dynamic, Separators::PERIOD_THOUSANDS);
+/// assert_eq!(display.to_string(), "1.234.567 satoshi");
+/// assert_eq!(Amount::from_str_with_separators("1.234.567 satoshi", Separators::PERIOD_THOUSANDS)?, amount);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Separators {
+    /// The character between groups of three digits before the decimal point, if any.
+    pub thousands: Option<char>,
+    /// The character between the whole and the fractional digits.
+    pub decimal: char,
+}
+
+impl Separators {
+    /// No thousands separator and a period as decimal point, as used by [`fmt::Display`].
+    pub const NONE: Self = Self { thousands: None, decimal: '.' };
+
+    /// A comma between thousands and a period as decimal point, e.g. "1,234.5".
+    pub const COMMA_THOUSANDS: Self = Self { thousands: Some(','), decimal: '.' };
+
+    /// A period between thousands and a comma as decimal point, e.g. "1.234,5".
+    pub const PERIOD_THOUSANDS: Self = Self { thousands: Some('.'), decimal: ',' };
+
+    /// Rewrites `s` using the separators of the parser into `buf`.
+    ///
+    /// Thousands separators become underscores after checking that they separate groups of
+    /// three digits, the decimal point becomes a period. The rest of `s`, starting at the first
+    /// character that is not part of the number, is copied unchanged.
+    fn normalize<'b>(self, s: &str, buf: &'b mut [u8]) -> Result<&'b str, ParseError> {
+        if s.len() > buf.len() {
+            return Err(InputTooLargeError { len: s.len() }.into());
+        }
+        let bad_position =
+            |position| BadPositionError { char: self.thousands.unwrap_or('_'), position };
+
+        let mut len = 0;
+        // The number of digits since the last thousands separator, `None` before the first one.
+        let mut group: Option<usize> = None;
+        let mut first_group = 0;
+        let mut in_integer = true;
+        let mut chars = s.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let next_is_digit = chars.peek().is_some_and(|(_, c)| c.is_ascii_digit());
+            let replacement = if c.is_ascii_digit() {
+                if in_integer {
+                    match group.as_mut() {
+                        Some(group) => *group += 1,
+                        None => first_group += 1,
+                    }
+                }
+                c
+            } else if in_integer && Some(c) == self.thousands && next_is_digit {
+                match group {
+                    Some(3) => {}
+                    None if (1..=3).contains(&first_group) => {}
+                    _ => return Err(bad_position(i).into()),
+                }
+                group = Some(0);
+                '_'
+            } else if c == '-' && i == 0 {
+                c
+            } else {
+                if in_integer && !matches!(group, None | Some(3)) {
+                    return Err(bad_position(i).into());
+                }
+                in_integer = false;
+                if c == self.decimal {
+                    '.'
+                } else {
+                    // The number ended, copy the rest.
+                    let rest = s.get(i..).expect("char boundary").as_bytes();
+                    let out = buf.get_mut(len..len + rest.len()).expect("buf is longer than s");
+                    out.copy_from_slice(rest);
+                    len += rest.len();
+                    break;
+                }
+            };
+            let out = buf.get_mut(len..).expect("buf is longer than s");
+            len += replacement.encode_utf8(out).len();
+        }
+        if in_integer && !matches!(group, None | Some(3)) {
+            return Err(bad_position(s.len()).into());
+        }
+        let normalized = buf.get(..len).expect("buf is longer than s");
+        Ok(core::str::from_utf8(normalized).expect("copied from a str and ASCII characters"))
+    }
+}
+
+impl Default for Separators {
+    fn default() -> Self { Self::NONE }
+}
+
 #[cfg(feature = "arbitrary")]
 impl<'a> Arbitrary<'a> for Denomination {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
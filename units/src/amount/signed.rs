@@ -12,8 +12,9 @@
 
 use super::error::ParseErrorInner;
 use super::{
-    parse_signed_to_satoshi, split_amount_and_denomination, Amount, Denomination, Display,
-    DisplayStyle, OutOfRangeError, ParseAmountError, ParseError,
+    parse_signed_to_satoshi, split_amount_and_denomination, Amount, Denomination,
+    DenominationPolicy, Display, DisplayStyle, OutOfRangeError, ParseAmountError, ParseError,
+    Separators, INPUT_STRING_LEN_LIMIT,
 };
 
 mod encapsulate {
@@ -198,6 +199,20 @@ pub fn from_str_with_denomination(s: &str) -> Result<Self, ParseError> {
         Self::from_str_in(amt, denom).map_err(Into::into)
     }
 
+    /// Parses amounts formatted with `separators`, as produced by [`Self::display_dynamic_with`].
+    ///
+    /// Thousands separators must separate groups of three digits before the decimal point. As
+    /// with [`FromStr`] the denomination is only optional for zero.
+    ///
+    /// # Errors
+    ///
+    /// If the amount is too big (positive or negative) or too precise, or a thousands separator is
+    /// misplaced.
+    pub fn from_str_with_separators(s: &str, separators: Separators) -> Result<Self, ParseError> {
+        let mut buf = [0; 2 * INPUT_STRING_LEN_LIMIT];
+        separators.normalize(s, &mut buf)?.parse()
+    }
+
     /// Expresses this [`SignedAmount`] as a floating-point value in the given [`Denomination`].
     ///
     /// Please be aware of the risk of using floating-point numbers.
@@ -266,6 +281,7 @@ pub fn display_in(self, denomination: Denomination) -> Display {
             sats_abs: self.unsigned_abs().to_sat(),
             is_negative: self.is_negative(),
             style: DisplayStyle::FixedDenomination { denomination, show_denomination: false },
+            separators: Separators::NONE,
         }
     }
 
@@ -280,6 +296,26 @@ pub fn display_dynamic(self) -> Display {
             sats_abs: self.unsigned_abs().to_sat(),
             is_negative: self.is_negative(),
             style: DisplayStyle::DynamicDenomination,
+            separators: Separators::NONE,
+        }
+    }
+
+    /// Constructs a new object that implements [`fmt::Display`] selecting the [`Denomination`]
+    /// with `policy` and separating digits with `separators`.
+    ///
+    /// The denomination is always shown. Use [`Self::from_str_with_separators`] to parse the
+    /// output back.
+    #[must_use]
+    pub fn display_dynamic_with(
+        self,
+        policy: DenominationPolicy,
+        separators: Separators,
+    ) -> Display {
+        Display {
+            sats_abs: self.unsigned_abs().to_sat(),
+            is_negative: self.is_negative(),
+            style: policy.into(),
+            separators,
         }
     }
 
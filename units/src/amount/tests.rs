@@ -125,6 +125,108 @@ fn from_int_btc() {
     assert_eq!(ssat(-200_000_000), amt);
 }
 
+#[test]
+#[cfg(feature = "alloc")]
+fn display_with_separators() {
+    let comma = Separators::COMMA_THOUSANDS;
+    let period = Separators::PERIOD_THOUSANDS;
+    let dynamic = DenominationPolicy::Dynamic;
+
+    assert_eq!(
+        sat(1_234_567).display_dynamic_with(dynamic, comma).to_string(),
+        "1,234,567 satoshi"
+    );
+    assert_eq!(sat(123).display_dynamic_with(dynamic, comma).to_string(), "123 satoshi");
+    assert_eq!(sat(0).display_dynamic_with(dynamic, comma).to_string(), "0 satoshi");
+    assert_eq!(
+        sat(123_456_789_012).display_dynamic_with(dynamic, comma).to_string(),
+        "1,234.56789012 BTC"
+    );
+    assert_eq!(
+        sat(123_456_789_012).display_dynamic_with(dynamic, period).to_string(),
+        "1.234,56789012 BTC"
+    );
+    assert_eq!(
+        ssat(-1_234_567).display_dynamic_with(dynamic, comma).to_string(),
+        "-1,234,567 satoshi"
+    );
+
+    let mbtc = DenominationPolicy::Fixed(Denomination::MilliBitcoin);
+    assert_eq!(sat(123_456_789).display_dynamic_with(mbtc, comma).to_string(), "1,234.56789 mBTC");
+    let bits = DenominationPolicy::Fixed(Denomination::Bit);
+    assert_eq!(sat(1_200_000).display_dynamic_with(bits, comma).to_string(), "12,000 bits");
+
+    // Formatter options account for the separators.
+    let display = sat(1_234_567).display_in(Denomination::Satoshi).separators(comma);
+    assert_eq!(format!("{:>12}", display), "   1,234,567");
+    assert_eq!(format!("{:012}", display), "0001,234,567");
+    let display = sat(150_000_000).display_in(Denomination::Bitcoin).separators(period);
+    assert_eq!(format!("{:.3}", display), "1,500");
+    assert_eq!(
+        sat(1_234_567).display_dynamic_with(dynamic, Separators::NONE).to_string(),
+        sat(1_234_567).display_dynamic().to_string()
+    );
+}
+
+#[test]
+fn parse_with_separators() {
+    let comma = Separators::COMMA_THOUSANDS;
+    let period = Separators::PERIOD_THOUSANDS;
+
+    assert_eq!(Amount::from_str_with_separators("1,234,567 satoshi", comma), Ok(sat(1_234_567)));
+    assert_eq!(
+        Amount::from_str_with_separators("1,234.56789012 BTC", comma),
+        Ok(sat(123_456_789_012))
+    );
+    assert_eq!(
+        Amount::from_str_with_separators("1.234,56789012 BTC", period),
+        Ok(sat(123_456_789_012))
+    );
+    assert_eq!(Amount::from_str_with_separators("0,5 BTC", period), Ok(sat(50_000_000)));
+    assert_eq!(Amount::from_str_with_separators("12 sat", comma), Ok(sat(12)));
+    assert_eq!(Amount::from_str_with_separators("0", comma), Ok(sat(0)));
+    assert_eq!(SignedAmount::from_str_with_separators("-1,234 sat", comma), Ok(ssat(-1_234)));
+    let space = Separators { thousands: Some(' '), decimal: '.' };
+    assert_eq!(Amount::from_str_with_separators("1 234 567 sat", space), Ok(sat(1_234_567)));
+
+    // Thousands separators must separate groups of three digits.
+    for s in ["1,23 sat", "1234,567 sat", "1,2345 sat", "12,34.5 BTC", "0.5 BTC", "1,00"] {
+        let separators = if s == "0.5 BTC" { period } else { comma };
+        let err = Amount::from_str_with_separators(s, separators).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ParseError(ParseErrorInner::Amount(ParseAmountError(
+                    ParseAmountErrorInner::BadPosition(_)
+                )))
+            ),
+            "{}",
+            s
+        );
+    }
+    assert!(Amount::from_str_with_separators("-1 sat", comma).is_err());
+    assert!(Amount::from_str_with_separators(&"1".repeat(101), comma).is_err());
+
+    // Round trips.
+    for sats in
+        [0, 1, 999, 1_000, 99_999_999, 100_000_000, 123_456_789_012, Amount::MAX_MONEY.to_sat()]
+    {
+        for separators in [comma, period, space, Separators::NONE] {
+            for policy in
+                [DenominationPolicy::Dynamic, DenominationPolicy::Fixed(Denomination::MicroBitcoin)]
+            {
+                let display = sat(sats).display_dynamic_with(policy, separators).to_string();
+                assert_eq!(
+                    Amount::from_str_with_separators(&display, separators),
+                    Ok(sat(sats)),
+                    "{}",
+                    display
+                );
+            }
+        }
+    }
+}
+
 #[test]
 #[cfg(feature = "alloc")]
 fn display_display_struct() {
@@ -135,6 +237,7 @@ fn display_display_struct() {
             denomination: Denomination::Bitcoin,
             show_denomination: true,
         },
+        separators: Separators::NONE,
     };
     assert_eq!(format!("{}", display_fixed_btc), "1 BTC");
 
@@ -145,6 +248,7 @@ fn display_display_struct() {
             denomination: Denomination::Satoshi,
             show_denomination: true,
         },
+        separators: Separators::NONE,
     };
     assert_eq!(format!("{}", display_fixed_sat), "1 satoshi");
 
@@ -152,6 +256,7 @@ fn display_display_struct() {
         sats_abs: 100_000_000,
         is_negative: false,
         style: DisplayStyle::DynamicDenomination,
+        separators: Separators::NONE,
     };
     assert_eq!(format!("{}", display_dynamic_btc), "1 BTC");
 
@@ -159,6 +264,7 @@ fn display_display_struct() {
         sats_abs: 99_999_999,
         is_negative: false,
         style: DisplayStyle::DynamicDenomination,
+        separators: Separators::NONE,
     };
     assert_eq!(format!("{}", display_dynamic_sat), "99999999 satoshi");
 
@@ -166,6 +272,7 @@ fn display_display_struct() {
         sats_abs: 100_000_000,
         is_negative: true,
         style: DisplayStyle::DynamicDenomination,
+        separators: Separators::NONE,
     };
     assert_eq!(format!("{}", display_negative_btc), "-1 BTC");
 
@@ -173,6 +280,7 @@ fn display_display_struct() {
         sats_abs: 99_999_999,
         is_negative: true,
         style: DisplayStyle::DynamicDenomination,
+        separators: Separators::NONE,
     };
     assert_eq!(format!("{}", display_negative_sat), "-99999999 satoshi");
 }
@@ -1518,3 +1626,34 @@ fn amount_div_by_weight_ceil_error() {
     let err = Amount::MAX.div_by_weight_ceil(Weight::from_wu(1)).unwrap_err();
     assert_eq!(err, NumOpError::while_doing(MathOp::Mul));
 }
+
+#[test]
+fn amount_checked_mul_ratio() {
+    assert_eq!(sat(1_000).checked_mul_ratio(2, 3), NumOpResult::Valid(sat(666)));
+    assert_eq!(sat(1_000).checked_mul_ratio_ceil(2, 3), NumOpResult::Valid(sat(667)));
+    assert_eq!(sat(999).checked_mul_ratio(1, 3), NumOpResult::Valid(sat(333)));
+    assert_eq!(sat(999).checked_mul_ratio_ceil(1, 3), NumOpResult::Valid(sat(333)));
+    assert_eq!(sat(1_000).checked_mul_ratio(0, 3), NumOpResult::Valid(Amount::ZERO));
+
+    // The intermediate product of `Amount::MAX` and `u64::MAX` does not overflow.
+    assert_eq!(Amount::MAX.checked_mul_ratio(u64::MAX, u64::MAX), NumOpResult::Valid(Amount::MAX));
+
+    let err = sat(1_000).checked_mul_ratio(1, 0).unwrap_err();
+    assert_eq!(err, NumOpError::while_doing(MathOp::Div));
+    let err = sat(1_000).checked_mul_ratio_ceil(1, 0).unwrap_err();
+    assert_eq!(err, NumOpError::while_doing(MathOp::Div));
+    let err = Amount::MAX.checked_mul_ratio(3, 2).unwrap_err();
+    assert_eq!(err, NumOpError::while_doing(MathOp::Mul));
+}
+
+#[test]
+fn amount_checked_mul_bps() {
+    assert_eq!(sat(123_456).checked_mul_bps(25), NumOpResult::Valid(sat(308)));
+    assert_eq!(sat(123_456).checked_mul_bps_ceil(25), NumOpResult::Valid(sat(309)));
+    assert_eq!(sat(123_456).checked_mul_bps(10_000), NumOpResult::Valid(sat(123_456)));
+    assert_eq!(sat(100).checked_mul_bps(1), NumOpResult::Valid(Amount::ZERO));
+    assert_eq!(sat(100).checked_mul_bps_ceil(1), NumOpResult::Valid(sat(1)));
+
+    let err = Amount::MAX.checked_mul_bps(10_001).unwrap_err();
+    assert_eq!(err, NumOpError::while_doing(MathOp::Mul));
+}
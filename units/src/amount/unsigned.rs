@@ -16,12 +16,16 @@
 use super::error::AmountDecoderError;
 use super::error::{ParseAmountErrorInner, ParseErrorInner};
 use super::{
-    parse_signed_to_satoshi, split_amount_and_denomination, Denomination, Display, DisplayStyle,
-    OutOfRangeError, ParseAmountError, ParseError, SignedAmount,
+    parse_signed_to_satoshi, split_amount_and_denomination, Denomination, DenominationPolicy,
+    Display, DisplayStyle, OutOfRangeError, ParseAmountError, ParseError, Separators, SignedAmount,
+    INPUT_STRING_LEN_LIMIT,
 };
 use crate::result::{MathOp, NumOpError as E, NumOpResult};
 use crate::{FeeRate, Weight};
 
+/// The number of basis points in one, i.e. in 100%.
+const BASIS_POINTS_PER_UNIT: u64 = 10_000;
+
 mod encapsulate {
     use super::OutOfRangeError;
 
@@ -209,6 +213,19 @@ pub fn from_str_with_denomination(s: &str) -> Result<Self, ParseError> {
         Self::from_str_in(amt, denom).map_err(Into::into)
     }
 
+    /// Parses amounts formatted with `separators`, as produced by [`Self::display_dynamic_with`].
+    ///
+    /// Thousands separators must separate groups of three digits before the decimal point. As
+    /// with [`FromStr`] the denomination is only optional for zero.
+    ///
+    /// # Errors
+    ///
+    /// If the amount is too big, too precise or negative, or a thousands separator is misplaced.
+    pub fn from_str_with_separators(s: &str, separators: Separators) -> Result<Self, ParseError> {
+        let mut buf = [0; 2 * INPUT_STRING_LEN_LIMIT];
+        separators.normalize(s, &mut buf)?.parse()
+    }
+
     /// Expresses this [`Amount`] as a floating-point value in the given [`Denomination`].
     ///
     /// Please be aware of the risk of using floating-point numbers.
@@ -280,6 +297,7 @@ pub fn display_in(self, denomination: Denomination) -> Display {
             sats_abs: self.to_sat(),
             is_negative: false,
             style: DisplayStyle::FixedDenomination { denomination, show_denomination: false },
+            separators: Separators::NONE,
         }
     }
 
@@ -294,9 +312,24 @@ pub fn display_dynamic(self) -> Display {
             sats_abs: self.to_sat(),
             is_negative: false,
             style: DisplayStyle::DynamicDenomination,
+            separators: Separators::NONE,
         }
     }
 
+    /// Constructs a new object that implements [`fmt::Display`] selecting the [`Denomination`]
+    /// with `policy` and separating digits with `separators`.
+    ///
+    /// The denomination is always shown. Use [`Self::from_str_with_separators`] to parse the
+    /// output back.
+    #[must_use]
+    pub fn display_dynamic_with(
+        self,
+        policy: DenominationPolicy,
+        separators: Separators,
+    ) -> Display {
+        Display { sats_abs: self.to_sat(), is_negative: false, style: policy.into(), separators }
+    }
+
     /// Returns a formatted string representing this [`Amount`] in the given [`Denomination`].
     ///
     /// Returned string does not include the denomination.
@@ -403,6 +436,67 @@ pub const fn checked_rem(self, rhs: u64) -> Option<Self> {
         }
     }
 
+    /// Checked multiplication by the ratio `num / den`, rounding down.
+    ///
+    /// The intermediate product cannot overflow, so this is exact up to the final rounding. See
+    /// also [`Self::checked_mul_ratio_ceil`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitcoin_units::Amount;
+    /// let amount = Amount::from_sat_u32(1_000);
+    /// assert_eq!(amount.checked_mul_ratio(1, 3).unwrap(), Amount::from_sat_u32(333));
+    /// assert!(amount.checked_mul_ratio(1, 0).is_error());
+    /// ```
+    pub const fn checked_mul_ratio(self, num: u64, den: u64) -> NumOpResult<Self> {
+        if den == 0 {
+            return R::Error(E::while_doing(MathOp::Div));
+        }
+        let product = self.to_sat() as u128 * num as u128;
+        Self::from_ratio_quotient(product / den as u128)
+    }
+
+    /// Checked multiplication by the ratio `num / den`, rounding up.
+    ///
+    /// Use this for fees, which should not be undercharged. See also [`Self::checked_mul_ratio`].
+    pub const fn checked_mul_ratio_ceil(self, num: u64, den: u64) -> NumOpResult<Self> {
+        if den == 0 {
+            return R::Error(E::while_doing(MathOp::Div));
+        }
+        let product = self.to_sat() as u128 * num as u128;
+        Self::from_ratio_quotient(product.div_ceil(den as u128))
+    }
+
+    /// Checked multiplication by `bps` basis points (hundredths of a percent), rounding down.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bitcoin_units::Amount;
+    /// // A 0.25% withdrawal fee.
+    /// let amount = Amount::from_sat_u32(123_456);
+    /// assert_eq!(amount.checked_mul_bps(25).unwrap(), Amount::from_sat_u32(308));
+    /// assert_eq!(amount.checked_mul_bps_ceil(25).unwrap(), Amount::from_sat_u32(309));
+    /// ```
+    pub const fn checked_mul_bps(self, bps: u64) -> NumOpResult<Self> {
+        self.checked_mul_ratio(bps, BASIS_POINTS_PER_UNIT)
+    }
+
+    /// Checked multiplication by `bps` basis points (hundredths of a percent), rounding up.
+    pub const fn checked_mul_bps_ceil(self, bps: u64) -> NumOpResult<Self> {
+        self.checked_mul_ratio_ceil(bps, BASIS_POINTS_PER_UNIT)
+    }
+
+    const fn from_ratio_quotient(quotient: u128) -> NumOpResult<Self> {
+        if quotient <= Self::MAX.to_sat() as u128 {
+            if let Ok(amount) = Self::from_sat(quotient as u64) {
+                return R::Valid(amount);
+            }
+        }
+        R::Error(E::while_doing(MathOp::Mul))
+    }
+
     /// Converts to a signed amount.
     #[rustfmt::skip] // Moves code comments to the wrong line.
     #[allow(clippy::missing_panics_doc)]
@@ -5,8 +5,10 @@
 #[cfg(feature = "serde")]
 pub mod serde;
 
+use core::convert::Infallible;
 use core::num::NonZeroU64;
-use core::ops;
+use core::str::FromStr;
+use core::{fmt, ops};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::{Arbitrary, Unstructured};
@@ -259,6 +261,235 @@ fn sum<I>(iter: I) -> Self
     }
 }
 
+impl FeeRate {
+    /// Constructs a new object that implements [`fmt::Display`] in the given [`Unit`].
+    ///
+    /// See the [`fmt::Display`] implementation of [`FeeRate`] for the format.
+    #[must_use]
+    pub fn display_in(self, unit: Unit) -> Display { Display { fee_rate: self, unit } }
+}
+
+/// Displays the fee rate in sat/vB, e.g. "12.5 sat/vB".
+///
+/// Without a precision the value is exact, trailing zeros are not shown. With a precision, e.g.
+/// `{:.2}`, the value is rounded to that many decimals with halves rounded up. Like a number, the
+/// value and unit are aligned to the right of the width by default. Use [`FeeRate::display_in`] to
+/// display the fee rate in another unit.
+impl fmt::Display for FeeRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.display_in(Unit::SatPerVb), f)
+    }
+}
+
+/// Parses a fee rate with a unit, e.g. "12.5 sat/vB", "3000 sat/kwu" or "0.00012 BTC/kvB".
+///
+/// The units of [`Unit`] are accepted in any case, the space before the unit is optional. The
+/// value must be exactly representable, fee rates are stored in satoshis per million virtual
+/// bytes, so for example "0.0000001 sat/vB" is rejected rather than rounded.
+impl FromStr for FeeRate {
+    type Err = ParseFeeRateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let unit = unit.strip_prefix(' ').unwrap_or(unit);
+        if !number.bytes().any(|b| b.is_ascii_digit()) {
+            return Err(ParseFeeRateError(ParseFeeRateErrorInner::InvalidNumber));
+        }
+        if unit.is_empty() {
+            return Err(ParseFeeRateError(ParseFeeRateErrorInner::MissingUnit));
+        }
+        let unit = Unit::ALL
+            .into_iter()
+            .find(|u| u.as_str().eq_ignore_ascii_case(unit))
+            .ok_or(ParseFeeRateError(ParseFeeRateErrorInner::UnknownUnit))?;
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        let fraction = fraction.trim_end_matches('0');
+        if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseFeeRateError(ParseFeeRateErrorInner::InvalidNumber));
+        }
+        let too_large = ParseFeeRateError(ParseFeeRateErrorInner::TooLarge);
+        let too_precise = ParseFeeRateError(ParseFeeRateErrorInner::TooPrecise);
+
+        // The value is `digits / 10^decimals` in `unit`.
+        let decimals = u32::try_from(fraction.len()).map_err(|_| too_precise.clone())?;
+        let scale = 10_u128.checked_pow(decimals).ok_or(too_precise.clone())?;
+        let mut digits: u128 = 0;
+        for b in whole.bytes().chain(fraction.bytes()) {
+            digits = digits
+                .checked_mul(10)
+                .and_then(|d| d.checked_add(u128::from(b - b'0')))
+                .ok_or(too_large.clone())?;
+        }
+        let sat_mvb = digits.checked_mul(unit.sat_per_mvb()).ok_or(too_large.clone())?;
+        if sat_mvb % scale != 0 {
+            return Err(too_precise);
+        }
+        u64::try_from(sat_mvb / scale).map(Self::from_sat_per_mvb).map_err(|_| too_large)
+    }
+}
+
+/// A unit a [`FeeRate`] is expressed in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Unit {
+    /// Satoshis per virtual byte, "sat/vB".
+    SatPerVb,
+    /// Satoshis per 1,000 virtual bytes, "sat/kvB".
+    SatPerKvb,
+    /// Satoshis per 1,000 weight units, "sat/kwu".
+    SatPerKwu,
+    /// Bitcoin per 1,000 virtual bytes, "BTC/kvB", the unit used by the Bitcoin Core RPC.
+    BtcPerKvb,
+}
+
+impl Unit {
+    const ALL: [Self; 4] = [Self::SatPerVb, Self::SatPerKvb, Self::SatPerKwu, Self::BtcPerKvb];
+
+    /// Returns the symbol of the unit, e.g. "sat/vB".
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::SatPerVb => "sat/vB",
+            Self::SatPerKvb => "sat/kvB",
+            Self::SatPerKwu => "sat/kwu",
+            Self::BtcPerKvb => "BTC/kvB",
+        }
+    }
+
+    /// The value of one unit in sat/MvB.
+    const fn sat_per_mvb(self) -> u128 {
+        match self {
+            Self::SatPerVb => 1_000_000,
+            Self::SatPerKvb => 1_000,
+            Self::SatPerKwu => 4_000,
+            Self::BtcPerKvb => 100_000_000_000,
+        }
+    }
+
+    /// The number of decimals needed to express one sat/MvB in this unit exactly.
+    const fn decimals(self) -> u32 {
+        match self {
+            Self::SatPerVb => 6,
+            Self::SatPerKvb => 3,
+            Self::SatPerKwu => 5,
+            Self::BtcPerKvb => 11,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+/// A helper that displays a fee rate in a [`Unit`], see [`FeeRate::display_in`].
+#[derive(Debug, Clone)]
+pub struct Display {
+    fee_rate: FeeRate,
+    unit: Unit,
+}
+
+impl fmt::Display for Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The value is `value / 10^decimals` in `unit`.
+        let decimals = self.unit.decimals();
+        let scale = 10_u128.pow(decimals);
+        let mut value =
+            u128::from(self.fee_rate.to_sat_per_mvb()) * scale / self.unit.sat_per_mvb();
+        let mut shown = decimals;
+        match f.precision() {
+            Some(precision) if precision < decimals as usize => {
+                // Cast ok, less than `decimals`.
+                shown = precision as u32;
+                let divisor = 10_u128.pow(decimals - shown);
+                value = (value + divisor / 2) / divisor;
+            }
+            Some(_) => {}
+            None =>
+                while shown > 0 && value % 10 == 0 {
+                    value /= 10;
+                    shown -= 1;
+                },
+        }
+
+        let scale = 10_u128.pow(shown);
+        let whole = value / scale;
+        let trailing_zeros =
+            f.precision().map_or(0, |precision| precision.saturating_sub(decimals as usize));
+
+        // Pad like a number, aligned to the right by default and zero padded after any sign.
+        let fraction_width = if shown > 0 { 1 + shown as usize + trailing_zeros } else { 0 };
+        let whole_width = whole.checked_ilog10().map_or(1, |log| log as usize + 1);
+        let width = whole_width + fraction_width + 1 + self.unit.as_str().len();
+        let padding = f.width().unwrap_or(0).saturating_sub(width);
+        let (left, right) = match (f.sign_aware_zero_pad(), f.align()) {
+            (true, _) => (0, 0),
+            (false, Some(fmt::Alignment::Left)) => (0, padding),
+            (false, Some(fmt::Alignment::Center)) => (padding / 2, padding.div_ceil(2)),
+            (false, _) => (padding, 0),
+        };
+        let fill = f.fill();
+        for _ in 0..left {
+            write!(f, "{}", fill)?;
+        }
+        if f.sign_aware_zero_pad() {
+            for _ in 0..padding {
+                f.write_str("0")?;
+            }
+        }
+
+        write!(f, "{}", whole)?;
+        if shown > 0 {
+            write!(f, ".{:01$}", value % scale, shown as usize)?;
+        }
+        for _ in 0..trailing_zeros {
+            f.write_str("0")?;
+        }
+        write!(f, " {}", self.unit)?;
+        for _ in 0..right {
+            write!(f, "{}", fill)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error parsing a [`FeeRate`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFeeRateError(ParseFeeRateErrorInner);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseFeeRateErrorInner {
+    MissingUnit,
+    UnknownUnit,
+    InvalidNumber,
+    TooPrecise,
+    TooLarge,
+}
+
+impl From<Infallible> for ParseFeeRateError {
+    fn from(never: Infallible) -> Self { match never {} }
+}
+
+impl fmt::Display for ParseFeeRateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            ParseFeeRateErrorInner::MissingUnit => f.write_str("the fee rate has no unit"),
+            ParseFeeRateErrorInner::UnknownUnit => f.write_str(
+                "unknown fee rate unit, expected one of sat/vB, sat/kvB, sat/kwu or BTC/kvB",
+            ),
+            ParseFeeRateErrorInner::InvalidNumber => f.write_str("invalid fee rate number"),
+            ParseFeeRateErrorInner::TooPrecise =>
+                f.write_str("the fee rate has more precision than one satoshi per million vbytes"),
+            ParseFeeRateErrorInner::TooLarge => f.write_str("the fee rate is too large"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseFeeRateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+}
+
 #[cfg(feature = "arbitrary")]
 impl<'a> Arbitrary<'a> for FeeRate {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -446,4 +677,88 @@ fn mvb() {
         let got = fee_rate.to_sat_per_mvb();
         assert_eq!(got, 1_234_567);
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn display() {
+        use alloc::format;
+        use alloc::string::ToString;
+
+        let fee_rate = FeeRate::from_sat_per_mvb(12_500_000);
+        assert_eq!(fee_rate.to_string(), "12.5 sat/vB");
+        assert_eq!(fee_rate.display_in(Unit::SatPerKvb).to_string(), "12500 sat/kvB");
+        assert_eq!(fee_rate.display_in(Unit::SatPerKwu).to_string(), "3125 sat/kwu");
+        assert_eq!(fee_rate.display_in(Unit::BtcPerKvb).to_string(), "0.000125 BTC/kvB");
+        assert_eq!(FeeRate::ZERO.to_string(), "0 sat/vB");
+        assert_eq!(FeeRate::from_sat_per_mvb(1).to_string(), "0.000001 sat/vB");
+        assert_eq!(
+            FeeRate::from_sat_per_mvb(1).display_in(Unit::SatPerKwu).to_string(),
+            "0.00025 sat/kwu"
+        );
+        assert_eq!(FeeRate::MAX.to_string(), "18446744073709.551615 sat/vB");
+
+        // Rounding halves up.
+        let fee_rate = FeeRate::from_sat_per_mvb(2_345_500);
+        assert_eq!(format!("{:.3}", fee_rate), "2.346 sat/vB");
+        assert_eq!(format!("{:.2}", fee_rate), "2.35 sat/vB");
+        assert_eq!(format!("{:.0}", fee_rate), "2 sat/vB");
+        assert_eq!(format!("{:.0}", FeeRate::from_sat_per_mvb(2_500_000)), "3 sat/vB");
+        assert_eq!(format!("{:.8}", fee_rate), "2.34550000 sat/vB");
+        assert_eq!(format!("{:.2}", FeeRate::from_sat_per_vb(1)), "1.00 sat/vB");
+        assert_eq!(format!("{:.1}", FeeRate::from_sat_per_mvb(999_999)), "1.0 sat/vB");
+
+        // Padding.
+        let fee_rate = FeeRate::from_sat_per_mvb(12_500_000);
+        assert_eq!(format!("{:14}", fee_rate), "   12.5 sat/vB");
+        assert_eq!(format!("{:<14}", fee_rate), "12.5 sat/vB   ");
+        assert_eq!(format!("{:*^14}", fee_rate), "*12.5 sat/vB**");
+        assert_eq!(format!("{:014}", fee_rate), "00012.5 sat/vB");
+        assert_eq!(format!("{:>16.3}", fee_rate), "   12.500 sat/vB");
+        assert_eq!(format!("{:>15.0}", fee_rate), "      13 sat/vB");
+        assert_eq!(format!("{:4}", fee_rate), "12.5 sat/vB");
+    }
+
+    #[test]
+    fn from_str() {
+        let fee_rate = FeeRate::from_sat_per_mvb(12_500_000);
+        assert_eq!("12.5 sat/vB".parse(), Ok(fee_rate));
+        assert_eq!("12.5sat/vb".parse(), Ok(fee_rate));
+        assert_eq!("12500 SAT/KVB".parse(), Ok(fee_rate));
+        assert_eq!("3125 sat/kwu".parse(), Ok(fee_rate));
+        assert_eq!("0.000125 BTC/kvB".parse(), Ok(fee_rate));
+        assert_eq!("0.00012500 btc/kvb".parse(), Ok(fee_rate));
+        assert_eq!(".5 sat/vB".parse(), Ok(FeeRate::from_sat_per_mvb(500_000)));
+        assert_eq!("3000 sat/kwu".parse(), Ok(FeeRate::from_sat_per_kwu(3000)));
+        assert_eq!("18446744073709.551615 sat/vB".parse(), Ok(FeeRate::MAX));
+
+        let err = |inner| Err::<FeeRate, _>(ParseFeeRateError(inner));
+        assert_eq!("12.5".parse(), err(ParseFeeRateErrorInner::MissingUnit));
+        assert_eq!("12.5 sat/byte".parse(), err(ParseFeeRateErrorInner::UnknownUnit));
+        assert_eq!("sat/vB".parse(), err(ParseFeeRateErrorInner::InvalidNumber));
+        assert_eq!(". sat/vB".parse(), err(ParseFeeRateErrorInner::InvalidNumber));
+        assert_eq!(".sat/vB".parse(), err(ParseFeeRateErrorInner::InvalidNumber));
+        assert_eq!("-1 sat/vB".parse(), err(ParseFeeRateErrorInner::InvalidNumber));
+        assert_eq!("1.2.3 sat/vB".parse(), err(ParseFeeRateErrorInner::InvalidNumber));
+        assert_eq!("0.0000001 sat/vB".parse(), err(ParseFeeRateErrorInner::TooPrecise));
+        assert_eq!("0.0001 sat/kwu".parse(), err(ParseFeeRateErrorInner::TooPrecise));
+        assert_eq!("18446744073709.551616 sat/vB".parse(), err(ParseFeeRateErrorInner::TooLarge));
+        assert_eq!(
+            "1000000000000000000000000000000000000000 sat/vB".parse(),
+            err(ParseFeeRateErrorInner::TooLarge)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn display_from_str_round_trip() {
+        use alloc::string::ToString;
+
+        for sat_mvb in [0, 1, 999, 4_000, 12_345_678, 1_000_000_000, u64::MAX] {
+            let fee_rate = FeeRate::from_sat_per_mvb(sat_mvb);
+            for unit in Unit::ALL {
+                let s = fee_rate.display_in(unit).to_string();
+                assert_eq!(s.parse(), Ok(fee_rate), "{}", s);
+            }
+        }
+    }
 }
@@ -7,6 +7,8 @@
 //! [`MedianTimePast`] types.
 
 pub mod error;
+#[cfg(kani)]
+mod verification;
 
 use core::fmt;
 
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Verification tests for the `absolute` module.
+
+use super::{Height, LockTime, MedianTimePast};
+
+fn any_height() -> Height {
+    let n = kani::any::<u32>();
+    kani::assume(Height::from_u32(n).is_ok());
+    Height::from_u32(n).unwrap()
+}
+
+fn any_mtp() -> MedianTimePast {
+    let n = kani::any::<u32>();
+    kani::assume(MedianTimePast::from_u32(n).is_ok());
+    MedianTimePast::from_u32(n).unwrap()
+}
+
+#[kani::proof]
+fn satisfaction_monotone_in_height() {
+    let lock = LockTime::from_consensus(kani::any());
+    let (h1, h2, mtp) = (any_height(), any_height(), any_mtp());
+    kani::assume(h1 <= h2);
+
+    // A lock satisfied at some height stays satisfied at all later heights.
+    if lock.is_satisfied_by(h1, mtp) {
+        assert!(lock.is_satisfied_by(h2, mtp));
+    }
+}
+
+#[kani::proof]
+fn satisfaction_monotone_in_time() {
+    let lock = LockTime::from_consensus(kani::any());
+    let (height, t1, t2) = (any_height(), any_mtp(), any_mtp());
+    kani::assume(t1 <= t2);
+
+    // A lock satisfied at some time stays satisfied at all later times.
+    if lock.is_satisfied_by(height, t1) {
+        assert!(lock.is_satisfied_by(height, t2));
+    }
+}
+
+#[kani::proof]
+fn satisfaction_never_crosses_units() {
+    let lock = LockTime::from_consensus(kani::any());
+    let (h1, h2, t1, t2) = (any_height(), any_height(), any_mtp(), any_mtp());
+
+    // A height lock only depends on the height and a time lock only on the time.
+    match lock {
+        LockTime::Blocks(_) => {
+            assert_eq!(lock.is_satisfied_by(h1, t1), lock.is_satisfied_by(h1, t2));
+            assert!(lock.is_satisfied_by_time(t1).is_err());
+        }
+        LockTime::Seconds(_) => {
+            assert_eq!(lock.is_satisfied_by(h1, t1), lock.is_satisfied_by(h2, t1));
+            assert!(lock.is_satisfied_by_height(h1).is_err());
+        }
+    }
+}
+
+#[kani::proof]
+fn implication_consistent_with_satisfaction() {
+    let lock = LockTime::from_consensus(kani::any());
+    let other = LockTime::from_consensus(kani::any());
+    let (height, mtp) = (any_height(), any_mtp());
+
+    if !lock.is_same_unit(other) {
+        assert!(!lock.is_implied_by(other));
+    }
+    // Satisfying the larger lock satisfies the smaller one.
+    if lock.is_implied_by(other) && other.is_satisfied_by(height, mtp) {
+        assert!(lock.is_satisfied_by(height, mtp));
+    }
+}
@@ -7,6 +7,8 @@
 //! [`NumberOf512Seconds`] types.
 
 pub mod error;
+#[cfg(kani)]
+mod verification;
 
 use core::{convert, fmt};
 
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Verification tests for the `relative` module.
+
+use super::LockTime;
+use crate::{BlockHeight, BlockMtp, Sequence};
+
+fn any_lock() -> LockTime {
+    let n = kani::any::<u32>();
+    kani::assume(LockTime::from_consensus(n).is_ok());
+    LockTime::from_consensus(n).unwrap()
+}
+
+#[kani::proof]
+fn satisfaction_monotone_in_height() {
+    let lock = any_lock();
+    let utxo_mined_at = BlockHeight::from_u32(kani::any());
+    let t1 = BlockHeight::from_u32(kani::any());
+    let t2 = BlockHeight::from_u32(kani::any());
+    kani::assume(utxo_mined_at <= t1 && t1 <= t2);
+
+    // A lock satisfied at some chain tip stays satisfied at all later chain tips.
+    if let Ok(true) = lock.is_satisfied_by_height(t1, utxo_mined_at) {
+        assert_eq!(lock.is_satisfied_by_height(t2, utxo_mined_at), Ok(true));
+    }
+}
+
+#[kani::proof]
+fn satisfaction_monotone_in_time() {
+    let lock = any_lock();
+    let utxo_mined_at = BlockMtp::from_u32(kani::any());
+    let t1 = BlockMtp::from_u32(kani::any());
+    let t2 = BlockMtp::from_u32(kani::any());
+    kani::assume(utxo_mined_at <= t1 && t1 <= t2);
+
+    // A lock satisfied at some chain tip stays satisfied at all later chain tips.
+    if let Ok(true) = lock.is_satisfied_by_time(t1, utxo_mined_at) {
+        assert_eq!(lock.is_satisfied_by_time(t2, utxo_mined_at), Ok(true));
+    }
+}
+
+#[kani::proof]
+fn satisfaction_never_crosses_units() {
+    let lock = any_lock();
+    let height = |n: u32| BlockHeight::from_u32(n);
+    let mtp = |n: u32| BlockMtp::from_u32(n);
+    let (tip_height, utxo_height, other_height) =
+        (height(kani::any()), height(kani::any()), height(kani::any()));
+    let (tip_mtp, utxo_mtp, other_mtp) = (mtp(kani::any()), mtp(kani::any()), mtp(kani::any()));
+
+    let satisfied = lock.is_satisfied_by(tip_height, tip_mtp, utxo_height, utxo_mtp);
+    // A height lock only depends on heights and a time lock only on times.
+    match lock {
+        LockTime::Blocks(_) => {
+            let other = lock.is_satisfied_by(tip_height, other_mtp, utxo_height, utxo_mtp);
+            assert_eq!(satisfied, other);
+            assert!(lock.is_satisfied_by_time(tip_mtp, utxo_mtp).is_err());
+        }
+        LockTime::Time(_) => {
+            let other = lock.is_satisfied_by(other_height, tip_mtp, utxo_height, utxo_mtp);
+            assert_eq!(satisfied, other);
+            assert!(lock.is_satisfied_by_height(tip_height, utxo_height).is_err());
+        }
+    }
+}
+
+#[kani::proof]
+fn implication_never_crosses_units() {
+    let lock = any_lock();
+    let sequence = Sequence::from_consensus(kani::any());
+
+    if let Ok(other) = LockTime::from_sequence(sequence) {
+        if !lock.is_same_unit(other) {
+            assert!(!lock.is_implied_by(other));
+            assert!(!lock.is_implied_by_sequence(sequence));
+        }
+    } else {
+        // Sequence numbers with the disable flag set never satisfy a lock.
+        assert!(!lock.is_implied_by_sequence(sequence));
+    }
+}